@@ -5,7 +5,7 @@ use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use futures::{future, io::BufReader, AsyncBufReadExt, FutureExt, Stream, StreamExt, TryStreamExt};
 use http::{Method, StatusCode};
 use recycle::VecExt;
-use rusoto_s3::{GetObjectRequest, Object, S3Client, S3};
+use rusoto_s3::{GetObjectRequest, Object, S3};
 use serde::{Deserialize, Serialize};
 use serde_closure::FnMutNamed;
 use std::{
@@ -17,7 +17,7 @@ use amadeus_core::{
 };
 use amadeus_types::{Data, DateTime, IpAddr, Url};
 
-use super::{list, retry, AwsCredentials, AwsError, AwsRegion, Ref, RUSOTO_DISPATCHER};
+use super::{list, retry, s3_client, AwsCredentials, AwsError, AwsRegion};
 
 #[derive(Clone, Debug)]
 pub struct Cloudfront {
@@ -34,11 +34,7 @@ impl Cloudfront {
 		region: AwsRegion, bucket: &str, prefix: &str, credentials: AwsCredentials,
 	) -> Result<Self, AwsError> {
 		let (bucket, prefix) = (bucket.to_owned(), prefix.to_owned());
-		let client = S3Client::new_with(
-			Ref(&*RUSOTO_DISPATCHER),
-			credentials.clone(),
-			region.clone(),
-		);
+		let client = s3_client(region.clone(), credentials.clone());
 
 		let objects = list(&client, &bucket, &prefix)
 			.await?
@@ -64,11 +60,7 @@ FnMutNamed! {
 			(self.credentials.clone(), self.region.clone(), self.bucket.clone());
 		#[allow(clippy::let_and_return)]
 		let ret = async move {
-			let client = S3Client::new_with(
-				Ref(once_cell::sync::Lazy::force(&RUSOTO_DISPATCHER)),
-				credentials,
-				region,
-			);
+			let client = s3_client(region, credentials);
 			let rows = retry(|| {
 				client.get_object(GetObjectRequest {
 					bucket: bucket.clone(),
@@ -0,0 +1,867 @@
+use futures::{future::LocalBoxFuture, ready, FutureExt, Stream};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusoto_s3::{
+	AbortMultipartUploadRequest, CompleteMultipartUploadRequest, CompletedMultipartUpload, CompletedPart, CopyObjectRequest, CreateMultipartUploadRequest, PutObjectRequest, S3Client, UploadPartCopyRequest, UploadPartRequest, S3
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	convert::TryInto, mem, ops::Range, pin::Pin, task::{Context, Poll}
+};
+
+use amadeus_core::{
+	par_sink::{FromParallelStream, ParallelSink, Reducer, ReducerSend}, par_stream::Identity, pipe::Sink
+};
+
+use super::{
+	retry, s3_client, AwsCredentials, AwsError, AwsRegion, S3Encryption, S3Partition, SseHeaders
+};
+
+// https://docs.aws.amazon.com/AmazonS3/latest/API/API_CopyObject.html#API_CopyObject_RequestSyntax
+const COPY_SOURCE: &AsciiSet = &NON_ALPHANUMERIC
+	.remove(b'/')
+	.remove(b'-')
+	.remove(b'_')
+	.remove(b'.')
+	.remove(b'~');
+
+/// The size of the parts that bodies larger than it are uploaded in. S3 requires
+/// all but the last part of a multipart upload to be at least 5 MiB.
+const PART_SIZE: usize = 16 * 1024 * 1024;
+
+/// The largest object that `CopyObject` can copy.
+const MAX_COPY_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// The smallest size of the parts that objects larger than [`MAX_COPY_SIZE`]
+/// are copied in.
+const MIN_COPY_PART_SIZE: u64 = 512 * 1024 * 1024;
+
+/// The most parts a multipart upload can have.
+const MAX_PARTS: u64 = 10_000;
+
+/// The size of the parts that an object of `len` bytes is copied in: at least
+/// [`MIN_COPY_PART_SIZE`], and large enough, in whole MiB, that the object fits
+/// in [`MAX_PARTS`] parts. This covers objects up to S3's limit of 5 TiB.
+fn copy_part_size(len: u64) -> u64 {
+	const MIB: u64 = 1024 * 1024;
+	let part_size = (len + MAX_PARTS - 1) / MAX_PARTS;
+	let part_size = (part_size + MIB - 1) / MIB * MIB;
+	part_size.max(MIN_COPY_PART_SIZE)
+}
+
+/// An object to be written by [`S3Copy`].
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum S3Object {
+	/// An unmodified existing object, which is copied server-side without its
+	/// contents passing through the client.
+	Copy(S3Partition),
+	/// New contents for the object at `key`. Bodies larger than 16 MiB are
+	/// uploaded in parts, which are aborted if the write is dropped before it
+	/// completes.
+	Put { key: String, body: Vec<u8> },
+}
+impl From<S3Partition> for S3Object {
+	fn from(partition: S3Partition) -> Self {
+		Self::Copy(partition)
+	}
+}
+
+/// A sink that writes [`S3Object`]s under `prefix` in `bucket`.
+///
+/// Objects that are [`S3Object::Copy`] are written with `CopyObject` to `prefix`
+/// followed by their existing key, avoiding downloading and re-uploading them,
+/// or, for those larger than the 5 GiB it supports, with a multipart upload of
+/// `UploadPartCopy` parts. Their source is read with the SSE-C key of the
+/// [`S3Partition`], if any.
+#[derive(Clone, Debug)]
+pub struct S3Copy {
+	region: AwsRegion,
+	bucket: String,
+	prefix: String,
+	credentials: AwsCredentials,
+	encryption: Option<S3Encryption>,
+}
+impl S3Copy {
+	pub fn new(region: AwsRegion, bucket: &str, prefix: &str) -> Self {
+		Self::new_with(region, bucket, prefix, AwsCredentials::Environment)
+	}
+	pub fn new_with(
+		region: AwsRegion, bucket: &str, prefix: &str, credentials: AwsCredentials,
+	) -> Self {
+		let (bucket, prefix) = (bucket.to_owned(), prefix.to_owned());
+		Self {
+			region,
+			bucket,
+			prefix,
+			credentials,
+			encryption: None,
+		}
+	}
+	/// Encrypt the objects written with `encryption`.
+	#[must_use]
+	pub fn with_encryption(self, encryption: S3Encryption) -> Self {
+		Self {
+			encryption: Some(encryption),
+			..self
+		}
+	}
+}
+
+impl<Item> ParallelSink<Item> for S3Copy
+where
+	Item: Into<S3Object>,
+{
+	type Done = Result<(), AwsError>;
+	type Pipe = Identity;
+	type ReduceA = S3CopyReducer;
+	type ReduceC = <Result<(), AwsError> as FromParallelStream<Result<(), AwsError>>>::ReduceC;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		let (_, reduce_c) =
+			<Result<(), AwsError> as FromParallelStream<Result<(), AwsError>>>::reducers();
+		(Identity, S3CopyReducer(self), reduce_c)
+	}
+}
+
+#[derive(Clone)]
+pub struct S3CopyReducer(S3Copy);
+impl<Item> Reducer<Item> for S3CopyReducer
+where
+	Item: Into<S3Object>,
+{
+	type Done = Result<(), AwsError>;
+	type Async = S3CopyReducerAsync;
+
+	fn into_async(self) -> Self::Async {
+		let S3Copy {
+			region,
+			bucket,
+			prefix,
+			credentials,
+			encryption,
+		} = self.0;
+		let client = s3_client(region, credentials);
+		S3CopyReducerAsync::new(client, bucket, prefix, SseHeaders::new(encryption.as_ref()))
+	}
+}
+impl<Item> ReducerSend<Item> for S3CopyReducer
+where
+	Item: Into<S3Object>,
+{
+	type Done = Result<(), AwsError>;
+}
+
+pub struct S3CopyReducerAsync {
+	client: S3Client,
+	bucket: String,
+	prefix: String,
+	sse: SseHeaders,
+	pending: Option<LocalBoxFuture<'static, Result<(), AwsError>>>,
+	result: Option<Result<(), AwsError>>,
+}
+impl S3CopyReducerAsync {
+	fn new(client: S3Client, bucket: String, prefix: String, sse: SseHeaders) -> Self {
+		Self {
+			client,
+			bucket,
+			prefix,
+			sse,
+			pending: None,
+			result: Some(Ok(())),
+		}
+	}
+}
+impl<Item> Sink<Item> for S3CopyReducerAsync
+where
+	Item: Into<S3Object>,
+{
+	type Done = Result<(), AwsError>;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Item>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.get_mut();
+		loop {
+			if let Some(pending) = &mut self_.pending {
+				let res = ready!(pending.as_mut().poll(cx));
+				self_.pending = None;
+				if let (Err(err), Some(Ok(()))) = (res, &self_.result) {
+					self_.result = Some(Err(err));
+				}
+			}
+			match ready!(stream.as_mut().poll_next(cx)) {
+				// Once an object has failed, drain the stream without writing the rest
+				Some(item) if matches!(self_.result, Some(Ok(()))) => {
+					let write = write(
+						self_.client.clone(),
+						self_.bucket.clone(),
+						self_.prefix.clone(),
+						self_.sse.clone(),
+						item.into(),
+					);
+					self_.pending = Some(write.boxed_local());
+				}
+				Some(_) => (),
+				None => break Poll::Ready(self_.result.take().unwrap()),
+			}
+		}
+	}
+}
+
+async fn write(
+	client: S3Client, bucket: String, prefix: String, sse: SseHeaders, object: S3Object,
+) -> Result<(), AwsError> {
+	match object {
+		S3Object::Copy(partition) => {
+			let key = format!("{}{}", prefix, partition.key());
+			let copy_source = format!(
+				"{}/{}{}",
+				partition.bucket(),
+				utf8_percent_encode(partition.key(), COPY_SOURCE),
+				partition
+					.version_id()
+					.map_or_else(String::new, |version_id| format!(
+						"?versionId={}",
+						version_id
+					))
+			);
+			let source_sse = SseHeaders::new(partition.encryption());
+			let len = partition.len();
+			if len > MAX_COPY_SIZE {
+				let mut upload = MultipartUpload::create(client, bucket, key, sse).await?;
+				let part_size = copy_part_size(len);
+				let mut start = 0;
+				while start < len {
+					let end = len.min(start + part_size);
+					upload
+						.upload_part_copy(&copy_source, start..end, &source_sse)
+						.await?;
+					start = end;
+				}
+				upload.complete().await?;
+				return Ok(());
+			}
+			let _ = retry(|| {
+				client.copy_object(CopyObjectRequest {
+					bucket: bucket.clone(),
+					key: key.clone(),
+					copy_source: copy_source.clone(),
+					copy_source_sse_customer_algorithm: source_sse.sse_customer_algorithm.clone(),
+					copy_source_sse_customer_key: source_sse.sse_customer_key.clone(),
+					copy_source_sse_customer_key_md5: source_sse.sse_customer_key_md5.clone(),
+					server_side_encryption: sse.server_side_encryption.clone(),
+					ssekms_key_id: sse.ssekms_key_id.clone(),
+					sse_customer_algorithm: sse.sse_customer_algorithm.clone(),
+					sse_customer_key: sse.sse_customer_key.clone(),
+					sse_customer_key_md5: sse.sse_customer_key_md5.clone(),
+					..CopyObjectRequest::default()
+				})
+			})
+			.await?;
+		}
+		S3Object::Put { key, body } if body.len() > PART_SIZE => {
+			let key = format!("{}{}", prefix, key);
+			let mut upload = MultipartUpload::create(client, bucket, key, sse).await?;
+			for part in body.chunks(PART_SIZE) {
+				upload.upload_part(part).await?;
+			}
+			upload.complete().await?;
+		}
+		S3Object::Put { key, body } => {
+			let key = format!("{}{}", prefix, key);
+			let _ = retry(|| {
+				client.put_object(PutObjectRequest {
+					bucket: bucket.clone(),
+					key: key.clone(),
+					body: Some(body.clone().into()),
+					server_side_encryption: sse.server_side_encryption.clone(),
+					ssekms_key_id: sse.ssekms_key_id.clone(),
+					sse_customer_algorithm: sse.sse_customer_algorithm.clone(),
+					sse_customer_key: sse.sse_customer_key.clone(),
+					sse_customer_key_md5: sse.sse_customer_key_md5.clone(),
+					..PutObjectRequest::default()
+				})
+			})
+			.await?;
+		}
+	}
+	Ok(())
+}
+
+/// An in-progress multipart upload, which is aborted if dropped before
+/// [`complete`](MultipartUpload::complete) so its parts aren't left accruing
+/// storage charges. The abort is spawned on the Tokio runtime the upload was
+/// created on, so is skipped if it was created outside of one.
+struct MultipartUpload {
+	client: S3Client,
+	bucket: String,
+	key: String,
+	upload_id: String,
+	sse: SseHeaders,
+	parts: Vec<CompletedPart>,
+	completed: bool,
+	runtime: Option<tokio::runtime::Handle>,
+}
+impl MultipartUpload {
+	async fn create(
+		client: S3Client, bucket: String, key: String, sse: SseHeaders,
+	) -> Result<Self, AwsError> {
+		let upload_id = retry(|| {
+			client.create_multipart_upload(CreateMultipartUploadRequest {
+				bucket: bucket.clone(),
+				key: key.clone(),
+				server_side_encryption: sse.server_side_encryption.clone(),
+				ssekms_key_id: sse.ssekms_key_id.clone(),
+				sse_customer_algorithm: sse.sse_customer_algorithm.clone(),
+				sse_customer_key: sse.sse_customer_key.clone(),
+				sse_customer_key_md5: sse.sse_customer_key_md5.clone(),
+				..CreateMultipartUploadRequest::default()
+			})
+		})
+		.await?
+		.upload_id
+		.ok_or_else(|| {
+			AwsError::ParseError("CreateMultipartUpload response has no UploadId".to_owned())
+		})?;
+		Ok(Self {
+			client,
+			bucket,
+			key,
+			upload_id,
+			sse,
+			parts: Vec::new(),
+			completed: false,
+			runtime: tokio::runtime::Handle::try_current().ok(),
+		})
+	}
+	fn next_part_number(&self) -> i64 {
+		(self.parts.len() + 1).try_into().unwrap()
+	}
+	async fn upload_part(&mut self, body: &[u8]) -> Result<(), AwsError> {
+		let part_number = self.next_part_number();
+		let (client, bucket, key, upload_id, sse) = (
+			&self.client,
+			&self.bucket,
+			&self.key,
+			&self.upload_id,
+			&self.sse,
+		);
+		let e_tag = retry(|| {
+			client.upload_part(UploadPartRequest {
+				bucket: bucket.clone(),
+				key: key.clone(),
+				upload_id: upload_id.clone(),
+				part_number,
+				body: Some(body.to_owned().into()),
+				sse_customer_algorithm: sse.sse_customer_algorithm.clone(),
+				sse_customer_key: sse.sse_customer_key.clone(),
+				sse_customer_key_md5: sse.sse_customer_key_md5.clone(),
+				..UploadPartRequest::default()
+			})
+		})
+		.await?
+		.e_tag;
+		self.parts.push(CompletedPart {
+			e_tag,
+			part_number: Some(part_number),
+		});
+		Ok(())
+	}
+	/// Upload the bytes `range` of the object `copy_source`, read with the SSE-C
+	/// headers `source_sse`, as the next part.
+	async fn upload_part_copy(
+		&mut self, copy_source: &str, range: Range<u64>, source_sse: &SseHeaders,
+	) -> Result<(), AwsError> {
+		let part_number = self.next_part_number();
+		let (client, bucket, key, upload_id, sse) = (
+			&self.client,
+			&self.bucket,
+			&self.key,
+			&self.upload_id,
+			&self.sse,
+		);
+		let copy_source_range = format!("bytes={}-{}", range.start, range.end - 1);
+		let e_tag = retry(|| {
+			client.upload_part_copy(UploadPartCopyRequest {
+				bucket: bucket.clone(),
+				key: key.clone(),
+				upload_id: upload_id.clone(),
+				part_number,
+				copy_source: copy_source.to_owned(),
+				copy_source_range: Some(copy_source_range.clone()),
+				copy_source_sse_customer_algorithm: source_sse.sse_customer_algorithm.clone(),
+				copy_source_sse_customer_key: source_sse.sse_customer_key.clone(),
+				copy_source_sse_customer_key_md5: source_sse.sse_customer_key_md5.clone(),
+				sse_customer_algorithm: sse.sse_customer_algorithm.clone(),
+				sse_customer_key: sse.sse_customer_key.clone(),
+				sse_customer_key_md5: sse.sse_customer_key_md5.clone(),
+				..UploadPartCopyRequest::default()
+			})
+		})
+		.await?
+		.copy_part_result
+		.and_then(|result| result.e_tag);
+		self.parts.push(CompletedPart {
+			e_tag,
+			part_number: Some(part_number),
+		});
+		Ok(())
+	}
+	async fn complete(mut self) -> Result<(), AwsError> {
+		let parts = mem::take(&mut self.parts);
+		let (client, bucket, key, upload_id) =
+			(&self.client, &self.bucket, &self.key, &self.upload_id);
+		let _ = retry(|| {
+			client.complete_multipart_upload(CompleteMultipartUploadRequest {
+				bucket: bucket.clone(),
+				key: key.clone(),
+				upload_id: upload_id.clone(),
+				multipart_upload: Some(CompletedMultipartUpload {
+					parts: Some(parts.clone()),
+				}),
+				..CompleteMultipartUploadRequest::default()
+			})
+		})
+		.await?;
+		self.completed = true;
+		Ok(())
+	}
+}
+impl Drop for MultipartUpload {
+	fn drop(&mut self) {
+		if self.completed {
+			return;
+		}
+		let client = self.client.clone();
+		let request = AbortMultipartUploadRequest {
+			bucket: mem::take(&mut self.bucket),
+			key: mem::take(&mut self.key),
+			upload_id: mem::take(&mut self.upload_id),
+			..AbortMultipartUploadRequest::default()
+		};
+		// Drop can't await, so abort in the background. Blocking on it instead
+		// would panic, as the client needs a runtime to make the request on.
+		// Failure is ignored, as there's no one to report it to; S3 lifecycle
+		// rules can clean up any stragglers.
+		if let Some(runtime) = &self.runtime {
+			drop(runtime.spawn(async move {
+				let _ = client.abort_multipart_upload(request).await;
+			}));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::{executor::block_on, stream};
+	use rusoto_core::signature::SignedRequest;
+	use rusoto_mock::{
+		MockCredentialsProvider, MockRequestDispatcher, MultipleMockRequestDispatcher
+	};
+	use std::sync::{Arc, Mutex};
+
+	use super::*;
+
+	#[test]
+	fn copy_unmodified() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let requests_ = requests.clone();
+		let dispatcher = MockRequestDispatcher::default()
+			.with_body("<CopyObjectResult><ETag>\"etag\"</ETag></CopyObjectResult>")
+			.with_request_checker(move |request| {
+				let copy_source = request
+					.headers()
+					.get("x-amz-copy-source")
+					.map(|value| String::from_utf8(value[0].clone()).unwrap());
+				requests_.lock().unwrap().push((
+					request.method().to_owned(),
+					request.path().to_owned(),
+					copy_source,
+				));
+			});
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let partition = S3Partition::new(
+			AwsRegion::UsEast1,
+			"source".to_owned(),
+			"logs/a b.gz".to_owned(),
+			1024,
+			AwsCredentials::Anonymous,
+		);
+		let mut sink = S3CopyReducerAsync::new(
+			client,
+			"destination".to_owned(),
+			"backup/".to_owned(),
+			SseHeaders::default(),
+		);
+		let mut items = stream::iter(vec![partition]);
+		assert!(block_on(Sink::<S3Partition>::send_all(&mut sink, &mut items)).is_none());
+		block_on(Sink::<S3Partition>::done(&mut sink)).unwrap();
+
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[(
+				"PUT".to_owned(),
+				"/destination/backup/logs/a b.gz".to_owned(),
+				Some("source/logs/a%20b.gz".to_owned())
+			)]
+		);
+	}
+
+	#[test]
+	fn abort_dropped_multipart() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let requests = requests.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				let upload_id = request.params.get("uploadId").cloned().flatten();
+				requests
+					.lock()
+					.unwrap()
+					.push((request.method().to_owned(), upload_id));
+			})
+		};
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			record(MockRequestDispatcher::default().with_body(
+				"<InitiateMultipartUploadResult><UploadId>upload</UploadId></InitiateMultipartUploadResult>",
+			)),
+			record(MockRequestDispatcher::default().with_header("ETag", "\"etag\"")),
+			record(MockRequestDispatcher::with_status(204)),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let mut runtime = tokio::runtime::Builder::new()
+			.basic_scheduler()
+			.build()
+			.unwrap();
+		let mut upload = runtime
+			.block_on(MultipartUpload::create(
+				client,
+				"destination".to_owned(),
+				"large".to_owned(),
+				SseHeaders::default(),
+			))
+			.unwrap();
+		runtime.block_on(upload.upload_part(&[0; 1024])).unwrap();
+		drop(upload);
+		// Let the abort spawned on the runtime run
+		runtime.block_on(async {
+			while requests.lock().unwrap().len() < 3 {
+				let () = tokio::task::yield_now().await;
+			}
+		});
+
+		let upload = Some("upload".to_owned());
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[
+				("POST".to_owned(), None),
+				("PUT".to_owned(), upload.clone()),
+				("DELETE".to_owned(), upload)
+			]
+		);
+	}
+
+	#[test]
+	fn drop_multipart_without_runtime() {
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			MockRequestDispatcher::default().with_body(
+				"<InitiateMultipartUploadResult><UploadId>upload</UploadId></InitiateMultipartUploadResult>",
+			),
+			MockRequestDispatcher::default().with_header("ETag", "\"etag\""),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		// Without a runtime to abort on, dropping skips the abort rather than
+		// panicking
+		let mut upload = block_on(MultipartUpload::create(
+			client,
+			"destination".to_owned(),
+			"large".to_owned(),
+			SseHeaders::default(),
+		))
+		.unwrap();
+		block_on(upload.upload_part(&[0; 1024])).unwrap();
+		drop(upload);
+	}
+
+	#[test]
+	fn multipart_without_upload_id() {
+		let dispatcher = MockRequestDispatcher::default()
+			.with_body("<InitiateMultipartUploadResult></InitiateMultipartUploadResult>");
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let res = block_on(MultipartUpload::create(
+			client,
+			"destination".to_owned(),
+			"large".to_owned(),
+			SseHeaders::default(),
+		));
+		assert!(matches!(res, Err(AwsError::ParseError(_))));
+	}
+
+	/// The encryption headers of `request`, sorted by name.
+	fn sse_headers(request: &SignedRequest) -> Vec<(String, String)> {
+		request
+			.headers()
+			.iter()
+			.filter(|(name, _)| name.contains("server-side-encryption"))
+			.map(|(name, value)| (name.clone(), String::from_utf8(value[0].clone()).unwrap()))
+			.collect()
+	}
+
+	#[test]
+	fn write_encrypted() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let requests = requests.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				requests
+					.lock()
+					.unwrap()
+					.push((request.method().to_owned(), sse_headers(request)));
+			})
+		};
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			record(MockRequestDispatcher::default().with_header("ETag", "\"etag\"")),
+			record(
+				MockRequestDispatcher::default()
+					.with_body("<CopyObjectResult><ETag>\"etag\"</ETag></CopyObjectResult>"),
+			),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let kms = S3Encryption::Kms {
+			key_id: Some("key".to_owned()),
+		};
+		let source = S3Partition::new(
+			AwsRegion::UsEast1,
+			"source".to_owned(),
+			"a".to_owned(),
+			1024,
+			AwsCredentials::Anonymous,
+		)
+		.with_encryption(S3Encryption::Customer { key: [1; 32] });
+		let mut sink = S3CopyReducerAsync::new(
+			client,
+			"destination".to_owned(),
+			"backup/".to_owned(),
+			SseHeaders::new(Some(&kms)),
+		);
+		let mut items = stream::iter(vec![
+			S3Object::Put {
+				key: "b".to_owned(),
+				body: vec![0; 1024],
+			},
+			S3Object::Copy(source),
+		]);
+		assert!(block_on(Sink::<S3Object>::send_all(&mut sink, &mut items)).is_none());
+		block_on(Sink::<S3Object>::done(&mut sink)).unwrap();
+
+		let kms = vec![
+			(
+				"x-amz-server-side-encryption".to_owned(),
+				"aws:kms".to_owned(),
+			),
+			(
+				"x-amz-server-side-encryption-aws-kms-key-id".to_owned(),
+				"key".to_owned(),
+			),
+		];
+		let copy_source = vec![
+			(
+				"x-amz-copy-source-server-side-encryption-customer-algorithm".to_owned(),
+				"AES256".to_owned(),
+			),
+			(
+				"x-amz-copy-source-server-side-encryption-customer-key".to_owned(),
+				"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=".to_owned(),
+			),
+			(
+				"x-amz-copy-source-server-side-encryption-customer-key-md5".to_owned(),
+				"4Funlf7OsLF0HL+vKU+fkg==".to_owned(),
+			),
+		];
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[
+				("PUT".to_owned(), kms.clone()),
+				(
+					"PUT".to_owned(),
+					copy_source.into_iter().chain(kms).collect()
+				)
+			]
+		);
+	}
+
+	#[test]
+	fn multipart_encrypted() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let requests = requests.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				requests
+					.lock()
+					.unwrap()
+					.push((request.method().to_owned(), sse_headers(request)));
+			})
+		};
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			record(MockRequestDispatcher::default().with_body(
+				"<InitiateMultipartUploadResult><UploadId>upload</UploadId></InitiateMultipartUploadResult>",
+			)),
+			record(MockRequestDispatcher::default().with_header("ETag", "\"etag\"")),
+			record(MockRequestDispatcher::default().with_body(
+				"<CompleteMultipartUploadResult><ETag>\"etag\"</ETag></CompleteMultipartUploadResult>",
+			)),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let customer = S3Encryption::Customer { key: [1; 32] };
+		let mut upload = block_on(MultipartUpload::create(
+			client,
+			"destination".to_owned(),
+			"large".to_owned(),
+			SseHeaders::new(Some(&customer)),
+		))
+		.unwrap();
+		block_on(upload.upload_part(&[0; 1024])).unwrap();
+		block_on(upload.complete()).unwrap();
+
+		// SSE-C keys are required to create the upload and for each part
+		let customer = vec![
+			(
+				"x-amz-server-side-encryption-customer-algorithm".to_owned(),
+				"AES256".to_owned(),
+			),
+			(
+				"x-amz-server-side-encryption-customer-key".to_owned(),
+				"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=".to_owned(),
+			),
+			(
+				"x-amz-server-side-encryption-customer-key-md5".to_owned(),
+				"4Funlf7OsLF0HL+vKU+fkg==".to_owned(),
+			),
+		];
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[
+				("POST".to_owned(), customer.clone()),
+				("PUT".to_owned(), customer),
+				("POST".to_owned(), vec![])
+			]
+		);
+	}
+
+	#[test]
+	fn copy_large_encrypted() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let requests = requests.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				let range = request
+					.headers()
+					.get("x-amz-copy-source-range")
+					.map(|value| String::from_utf8(value[0].clone()).unwrap());
+				requests.lock().unwrap().push((
+					request.method().to_owned(),
+					range,
+					sse_headers(request),
+				));
+			})
+		};
+		let len = MAX_COPY_SIZE + 1;
+		// The last part is just the last byte
+		let parts = MAX_COPY_SIZE / MIN_COPY_PART_SIZE + 1;
+		let dispatcher = MultipleMockRequestDispatcher::new(
+			std::iter::once(MockRequestDispatcher::default().with_body(
+				"<InitiateMultipartUploadResult><UploadId>upload</UploadId></InitiateMultipartUploadResult>",
+			))
+			.chain((0..parts).map(|_| {
+				MockRequestDispatcher::default()
+					.with_body("<CopyPartResult><ETag>\"etag\"</ETag></CopyPartResult>")
+			}))
+			.chain(std::iter::once(MockRequestDispatcher::default().with_body(
+				"<CompleteMultipartUploadResult><ETag>\"etag\"</ETag></CompleteMultipartUploadResult>",
+			)))
+			.map(record)
+			.collect::<Vec<_>>(),
+		);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let source = S3Partition::new(
+			AwsRegion::UsEast1,
+			"source".to_owned(),
+			"a".to_owned(),
+			len,
+			AwsCredentials::Anonymous,
+		)
+		.with_encryption(S3Encryption::Customer { key: [1; 32] });
+		let mut sink = S3CopyReducerAsync::new(
+			client,
+			"destination".to_owned(),
+			"backup/".to_owned(),
+			SseHeaders::default(),
+		);
+		let mut items = stream::iter(vec![S3Object::Copy(source)]);
+		assert!(block_on(Sink::<S3Object>::send_all(&mut sink, &mut items)).is_none());
+		block_on(Sink::<S3Object>::done(&mut sink)).unwrap();
+
+		// Each part is copied with the source's SSE-C key
+		let copy_source = vec![
+			(
+				"x-amz-copy-source-server-side-encryption-customer-algorithm".to_owned(),
+				"AES256".to_owned(),
+			),
+			(
+				"x-amz-copy-source-server-side-encryption-customer-key".to_owned(),
+				"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=".to_owned(),
+			),
+			(
+				"x-amz-copy-source-server-side-encryption-customer-key-md5".to_owned(),
+				"4Funlf7OsLF0HL+vKU+fkg==".to_owned(),
+			),
+		];
+		let expected = std::iter::once(("POST".to_owned(), None, vec![]))
+			.chain((0..parts).map(|i| {
+				let start = i * MIN_COPY_PART_SIZE;
+				let end = len.min(start + MIN_COPY_PART_SIZE) - 1;
+				(
+					"PUT".to_owned(),
+					Some(format!("bytes={}-{}", start, end)),
+					copy_source.clone(),
+				)
+			}))
+			.chain(std::iter::once(("POST".to_owned(), None, vec![])))
+			.collect::<Vec<_>>();
+		assert_eq!(*requests.lock().unwrap(), expected);
+		assert_eq!(
+			expected[expected.len() - 2].1,
+			Some(format!("bytes={}-{}", MAX_COPY_SIZE, MAX_COPY_SIZE))
+		);
+	}
+
+	#[test]
+	fn copy_part_count() {
+		let parts = |len: u64| {
+			let part_size = copy_part_size(len);
+			(part_size, (len + part_size - 1) / part_size)
+		};
+		assert_eq!(parts(MAX_COPY_SIZE + 1), (MIN_COPY_PART_SIZE, 11));
+		// S3's largest object needs parts larger than the minimum to fit
+		let max_object = 5 * 1024 * 1024 * 1024 * 1024;
+		let (part_size, count) = parts(max_object);
+		assert!(part_size > MIN_COPY_PART_SIZE);
+		assert_eq!(part_size % (1024 * 1024), 0);
+		assert!(count <= MAX_PARTS, "{}", count);
+		assert_eq!(parts(max_object - 1).1, count);
+	}
+
+	#[test]
+	fn debug_redacts_key() {
+		let encryption = S3Encryption::Customer { key: [1; 32] };
+		let debug = format!("{:?} {:?}", encryption, SseHeaders::new(Some(&encryption)));
+		assert!(!debug.contains("AQEBAQEB"), "{}", debug);
+		assert!(!debug.contains("[1, 1"), "{}", debug);
+		assert_eq!(format!("{:?}", encryption), "Customer { key: <redacted> }");
+	}
+}
@@ -12,7 +12,7 @@ use amadeus_core::{
 	file::{Directory, File, Page, Partition, PathBuf}, util::IoError
 };
 
-use super::{retry, AwsCredentials, AwsError, AwsRegion, Ref, RUSOTO_DISPATCHER};
+use super::{retry, s3_client, AwsCredentials, AwsError, AwsRegion, S3Encryption, SseHeaders};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct S3Directory {
@@ -20,6 +20,8 @@ pub struct S3Directory {
 	bucket: String,
 	prefix: String,
 	credentials: AwsCredentials,
+	versions: bool,
+	encryption: Option<S3Encryption>,
 }
 impl S3Directory {
 	pub fn new(region: AwsRegion, bucket: &str, prefix: &str) -> Self {
@@ -34,6 +36,26 @@ impl S3Directory {
 			bucket,
 			prefix,
 			credentials,
+			versions: false,
+			encryption: None,
+		}
+	}
+	/// List every version of the objects in a bucket with versioning enabled,
+	/// with a partition for each key and version rather than just the latest.
+	#[must_use]
+	pub fn list_versions(self) -> Self {
+		Self {
+			versions: true,
+			..self
+		}
+	}
+	/// Read the objects with the SSE-C key of `encryption`, as is required for
+	/// objects encrypted with it.
+	#[must_use]
+	pub fn with_encryption(self, encryption: S3Encryption) -> Self {
+		Self {
+			encryption: Some(encryption),
+			..self
 		}
 	}
 }
@@ -50,24 +72,37 @@ impl Directory for S3Directory {
 			bucket,
 			prefix,
 			credentials,
+			versions,
+			encryption,
 		} = self;
-		let client = S3Client::new_with(
-			Ref(&*RUSOTO_DISPATCHER),
-			credentials.clone(),
-			region.clone(),
-		);
-		let objects = super::list(&client, &bucket, &prefix).await?;
+		let client = s3_client(region.clone(), credentials.clone());
+		let objects: Vec<(String, i64, Option<String>)> = if !versions {
+			super::list(&client, &bucket, &prefix)
+				.await?
+				.into_iter()
+				.map(|object| (object.key.unwrap(), object.size.unwrap(), None))
+				.collect()
+		} else {
+			super::list_versions(&client, &bucket, &prefix)
+				.await?
+				.into_iter()
+				.map(|version| {
+					let key = version.key.unwrap();
+					(key, version.size.unwrap(), version.version_id)
+				})
+				.collect()
+		};
 
 		let mut current_path = PathBuf::new();
 		let mut skip = false;
 		let mut last_key: Option<String> = None;
-		objects
+		Ok(objects
 			.into_iter()
-			.filter(|object| {
-				let key = object.key.as_ref().unwrap();
+			.filter(|(key, _, _)| {
 				assert!(key.starts_with(&prefix));
 				let key = &key[prefix.len()..];
-				assert!(last_key.is_none() || **last_key.as_ref().unwrap() < *key, "S3 API not returning objects in \"UTF-8 character encoding in lexicographical order\" as their docs specify");
+				// Versions of the same key are listed together
+				assert!(last_key.is_none() || **last_key.as_ref().unwrap() < *key || (versions && **last_key.as_ref().unwrap() == *key), "S3 API not returning objects in \"UTF-8 character encoding in lexicographical order\" as their docs specify");
 				last_key = Some(key.to_owned());
 				let mut path = key.split('/').collect::<Vec<&str>>();
 				let file_name = path.pop().unwrap();
@@ -98,16 +133,16 @@ impl Directory for S3Directory {
 				current_path.set_file_name::<Vec<u8>>(None);
 				ret
 			})
-			.map(|object| {
-				Ok(S3Partition {
-					region: region.clone(),
-					bucket: bucket.clone(),
-					key: object.key.unwrap(),
-					len: object.size.unwrap().try_into().unwrap(),
-					credentials: credentials.clone()
-				})
+			.map(|(key, size, version_id)| S3Partition {
+				region: region.clone(),
+				bucket: bucket.clone(),
+				key,
+				version_id,
+				len: size.try_into().unwrap(),
+				credentials: credentials.clone(),
+				encryption: encryption.clone(),
 			})
-			.collect()
+			.collect())
 	}
 }
 
@@ -126,7 +161,9 @@ pub struct S3File {
 	region: AwsRegion,
 	bucket: String,
 	key: String,
+	version_id: Option<String>,
 	credentials: AwsCredentials,
+	encryption: Option<S3Encryption>,
 }
 impl S3File {
 	pub fn new(region: AwsRegion, bucket: &str, key: &str) -> Self {
@@ -140,7 +177,27 @@ impl S3File {
 			region,
 			bucket,
 			key,
+			version_id: None,
 			credentials,
+			encryption: None,
+		}
+	}
+	/// Read the version `version_id` of the object rather than the latest, for
+	/// reproducible reads of a bucket with versioning enabled.
+	#[must_use]
+	pub fn at_version(self, version_id: &str) -> Self {
+		Self {
+			version_id: Some(version_id.to_owned()),
+			..self
+		}
+	}
+	/// Read the object with the SSE-C key of `encryption`, as is required for
+	/// an object encrypted with it.
+	#[must_use]
+	pub fn with_encryption(self, encryption: S3Encryption) -> Self {
+		Self {
+			encryption: Some(encryption),
+			..self
 		}
 	}
 }
@@ -159,8 +216,16 @@ impl Partition for S3File {
 	type Error = IoError;
 
 	async fn pages(self) -> Result<Vec<Self::Page>, Self::Error> {
+		let client = s3_client(self.region, self.credentials);
 		Ok(vec![
-			S3Page::new(self.region, self.bucket, self.key, self.credentials).await,
+			S3Page::new(
+				client,
+				self.bucket,
+				self.key,
+				self.version_id,
+				SseHeaders::new(self.encryption.as_ref()),
+			)
+			.await,
 		])
 	}
 }
@@ -170,8 +235,56 @@ pub struct S3Partition {
 	region: AwsRegion,
 	bucket: String,
 	key: String,
+	version_id: Option<String>,
 	len: u64,
 	credentials: AwsCredentials,
+	encryption: Option<S3Encryption>,
+}
+impl S3Partition {
+	pub fn new(
+		region: AwsRegion, bucket: String, key: String, len: u64, credentials: AwsCredentials,
+	) -> Self {
+		Self {
+			region,
+			bucket,
+			key,
+			version_id: None,
+			len,
+			credentials,
+			encryption: None,
+		}
+	}
+	/// Read the version `version_id` of the object rather than the latest.
+	#[must_use]
+	pub fn at_version(self, version_id: String) -> Self {
+		Self {
+			version_id: Some(version_id),
+			..self
+		}
+	}
+	/// Read the object with the SSE-C key of `encryption`.
+	#[must_use]
+	pub fn with_encryption(self, encryption: S3Encryption) -> Self {
+		Self {
+			encryption: Some(encryption),
+			..self
+		}
+	}
+	pub fn bucket(&self) -> &str {
+		&self.bucket
+	}
+	pub fn key(&self) -> &str {
+		&self.key
+	}
+	pub fn version_id(&self) -> Option<&str> {
+		self.version_id.as_deref()
+	}
+	pub fn encryption(&self) -> Option<&S3Encryption> {
+		self.encryption.as_ref()
+	}
+	pub(crate) fn len(&self) -> u64 {
+		self.len
+	}
 }
 #[async_trait(?Send)]
 impl Partition for S3Partition {
@@ -179,13 +292,16 @@ impl Partition for S3Partition {
 	type Error = IoError;
 
 	async fn pages(self) -> Result<Vec<Self::Page>, Self::Error> {
-		let client = S3Client::new_with(Ref(&*RUSOTO_DISPATCHER), self.credentials, self.region);
-		let (bucket, key, len) = (self.bucket, self.key, self.len);
+		let client = s3_client(self.region, self.credentials);
+		let (bucket, key, version_id, len) = (self.bucket, self.key, self.version_id, self.len);
+		let sse = SseHeaders::new(self.encryption.as_ref());
 		let inner = Arc::new(S3PageInner {
 			client,
 			bucket,
 			key,
+			version_id,
 			len,
+			sse,
 		});
 		Ok(vec![S3Page { inner }])
 	}
@@ -195,20 +311,25 @@ struct S3PageInner {
 	client: S3Client,
 	bucket: String,
 	key: String,
+	version_id: Option<String>,
 	len: u64,
+	sse: SseHeaders,
 }
 pub struct S3Page {
 	inner: Arc<S3PageInner>,
 }
 impl S3Page {
 	async fn new(
-		region: AwsRegion, bucket: String, key: String, credentials: AwsCredentials,
+		client: S3Client, bucket: String, key: String, version_id: Option<String>, sse: SseHeaders,
 	) -> Self {
-		let client = S3Client::new_with(Ref(&*RUSOTO_DISPATCHER), credentials, region);
 		let object = retry(|| {
 			client.head_object(HeadObjectRequest {
 				bucket: bucket.clone(),
 				key: key.clone(),
+				version_id: version_id.clone(),
+				sse_customer_algorithm: sse.sse_customer_algorithm.clone(),
+				sse_customer_key: sse.sse_customer_key.clone(),
+				sse_customer_key_md5: sse.sse_customer_key_md5.clone(),
 				..HeadObjectRequest::default()
 			})
 		})
@@ -219,7 +340,9 @@ impl S3Page {
 			client,
 			bucket,
 			key,
+			version_id,
 			len,
+			sse,
 		});
 		Self { inner }
 	}
@@ -250,7 +373,11 @@ impl Page for S3Page {
 				let res = self_.inner.client.get_object(GetObjectRequest {
 					bucket: self_.inner.bucket.clone(),
 					key: self_.inner.key.clone(),
+					version_id: self_.inner.version_id.clone(),
 					range: Some(format!("bytes={}-{}", start, end)),
+					sse_customer_algorithm: self_.inner.sse.sse_customer_algorithm.clone(),
+					sse_customer_key: self_.inner.sse.sse_customer_key.clone(),
+					sse_customer_key_md5: self_.inner.sse.sse_customer_key_md5.clone(),
 					..GetObjectRequest::default()
 				});
 				let res = res.await;
@@ -285,3 +412,291 @@ impl Page for S3Page {
 		todo!("Tracking at https://github.com/constellation-rs/amadeus/issues/61")
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use rusoto_core::signature::SignedRequest;
+	use rusoto_mock::{
+		MockCredentialsProvider, MockRequestDispatcher, MultipleMockRequestDispatcher
+	};
+	use std::{
+		io::{BufRead, BufReader, Write}, net::TcpListener, sync::{
+			atomic::{AtomicUsize, Ordering}, Mutex
+		}, thread
+	};
+
+	use super::*;
+
+	/// Serve ranged GETs of zeros over HTTP/1.1, with keep-alive or closing the
+	/// connection after each response, counting the connections accepted.
+	fn serve(keep_alive: bool) -> (String, Arc<AtomicUsize>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let endpoint = format!("http://{}", listener.local_addr().unwrap());
+		let connections = Arc::new(AtomicUsize::new(0));
+		let connections_ = connections.clone();
+		let _ = thread::spawn(move || {
+			for stream in listener.incoming() {
+				let _ = connections_.fetch_add(1, Ordering::SeqCst);
+				let mut stream = stream.unwrap();
+				let _ = thread::spawn(move || {
+					let mut reader = BufReader::new(stream.try_clone().unwrap());
+					loop {
+						let mut len = 0;
+						loop {
+							let mut line = String::new();
+							if reader.read_line(&mut line).unwrap() == 0 {
+								return;
+							}
+							let line = line.to_ascii_lowercase();
+							if let Some(range) = line.strip_prefix("range: bytes=") {
+								let mut bounds = range.trim().splitn(2, '-');
+								let (start, end) = (bounds.next().unwrap(), bounds.next().unwrap());
+								len = end.parse::<usize>().unwrap()
+									- start.parse::<usize>().unwrap()
+									+ 1;
+							}
+							if line == "\r\n" {
+								break;
+							}
+						}
+						let connection = if keep_alive { "keep-alive" } else { "close" };
+						let mut response = format!(
+							"HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: {}\r\n\r\n",
+							len, connection
+						)
+						.into_bytes();
+						response.resize(response.len() + len, 0);
+						stream.write_all(&response).unwrap();
+						if !keep_alive {
+							return;
+						}
+					}
+				});
+			}
+		});
+		(endpoint, connections)
+	}
+
+	/// Make ten small reads of each of 20 partitions, one after another, from a
+	/// server with or without keep-alive, returning the connections it accepted.
+	fn small_reads(keep_alive: bool) -> usize {
+		let (endpoint, connections) = serve(keep_alive);
+		let region = AwsRegion::Custom {
+			name: "local".to_owned(),
+			endpoint,
+		};
+		let credentials = AwsCredentials::AccessKey {
+			id: "id".to_owned(),
+			secret: "secret".to_owned(),
+		};
+		let mut runtime = tokio::runtime::Builder::new()
+			.basic_scheduler()
+			.enable_all()
+			.build()
+			.unwrap();
+		runtime.block_on(async {
+			for i in 0..20 {
+				let partition = S3Partition::new(
+					region.clone(),
+					"bucket".to_owned(),
+					format!("key{}", i),
+					1000,
+					credentials.clone(),
+				);
+				let page = partition.pages().await.unwrap().pop().unwrap();
+				for offset in (0..1000).step_by(100) {
+					assert_eq!(&*page.read(offset, 10).await.unwrap(), &[0; 10][..]);
+				}
+			}
+		});
+		connections.load(Ordering::SeqCst)
+	}
+
+	#[test]
+	fn connection_reuse() {
+		// Every read sets up a connection unless they're kept alive, in which
+		// case the partitions share the one connection
+		assert_eq!(small_reads(false), 200);
+		assert_eq!(small_reads(true), 1);
+	}
+
+	#[test]
+	fn clients_evicted() {
+		let region = |i: usize| AwsRegion::Custom {
+			name: "local".to_owned(),
+			endpoint: format!("http://127.0.0.1:{}", i),
+		};
+		let mut clients = crate::Clients::default();
+		for i in 0..crate::MAX_CLIENTS {
+			let _ = clients.get(region(i), AwsCredentials::Anonymous);
+		}
+		let _ = clients.get(region(0), AwsCredentials::Anonymous);
+		let _ = clients.get(region(crate::MAX_CLIENTS), AwsCredentials::Anonymous);
+		// The least recently used, region 1, was evicted to make room
+		let held = clients
+			.0
+			.iter()
+			.map(|((region, _), _)| region.clone())
+			.collect::<Vec<_>>();
+		assert_eq!(held.len(), crate::MAX_CLIENTS);
+		assert!(held.contains(&region(0)));
+		assert!(!held.contains(&region(1)));
+		assert!(held.contains(&region(crate::MAX_CLIENTS)));
+	}
+
+	#[test]
+	fn read_version() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let requests = requests.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				let version_id = request.params.get("versionId").cloned().flatten();
+				requests
+					.lock()
+					.unwrap()
+					.push((request.method().to_owned(), version_id));
+			})
+		};
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			record(MockRequestDispatcher::default().with_header("Content-Length", "5")),
+			record(MockRequestDispatcher::default().with_body("hello")),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let page = block_on(S3Page::new(
+			client,
+			"bucket".to_owned(),
+			"key".to_owned(),
+			Some("v1".to_owned()),
+			SseHeaders::default(),
+		));
+		assert_eq!(&*block_on(page.read(0, 5)).unwrap(), b"hello");
+
+		let version = Some("v1".to_owned());
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[
+				("HEAD".to_owned(), version.clone()),
+				("GET".to_owned(), version)
+			]
+		);
+	}
+
+	#[test]
+	fn read_encrypted() {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let requests = requests.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				let header = |name: &str| {
+					request
+						.headers()
+						.get(name)
+						.map(|value| String::from_utf8(value[0].clone()).unwrap())
+				};
+				requests.lock().unwrap().push((
+					request.method().to_owned(),
+					header("x-amz-server-side-encryption-customer-algorithm"),
+					header("x-amz-server-side-encryption-customer-key"),
+					header("x-amz-server-side-encryption-customer-key-md5"),
+				));
+			})
+		};
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			record(MockRequestDispatcher::default().with_header("Content-Length", "5")),
+			record(MockRequestDispatcher::default().with_body("hello")),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let encryption = S3Encryption::Customer { key: [1; 32] };
+		let page = block_on(S3Page::new(
+			client,
+			"bucket".to_owned(),
+			"key".to_owned(),
+			None,
+			SseHeaders::new(Some(&encryption)),
+		));
+		assert_eq!(&*block_on(page.read(0, 5)).unwrap(), b"hello");
+
+		// SSE-C keys are required on every request, reads included
+		let headers = (
+			Some("AES256".to_owned()),
+			Some("AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=".to_owned()),
+			Some("4Funlf7OsLF0HL+vKU+fkg==".to_owned()),
+		);
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[
+				(
+					"HEAD".to_owned(),
+					headers.0.clone(),
+					headers.1.clone(),
+					headers.2.clone()
+				),
+				("GET".to_owned(), headers.0, headers.1, headers.2)
+			]
+		);
+	}
+
+	#[test]
+	fn list_versions() {
+		let markers = Arc::new(Mutex::new(Vec::new()));
+		let record = |dispatcher: MockRequestDispatcher| {
+			let markers = markers.clone();
+			dispatcher.with_request_checker(move |request: &SignedRequest| {
+				let marker = |name: &str| request.params.get(name).cloned().flatten();
+				markers
+					.lock()
+					.unwrap()
+					.push((marker("key-marker"), marker("version-id-marker")));
+			})
+		};
+		let dispatcher = MultipleMockRequestDispatcher::new(vec![
+			record(MockRequestDispatcher::default().with_body(
+				"<ListVersionsResult>\
+					<IsTruncated>true</IsTruncated>\
+					<NextKeyMarker>logs/a</NextKeyMarker>\
+					<NextVersionIdMarker>a1</NextVersionIdMarker>\
+					<Version><Key>logs/a</Key><VersionId>a2</VersionId><Size>2</Size></Version>\
+					<Version><Key>logs/a</Key><VersionId>a1</VersionId><Size>1</Size></Version>\
+				</ListVersionsResult>",
+			)),
+			record(MockRequestDispatcher::default().with_body(
+				"<ListVersionsResult>\
+					<IsTruncated>false</IsTruncated>\
+					<DeleteMarker><Key>logs/b</Key><VersionId>b2</VersionId></DeleteMarker>\
+					<Version><Key>logs/b</Key><VersionId>b1</VersionId><Size>3</Size></Version>\
+				</ListVersionsResult>",
+			)),
+		]);
+		let client = S3Client::new_with(dispatcher, MockCredentialsProvider, AwsRegion::UsEast1);
+
+		let versions = block_on(crate::list_versions(&client, "bucket", "logs/")).unwrap();
+		let versions = versions
+			.into_iter()
+			.map(|version| {
+				(
+					version.key.unwrap(),
+					version.version_id.unwrap(),
+					version.size.unwrap(),
+				)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			versions,
+			[
+				("logs/a".to_owned(), "a2".to_owned(), 2),
+				("logs/a".to_owned(), "a1".to_owned(), 1),
+				("logs/b".to_owned(), "b1".to_owned(), 3)
+			]
+		);
+		assert_eq!(
+			*markers.lock().unwrap(),
+			[
+				(None, None),
+				(Some("logs/a".to_owned()), Some("a1".to_owned()))
+			]
+		);
+	}
+}
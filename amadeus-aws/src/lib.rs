@@ -31,6 +31,7 @@
 #![deny(unsafe_code)]
 
 mod cloudfront;
+mod copy;
 mod file;
 
 use async_trait::async_trait;
@@ -40,10 +41,12 @@ use rusoto_core::{
 	credential::StaticProvider, request::{DispatchSignedRequest, DispatchSignedRequestFuture, HttpClient}, signature::SignedRequest, RusotoError
 };
 use rusoto_credential::{CredentialsError, DefaultCredentialsProvider, ProvideAwsCredentials};
-use rusoto_s3::{GetObjectError, ListObjectsV2Error, ListObjectsV2Request, Object, S3Client, S3};
+use rusoto_s3::{
+	CompleteMultipartUploadError, CopyObjectError, CreateMultipartUploadError, GetObjectError, ListObjectVersionsError, ListObjectVersionsRequest, ListObjectsV2Error, ListObjectsV2Request, Object, ObjectVersion, PutObjectError, S3Client, UploadPartCopyError, UploadPartError, S3
+};
 use serde::{Deserialize, Serialize};
 use std::{
-	error, fmt::{self, Display}, future::Future, io, ops::FnMut, time::Duration
+	error, fmt::{self, Display}, future::Future, io, ops::FnMut, sync::{Mutex, PoisonError}, time::Duration
 };
 
 use amadeus_core::util::{IoError, ResultExpand};
@@ -51,7 +54,9 @@ use amadeus_core::util::{IoError, ResultExpand};
 #[doc(inline)]
 pub use cloudfront::{Cloudfront, CloudfrontRow};
 #[doc(inline)]
-pub use file::{S3Directory, S3File};
+pub use copy::{S3Copy, S3Object};
+#[doc(inline)]
+pub use file::{S3Directory, S3File, S3Partition};
 #[doc(inline)]
 pub use rusoto_core::Region as AwsRegion;
 
@@ -61,6 +66,43 @@ static RUSOTO_DISPATCHER: Lazy<HttpClient> =
 	Lazy::new(|| HttpClient::new().expect("failed to create request dispatcher"));
 static RUSOTO_CREDENTIALS_PROVIDER: Lazy<DefaultCredentialsProvider> =
 	Lazy::new(|| DefaultCredentialsProvider::new().expect("failed to create credentials provider"));
+/// The clients shared by all sources and partitions in this process, by region
+/// and credentials. They all dispatch through the one `RUSOTO_DISPATCHER`,
+/// whose hyper client keeps connections alive and pools them per host.
+static RUSOTO_CLIENTS: Lazy<Mutex<Clients>> = Lazy::new(Default::default);
+
+fn s3_client(region: AwsRegion, credentials: AwsCredentials) -> S3Client {
+	RUSOTO_CLIENTS
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.get(region, credentials)
+}
+
+/// The most clients [`Clients`] holds before evicting the least recently used.
+const MAX_CLIENTS: usize = 16;
+
+/// The clients of the regions and credentials most recently used, least
+/// recently used first.
+#[derive(Default)]
+struct Clients(Vec<((AwsRegion, AwsCredentials), S3Client)>);
+impl Clients {
+	fn get(&mut self, region: AwsRegion, credentials: AwsCredentials) -> S3Client {
+		let key = (region, credentials);
+		let entry = if let Some(i) = self.0.iter().position(|(key_, _)| *key_ == key) {
+			self.0.remove(i)
+		} else {
+			if self.0.len() == MAX_CLIENTS {
+				drop(self.0.remove(0));
+			}
+			let (region, credentials) = key.clone();
+			let client = S3Client::new_with(Ref(&*RUSOTO_DISPATCHER), credentials, region);
+			(key, client)
+		};
+		let client = entry.1.clone();
+		self.0.push(entry);
+		client
+	}
+}
 
 fn retry<F, FU, T, S>(f: F) -> impl Future<Output = Result<T, RusotoError<S>>>
 where
@@ -117,6 +159,33 @@ async fn list(
 	objects
 }
 
+/// List every version of the objects under `prefix`, ordered by key and then
+/// newest first. Delete markers are omitted.
+async fn list_versions(
+	client: &S3Client, bucket: &str, prefix: &str,
+) -> Result<Vec<ObjectVersion>, RusotoError<ListObjectVersionsError>> {
+	let mut versions = Vec::new();
+	let (mut key_marker, mut version_id_marker) = (None, None);
+	loop {
+		let res = retry(|| {
+			client.list_object_versions(ListObjectVersionsRequest {
+				bucket: bucket.to_owned(),
+				prefix: Some(prefix.to_owned()),
+				key_marker: key_marker.clone(),
+				version_id_marker: version_id_marker.clone(),
+				..ListObjectVersionsRequest::default()
+			})
+		})
+		.await?;
+		versions.extend(res.versions.unwrap_or_default());
+		if res.is_truncated != Some(true) {
+			break Ok(versions);
+		}
+		key_marker = res.next_key_marker;
+		version_id_marker = res.next_version_id_marker;
+	}
+}
+
 struct Ref<T: 'static>(&'static T);
 impl<T: 'static> Copy for Ref<T> {}
 impl<T: 'static> Clone for Ref<T> {
@@ -135,7 +204,7 @@ where
 	}
 }
 
-#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 pub enum AwsCredentials {
 	Anonymous,
 	AccessKey { id: String, secret: String },
@@ -167,11 +236,79 @@ impl ProvideAwsCredentials for AwsCredentials {
 	}
 }
 
+/// Server-side encryption of the objects read or written, for buckets that
+/// require it.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum S3Encryption {
+	/// SSE-KMS with the KMS key `key_id`, or the AWS managed key if `None`. S3
+	/// decrypts these objects transparently, so this only affects writes.
+	Kms { key_id: Option<String> },
+	/// SSE-C with a 256-bit key held by the client, which S3 requires on every
+	/// read and write of the object.
+	Customer { key: [u8; 32] },
+}
+impl fmt::Debug for S3Encryption {
+	/// The SSE-C key is redacted, so that it isn't leaked into logs.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Kms { key_id } => f.debug_struct("Kms").field("key_id", key_id).finish(),
+			Self::Customer { .. } => f
+				.debug_struct("Customer")
+				.field("key", &format_args!("<redacted>"))
+				.finish(),
+		}
+	}
+}
+
+/// The encryption headers of requests for an object encrypted with an
+/// [`S3Encryption`].
+#[derive(Clone, Default)]
+struct SseHeaders {
+	server_side_encryption: Option<String>,
+	ssekms_key_id: Option<String>,
+	sse_customer_algorithm: Option<String>,
+	sse_customer_key: Option<String>,
+	sse_customer_key_md5: Option<String>,
+}
+impl fmt::Debug for SseHeaders {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SseHeaders")
+			.field("server_side_encryption", &self.server_side_encryption)
+			.field("ssekms_key_id", &self.ssekms_key_id)
+			.field("sse_customer_algorithm", &self.sse_customer_algorithm)
+			.field(
+				"sse_customer_key",
+				&self.sse_customer_key.as_ref().map(|_| "<redacted>"),
+			)
+			.field("sse_customer_key_md5", &self.sse_customer_key_md5)
+			.finish()
+	}
+}
+impl SseHeaders {
+	fn new(encryption: Option<&S3Encryption>) -> Self {
+		match encryption {
+			None => Self::default(),
+			Some(S3Encryption::Kms { key_id }) => Self {
+				server_side_encryption: Some("aws:kms".to_owned()),
+				ssekms_key_id: key_id.clone(),
+				..Self::default()
+			},
+			Some(S3Encryption::Customer { key }) => Self {
+				sse_customer_algorithm: Some("AES256".to_owned()),
+				sse_customer_key: Some(base64::encode(key)),
+				sse_customer_key_md5: Some(base64::encode(md5::compute(key).0)),
+				..Self::default()
+			},
+		}
+	}
+}
+
 #[derive(Debug)]
 #[allow(clippy::pub_enum_variant_names)]
 pub enum AwsError {
 	NoSuchBucket(String),
 	NoSuchKey(String),
+	ObjectNotInActiveTier(String),
 	HttpDispatch(rusoto_core::request::HttpDispatchError),
 	Credentials(CredentialsError),
 	Validation(String),
@@ -184,6 +321,7 @@ impl Clone for AwsError {
 		match self {
 			Self::NoSuchBucket(err) => Self::NoSuchBucket(err.clone()),
 			Self::NoSuchKey(err) => Self::NoSuchKey(err.clone()),
+			Self::ObjectNotInActiveTier(err) => Self::ObjectNotInActiveTier(err.clone()),
 			Self::HttpDispatch(err) => Self::HttpDispatch(err.clone()),
 			Self::Credentials(CredentialsError { message }) => {
 				Self::Credentials(CredentialsError {
@@ -210,6 +348,7 @@ impl PartialEq for AwsError {
 		match (self, other) {
 			(Self::NoSuchBucket(a), Self::NoSuchBucket(b))
 			| (Self::NoSuchKey(a), Self::NoSuchKey(b))
+			| (Self::ObjectNotInActiveTier(a), Self::ObjectNotInActiveTier(b))
 			| (Self::Validation(a), Self::Validation(b))
 			| (Self::ParseError(a), Self::ParseError(b)) => a == b,
 			(Self::HttpDispatch(a), Self::HttpDispatch(b)) => a == b,
@@ -226,6 +365,7 @@ impl Display for AwsError {
 		match self {
 			Self::NoSuchBucket(err)
 			| Self::NoSuchKey(err)
+			| Self::ObjectNotInActiveTier(err)
 			| Self::Validation(err)
 			| Self::ParseError(err) => err.fmt(f),
 			Self::HttpDispatch(err) => err.fmt(f),
@@ -263,6 +403,11 @@ impl From<ListObjectsV2Error> for AwsError {
 		}
 	}
 }
+impl From<ListObjectVersionsError> for AwsError {
+	fn from(err: ListObjectVersionsError) -> Self {
+		match err {}
+	}
+}
 impl From<GetObjectError> for AwsError {
 	fn from(err: GetObjectError) -> Self {
 		match err {
@@ -270,3 +415,35 @@ impl From<GetObjectError> for AwsError {
 		}
 	}
 }
+impl From<CopyObjectError> for AwsError {
+	fn from(err: CopyObjectError) -> Self {
+		match err {
+			CopyObjectError::ObjectNotInActiveTierError(err) => Self::ObjectNotInActiveTier(err),
+		}
+	}
+}
+impl From<PutObjectError> for AwsError {
+	fn from(err: PutObjectError) -> Self {
+		match err {}
+	}
+}
+impl From<CreateMultipartUploadError> for AwsError {
+	fn from(err: CreateMultipartUploadError) -> Self {
+		match err {}
+	}
+}
+impl From<UploadPartError> for AwsError {
+	fn from(err: UploadPartError) -> Self {
+		match err {}
+	}
+}
+impl From<UploadPartCopyError> for AwsError {
+	fn from(err: UploadPartCopyError) -> Self {
+		match err {}
+	}
+}
+impl From<CompleteMultipartUploadError> for AwsError {
+	fn from(err: CompleteMultipartUploadError) -> Self {
+		match err {}
+	}
+}
@@ -9,8 +9,8 @@ use amadeus_types::Webpage;
 
 use super::parser;
 
-const BUF: usize = 1 << 22; // 4 MiB
-const CHOMP: usize = 1 << 13; // 8 KiB
+pub(crate) const BUF: usize = 1 << 22; // 4 MiB
+pub(crate) const CHOMP: usize = 1 << 13; // 8 KiB, by default
 
 #[pin_project]
 #[derive(Clone, Debug)]
@@ -20,6 +20,9 @@ pub(crate) struct WarcParser<I> {
 	state: WarcParserState,
 	res: Vec<u8>,
 	offset: usize,
+	chomp: usize,
+	skip_payload: bool,
+	skip: usize,
 }
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum WarcParserState {
@@ -36,8 +39,23 @@ impl<I> WarcParser<I> {
 			state: WarcParserState::Info,
 			res: Vec::with_capacity(BUF),
 			offset: 0,
+			chomp: CHOMP,
+			skip_payload: false,
+			skip: 0,
 		}
 	}
+	/// Read up to `chomp` bytes of input at a time.
+	pub(crate) fn chomp(mut self, chomp: usize) -> Self {
+		assert_ne!(chomp, 0);
+		self.chomp = chomp;
+		self
+	}
+	/// Leave `Webpage::contents` empty, and read past the payload rather than
+	/// buffering it, so only the headers of each response are held.
+	pub(crate) fn skip_payload(mut self) -> Self {
+		self.skip_payload = true;
+		self
+	}
 }
 impl<I> WarcParser<I>
 where
@@ -48,6 +66,13 @@ where
 			return Ok(None);
 		}
 		'chomp: loop {
+			if self.skip != 0 {
+				let skip = self.skip as u64;
+				if io::copy(&mut self.input.by_ref().take(skip), &mut io::sink())? < skip {
+					return Err(io::ErrorKind::UnexpectedEof.into());
+				}
+				self.skip = 0;
+			}
 			assert!(
 				self.res.len() < BUF,
 				"Individual record > configured BUF {:?}",
@@ -57,7 +82,7 @@ where
 				&mut self
 					.input
 					.by_ref()
-					.take(CHOMP.min(BUF - self.res.len()) as u64),
+					.take(self.chomp.min(BUF - self.res.len()) as u64),
 				&mut self.res,
 			)?;
 			assert_eq!(self.res.capacity(), BUF);
@@ -73,6 +98,31 @@ where
 				if self.offset == self.res.len() {
 					continue 'chomp;
 				}
+				if self.skip_payload && self.state == WarcParserState::Response {
+					let (record, record_len) = match parser::record_header(&self.res[self.offset..])
+					{
+						Ok((rem, (record, length))) => {
+							let record_len = self.res.len() - self.offset - rem.len() + length + 4; // 4 is \r\n\r\n
+							(record, record_len)
+						}
+						Err(nom::Err::Incomplete(_)) => {
+							continue 'chomp;
+						}
+						_ => panic!(),
+					};
+					assert!(record.type_ == parser::RecordType::Response);
+					let page = Webpage {
+						ip: record.ip_address.unwrap().parse().unwrap(),
+						url: Url::parse(record.target_uri.unwrap()).unwrap(),
+						contents: Cow::Borrowed(&[]),
+					};
+					// Whatever of the record isn't buffered yet is read past
+					let buffered = self.res.len() - self.offset;
+					self.skip = record_len.saturating_sub(buffered);
+					self.offset += record_len.min(buffered);
+					self.state = WarcParserState::Metadata;
+					return Ok(Some(page));
+				}
 				let record = match parser::record(&self.res[self.offset..]) {
 					Ok((rem, record)) => {
 						let record_len = self.res.len() - self.offset - rem.len() + 4; // 4 is \r\n\r\n
@@ -132,12 +182,21 @@ where
 			return Poll::Ready(Ok(None));
 		}
 		'chomp: loop {
+			while *self_.skip != 0 {
+				let mut buf = [0; CHOMP];
+				let len = (*self_.skip).min(CHOMP);
+				let n = ready!(self_.input.as_mut().poll_read(cx, &mut buf[..len]))?;
+				if n == 0 {
+					return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+				}
+				*self_.skip -= n;
+			}
 			assert!(
 				self_.res.len() < BUF,
 				"Individual record > configured BUF {:?}",
 				BUF
 			);
-			let from = (&mut self_.input).take(CHOMP.min(BUF - self_.res.len()) as u64);
+			let from = (&mut self_.input).take((*self_.chomp).min(BUF - self_.res.len()) as u64);
 			let copy = futures::io::copy(from, self_.res);
 			pin_mut!(copy);
 			let n = ready!(copy.poll(cx))?;
@@ -154,6 +213,32 @@ where
 				if *self_.offset == self_.res.len() {
 					continue 'chomp;
 				}
+				if *self_.skip_payload && *self_.state == WarcParserState::Response {
+					let (record, record_len) =
+						match parser::record_header(&self_.res[*self_.offset..]) {
+							Ok((rem, (record, length))) => {
+								let record_len =
+									self_.res.len() - *self_.offset - rem.len() + length + 4; // 4 is \r\n\r\n
+								(record, record_len)
+							}
+							Err(nom::Err::Incomplete(_)) => {
+								continue 'chomp;
+							}
+							_ => panic!(),
+						};
+					assert!(record.type_ == parser::RecordType::Response);
+					let page = Webpage {
+						ip: record.ip_address.unwrap().parse().unwrap(),
+						url: Url::parse(record.target_uri.unwrap()).unwrap(),
+						contents: Cow::Borrowed(&[]),
+					};
+					// Whatever of the record isn't buffered yet is read past
+					let buffered = self_.res.len() - *self_.offset;
+					*self_.skip = record_len.saturating_sub(buffered);
+					*self_.offset += record_len.min(buffered);
+					*self_.state = WarcParserState::Metadata;
+					return Poll::Ready(Ok(Some(page)));
+				}
 				let record = match parser::record(&self_.res[*self_.offset..]) {
 					Ok((rem, record)) => {
 						let record_len = self_.res.len() - *self_.offset - rem.len() + 4; // 4 is \r\n\r\n
@@ -225,3 +310,153 @@ where
 		)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use super::*;
+
+	fn record(type_: &str, headers: &str, content: &[u8]) -> Vec<u8> {
+		let mut record = format!(
+			"WARC/1.0\r\nWARC-Type: {}\r\n{}Content-Length: {}\r\n\r\n",
+			type_,
+			headers,
+			content.len()
+		)
+		.into_bytes();
+		record.extend_from_slice(content);
+		record.extend_from_slice(b"\r\n\r\n");
+		record
+	}
+
+	fn warc(pages: usize, payload: usize) -> Vec<u8> {
+		let mut warc = record("warcinfo", "", b"info");
+		for i in 0..pages {
+			let uri = format!("WARC-Target-URI: http://example.com/{}\r\n", i);
+			warc.extend(record("request", &uri, b"GET"));
+			warc.extend(record(
+				"response",
+				&format!("{}WARC-IP-Address: 10.0.0.{}\r\n", uri, i),
+				&vec![b'x'; payload + i],
+			));
+			warc.extend(record("metadata", &uri, b"meta"));
+		}
+		warc
+	}
+
+	#[test]
+	fn skip_payload() {
+		let warc = warc(100, 1000);
+
+		let pages = WarcParser::new(Cursor::new(&warc))
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+		let skipped = WarcParser::new(Cursor::new(&warc))
+			.skip_payload()
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+
+		assert_eq!(pages.len(), 100);
+		assert_eq!(skipped.len(), 100);
+		for (i, (page, skipped)) in pages.iter().zip(&skipped).enumerate() {
+			assert_eq!(page.contents.len(), 1000 + i);
+			assert_eq!(skipped.url, page.url);
+			assert_eq!(skipped.ip, page.ip);
+			assert_eq!(skipped.url.as_str(), format!("http://example.com/{}", i));
+			assert!(skipped.contents.is_empty());
+		}
+	}
+
+	#[test]
+	fn skip_payload_async() {
+		let warc = warc(100, 1000);
+
+		let pages =
+			futures::executor::block_on_stream(WarcParser::new(futures::io::Cursor::new(&warc)))
+				.collect::<Result<Vec<_>, _>>()
+				.unwrap();
+		let skipped = futures::executor::block_on_stream(
+			WarcParser::new(futures::io::Cursor::new(&warc)).skip_payload(),
+		)
+		.collect::<Result<Vec<_>, _>>()
+		.unwrap();
+
+		assert_eq!(skipped.len(), 100);
+		for (page, skipped) in pages.iter().zip(&skipped) {
+			assert_eq!((&skipped.url, skipped.ip), (&page.url, page.ip));
+			assert!(skipped.contents.is_empty());
+		}
+	}
+
+	#[test]
+	fn skip_payload_unbuffered() {
+		// Payloads larger than the buffer can only be read past
+		let warc = warc(3, 2 * BUF);
+
+		let mut parser = WarcParser::new(Cursor::new(&warc)).skip_payload();
+		let mut pages = 0;
+		while let Some(page) = parser.next_borrowed().unwrap() {
+			assert!(page.contents.is_empty());
+			assert!(parser.res.len() <= CHOMP);
+			pages += 1;
+		}
+		assert_eq!(pages, 3);
+	}
+
+	#[test]
+	fn skip_payload_buffered() {
+		let warc = warc(50, 1 << 20);
+
+		// The bytes held in the buffer as each page is returned
+		let buffered = |skip_payload| {
+			let parser = WarcParser::new(Cursor::new(&warc));
+			let mut parser = if skip_payload {
+				parser.skip_payload()
+			} else {
+				parser
+			};
+			let mut buffered = Vec::new();
+			while parser.next_borrowed().unwrap().is_some() {
+				buffered.push(parser.res.len());
+			}
+			buffered
+		};
+		let (full, skipped) = (buffered(false), buffered(true));
+		assert_eq!((full.len(), skipped.len()), (50, 50));
+		assert!(full.iter().all(|&len| len > 1 << 20), "{:?}", full);
+		assert!(skipped.iter().all(|&len| len <= CHOMP), "{:?}", skipped);
+	}
+
+	/// Counts the reads made of `inner`.
+	struct Counting<R> {
+		inner: R,
+		reads: usize,
+	}
+	impl<R: Read> Read for Counting<R> {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			self.reads += 1;
+			self.inner.read(buf)
+		}
+	}
+
+	#[test]
+	fn chomp() {
+		let warc = warc(10, 100_000);
+
+		let mut reads = Vec::new();
+		for &chomp in &[CHOMP, 1 << 20] {
+			let mut input = Counting {
+				inner: Cursor::new(&warc),
+				reads: 0,
+			};
+			let pages = WarcParser::new(&mut input)
+				.chomp(chomp)
+				.collect::<Result<Vec<_>, _>>()
+				.unwrap();
+			assert_eq!(pages.len(), 10);
+			reads.push(input.reads);
+		}
+		assert!(reads[1] < reads[0], "{:?}", reads);
+	}
+}
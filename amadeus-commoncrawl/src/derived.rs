@@ -0,0 +1,512 @@
+//! Parsers for the WAT and WET files CommonCrawl derives from each WARC file.
+//!
+//! Both are WARC files themselves: a `warcinfo` record followed by a record per
+//! capture, `metadata` records of JSON for WAT and `conversion` records of the
+//! extracted plain text for WET.
+
+use futures::{pin_mut, ready, AsyncRead, AsyncReadExt, Stream};
+use pin_project::pin_project;
+use std::{
+	borrow::Cow, future::Future, io::{self, Read}, iter, ops::Range, pin::Pin, str, task::{Context, Poll}
+};
+use url::Url;
+
+use super::{
+	commoncrawl::{BUF, CHOMP}, parser
+};
+
+/// A record of a WAT file: the metadata extracted from a capture of `url`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WatRecord<'a> {
+	pub url: Url,
+	/// The metadata as a JSON document.
+	pub metadata: Cow<'a, str>,
+}
+impl<'a> WatRecord<'a> {
+	fn new((url, metadata): (Url, &'a str)) -> Self {
+		Self {
+			url,
+			metadata: Cow::Borrowed(metadata),
+		}
+	}
+	#[must_use]
+	pub fn to_owned(&self) -> WatRecord<'static> {
+		WatRecord {
+			url: self.url.clone(),
+			metadata: Cow::Owned(self.metadata.clone().into_owned()),
+		}
+	}
+}
+
+/// A record of a WET file: the plain text extracted from a capture of `url`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WetRecord<'a> {
+	pub url: Url,
+	pub text: Cow<'a, str>,
+}
+impl<'a> WetRecord<'a> {
+	fn new((url, text): (Url, &'a str)) -> Self {
+		Self {
+			url,
+			text: Cow::Borrowed(text),
+		}
+	}
+	#[must_use]
+	pub fn to_owned(&self) -> WetRecord<'static> {
+		WetRecord {
+			url: self.url.clone(),
+			text: Cow::Owned(self.text.clone().into_owned()),
+		}
+	}
+}
+
+/// Parse a WAT file, yielding a [`WatRecord`] for each `metadata` record.
+#[pin_project]
+#[derive(Clone, Debug)]
+pub struct WatParser<I>(#[pin] RecordParser<I>);
+impl<I> WatParser<I> {
+	pub fn new(input: I) -> Self {
+		Self(RecordParser::new(input, parser::RecordType::Metadata))
+	}
+	/// Read up to `chomp` bytes of input at a time.
+	///
+	/// # Panics
+	///
+	/// Panics if `chomp` is zero.
+	#[must_use]
+	pub fn chomp(self, chomp: usize) -> Self {
+		Self(self.0.chomp(chomp))
+	}
+}
+impl<I> WatParser<I>
+where
+	I: Read,
+{
+	pub fn next_borrowed(&mut self) -> Result<Option<WatRecord<'_>>, io::Error> {
+		Ok(self.0.next_borrowed()?.map(WatRecord::new))
+	}
+}
+impl<I> WatParser<I>
+where
+	I: AsyncRead,
+{
+	pub fn poll_next_borrowed(
+		self: Pin<&mut Self>, cx: &mut Context,
+	) -> Poll<Result<Option<WatRecord<'_>>, io::Error>> {
+		self.project()
+			.0
+			.poll_next_borrowed(cx)
+			.map_ok(|next| next.map(WatRecord::new))
+	}
+}
+impl<I> Iterator for WatParser<I>
+where
+	I: Read,
+{
+	type Item = Result<WatRecord<'static>, io::Error>;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_borrowed()
+			.transpose()
+			.map(|x| x.map(|x| x.to_owned()))
+	}
+}
+impl<I> Stream for WatParser<I>
+where
+	I: AsyncRead,
+{
+	type Item = Result<WatRecord<'static>, io::Error>;
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		Poll::Ready(
+			ready!(self.poll_next_borrowed(cx))
+				.transpose()
+				.map(|x| x.map(|x| x.to_owned())),
+		)
+	}
+}
+
+/// Parse a WET file, yielding a [`WetRecord`] for each `conversion` record.
+#[pin_project]
+#[derive(Clone, Debug)]
+pub struct WetParser<I>(#[pin] RecordParser<I>);
+impl<I> WetParser<I> {
+	pub fn new(input: I) -> Self {
+		Self(RecordParser::new(input, parser::RecordType::Conversion))
+	}
+	/// Read up to `chomp` bytes of input at a time.
+	///
+	/// # Panics
+	///
+	/// Panics if `chomp` is zero.
+	#[must_use]
+	pub fn chomp(self, chomp: usize) -> Self {
+		Self(self.0.chomp(chomp))
+	}
+}
+impl<I> WetParser<I>
+where
+	I: Read,
+{
+	pub fn next_borrowed(&mut self) -> Result<Option<WetRecord<'_>>, io::Error> {
+		Ok(self.0.next_borrowed()?.map(WetRecord::new))
+	}
+}
+impl<I> WetParser<I>
+where
+	I: AsyncRead,
+{
+	pub fn poll_next_borrowed(
+		self: Pin<&mut Self>, cx: &mut Context,
+	) -> Poll<Result<Option<WetRecord<'_>>, io::Error>> {
+		self.project()
+			.0
+			.poll_next_borrowed(cx)
+			.map_ok(|next| next.map(WetRecord::new))
+	}
+}
+impl<I> Iterator for WetParser<I>
+where
+	I: Read,
+{
+	type Item = Result<WetRecord<'static>, io::Error>;
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_borrowed()
+			.transpose()
+			.map(|x| x.map(|x| x.to_owned()))
+	}
+}
+impl<I> Stream for WetParser<I>
+where
+	I: AsyncRead,
+{
+	type Item = Result<WetRecord<'static>, io::Error>;
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		Poll::Ready(
+			ready!(self.poll_next_borrowed(cx))
+				.transpose()
+				.map(|x| x.map(|x| x.to_owned())),
+		)
+	}
+}
+
+/// The streaming structure of [`WarcParser`](super::commoncrawl::WarcParser),
+/// for files of a `warcinfo` record followed by records all of type `type_`.
+/// Malformed input is an [`InvalidData`](io::ErrorKind::InvalidData) error.
+#[pin_project]
+#[derive(Clone, Debug)]
+struct RecordParser<I> {
+	#[pin]
+	input: I,
+	records: Records,
+}
+/// The URL of the next record, and the range of `res` holding its content.
+type Next = (Url, Range<usize>);
+
+#[derive(Clone, Debug)]
+struct Records {
+	state: RecordParserState,
+	type_: parser::RecordType,
+	res: Vec<u8>,
+	offset: usize,
+	chomp: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum RecordParserState {
+	Info,
+	Record,
+	Done,
+}
+impl<I> RecordParser<I> {
+	fn new(input: I, type_: parser::RecordType) -> Self {
+		Self {
+			input,
+			records: Records {
+				state: RecordParserState::Info,
+				type_,
+				res: Vec::with_capacity(BUF),
+				offset: 0,
+				chomp: CHOMP,
+			},
+		}
+	}
+	fn chomp(mut self, chomp: usize) -> Self {
+		assert_ne!(chomp, 0);
+		self.records.chomp = chomp;
+		self
+	}
+}
+impl<I> RecordParser<I>
+where
+	I: Read,
+{
+	/// The URL and content of the next record.
+	fn next_borrowed(&mut self) -> Result<Option<(Url, &str)>, io::Error> {
+		while self.records.state != RecordParserState::Done {
+			if let Some((url, range)) = self.records.parse()? {
+				return Ok(Some((url, self.records.content(range)?)));
+			}
+			let len = self.records.chomp_len()?;
+			let n = io::copy(&mut self.input.by_ref().take(len), &mut self.records.res)?;
+			if n == 0 {
+				self.records.finish()?;
+			}
+		}
+		Ok(None)
+	}
+}
+impl<I> RecordParser<I>
+where
+	I: AsyncRead,
+{
+	/// The URL and content of the next record.
+	fn poll_next_borrowed(
+		self: Pin<&mut Self>, cx: &mut Context,
+	) -> Poll<Result<Option<(Url, &str)>, io::Error>> {
+		let mut self_ = self.project();
+		let records = self_.records;
+		while records.state != RecordParserState::Done {
+			if let Some((url, range)) = records.parse()? {
+				return Poll::Ready(Ok(Some((url, records.content(range)?))));
+			}
+			let from = (&mut self_.input).take(records.chomp_len()?);
+			let copy = futures::io::copy(from, &mut records.res);
+			pin_mut!(copy);
+			let n = ready!(copy.poll(cx))?;
+			if n == 0 {
+				records.finish()?;
+			}
+		}
+		Poll::Ready(Ok(None))
+	}
+}
+impl Records {
+	/// Parse the next record of `type_` from the input read so far, returning
+	/// `None` if more is needed.
+	fn parse(&mut self) -> Result<Option<Next>, io::Error> {
+		loop {
+			let _ = self.res.splice(..self.offset, iter::empty());
+			self.offset = 0;
+			if self.res.is_empty() {
+				return Ok(None);
+			}
+			let record = match parser::record(&self.res) {
+				Ok((rem, record)) => {
+					let record_len = self.res.len() - rem.len() + 4; // 4 is \r\n\r\n
+					if record_len > self.res.len() {
+						return Ok(None);
+					}
+					self.offset = record_len;
+					record
+				}
+				Err(nom::Err::Incomplete(_)) => return Ok(None),
+				Err(_) => return Err(invalid_data("malformed WARC record")),
+			};
+			match self.state {
+				RecordParserState::Info => {
+					if record.type_ != parser::RecordType::WARCInfo {
+						return Err(invalid_data("expected a warcinfo record"));
+					}
+					self.state = RecordParserState::Record;
+				}
+				RecordParserState::Record => {
+					if record.type_ != self.type_ {
+						return Err(invalid_data(format!(
+							"expected a {:?} record, got {:?}",
+							self.type_, record.type_
+						)));
+					}
+					let url = record
+						.target_uri
+						.ok_or_else(|| invalid_data("record has no WARC-Target-URI"))?;
+					let url = Url::parse(url).map_err(invalid_data)?;
+					let content: *const u8 = record.content.as_ptr();
+					let buffer: *const u8 = self.res.as_slice().as_ptr();
+					let start = (content as usize) - (buffer as usize);
+					return Ok(Some((url, start..start + record.content.len())));
+				}
+				RecordParserState::Done => unreachable!(),
+			}
+		}
+	}
+	/// How much input to read next.
+	fn chomp_len(&self) -> Result<u64, io::Error> {
+		if self.res.len() >= BUF {
+			return Err(invalid_data(format!(
+				"individual record > configured BUF {:?}",
+				BUF
+			)));
+		}
+		Ok(self.chomp.min(BUF - self.res.len()) as u64)
+	}
+	/// The input has ended, which is only valid between records.
+	fn finish(&mut self) -> Result<(), io::Error> {
+		if !self.res.is_empty() || self.state == RecordParserState::Info {
+			return Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"input ended mid-record",
+			));
+		}
+		self.state = RecordParserState::Done;
+		Ok(())
+	}
+	fn content(&self, range: Range<usize>) -> Result<&str, io::Error> {
+		str::from_utf8(&self.res[range]).map_err(invalid_data)
+	}
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::{executor::block_on, StreamExt};
+	use std::io::Cursor;
+
+	use super::*;
+
+	fn record(type_: &str, headers: &str, content: &[u8]) -> Vec<u8> {
+		let mut record = format!(
+			"WARC/1.0\r\nWARC-Type: {}\r\n{}Content-Length: {}\r\n\r\n",
+			type_,
+			headers,
+			content.len()
+		)
+		.into_bytes();
+		record.extend_from_slice(content);
+		record.extend_from_slice(b"\r\n\r\n");
+		record
+	}
+
+	fn file(type_: &str, content_type: &str, records: &[(&str, &str)]) -> Vec<u8> {
+		let mut file = record("warcinfo", "", b"info");
+		for (url, content) in records {
+			let headers = format!(
+				"WARC-Target-URI: {}\r\nContent-Type: {}\r\n",
+				url, content_type
+			);
+			file.extend(record(type_, &headers, content.as_bytes()));
+		}
+		file
+	}
+
+	#[test]
+	fn wat() {
+		let records = [
+			(
+				"http://example.com/",
+				r#"{"Envelope":{"WARC-Header-Metadata":{}}}"#,
+			),
+			(
+				"http://example.com/a",
+				r#"{"Envelope":{"Payload-Metadata":{}}}"#,
+			),
+		];
+		let wat = file("metadata", "application/json", &records);
+
+		let parsed = WatParser::new(Cursor::new(&wat))
+			.chomp(16)
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+		assert_eq!(parsed.len(), 2);
+		for (record, (url, metadata)) in parsed.iter().zip(&records) {
+			assert_eq!(record.url.as_str(), *url);
+			assert_eq!(record.metadata, *metadata);
+		}
+
+		let streamed = block_on(
+			WatParser::new(futures::io::Cursor::new(&wat))
+				.map(Result::unwrap)
+				.collect::<Vec<_>>(),
+		);
+		assert_eq!(streamed, parsed);
+	}
+
+	#[test]
+	fn wet() {
+		let records = [
+			(
+				"http://example.com/",
+				"Example Domain\nThis domain is for use in examples.",
+			),
+			("http://example.org/é", "Ünïcödé text"),
+			("http://example.net/", ""),
+		];
+		let wet = file("conversion", "text/plain", &records);
+
+		let parsed = WetParser::new(Cursor::new(&wet))
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+		assert_eq!(parsed.len(), 3);
+		for (record, (url, text)) in parsed.iter().zip(&records) {
+			assert_eq!(record.url, Url::parse(url).unwrap());
+			assert_eq!(record.text, *text);
+		}
+
+		let streamed = block_on(
+			WetParser::new(futures::io::Cursor::new(&wet))
+				.chomp(7)
+				.map(Result::unwrap)
+				.collect::<Vec<_>>(),
+		);
+		assert_eq!(streamed, parsed);
+	}
+
+	#[test]
+	fn wet_invalid_utf8() {
+		let mut wet = record("warcinfo", "", b"info");
+		wet.extend(record(
+			"conversion",
+			"WARC-Target-URI: http://example.com/\r\n",
+			b"\xff\xfe",
+		));
+		let err = WetParser::new(Cursor::new(&wet))
+			.next()
+			.unwrap()
+			.unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn malformed() {
+		let next_err = |wet: &[u8]| {
+			let sync = WetParser::new(Cursor::new(wet))
+				.next()
+				.unwrap()
+				.unwrap_err();
+			let async_ = block_on(WetParser::new(futures::io::Cursor::new(wet)).next())
+				.unwrap()
+				.unwrap_err();
+			assert_eq!(sync.kind(), async_.kind());
+			sync.kind()
+		};
+		let info = record("warcinfo", "", b"info");
+
+		let not_warc = b"garbage\r\n\r\n".to_vec();
+		assert_eq!(next_err(&not_warc), io::ErrorKind::InvalidData);
+
+		let no_info = file("conversion", "text/plain", &[("http://example.com/", "a")])
+			[info.len()..]
+			.to_vec();
+		assert_eq!(next_err(&no_info), io::ErrorKind::InvalidData);
+
+		let wrong_type = [&info[..], &record("response", "", b"a")].concat();
+		assert_eq!(next_err(&wrong_type), io::ErrorKind::InvalidData);
+
+		let no_url = [&info[..], &record("conversion", "", b"a")].concat();
+		assert_eq!(next_err(&no_url), io::ErrorKind::InvalidData);
+
+		let bad_url = [
+			&info[..],
+			&record("conversion", "WARC-Target-URI: not a url\r\n", b"a"),
+		]
+		.concat();
+		assert_eq!(next_err(&bad_url), io::ErrorKind::InvalidData);
+
+		let mut truncated = file("conversion", "text/plain", &[("http://example.com/", "a")]);
+		truncated.truncate(truncated.len() - 3);
+		assert_eq!(next_err(&truncated), io::ErrorKind::UnexpectedEof);
+
+		assert_eq!(next_err(b""), io::ErrorKind::UnexpectedEof);
+	}
+}
@@ -27,25 +27,29 @@
 #![deny(unsafe_code)]
 
 mod commoncrawl;
+mod derived;
 mod parser;
 
-use async_compression::futures::bufread::GzipDecoder; // TODO: use stream or https://github.com/alexcrichton/flate2-rs/pull/214
 use futures::{io::BufReader, AsyncBufReadExt, FutureExt, Stream, StreamExt, TryStreamExt};
 use reqwest_resume::ClientExt;
 use serde_closure::FnMutNamed;
 use std::{io, time};
 
 use amadeus_core::{
-	into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::DistParStream, Source
+	file::{Codec, DecompressingReader}, into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::DistParStream, Source
 };
 use amadeus_types::Webpage;
 
-use commoncrawl::WarcParser;
+use commoncrawl::{WarcParser, CHOMP};
+
+pub use derived::{WatParser, WatRecord, WetParser, WetRecord};
 
 /// See https://commoncrawl.s3.amazonaws.com/crawl-data/index.html
 #[derive(Clone, Debug)]
 pub struct CommonCrawl {
 	urls: Vec<String>,
+	skip_payload: bool,
+	read_chunk_size: usize,
 }
 impl CommonCrawl {
 	/// CC-MAIN-2020-24
@@ -65,9 +69,8 @@ impl CommonCrawl {
 			.await?
 			.bytes_stream()
 			.map_err(|e| io::Error::new(io::ErrorKind::Other, e));
-		let body = BufReader::new(body.into_async_read());
-		let mut body = GzipDecoder::new(body); // Content-Encoding isn't set, so decode manually
-		body.multiple_members(true);
+		// Content-Encoding isn't set, so decode manually
+		let body = DecompressingReader::new(body.into_async_read(), Codec::Gzip);
 
 		let urls = BufReader::new(body)
 			.lines()
@@ -76,7 +79,31 @@ impl CommonCrawl {
 			})
 			.collect()
 			.await;
-		Ok(Self { urls })
+		Ok(Self {
+			urls,
+			skip_payload: false,
+			read_chunk_size: CHOMP,
+		})
+	}
+	/// Parse only the headers of each record, leaving [`Webpage::contents`] empty.
+	/// Each page's body is read past rather than buffered and copied out, for
+	/// when only its URL and IP address are needed.
+	#[must_use]
+	pub fn skip_payload(mut self) -> Self {
+		self.skip_payload = true;
+		self
+	}
+	/// Read each WARC file `read_chunk_size` bytes at a time, rather than the
+	/// default of 8 KiB. Larger reads mean fewer reads of the response body.
+	///
+	/// # Panics
+	///
+	/// Panics if `read_chunk_size` is zero.
+	#[must_use]
+	pub fn read_chunk_size(mut self, read_chunk_size: usize) -> Self {
+		assert_ne!(read_chunk_size, 0, "read_chunk_size must be non-zero");
+		self.read_chunk_size = read_chunk_size;
+		self
 	}
 }
 
@@ -86,17 +113,23 @@ type Output = std::pin::Pin<Box<dyn Stream<Item = Result<Webpage<'static>, io::E
 type Output = impl Stream<Item = Result<Webpage<'static>, io::Error>> + Send;
 
 FnMutNamed! {
-	pub type Closure<> = |self|url=> String| -> Output where {
+	pub type Closure<> = |self, skip_payload: bool, read_chunk_size: usize|url=> String| -> Output where {
+		let (skip_payload, read_chunk_size) = (self.skip_payload, self.read_chunk_size);
 		#[allow(clippy::let_and_return)]
 		let ret = async move {
 				let body = reqwest_resume::get(url.parse().unwrap()).await.unwrap();
 				let body = body
 					.bytes_stream()
 					.map_err(|e| io::Error::new(io::ErrorKind::Other, e));
-				let body = BufReader::new(body.into_async_read());
-				let mut body = GzipDecoder::new(body); // Content-Encoding isn't set, so decode manually
-				body.multiple_members(true);
-				WarcParser::new(body)
+				let body = BufReader::with_capacity(read_chunk_size, body.into_async_read());
+				// Content-Encoding isn't set, so decode manually
+				let body = DecompressingReader::new(body, Codec::Gzip);
+				let parser = WarcParser::new(body).chomp(read_chunk_size);
+				if skip_payload {
+					parser.skip_payload()
+				} else {
+					parser
+				}
 			}
 			.flatten_stream();
 		#[cfg(not(nightly))]
@@ -124,6 +157,8 @@ impl Source for CommonCrawl {
 	}
 	#[allow(clippy::let_and_return)]
 	fn dist_stream(self) -> Self::DistStream {
-		self.urls.into_dist_stream().flat_map(Closure::new())
+		self.urls
+			.into_dist_stream()
+			.flat_map(Closure::new(self.skip_payload, self.read_chunk_size))
 	}
 }
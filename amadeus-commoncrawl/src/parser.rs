@@ -4,7 +4,7 @@
 use nom::{complete, do_parse, many1, map_res, named, opt, space, tag, Err, IResult, Needed};
 use std::{fmt, str};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub(crate) enum RecordType {
 	WARCInfo,
 	Response,
@@ -155,28 +155,33 @@ named!(warc_header<&[u8], ((&str, &str), Vec<(&str,&str)>) >,
 /// ```
 #[inline(always)]
 pub(crate) fn record(input: &[u8]) -> IResult<&[u8], Record> {
+	record_header(input).and_then(|(i, (record, length))| {
+		if length <= i.len() {
+			let content = &i[0..length];
+			Ok((&i[length..], Record { content, ..record }))
+		} else {
+			Err(Err::Incomplete(Needed::Size(length - i.len())))
+		}
+	})
+}
+
+/// Parses the headers of one record, returning the input from the start of
+/// its content, and the record, with empty content, along with the length of
+/// its content.
+#[inline(always)]
+pub(crate) fn record_header(input: &[u8]) -> IResult<&[u8], (Record<'_>, usize)> {
 	// TODO if the stream parser does not get all the header it fails .
 	// like a default size of 10 doesnt for for a producer
-	warc_header(input).and_then(|(mut i, tuple_vec)| {
+	warc_header(input).and_then(|(i, tuple_vec)| {
 		let (_name, _version) = tuple_vec.0;
 		let headers = tuple_vec.1; // not need figure it out
-		let mut content = None;
-		let mut bytes_needed = 1;
+		let mut length = None;
 		let mut type_ = None;
 		let mut target_uri = None;
 		let mut ip_address = None;
 		for &(k, v) in &headers {
 			match k {
-				"Content-Length" => {
-					let length_number = v.parse::<usize>().unwrap();
-					if length_number <= i.len() {
-						content = Some(&i[0..length_number]);
-						i = &i[length_number..];
-						bytes_needed = 0;
-					} else {
-						bytes_needed = length_number - i.len();
-					}
-				}
+				"Content-Length" => length = Some(v.parse::<usize>().unwrap()),
 				"WARC-Type" => {
 					type_ = Some(v);
 				}
@@ -185,18 +190,18 @@ pub(crate) fn record(input: &[u8]) -> IResult<&[u8], Record> {
 				_ => (),
 			}
 		}
-		match content {
-			Some(content) => {
+		match length {
+			Some(length) => {
 				let entry = Record {
 					// headers: h,
 					type_: RecordType::parse(type_.unwrap()),
 					target_uri,
 					ip_address,
-					content,
+					content: &[],
 				};
-				Ok((i, entry))
+				Ok((i, (entry, length)))
 			}
-			None => Err(Err::Incomplete(Needed::Size(bytes_needed))),
+			None => Err(Err::Incomplete(Needed::Size(1))),
 		}
 	})
 }
@@ -2,7 +2,9 @@
 
 #![allow(clippy::type_complexity)]
 
+mod decompress;
 mod local;
+mod retry;
 
 use async_trait::async_trait;
 use futures::{future::LocalBoxFuture, ready};
@@ -14,9 +16,11 @@ use widestring::U16String;
 
 use crate::pool::ProcessSend;
 
+pub use decompress::{Codec, Decompress, DecompressError, DecompressingPage, DecompressingReader};
 pub use local::LocalFile;
+pub use retry::RetryingPage;
 
-const PAGE_SIZE: usize = 10 * 1024 * 1024; // `Reader` reads this many bytes at a time
+const PAGE_SIZE: usize = 10 * 1024 * 1024; // `Reader` reads up to this many bytes at a time by default
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct OsString {
@@ -278,6 +282,7 @@ where
 	#[pin]
 	pending: Option<LocalBoxFuture<'static, Result<Box<[u8]>, P::Error>>>,
 	offset: u64,
+	chunk_size: usize,
 }
 #[allow(clippy::len_without_is_empty)]
 impl<P> Reader<P>
@@ -289,8 +294,22 @@ where
 			page,
 			pending: None,
 			offset: 0,
+			chunk_size: PAGE_SIZE,
 		}
 	}
+	/// Read up to `chunk_size` bytes with each call to [`Page::read`], rather
+	/// than the default of 10 MiB. Larger chunks mean fewer requests to
+	/// high-latency pages like S3 objects.
+	///
+	/// # Panics
+	///
+	/// Panics if `chunk_size` is zero.
+	#[must_use]
+	pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+		assert_ne!(chunk_size, 0, "chunk_size must be non-zero");
+		self.chunk_size = chunk_size;
+		self
+	}
 }
 impl<P> futures::io::AsyncRead for Reader<P>
 where
@@ -303,7 +322,7 @@ where
 		if self_.pending.is_none() {
 			let start = *self_.offset;
 			let len = buf.len();
-			let len = len.min(PAGE_SIZE);
+			let len = len.min(*self_.chunk_size);
 			let pending = self_.page.read(start, len);
 			*self_.pending = Some(pending);
 		}
@@ -0,0 +1,446 @@
+use async_trait::async_trait;
+use flate2::write::MultiGzDecoder;
+use futures::{future::LocalBoxFuture, io::AsyncRead, lock::Mutex, ready};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	convert::TryFrom, error, fmt, io::{self, Write}, mem, pin::Pin, sync::Arc, task::{Context, Poll}
+};
+
+use super::{File, Page, Partition, PAGE_SIZE};
+use crate::util::IoError;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// The compressed bytes decompressed at a time, which bounds the decompressed
+/// bytes held in memory by the compression ratio.
+const INPUT_CHUNK: usize = 64 * 1024;
+
+/// The compression of the bytes of a [`Page`].
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Codec {
+	/// Uncompressed.
+	Identity,
+	/// Gzip, including files of several concatenated gzip members as WARC uses.
+	Gzip,
+	/// Zstandard, including files of several concatenated frames.
+	Zstd,
+	/// Detected from the leading magic bytes, falling back to [`Codec::Identity`].
+	Detect,
+}
+
+/// A [`File`] or [`Partition`] whose pages are wrapped in [`DecompressingPage`]s,
+/// so that sources built on it like `Json`, `Csv` and `Warc` see plaintext.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Decompress<T> {
+	inner: T,
+	codec: Codec,
+}
+impl<T> Decompress<T> {
+	pub fn new(inner: T, codec: Codec) -> Self {
+		Self { inner, codec }
+	}
+}
+#[async_trait(?Send)]
+impl<F> File for Decompress<F>
+where
+	F: File,
+{
+	type Partition = Decompress<F::Partition>;
+	type Error = F::Error;
+
+	async fn partitions(self) -> Result<Vec<Self::Partition>, Self::Error> {
+		let codec = self.codec;
+		Ok(self
+			.inner
+			.partitions()
+			.await?
+			.into_iter()
+			.map(|partition| Decompress::new(partition, codec))
+			.collect())
+	}
+}
+#[async_trait(?Send)]
+impl<P> Partition for Decompress<P>
+where
+	P: Partition,
+	P::Page: 'static,
+{
+	type Page = DecompressingPage<P::Page>;
+	type Error = P::Error;
+
+	async fn pages(self) -> Result<Vec<Self::Page>, Self::Error> {
+		let codec = self.codec;
+		Ok(self
+			.inner
+			.pages()
+			.await?
+			.into_iter()
+			.map(|page| DecompressingPage::new(page, codec))
+			.collect())
+	}
+}
+
+/// A read-only [`Page`] of the decompressed bytes of `page`.
+///
+/// Compressed streams can't be seeked into, so `page` is decompressed a chunk at
+/// a time as it's read through, such as by [`Page::reader`], and a read from
+/// before the last restarts decompression from the beginning. Only the chunk
+/// being decompressed is held in memory.
+pub struct DecompressingPage<P>
+where
+	P: Page,
+{
+	inner: Arc<Inner<P>>,
+}
+struct Inner<P>
+where
+	P: Page,
+{
+	page: P,
+	codec: Codec,
+	position: Mutex<Position>,
+	len: Mutex<Option<u64>>,
+}
+impl<P> DecompressingPage<P>
+where
+	P: Page,
+{
+	pub fn new(page: P, codec: Codec) -> Self {
+		Self {
+			inner: Arc::new(Inner {
+				page,
+				codec,
+				position: Mutex::new(Position::new(codec)),
+				len: Mutex::new(None),
+			}),
+		}
+	}
+}
+impl<P> Clone for DecompressingPage<P>
+where
+	P: Page,
+{
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone(),
+		}
+	}
+}
+impl<P> fmt::Debug for DecompressingPage<P>
+where
+	P: Page,
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DecompressingPage")
+			.field("codec", &self.inner.codec)
+			.finish()
+	}
+}
+impl<P> Page for DecompressingPage<P>
+where
+	P: Page + 'static,
+{
+	type Error = DecompressError<P::Error>;
+
+	/// Decompresses the whole of `page` the first time it's called, discarding
+	/// the output.
+	fn len(&self) -> LocalBoxFuture<'static, Result<u64, Self::Error>> {
+		let self_ = self.clone();
+		Box::pin(async move {
+			let mut len = self_.inner.len.lock().await;
+			if let Some(len) = *len {
+				return Ok(len);
+			}
+			let mut position = Position::new(self_.inner.codec);
+			while position.fill(&self_.inner.page).await? {
+				position.skip(u64::MAX);
+			}
+			*len = Some(position.offset);
+			Ok(position.offset)
+		})
+	}
+	fn read(
+		&self, offset: u64, len: usize,
+	) -> LocalBoxFuture<'static, Result<Box<[u8]>, Self::Error>> {
+		let self_ = self.clone();
+		Box::pin(async move {
+			let mut position = self_.inner.position.lock().await;
+			if position.offset > offset {
+				*position = Position::new(self_.inner.codec);
+			}
+			let mut ret = Vec::new();
+			while ret.len() < len {
+				position.skip(offset);
+				let output = position.decoding.output();
+				if output.is_empty() {
+					if !position.fill(&self_.inner.page).await? {
+						break;
+					}
+					continue;
+				}
+				let take = output.len().min(len - ret.len());
+				ret.extend_from_slice(&output[..take]);
+				position.consume(take);
+			}
+			Ok(ret.into_boxed_slice())
+		})
+	}
+	fn write(
+		&self, _offset: u64, _buf: Box<[u8]>,
+	) -> LocalBoxFuture<'static, Result<(), Self::Error>> {
+		let err = io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"can't write to a decompressing page",
+		);
+		Box::pin(async move { Err(DecompressError::Decompress(err.into())) })
+	}
+}
+
+/// How far through a [`DecompressingPage`] decompression has got.
+struct Position {
+	decoding: Decoding,
+	/// The last read of the page, and how much of it has been decompressed.
+	input: Box<[u8]>,
+	consumed: usize,
+	/// The offset into the page of the next read.
+	page_offset: u64,
+	/// The offset into the decompressed bytes of `decoding`'s output.
+	offset: u64,
+}
+impl Position {
+	fn new(codec: Codec) -> Self {
+		Self {
+			decoding: Decoding::new(codec),
+			input: Box::new([]),
+			consumed: 0,
+			page_offset: 0,
+			offset: 0,
+		}
+	}
+	/// Decompress more of `page`, returning `false` once it's all been
+	/// decompressed.
+	async fn fill<P: Page>(&mut self, page: &P) -> Result<bool, DecompressError<P::Error>> {
+		if self.decoding.is_finished() {
+			return Ok(false);
+		}
+		if self.consumed == self.input.len() {
+			self.input = page
+				.read(self.page_offset, PAGE_SIZE)
+				.await
+				.map_err(DecompressError::Page)?;
+			self.consumed = 0;
+			self.page_offset += u64::try_from(self.input.len()).unwrap();
+		}
+		self.consumed += self
+			.decoding
+			.push(&self.input[self.consumed..])
+			.map_err(|err| DecompressError::Decompress(err.into()))?;
+		Ok(true)
+	}
+	/// Discard any output before `offset`.
+	fn skip(&mut self, offset: u64) {
+		let skip = usize::try_from(offset.saturating_sub(self.offset))
+			.unwrap_or(usize::MAX)
+			.min(self.decoding.output().len());
+		self.consume(skip);
+	}
+	fn consume(&mut self, len: usize) {
+		self.decoding.consume(len);
+		self.offset += u64::try_from(len).unwrap();
+	}
+}
+
+/// An [`AsyncRead`] of the decompressed bytes of `reader`, such as a response
+/// body, decompressed a chunk at a time as it's read.
+#[pin_project]
+pub struct DecompressingReader<R> {
+	#[pin]
+	reader: R,
+	decoding: Decoding,
+	input: Box<[u8]>,
+	filled: usize,
+	consumed: usize,
+}
+impl<R> DecompressingReader<R> {
+	pub fn new(reader: R, codec: Codec) -> Self {
+		Self {
+			reader,
+			decoding: Decoding::new(codec),
+			input: vec![0; INPUT_CHUNK].into_boxed_slice(),
+			filled: 0,
+			consumed: 0,
+		}
+	}
+}
+impl<R> AsyncRead for DecompressingReader<R>
+where
+	R: AsyncRead,
+{
+	fn poll_read(
+		self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8],
+	) -> Poll<io::Result<usize>> {
+		let mut self_ = self.project();
+		loop {
+			let output = self_.decoding.output();
+			if !output.is_empty() || self_.decoding.is_finished() || buf.is_empty() {
+				let len = output.len().min(buf.len());
+				buf[..len].copy_from_slice(&output[..len]);
+				self_.decoding.consume(len);
+				return Poll::Ready(Ok(len));
+			}
+			if *self_.consumed == *self_.filled {
+				*self_.filled = ready!(self_.reader.as_mut().poll_read(cx, self_.input))?;
+				*self_.consumed = 0;
+			}
+			*self_.consumed += self_
+				.decoding
+				.push(&self_.input[*self_.consumed..*self_.filled])?;
+		}
+	}
+}
+
+/// The decompression of a stream pushed to it a chunk at a time.
+struct Decoding {
+	codec: Codec,
+	state: DecodingState,
+	output: Vec<u8>,
+	consumed: usize,
+}
+enum DecodingState {
+	/// Buffering the start of the input until there's enough to detect the
+	/// codec from.
+	Detecting(Vec<u8>),
+	Decoding(Decoder),
+	Finished,
+}
+impl Decoding {
+	fn new(codec: Codec) -> Self {
+		Self {
+			codec,
+			state: DecodingState::Detecting(Vec::new()),
+			output: Vec::new(),
+			consumed: 0,
+		}
+	}
+	/// Decompress up to [`INPUT_CHUNK`] bytes of `input`, returning how many
+	/// were consumed. An empty `input` marks the end of the stream.
+	fn push(&mut self, input: &[u8]) -> io::Result<usize> {
+		let _ = self.output.drain(..self.consumed);
+		self.consumed = 0;
+		let input = &input[..input.len().min(INPUT_CHUNK)];
+		match &mut self.state {
+			DecodingState::Detecting(start) => {
+				start.extend_from_slice(input);
+				if start.len() < ZSTD_MAGIC.len() && !input.is_empty() {
+					return Ok(input.len());
+				}
+				let start = mem::take(start);
+				if start.is_empty() {
+					// An empty stream is empty whatever the codec
+					self.state = DecodingState::Finished;
+					return Ok(0);
+				}
+				let mut decoder = Decoder::new(self.codec, &start)?;
+				decoder.write(&start, &mut self.output)?;
+				self.state = DecodingState::Decoding(decoder);
+				if !input.is_empty() {
+					return Ok(input.len());
+				}
+			}
+			DecodingState::Decoding(decoder) if !input.is_empty() => {
+				decoder.write(input, &mut self.output)?;
+				return Ok(input.len());
+			}
+			DecodingState::Decoding(_) | DecodingState::Finished => (),
+		}
+		if let DecodingState::Decoding(decoder) =
+			mem::replace(&mut self.state, DecodingState::Finished)
+		{
+			decoder.finish(&mut self.output)?;
+		}
+		Ok(0)
+	}
+	/// The decompressed bytes not yet consumed.
+	fn output(&self) -> &[u8] {
+		&self.output[self.consumed..]
+	}
+	fn consume(&mut self, len: usize) {
+		self.consumed += len;
+	}
+	fn is_finished(&self) -> bool {
+		matches!(self.state, DecodingState::Finished)
+	}
+}
+
+enum Decoder {
+	Identity,
+	Gzip(MultiGzDecoder<Vec<u8>>),
+	Zstd(zstd::stream::write::Decoder<Vec<u8>>),
+}
+impl Decoder {
+	fn new(codec: Codec, start: &[u8]) -> io::Result<Self> {
+		Ok(match codec {
+			Codec::Gzip => Self::Gzip(MultiGzDecoder::new(Vec::new())),
+			Codec::Zstd => Self::Zstd(zstd::stream::write::Decoder::new(Vec::new())?),
+			Codec::Detect if start.starts_with(&GZIP_MAGIC) => {
+				Self::Gzip(MultiGzDecoder::new(Vec::new()))
+			}
+			Codec::Detect if start.starts_with(&ZSTD_MAGIC) => {
+				Self::Zstd(zstd::stream::write::Decoder::new(Vec::new())?)
+			}
+			Codec::Identity | Codec::Detect => Self::Identity,
+		})
+	}
+	/// Decompress `input`, appending the output to `output`.
+	fn write(&mut self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+		match self {
+			Self::Identity => output.extend_from_slice(input),
+			Self::Gzip(decoder) => {
+				decoder.write_all(input)?;
+				decoder.flush()?;
+				output.append(decoder.get_mut());
+			}
+			Self::Zstd(decoder) => {
+				decoder.write_all(input)?;
+				decoder.flush()?;
+				output.append(decoder.get_mut());
+			}
+		}
+		Ok(())
+	}
+	fn finish(self, output: &mut Vec<u8>) -> io::Result<()> {
+		match self {
+			Self::Identity => (),
+			Self::Gzip(decoder) => output.append(&mut decoder.finish()?),
+			Self::Zstd(mut decoder) => {
+				decoder.flush()?;
+				output.append(decoder.get_mut());
+			}
+		}
+		Ok(())
+	}
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum DecompressError<E> {
+	Page(E),
+	Decompress(IoError),
+}
+impl<E: fmt::Display> fmt::Display for DecompressError<E> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Page(err) => err.fmt(f),
+			Self::Decompress(err) => write!(f, "error decompressing page: {}", err),
+		}
+	}
+}
+impl<E: error::Error> error::Error for DecompressError<E> {}
+impl<E: Into<io::Error>> From<DecompressError<E>> for io::Error {
+	fn from(err: DecompressError<E>) -> Self {
+		match err {
+			DecompressError::Page(err) => err.into(),
+			DecompressError::Decompress(err) => err.into(),
+		}
+	}
+}
@@ -0,0 +1,102 @@
+use futures::future::LocalBoxFuture;
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+use super::{Page, PAGE_SIZE};
+use crate::util;
+
+/// The longest [`RetryingPage`] waits between attempts, unless its `backoff` is
+/// longer.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// A [`Page`] that retries failed reads, backing off exponentially from `backoff`
+/// between attempts, up to an hour or `backoff` if that's longer.
+///
+/// Reads are issued in chunks; when a chunk fails, only it is retried, resuming
+/// after the data already read. `retries` bounds the consecutive failures
+/// tolerated without any progress being made.
+#[derive(Debug)]
+pub struct RetryingPage<P> {
+	page: Arc<P>,
+	retries: u32,
+	backoff: Duration,
+}
+impl<P> RetryingPage<P> {
+	pub fn new(page: P, retries: u32, backoff: Duration) -> Self {
+		Self {
+			page: Arc::new(page),
+			retries,
+			backoff,
+		}
+	}
+	/// The duration waited before retry number `attempt`, counting from zero.
+	pub fn backoff(&self, attempt: u32) -> Duration {
+		let max_backoff = self.backoff.max(MAX_BACKOFF);
+		self.backoff
+			.checked_mul(2_u32.pow(attempt.min(16)))
+			.map_or(max_backoff, |delay| delay.min(max_backoff))
+	}
+}
+impl<P> Clone for RetryingPage<P> {
+	fn clone(&self) -> Self {
+		Self {
+			page: self.page.clone(),
+			retries: self.retries,
+			backoff: self.backoff,
+		}
+	}
+}
+
+impl<P> Page for RetryingPage<P>
+where
+	P: Page + 'static,
+{
+	type Error = P::Error;
+
+	fn len(&self) -> LocalBoxFuture<'static, Result<u64, Self::Error>> {
+		let self_ = self.clone();
+		Box::pin(async move {
+			let mut attempt = 0;
+			loop {
+				match self_.page.len().await {
+					Ok(len) => break Ok(len),
+					Err(_) if attempt < self_.retries => {
+						util::delay(self_.backoff(attempt)).await;
+						attempt += 1;
+					}
+					Err(err) => break Err(err),
+				}
+			}
+		})
+	}
+	fn read(
+		&self, offset: u64, len: usize,
+	) -> LocalBoxFuture<'static, Result<Box<[u8]>, Self::Error>> {
+		let self_ = self.clone();
+		Box::pin(async move {
+			let mut buf = Vec::with_capacity(len);
+			let mut attempt = 0;
+			while buf.len() < len {
+				let chunk_offset = offset + u64::try_from(buf.len()).unwrap();
+				let chunk_len = (len - buf.len()).min(PAGE_SIZE);
+				match self_.page.read(chunk_offset, chunk_len).await {
+					Ok(chunk) if chunk.is_empty() => break,
+					Ok(chunk) => {
+						buf.extend_from_slice(&chunk);
+						attempt = 0;
+					}
+					Err(_) if attempt < self_.retries => {
+						util::delay(self_.backoff(attempt)).await;
+						attempt += 1;
+					}
+					Err(err) => return Err(err),
+				}
+			}
+			Ok(buf.into_boxed_slice())
+		})
+	}
+	fn write(
+		&self, offset: u64, buf: Box<[u8]>,
+	) -> LocalBoxFuture<'static, Result<(), Self::Error>> {
+		self.page.write(offset, buf)
+	}
+}
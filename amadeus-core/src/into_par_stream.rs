@@ -3,7 +3,8 @@ use crate::par_stream::{DistributedStream, ParallelStream, StreamTask};
 mod collections;
 mod iterator;
 mod slice;
-pub use self::{collections::*, iterator::*, slice::*};
+mod stream;
+pub use self::{collections::*, iterator::*, slice::*, stream::*};
 
 impl_par_dist_rename! {
 	pub trait IntoParallelStream {
@@ -8,7 +8,7 @@ use std::{
 use super::{
 	DistributedStream, IntoDistributedStream, IntoParallelStream, ParallelStream, StreamTask
 };
-use crate::pool::ProcessSend;
+use crate::{plan::Plan, pool::ProcessSend};
 
 pub trait IteratorExt: Iterator + Sized {
 	#[inline]
@@ -37,20 +37,68 @@ impl_par_dist_rename! {
 		fn size_hint(&self) -> (usize, Option<usize>) {
 			self.0.size_hint()
 		}
+		fn explain(&self) -> Plan {
+			let (lower, upper) = self.0.size_hint();
+			Plan::source::<Self>(Some(lower).filter(|_| upper == Some(lower)))
+		}
 		#[inline]
 		fn next_task(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<Self::Task>> {
 			Poll::Ready(self.0.next().map(IterStreamTask::new))
 		}
 	}
+
+	impl<I: Iterator> IterParStream<I> {
+		/// Hint the cost of processing each item with `f`, such as the size of
+		/// the partition it names, for pools that schedule
+		/// [largest first](crate::pool::Scheduling::LargestFirst).
+		#[inline]
+		pub fn cost_hint<F>(self, f: F) -> CostHint<Self, F>
+		where
+			F: FnMut(&I::Item) -> u64,
+		{
+			CostHint(self, f)
+		}
+	}
+
+	impl<I: Iterator, F> ParallelStream for CostHint<IterParStream<I>, F>
+	where
+		F: FnMut(&I::Item) -> u64,
+		I::Item: Send + 'static,
+	{
+		type Item = I::Item;
+		type Task = IterStreamTask<I::Item>;
+
+		#[inline]
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(self.0).0.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			let (lower, upper) = (self.0).0.size_hint();
+			Plan::source::<IterParStream<I>>(Some(lower).filter(|_| upper == Some(lower)))
+		}
+		#[inline]
+		fn next_task(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (stream, f) = (self_.0, self_.1);
+			Poll::Ready(stream.0.next().map(|item| {
+				let cost = f(&item);
+				IterStreamTask(Some(item), Some(cost))
+			}))
+		}
+	}
 }
 
+#[pin_project]
+#[must_use]
+pub struct CostHint<S, F>(S, F);
+
 #[pin_project]
 #[derive(Serialize, Deserialize)]
-pub struct IterStreamTask<T>(Option<T>);
+pub struct IterStreamTask<T>(Option<T>, Option<u64>);
 impl<T> IterStreamTask<T> {
 	#[inline]
 	fn new(t: T) -> Self {
-		Self(Some(t))
+		Self(Some(t), None)
 	}
 }
 
@@ -62,6 +110,10 @@ impl<T> StreamTask for IterStreamTask<T> {
 	fn into_async(self) -> Self::Async {
 		self
 	}
+	#[inline]
+	fn cost(&self) -> Option<u64> {
+		self.1
+	}
 }
 impl<T> Stream for IterStreamTask<T> {
 	type Item = T;
@@ -63,6 +63,9 @@ impl StreamTask for Never {
 	fn into_async(self) -> Self::Async {
 		self
 	}
+	fn cost(&self) -> Option<u64> {
+		match self.0 {}
+	}
 }
 impl Stream for Never {
 	type Item = Self;
@@ -0,0 +1,60 @@
+use futures::Stream;
+use pin_project::pin_project;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
+
+/// Wrap `stream` as a [`ParallelStream`] of a single partition, to feed an async
+/// producer such as a channel receiver or a websocket into a pipeline.
+///
+/// The stream is a single task, so it's processed by just one worker of the
+/// pool. As it can't be serialized, there's no equivalent for distributed
+/// streams.
+#[inline]
+pub fn from_stream<S: Stream>(stream: S) -> FromStream<S> {
+	FromStream(Some(stream))
+}
+
+#[pin_project]
+#[must_use]
+pub struct FromStream<S>(Option<S>);
+
+impl<S: Stream> ParallelStream for FromStream<S>
+where
+	S: Send + 'static,
+	S::Item: Send + 'static,
+{
+	type Item = S::Item;
+	type Task = FromStreamTask<S>;
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.0.as_ref().map_or((0, Some(0)), Stream::size_hint)
+	}
+	fn explain(&self) -> Plan {
+		Plan::source::<Self>(Some(usize::from(self.0.is_some())))
+	}
+	#[inline]
+	fn next_task(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<Self::Task>> {
+		Poll::Ready(self.project().0.take().map(FromStreamTask))
+	}
+}
+
+pub struct FromStreamTask<S>(S);
+
+impl<S: Stream> StreamTask for FromStreamTask<S> {
+	type Item = S::Item;
+	type Async = S;
+
+	#[inline]
+	fn into_async(self) -> Self::Async {
+		self.0
+	}
+	#[inline]
+	fn cost(&self) -> Option<u64> {
+		self.0.size_hint().1.map(|len| len as u64)
+	}
+}
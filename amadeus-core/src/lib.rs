@@ -98,6 +98,7 @@ pub mod par_pipe;
 pub mod par_sink;
 pub mod par_stream;
 pub mod pipe;
+pub mod plan;
 pub mod pool;
 mod source;
 pub mod util;
@@ -1,12 +1,12 @@
 #![allow(unused_qualifications)]
 
 use either::Either;
-use futures::Stream;
+use futures::{Future, Stream};
 use serde_closure::traits;
-use std::{cmp::Ordering, hash::Hash, iter, ops};
+use std::{cmp::Ordering, hash::Hash, io, iter, ops, time::Duration};
 
 use super::{par_sink::*, par_stream::*};
-use crate::{pipe::Pipe, pool::ProcessSend};
+use crate::{file::Page, pipe::Pipe, pool::ProcessSend};
 
 #[must_use]
 pub trait PipeTask<Input> {
@@ -35,6 +35,26 @@ macro_rules! pipe {
 				$assert_pipe(Inspect::new(self, f))
 			}
 
+			#[inline]
+			fn inspect_err<F, T, E>(self, f: F) -> InspectErr<Self, F>
+			where
+				F: $fns::FnMut(&E) + Clone + $send + 'static,
+				Self: $pipe<Input, Output = Result<T, E>> + Sized,
+			{
+				$assert_pipe(InspectErr::new(self, f))
+			}
+
+			#[inline]
+			fn tee<F, W, E>(self, writer: F, encoder: E) -> Tee<Self, F, E>
+			where
+				F: $fns::FnMut() -> W + Clone + $send + 'static,
+				W: io::Write,
+				E: for<'a> Encoder<&'a Self::Output> + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_pipe(Tee::new(self, writer, encoder))
+			}
+
 			#[inline]
 			fn update<F>(self, f: F) -> Update<Self, F>
 			where
@@ -53,6 +73,45 @@ macro_rules! pipe {
 				$assert_pipe(Map::new(self, f))
 			}
 
+			/// Run the async work `f` returns for each item, yielding its output.
+			/// Items are processed one at a time within each partition; see
+			/// [`map_async_buffered`](Self::map_async_buffered) to overlap them.
+			#[inline]
+			fn map_async<Fut, F>(self, f: F) -> MapAsync<Self, F>
+			where
+				F: $fns::FnMut(Self::Output) -> Fut + Clone + $send + 'static,
+				Fut: Future,
+				Self: Sized,
+			{
+				$assert_pipe(MapAsync::new(self, f, 1))
+			}
+
+			/// Like [`map_async`](Self::map_async), but running the work of up to
+			/// `concurrency` items at once, while still yielding their outputs in
+			/// order within each partition.
+			#[inline]
+			fn map_async_buffered<Fut, F>(self, f: F, concurrency: usize) -> MapAsync<Self, F>
+			where
+				F: $fns::FnMut(Self::Output) -> Fut + Clone + $send + 'static,
+				Fut: Future,
+				Self: Sized,
+			{
+				assert_ne!(concurrency, 0, "concurrency must be nonzero");
+				$assert_pipe(MapAsync::new(self, f, concurrency))
+			}
+
+			/// Derive a value from a borrow of each item, such as a key to group
+			/// by, yielding it alongside the item, which is neither cloned nor
+			/// consumed.
+			#[inline]
+			fn map_ref<B, F>(self, f: F) -> MapRef<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> B + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_pipe(MapRef::new(self, f))
+			}
+
 			#[inline]
 			fn flat_map<B, F>(self, f: F) -> FlatMap<Self, F>
 			where
@@ -63,6 +122,85 @@ macro_rules! pipe {
 				$assert_pipe(FlatMap::new(self, f))
 			}
 
+			/// Like [`flat_map`](Self::flat_map), but polling the streams of up to
+			/// `concurrency` items at once, while still yielding their items in
+			/// order within each partition. Each stream buffers at most 1024 items
+			/// while those before it are yielded, after which it isn't polled until
+			/// it's the oldest.
+			#[inline]
+			fn flat_map_buffered_ordered<B, F>(self, f: F, concurrency: usize) -> FlatMapBufferedOrdered<Self, F>
+			where
+				F: $fns::FnMut(Self::Output) -> B + Clone + $send + 'static,
+				B: Stream,
+				Self: Sized,
+			{
+				assert_ne!(concurrency, 0, "concurrency must be nonzero");
+				$assert_pipe(FlatMapBufferedOrdered::new(self, f, concurrency))
+			}
+
+			/// Like [`flat_map`](Self::flat_map), but with `f` returning an iterator,
+			/// and each output tagged with the index of the item it came from
+			/// within its partition. The outputs of each item are adjacent, so
+			/// they can be reassembled into per-item groups downstream.
+			#[inline]
+			fn flat_map_ordered_iter<B, F>(self, f: F) -> FlatMapOrderedIter<Self, F>
+			where
+				F: $fns::FnMut(Self::Output) -> B + Clone + $send + 'static,
+				B: IntoIterator,
+				Self: Sized,
+			{
+				$assert_pipe(FlatMapOrderedIter::new(self, f))
+			}
+
+			#[inline]
+			fn rate_limit(self, per_second: f64) -> RateLimit<Self>
+			where
+				Self: Sized,
+			{
+				$assert_pipe(RateLimit::new(self, per_second))
+			}
+
+			#[inline]
+			fn timeout(self, duration: Duration, policy: TimeoutPolicy) -> Timeout<Self>
+			where
+				Self::Output: Future,
+				Self: Sized,
+			{
+				$assert_pipe(Timeout::new(self, duration, policy))
+			}
+
+			#[inline]
+			fn retry<F, Fut, T, E>(self, attempts: u32, backoff: Backoff, f: F) -> Retry<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> Fut + Clone + $send + 'static,
+				Fut: Future<Output = Result<T, E>>,
+				Self: Sized,
+			{
+				assert_ne!(attempts, 0, "attempts must be nonzero");
+				$assert_pipe(Retry::new(self, f, attempts, backoff))
+			}
+
+			#[inline]
+			fn catch_panics(self, policy: PanicPolicy) -> CatchPanics<Self>
+			where
+				Self: Sized,
+			{
+				$assert_pipe(CatchPanics::new(self, policy))
+			}
+
+			#[inline]
+			fn window_by<F, K, V>(self, timestamp: F, size: Duration, slide: Duration) -> WindowBy<Self, F>
+			where
+				F: $fns::FnMut(&V) -> Duration + Clone + $send + 'static,
+				K: Clone,
+				V: Clone,
+				Self: $pipe<Input, Output = (K, V)> + Sized,
+			{
+				assert_ne!(size, Duration::from_secs(0), "size must be non-zero");
+				assert_ne!(slide, Duration::from_secs(0), "slide must be non-zero");
+				$assert_pipe(WindowBy::new(self, timestamp, size, slide))
+			}
+
 			#[inline]
 			fn filter<F>(self, f: F) -> Filter<Self, F>
 			where
@@ -72,6 +210,91 @@ macro_rules! pipe {
 				$assert_pipe(Filter::new(self, f))
 			}
 
+			/// Drop the repeats of each item, keeping its first occurrence where it
+			/// was. Unlike [`distinct_by`](Self::distinct_by) this preserves order,
+			/// but only within each partition.
+			///
+			/// The items seen so far are remembered for the duration of each
+			/// partition, so an item repeated across partitions is kept once in
+			/// each.
+			#[inline]
+			fn unique_ordered(self) -> UniqueOrdered<Self>
+			where
+				Self::Output: Eq + Hash + Clone,
+				Self: Sized,
+			{
+				$assert_pipe(UniqueOrdered::new(self))
+			}
+
+			/// Keep each item independently with probability `p`. Each partition
+			/// draws from an RNG seeded from `seed`, so the selection is
+			/// reproducible given the same seed and partitioning.
+			#[inline]
+			fn sample_fraction(self, p: f64, seed: u64) -> SampleFraction<Self>
+			where
+				Self: Sized,
+			{
+				assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+				$assert_pipe(SampleFraction::new(self, p, seed))
+			}
+
+			/// Yield every `step`th item of each partition, starting with the first.
+			#[inline]
+			fn step_by(self, step: usize) -> StepBy<Self>
+			where
+				Self: Sized,
+			{
+				assert_ne!(step, 0, "step must be non-zero");
+				$assert_pipe(StepBy::new(self, step))
+			}
+
+			#[inline]
+			fn sample_per_interval<F>(
+				self, timestamp: F, interval: Duration, policy: SamplePolicy,
+			) -> SamplePerInterval<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> Duration + Clone + $send + 'static,
+				Self: Sized,
+			{
+				assert_ne!(interval, Duration::from_secs(0), "interval must be non-zero");
+				$assert_pipe(SamplePerInterval::new(self, timestamp, interval, policy))
+			}
+
+			/// Group consecutive items of each partition for which `f` returns equal
+			/// keys, yielding each maximal run along with its key.
+			#[inline]
+			fn chunk_by<K, F>(self, f: F) -> ChunkBy<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> K + Clone + $send + 'static,
+				K: PartialEq,
+				Self: Sized,
+			{
+				$assert_pipe(ChunkBy::new(self, f))
+			}
+
+			/// Group consecutive items of each partition into batches whose sizes,
+			/// as estimated by `size`, sum to no more than `max_bytes`, such as to
+			/// bound the size of output files or request payloads. An item larger
+			/// than `max_bytes` forms a batch of its own.
+			#[inline]
+			fn batch_by_bytes<F>(self, max_bytes: usize, size: F) -> BatchByBytes<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> usize + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_pipe(BatchByBytes::new(self, max_bytes, size))
+			}
+
+			/// Stop polling each partition once it has finished, returning `None`
+			/// thereafter rather than relying on it tolerating being polled again.
+			#[inline]
+			fn fuse(self) -> Fuse<Self>
+			where
+				Self: Sized,
+			{
+				$assert_pipe(Fuse::new(self))
+			}
+
 			#[inline]
 			fn cloned<'a, T>(self) -> Cloned<Self, T, Input>
 			where
@@ -134,6 +357,20 @@ macro_rules! pipe {
 				$assert_sink(Fork::new(self, sink, sink_ref))
 			}
 
+			/// Map each item with `f`, feeding the `Ok`s into `sink` and collecting
+			/// the `Err`s, rather than stopping at the first error. Completes to
+			/// the output of `sink` along with all the errors.
+			#[inline]
+			fn map_with_errors<F, T, E, S>(self, f: F, sink: S) -> MapWithErrors<Self, F, S>
+			where
+				F: $fns::FnMut(Self::Output) -> Result<T, E> + Clone + $send + 'static,
+				S: $sink<T>,
+				E: $send + 'static,
+				Self: Sized,
+			{
+				$assert_sink(MapWithErrors::new(self, f, sink))
+			}
+
 			#[inline]
 			fn for_each<F>(self, f: F) -> ForEach<Self, F>
 			where
@@ -143,6 +380,27 @@ macro_rules! pipe {
 				$assert_sink(ForEach::new(self, f))
 			}
 
+			#[inline]
+			fn for_each_batched<F>(self, batch_size: usize, f: F) -> ForEachBatched<Self, F>
+			where
+				F: $fns::FnMut(Vec<Self::Output>) + Clone + $send + 'static,
+				Self::Output: 'static,
+				Self: Sized,
+			{
+				assert_ne!(batch_size, 0, "batch_size must be non-zero");
+				$assert_sink(ForEachBatched::new(self, batch_size, f))
+			}
+
+			#[inline]
+			fn write_page<Pg, E>(self, page: Pg, encoder: E) -> WritePage<Self, Pg, E>
+			where
+				Pg: Page + 'static,
+				E: Encoder<Self::Output> + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_sink(WritePage::new(self, page, encoder))
+			}
+
 			#[inline]
 			fn fold<ID, F, B>(self, identity: ID, op: F) -> Fold<Self, ID, F, B>
 			where
@@ -154,6 +412,20 @@ macro_rules! pipe {
 				$assert_sink(Fold::new(self, identity, op))
 			}
 
+			/// Fold each partition separately, returning one value per partition
+			/// rather than combining them.
+			#[inline]
+			fn fold_partitions<ID, F, B>(self, identity: ID, op: F) -> FoldPartitions<Self, ID, F, B>
+			where
+				ID: $fns::FnMut() -> B + Clone + $send + 'static,
+				F: $fns::FnMut(B, Self::Output) -> B + Clone + $send + 'static,
+				B: $send + 'static,
+				Self::Output: 'static,
+				Self: Sized,
+			{
+				$assert_sink(FoldPartitions::new(self, identity, op))
+			}
+
 			#[inline]
 			fn group_by<S, A, B>(self, sink: S) -> GroupBy<Self, S>
 			where
@@ -165,7 +437,50 @@ macro_rules! pipe {
 				S::Done: $send + 'static,
 				Self: $pipe<Input, Output = (A, B)> + Sized,
 			{
-				$assert_sink(GroupBy::new(self, sink))
+				$assert_sink(GroupBy::new(self, sink, RandomHasher))
+			}
+
+			/// Like [`group_by`](Self::group_by), but grouping with `hasher` rather
+			/// than a randomly-seeded one, such as a [`SeededHasher`] to harden
+			/// against clustered or adversarial keys reproducibly.
+			#[inline]
+			fn group_by_with_hasher<S, H, A, B>(self, sink: S, hasher: H) -> GroupBy<Self, S, H>
+			where
+				A: Eq + Hash + $send + 'static,
+				H: MakeHasher + Clone + $send + 'static,
+				H::Hasher: Send + 'static,
+				S: $sink<B>,
+				<S::Pipe as $pipe<B>>::Task: Clone + $send + 'static,
+				S::ReduceA: 'static,
+				S::ReduceC: Clone,
+				S::Done: $send + 'static,
+				Self: $pipe<Input, Output = (A, B)> + Sized,
+			{
+				$assert_sink(GroupBy::new(self, sink, hasher))
+			}
+
+			/// Aggregate the values of each key per time window with `sink`. See
+			/// [`window_by`](Self::window_by) for how windows are assigned.
+			#[inline]
+			fn group_by_window<F, S, K, V>(
+				self, timestamp: F, size: Duration, slide: Duration, sink: S,
+			) -> GroupBy<WindowBy<Self, F>, S>
+			where
+				F: $fns::FnMut(&V) -> Duration + Clone + $send + 'static,
+				K: Clone + Eq + Hash + $send + 'static,
+				V: Clone,
+				S: $sink<V>,
+				<S::Pipe as $pipe<V>>::Task: Clone + $send + 'static,
+				S::ReduceA: 'static,
+				S::ReduceC: Clone,
+				S::Done: $send + 'static,
+				Self: $pipe<Input, Output = (K, V)> + Sized,
+			{
+				$assert_sink(GroupBy::new(
+					self.window_by(timestamp, size, slide),
+					sink,
+					RandomHasher,
+				))
 			}
 
 			#[inline]
@@ -195,6 +510,15 @@ macro_rules! pipe {
 				$assert_sink(Count::new(self))
 			}
 
+			#[inline]
+			fn count_where<F>(self, f: F) -> CountWhere<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> bool + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_sink(CountWhere::new(self, f))
+			}
+
 			#[inline]
 			fn sum<B>(self) -> Sum<Self, B>
 			where
@@ -220,6 +544,24 @@ macro_rules! pipe {
 				$assert_sink(StdDev::new(self))
 			}
 
+			#[inline]
+			fn ewma(self, alpha: f64) -> Ewma<Self>
+			where
+				Self: $pipe<Input, Output = f64> + Sized,
+			{
+				assert!(0.0 < alpha && alpha <= 1.0, "alpha must be in (0, 1]");
+				$assert_sink(Ewma::new(self, alpha))
+			}
+
+			#[inline]
+			fn ewma_partitions(self, alpha: f64) -> EwmaPartitions<Self>
+			where
+				Self: $pipe<Input, Output = f64> + Sized,
+			{
+				assert!(0.0 < alpha && alpha <= 1.0, "alpha must be in (0, 1]");
+				$assert_sink(EwmaPartitions::new(self, alpha))
+			}
+
 			#[inline]
 			fn combine<F>(self, f: F) -> Combine<Self, F>
 			where
@@ -296,7 +638,44 @@ macro_rules! pipe {
 				Self::Output: Hash + Eq + Clone + $send + 'static,
 				Self: Sized,
 			{
-				$assert_sink(MostFrequent::new(self, n, probability, tolerance))
+				$assert_sink(MostFrequent::new(self, n, probability, tolerance, usize::MAX))
+			}
+
+			#[inline]
+			fn most_frequent_bounded(
+				self, n: usize, probability: f64, tolerance: f64, max_counters: usize,
+			) -> MostFrequent<Self>
+			where
+				Self::Output: Hash + Eq + Clone + $send + 'static,
+				Self: Sized,
+			{
+				assert!(max_counters >= 2, "max_counters must be at least 2");
+				$assert_sink(MostFrequent::new(self, n, probability, tolerance, max_counters))
+			}
+
+			#[inline]
+			fn top_n_per_key<K, S, A, B>(self, n: usize, key: K, score: S) -> TopNPerKey<Self, K, S>
+			where
+				K: $fns::FnMut(&Self::Output) -> A + Clone + $send + 'static,
+				S: $fns::FnMut(&Self::Output) -> B + Clone + $send + 'static,
+				A: Eq + Hash + $send + 'static,
+				B: Ord + $send + 'static,
+				Self::Output: $send + 'static,
+				Self: Sized,
+			{
+				assert_ne!(n, 0, "n must be non-zero");
+				$assert_sink(TopNPerKey::new(self, n, key, score))
+			}
+
+			#[inline]
+			fn reduce_by_key<F, A, B>(self, f: F) -> ReduceByKey<Self, F>
+			where
+				F: $fns::FnMut(B, B) -> B + Clone + $send + 'static,
+				A: Eq + Hash + $send + 'static,
+				B: $send + 'static,
+				Self: $pipe<Input, Output = (A, B)> + Sized,
+			{
+				$assert_sink(ReduceByKey::new(self, f))
 			}
 
 			#[inline]
@@ -317,6 +696,51 @@ macro_rules! pipe {
 				))
 			}
 
+			#[inline]
+			fn distinct_by<F, K>(self, f: F) -> DistinctBy<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> K + Clone + $send + 'static,
+				K: Eq + Hash + $send + 'static,
+				Self::Output: $send + 'static,
+				Self: Sized,
+			{
+				$assert_sink(DistinctBy::new(self, f))
+			}
+
+			#[inline]
+			fn distinct_count_by<A, B>(self) -> DistinctCountBy<Self>
+			where
+				Self: $pipe<Input, Output = (A, B)> + Sized,
+				A: Eq + Hash + $send + 'static,
+				B: Eq + Hash + $send + 'static,
+			{
+				$assert_sink(DistinctCountBy::new(self))
+			}
+
+			#[inline]
+			fn collect_bounded(self, max_items: usize) -> CollectBounded<Self>
+			where
+				Self::Output: $send + 'static,
+				Self: Sized,
+			{
+				$assert_sink(CollectBounded::new(self, max_items))
+			}
+
+			#[inline]
+			fn distinct_approx<F, I>(
+				self, f: F, bands: usize, rows: usize,
+			) -> DistinctApprox<Self, F>
+			where
+				F: $fns::FnMut(&Self::Output) -> I + Clone + $send + 'static,
+				I: IntoIterator,
+				I::Item: Hash + 'static,
+				Self::Output: $send + 'static,
+				Self: Sized,
+			{
+				assert!(bands != 0 && rows != 0, "bands and rows must be non-zero");
+				$assert_sink(DistinctApprox::new(self, f, bands, rows))
+			}
+
 			#[inline]
 			fn sample_unstable(self, samples: usize) -> SampleUnstable<Self>
 			where
@@ -1,28 +1,42 @@
 mod all;
 mod any;
 mod collect;
+mod collect_bounded;
 mod combine;
 mod combiner;
 mod count;
+mod distinct_approx;
+mod distinct_by;
+mod distinct_count;
+mod ewma;
 mod fold;
 mod folder;
 mod for_each;
 mod fork;
 mod group_by;
+mod group_by_snapshots;
 mod histogram;
+mod map_with_errors;
 mod max;
 mod mean;
 mod pipe;
+mod reduce_by_key;
+mod reduce_tree;
 mod sample;
+mod scan_global;
+mod sorted;
 mod stddev;
 mod sum;
+mod top_n_per_key;
 mod tuple;
+mod write_page;
+mod write_partitioned;
 
 use super::par_pipe::*;
 use crate::{pipe::Sink, pool::ProcessSend};
 
 pub use self::{
-	all::*, any::*, collect::*, combine::*, combiner::*, count::*, fold::*, folder::*, for_each::*, fork::*, group_by::*, histogram::*, max::*, mean::*, pipe::*, sample::*, stddev::*, sum::*, tuple::*
+	all::*, any::*, collect::*, collect_bounded::*, combine::*, combiner::*, count::*, distinct_approx::*, distinct_by::*, distinct_count::*, ewma::*, fold::*, folder::*, for_each::*, fork::*, group_by::*, group_by_snapshots::*, histogram::*, map_with_errors::*, max::*, mean::*, pipe::*, reduce_by_key::*, reduce_tree::*, sample::*, scan_global::*, sorted::*, stddev::*, sum::*, top_n_per_key::*, tuple::*, write_page::*, write_partitioned::*
 };
 
 #[must_use]
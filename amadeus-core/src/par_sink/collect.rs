@@ -155,6 +155,34 @@ impl<Item: IntoIterator<Item = B>, T: Extend<B>, B> Sink<Item> for ExtendReducer
 	}
 }
 
+/// Extends a borrowed collection with each item, rather than a new one, for
+/// [`collect_into`](crate::par_stream::ParallelStream::collect_into).
+#[derive(new)]
+pub struct ExtendIntoReducer<'a, T>(&'a mut T);
+impl<'a, Item: IntoIterator<Item = B>, T: Extend<B>, B> Reducer<Item> for ExtendIntoReducer<'a, T> {
+	type Done = ();
+	type Async = ExtendIntoReducerAsync<'a, T>;
+
+	fn into_async(self) -> Self::Async {
+		ExtendIntoReducerAsync(self.0)
+	}
+}
+
+pub struct ExtendIntoReducerAsync<'a, T>(&'a mut T);
+impl<Item: IntoIterator<Item = B>, T: Extend<B>, B> Sink<Item> for ExtendIntoReducerAsync<'_, T> {
+	type Done = ();
+
+	#[inline]
+	fn poll_forward(
+		mut self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Item>>,
+	) -> Poll<Self::Done> {
+		while let Some(item) = ready!(stream.as_mut().poll_next(cx)) {
+			self.0.extend(item);
+		}
+		Poll::Ready(())
+	}
+}
+
 #[derive(Educe, Serialize, Deserialize)]
 #[educe(Clone(bound = "R: Clone"), Default(bound = "R: Default"))]
 #[serde(
@@ -0,0 +1,102 @@
+use derive_new::new;
+use educe::Educe;
+use serde::{Deserialize, Serialize};
+use std::{error, fmt, marker::PhantomData};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// Collect items into a `Vec`, or fail with [`LimitExceeded`] if there are
+/// more than `max_items`, rather than exhausting memory on an unexpectedly
+/// large result.
+///
+/// Each reducer stops buffering once its own items pass the limit, so it holds
+/// no more than `max_items`, and the limit is checked again against the total
+/// as their results are merged.
+#[derive(new)]
+#[must_use]
+pub struct CollectBounded<P> {
+	pipe: P,
+	max_items: usize,
+}
+
+/// The error returned by [`collect_bounded`](crate::par_stream::ParallelStream::collect_bounded)
+/// when there are more items than the limit.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct LimitExceeded {
+	pub max_items: usize,
+}
+impl error::Error for LimitExceeded {}
+impl fmt::Display for LimitExceeded {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "more than {} items collected", self.max_items)
+	}
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item>, Item> ParallelSink<Item> for CollectBounded<P>
+	where
+		P::Output: Send + 'static,
+	{
+		folder_par_sink!(
+			CollectBoundedFolder<StepA>,
+			CollectBoundedFolder<StepB>,
+			self,
+			CollectBoundedFolder::new(self.max_items),
+			CollectBoundedFolder::new(self.max_items)
+		);
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone)]
+pub struct CollectBoundedFolder<Step> {
+	max_items: usize,
+	#[new(default)]
+	marker: PhantomData<fn() -> Step>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+impl<T> FolderSync<T> for CollectBoundedFolder<StepA> {
+	type State = Option<Vec<T>>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		Some(Vec::new())
+	}
+	fn push(&mut self, state: &mut Self::State, item: T) {
+		if let Some(items) = state {
+			if items.len() == self.max_items {
+				*state = None;
+			} else {
+				items.push(item);
+			}
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<T> FolderSync<Option<Vec<T>>> for CollectBoundedFolder<StepB> {
+	type State = Option<Vec<T>>;
+	type Done = Result<Vec<T>, LimitExceeded>;
+
+	fn zero(&mut self) -> Self::State {
+		Some(Vec::new())
+	}
+	fn push(&mut self, state: &mut Self::State, item: Option<Vec<T>>) {
+		*state = match (state.take(), item) {
+			(Some(mut items), Some(item)) if items.len() + item.len() <= self.max_items => {
+				items.extend(item);
+				Some(items)
+			}
+			_ => None,
+		};
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state.ok_or(LimitExceeded {
+			max_items: self.max_items,
+		})
+	}
+}
@@ -1,5 +1,6 @@
 use derive_new::new;
 use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
 
 use super::{
 	folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink, SumFolder
@@ -43,3 +44,54 @@ impl<Item> FolderSync<Item> for CountFolder {
 		state
 	}
 }
+
+/// Count the items for which `f` returns `true`, like `filter(f).count()`.
+#[derive(new)]
+#[must_use]
+pub struct CountWhere<P, F> {
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item>, Item, F> ParallelSink<Item> for CountWhere<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = bool> + Clone + Send + 'static,
+	{
+		folder_par_sink!(
+			CountWhereFolder<F>,
+			SumFolder<usize>,
+			self,
+			CountWhereFolder::new(self.f),
+			SumFolder::new()
+		);
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, new)]
+pub struct CountWhereFolder<F> {
+	f: F,
+}
+
+impl<F, Item> FolderSync<Item> for CountWhereFolder<F>
+where
+	F: for<'a> FnMut<(&'a Item,), Output = bool>,
+{
+	type State = usize;
+	type Done = Self::State;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		0
+	}
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, item: Item) {
+		if self.f.call_mut((&item,)) {
+			*state += 1;
+		}
+	}
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
@@ -0,0 +1,141 @@
+#![allow(clippy::type_complexity)]
+
+use derive_new::new;
+use educe::Educe;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{collections::HashMap, hash::Hash, marker::PhantomData, mem};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+use amadeus_streaming::MinHash;
+
+/// Group items into candidate near-duplicates by locality-sensitive hashing of
+/// the [`MinHash`] signatures of the features `f` returns for each, such as the
+/// shingles of a document.
+///
+/// Each item's signature has `bands * rows` hash functions, and two items are
+/// candidates if any band of `rows` of them matches, which happens with
+/// probability `1 - (1 - s^rows)^bands` for items with Jaccard similarity `s`.
+/// Groups are the transitive closure of candidate pairs, so every item is in
+/// exactly one group, with unique items, and items without any features, in
+/// groups of their own. Items are held in memory along with their signatures
+/// until all partitions are merged.
+#[derive(new)]
+#[must_use]
+pub struct DistinctApprox<P, F> {
+	pipe: P,
+	f: F,
+	bands: usize,
+	rows: usize,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item>, Item, F, I> ParallelSink<Item> for DistinctApprox<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = I> + Clone + Send + 'static,
+		I: IntoIterator,
+		I::Item: Hash + 'static,
+		P::Output: Send + 'static,
+	{
+		folder_par_sink!(
+			DistinctApproxFolder<F, I::Item, StepA>,
+			DistinctApproxFolder<(), I::Item, StepB>,
+			self,
+			DistinctApproxFolder::new(self.f, self.bands, self.rows),
+			DistinctApproxFolder::new((), self.bands, self.rows)
+		);
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "F: Clone"))]
+#[serde(
+	bound(serialize = "F: Serialize"),
+	bound(deserialize = "F: Deserialize<'de>")
+)]
+pub struct DistinctApproxFolder<F, V, Step> {
+	f: F,
+	bands: usize,
+	rows: usize,
+	#[new(default)]
+	marker: PhantomData<fn() -> (V, Step)>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+impl<F, I, T> FolderSync<T> for DistinctApproxFolder<F, I::Item, StepA>
+where
+	F: for<'a> FnMut<(&'a T,), Output = I>,
+	I: IntoIterator,
+	I::Item: Hash,
+{
+	type State = Vec<(T, MinHash<I::Item>)>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: T) {
+		let mut signature = MinHash::new(self.bands * self.rows);
+		for feature in self.f.call_mut((&item,)) {
+			signature.push(&feature);
+		}
+		state.push((item, signature));
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<V, T> FolderSync<Vec<(T, MinHash<V>)>> for DistinctApproxFolder<(), V, StepB>
+where
+	V: Hash,
+{
+	type State = Vec<(T, MinHash<V>)>;
+	type Done = Vec<Vec<T>>;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, mut item: Vec<(T, MinHash<V>)>) {
+		if state.len() < item.len() {
+			mem::swap(state, &mut item);
+		}
+		state.extend(item);
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		let mut parents = (0..state.len()).collect::<Vec<_>>();
+		let mut buckets = HashMap::new();
+		for (i, (_, signature)) in state.iter().enumerate() {
+			// Items without features would otherwise all match each other
+			if signature.is_empty() {
+				continue;
+			}
+			for (band, hash) in signature.bands(self.rows).enumerate() {
+				let first = *buckets.entry((band, hash)).or_insert(i);
+				let (a, b) = (root(&mut parents, first), root(&mut parents, i));
+				parents[a.max(b)] = a.min(b);
+			}
+		}
+		let mut groups = Vec::<Vec<T>>::new();
+		let mut indices = HashMap::new();
+		for (i, (item, _)) in state.into_iter().enumerate() {
+			let root = root(&mut parents, i);
+			let index = *indices.entry(root).or_insert_with(|| {
+				groups.push(Vec::new());
+				groups.len() - 1
+			});
+			groups[index].push(item);
+		}
+		groups
+	}
+}
+
+/// Find the root of `i` in the union-find forest `parents`, halving the path.
+fn root(parents: &mut [usize], mut i: usize) -> usize {
+	while parents[i] != i {
+		parents[i] = parents[parents[i]];
+		i = parents[i];
+	}
+	i
+}
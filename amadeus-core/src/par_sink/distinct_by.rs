@@ -0,0 +1,93 @@
+use derive_new::new;
+use educe::Educe;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{hash::Hash, marker::PhantomData};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// Deduplicate items on the key `f` returns for each, keeping one full item per
+/// key, in the order their keys were first seen.
+///
+/// Each reducer keeps the first item it sees of each key, and when merging the
+/// results of reducers the item of the one merged first is kept. So where
+/// duplicates are in the same partition the earliest survives, but which
+/// survives of duplicates in different partitions depends on the order they
+/// finish in.
+#[derive(new)]
+#[must_use]
+pub struct DistinctBy<P, F> {
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item>, Item, F, K> ParallelSink<Item> for DistinctBy<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = K> + Clone + Send + 'static,
+		K: Eq + Hash + Send + 'static,
+		P::Output: Send + 'static,
+	{
+		folder_par_sink!(
+			DistinctByFolder<F, StepA>,
+			DistinctByFolder<(), StepB>,
+			self,
+			DistinctByFolder::new(self.f),
+			DistinctByFolder::new(())
+		);
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "F: Clone"))]
+#[serde(
+	bound(serialize = "F: Serialize"),
+	bound(deserialize = "F: Deserialize<'de>")
+)]
+pub struct DistinctByFolder<F, Step> {
+	f: F,
+	#[new(default)]
+	marker: PhantomData<fn() -> Step>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+impl<F, K, T> FolderSync<T> for DistinctByFolder<F, StepA>
+where
+	F: for<'a> FnMut<(&'a T,), Output = K>,
+	K: Eq + Hash,
+{
+	type State = IndexMap<K, T>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: T) {
+		let _ = state.entry(self.f.call_mut((&item,))).or_insert(item);
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<K, T> FolderSync<IndexMap<K, T>> for DistinctByFolder<(), StepB>
+where
+	K: Eq + Hash,
+{
+	type State = IndexMap<K, T>;
+	type Done = Vec<T>;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: IndexMap<K, T>) {
+		for (key, item) in item {
+			let _ = state.entry(key).or_insert(item);
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state.into_iter().map(|(_, item)| item).collect()
+	}
+}
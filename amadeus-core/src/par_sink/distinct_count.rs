@@ -0,0 +1,85 @@
+#![allow(clippy::type_complexity)]
+
+use derive_new::new;
+use educe::Educe;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{HashMap, HashSet}, hash::Hash, marker::PhantomData, mem
+};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// The exact number of distinct values for each key. Each reducer holds the set of
+/// values seen for each key, so this is suited to moderate cardinalities; see
+/// [`MostDistinct`](super::MostDistinct) for an approximate alternative.
+#[derive(new)]
+#[must_use]
+pub struct DistinctCountBy<P> {
+	pipe: P,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item, Output = (A, B)>, Item, A, B> ParallelSink<Item> for DistinctCountBy<P>
+	where
+		A: Eq + Hash + Send + 'static,
+		B: Eq + Hash + Send + 'static,
+	{
+		folder_par_sink!(DistinctCountByFolder<A, B, StepA>, DistinctCountByFolder<A, B, StepB>, self, DistinctCountByFolder::new(), DistinctCountByFolder::new());
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone)]
+#[serde(bound = "")]
+pub struct DistinctCountByFolder<A, B, Step> {
+	marker: PhantomData<fn() -> (A, B, Step)>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+impl<A, B> FolderSync<(A, B)> for DistinctCountByFolder<A, B, StepA>
+where
+	A: Eq + Hash,
+	B: Eq + Hash,
+{
+	type State = HashMap<A, HashSet<B>>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		HashMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, (a, b): (A, B)) {
+		let _ = state.entry(a).or_default().insert(b);
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<A, B> FolderSync<HashMap<A, HashSet<B>>> for DistinctCountByFolder<A, B, StepB>
+where
+	A: Eq + Hash,
+	B: Eq + Hash,
+{
+	type State = HashMap<A, HashSet<B>>;
+	type Done = HashMap<A, u64>;
+
+	fn zero(&mut self) -> Self::State {
+		HashMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: HashMap<A, HashSet<B>>) {
+		for (a, mut b) in item {
+			let values = state.entry(a).or_default();
+			if values.len() < b.len() {
+				mem::swap(values, &mut b);
+			}
+			values.extend(b);
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+			.into_iter()
+			.map(|(a, b)| (a, b.len() as u64))
+			.collect()
+	}
+}
@@ -0,0 +1,195 @@
+use derive_new::new;
+use educe::Educe;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+use super::{
+	folder_par_sink, DistributedPipe, DistributedSink, Final, FolderPartitions, FolderSync, FolderSyncReducer, Inter, ParallelPipe, ParallelSink
+};
+use crate::util::u64_to_f64;
+
+/// Exponentially-weighted moving average.
+///
+/// This is order-sensitive: each reducer folds the items it is given in order, but
+/// the per-reducer results are merged in whatever order they complete. As such the
+/// result is only deterministic when the stream is a single partition, or when run
+/// on a deterministic pool, which merges them in partition order. See
+/// [`EwmaPartitions`] for the unmerged per-partition results.
+#[derive(new)]
+#[must_use]
+pub struct Ewma<P> {
+	pipe: P,
+	alpha: f64,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item, Output = f64>, Item> ParallelSink<Item> for Ewma<P> {
+		folder_par_sink!(
+			EwmaFolder<StepA>,
+			EwmaFolder<StepB>,
+			self,
+			EwmaFolder::new(self.alpha),
+			EwmaFolder::new(self.alpha)
+		);
+	}
+}
+
+/// Exponentially-weighted moving average of each partition, i.e. each of the
+/// stream's tasks, omitting those that are empty. The averages are in partition
+/// order for the partitions processed by each worker, but otherwise in the order
+/// they complete, unless run on a deterministic pool.
+#[derive(new)]
+#[must_use]
+pub struct EwmaPartitions<P> {
+	pipe: P,
+	alpha: f64,
+}
+
+impl<P: ParallelPipe<Item, Output = f64>, Item> ParallelSink<Item> for EwmaPartitions<P> {
+	type Done = Vec<f64>;
+	type Pipe = FolderPartitions<P, EwmaFolder<StepA>>;
+	type ReduceA = FolderSyncReducer<Option<f64>, EwmaFolder<StepC>, Inter>;
+	type ReduceC = FolderSyncReducer<Vec<f64>, EwmaFolder<StepC>, Final>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		(
+			FolderPartitions::new(self.pipe, EwmaFolder::new(self.alpha)),
+			FolderSyncReducer::new(EwmaFolder::new(self.alpha)),
+			FolderSyncReducer::new(EwmaFolder::new(self.alpha)),
+		)
+	}
+}
+impl<P: DistributedPipe<Item, Output = f64>, Item> DistributedSink<Item> for EwmaPartitions<P> {
+	type Done = Vec<f64>;
+	type Pipe = FolderPartitions<P, EwmaFolder<StepA>>;
+	type ReduceA = FolderSyncReducer<Option<f64>, EwmaFolder<StepC>, Inter>;
+	type ReduceB = FolderSyncReducer<Vec<f64>, EwmaFolder<StepC>, Inter>;
+	type ReduceC = FolderSyncReducer<Vec<f64>, EwmaFolder<StepC>, Final>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		(
+			FolderPartitions::new(self.pipe, EwmaFolder::new(self.alpha)),
+			FolderSyncReducer::new(EwmaFolder::new(self.alpha)),
+			FolderSyncReducer::new(EwmaFolder::new(self.alpha)),
+			FolderSyncReducer::new(EwmaFolder::new(self.alpha)),
+		)
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone)]
+#[serde(bound = "")]
+pub struct EwmaFolder<Step> {
+	alpha: f64,
+	marker: PhantomData<fn() -> Step>,
+}
+
+pub struct StepA;
+pub struct StepB;
+pub struct StepC;
+
+#[derive(Serialize, Deserialize, new)]
+pub struct EwmaState {
+	#[new(default)]
+	first: f64,
+	#[new(default)]
+	value: f64,
+	#[new(default)]
+	count: u64,
+}
+
+impl FolderSync<f64> for EwmaFolder<StepA> {
+	type State = EwmaState;
+	type Done = Option<f64>;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		EwmaState::new()
+	}
+
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, item: f64) {
+		if state.count == 0 {
+			state.first = item;
+			state.value = item;
+		} else {
+			state.value = self.alpha * item + (1.0 - self.alpha) * state.value;
+		}
+		state.count += 1;
+	}
+
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		Some(state.value).filter(|_| state.count != 0)
+	}
+}
+
+impl FolderSync<EwmaState> for EwmaFolder<StepB> {
+	type State = EwmaState;
+	type Done = Option<f64>;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		EwmaState::new()
+	}
+
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, item: EwmaState) {
+		if item.count == 0 {
+			return;
+		}
+		if state.count == 0 {
+			*state = item;
+			return;
+		}
+		// Continuing the average of `state` over the items of `item` is equivalent to
+		// replacing the contribution of `item`'s first value with that of `state`.
+		let decay = (1.0 - self.alpha).powf(u64_to_f64(item.count));
+		state.value = item.value + decay * (state.value - item.first);
+		state.count += item.count;
+	}
+
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		Some(state.value).filter(|_| state.count != 0)
+	}
+}
+
+impl FolderSync<Option<f64>> for EwmaFolder<StepC> {
+	type State = Vec<f64>;
+	type Done = Self::State;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, item: Option<f64>) {
+		state.extend(item);
+	}
+
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl FolderSync<Vec<f64>> for EwmaFolder<StepC> {
+	type State = Vec<f64>;
+	type Done = Self::State;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, mut item: Vec<f64>) {
+		state.append(&mut item);
+	}
+
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 use serde_closure::traits::FnMut;
 use std::marker::PhantomData;
 
-use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+use super::{
+	folder_par_sink, DistributedPipe, DistributedSink, Final, FolderPartitions, FolderSync, FolderSyncReducer, Inter, ParallelPipe, ParallelSink
+};
+use crate::pool::ProcessSend;
 
 #[derive(new)]
 #[must_use]
@@ -84,3 +87,110 @@ where
 		state
 	}
 }
+
+/// Fold each partition separately, returning the folded value of each,
+/// including those that are empty, rather than combining them as [`Fold`]
+/// does. The values of partitions processed by the same worker are in
+/// partition order, but those of different workers are in the order they
+/// complete.
+#[derive(new)]
+#[must_use]
+pub struct FoldPartitions<P, ID, F, B> {
+	pipe: P,
+	identity: ID,
+	op: F,
+	marker: PhantomData<fn() -> B>,
+}
+
+impl<P: ParallelPipe<Item>, Item, ID, F, B> ParallelSink<Item> for FoldPartitions<P, ID, F, B>
+where
+	ID: FnMut<(), Output = B> + Clone + Send + 'static,
+	F: FnMut<(B, P::Output), Output = B> + Clone + Send + 'static,
+	B: Send + 'static,
+	P::Output: 'static,
+{
+	type Done = Vec<B>;
+	type Pipe = FolderPartitions<P, FoldFolder<P::Output, ID, F, B, StepC>>;
+	type ReduceA = FolderSyncReducer<B, FoldFolder<P::Output, (), (), B, StepD>, Inter>;
+	type ReduceC = FolderSyncReducer<Vec<B>, FoldFolder<P::Output, (), (), B, StepD>, Final>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		(
+			FolderPartitions::new(self.pipe, FoldFolder::new(self.identity, self.op)),
+			FolderSyncReducer::new(FoldFolder::new((), ())),
+			FolderSyncReducer::new(FoldFolder::new((), ())),
+		)
+	}
+}
+impl<P: DistributedPipe<Item>, Item, ID, F, B> DistributedSink<Item> for FoldPartitions<P, ID, F, B>
+where
+	ID: FnMut<(), Output = B> + Clone + ProcessSend + 'static,
+	F: FnMut<(B, P::Output), Output = B> + Clone + ProcessSend + 'static,
+	B: ProcessSend + 'static,
+	P::Output: 'static,
+{
+	type Done = Vec<B>;
+	type Pipe = FolderPartitions<P, FoldFolder<P::Output, ID, F, B, StepC>>;
+	type ReduceA = FolderSyncReducer<B, FoldFolder<P::Output, (), (), B, StepD>, Inter>;
+	type ReduceB = FolderSyncReducer<Vec<B>, FoldFolder<P::Output, (), (), B, StepD>, Inter>;
+	type ReduceC = FolderSyncReducer<Vec<B>, FoldFolder<P::Output, (), (), B, StepD>, Final>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		(
+			FolderPartitions::new(self.pipe, FoldFolder::new(self.identity, self.op)),
+			FolderSyncReducer::new(FoldFolder::new((), ())),
+			FolderSyncReducer::new(FoldFolder::new((), ())),
+			FolderSyncReducer::new(FoldFolder::new((), ())),
+		)
+	}
+}
+
+pub struct StepC;
+pub struct StepD;
+
+impl<Item, ID, F, B> FolderSync<Item> for FoldFolder<Item, ID, F, B, StepC>
+where
+	ID: FnMut<(), Output = B>,
+	F: FnMut<(B, Item), Output = B>,
+{
+	type State = B;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		self.identity.call_mut(())
+	}
+	fn push(&mut self, state: &mut Self::State, item: Item) {
+		replace_with_or_abort(state, |state| self.op.call_mut((state, item)));
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<A, B> FolderSync<B> for FoldFolder<A, (), (), B, StepD> {
+	type State = Vec<B>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: B) {
+		state.push(item);
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<A, B> FolderSync<Vec<B>> for FoldFolder<A, (), (), B, StepD> {
+	type State = Vec<B>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, mut item: Vec<B>) {
+		state.append(&mut item);
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
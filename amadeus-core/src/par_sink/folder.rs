@@ -9,8 +9,10 @@ use std::{
 	future::Future, marker::PhantomData, pin::Pin, task::{Context, Poll}
 };
 
-use super::{Reducer, ReducerProcessSend, ReducerSend};
-use crate::{pipe::Sink, pool::ProcessSend};
+use super::{ParallelPipe, PipeTask, Reducer, ReducerProcessSend, ReducerSend};
+use crate::{
+	pipe::{Pipe, Sink}, pool::ProcessSend
+};
 
 mod macros {
 	#[macro_export]
@@ -186,3 +188,74 @@ where
 		Poll::Ready(folder.done(self_.state.take().unwrap()))
 	}
 }
+
+/// A pipe folding the items of each of its tasks, i.e. of each partition of
+/// the stream, with `folder`, yielding the result once the partition is
+/// exhausted. Empty partitions yield the result of folding no items.
+#[derive(new)]
+pub struct FolderPartitions<P, F> {
+	pipe: P,
+	folder: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Input>, F, Input> ParallelPipe<Input> for FolderPartitions<P, F>
+	where
+		F: FolderSync<P::Output> + Clone + Send + 'static,
+	{
+		type Output = F::Done;
+		type Task = FolderPartitionsTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			FolderPartitionsTask::new(self.pipe.task(), self.folder.clone())
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct FolderPartitionsTask<T, F> {
+	task: T,
+	folder: F,
+}
+impl<T: PipeTask<Input>, F, Input> PipeTask<Input> for FolderPartitionsTask<T, F>
+where
+	F: FolderSync<T::Output>,
+{
+	type Output = F::Done;
+	type Async = FolderPartitionsAsync<T::Async, F, F::State>;
+
+	fn into_async(mut self) -> Self::Async {
+		FolderPartitionsAsync {
+			task: self.task.into_async(),
+			state: Some(self.folder.zero()),
+			folder: self.folder,
+		}
+	}
+}
+
+#[pin_project]
+pub struct FolderPartitionsAsync<T, F, S> {
+	#[pin]
+	task: T,
+	folder: F,
+	state: Option<S>,
+}
+impl<T: Pipe<Input>, F, Input> Pipe<Input> for FolderPartitionsAsync<T, F, F::State>
+where
+	F: FolderSync<T::Output>,
+{
+	type Output = F::Done;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		let folder = self_.folder;
+		if let Some(state) = self_.state {
+			while let Some(item) = ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+				folder.push(state, item);
+			}
+		}
+		Poll::Ready(self_.state.take().map(|state| folder.done(state)))
+	}
+}
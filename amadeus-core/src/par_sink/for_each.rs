@@ -5,11 +5,11 @@ use pin_project::pin_project;
 use serde::{Deserialize, Serialize};
 use serde_closure::traits::FnMut;
 use std::{
-	marker::PhantomData, pin::Pin, task::{Context, Poll}
+	marker::PhantomData, mem, pin::Pin, task::{Context, Poll}
 };
 
 use super::{
-	DistributedPipe, DistributedSink, ParallelPipe, ParallelSink, PushReducer, Reducer, ReducerProcessSend, ReducerSend
+	DistributedPipe, DistributedSink, FolderPartitions, FolderSync, ParallelPipe, ParallelSink, PushReducer, Reducer, ReducerProcessSend, ReducerSend
 };
 use crate::{pipe::Sink, pool::ProcessSend};
 
@@ -107,3 +107,92 @@ where
 		Poll::Ready(())
 	}
 }
+
+/// Like [`ForEach`], but hands items to `f` in batches of up to `batch_size`.
+///
+/// Each partition flushes its batch when it fills, and flushes whatever remains
+/// once the partition is exhausted.
+#[derive(new)]
+#[must_use]
+pub struct ForEachBatched<P, F> {
+	pipe: P,
+	batch_size: usize,
+	f: F,
+}
+
+impl<P: ParallelPipe<Item>, Item, F> ParallelSink<Item> for ForEachBatched<P, F>
+where
+	F: FnMut<(Vec<P::Output>,), Output = ()> + Clone + Send + 'static,
+	P::Output: 'static,
+{
+	type Done = ();
+	type Pipe = FolderPartitions<P, ForEachBatchedFolder<P::Output, F>>;
+	type ReduceA = PushReducer<()>;
+	type ReduceC = PushReducer<()>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		(
+			FolderPartitions::new(
+				self.pipe,
+				ForEachBatchedFolder(self.f, self.batch_size, PhantomData),
+			),
+			PushReducer::new(),
+			PushReducer::new(),
+		)
+	}
+}
+impl<P: DistributedPipe<Item>, Item, F> DistributedSink<Item> for ForEachBatched<P, F>
+where
+	F: FnMut<(Vec<P::Output>,), Output = ()> + Clone + ProcessSend + 'static,
+	P::Output: 'static,
+{
+	type Done = ();
+	type Pipe = FolderPartitions<P, ForEachBatchedFolder<P::Output, F>>;
+	type ReduceA = PushReducer<()>;
+	type ReduceB = PushReducer<()>;
+	type ReduceC = PushReducer<()>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		(
+			FolderPartitions::new(
+				self.pipe,
+				ForEachBatchedFolder(self.f, self.batch_size, PhantomData),
+			),
+			PushReducer::new(),
+			PushReducer::new(),
+			PushReducer::new(),
+		)
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize)]
+#[educe(Clone(bound = "F: Clone"))]
+#[serde(
+	bound(serialize = "F: Serialize"),
+	bound(deserialize = "F: Deserialize<'de>")
+)]
+pub struct ForEachBatchedFolder<Item, F>(F, usize, PhantomData<fn() -> Item>);
+
+impl<Item, F> FolderSync<Item> for ForEachBatchedFolder<Item, F>
+where
+	F: FnMut<(Vec<Item>,), Output = ()>,
+{
+	type State = Vec<Item>;
+	type Done = ();
+
+	fn zero(&mut self) -> Self::State {
+		Vec::with_capacity(self.1)
+	}
+	fn push(&mut self, state: &mut Self::State, item: Item) {
+		state.push(item);
+		if state.len() == self.1 {
+			let batch = mem::replace(state, Vec::with_capacity(self.1));
+			self.0.call_mut((batch,));
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		if !state.is_empty() {
+			self.0.call_mut((state,));
+		}
+	}
+}
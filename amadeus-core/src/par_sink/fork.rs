@@ -15,7 +15,7 @@ use super::{
 	DistributedPipe, DistributedSink, ParallelPipe, ParallelSink, PipeTask, ReduceA2, ReduceC2
 };
 use crate::{
-	par_stream::{ParallelStream, StreamTask}, pipe::Pipe, util::transmute
+	par_stream::{ParallelStream, StreamTask}, pipe::Pipe, plan::{short_name, Plan, Stage}, util::transmute
 };
 
 #[pin_project]
@@ -44,6 +44,13 @@ impl_par_dist! {
 		fn size_hint(&self) -> (usize, Option<usize>) {
 			self.a.size_hint()
 		}
+		fn explain(&self) -> Plan {
+			self.a.explain().then_stage(
+				Stage::of::<Self>()
+					.detail("pipe", short_name::<B>())
+					.detail("pipe_ref", short_name::<C>()),
+			)
+		}
 		#[inline(always)]
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			let self_ = self.project();
@@ -163,6 +170,9 @@ where
 			marker: PhantomData,
 		}
 	}
+	fn cost(&self) -> Option<u64> {
+		self.stream.cost()
+	}
 }
 impl<A, B, C, Input, RefAItem> PipeTask<Input> for JoinTask<A, B, C, RefAItem>
 where
@@ -7,7 +7,7 @@ use indexmap::IndexMap;
 use pin_project::pin_project;
 use serde::{Deserialize, Serialize};
 use std::{
-	hash::Hash, marker::PhantomData, mem, pin::Pin, task::{Context, Poll}
+	collections::hash_map::{DefaultHasher, RandomState}, hash::{BuildHasher, Hash, Hasher}, marker::PhantomData, mem, pin::Pin, task::{Context, Poll}
 };
 use sum::Sum2;
 
@@ -18,58 +18,117 @@ use crate::{
 	pipe::{Pipe, Sink, StreamExt as _}, pool::ProcessSend
 };
 
+/// Makes the [`BuildHasher`] with which [`GroupBy`] hashes keys. It's this
+/// rather than the `BuildHasher` that's sent to each reducer, so that
+/// hashers that can't be serialized, like [`RandomState`], can be used.
+pub trait MakeHasher {
+	type Hasher: BuildHasher + Clone + Default;
+
+	fn make_hasher(&self) -> Self::Hasher;
+}
+
+/// Makes a freshly-seeded [`RandomState`], as used by
+/// [`HashMap`](std::collections::HashMap). This is the default.
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Debug)]
+pub struct RandomHasher;
+impl MakeHasher for RandomHasher {
+	type Hasher = RandomState;
+
+	fn make_hasher(&self) -> Self::Hasher {
+		RandomState::new()
+	}
+}
+
+/// A [`BuildHasher`] that hashes identically in every process given the same
+/// `seed`, for reproducible grouping and partition assignment. Different seeds
+/// spread clustered keys differently. The underlying algorithm is that of
+/// [`DefaultHasher`], so hashes are only stable across builds with the same
+/// Rust version.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize, Debug)]
+pub struct SeededHasher {
+	seed: u64,
+}
+impl SeededHasher {
+	pub fn new(seed: u64) -> Self {
+		Self { seed }
+	}
+}
+impl BuildHasher for SeededHasher {
+	type Hasher = DefaultHasher;
+
+	fn build_hasher(&self) -> Self::Hasher {
+		let mut hasher = DefaultHasher::new();
+		hasher.write_u64(self.seed);
+		hasher
+	}
+}
+impl MakeHasher for SeededHasher {
+	type Hasher = Self;
+
+	fn make_hasher(&self) -> Self::Hasher {
+		*self
+	}
+}
+
 #[derive(new)]
 #[must_use]
-pub struct GroupBy<A, B> {
+pub struct GroupBy<A, B, H = RandomHasher> {
 	a: A,
 	b: B,
+	hasher: H,
 }
 
-impl<A: ParallelPipe<Item, Output = (T, U)>, B: ParallelSink<U>, Item, T, U> ParallelSink<Item>
-	for GroupBy<A, B>
+impl<A: ParallelPipe<Item, Output = (T, U)>, B: ParallelSink<U>, H, Item, T, U> ParallelSink<Item>
+	for GroupBy<A, B, H>
 where
 	T: Eq + Hash + Send + 'static,
+	H: MakeHasher + Clone + Send + 'static,
+	H::Hasher: Send + 'static,
 	<B::Pipe as ParallelPipe<U>>::Task: Clone + Send + 'static,
 	B::ReduceA: Clone + Send + 'static,
 	B::ReduceC: Clone,
 	B::Done: Send + 'static,
 {
-	type Done = IndexMap<T, B::Done>;
+	type Done = IndexMap<T, B::Done, H::Hasher>;
 	type Pipe = A;
-	type ReduceA = GroupByReducerA<<B::Pipe as ParallelPipe<U>>::Task, B::ReduceA, T, U>;
+	type ReduceA = GroupByReducerA<<B::Pipe as ParallelPipe<U>>::Task, B::ReduceA, T, U, H>;
 	type ReduceC = GroupByReducerB<
 		B::ReduceC,
 		T,
 		<B::ReduceA as ReducerSend<<B::Pipe as ParallelPipe<U>>::Output>>::Done,
+		H,
 	>;
 
 	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
 		let (a, b, c) = self.b.reducers();
 		(
 			self.a,
-			GroupByReducerA::new(a.task(), b),
-			GroupByReducerB::new(c),
+			GroupByReducerA::new(a.task(), b, self.hasher.clone()),
+			GroupByReducerB::new(c, self.hasher),
 		)
 	}
 }
 
-impl<A: DistributedPipe<Item, Output = (T, U)>, B: DistributedSink<U>, Item, T, U>
-	DistributedSink<Item> for GroupBy<A, B>
+impl<A: DistributedPipe<Item, Output = (T, U)>, B: DistributedSink<U>, H, Item, T, U>
+	DistributedSink<Item> for GroupBy<A, B, H>
 where
 	T: Eq + Hash + ProcessSend + 'static,
+	H: MakeHasher + Clone + ProcessSend + 'static,
+	H::Hasher: Send + 'static,
 	<B::Pipe as DistributedPipe<U>>::Task: Clone + ProcessSend + 'static,
 	B::ReduceA: Clone + ProcessSend + 'static,
 	B::ReduceB: Clone,
 	B::ReduceC: Clone,
 	B::Done: ProcessSend + 'static,
 {
-	type Done = IndexMap<T, B::Done>;
+	type Done = IndexMap<T, B::Done, H::Hasher>;
 	type Pipe = A;
-	type ReduceA = GroupByReducerA<<B::Pipe as DistributedPipe<U>>::Task, B::ReduceA, T, U>;
+	type ReduceA = GroupByReducerA<<B::Pipe as DistributedPipe<U>>::Task, B::ReduceA, T, U, H>;
 	type ReduceB = GroupByReducerB<
 		B::ReduceB,
 		T,
 		<B::ReduceA as ReducerSend<<B::Pipe as DistributedPipe<U>>::Output>>::Done,
+		H,
 	>;
 	type ReduceC = GroupByReducerB<
 		B::ReduceC,
@@ -77,62 +136,72 @@ where
 		<B::ReduceB as ReducerProcessSend<
 			<B::ReduceA as Reducer<<B::Pipe as DistributedPipe<U>>::Output>>::Done,
 		>>::Done,
+		H,
 	>;
 
 	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
 		let (a, b, c, d) = self.b.reducers();
 		(
 			self.a,
-			GroupByReducerA::new(a.task(), b),
-			GroupByReducerB::new(c),
-			GroupByReducerB::new(d),
+			GroupByReducerA::new(a.task(), b, self.hasher.clone()),
+			GroupByReducerB::new(c, self.hasher.clone()),
+			GroupByReducerB::new(d, self.hasher),
 		)
 	}
 }
 
 #[derive(Educe, Serialize, Deserialize, new)]
-#[educe(Clone(bound = "P: Clone, R: Clone"))]
+#[educe(Clone(bound = "P: Clone, R: Clone, H: Clone"))]
 #[serde(
-	bound(serialize = "P: Serialize, R: Serialize"),
-	bound(deserialize = "P: Deserialize<'de>, R: Deserialize<'de>")
+	bound(serialize = "P: Serialize, R: Serialize, H: Serialize"),
+	bound(deserialize = "P: Deserialize<'de>, R: Deserialize<'de>, H: Deserialize<'de>")
 )]
-pub struct GroupByReducerA<P, R, T, U>(P, R, PhantomData<fn() -> (T, U)>);
+pub struct GroupByReducerA<P, R, T, U, H>(P, R, H, PhantomData<fn() -> (T, U)>);
 
-impl<P, R, T, U> Reducer<(T, U)> for GroupByReducerA<P, R, T, U>
+impl<P, R, T, U, H> Reducer<(T, U)> for GroupByReducerA<P, R, T, U, H>
 where
 	P: PipeTask<U>,
 	R: Reducer<P::Output> + Clone,
 	T: Eq + Hash,
+	H: MakeHasher,
 {
-	type Done = IndexMap<T, R::Done>;
-	type Async = GroupByReducerAAsync<P::Async, R, T, U>;
+	type Done = IndexMap<T, R::Done, H::Hasher>;
+	type Async = GroupByReducerAAsync<P::Async, R, T, U, H::Hasher>;
 
 	fn into_async(self) -> Self::Async {
-		GroupByReducerAAsync::new(self.0.into_async(), self.1)
+		GroupByReducerAAsync::new(
+			self.0.into_async(),
+			self.1,
+			IndexMap::with_hasher(self.2.make_hasher()),
+		)
 	}
 }
-impl<P, R, T, U> ReducerProcessSend<(T, U)> for GroupByReducerA<P, R, T, U>
+impl<P, R, T, U, H> ReducerProcessSend<(T, U)> for GroupByReducerA<P, R, T, U, H>
 where
 	P: PipeTask<U>,
 	R: Reducer<P::Output> + Clone,
 	T: Eq + Hash + ProcessSend + 'static,
+	H: MakeHasher,
+	H::Hasher: Send + 'static,
 	R::Done: ProcessSend + 'static,
 {
-	type Done = IndexMap<T, R::Done>;
+	type Done = IndexMap<T, R::Done, H::Hasher>;
 }
-impl<P, R, T, U> ReducerSend<(T, U)> for GroupByReducerA<P, R, T, U>
+impl<P, R, T, U, H> ReducerSend<(T, U)> for GroupByReducerA<P, R, T, U, H>
 where
 	P: PipeTask<U>,
 	R: Reducer<P::Output> + Clone,
 	T: Eq + Hash + Send + 'static,
+	H: MakeHasher,
+	H::Hasher: Send + 'static,
 	R::Done: Send + 'static,
 {
-	type Done = IndexMap<T, R::Done>;
+	type Done = IndexMap<T, R::Done, H::Hasher>;
 }
 
 #[pin_project]
 #[derive(new)]
-pub struct GroupByReducerAAsync<P, R, T, U>
+pub struct GroupByReducerAAsync<P, R, T, U, H>
 where
 	P: Pipe<U>,
 	R: Reducer<P::Output>,
@@ -140,19 +209,19 @@ where
 	#[pin]
 	pipe: P,
 	factory: R,
+	map: IndexMap<T, Pin<Box<R::Async>>, H>,
 	#[new(default)]
 	pending: Option<Sum2<(T, Option<U>, Option<Pin<Box<R::Async>>>), Vec<Option<R::Done>>>>,
-	#[new(default)]
-	map: IndexMap<T, Pin<Box<R::Async>>>,
 }
 
-impl<P, R, T, U> Sink<(T, U)> for GroupByReducerAAsync<P, R, T, U>
+impl<P, R, T, U, H> Sink<(T, U)> for GroupByReducerAAsync<P, R, T, U, H>
 where
 	P: Pipe<U>,
 	R: Reducer<P::Output> + Clone,
 	T: Eq + Hash,
+	H: BuildHasher + Clone,
 {
-	type Done = IndexMap<T, R::Done>;
+	type Done = IndexMap<T, R::Done, H>;
 
 	#[inline(always)]
 	fn poll_forward(
@@ -228,11 +297,14 @@ where
 					if !done_ {
 						return Poll::Pending;
 					}
-					let ret = mem::take(self_.map)
-						.into_iter()
-						.zip(done.iter_mut())
-						.map(|((k, _), v)| (k, v.take().unwrap()))
-						.collect();
+					let hasher = self_.map.hasher().clone();
+					let map = mem::replace(self_.map, IndexMap::with_hasher(hasher.clone()));
+					let mut ret = IndexMap::with_capacity_and_hasher(map.len(), hasher);
+					ret.extend(
+						map.into_iter()
+							.zip(done.iter_mut())
+							.map(|((k, _), v)| (k, v.take().unwrap())),
+					);
 					return Poll::Ready(ret);
 				}
 			}
@@ -241,66 +313,71 @@ where
 }
 
 #[derive(Educe, Serialize, Deserialize, new)]
-#[educe(Clone(bound = "R: Clone"))]
+#[educe(Clone(bound = "R: Clone, H: Clone"))]
 #[serde(
-	bound(serialize = "R: Serialize"),
-	bound(deserialize = "R: Deserialize<'de>")
+	bound(serialize = "R: Serialize, H: Serialize"),
+	bound(deserialize = "R: Deserialize<'de>, H: Deserialize<'de>")
 )]
-pub struct GroupByReducerB<R, T, U>(R, PhantomData<fn() -> (T, U)>);
+pub struct GroupByReducerB<R, T, U, H>(R, H, PhantomData<fn() -> (T, U)>);
 
-impl<R, T, U> Reducer<IndexMap<T, U>> for GroupByReducerB<R, T, U>
+impl<R, T, U, H> Reducer<IndexMap<T, U, H::Hasher>> for GroupByReducerB<R, T, U, H>
 where
 	R: Reducer<U> + Clone,
 	T: Eq + Hash,
+	H: MakeHasher,
 {
-	type Done = IndexMap<T, R::Done>;
-	type Async = GroupByReducerBAsync<R, T, U>;
+	type Done = IndexMap<T, R::Done, H::Hasher>;
+	type Async = GroupByReducerBAsync<R, T, U, H::Hasher>;
 
 	fn into_async(self) -> Self::Async {
-		GroupByReducerBAsync::new(self.0)
+		GroupByReducerBAsync::new(self.0, IndexMap::with_hasher(self.1.make_hasher()))
 	}
 }
-impl<R, T, U> ReducerProcessSend<IndexMap<T, U>> for GroupByReducerB<R, T, U>
+impl<R, T, U, H> ReducerProcessSend<IndexMap<T, U, H::Hasher>> for GroupByReducerB<R, T, U, H>
 where
 	R: Reducer<U> + Clone,
 	T: Eq + Hash + ProcessSend + 'static,
+	H: MakeHasher,
+	H::Hasher: Send + 'static,
 	R::Done: ProcessSend + 'static,
 {
-	type Done = IndexMap<T, R::Done>;
+	type Done = IndexMap<T, R::Done, H::Hasher>;
 }
-impl<R, T, U> ReducerSend<IndexMap<T, U>> for GroupByReducerB<R, T, U>
+impl<R, T, U, H> ReducerSend<IndexMap<T, U, H::Hasher>> for GroupByReducerB<R, T, U, H>
 where
 	R: Reducer<U> + Clone,
 	T: Eq + Hash + Send + 'static,
+	H: MakeHasher,
+	H::Hasher: Send + 'static,
 	R::Done: Send + 'static,
 {
-	type Done = IndexMap<T, R::Done>;
+	type Done = IndexMap<T, R::Done, H::Hasher>;
 }
 
 #[pin_project]
 #[derive(new)]
-pub struct GroupByReducerBAsync<R, T, U>
+pub struct GroupByReducerBAsync<R, T, U, H>
 where
 	R: Reducer<U>,
 {
 	f: R,
+	map: IndexMap<T, Pin<Box<R::Async>>, H>,
 	#[new(default)]
-	pending: Option<Sum2<IndexMap<T, (U, Option<Pin<Box<R::Async>>>)>, Vec<Option<R::Done>>>>,
-	#[new(default)]
-	map: IndexMap<T, Pin<Box<R::Async>>>,
+	pending: Option<Sum2<Vec<(T, (U, Option<Pin<Box<R::Async>>>))>, Vec<Option<R::Done>>>>,
 }
 
-impl<R, T, U> Sink<IndexMap<T, U>> for GroupByReducerBAsync<R, T, U>
+impl<R, T, U, H> Sink<IndexMap<T, U, H>> for GroupByReducerBAsync<R, T, U, H>
 where
 	R: Reducer<U> + Clone,
 	T: Eq + Hash,
+	H: BuildHasher + Clone,
 {
-	type Done = IndexMap<T, R::Done>;
+	type Done = IndexMap<T, R::Done, H>;
 
 	#[inline(always)]
 	fn poll_forward(
 		self: Pin<&mut Self>, cx: &mut Context,
-		mut stream: Pin<&mut impl Stream<Item = IndexMap<T, U>>>,
+		mut stream: Pin<&mut impl Stream<Item = IndexMap<T, U, H>>>,
 	) -> Poll<Self::Done> {
 		let self_ = self.project();
 		loop {
@@ -350,7 +427,7 @@ where
 							let _ = v.take();
 						}
 						if let Some(v) = v {
-							let _ = pending.insert(k, (v, r));
+							pending.push((k, (v, r)));
 							return Poll::Pending;
 						}
 						if let Some(r) = r {
@@ -379,11 +456,14 @@ where
 					if !done_ {
 						return Poll::Pending;
 					}
-					let ret = mem::take(self_.map)
-						.into_iter()
-						.zip(done.iter_mut())
-						.map(|((k, _), v)| (k, v.take().unwrap()))
-						.collect();
+					let hasher = self_.map.hasher().clone();
+					let map = mem::replace(self_.map, IndexMap::with_hasher(hasher.clone()));
+					let mut ret = IndexMap::with_capacity_and_hasher(map.len(), hasher);
+					ret.extend(
+						map.into_iter()
+							.zip(done.iter_mut())
+							.map(|((k, _), v)| (k, v.take().unwrap())),
+					);
 					return Poll::Ready(ret);
 				}
 			}
@@ -0,0 +1,187 @@
+#![allow(clippy::type_complexity)]
+
+use educe::Educe;
+use either::Either;
+use indexmap::{map::Entry, IndexMap};
+use replace_with::replace_with_or_abort;
+use serde_closure::traits::FnMut;
+use std::{
+	hash::Hash, marker::PhantomData, mem, sync::{Arc, Mutex, PoisonError}
+};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// Fold the values of each key like [`Fold`](super::Fold), calling `snapshot`
+/// with the per-key results over all workers so far each time a worker has
+/// received another `every` items, and once with the final result.
+///
+/// Each worker folds its items locally, merging them into a total shared by all
+/// workers before each snapshot, so a snapshot reflects a prefix of the items of
+/// each partition. The total is shared in memory, so this is not available for
+/// distributed streams.
+#[must_use]
+pub struct GroupBySnapshots<P, ID, F, S, A, B> {
+	pipe: P,
+	every: usize,
+	identity: ID,
+	op: F,
+	shared: Arc<Mutex<Shared<A, B, S>>>,
+}
+impl<P, ID, F, S, A, B> GroupBySnapshots<P, ID, F, S, A, B> {
+	pub(crate) fn new(pipe: P, every: usize, identity: ID, op: F, snapshot: S) -> Self {
+		let shared = Arc::new(Mutex::new(Shared {
+			total: IndexMap::new(),
+			snapshot,
+		}));
+		Self {
+			pipe,
+			every,
+			identity,
+			op,
+			shared,
+		}
+	}
+}
+
+/// The total over all workers so far, and what to publish it to.
+struct Shared<A, B, S> {
+	total: IndexMap<A, B>,
+	snapshot: S,
+}
+impl<A, B, S> Shared<A, B, S>
+where
+	A: Eq + Hash,
+	S: for<'a> FnMut<(&'a IndexMap<A, B>,), Output = ()>,
+{
+	fn merge<V, F>(&mut self, op: &mut F, map: IndexMap<A, B>)
+	where
+		F: FnMut<(B, Either<V, B>), Output = B>,
+	{
+		for (key, value) in map {
+			match self.total.entry(key) {
+				Entry::Occupied(entry) => replace_with_or_abort(entry.into_mut(), |state| {
+					op.call_mut((state, Either::Right(value)))
+				}),
+				Entry::Vacant(entry) => {
+					let _ = entry.insert(value);
+				}
+			}
+		}
+	}
+	fn publish(&mut self) {
+		self.snapshot.call_mut((&self.total,));
+	}
+}
+
+impl<P: ParallelPipe<Item, Output = (A, V)>, Item, A, V, ID, F, S, B> ParallelSink<Item>
+	for GroupBySnapshots<P, ID, F, S, A, B>
+where
+	A: Eq + Hash + Send + 'static,
+	ID: FnMut<(), Output = B> + Clone + Send + 'static,
+	F: FnMut<(B, Either<V, B>), Output = B> + Clone + Send + 'static,
+	S: for<'a> FnMut<(&'a IndexMap<A, B>,), Output = ()> + Send + 'static,
+	B: Send + 'static,
+{
+	folder_par_sink!(
+		GroupBySnapshotsFolder<ID, F, S, A, V, B>,
+		GroupBySnapshotsMergeFolder<F, S, A, V, B>,
+		self,
+		GroupBySnapshotsFolder::new(self.every, self.identity, self.op.clone(), self.shared.clone()),
+		GroupBySnapshotsMergeFolder::new(self.op, self.shared)
+	);
+}
+
+#[derive(Educe)]
+#[educe(Clone(bound = "ID: Clone, F: Clone"))]
+pub struct GroupBySnapshotsFolder<ID, F, S, A, V, B> {
+	every: usize,
+	identity: ID,
+	op: F,
+	shared: Arc<Mutex<Shared<A, B, S>>>,
+	count: usize,
+	marker: PhantomData<fn() -> V>,
+}
+impl<ID, F, S, A, V, B> GroupBySnapshotsFolder<ID, F, S, A, V, B> {
+	fn new(every: usize, identity: ID, op: F, shared: Arc<Mutex<Shared<A, B, S>>>) -> Self {
+		Self {
+			every,
+			identity,
+			op,
+			shared,
+			count: 0,
+			marker: PhantomData,
+		}
+	}
+}
+
+impl<A, V, ID, F, S, B> FolderSync<(A, V)> for GroupBySnapshotsFolder<ID, F, S, A, V, B>
+where
+	A: Eq + Hash,
+	ID: FnMut<(), Output = B>,
+	F: FnMut<(B, Either<V, B>), Output = B>,
+	S: for<'a> FnMut<(&'a IndexMap<A, B>,), Output = ()>,
+{
+	type State = IndexMap<A, B>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, map: &mut Self::State, (key, value): (A, V)) {
+		let (identity, op) = (&mut self.identity, &mut self.op);
+		let state = map.entry(key).or_insert_with(|| identity.call_mut(()));
+		replace_with_or_abort(state, |state| op.call_mut((state, Either::Left(value))));
+		self.count += 1;
+		if self.count == self.every {
+			self.count = 0;
+			// A panic while snapshotting leaves the total intact, so carry on with it
+			let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+			shared.merge(op, mem::replace(map, IndexMap::new()));
+			shared.publish();
+		}
+	}
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+
+/// Merges what's left of each worker's results into the total, then publishes
+/// and returns it.
+#[derive(Educe)]
+#[educe(Clone(bound = "F: Clone"))]
+pub struct GroupBySnapshotsMergeFolder<F, S, A, V, B> {
+	op: F,
+	shared: Arc<Mutex<Shared<A, B, S>>>,
+	marker: PhantomData<fn() -> V>,
+}
+impl<F, S, A, V, B> GroupBySnapshotsMergeFolder<F, S, A, V, B> {
+	fn new(op: F, shared: Arc<Mutex<Shared<A, B, S>>>) -> Self {
+		Self {
+			op,
+			shared,
+			marker: PhantomData,
+		}
+	}
+}
+
+impl<A, V, F, S, B> FolderSync<IndexMap<A, B>> for GroupBySnapshotsMergeFolder<F, S, A, V, B>
+where
+	A: Eq + Hash,
+	F: FnMut<(B, Either<V, B>), Output = B>,
+	S: for<'a> FnMut<(&'a IndexMap<A, B>,), Output = ()>,
+{
+	type State = ();
+	type Done = IndexMap<A, B>;
+
+	fn zero(&mut self) -> Self::State {}
+	fn push(&mut self, _state: &mut Self::State, item: IndexMap<A, B>) {
+		let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+		shared.merge(&mut self.op, item);
+	}
+	fn done(&mut self, _state: Self::State) -> Self::Done {
+		let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+		shared.publish();
+		mem::replace(&mut shared.total, IndexMap::new())
+	}
+}
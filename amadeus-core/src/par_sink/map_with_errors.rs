@@ -0,0 +1,167 @@
+#![allow(clippy::type_complexity)]
+
+use derive_new::new;
+use futures::{pin_mut, ready, stream, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	collections::VecDeque, marker::PhantomData, pin::Pin, task::{Context, Poll}
+};
+use sum::Sum2;
+
+use super::{
+	DistributedPipe, DistributedSink, FromDistributedStream, FromParallelStream, ParallelPipe, ParallelSink, PipeTask, ReduceA2, ReduceC2
+};
+use crate::{pipe::Pipe, pool::ProcessSend};
+
+#[derive(new)]
+#[must_use]
+pub struct MapWithErrors<P, F, S> {
+	pipe: P,
+	f: F,
+	sink: S,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Input>, F, S, Input, T, E> ParallelPipe<Input> for MapWithErrors<P, F, S>
+	where
+		F: FnMut<(P::Output,), Output = Result<T, E>> + Clone + Send + 'static,
+		S: ParallelPipe<T>,
+	{
+		type Output = Sum2<S::Output, E>;
+		type Task = MapWithErrorsTask<P::Task, F, S::Task>;
+
+		fn task(&self) -> Self::Task {
+			MapWithErrorsTask::new(self.pipe.task(), self.f.clone(), self.sink.task())
+		}
+	}
+}
+
+impl<P: ParallelPipe<Input>, F, S, Input, T, E> ParallelSink<Input> for MapWithErrors<P, F, S>
+where
+	F: FnMut<(P::Output,), Output = Result<T, E>> + Clone + Send + 'static,
+	S: ParallelSink<T>,
+	E: Send + 'static,
+{
+	type Done = (S::Done, Vec<E>);
+	type Pipe = MapWithErrors<P, F, S::Pipe>;
+	type ReduceA = ReduceA2<S::ReduceA, <Vec<E> as FromParallelStream<E>>::ReduceA>;
+	type ReduceC = ReduceC2<S::ReduceC, <Vec<E> as FromParallelStream<E>>::ReduceC>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		let (pipe, reduce_a, reduce_c) = self.sink.reducers();
+		let (errors_a, errors_c) = <Vec<E> as FromParallelStream<E>>::reducers();
+		(
+			MapWithErrors::new(self.pipe, self.f, pipe),
+			ReduceA2::new(reduce_a, errors_a),
+			ReduceC2::new(reduce_c, errors_c),
+		)
+	}
+}
+impl<P: DistributedPipe<Input>, F, S, Input, T, E> DistributedSink<Input> for MapWithErrors<P, F, S>
+where
+	F: FnMut<(P::Output,), Output = Result<T, E>> + Clone + ProcessSend + 'static,
+	S: DistributedSink<T>,
+	E: ProcessSend + 'static,
+{
+	type Done = (S::Done, Vec<E>);
+	type Pipe = MapWithErrors<P, F, S::Pipe>;
+	type ReduceA = ReduceA2<S::ReduceA, <Vec<E> as FromDistributedStream<E>>::ReduceA>;
+	type ReduceB = ReduceC2<S::ReduceB, <Vec<E> as FromDistributedStream<E>>::ReduceB>;
+	type ReduceC = ReduceC2<S::ReduceC, <Vec<E> as FromDistributedStream<E>>::ReduceC>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		let (pipe, reduce_a, reduce_b, reduce_c) = self.sink.reducers();
+		let (errors_a, errors_b, errors_c) = <Vec<E> as FromDistributedStream<E>>::reducers();
+		(
+			MapWithErrors::new(self.pipe, self.f, pipe),
+			ReduceA2::new(reduce_a, errors_a),
+			ReduceC2::new(reduce_b, errors_b),
+			ReduceC2::new(reduce_c, errors_c),
+		)
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct MapWithErrorsTask<A, F, B> {
+	task: A,
+	f: F,
+	sink: B,
+}
+impl<A: PipeTask<Input>, F, B, Input, T, E> PipeTask<Input> for MapWithErrorsTask<A, F, B>
+where
+	F: FnMut<(A::Output,), Output = Result<T, E>>,
+	B: PipeTask<T>,
+{
+	type Output = Sum2<B::Output, E>;
+	type Async = MapWithErrorsAsync<A::Async, F, B::Async, E>;
+
+	fn into_async(self) -> Self::Async {
+		MapWithErrorsAsync {
+			task: self.task.into_async(),
+			f: self.f,
+			sink: self.sink.into_async(),
+			errors: VecDeque::new(),
+			done: false,
+			marker: PhantomData,
+		}
+	}
+}
+
+/// Feeds the `Ok`s through the sink's pipe, yielding its output as
+/// `Sum2::A`, and yields the `Err`s as `Sum2::B`.
+#[pin_project]
+pub struct MapWithErrorsAsync<A, F, B, E> {
+	#[pin]
+	task: A,
+	f: F,
+	#[pin]
+	sink: B,
+	errors: VecDeque<E>,
+	done: bool,
+	marker: PhantomData<fn() -> E>,
+}
+
+impl<A: Pipe<Input>, F, B, Input, T, E> Pipe<Input> for MapWithErrorsAsync<A, F, B, E>
+where
+	F: FnMut<(A::Output,), Output = Result<T, E>>,
+	B: Pipe<T>,
+{
+	type Output = Sum2<B::Output, E>;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		loop {
+			if let Some(err) = self_.errors.pop_front() {
+				break Poll::Ready(Some(Sum2::B(err)));
+			}
+			if *self_.done {
+				break Poll::Ready(None);
+			}
+			let (task, f, errors) = (&mut self_.task, &mut *self_.f, &mut *self_.errors);
+			let oks = stream::poll_fn(|cx| {
+				let item = ready!(task.as_mut().poll_next(cx, stream.as_mut()));
+				match item.map(|item| f.call_mut((item,))) {
+					Some(Ok(item)) => Poll::Ready(Some(item)),
+					// Hand the error back to be yielded before continuing
+					Some(Err(err)) => {
+						errors.push_back(err);
+						cx.waker().wake_by_ref();
+						Poll::Pending
+					}
+					None => Poll::Ready(None),
+				}
+			});
+			pin_mut!(oks);
+			match self_.sink.as_mut().poll_next(cx, oks) {
+				Poll::Ready(Some(item)) => break Poll::Ready(Some(Sum2::A(item))),
+				Poll::Ready(None) => *self_.done = true,
+				Poll::Pending if self_.errors.is_empty() => break Poll::Pending,
+				Poll::Pending => (),
+			}
+		}
+	}
+}
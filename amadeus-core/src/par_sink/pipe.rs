@@ -7,7 +7,7 @@ use std::{
 
 use super::{DistributedPipe, DistributedSink, ParallelPipe, ParallelSink, PipeTask};
 use crate::{
-	par_stream::{ParallelStream, StreamTask}, pipe::{Pipe as _, PipePipe, StreamExt, StreamPipe}
+	par_stream::{ParallelStream, StreamTask}, pipe::{Pipe as _, PipePipe, StreamExt, StreamPipe}, plan::{short_name, Plan, Stage}
 };
 
 #[pin_project]
@@ -24,6 +24,9 @@ impl_par_dist! {
 		type Item = B::Output;
 		type Task = JoinTask<A::Task, B::Task>;
 
+		fn explain(&self) -> Plan {
+			self.a.explain().then_stage(Stage::of::<Self>().detail("pipe", short_name::<B>()))
+		}
 		#[inline(always)]
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			let self_ = self.project();
@@ -100,6 +103,9 @@ impl<A: StreamTask, B: PipeTask<A::Item>> StreamTask for JoinTask<A, B> {
 	fn into_async(self) -> Self::Async {
 		self.a.into_async().pipe(self.b.into_async())
 	}
+	fn cost(&self) -> Option<u64> {
+		self.a.cost()
+	}
 }
 
 impl<A: PipeTask<Input>, B: PipeTask<A::Output>, Input> PipeTask<Input> for JoinTask<A, B> {
@@ -0,0 +1,106 @@
+use derive_new::new;
+use educe::Educe;
+use indexmap::{map::Entry, IndexMap};
+use replace_with::replace_with_or_abort;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{hash::Hash, marker::PhantomData};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// Reduce the values of each key with the associative `f`. Each reducer keeps a
+/// single accumulator per key rather than a list of its values, and these are
+/// merged across partitions, so memory is proportional to the number of distinct
+/// keys rather than the number of items.
+#[derive(new)]
+#[must_use]
+pub struct ReduceByKey<P, F> {
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item, Output = (A, B)>, Item, F, A, B> ParallelSink<Item> for ReduceByKey<P, F>
+	where
+		F: FnMut<(B, B), Output = B> + Clone + Send + 'static,
+		A: Eq + Hash + Send + 'static,
+		B: Send + 'static,
+	{
+		folder_par_sink!(
+			ReduceByKeyFolder<F, StepA>,
+			ReduceByKeyFolder<F, StepB>,
+			self,
+			ReduceByKeyFolder::new(self.f.clone()),
+			ReduceByKeyFolder::new(self.f)
+		);
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "F: Clone"))]
+#[serde(
+	bound(serialize = "F: Serialize"),
+	bound(deserialize = "F: Deserialize<'de>")
+)]
+pub struct ReduceByKeyFolder<F, Step> {
+	f: F,
+	#[new(default)]
+	marker: PhantomData<fn() -> Step>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+impl<F, A, B> FolderSync<(A, B)> for ReduceByKeyFolder<F, StepA>
+where
+	F: FnMut<(B, B), Output = B>,
+	A: Eq + Hash,
+{
+	type State = IndexMap<A, B>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, (key, value): (A, B)) {
+		reduce(&mut self.f, state, key, value);
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<F, A, B> FolderSync<IndexMap<A, B>> for ReduceByKeyFolder<F, StepB>
+where
+	F: FnMut<(B, B), Output = B>,
+	A: Eq + Hash,
+{
+	type State = IndexMap<A, B>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: IndexMap<A, B>) {
+		for (key, value) in item {
+			reduce(&mut self.f, state, key, value);
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+
+fn reduce<F, A, B>(f: &mut F, state: &mut IndexMap<A, B>, key: A, value: B)
+where
+	F: FnMut<(B, B), Output = B>,
+	A: Eq + Hash,
+{
+	match state.entry(key) {
+		Entry::Occupied(entry) => {
+			replace_with_or_abort(entry.into_mut(), |acc| f.call_mut((acc, value)));
+		}
+		Entry::Vacant(entry) => {
+			let _ = entry.insert(value);
+		}
+	}
+}
@@ -0,0 +1,132 @@
+use derive_new::new;
+use futures::{future::BoxFuture, pin_mut, stream, Stream, StreamExt as _};
+use pin_project::pin_project;
+use std::{
+	error::Error, mem, pin::Pin, task::{Context, Poll}
+};
+
+use super::{Reducer, ReducerSend};
+use crate::{
+	pipe::{Sink, StreamExt}, pool::ThreadPool
+};
+
+/// A reducer that combines the partial results sent to it in a tree, spawning
+/// `reducer` on `pool` to combine them `arity` at a time, and then the results of
+/// those, and so on until one remains.
+///
+/// `reducer` must take and output the same type, and combine associatively and
+/// commutatively: partials are combined in the order they complete, `arity` at
+/// a time from whichever are ready, rather than in the order they were sent.
+#[derive(Clone, new)]
+pub struct ReduceTree<P, R> {
+	pool: P,
+	reducer: R,
+	arity: usize,
+}
+
+impl<P, R, T> Reducer<T> for ReduceTree<P, R>
+where
+	P: ThreadPool,
+	R: ReducerSend<T, Done = T> + Clone + Send + 'static,
+	T: Send + 'static,
+{
+	type Done = T;
+	type Async = ReduceTreeAsync<P, R, T>;
+
+	fn into_async(self) -> Self::Async {
+		assert!(self.arity >= 2, "arity must be at least 2");
+		ReduceTreeAsync::new(self.pool, self.reducer, self.arity)
+	}
+}
+impl<P, R, T> ReducerSend<T> for ReduceTree<P, R>
+where
+	P: ThreadPool,
+	R: ReducerSend<T, Done = T> + Clone + Send + 'static,
+	T: Send + 'static,
+{
+	type Done = T;
+}
+
+#[pin_project]
+#[derive(new)]
+pub struct ReduceTreeAsync<P, R, T> {
+	pool: P,
+	reducer: R,
+	arity: usize,
+	#[new(default)]
+	ready: Vec<T>,
+	#[new(default)]
+	running: Running<T>,
+	#[new(default)]
+	done: bool,
+}
+
+type Running<T> = stream::FuturesUnordered<BoxFuture<'static, Result<T, Box<dyn Error + Send>>>>;
+
+fn combine<P, R, T>(pool: &P, reducer: &R, running: &mut Running<T>, items: Vec<T>)
+where
+	P: ThreadPool,
+	R: ReducerSend<T, Done = T> + Clone + Send + 'static,
+	T: Send + 'static,
+{
+	let reducer = reducer.clone();
+	running.push(pool.spawn(move || async move {
+		let sink = reducer.into_async();
+		pin_mut!(sink);
+		stream::iter(items).sink(sink).await
+	}));
+}
+
+impl<P, R, T> Sink<T> for ReduceTreeAsync<P, R, T>
+where
+	P: ThreadPool,
+	R: ReducerSend<T, Done = T> + Clone + Send + 'static,
+	T: Send + 'static,
+{
+	type Done = T;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = T>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.project();
+		loop {
+			let mut progress = false;
+			while let Poll::Ready(Some(item)) = self_.running.poll_next_unpin(cx) {
+				let item = item.unwrap_or_else(|err| {
+					panic!("Amadeus: task '<unnamed>' panicked at '{}'", err)
+				});
+				self_.ready.push(item);
+				progress = true;
+			}
+			if !*self_.done {
+				match stream.as_mut().poll_next(cx) {
+					Poll::Ready(Some(item)) => {
+						self_.ready.push(item);
+						progress = true;
+					}
+					Poll::Ready(None) => {
+						*self_.done = true;
+						progress = true;
+					}
+					Poll::Pending => (),
+				}
+			}
+			while self_.ready.len() >= *self_.arity {
+				let items = self_.ready.drain(..*self_.arity).collect();
+				combine(self_.pool, self_.reducer, self_.running, items);
+			}
+			if *self_.done && self_.running.is_empty() {
+				if self_.ready.len() == 1 {
+					return Poll::Ready(self_.ready.pop().unwrap());
+				}
+				// Fewer than `arity` remain, or none were sent at all
+				let items = mem::take(self_.ready);
+				combine(self_.pool, self_.reducer, self_.running, items);
+				progress = true;
+			}
+			if !progress {
+				return Poll::Pending;
+			}
+		}
+	}
+}
@@ -105,6 +105,8 @@ where
 	}
 }
 
+/// Track the `n` most frequent items, with a count-min sketch of at most
+/// `max_counters` counters for the rest. See [`Top`].
 #[derive(new)]
 #[must_use]
 pub struct MostFrequent<P> {
@@ -112,6 +114,7 @@ pub struct MostFrequent<P> {
 	n: usize,
 	probability: f64,
 	tolerance: f64,
+	max_counters: usize,
 }
 
 impl_par_dist! {
@@ -123,8 +126,14 @@ impl_par_dist! {
 			MostFrequentFolder,
 			SumZeroFolder<Top<P::Output, usize>>,
 			self,
-			MostFrequentFolder::new(self.n, self.probability, self.tolerance),
-			SumZeroFolder::new(Top::new(self.n, self.probability, self.tolerance, ()))
+			MostFrequentFolder::new(self.n, self.probability, self.tolerance, self.max_counters),
+			SumZeroFolder::new(Top::with_max_counters(
+				self.n,
+				self.probability,
+				self.tolerance,
+				self.max_counters,
+				()
+			))
 		);
 	}
 }
@@ -134,6 +143,7 @@ pub struct MostFrequentFolder {
 	n: usize,
 	probability: f64,
 	tolerance: f64,
+	max_counters: usize,
 }
 
 impl<Item> FolderSync<Item> for MostFrequentFolder
@@ -144,7 +154,13 @@ where
 	type Done = Self::State;
 
 	fn zero(&mut self) -> Self::State {
-		Top::new(self.n, self.probability, self.tolerance, ())
+		Top::with_max_counters(
+			self.n,
+			self.probability,
+			self.tolerance,
+			self.max_counters,
+			(),
+		)
 	}
 	fn push(&mut self, state: &mut Self::State, item: Item) {
 		state.push(item, &1)
@@ -0,0 +1,112 @@
+use derive_new::new;
+use educe::Educe;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::marker::PhantomData;
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// The running totals of items tagged with their position in the source, as by
+/// [`SourceIndexed`](crate::par_stream::SourceIndexed), combined with `f` from
+/// `zero` across all partitions.
+///
+/// Each reducer computes the running totals of the partitions it's allocated,
+/// then once they're merged the partitions are ordered and each is offset by
+/// the total of those before it. For this to equal a sequential scan `f` must be
+/// associative and commutative, with `zero` as its identity.
+#[derive(new)]
+#[must_use]
+pub struct ScanGlobal<P, F, T> {
+	pipe: P,
+	zero: T,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item, Output = ((usize, usize), T)>, Item, F, T> ParallelSink<Item>
+		for ScanGlobal<P, F, T>
+	where
+		F: for<'a, 'b> FnMut<(&'a T, &'b T), Output = T> + Clone + Send + 'static,
+		T: Clone + Send + 'static,
+	{
+		folder_par_sink!(
+			ScanGlobalFolder<F, T, StepA>,
+			ScanGlobalFolder<F, T, StepB>,
+			self,
+			ScanGlobalFolder::new(self.zero.clone(), self.f.clone()),
+			ScanGlobalFolder::new(self.zero, self.f)
+		);
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "F: Clone, T: Clone"))]
+#[serde(
+	bound(serialize = "F: Serialize, T: Serialize"),
+	bound(deserialize = "F: Deserialize<'de>, T: Deserialize<'de>")
+)]
+pub struct ScanGlobalFolder<F, T, Step> {
+	zero: T,
+	f: F,
+	#[new(default)]
+	marker: PhantomData<fn() -> Step>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+impl<F, T> FolderSync<((usize, usize), T)> for ScanGlobalFolder<F, T, StepA>
+where
+	F: for<'a, 'b> FnMut<(&'a T, &'b T), Output = T>,
+{
+	/// The running totals of each partition, by the index of the partition.
+	type State = Vec<(usize, Vec<T>)>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, ((partition, _), item): ((usize, usize), T)) {
+		// A reducer's partitions are processed one after another, and the items
+		// of each in order
+		match state.last_mut() {
+			Some((last, totals)) if *last == partition => {
+				let total = self.f.call_mut((totals.last().unwrap(), &item));
+				totals.push(total);
+			}
+			_ => {
+				let total = self.f.call_mut((&self.zero, &item));
+				state.push((partition, vec![total]));
+			}
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<F, T> FolderSync<Vec<(usize, Vec<T>)>> for ScanGlobalFolder<F, T, StepB>
+where
+	F: for<'a, 'b> FnMut<(&'a T, &'b T), Output = T>,
+	T: Clone,
+{
+	type State = Vec<(usize, Vec<T>)>;
+	type Done = Vec<T>;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: Vec<(usize, Vec<T>)>) {
+		state.extend(item);
+	}
+	fn done(&mut self, mut state: Self::State) -> Self::Done {
+		state.sort_unstable_by_key(|&(partition, _)| partition);
+		let mut base = self.zero.clone();
+		let mut ret = Vec::with_capacity(state.iter().map(|(_, totals)| totals.len()).sum());
+		for (_, totals) in state {
+			let total = self.f.call_mut((&base, totals.last().unwrap()));
+			ret.extend(totals.iter().map(|local| self.f.call_mut((&base, local))));
+			base = total;
+		}
+		ret
+	}
+}
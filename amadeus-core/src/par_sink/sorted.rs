@@ -0,0 +1,365 @@
+use derive_new::new;
+use educe::Educe;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+	cmp::Reverse, collections::{BTreeSet, BinaryHeap}, fs, io::{self, BufReader, BufWriter, Write}, iter::FusedIterator, marker::PhantomData, mem, path::{Path, PathBuf}, vec
+};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// Sort all items with an external merge sort. Each reducer buffers up to `budget`
+/// items, sorting and spilling them to a file in `dir` whenever the buffer fills.
+/// The sorted runs are then lazily merged by the returned [`SortedSpillIter`].
+///
+/// The spill files are only readable by the process that wrote them, so this is
+/// not available for distributed streams.
+#[derive(new)]
+#[must_use]
+pub struct SortedSpill<P> {
+	pipe: P,
+	budget: usize,
+	dir: PathBuf,
+}
+
+impl<P: ParallelPipe<Item>, Item> ParallelSink<Item> for SortedSpill<P>
+where
+	P::Output: Ord + Serialize + DeserializeOwned + Send + 'static,
+{
+	folder_par_sink!(
+		SortedSpillFolder<P::Output, StepA>,
+		SortedSpillFolder<P::Output, StepB>,
+		self,
+		SortedSpillFolder::new(self.budget, self.dir.clone()),
+		SortedSpillFolder::new(self.budget, self.dir)
+	);
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone)]
+#[serde(bound = "")]
+pub struct SortedSpillFolder<T, Step> {
+	budget: usize,
+	dir: PathBuf,
+	marker: PhantomData<fn() -> (T, Step)>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+pub struct SortedSpillState<T> {
+	buffer: Vec<T>,
+	runs: Vec<Run<T>>,
+}
+
+impl<T> FolderSync<T> for SortedSpillFolder<T, StepA>
+where
+	T: Ord + Serialize,
+{
+	type State = SortedSpillState<T>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		SortedSpillState {
+			buffer: Vec::new(),
+			runs: Vec::new(),
+		}
+	}
+	fn push(&mut self, state: &mut Self::State, item: T) {
+		state.buffer.push(item);
+		if state.buffer.len() >= self.budget {
+			state.buffer.sort();
+			let file = SpillFile::write(&self.dir, &state.buffer)
+				.unwrap_or_else(|err| panic!("Amadeus: failed to spill sorted run: {}", err));
+			state.buffer.clear();
+			state.runs.push(Run::File(file, PhantomData));
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<T> FolderSync<SortedSpillState<T>> for SortedSpillFolder<T, StepB>
+where
+	T: Ord + DeserializeOwned,
+{
+	type State = Vec<Run<T>>;
+	type Done = SortedSpillIter<T>;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, mut item: SortedSpillState<T>) {
+		state.append(&mut item.runs);
+		if !item.buffer.is_empty() {
+			item.buffer.sort();
+			state.push(Run::Memory(item.buffer));
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		SortedSpillIter::new(state)
+	}
+}
+
+/// Deduplicate all items with an external merge. Each reducer holds up to
+/// `budget` unique items, spilling them as a sorted run to a file in `dir`
+/// whenever it fills. The runs are then lazily merged, skipping duplicates, by
+/// the returned [`DistinctSpillIter`].
+///
+/// As with [`SortedSpill`], this is not available for distributed streams.
+#[derive(new)]
+#[must_use]
+pub struct DistinctSpill<P> {
+	pipe: P,
+	budget: usize,
+	dir: PathBuf,
+}
+
+impl<P: ParallelPipe<Item>, Item> ParallelSink<Item> for DistinctSpill<P>
+where
+	P::Output: Ord + Serialize + DeserializeOwned + Send + 'static,
+{
+	folder_par_sink!(
+		DistinctSpillFolder<P::Output, StepA>,
+		DistinctSpillFolder<P::Output, StepB>,
+		self,
+		DistinctSpillFolder::new(self.budget, self.dir.clone()),
+		DistinctSpillFolder::new(self.budget, self.dir)
+	);
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone)]
+#[serde(bound = "")]
+pub struct DistinctSpillFolder<T, Step> {
+	budget: usize,
+	dir: PathBuf,
+	marker: PhantomData<fn() -> (T, Step)>,
+}
+
+pub struct DistinctSpillState<T> {
+	set: BTreeSet<T>,
+	runs: Vec<Run<T>>,
+}
+
+impl<T> FolderSync<T> for DistinctSpillFolder<T, StepA>
+where
+	T: Ord + Serialize,
+{
+	type State = DistinctSpillState<T>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		DistinctSpillState {
+			set: BTreeSet::new(),
+			runs: Vec::new(),
+		}
+	}
+	fn push(&mut self, state: &mut Self::State, item: T) {
+		let _ = state.set.insert(item);
+		if state.set.len() >= self.budget {
+			let run = mem::take(&mut state.set).into_iter().collect::<Vec<_>>();
+			let file = SpillFile::write(&self.dir, &run)
+				.unwrap_or_else(|err| panic!("Amadeus: failed to spill distinct run: {}", err));
+			state.runs.push(Run::File(file, PhantomData));
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<T> FolderSync<DistinctSpillState<T>> for DistinctSpillFolder<T, StepB>
+where
+	T: Ord + DeserializeOwned,
+{
+	type State = Vec<Run<T>>;
+	type Done = DistinctSpillIter<T>;
+
+	fn zero(&mut self) -> Self::State {
+		Vec::new()
+	}
+	fn push(&mut self, state: &mut Self::State, mut item: DistinctSpillState<T>) {
+		state.append(&mut item.runs);
+		if !item.set.is_empty() {
+			state.push(Run::Memory(item.set.into_iter().collect()));
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		DistinctSpillIter {
+			iter: SortedSpillIter::new(state),
+			next: None,
+		}
+	}
+}
+
+pub enum Run<T> {
+	Memory(Vec<T>),
+	File(SpillFile, PhantomData<fn() -> T>),
+}
+
+/// A file holding a sorted run, deleted on drop.
+pub struct SpillFile {
+	path: PathBuf,
+	len: usize,
+}
+impl SpillFile {
+	fn write<T: Serialize>(dir: &Path, items: &[T]) -> io::Result<Self> {
+		let (path, file) = loop {
+			let path = dir.join(format!("amadeus-sort-{:016x}.run", rand::random::<u64>()));
+			match fs::OpenOptions::new()
+				.write(true)
+				.create_new(true)
+				.open(&path)
+			{
+				Ok(file) => break (path, file),
+				Err(err) if err.kind() == io::ErrorKind::AlreadyExists => (),
+				Err(err) => return Err(err),
+			}
+		};
+		let spill_file = Self {
+			path,
+			len: items.len(),
+		};
+		let mut writer = BufWriter::new(file);
+		for item in items {
+			bincode::serialize_into(&mut writer, item).map_err(|err| into_io_error(*err))?;
+		}
+		writer.flush()?;
+		Ok(spill_file)
+	}
+}
+impl Drop for SpillFile {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+fn into_io_error(err: bincode::ErrorKind) -> io::Error {
+	match err {
+		bincode::ErrorKind::Io(err) => err,
+		err => io::Error::new(io::ErrorKind::InvalidData, err),
+	}
+}
+
+enum RunReader<T> {
+	Memory(vec::IntoIter<T>),
+	File {
+		reader: BufReader<fs::File>,
+		remaining: usize,
+		_file: SpillFile,
+	},
+}
+impl<T> Iterator for RunReader<T>
+where
+	T: DeserializeOwned,
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		match self {
+			Self::Memory(iter) => iter.next(),
+			Self::File {
+				reader, remaining, ..
+			} => {
+				if *remaining == 0 {
+					return None;
+				}
+				*remaining -= 1;
+				Some(bincode::deserialize_from(reader).unwrap_or_else(|err| {
+					panic!("Amadeus: failed to read spilled sorted run: {}", err)
+				}))
+			}
+		}
+	}
+}
+
+/// The items of a [`SortedSpill`] in ascending order, merged from its sorted runs.
+///
+/// # Panics
+///
+/// Panics if a spilled run can't be read back.
+pub struct SortedSpillIter<T> {
+	runs: Vec<RunReader<T>>,
+	heap: BinaryHeap<Reverse<(T, usize)>>,
+}
+impl<T> SortedSpillIter<T>
+where
+	T: Ord + DeserializeOwned,
+{
+	fn new(runs: Vec<Run<T>>) -> Self {
+		let mut runs = runs
+			.into_iter()
+			.map(|run| match run {
+				Run::Memory(items) => Ok(RunReader::Memory(items.into_iter())),
+				Run::File(file, _) => Ok(RunReader::File {
+					reader: BufReader::new(fs::File::open(&file.path)?),
+					remaining: file.len,
+					_file: file,
+				}),
+			})
+			.collect::<io::Result<Vec<_>>>()
+			.unwrap_or_else(|err| panic!("Amadeus: failed to open spilled sorted run: {}", err));
+		let heap = runs
+			.iter_mut()
+			.enumerate()
+			.filter_map(|(i, run)| run.next().map(|item| Reverse((item, i))))
+			.collect();
+		Self { runs, heap }
+	}
+}
+impl<T> Iterator for SortedSpillIter<T>
+where
+	T: Ord + DeserializeOwned,
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		let Reverse((item, i)) = self.heap.pop()?;
+		if let Some(next) = self.runs[i].next() {
+			self.heap.push(Reverse((next, i)));
+		}
+		Some(item)
+	}
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let len = self.heap.len()
+			+ self
+				.runs
+				.iter()
+				.map(|run| match run {
+					RunReader::Memory(iter) => iter.len(),
+					RunReader::File { remaining, .. } => *remaining,
+				})
+				.sum::<usize>();
+		(len, Some(len))
+	}
+}
+impl<T> FusedIterator for SortedSpillIter<T> where T: Ord + DeserializeOwned {}
+
+/// The unique items of a [`DistinctSpill`] in ascending order.
+///
+/// # Panics
+///
+/// Panics if a spilled run can't be read back.
+pub struct DistinctSpillIter<T> {
+	iter: SortedSpillIter<T>,
+	next: Option<T>,
+}
+impl<T> Iterator for DistinctSpillIter<T>
+where
+	T: Ord + DeserializeOwned,
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		let item = self.next.take().or_else(|| self.iter.next())?;
+		loop {
+			match self.iter.next() {
+				Some(next) if next == item => (),
+				next => {
+					self.next = next;
+					break Some(item);
+				}
+			}
+		}
+	}
+}
+impl<T> FusedIterator for DistinctSpillIter<T> where T: Ord + DeserializeOwned {}
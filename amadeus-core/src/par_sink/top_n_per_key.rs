@@ -0,0 +1,148 @@
+#![allow(clippy::type_complexity)]
+
+use derive_new::new;
+use educe::Educe;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	cmp::{Ordering, Reverse}, collections::BinaryHeap, hash::Hash, marker::PhantomData
+};
+
+use super::{folder_par_sink, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+
+/// Keep the `n` highest-scoring items of each key. Each reducer keeps a bounded
+/// heap per key, and these are merged across partitions. Each key's items are
+/// returned in descending order of score; which of several items with equal
+/// scores survive is unspecified.
+#[derive(new)]
+#[must_use]
+pub struct TopNPerKey<P, K, S> {
+	pipe: P,
+	n: usize,
+	key: K,
+	score: S,
+}
+
+impl_par_dist! {
+	impl<P: ParallelPipe<Item>, Item, K, S, A, B> ParallelSink<Item> for TopNPerKey<P, K, S>
+	where
+		K: for<'a> FnMut<(&'a P::Output,), Output = A> + Clone + Send + 'static,
+		S: for<'a> FnMut<(&'a P::Output,), Output = B> + Clone + Send + 'static,
+		A: Eq + Hash + Send + 'static,
+		B: Ord + Send + 'static,
+		P::Output: Send + 'static,
+	{
+		folder_par_sink!(
+			TopNPerKeyFolder<K, S, A, B, StepA>,
+			TopNPerKeyFolder<(), (), A, B, StepB>,
+			self,
+			TopNPerKeyFolder::new(self.n, self.key, self.score),
+			TopNPerKeyFolder::new(self.n, (), ())
+		);
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "K: Clone, S: Clone"))]
+#[serde(
+	bound(serialize = "K: Serialize, S: Serialize"),
+	bound(deserialize = "K: Deserialize<'de>, S: Deserialize<'de>")
+)]
+pub struct TopNPerKeyFolder<K, S, A, B, Step> {
+	n: usize,
+	key: K,
+	score: S,
+	marker: PhantomData<fn() -> (A, B, Step)>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+type Heaps<A, B, T> = IndexMap<A, BinaryHeap<Reverse<Scored<B, T>>>>;
+
+impl<K, S, A, B, T> FolderSync<T> for TopNPerKeyFolder<K, S, A, B, StepA>
+where
+	K: for<'a> FnMut<(&'a T,), Output = A>,
+	S: for<'a> FnMut<(&'a T,), Output = B>,
+	A: Eq + Hash,
+	B: Ord,
+{
+	type State = Heaps<A, B, T>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: T) {
+		let key = self.key.call_mut((&item,));
+		let score = self.score.call_mut((&item,));
+		push(state.entry(key).or_default(), self.n, Scored(score, item));
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<A, B, T> FolderSync<Heaps<A, B, T>> for TopNPerKeyFolder<(), (), A, B, StepB>
+where
+	A: Eq + Hash,
+	B: Ord,
+{
+	type State = Heaps<A, B, T>;
+	type Done = IndexMap<A, Vec<T>>;
+
+	fn zero(&mut self) -> Self::State {
+		IndexMap::new()
+	}
+	fn push(&mut self, state: &mut Self::State, item: Heaps<A, B, T>) {
+		for (key, heap) in item {
+			let state = state.entry(key).or_default();
+			for Reverse(item) in heap {
+				push(state, self.n, item);
+			}
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+			.into_iter()
+			.map(|(key, heap)| {
+				let items = heap
+					.into_sorted_vec()
+					.into_iter()
+					.map(|Reverse(Scored(_, item))| item)
+					.collect();
+				(key, items)
+			})
+			.collect()
+	}
+}
+
+/// Push onto a min-heap of at most `n` items, evicting the lowest score.
+fn push<B: Ord, T>(heap: &mut BinaryHeap<Reverse<Scored<B, T>>>, n: usize, item: Scored<B, T>) {
+	if heap.len() < n {
+		heap.push(Reverse(item));
+	} else if heap.peek().map_or(false, |Reverse(min)| min.0 < item.0) {
+		let _ = heap.pop();
+		heap.push(Reverse(item));
+	}
+}
+
+/// An item ordered only by its score.
+#[derive(Serialize, Deserialize)]
+pub struct Scored<B, T>(B, T);
+impl<B: Ord, T> PartialEq for Scored<B, T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.0 == other.0
+	}
+}
+impl<B: Ord, T> Eq for Scored<B, T> {}
+impl<B: Ord, T> PartialOrd for Scored<B, T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl<B: Ord, T> Ord for Scored<B, T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.0.cmp(&other.0)
+	}
+}
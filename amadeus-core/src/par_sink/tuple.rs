@@ -47,13 +47,13 @@ where
 }
 
 macro_rules! impl_tuple {
-	($reducea:ident $reduceaasync:ident $reduceb:ident $reducebasync:ident $async:ident $enum:ident $join:ident $($copy:ident)? : $($num:tt $t:ident $s:ident $i:ident $r:ident $o:ident $c:ident $iterator:ident $reducera:ident $reducerb:ident $($copyb:ident)? , $comma:tt)*) => (
+	($reducea:ident $reduceaasync:ident $reduceb:ident $reducebasync:ident $async:ident $enum:ident $join:ident $($clone:ident)? : $($num:tt $t:ident $s:ident $i:ident $r:ident $o:ident $c:ident $iterator:ident $reducera:ident $reducerb:ident $($cloneb:ident)? , $comma:tt)*) => (
 		impl<
 				Item,
 				$($r: ParallelSink<Item, Done = $o>,)*
 				$($o,)*
 			> ParallelSink<Item> for ($($r,)*)
-				where Item: $($copy)*,
+				where Item: $($clone)*,
 		{
 			type Done = ($($o,)*);
 			type Pipe = ($($r::Pipe,)*);
@@ -74,7 +74,7 @@ macro_rules! impl_tuple {
 				$($r: DistributedSink<Item, Done = $o>,)*
 				$($o,)*
 			> DistributedSink<Item> for ($($r,)*)
-				where Item: $($copy)*,
+				where Item: $($clone)*,
 		{
 			type Done = ($($o,)*);
 			type Pipe = ($($r::Pipe,)*);
@@ -95,7 +95,7 @@ macro_rules! impl_tuple {
 
 		impl<Input, $($i: ParallelPipe<Input>,)*>
 			ParallelPipe<Input> for ($($i,)*)
-				where Input: $($copy)*,
+				where Input: $($clone)*,
 		{
 			type Output = $enum<$($i::Output,)*>;
 			type Task = ($($i::Task,)*);
@@ -106,7 +106,7 @@ macro_rules! impl_tuple {
 		}
 		impl<Input, $($i: DistributedPipe<Input>,)*>
 			DistributedPipe<Input> for ($($i,)*)
-				where Input: $($copy)*,
+				where Input: $($clone)*,
 		{
 			type Output = $enum<$($i::Output,)*>;
 			type Task = ($($i::Task,)*);
@@ -118,7 +118,7 @@ macro_rules! impl_tuple {
 
 		impl<Input, $($c: PipeTask<Input>,)*> PipeTask<Input> for ($($c,)*)
 		where
-			Input: $($copy)*,
+			Input: $($clone)*,
 		{
 			type Output = $enum<$($c::Output,)*>;
 			type Async = $async<Input, $($c::Async,)*>;
@@ -142,7 +142,7 @@ macro_rules! impl_tuple {
 		#[allow(unused_variables)]
 		impl<Input, $($c: Pipe<Input>,)*> Pipe<Input> for $async<Input, $($c,)*>
 		where
-			Input: $($copy)*,
+			Input: $($clone)*,
 		{
 			type Output = $enum<$($c::Output,)*>;
 
@@ -151,7 +151,7 @@ macro_rules! impl_tuple {
 				self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
 			) -> Poll<Option<Self::Output>> {
 				let mut self_ = self.project();
-				// buffer, copy to each
+				// buffer, clone to each
 				loop {
 					if self_.pending.is_none() {
 						*self_.pending = Some(ready!(stream.as_mut().poll_next(cx)));
@@ -164,8 +164,8 @@ macro_rules! impl_tuple {
 							if !*given {
 								*given = true;
 								$(
-									return Poll::Ready(*pending);
-									let $copyb = ();
+									return Poll::Ready(pending.clone());
+									let $cloneb = ();
 								)?
 								Poll::Ready(pending.take())
 							} else {
@@ -337,13 +337,13 @@ macro_rules! impl_tuple {
 }
 impl_tuple!(ReduceA0 ReduceA0Async ReduceC0 ReduceC0Async AsyncTuple0 Sum0 Join0:);
 impl_tuple!(ReduceA1 ReduceA1Async ReduceC1 ReduceC1Async AsyncTuple1 Sum1 Join1: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0,,);
-impl_tuple!(ReduceA2 ReduceA2Async ReduceC2 ReduceC2Async AsyncTuple2 Sum2 Join2 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,,);
-impl_tuple!(ReduceA3 ReduceA3Async ReduceC3 ReduceC3Async AsyncTuple3 Sum3 Join3 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Copy,,);
-impl_tuple!(ReduceA4 ReduceA4Async ReduceC4 ReduceC4Async AsyncTuple4 Sum4 Join4 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Copy,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Copy,,);
-impl_tuple!(ReduceA5 ReduceA5Async ReduceC5 ReduceC5Async AsyncTuple5 Sum5 Join5 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Copy,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Copy,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Copy,,);
-impl_tuple!(ReduceA6 ReduceA6Async ReduceC6 ReduceC6Async AsyncTuple6 Sum6 Join6 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Copy,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Copy,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Copy,, 5 F S5 I5 R5 O5 C5 iterator_5 reducer_a_5 reducer_b_5 Copy,,);
-impl_tuple!(ReduceA7 ReduceA7Async ReduceC7 ReduceC7Async AsyncTuple7 Sum7 Join7 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Copy,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Copy,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Copy,, 5 F S5 I5 R5 O5 C5 iterator_5 reducer_a_5 reducer_b_5 Copy,, 6 G S6 I6 R6 O6 C6 iterator_6 reducer_a_6 reducer_b_6 Copy,,);
-impl_tuple!(ReduceA8 ReduceA8Async ReduceC8 ReduceC8Async AsyncTuple8 Sum8 Join8 Copy: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Copy,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Copy,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Copy,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Copy,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Copy,, 5 F S5 I5 R5 O5 C5 iterator_5 reducer_a_5 reducer_b_5 Copy,, 6 G S6 I6 R6 O6 C6 iterator_6 reducer_a_6 reducer_b_6 Copy,, 7 H S7 I7 R7 O7 C7 iterator_7 reducer_a_7 reducer_b_7 Copy,,);
+impl_tuple!(ReduceA2 ReduceA2Async ReduceC2 ReduceC2Async AsyncTuple2 Sum2 Join2 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,,);
+impl_tuple!(ReduceA3 ReduceA3Async ReduceC3 ReduceC3Async AsyncTuple3 Sum3 Join3 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Clone,,);
+impl_tuple!(ReduceA4 ReduceA4Async ReduceC4 ReduceC4Async AsyncTuple4 Sum4 Join4 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Clone,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Clone,,);
+impl_tuple!(ReduceA5 ReduceA5Async ReduceC5 ReduceC5Async AsyncTuple5 Sum5 Join5 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Clone,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Clone,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Clone,,);
+impl_tuple!(ReduceA6 ReduceA6Async ReduceC6 ReduceC6Async AsyncTuple6 Sum6 Join6 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Clone,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Clone,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Clone,, 5 F S5 I5 R5 O5 C5 iterator_5 reducer_a_5 reducer_b_5 Clone,,);
+impl_tuple!(ReduceA7 ReduceA7Async ReduceC7 ReduceC7Async AsyncTuple7 Sum7 Join7 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Clone,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Clone,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Clone,, 5 F S5 I5 R5 O5 C5 iterator_5 reducer_a_5 reducer_b_5 Clone,, 6 G S6 I6 R6 O6 C6 iterator_6 reducer_a_6 reducer_b_6 Clone,,);
+impl_tuple!(ReduceA8 ReduceA8Async ReduceC8 ReduceC8Async AsyncTuple8 Sum8 Join8 Clone: 0 A S0 I0 R0 O0 C0 iterator_0 reducer_a_0 reducer_b_0 Clone,, 1 B S1 I1 R1 O1 C1 iterator_1 reducer_a_1 reducer_b_1 Clone,, 2 C S2 I2 R2 O2 C2 iterator_2 reducer_a_2 reducer_b_2 Clone,, 3 D S3 I3 R3 O3 C3 iterator_3 reducer_a_3 reducer_b_3 Clone,, 4 E S4 I4 R4 O4 C4 iterator_4 reducer_a_4 reducer_b_4 Clone,, 5 F S5 I5 R5 O5 C5 iterator_5 reducer_a_5 reducer_b_5 Clone,, 6 G S6 I6 R6 O6 C6 iterator_6 reducer_a_6 reducer_b_6 Clone,, 7 H S7 I7 R7 O7 C7 iterator_7 reducer_a_7 reducer_b_7 Clone,,);
 
 #[pin_project(project = PeekableProj)]
 #[derive(Debug)]
@@ -0,0 +1,409 @@
+use derive_new::new;
+use educe::Educe;
+use futures::{channel::mpsc, future::LocalBoxFuture, ready, FutureExt, Stream};
+use serde::{Deserialize, Serialize};
+use std::{
+	convert::TryFrom, io, mem, pin::Pin, task::{Context, Poll}
+};
+
+use super::{
+	DistributedPipe, DistributedSink, FolderSync, FolderSyncReducer, Inter, ParallelPipe, ParallelSink, Reducer, ReducerSend
+};
+use crate::{file::Page, pipe::Sink, pool::ProcessSend, util::IoError};
+
+/// The size of the chunks that each thread encodes items into before sending
+/// them to be written.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Serializes items to bytes for [`WritePage`], e.g. as a CSV or JSON line.
+pub trait Encoder<Item> {
+	fn encode(&mut self, item: Item, buf: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Encode each item with [`bincode`], preceded by its length as a little-endian
+/// `u64`.
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Debug)]
+pub struct LengthPrefixed;
+impl<Item> Encoder<Item> for LengthPrefixed
+where
+	Item: Serialize,
+{
+	fn encode(&mut self, item: Item, buf: &mut Vec<u8>) -> io::Result<()> {
+		let len = bincode::serialized_size(&item).map_err(|err| into_io_error(*err))?;
+		buf.extend_from_slice(&len.to_le_bytes());
+		bincode::serialize_into(buf, &item).map_err(|err| into_io_error(*err))
+	}
+}
+
+fn into_io_error(err: bincode::ErrorKind) -> io::Error {
+	match err {
+		bincode::ErrorKind::Io(err) => err,
+		err => io::Error::new(io::ErrorKind::InvalidData, err),
+	}
+}
+
+/// Write all items, serialized by `encoder`, to `page`, returning the number of
+/// bytes written.
+///
+/// On a [`ThreadPool`](crate::pool::ThreadPool) each thread encodes items into
+/// chunks of about a megabyte, which are sent back and appended to `page` as
+/// they fill, so only a couple of chunks per thread are held in memory at a
+/// time. On a [`ProcessPool`](crate::pool::ProcessPool) each process's output
+/// is instead buffered and appended whole. Either way items are contiguous but
+/// not ordered.
+#[derive(new)]
+#[must_use]
+pub struct WritePage<P, Pg, E> {
+	pipe: P,
+	page: Pg,
+	encoder: E,
+}
+
+impl<P: ParallelPipe<Item>, Item, Pg, E> ParallelSink<Item> for WritePage<P, Pg, E>
+where
+	Pg: Page + 'static,
+	E: Encoder<P::Output> + Clone + Send + 'static,
+{
+	type Done = Result<u64, IoError>;
+	type Pipe = P;
+	type ReduceA = EncodeChunksReducer<E>;
+	type ReduceC = WriteChunksReducer<Pg>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		// Each sender has a slot of its own, so each thread can have a chunk
+		// queued while it encodes the next
+		let (sender, receiver) = mpsc::channel(0);
+		(
+			self.pipe,
+			EncodeChunksReducer {
+				encoder: self.encoder,
+				sender,
+			},
+			WriteChunksReducer {
+				page: self.page,
+				receiver,
+			},
+		)
+	}
+}
+impl<P: DistributedPipe<Item>, Item, Pg, E> DistributedSink<Item> for WritePage<P, Pg, E>
+where
+	Pg: Page + 'static,
+	E: Encoder<P::Output> + Clone + ProcessSend + 'static,
+{
+	type Done = Result<u64, IoError>;
+	type Pipe = P;
+	type ReduceA = FolderSyncReducer<P::Output, EncodeFolder<E>, Inter>;
+	type ReduceB = FolderSyncReducer<Result<Vec<u8>, IoError>, ConcatFolder, Inter>;
+	type ReduceC = WritePageReducer<Pg>;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		(
+			self.pipe,
+			FolderSyncReducer::new(EncodeFolder::new(self.encoder)),
+			FolderSyncReducer::new(ConcatFolder),
+			WritePageReducer(self.page),
+		)
+	}
+}
+
+#[derive(Educe)]
+#[educe(Clone(bound = "E: Clone"))]
+pub struct EncodeChunksReducer<E> {
+	encoder: E,
+	sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl<Item, E> Reducer<Item> for EncodeChunksReducer<E>
+where
+	E: Encoder<Item>,
+{
+	type Done = Result<(), IoError>;
+	type Async = EncodeChunksReducerAsync<E>;
+
+	fn into_async(self) -> Self::Async {
+		EncodeChunksReducerAsync {
+			encoder: self.encoder,
+			sender: self.sender,
+			buf: Vec::new(),
+			full: false,
+			done: false,
+			result: Some(Ok(())),
+		}
+	}
+}
+impl<Item, E> ReducerSend<Item> for EncodeChunksReducer<E>
+where
+	E: Encoder<Item>,
+{
+	type Done = Result<(), IoError>;
+}
+
+/// Encodes items into a chunk, sending it to be written once it's full. Once an
+/// encoding has failed, further items are dropped.
+pub struct EncodeChunksReducerAsync<E> {
+	encoder: E,
+	sender: mpsc::Sender<Vec<u8>>,
+	buf: Vec<u8>,
+	full: bool,
+	done: bool,
+	result: Option<Result<(), IoError>>,
+}
+// `encoder` is never pinned
+impl<E> Unpin for EncodeChunksReducerAsync<E> {}
+
+impl<Item, E> Sink<Item> for EncodeChunksReducerAsync<E>
+where
+	E: Encoder<Item>,
+{
+	type Done = Result<(), IoError>;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Item>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.get_mut();
+		loop {
+			if self_.full {
+				// An error means the writer has gone, so there's nowhere to send it
+				if ready!(self_.sender.poll_ready(cx)).is_ok() {
+					let _ = self_.sender.start_send(mem::take(&mut self_.buf));
+				}
+				self_.buf.clear();
+				self_.full = false;
+			}
+			if self_.done {
+				break Poll::Ready(self_.result.take().unwrap());
+			}
+			match ready!(stream.as_mut().poll_next(cx)) {
+				Some(item) if matches!(self_.result, Some(Ok(()))) => {
+					if let Err(err) = self_.encoder.encode(item, &mut self_.buf) {
+						self_.result = Some(Err(err.into()));
+					} else if self_.buf.len() >= CHUNK_SIZE {
+						self_.full = true;
+					}
+				}
+				Some(_) => (),
+				None => {
+					self_.full = matches!(self_.result, Some(Ok(()))) && !self_.buf.is_empty();
+					self_.done = true;
+				}
+			}
+		}
+	}
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "E: Clone"))]
+pub struct EncodeFolder<E> {
+	encoder: E,
+}
+
+impl<Item, E> FolderSync<Item> for EncodeFolder<E>
+where
+	E: Encoder<Item>,
+{
+	type State = Result<Vec<u8>, IoError>;
+	type Done = Self::State;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		Ok(Vec::new())
+	}
+
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, item: Item) {
+		if let Ok(buf) = state {
+			if let Err(err) = self.encoder.encode(item, buf) {
+				*state = Err(err.into());
+			}
+		}
+	}
+
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConcatFolder;
+
+impl FolderSync<Result<Vec<u8>, IoError>> for ConcatFolder {
+	type State = Result<Vec<u8>, IoError>;
+	type Done = Self::State;
+
+	#[inline(always)]
+	fn zero(&mut self) -> Self::State {
+		Ok(Vec::new())
+	}
+
+	#[inline(always)]
+	fn push(&mut self, state: &mut Self::State, item: Result<Vec<u8>, IoError>) {
+		match (state, item) {
+			(Ok(state), Ok(mut item)) => state.append(&mut item),
+			(state @ Ok(_), Err(err)) => *state = Err(err),
+			(Err(_), _) => (),
+		}
+	}
+
+	#[inline(always)]
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+
+pub struct WriteChunksReducer<Pg> {
+	page: Pg,
+	receiver: mpsc::Receiver<Vec<u8>>,
+}
+
+impl<Pg> Reducer<Result<(), IoError>> for WriteChunksReducer<Pg>
+where
+	Pg: Page + 'static,
+{
+	type Done = Result<u64, IoError>;
+	type Async = WriteChunksReducerAsync<Pg>;
+
+	fn into_async(self) -> Self::Async {
+		WriteChunksReducerAsync {
+			appender: Appender::new(self.page),
+			receiver: self.receiver,
+			done: false,
+		}
+	}
+}
+
+/// Appends each chunk to the page as it's received, while waiting for the
+/// threads encoding them to finish.
+pub struct WriteChunksReducerAsync<Pg> {
+	appender: Appender<Pg>,
+	receiver: mpsc::Receiver<Vec<u8>>,
+	done: bool,
+}
+
+impl<Pg> Sink<Result<(), IoError>> for WriteChunksReducerAsync<Pg>
+where
+	Pg: Page + 'static,
+{
+	type Done = Result<u64, IoError>;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context,
+		mut stream: Pin<&mut impl Stream<Item = Result<(), IoError>>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.get_mut();
+		loop {
+			ready!(self_.appender.poll_pending(cx));
+			if let Poll::Ready(Some(buf)) = Pin::new(&mut self_.receiver).poll_next(cx) {
+				self_.appender.append(buf);
+				continue;
+			}
+			if self_.done {
+				// Every thread has finished, so all their chunks have been received
+				break Poll::Ready(self_.appender.finish());
+			}
+			match ready!(stream.as_mut().poll_next(cx)) {
+				Some(Ok(())) => (),
+				Some(Err(err)) => self_.appender.fail(err),
+				None => self_.done = true,
+			}
+		}
+	}
+}
+
+pub struct WritePageReducer<Pg>(Pg);
+
+impl<Pg> Reducer<Result<Vec<u8>, IoError>> for WritePageReducer<Pg>
+where
+	Pg: Page + 'static,
+{
+	type Done = Result<u64, IoError>;
+	type Async = WritePageReducerAsync<Pg>;
+
+	fn into_async(self) -> Self::Async {
+		WritePageReducerAsync(Appender::new(self.0))
+	}
+}
+
+/// Appends each buffer to the page once the previous write has completed.
+pub struct WritePageReducerAsync<Pg>(Appender<Pg>);
+
+impl<Pg> Sink<Result<Vec<u8>, IoError>> for WritePageReducerAsync<Pg>
+where
+	Pg: Page + 'static,
+{
+	type Done = Result<u64, IoError>;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context,
+		mut stream: Pin<&mut impl Stream<Item = Result<Vec<u8>, IoError>>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.get_mut();
+		loop {
+			ready!(self_.0.poll_pending(cx));
+			match ready!(stream.as_mut().poll_next(cx)) {
+				Some(Ok(buf)) => self_.0.append(buf),
+				Some(Err(err)) => self_.0.fail(err),
+				None => break Poll::Ready(self_.0.finish()),
+			}
+		}
+	}
+}
+
+/// Writes buffers one after another to the end of a page. Once a write or an
+/// encoding has failed, further buffers are dropped.
+struct Appender<Pg> {
+	page: Pg,
+	offset: u64,
+	pending: Option<LocalBoxFuture<'static, Result<(), IoError>>>,
+	result: Option<Result<(), IoError>>,
+}
+// `page` is never pinned
+impl<Pg> Unpin for Appender<Pg> {}
+
+impl<Pg> Appender<Pg>
+where
+	Pg: Page + 'static,
+{
+	fn new(page: Pg) -> Self {
+		Self {
+			page,
+			offset: 0,
+			pending: None,
+			result: Some(Ok(())),
+		}
+	}
+	fn poll_pending(&mut self, cx: &mut Context) -> Poll<()> {
+		if let Some(pending) = &mut self.pending {
+			let res = ready!(pending.as_mut().poll(cx));
+			self.pending = None;
+			if let Err(err) = res {
+				self.fail(err);
+			}
+		}
+		Poll::Ready(())
+	}
+	/// Must only be called once [`poll_pending`](Self::poll_pending) is ready.
+	fn append(&mut self, buf: Vec<u8>) {
+		if buf.is_empty() || !matches!(self.result, Some(Ok(()))) {
+			return;
+		}
+		let offset = self.offset;
+		self.offset += u64::try_from(buf.len()).unwrap();
+		let write = self.page.write(offset, buf.into_boxed_slice());
+		self.pending = Some(
+			write
+				.map(|res| res.map_err(|err| err.into().into()))
+				.boxed_local(),
+		);
+	}
+	fn fail(&mut self, err: IoError) {
+		if let Some(Ok(())) = self.result {
+			self.result = Some(Err(err));
+		}
+	}
+	fn finish(&mut self) -> Result<u64, IoError> {
+		let offset = self.offset;
+		self.result.take().unwrap().map(|()| offset)
+	}
+}
@@ -0,0 +1,198 @@
+use derive_new::new;
+use educe::Educe;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	collections::HashMap, fmt::{Display, Write as _}, fs, io::Write, marker::PhantomData, mem, path::PathBuf
+};
+
+use super::{folder_par_sink, Encoder, FolderSync, FolderSyncReducer, ParallelPipe, ParallelSink};
+use crate::util::IoError;
+
+/// Flush a partition's buffer once it holds this many bytes.
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Write all items, serialized by `encoder`, to files laid out Hive-style by the
+/// partition value `key` returns, i.e. `dir/column=value/part-…`, returning the
+/// number of bytes written. Values are escaped as Hive does.
+///
+/// Each reducer writes its own file in each partition it sees items of, buffering
+/// items until more than `max_partitions` partitions are buffered, whereupon the
+/// largest buffer is appended to its file, bounding memory usage for high
+/// cardinality keys.
+///
+/// The files are written to the local filesystem, so this is not available for
+/// distributed streams.
+#[derive(new)]
+#[must_use]
+pub struct WritePartitioned<P, F, E> {
+	pipe: P,
+	dir: PathBuf,
+	column: String,
+	key: F,
+	encoder: E,
+	max_partitions: usize,
+}
+
+impl<P: ParallelPipe<Item>, Item, F, K, E> ParallelSink<Item> for WritePartitioned<P, F, E>
+where
+	F: for<'a> FnMut<(&'a P::Output,), Output = K> + Clone + Send + 'static,
+	K: Display,
+	E: Encoder<P::Output> + Clone + Send + 'static,
+{
+	folder_par_sink!(
+		WritePartitionedFolder<P::Output, F, E, StepA>,
+		WritePartitionedFolder<P::Output, F, E, StepB>,
+		self,
+		WritePartitionedFolder::new(
+			self.dir.clone(),
+			self.column.clone(),
+			self.key.clone(),
+			self.encoder.clone(),
+			self.max_partitions
+		),
+		WritePartitionedFolder::new(
+			self.dir,
+			self.column,
+			self.key,
+			self.encoder,
+			self.max_partitions
+		)
+	);
+}
+
+#[derive(Educe, Serialize, Deserialize, new)]
+#[educe(Clone(bound = "F: Clone, E: Clone"))]
+#[serde(
+	bound(serialize = "F: Serialize, E: Serialize"),
+	bound(deserialize = "F: Deserialize<'de>, E: Deserialize<'de>")
+)]
+pub struct WritePartitionedFolder<Item, F, E, Step> {
+	dir: PathBuf,
+	column: String,
+	key: F,
+	encoder: E,
+	max_partitions: usize,
+	#[new(default)]
+	marker: PhantomData<fn() -> (Item, Step)>,
+}
+
+pub struct StepA;
+pub struct StepB;
+
+pub struct WritePartitionedState {
+	id: u64,
+	buffers: HashMap<String, Vec<u8>>,
+	written: Result<u64, IoError>,
+}
+
+impl<Item, F, K, E> FolderSync<Item> for WritePartitionedFolder<Item, F, E, StepA>
+where
+	F: for<'a> FnMut<(&'a Item,), Output = K>,
+	K: Display,
+	E: Encoder<Item>,
+{
+	type State = WritePartitionedState;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		WritePartitionedState {
+			id: rand::random(),
+			buffers: HashMap::new(),
+			written: Ok(0),
+		}
+	}
+	fn push(&mut self, state: &mut Self::State, item: Item) {
+		if state.written.is_err() {
+			return;
+		}
+		let value = escape(&self.key.call_mut((&item,)).to_string());
+		let buffer = state.buffers.entry(value).or_default();
+		if let Err(err) = self.encoder.encode(item, buffer) {
+			state.written = Err(err.into());
+			return;
+		}
+		// Buffers are flushed once full, so a full buffer is the largest
+		if buffer.len() >= BUFFER_SIZE || state.buffers.len() > self.max_partitions {
+			let largest = state
+				.buffers
+				.iter()
+				.max_by_key(|(_, buffer)| buffer.len())
+				.map(|(value, _)| value.clone());
+			if let Some((value, buffer)) =
+				largest.and_then(|value| state.buffers.remove_entry(&value))
+			{
+				self.write(state, &value, &buffer);
+			}
+		}
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+impl<Item, F, E, Step> WritePartitionedFolder<Item, F, E, Step> {
+	fn flush(&self, state: &mut WritePartitionedState) {
+		for (value, buffer) in mem::take(&mut state.buffers) {
+			self.write(state, &value, &buffer);
+		}
+	}
+	/// Append `buffer` to this reducer's file in the partition `value`.
+	fn write(&self, state: &mut WritePartitionedState, value: &str, buffer: &[u8]) {
+		if state.written.is_err() {
+			return;
+		}
+		let dir = self.dir.join(format!("{}={}", self.column, value));
+		let file = dir.join(format!("part-{:016x}", state.id));
+		let res = fs::create_dir_all(&dir).and_then(|()| {
+			fs::OpenOptions::new()
+				.create(true)
+				.append(true)
+				.open(file)?
+				.write_all(buffer)
+		});
+		match res {
+			Ok(()) => {
+				if let Ok(written) = &mut state.written {
+					*written += buffer.len() as u64;
+				}
+			}
+			Err(err) => state.written = Err(err.into()),
+		}
+	}
+}
+
+impl<Item, F, E> FolderSync<WritePartitionedState> for WritePartitionedFolder<Item, F, E, StepB> {
+	type State = Result<u64, IoError>;
+	type Done = Self::State;
+
+	fn zero(&mut self) -> Self::State {
+		Ok(0)
+	}
+	fn push(&mut self, state: &mut Self::State, mut item: WritePartitionedState) {
+		self.flush(&mut item);
+		*state = match (mem::replace(state, Ok(0)), item.written) {
+			(Ok(a), Ok(b)) => Ok(a + b),
+			(Err(err), _) | (_, Err(err)) => Err(err),
+		};
+	}
+	fn done(&mut self, state: Self::State) -> Self::Done {
+		state
+	}
+}
+
+/// Escape a partition value as Hive does, so it's a valid directory name and
+/// can't be confused for a nested partition.
+fn escape(value: &str) -> String {
+	let mut ret = String::with_capacity(value.len());
+	for c in value.chars() {
+		if c.is_ascii_control() || "\"#%'*/:=?\\{[]^".contains(c) {
+			write!(ret, "%{:02X}", c as u32).unwrap();
+		} else {
+			ret.push(c);
+		}
+	}
+	if ret.is_empty() {
+		ret.push_str("__HIVE_DEFAULT_PARTITION__");
+	}
+	ret
+}
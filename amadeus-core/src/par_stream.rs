@@ -3,36 +3,65 @@
 
 #![allow(clippy::too_many_lines, unused_qualifications)]
 
+mod batch_by_bytes;
+mod buffered;
+mod catch_panics;
 mod chain;
+mod checkpoint;
+mod chunk_by;
 mod cloned;
 mod filter;
 mod filter_map_sync;
 mod flat_map;
+mod flat_map_buffered_ordered;
+mod flat_map_ordered_iter;
 mod flat_map_sync;
+mod fuse;
 mod identity;
 mod inspect;
+mod inspect_err;
+mod interleave;
 mod join;
 mod map;
+mod map_async;
+mod map_ref;
 mod map_sync;
+mod merge_join;
+mod observe_throughput;
+mod ordered;
+mod rate_limit;
+mod retry;
+mod sample_fraction;
+mod sample_per_interval;
+mod source_indexed;
+mod split_at_key_boundaries;
+mod step_by;
 mod sum_type;
+mod tee;
+mod timeout;
+mod unique_ordered;
 mod update;
+mod watch;
+mod window_by;
+mod zip_with_index;
 
 use async_trait::async_trait;
 use either::Either;
-use futures::{future, pin_mut, stream::StreamExt as _, Stream};
+use futures::{future, pin_mut, stream::StreamExt as _, Future, Stream};
 use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_closure::{traits, FnOnce};
 use std::{
-	cmp::Ordering, hash::Hash, iter, ops, pin::Pin, task::{Context, Poll}, vec
+	cmp::{self, Ordering}, collections::HashMap, fmt::Display, hash::Hash, io, iter, ops, path::PathBuf, pin::Pin, task::{Context, Poll}, time::Duration, vec
 };
 
 use super::{par_pipe::*, par_sink::*};
 use crate::{
-	into_par_stream::{IntoDistributedStream, IntoParallelStream}, pipe::{Sink, StreamExt}, pool::{ProcessPool, ProcessSend, ThreadPool}
+	file::Page, into_par_stream::{IntoDistributedStream, IntoParallelStream}, pipe::{Sink, StreamExt}, plan::Plan, pool::{ProcessPool, ProcessSend, Scheduling, ThreadPool}, util::IoError
 };
 
 pub use self::{
-	chain::*, cloned::*, filter::*, filter_map_sync::*, flat_map::*, flat_map_sync::*, identity::*, inspect::*, join::*, map::*, map_sync::*, update::*
+	batch_by_bytes::*, buffered::*, catch_panics::*, chain::*, checkpoint::*, chunk_by::*, cloned::*, filter::*, filter_map_sync::*, flat_map::*, flat_map_buffered_ordered::*, flat_map_ordered_iter::*, flat_map_sync::*, fuse::*, identity::*, inspect::*, inspect_err::*, interleave::*, join::*, map::*, map_async::*, map_ref::*, map_sync::*, merge_join::*, observe_throughput::*, ordered::*, rate_limit::*, retry::*, sample_fraction::*, sample_per_interval::*, source_indexed::*, split_at_key_boundaries::*, step_by::*, tee::*, timeout::*, unique_ordered::*, update::*, watch::*, window_by::*, zip_with_index::*
 };
 
 #[must_use]
@@ -41,6 +70,13 @@ pub trait StreamTask {
 	type Async: Stream<Item = Self::Item>;
 
 	fn into_async(self) -> Self::Async;
+
+	/// A hint of the relative cost of processing this task, such as the size of
+	/// its partition, for pools that schedule
+	/// [largest first](crate::pool::Scheduling::LargestFirst).
+	fn cost(&self) -> Option<u64> {
+		None
+	}
 }
 
 macro_rules! stream {
@@ -55,6 +91,25 @@ macro_rules! stream {
 			fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>>;
 			fn size_hint(&self) -> (usize, Option<usize>);
 
+			/// Describe the planned execution of this stream without reading any
+			/// data: the partitions of its source and what it pushes down, such as
+			/// the columns projected, followed by the chain of combinators.
+			///
+			/// Sources that don't provide a plan are described by just their type.
+			fn explain(&self) -> Plan {
+				Plan::source::<Self>(None)
+			}
+
+			/// Describe the planned execution of this stream reduced by `sink`, as
+			/// by [`pipe`](Self::pipe), without reading any data.
+			fn explain_with<S>(&self, _sink: &S) -> Plan
+			where
+				S: $sink<Self::Item>,
+				Self: Sized,
+			{
+				self.explain().sink::<S>()
+			}
+
 			$($items)*
 
 			#[inline]
@@ -66,6 +121,38 @@ macro_rules! stream {
 				$assert_stream(Inspect::new(self, f))
 			}
 
+			/// Call `f` on each error of a stream of `Result`s, such as to log or
+			/// count them, passing both successes and errors through unchanged.
+			#[inline]
+			fn inspect_err<F, T, E>(self, f: F) -> InspectErr<Self, F>
+			where
+				F: $fns::FnMut(&E) + Clone + $send + 'static,
+				Self: $stream<Item = Result<T, E>> + Sized,
+			{
+				$assert_stream(InspectErr::new(self, f))
+			}
+
+			/// Write each item, serialized by `encoder`, to a writer while passing it
+			/// through unchanged, like [`inspect`](Self::inspect) but persisting to
+			/// disk or a log, to diagnose where data goes wrong in a long pipeline.
+			/// `writer` is called to open a writer for each partition, and it's
+			/// flushed once the partition is done, so either open a file per
+			/// partition or a shared one in append mode.
+			///
+			/// # Panics
+			///
+			/// Panics if encoding an item or writing or flushing the writer fails.
+			#[inline]
+			fn tee<F, W, E>(self, writer: F, encoder: E) -> Tee<Self, F, E>
+			where
+				F: $fns::FnMut() -> W + Clone + $send + 'static,
+				W: io::Write,
+				E: for<'a> Encoder<&'a Self::Item> + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_stream(Tee::new(self, writer, encoder))
+			}
+
 			#[inline]
 			fn update<F>(self, f: F) -> Update<Self, F>
 			where
@@ -84,6 +171,45 @@ macro_rules! stream {
 				$assert_stream(Map::new(self, f))
 			}
 
+			/// Run the async work `f` returns for each item, yielding its output.
+			/// Items are processed one at a time within each partition; see
+			/// [`map_async_buffered`](Self::map_async_buffered) to overlap them.
+			#[inline]
+			fn map_async<Fut, F>(self, f: F) -> MapAsync<Self, F>
+			where
+				F: $fns::FnMut(Self::Item) -> Fut + Clone + $send + 'static,
+				Fut: Future,
+				Self: Sized,
+			{
+				$assert_stream(MapAsync::new(self, f, 1))
+			}
+
+			/// Like [`map_async`](Self::map_async), but running the work of up to
+			/// `concurrency` items at once, while still yielding their outputs in
+			/// order within each partition.
+			#[inline]
+			fn map_async_buffered<Fut, F>(self, f: F, concurrency: usize) -> MapAsync<Self, F>
+			where
+				F: $fns::FnMut(Self::Item) -> Fut + Clone + $send + 'static,
+				Fut: Future,
+				Self: Sized,
+			{
+				assert_ne!(concurrency, 0, "concurrency must be nonzero");
+				$assert_stream(MapAsync::new(self, f, concurrency))
+			}
+
+			/// Derive a value from a borrow of each item, such as a key to group
+			/// by, yielding it alongside the item, which is neither cloned nor
+			/// consumed.
+			#[inline]
+			fn map_ref<B, F>(self, f: F) -> MapRef<Self, F>
+			where
+				F: $fns::FnMut(&Self::Item) -> B + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_stream(MapRef::new(self, f))
+			}
+
 			#[inline]
 			fn flat_map<B, F>(self, f: F) -> FlatMap<Self, F>
 			where
@@ -94,6 +220,105 @@ macro_rules! stream {
 				$assert_stream(FlatMap::new(self, f))
 			}
 
+			/// Like [`flat_map`](Self::flat_map), but polling the streams of up to
+			/// `concurrency` items at once, while still yielding their items in
+			/// order within each partition. Each stream buffers at most 1024 items
+			/// while those before it are yielded, after which it isn't polled until
+			/// it's the oldest.
+			#[inline]
+			fn flat_map_buffered_ordered<B, F>(self, f: F, concurrency: usize) -> FlatMapBufferedOrdered<Self, F>
+			where
+				F: $fns::FnMut(Self::Item) -> B + Clone + $send + 'static,
+				B: Stream,
+				Self: Sized,
+			{
+				assert_ne!(concurrency, 0, "concurrency must be nonzero");
+				$assert_stream(FlatMapBufferedOrdered::new(self, f, concurrency))
+			}
+
+			/// Like [`flat_map`](Self::flat_map), but with `f` returning an iterator,
+			/// and each output tagged with the index of the item it came from
+			/// within its partition. The outputs of each item are adjacent, so
+			/// they can be reassembled into per-item groups downstream.
+			#[inline]
+			fn flat_map_ordered_iter<B, F>(self, f: F) -> FlatMapOrderedIter<Self, F>
+			where
+				F: $fns::FnMut(Self::Item) -> B + Clone + $send + 'static,
+				B: IntoIterator,
+				Self: Sized,
+			{
+				$assert_stream(FlatMapOrderedIter::new(self, f))
+			}
+
+			/// Await each item, which must be a future, handling those that don't
+			/// complete within `duration` of being received according to `policy`.
+			///
+			/// An item that times out is dropped without being polled further, so a
+			/// hung request doesn't stall the rest of its partition.
+			#[inline]
+			fn timeout(self, duration: Duration, policy: TimeoutPolicy) -> Timeout<Self>
+			where
+				Self::Item: Future,
+				Self: Sized,
+			{
+				$assert_stream(Timeout::new(self, duration, policy))
+			}
+
+			/// Run the fallible async work `f` returns for each item, calling it
+			/// again after waiting per `backoff` each time it fails, up to
+			/// `attempts` times in all. Yields the first success, or the last error
+			/// once attempts are exhausted, to be handled like any other error
+			/// item, such as by a `map_with_errors` sink.
+			///
+			/// Items are retried one at a time, so a failing item delays the rest
+			/// of its partition.
+			///
+			/// # Panics
+			///
+			/// Panics if `attempts` is zero.
+			#[inline]
+			fn retry<F, Fut, T, E>(self, attempts: u32, backoff: Backoff, f: F) -> Retry<Self, F>
+			where
+				F: $fns::FnMut(&Self::Item) -> Fut + Clone + $send + 'static,
+				Fut: Future<Output = Result<T, E>>,
+				Self: Sized,
+			{
+				assert_ne!(attempts, 0, "attempts must be nonzero");
+				$assert_stream(Retry::new(self, f, attempts, backoff))
+			}
+
+			/// Catch panics raised while processing each item, such as by a `map`
+			/// closure over dirty data, handling the item according to `policy`.
+			///
+			/// Upstream state can't be trusted after a panic, so the rest of the
+			/// partition is lost, while other partitions carry on. Streams of many
+			/// small partitions lose the least.
+			#[inline]
+			fn catch_panics(self, policy: PanicPolicy) -> CatchPanics<Self>
+			where
+				Self: Sized,
+			{
+				$assert_stream(CatchPanics::new(self, policy))
+			}
+
+			/// Assign each `(key, value)` to every window of length `size` that
+			/// contains its timestamp, with windows starting every `slide`, yielding
+			/// `((window_start, key), value)` for each. Windows are aligned to
+			/// multiples of `slide` since the epoch of `timestamp`, so `slide ==
+			/// size` gives tumbling windows, and `slide < size` overlapping ones.
+			#[inline]
+			fn window_by<F, K, V>(self, timestamp: F, size: Duration, slide: Duration) -> WindowBy<Self, F>
+			where
+				F: $fns::FnMut(&V) -> Duration + Clone + $send + 'static,
+				K: Clone,
+				V: Clone,
+				Self: $stream<Item = (K, V)> + Sized,
+			{
+				assert_ne!(size, Duration::from_secs(0), "size must be non-zero");
+				assert_ne!(slide, Duration::from_secs(0), "slide must be non-zero");
+				$assert_stream(WindowBy::new(self, timestamp, size, slide))
+			}
+
 			#[inline]
 			fn filter<F>(self, f: F) -> Filter<Self, F>
 			where
@@ -103,6 +328,161 @@ macro_rules! stream {
 				$assert_stream(Filter::new(self, f))
 			}
 
+			/// Drop the repeats of each item, keeping its first occurrence where it
+			/// was. Unlike [`distinct_by`](Self::distinct_by) this preserves order,
+			/// but only within each partition.
+			///
+			/// The items seen so far are remembered for the duration of each
+			/// partition, so an item repeated across partitions is kept once in
+			/// each.
+			#[inline]
+			fn unique_ordered(self) -> UniqueOrdered<Self>
+			where
+				Self::Item: Eq + Hash + Clone,
+				Self: Sized,
+			{
+				$assert_stream(UniqueOrdered::new(self))
+			}
+
+			/// Keep each item independently with probability `p`. Each partition
+			/// draws from an RNG seeded from `seed`, so the selection is
+			/// reproducible given the same seed and partitioning.
+			#[inline]
+			fn sample_fraction(self, p: f64, seed: u64) -> SampleFraction<Self>
+			where
+				Self: Sized,
+			{
+				assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+				$assert_stream(SampleFraction::new(self, p, seed))
+			}
+
+			/// Yield every `step`th item of each partition, starting with the first.
+			#[inline]
+			fn step_by(self, step: usize) -> StepBy<Self>
+			where
+				Self: Sized,
+			{
+				assert_ne!(step, 0, "step must be non-zero");
+				$assert_stream(StepBy::new(self, step))
+			}
+
+			/// Downsample to one item per interval of length `interval`, keeping the
+			/// first or last per `policy`, by the time `timestamp` returns for each.
+			/// Intervals are aligned to multiples of `interval` since the epoch of
+			/// `timestamp`.
+			///
+			/// Intervals are per partition, so a partition keeps an item of each
+			/// interval it has items in regardless of the others. Items are expected
+			/// in time order within each partition: an item starts a new run, of
+			/// which one is kept, whenever its interval differs from the previous
+			/// item's.
+			#[inline]
+			fn sample_per_interval<F>(
+				self, timestamp: F, interval: Duration, policy: SamplePolicy,
+			) -> SamplePerInterval<Self, F>
+			where
+				F: $fns::FnMut(&Self::Item) -> Duration + Clone + $send + 'static,
+				Self: Sized,
+			{
+				assert_ne!(interval, Duration::from_secs(0), "interval must be non-zero");
+				$assert_stream(SamplePerInterval::new(self, timestamp, interval, policy))
+			}
+
+			/// Group consecutive items of each partition for which `f` returns equal
+			/// keys, yielding each maximal run along with its key.
+			#[inline]
+			fn chunk_by<K, F>(self, f: F) -> ChunkBy<Self, F>
+			where
+				F: $fns::FnMut(&Self::Item) -> K + Clone + $send + 'static,
+				K: PartialEq,
+				Self: Sized,
+			{
+				$assert_stream(ChunkBy::new(self, f))
+			}
+
+			/// Group consecutive items of each partition into batches whose sizes,
+			/// as estimated by `size`, sum to no more than `max_bytes`, such as to
+			/// bound the size of output files or request payloads. An item larger
+			/// than `max_bytes` forms a batch of its own.
+			#[inline]
+			fn batch_by_bytes<F>(self, max_bytes: usize, size: F) -> BatchByBytes<Self, F>
+			where
+				F: $fns::FnMut(&Self::Item) -> usize + Clone + $send + 'static,
+				Self: Sized,
+			{
+				$assert_stream(BatchByBytes::new(self, max_bytes, size))
+			}
+
+			/// Stop polling each partition once it has finished, returning `None`
+			/// thereafter rather than relying on it tolerating being polled again.
+			#[inline]
+			fn fuse(self) -> Fuse<Self>
+			where
+				Self: Sized,
+			{
+				$assert_stream(Fuse::new(self))
+			}
+
+			/// Buffer up to `capacity` items from upstream within each partition.
+			/// Upstream is run as a task of its own on the worker, so it runs ahead
+			/// while downstream is waiting, such as on I/O, but stops once
+			/// `capacity` items are buffered, so a slow consumer throttles it
+			/// rather than it buffering unboundedly. This prefetches from latent
+			/// sources like S3 or HTTP, overlapping their reads with processing of
+			/// the items already read.
+			#[inline]
+			fn buffered(self, capacity: usize) -> Buffered<Self>
+			where
+				<Self::Task as StreamTask>::Async: 'static,
+				Self::Item: 'static,
+				Self: Sized,
+			{
+				assert_ne!(capacity, 0, "capacity must be nonzero");
+				$assert_stream(Buffered::new(self, capacity))
+			}
+
+			/// Skip the partitions recorded in `store` as completed by an earlier
+			/// run, and record each of this run's partitions in it as soon as the
+			/// sink has taken its last item, so that a run that fails partway
+			/// through is restarted from the partitions it didn't finish.
+			/// Partitions are identified by the order upstream yields them in,
+			/// which must be the same between runs. For distributed streams, the
+			/// store's path must be shared by every process.
+			///
+			/// This suits sinks whose effects are per item, such as writing the
+			/// items out, as skipped partitions contribute nothing to the result
+			/// of a restarted run. A partition that fails is repeated on restart,
+			/// with any side effects its items had before the failure; making
+			/// sinks idempotent is up to the user.
+			#[inline]
+			fn checkpoint(self, store: CheckpointStore) -> Checkpoint<Self>
+			where
+				Self: Sized,
+			{
+				$assert_stream(Checkpoint::new(self, store))
+			}
+
+			/// Emit no more than `per_second` items per second, for example to be
+			/// polite to an external API called per item. Items are spaced out
+			/// evenly, with the budget shared by all partitions running in the
+			/// same process; for distributed streams, each process has its own
+			/// budget of `per_second`.
+			///
+			/// Each item is held back once it has been pulled from upstream, so this
+			/// should come before the calls it is limiting.
+			///
+			/// # Panics
+			///
+			/// If `per_second` isn't positive, or is so small that items would be
+			/// more than `u32::MAX` seconds apart.
+			#[inline]
+			fn rate_limit(self, per_second: f64) -> RateLimit<Self>
+			where
+				Self: Sized,
+			{
+				$assert_stream(RateLimit::new(self, per_second))
+			}
+
 			#[inline]
 			fn left_join<K, V1, V2>(self, right: impl IntoIterator<Item = (K, V2)>) -> LeftJoin<Self, K, V1, V2>
 			where
@@ -134,6 +514,30 @@ macro_rules! stream {
 				$assert_stream(Chain::new(self, chain.$into_stream_fn()))
 			}
 
+			/// Merge with `other`, alternating between taking a partition from each.
+			/// Partitions are processed in parallel, so their items are exhausted at
+			/// different rates and are not strictly alternated.
+			#[inline]
+			fn interleave<C>(self, other: C) -> Interleave<Self, C::$xxx>
+			where
+				C: $into_stream<Item = Self::Item>,
+				Self: Sized,
+			{
+				$assert_stream(Interleave::new(self, other.$into_stream_fn()))
+			}
+
+			/// Run as a single partition, so that items are yielded in source order
+			/// through any subsequent combinators and sinks. This makes per-partition
+			/// combinators like [`step_by`](Self::step_by) and
+			/// [`chunk_by`](Self::chunk_by) deterministic, at the cost of parallelism.
+			#[inline]
+			fn ordered(self) -> Ordered<Self, Self::Task>
+			where
+				Self: Sized,
+			{
+				$assert_stream(Ordered::new(self))
+			}
+
 			#[inline]
 			async fn for_each<P, F>(self, pool: &P, f: F)
 			where
@@ -147,6 +551,40 @@ macro_rules! stream {
 					.await
 			}
 
+			/// Call `f` with the items in batches of up to `batch_size`. Each
+			/// partition, i.e. each of the stream's tasks, is batched separately,
+			/// flushing whatever remains once it's exhausted.
+			#[inline]
+			async fn for_each_batched<P, F>(self, pool: &P, batch_size: usize, f: F)
+			where
+				P: $pool,
+				F: $fns::FnMut(Vec<Self::Item>) + Clone + $send + 'static,
+				Self::Item: 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(
+					pool,
+					$pipe::<Self::Item>::for_each_batched(Identity, batch_size, f),
+				)
+				.await
+			}
+
+			/// Write all items, serialized by `encoder`, to `page`. See [`WritePage`].
+			#[inline]
+			async fn write_page<P, Pg, E>(self, pool: &P, page: Pg, encoder: E) -> Result<u64, IoError>
+			where
+				P: $pool,
+				Pg: Page + 'static,
+				E: Encoder<Self::Item> + Clone + $send + 'static,
+				Self::Item: 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::write_page(Identity, page, encoder))
+					.await
+			}
+
 			#[inline]
 			async fn fold<P, ID, F, B>(self, pool: &P, identity: ID, op: F) -> B
 			where
@@ -165,6 +603,29 @@ macro_rules! stream {
 				.await
 			}
 
+			/// Fold each partition, i.e. each of the stream's tasks, separately,
+			/// returning the folded value of each rather than combining them as
+			/// [`fold`](Self::fold) does. Empty partitions are folded to `identity()`.
+			/// The values are in partition order for the partitions processed by
+			/// each worker of the pool, but otherwise in the order they complete.
+			#[inline]
+			async fn fold_partitions<P, ID, F, B>(self, pool: &P, identity: ID, op: F) -> Vec<B>
+			where
+				P: $pool,
+				ID: $fns::FnMut() -> B + Clone + $send + 'static,
+				F: $fns::FnMut(B, Self::Item) -> B + Clone + $send + 'static,
+				B: $send + 'static,
+				Self::Item: 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(
+					pool,
+					$pipe::<Self::Item>::fold_partitions(Identity, identity, op),
+				)
+				.await
+			}
+
 			#[inline]
 			async fn histogram<P>(self, pool: &P) -> Vec<(Self::Item, usize)>
 			where
@@ -202,6 +663,21 @@ macro_rules! stream {
 					.await
 			}
 
+			/// Count the items for which `f` returns `true`, like
+			/// `filter(f).count(pool)`.
+			#[inline]
+			async fn count_where<P, F>(self, pool: &P, f: F) -> usize
+			where
+				P: $pool,
+				F: $fns::FnMut(&Self::Item) -> bool + Clone + $send + 'static,
+				Self::Item: 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::count_where(Identity, f))
+					.await
+			}
+
 			#[inline]
 			async fn sum<P, S>(self, pool: &P) -> S
 			where
@@ -239,6 +715,38 @@ macro_rules! stream {
 				.await
 			}
 
+			/// Exponentially-weighted moving average, with `alpha` the weight given to each
+			/// new item. This is order-sensitive, so it's only deterministic when the
+			/// stream is a single partition, or when run on a deterministic pool.
+			#[inline]
+			async fn ewma<P>(self, pool: &P, alpha: f64) -> Option<f64>
+			where
+				P: $pool,
+				Self::Item: 'static,
+				Self::Task: 'static,
+				Self: $stream<Item = f64> + Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::ewma(Identity, alpha))
+					.await
+			}
+
+			/// Exponentially-weighted moving average of each partition, i.e. each of the
+			/// stream's tasks, omitting those that are empty. The averages are in
+			/// partition order for the partitions processed by each worker of the pool,
+			/// but otherwise in the order they complete, unless run on a deterministic
+			/// pool.
+			#[inline]
+			async fn ewma_partitions<P>(self, pool: &P, alpha: f64) -> Vec<f64>
+			where
+				P: $pool,
+				Self::Item: 'static,
+				Self::Task: 'static,
+				Self: $stream<Item = f64> + Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::ewma_partitions(Identity, alpha))
+					.await
+			}
+
 			#[inline]
 			async fn combine<P, F>(self, pool: &P, f: F) -> Option<Self::Item>
 			where
@@ -347,6 +855,86 @@ macro_rules! stream {
 				.await
 			}
 
+			/// Like [`most_frequent`](Self::most_frequent), but with at most
+			/// `max_counters` counters in each partition's count-min sketch, so that
+			/// memory is bounded however small `tolerance` is. If the cap binds, the
+			/// sketch is narrower than `tolerance` asks for, so the counts of items
+			/// outside the top `n` are overestimated more, and infrequent items are
+			/// more likely to displace true heavy hitters near the bottom of the
+			/// top `n`. Items that are much more frequent than the rest are still
+			/// reported.
+			///
+			/// # Panics
+			///
+			/// Panics if `max_counters` is less than 2.
+			#[inline]
+			async fn most_frequent_bounded<P>(
+				self, pool: &P, n: usize, probability: f64, tolerance: f64, max_counters: usize,
+			) -> ::amadeus_streaming::Top<Self::Item, usize>
+			where
+				P: $pool,
+				Self::Item: Hash + Eq + Clone + $send + 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(
+					pool,
+					$pipe::<Self::Item>::most_frequent_bounded(
+						Identity,
+						n,
+						probability,
+						tolerance,
+						max_counters,
+					),
+				)
+				.await
+			}
+
+			/// The `n` highest-scoring items of each key, as given by `key` and
+			/// `score`. Each key's items are in descending order of score. Of items
+			/// with equal scores, those seen first in each partition are kept, but
+			/// which survive the merging of partitions is unspecified, as is their
+			/// order. Keys are in the order they're first seen as partitions are
+			/// merged, which is source order only on a deterministic pool.
+			#[inline]
+			async fn top_n_per_key<P, K, S, A, B>(
+				self, pool: &P, n: usize, key: K, score: S,
+			) -> IndexMap<A, Vec<Self::Item>>
+			where
+				P: $pool,
+				K: $fns::FnMut(&Self::Item) -> A + Clone + $send + 'static,
+				S: $fns::FnMut(&Self::Item) -> B + Clone + $send + 'static,
+				A: Eq + Hash + $send + 'static,
+				B: Ord + $send + 'static,
+				Self::Item: $send + 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::top_n_per_key(Identity, n, key, score))
+					.await
+			}
+
+			/// Reduce the values of each key with `f`, keeping one accumulator per
+			/// key rather than all of its values. Values are reduced in order within
+			/// each partition, but partitions are merged as they complete, so `f`
+			/// should be commutative as well as associative unless run on a
+			/// deterministic pool. Keys are in the order they're first seen as
+			/// partitions are merged, which is source order only on a deterministic
+			/// pool.
+			#[inline]
+			async fn reduce_by_key<P, F, A, B>(self, pool: &P, f: F) -> IndexMap<A, B>
+			where
+				P: $pool,
+				F: $fns::FnMut(B, B) -> B + Clone + $send + 'static,
+				A: Eq + Hash + $send + 'static,
+				B: $send + 'static,
+				Self::Task: 'static,
+				Self: $stream<Item = (A, B)> + Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::reduce_by_key(Identity, f))
+					.await
+			}
+
 			#[inline]
 			async fn most_distinct<P, A, B>(
 				self, pool: &P, n: usize, probability: f64, tolerance: f64, error_rate: f64,
@@ -371,6 +959,97 @@ macro_rules! stream {
 				.await
 			}
 
+			/// Deduplicate items on the key `f` returns for each, keeping one full
+			/// item per key. See [`DistinctBy`](crate::par_sink::DistinctBy) for
+			/// which duplicate survives.
+			#[inline]
+			async fn distinct_by<P, F, K>(self, pool: &P, f: F) -> Vec<Self::Item>
+			where
+				P: $pool,
+				F: $fns::FnMut(&Self::Item) -> K + Clone + $send + 'static,
+				K: Eq + Hash + $send + 'static,
+				Self::Item: $send + 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::distinct_by(Identity, f))
+					.await
+			}
+
+			/// The running totals of the items in source order, combined with `f`
+			/// from `zero`, across all partitions rather than restarting at each,
+			/// such as a cumulative sum. See [`ScanGlobal`](crate::par_sink::ScanGlobal)
+			/// for how they're computed, which requires `f` be associative and
+			/// commutative with `zero` as its identity.
+			#[inline]
+			async fn scan_global<P, F>(self, pool: &P, zero: Self::Item, f: F) -> Vec<Self::Item>
+			where
+				P: $pool,
+				F: $fns::FnMut(&Self::Item, &Self::Item) -> Self::Item + Clone + $send + 'static,
+				Self::Item: Clone + $send + 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				SourceIndexed::new(self)
+					.pipe(pool, ScanGlobal::new(Identity, zero, f))
+					.await
+			}
+
+			/// The exact number of distinct values for each key.
+			#[inline]
+			async fn distinct_count_by<P, A, B>(self, pool: &P) -> HashMap<A, u64>
+			where
+				P: $pool,
+				Self: $stream<Item = (A, B)> + Sized,
+				A: Eq + Hash + $send + 'static,
+				B: Eq + Hash + $send + 'static,
+				Self::Task: 'static,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::distinct_count_by(Identity))
+					.await
+			}
+
+			/// Collect items into a `Vec`, or fail if there are more than
+			/// `max_items`, rather than exhausting memory. See
+			/// [`CollectBounded`](crate::par_sink::CollectBounded).
+			#[inline]
+			async fn collect_bounded<P>(
+				self, pool: &P, max_items: usize,
+			) -> Result<Vec<Self::Item>, LimitExceeded>
+			where
+				P: $pool,
+				Self::Item: $send + 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(pool, $pipe::<Self::Item>::collect_bounded(Identity, max_items))
+					.await
+			}
+
+			/// Group items into candidate near-duplicates, by locality-sensitive
+			/// hashing of the [`MinHash`](amadeus_streaming::MinHash) signatures of
+			/// the features `f` returns for each. See
+			/// [`DistinctApprox`](crate::par_sink::DistinctApprox).
+			#[inline]
+			async fn distinct_approx<P, F, I>(
+				self, pool: &P, f: F, bands: usize, rows: usize,
+			) -> Vec<Vec<Self::Item>>
+			where
+				P: $pool,
+				F: $fns::FnMut(&Self::Item) -> I + Clone + $send + 'static,
+				I: IntoIterator,
+				I::Item: Hash + 'static,
+				Self::Item: $send + 'static,
+				Self::Task: 'static,
+				Self: Sized,
+			{
+				self.pipe(
+					pool,
+					$pipe::<Self::Item>::distinct_approx(Identity, f, bands, rows),
+				)
+				.await
+			}
+
 			#[inline]
 			async fn sample_unstable<P>(
 				self, pool: &P, samples: usize,
@@ -474,6 +1153,7 @@ stream!(ParallelStream ParallelPipe ParallelSink FromParallelStream IntoParallel
 				tasks.iter().map(Vec::len).collect::<Vec<_>>()
 			);
 		}
+		let tasks = schedule(tasks, pool.scheduling(), pool.is_deterministic());
 
 		let handles = tasks
 			.into_iter()
@@ -492,8 +1172,14 @@ stream!(ParallelStream ParallelPipe ParallelSink FromParallelStream IntoParallel
 					}
 					sink.done().await
 				})
-			})
-			.collect::<futures::stream::FuturesUnordered<_>>();
+			});
+		// Combine the partial results in the order of their partitions if the
+		// pool is deterministic, rather than as they complete
+		let handles = if pool.is_deterministic() {
+			handles.collect::<futures::stream::FuturesOrdered<_>>().left_stream()
+		} else {
+			handles.collect::<futures::stream::FuturesUnordered<_>>().right_stream()
+		};
 		let stream = handles.map(|item| {
 			item.unwrap_or_else(|err| panic!("Amadeus: task '<unnamed>' panicked at '{}'", err))
 		});
@@ -502,6 +1188,31 @@ stream!(ParallelStream ParallelPipe ParallelSink FromParallelStream IntoParallel
 		stream.sink(reduce_c).await
 	}
 
+	/// Like [`reduce`](ParallelStream::reduce), but combining the partial results
+	/// in a tree, with `reduce_b` spawned on the pool to combine them `arity` at
+	/// a time as they complete, rather than folding them all in one reducer.
+	///
+	/// `reduce_b` must take and output the same type, and combine associatively
+	/// and commutatively, as partials are combined in the order they complete.
+	async fn reduce_tree<P, R1, R2>(
+		self, pool: &P, reduce_a: R1, reduce_b: R2, arity: usize,
+	) -> <R1 as ReducerSend<Self::Item>>::Done
+	where
+		P: ThreadPool,
+		R1: ReducerSend<Self::Item> + Clone + Send + 'static,
+		R2: ReducerSend<
+				<R1 as ReducerSend<Self::Item>>::Done,
+				Done = <R1 as ReducerSend<Self::Item>>::Done,
+			> + Clone
+			+ Send
+			+ 'static,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let reduce_c = ReduceTree::new(pool.clone(), reduce_b, arity);
+		self.reduce(pool, reduce_a, reduce_c).await
+	}
+
 	async fn pipe<P, ParSink, A>(self, pool: &P, sink: ParSink) -> A
 	where
 		P: ThreadPool,
@@ -517,6 +1228,217 @@ stream!(ParallelStream ParallelPipe ParallelSink FromParallelStream IntoParallel
 			.await
 	}
 
+	/// Pair each item with an ID that is unique across all partitions, for
+	/// example to use as a surrogate key.
+	///
+	/// Partitions reserve blocks of IDs from a shared counter as they need them,
+	/// so IDs increase monotonically within a partition but aren't contiguous
+	/// across partitions.
+	fn zip_with_index(self) -> ZipWithIndex<Self>
+	where
+		Self: Sized,
+	{
+		assert_parallel_stream(ZipWithIndex::new(self))
+	}
+
+	/// Count the items passing this point, and their size in bytes as given by
+	/// `bytes`, into `throughput`, from which items/sec and bytes/sec can be read
+	/// once the job completes. Observe several stages with a handle each to
+	/// compare them.
+	///
+	/// The counters are shared in memory, so this is not available for
+	/// distributed streams.
+	fn observe_throughput<F>(self, throughput: &Throughput, bytes: F) -> ObserveThroughput<Self, F>
+	where
+		F: FnMut(&Self::Item) -> usize + Clone + Send + 'static,
+		Self: Sized,
+	{
+		assert_parallel_stream(ObserveThroughput::new(self, throughput.clone(), bytes))
+	}
+
+	/// Maintain the running aggregate `running` of the items passing through this
+	/// stage, such as a [`RunningCount`], [`RunningSum`] or [`RunningMean`],
+	/// returning a receiver of snapshots of it.
+	///
+	/// Each task aggregates its items locally, merging them into the shared total
+	/// every 1024 items or `period`, whichever is sooner, and when it ends. The
+	/// total is sent to the receiver at most once per `period`, and whenever a
+	/// task ends, so the receiver's last value is the aggregate over the whole
+	/// stream. The receiver is a [`Stream`] of snapshots, finishing when the
+	/// stream is dropped.
+	///
+	/// The total is shared in memory, so this is not available for distributed
+	/// streams.
+	fn watch<R>(
+		self, running: R, period: Duration,
+	) -> (Watch<Self, R>, tokio::sync::watch::Receiver<R::Output>)
+	where
+		R: Running<Self::Item>,
+		Self: Sized,
+	{
+		let (sender, receiver) = tokio::sync::watch::channel(running.output());
+		(
+			assert_parallel_stream(Watch::new(self, running, period, sender)),
+			receiver,
+		)
+	}
+
+	/// Align `self` and `other`, which must both be sorted by key across and
+	/// within their partitions, into [`KeySegment`]s of around `segment_len`
+	/// items, each holding all of the items of both for a range of keys, so a
+	/// merge join can be run on each independently.
+	///
+	/// The partitions of the inputs are read in order by the tasks of this
+	/// stream, which take turns to cut the next segment, so only the segments
+	/// being processed and a partition of each input are held in memory at once.
+	/// The inputs are shared in memory, so this is not available for distributed
+	/// streams.
+	fn split_at_key_boundaries<C, K, V1, V2>(
+		self, other: C, segment_len: usize,
+	) -> SplitAtKeyBoundaries<Self, C::ParStream, Self::Task, <C::ParStream as ParallelStream>::Task>
+	where
+		C: IntoParallelStream<Item = (K, V2)>,
+		K: Ord + Send + 'static,
+		V1: Send + 'static,
+		V2: Send + 'static,
+		Self: ParallelStream<Item = (K, V1)> + Sized,
+	{
+		assert_ne!(segment_len, 0, "segment_len must be nonzero");
+		assert_parallel_stream(SplitAtKeyBoundaries::new(
+			self,
+			other.into_par_stream(),
+			segment_len,
+		))
+	}
+
+	/// Join `self` and `other`, which must both be sorted by key across and
+	/// within their partitions, by merging them rather than building hash tables
+	/// as [`inner_join`](Self::inner_join) and [`left_join`](Self::left_join) do.
+	/// Each match yields a row, as do the unmatched items of the sides
+	/// `join_type` requires.
+	///
+	/// The inputs are aligned as by
+	/// [`split_at_key_boundaries`](Self::split_at_key_boundaries) into segments
+	/// of around `segment_len` items, which are joined in parallel as the inputs
+	/// are read, so the inputs needn't fit in memory. This is not available for
+	/// distributed streams.
+	fn merge_join<C, K, V1, V2>(
+		self, other: C, segment_len: usize, join_type: JoinType,
+	) -> MergeJoin<Self, C::ParStream, Self::Task, <C::ParStream as ParallelStream>::Task, K, V1, V2>
+	where
+		C: IntoParallelStream<Item = (K, V2)>,
+		K: Ord + Clone + Send + 'static,
+		V1: Clone + Send + 'static,
+		V2: Clone + Send + 'static,
+		Self: ParallelStream<Item = (K, V1)> + Sized,
+	{
+		assert_ne!(segment_len, 0, "segment_len must be nonzero");
+		assert_parallel_stream(MergeJoin::new(
+			self,
+			other.into_par_stream(),
+			segment_len,
+			join_type,
+		))
+	}
+
+	/// Fold the values of each key, calling `snapshot` with the per-key results
+	/// over all workers so far each time a worker has received another `every`
+	/// items, and once with the final result, which is the same as if no
+	/// snapshots were taken.
+	///
+	/// The total is shared in memory, so this is not available for distributed
+	/// streams.
+	async fn group_by_snapshots<P, ID, F, S, A, V, B>(
+		self, pool: &P, every: usize, identity: ID, op: F, snapshot: S,
+	) -> IndexMap<A, B>
+	where
+		P: ThreadPool,
+		ID: FnMut() -> B + Clone + Send + 'static,
+		F: FnMut(B, Either<V, B>) -> B + Clone + Send + 'static,
+		S: FnMut(&IndexMap<A, B>) + Send + 'static,
+		A: Eq + Hash + Send + 'static,
+		V: 'static,
+		B: Send + 'static,
+		Self::Task: 'static,
+		Self: ParallelStream<Item = (A, V)> + Sized,
+	{
+		assert_ne!(every, 0, "every must be non-zero");
+		self.pipe(
+			pool,
+			GroupBySnapshots::new(Identity, every, identity, op, snapshot),
+		)
+		.await
+	}
+
+	/// Run a tuple of sinks over this stream in a single pass, returning a tuple of
+	/// their outputs. Each item is cloned to each of the sinks.
+	async fn zip_sinks<P, ParSinks, A>(self, pool: &P, sinks: ParSinks) -> A
+	where
+		P: ThreadPool,
+		ParSinks: ParallelSink<Self::Item, Done = A>,
+		<ParSinks::Pipe as ParallelPipe<Self::Item>>::Task: 'static,
+		ParSinks::ReduceA: 'static,
+		Self::Item: Clone,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		self.pipe(pool, sinks).await
+	}
+
+	/// Sort all items, spilling sorted runs of up to `budget` items per reducer to
+	/// files in `dir` so as to bound memory usage. See [`SortedSpill`].
+	async fn sorted_spill<P>(
+		self, pool: &P, budget: usize, dir: PathBuf,
+	) -> SortedSpillIter<Self::Item>
+	where
+		P: ThreadPool,
+		Self::Item: Ord + Serialize + DeserializeOwned + Send + 'static,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		assert_ne!(budget, 0, "budget must be non-zero");
+		self.pipe(pool, SortedSpill::new(Identity, budget, dir)).await
+	}
+
+	/// Deduplicate all items, spilling sorted runs of up to `budget` unique items
+	/// per reducer to files in `dir` so as to bound memory usage. See
+	/// [`DistinctSpill`].
+	async fn distinct_spill<P>(
+		self, pool: &P, budget: usize, dir: PathBuf,
+	) -> DistinctSpillIter<Self::Item>
+	where
+		P: ThreadPool,
+		Self::Item: Ord + Serialize + DeserializeOwned + Send + 'static,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		assert_ne!(budget, 0, "budget must be non-zero");
+		self.pipe(pool, DistinctSpill::new(Identity, budget, dir)).await
+	}
+
+	/// Write all items, serialized by `encoder`, to files under `dir` in
+	/// directories `column=value` by the value `key` returns for each. See
+	/// [`WritePartitioned`].
+	async fn write_partitioned<P, F, K, E>(
+		self, pool: &P, dir: PathBuf, column: &str, key: F, encoder: E, max_partitions: usize,
+	) -> Result<u64, IoError>
+	where
+		P: ThreadPool,
+		F: for<'a> ops::FnMut(&'a Self::Item) -> K + Clone + Send + 'static,
+		K: Display,
+		E: Encoder<Self::Item> + Clone + Send + 'static,
+		Self::Item: 'static,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		assert_ne!(max_partitions, 0, "max_partitions must be non-zero");
+		self.pipe(
+			pool,
+			WritePartitioned::new(Identity, dir, column.to_owned(), key, encoder, max_partitions),
+		)
+		.await
+	}
+
 	// These messy bounds are unfortunately necessary as requiring 'static in ParallelSink breaks sink_b being e.g. Identity.count()
 	async fn fork<P, ParSinkA, ParSinkB, A, B>(
 		self, pool: &P, sink_a: ParSinkA, sink_b: ParSinkB,
@@ -563,6 +1485,55 @@ stream!(ParallelStream ParallelPipe ParallelSink FromParallelStream IntoParallel
 			.await
 	}
 
+	/// Like [`group_by`](ParallelStream::group_by), but grouping with `hasher`.
+	async fn group_by_with_hasher<P, S, H, A, B>(
+		self, pool: &P, sink: S, hasher: H,
+	) -> IndexMap<A, S::Done, H::Hasher>
+	where
+		P: ThreadPool,
+		A: Eq + Hash + Send + 'static,
+		B: 'static,
+		H: MakeHasher + Clone + Send + 'static,
+		H::Hasher: Send + 'static,
+		S: ParallelSink<B>,
+		<S::Pipe as ParallelPipe<B>>::Task: Clone + Send + 'static,
+		S::ReduceA: 'static,
+		S::ReduceC: Clone,
+		S::Done: Send + 'static,
+		Self::Task: 'static,
+		Self: ParallelStream<Item = (A, B)> + Sized,
+	{
+		self.pipe(
+			pool,
+			ParallelPipe::<Self::Item>::group_by_with_hasher(Identity, sink, hasher),
+		)
+		.await
+	}
+
+	/// Aggregate the values of each key per time window with `sink`, yielding
+	/// `(window_start, key)` for each window a key has values in. See
+	/// [`window_by`](ParallelStream::window_by) for how windows are assigned.
+	async fn group_by_window<P, F, S, K, V>(
+		self, pool: &P, timestamp: F, size: Duration, slide: Duration, sink: S,
+	) -> IndexMap<(Duration, K), S::Done>
+	where
+		P: ThreadPool,
+		F: ops::FnMut(&V) -> Duration + Clone + Send + 'static,
+		K: Clone + Eq + Hash + Send + 'static,
+		V: Clone + 'static,
+		S: ParallelSink<V>,
+		<S::Pipe as ParallelPipe<V>>::Task: Clone + Send + 'static,
+		S::ReduceA: 'static,
+		S::ReduceC: Clone,
+		S::Done: Send + 'static,
+		Self::Task: 'static,
+		Self: ParallelStream<Item = (K, V)> + Sized,
+	{
+		self.window_by(timestamp, size, slide)
+			.group_by(pool, sink)
+			.await
+	}
+
 	async fn collect<P, B>(self, pool: &P) -> B
 	where
 		P: ThreadPool,
@@ -574,6 +1545,37 @@ stream!(ParallelStream ParallelPipe ParallelSink FromParallelStream IntoParallel
 		self.pipe(pool, ParallelPipe::<Self::Item>::collect(Identity))
 			.await
 	}
+	/// Like [`collect`](ParallelStream::collect), but appending to `into` rather
+	/// than a new collection, so that its capacity can be reused across runs.
+	async fn collect_into<P, B, T>(self, pool: &P, into: &mut B)
+	where
+		P: ThreadPool,
+		B: FromParallelStream<Self::Item> + Extend<T>,
+		B::ReduceA: Send + 'static,
+		<B::ReduceA as ReducerSend<Self::Item>>::Done: IntoIterator<Item = T>,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let (reduce_a, _) = B::reducers();
+		self.reduce(pool, reduce_a, ExtendIntoReducer::new(into)).await;
+	}
+	/// Like [`collect`](ParallelStream::collect), but returning items in source order:
+	/// in the order of the tasks the source yields, such as the row groups of a
+	/// Parquet file, and in order within each. Items are tagged with their
+	/// position and sorted once collected, so they're all buffered before any are
+	/// returned.
+	async fn collect_ordered<P, B>(self, pool: &P) -> B
+	where
+		P: ThreadPool,
+		B: iter::FromIterator<Self::Item>,
+		Self::Item: Send + 'static,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let mut items: Vec<_> = SourceIndexed::new(self).collect(pool).await;
+		items.sort_unstable_by_key(|&(index, _)| index);
+		items.into_iter().map(|(_, item)| item).collect()
+	}
 });
 
 stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream IntoDistributedStream into_dist_stream DistStream ProcessPool ProcessSend traits assert_distributed_stream cfg_attr(not(nightly), serde_closure::desugar) {
@@ -637,6 +1639,8 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 				tasks.iter().map(Vec::len).collect::<Vec<_>>()
 			);
 		}
+		let scheduling = pool.scheduling();
+		let tasks = schedule(tasks, scheduling, pool.is_deterministic());
 
 		let handles = tasks
 			.into_iter()
@@ -644,6 +1648,7 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 			.map(|tasks| {
 				let reduce_b = reduce_b.clone();
 				let reduce_a = reduce_a.clone();
+				// The process's pool schedules its tasks as this pool does
 				pool.spawn(FnOnce!(move |pool: &P::ThreadPool| {
 					let mut process_tasks = tasks.into_iter();
 
@@ -687,6 +1692,7 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 							tasks.iter().map(Vec::len).collect::<Vec<_>>()
 						);
 					}
+					let tasks = schedule(tasks, scheduling, pool.is_deterministic());
 					let handles = tasks
 						.into_iter()
 						.filter(|tasks| !tasks.is_empty())
@@ -704,8 +1710,12 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 								}
 								sink.done().await
 							})
-						})
-						.collect::<futures::stream::FuturesUnordered<_>>();
+						});
+					let handles = if pool.is_deterministic() {
+						handles.collect::<futures::stream::FuturesOrdered<_>>().left_stream()
+					} else {
+						handles.collect::<futures::stream::FuturesUnordered<_>>().right_stream()
+					};
 
 					let stream = handles.map(|item| {
 						item.unwrap_or_else(|err| {
@@ -718,8 +1728,12 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 						stream.sink(reduce_b).await
 					}
 				}))
-			})
-			.collect::<futures::stream::FuturesUnordered<_>>();
+			});
+		let handles = if pool.is_deterministic() {
+			handles.collect::<futures::stream::FuturesOrdered<_>>().left_stream()
+		} else {
+			handles.collect::<futures::stream::FuturesUnordered<_>>().right_stream()
+		};
 		let stream = handles.map(|item| {
 			item.unwrap_or_else(|err| panic!("Amadeus: task '<unnamed>' panicked at '{}'", err))
 		});
@@ -744,6 +1758,22 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 			.await
 	}
 
+	/// Run a tuple of sinks over this stream in a single pass, returning a tuple of
+	/// their outputs. Each item is cloned to each of the sinks.
+	async fn zip_sinks<P, DistSinks, A>(self, pool: &P, sinks: DistSinks) -> A
+	where
+		P: ProcessPool,
+		DistSinks: DistributedSink<Self::Item, Done = A>,
+		<DistSinks::Pipe as DistributedPipe<Self::Item>>::Task: 'static,
+		DistSinks::ReduceA: 'static,
+		DistSinks::ReduceB: 'static,
+		Self::Item: Clone,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		self.pipe(pool, sinks).await
+	}
+
 	// These messy bounds are unfortunately necessary as requiring 'static in DistributedSink breaks sink_b being e.g. Identity.count()
 	async fn fork<P, DistSinkA, DistSinkB, A, B>(
 		self, pool: &P, sink_a: DistSinkA, sink_b: DistSinkB,
@@ -797,6 +1827,57 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 		.await
 	}
 
+	/// Like [`group_by`](DistributedStream::group_by), but grouping with `hasher`.
+	async fn group_by_with_hasher<P, S, H, A, B>(
+		self, pool: &P, sink: S, hasher: H,
+	) -> IndexMap<A, S::Done, H::Hasher>
+	where
+		P: ProcessPool,
+		A: Eq + Hash + ProcessSend + 'static,
+		B: 'static,
+		H: MakeHasher + Clone + ProcessSend + 'static,
+		H::Hasher: Send + 'static,
+		S: DistributedSink<B>,
+		<S::Pipe as DistributedPipe<B>>::Task: Clone + ProcessSend + 'static,
+		S::ReduceA: 'static,
+		S::ReduceB: 'static,
+		S::ReduceC: Clone,
+		S::Done: ProcessSend + 'static,
+		Self::Task: 'static,
+		Self: DistributedStream<Item = (A, B)> + Sized,
+	{
+		self.pipe(
+			pool,
+			DistributedPipe::<Self::Item>::group_by_with_hasher(Identity, sink, hasher),
+		)
+		.await
+	}
+
+	/// Aggregate the values of each key per time window with `sink`, yielding
+	/// `(window_start, key)` for each window a key has values in. See
+	/// [`window_by`](DistributedStream::window_by) for how windows are assigned.
+	async fn group_by_window<P, F, S, K, V>(
+		self, pool: &P, timestamp: F, size: Duration, slide: Duration, sink: S,
+	) -> IndexMap<(Duration, K), S::Done>
+	where
+		P: ProcessPool,
+		F: traits::FnMut(&V) -> Duration + Clone + ProcessSend + 'static,
+		K: Clone + Eq + Hash + ProcessSend + 'static,
+		V: Clone + 'static,
+		S: DistributedSink<V>,
+		<S::Pipe as DistributedPipe<V>>::Task: Clone + ProcessSend + 'static,
+		S::ReduceA: 'static,
+		S::ReduceB: 'static,
+		S::ReduceC: Clone,
+		S::Done: ProcessSend + 'static,
+		Self::Task: 'static,
+		Self: DistributedStream<Item = (K, V)> + Sized,
+	{
+		self.window_by(timestamp, size, slide)
+			.group_by(pool, sink)
+			.await
+	}
+
 	async fn collect<P, B>(self, pool: &P) -> B
 	where
 		P: ProcessPool,
@@ -809,4 +1890,62 @@ stream!(DistributedStream DistributedPipe DistributedSink FromDistributedStream
 		self.pipe(pool, DistributedPipe::<Self::Item>::collect(Identity))
 			.await
 	}
+	/// Like [`collect`](DistributedStream::collect), but appending to `into` rather
+	/// than a new collection, so that its capacity can be reused across runs.
+	async fn collect_into<P, B, T>(self, pool: &P, into: &mut B)
+	where
+		P: ProcessPool,
+		B: FromDistributedStream<Self::Item> + Extend<T>,
+		B::ReduceA: ProcessSend + 'static,
+		B::ReduceB: ProcessSend + 'static,
+		<B::ReduceB as ReducerProcessSend<<B::ReduceA as ReducerSend<Self::Item>>::Done>>::Done: IntoIterator<Item = T>,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let (reduce_a, reduce_b, _) = B::reducers();
+		self.reduce(pool, reduce_a, reduce_b, ExtendIntoReducer::new(into)).await;
+	}
+	/// Like [`collect`](DistributedStream::collect), but returning items in source order:
+	/// in the order of the tasks the source yields, such as the row groups of a
+	/// Parquet file, and in order within each. Items are tagged with their
+	/// position and sorted once collected, so they're all buffered before any are
+	/// returned.
+	async fn collect_ordered<P, B>(self, pool: &P) -> B
+	where
+		P: ProcessPool,
+		B: iter::FromIterator<Self::Item>,
+		Self::Item: ProcessSend + 'static,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let mut items: Vec<_> = SourceIndexed::new(self).collect(pool).await;
+		items.sort_unstable_by_key(|&(index, _)| index);
+		items.into_iter().map(|(_, item)| item).collect()
+	}
 });
+
+/// Reallocate `tasks`, allocated in order among workers, according to
+/// `scheduling`. Deterministic pools keep them in order, so that they're reduced
+/// in source order.
+fn schedule<T: StreamTask>(
+	tasks: Vec<Vec<T>>, scheduling: Scheduling, deterministic: bool,
+) -> Vec<Vec<T>> {
+	match scheduling {
+		Scheduling::LargestFirst if !deterministic => {
+			let mut workers = tasks.iter().map(|_| (0_u64, vec![])).collect::<Vec<_>>();
+			let mut tasks = tasks
+				.into_iter()
+				.flatten()
+				.map(|task| (task.cost().unwrap_or(1), task))
+				.collect::<Vec<_>>();
+			tasks.sort_by_key(|&(cost, _)| cmp::Reverse(cost));
+			for (cost, task) in tasks {
+				let (load, worker) = workers.iter_mut().min_by_key(|(load, _)| *load).unwrap();
+				*load = load.saturating_add(cost);
+				worker.push(task);
+			}
+			workers.into_iter().map(|(_, tasks)| tasks).collect()
+		}
+		_ => tasks,
+	}
+}
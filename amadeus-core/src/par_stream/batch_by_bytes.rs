@@ -0,0 +1,186 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	mem, pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct BatchByBytes<P, F> {
+	#[pin]
+	pipe: P,
+	max_bytes: usize,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F> ParallelStream for BatchByBytes<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Item,), Output = usize> + Clone + Send + 'static,
+	{
+		type Item = Vec<P::Item>;
+		type Task = BatchByBytesTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, self.pipe.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (max_bytes, f) = (*self_.max_bytes, self_.f);
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| BatchByBytesTask::new(task, max_bytes, f.clone())))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, Input> ParallelPipe<Input> for BatchByBytes<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = usize> + Clone + Send + 'static,
+	{
+		type Output = Vec<P::Output>;
+		type Task = BatchByBytesTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			BatchByBytesTask::new(self.pipe.task(), self.max_bytes, self.f.clone())
+		}
+	}
+}
+
+#[pin_project]
+#[derive(Serialize, Deserialize, new)]
+pub struct BatchByBytesTask<T, F> {
+	#[pin]
+	task: T,
+	max_bytes: usize,
+	f: F,
+}
+
+impl<C: StreamTask, F> StreamTask for BatchByBytesTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = usize> + Clone,
+{
+	type Item = Vec<C::Item>;
+	type Async = BatchByBytesAsync<C::Async, F, C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		BatchByBytesAsync::new(self.task.into_async(), self.max_bytes, self.f)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F, Input> PipeTask<Input> for BatchByBytesTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = usize> + Clone,
+{
+	type Output = Vec<C::Output>;
+	type Async = BatchByBytesAsync<C::Async, F, C::Output>;
+
+	fn into_async(self) -> Self::Async {
+		BatchByBytesAsync::new(self.task.into_async(), self.max_bytes, self.f)
+	}
+}
+
+/// The current batch is held by the task, so batches don't span partitions.
+#[pin_project]
+#[derive(new)]
+pub struct BatchByBytesAsync<T, F, Item> {
+	#[pin]
+	task: T,
+	max_bytes: usize,
+	f: F,
+	#[new(default)]
+	batch: Batch<Item>,
+	#[new(default)]
+	done: bool,
+}
+
+struct Batch<Item> {
+	items: Vec<Item>,
+	bytes: usize,
+}
+impl<Item> Default for Batch<Item> {
+	fn default() -> Self {
+		Self {
+			items: Vec::new(),
+			bytes: 0,
+		}
+	}
+}
+impl<Item> Batch<Item> {
+	/// Add `item` of size `bytes` to the batch, returning the batch so far if
+	/// it would take it over `max_bytes`.
+	fn push(&mut self, item: Item, bytes: usize, max_bytes: usize) -> Option<Vec<Item>> {
+		let full = !self.items.is_empty() && self.bytes.saturating_add(bytes) > max_bytes;
+		let batch = if full {
+			self.bytes = 0;
+			Some(mem::take(&mut self.items))
+		} else {
+			None
+		};
+		self.items.push(item);
+		self.bytes = self.bytes.saturating_add(bytes);
+		batch
+	}
+	fn take(&mut self) -> Option<Vec<Item>> {
+		self.bytes = 0;
+		Some(mem::take(&mut self.items)).filter(|items| !items.is_empty())
+	}
+}
+
+impl<C: Stream, F> Stream for BatchByBytesAsync<C, F, C::Item>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = usize> + Clone,
+{
+	type Item = Vec<C::Item>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		if *self_.done {
+			return Poll::Ready(None);
+		}
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx)) {
+			let bytes = self_.f.call_mut((&item,));
+			if let Some(batch) = self_.batch.push(item, bytes, *self_.max_bytes) {
+				return Poll::Ready(Some(batch));
+			}
+		}
+		*self_.done = true;
+		Poll::Ready(self_.batch.take())
+	}
+}
+
+impl<C: Pipe<Input>, F, Input> Pipe<Input> for BatchByBytesAsync<C, F, C::Output>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = usize> + Clone,
+{
+	type Output = Vec<C::Output>;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		if *self_.done {
+			return Poll::Ready(None);
+		}
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+			let bytes = self_.f.call_mut((&item,));
+			if let Some(batch) = self_.batch.push(item, bytes, *self_.max_bytes) {
+				return Poll::Ready(Some(batch));
+			}
+		}
+		*self_.done = true;
+		Poll::Ready(self_.batch.take())
+	}
+}
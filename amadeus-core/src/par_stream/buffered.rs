@@ -0,0 +1,127 @@
+use derive_new::new;
+use futures::{
+	channel::mpsc, future::{self, RemoteHandle}, pin_mut, ready, Future, FutureExt, Stream, StreamExt
+};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Buffered<P> {
+	#[pin]
+	stream: P,
+	capacity: usize,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for Buffered<P>
+	where
+		<P::Task as StreamTask>::Async: 'static,
+		P::Item: 'static,
+	{
+		type Item = P::Item;
+		type Task = BufferedTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.stream.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.stream.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let capacity = *self_.capacity;
+			self_
+				.stream
+				.next_task(cx)
+				.map(|task| task.map(|task| BufferedTask::new(task, capacity)))
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct BufferedTask<T> {
+	task: T,
+	capacity: usize,
+}
+
+impl<C: StreamTask> StreamTask for BufferedTask<C>
+where
+	C::Async: 'static,
+	C::Item: 'static,
+{
+	type Item = C::Item;
+	type Async = BufferedAsync<C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		BufferedAsync::new(self.task.into_async(), self.capacity)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+
+/// Upstream runs in a task of its own on the current thread, sending its items
+/// over a channel of `capacity`, so it's polled while downstream is busy but not
+/// once the channel is full. It's cancelled if this is dropped, and a panic in
+/// it is resumed here.
+#[pin_project]
+pub struct BufferedAsync<Item> {
+	#[pin]
+	receiver: mpsc::Receiver<Item>,
+	handle: Option<RemoteHandle<()>>,
+}
+
+impl<Item: 'static> BufferedAsync<Item> {
+	fn new<S: Stream<Item = Item> + 'static>(stream: S, capacity: usize) -> Self {
+		let (mut sender, receiver) = mpsc::channel(capacity - 1);
+		let (remote, handle) = async move {
+			pin_mut!(stream);
+			// Wait for room before pulling each item, so that no more than
+			// `capacity` are read ahead
+			while future::poll_fn(|cx| sender.poll_ready(cx)).await.is_ok() {
+				match stream.next().await {
+					Some(item) => {
+						if sender.start_send(item).is_err() {
+							break;
+						}
+					}
+					None => break,
+				}
+			}
+		}
+		.remote_handle();
+		#[cfg(not(target_arch = "wasm32"))]
+		drop(tokio::task::spawn_local(remote));
+		#[cfg(target_arch = "wasm32")]
+		wasm_bindgen_futures::spawn_local(remote);
+		Self {
+			receiver,
+			handle: Some(handle),
+		}
+	}
+}
+
+impl<Item> Stream for BufferedAsync<Item> {
+	type Item = Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		if let Some(item) = ready!(self_.receiver.poll_next(cx)) {
+			return Poll::Ready(Some(item));
+		}
+		// Upstream has finished or panicked
+		if let Some(handle) = self_.handle {
+			ready!(Pin::new(handle).poll(cx));
+			*self_.handle = None;
+		}
+		Poll::Ready(None)
+	}
+}
@@ -0,0 +1,170 @@
+use derive_new::new;
+use futures::Stream;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	any::Any, error, fmt, panic::{self, AssertUnwindSafe}, pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+/// What [`catch_panics`](super::ParallelStream::catch_panics) does with an item
+/// whose processing panicked.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum PanicPolicy {
+	/// Yield `Err(Panicked)` in its place, then end the partition.
+	Error,
+	/// End the partition.
+	Drop,
+}
+
+/// The error yielded for an item that panicked under [`PanicPolicy::Error`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Panicked {
+	/// The panic message, if it was a string.
+	pub message: Option<String>,
+}
+impl Panicked {
+	fn new(payload: &(dyn Any + Send)) -> Self {
+		let message = payload
+			.downcast_ref::<&str>()
+			.map(|message| (*message).to_owned())
+			.or_else(|| payload.downcast_ref::<String>().cloned());
+		Self { message }
+	}
+}
+impl error::Error for Panicked {}
+impl fmt::Display for Panicked {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match &self.message {
+			Some(message) => write!(f, "item panicked: {}", message),
+			None => f.write_str("item panicked"),
+		}
+	}
+}
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct CatchPanics<P> {
+	#[pin]
+	pipe: P,
+	policy: PanicPolicy,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for CatchPanics<P> {
+		type Item = Result<P::Item, Panicked>;
+		type Task = CatchPanicsTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			// A panic ends its partition early under either policy
+			(0, self.pipe.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let policy = *self_.policy;
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| CatchPanicsTask::new(task, policy)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for CatchPanics<P> {
+		type Output = Result<P::Output, Panicked>;
+		type Task = CatchPanicsTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			CatchPanicsTask::new(self.pipe.task(), self.policy)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct CatchPanicsTask<T> {
+	task: T,
+	policy: PanicPolicy,
+}
+
+impl<C: StreamTask> StreamTask for CatchPanicsTask<C> {
+	type Item = Result<C::Item, Panicked>;
+	type Async = CatchPanicsAsync<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		CatchPanicsAsync::new(self.task.into_async(), self.policy)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for CatchPanicsTask<C> {
+	type Output = Result<C::Output, Panicked>;
+	type Async = CatchPanicsAsync<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		CatchPanicsAsync::new(self.task.into_async(), self.policy)
+	}
+}
+
+/// Polls upstream within [`catch_unwind`](std::panic::catch_unwind). Upstream
+/// can be left in any state by a panic, so it isn't polled again: the rest of
+/// the partition is lost.
+#[pin_project]
+#[derive(new)]
+pub struct CatchPanicsAsync<T> {
+	#[pin]
+	task: T,
+	policy: PanicPolicy,
+	#[new(default)]
+	panicked: bool,
+}
+
+impl<C: Stream> Stream for CatchPanicsAsync<C> {
+	type Item = Result<C::Item, Panicked>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		let mut task = self_.task;
+		caught(self_.panicked, *self_.policy, || {
+			task.as_mut().poll_next(cx)
+		})
+	}
+}
+
+impl<C: Pipe<Input>, Input> Pipe<Input> for CatchPanicsAsync<C> {
+	type Output = Result<C::Output, Panicked>;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let self_ = self.project();
+		let mut task = self_.task;
+		caught(self_.panicked, *self_.policy, || {
+			task.as_mut().poll_next(cx, stream.as_mut())
+		})
+	}
+}
+
+/// Polls with `poll`, ending the partition once it has panicked.
+fn caught<T>(
+	panicked: &mut bool, policy: PanicPolicy, poll: impl FnOnce() -> Poll<Option<T>>,
+) -> Poll<Option<Result<T, Panicked>>> {
+	if *panicked {
+		return Poll::Ready(None);
+	}
+	match panic::catch_unwind(AssertUnwindSafe(poll)) {
+		Ok(poll) => poll.map(|item| item.map(Ok)),
+		Err(payload) => {
+			*panicked = true;
+			match policy {
+				PanicPolicy::Error => Poll::Ready(Some(Err(Panicked::new(&*payload)))),
+				PanicPolicy::Drop => Poll::Ready(None),
+			}
+		}
+	}
+}
@@ -7,6 +7,7 @@ use std::{
 };
 
 use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
 
 #[pin_project]
 #[derive(new)]
@@ -35,6 +36,9 @@ impl_par_dist! {
 				},
 			)
 		}
+		fn explain(&self) -> Plan {
+			self.a.explain().merge::<Self>(self.b.explain())
+		}
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			let self_ = self.project();
 			match self_.a.next_task(cx) {
@@ -62,6 +66,12 @@ impl<A: StreamTask, B: StreamTask<Item = A::Item>> StreamTask for ChainTask<A, B
 			ChainTask::B(b) => ChainTask::B(b.into_async()),
 		}
 	}
+	fn cost(&self) -> Option<u64> {
+		match self {
+			ChainTask::A(a) => a.cost(),
+			ChainTask::B(b) => b.cost(),
+		}
+	}
 }
 impl<A: Stream, B: Stream<Item = A::Item>> Stream for ChainTask<A, B> {
 	type Item = A::Item;
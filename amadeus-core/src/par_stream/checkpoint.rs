@@ -0,0 +1,169 @@
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashSet, fs, io::{self, Write}, path::{Path, PathBuf}, pin::Pin, sync::Arc, task::{Context, Poll}
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::{plan::Plan, util::IoError};
+
+/// A record of which partitions of a stream have completed, kept in a file of
+/// partition indices, one per line.
+///
+/// Each partition is appended to the record by the task that ran it, as soon as
+/// its last item has been taken by the pipeline's sink, so a run that fails
+/// partway through keeps the partitions that completed before the failure.
+#[derive(Clone, Debug)]
+pub struct CheckpointStore {
+	path: PathBuf,
+	completed: Arc<HashSet<u64>>,
+}
+impl CheckpointStore {
+	/// Open the record at `path`, reading the partitions completed by earlier
+	/// runs if it exists.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, IoError> {
+		let path = path.as_ref().to_owned();
+		let completed = match fs::read_to_string(&path) {
+			Ok(contents) => {
+				// A line cut short by a failure while it was being written
+				// isn't terminated, and its partition is repeated
+				let mut lines = contents.split_terminator('\n').collect::<Vec<_>>();
+				if !contents.ends_with('\n') {
+					let _ = lines.pop();
+				}
+				lines
+					.into_iter()
+					.map(|line| {
+						line.parse()
+							.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+					})
+					.collect::<Result<_, _>>()?
+			}
+			Err(err) if err.kind() == io::ErrorKind::NotFound => HashSet::new(),
+			Err(err) => return Err(err.into()),
+		};
+		Ok(Self {
+			path,
+			completed: Arc::new(completed),
+		})
+	}
+	/// The partitions completed by earlier runs, by index.
+	pub fn completed(&self) -> &HashSet<u64> {
+		&self.completed
+	}
+	/// Delete the record, so the next run starts from scratch.
+	pub fn clear(self) -> Result<(), IoError> {
+		match fs::remove_file(&self.path) {
+			Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[pin_project]
+#[must_use]
+pub struct Checkpoint<P> {
+	#[pin]
+	stream: P,
+	store: CheckpointStore,
+	next: u64,
+}
+impl<P> Checkpoint<P> {
+	pub fn new(stream: P, store: CheckpointStore) -> Self {
+		Self {
+			stream,
+			store,
+			next: 0,
+		}
+	}
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for Checkpoint<P> {
+		type Item = P::Item;
+		type Task = CheckpointTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, self.stream.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.stream.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let mut self_ = self.project();
+			loop {
+				let task = ready!(self_.stream.as_mut().next_task(cx));
+				let index = *self_.next;
+				*self_.next += 1;
+				match task {
+					Some(task) if !self_.store.completed.contains(&index) => {
+						let path = self_.store.path.clone();
+						break Poll::Ready(Some(CheckpointTask::new(task, path, index)));
+					}
+					Some(_) => (),
+					None => break Poll::Ready(None),
+				}
+			}
+		}
+	}
+}
+
+/// A partition that appends its index to the record at `path` once it has
+/// finished.
+#[pin_project]
+#[derive(Serialize, Deserialize)]
+pub struct CheckpointTask<T> {
+	#[pin]
+	task: T,
+	path: Option<PathBuf>,
+	index: u64,
+}
+impl<T> CheckpointTask<T> {
+	fn new(task: T, path: PathBuf, index: u64) -> Self {
+		Self {
+			task,
+			path: Some(path),
+			index,
+		}
+	}
+}
+
+impl<C: StreamTask> StreamTask for CheckpointTask<C> {
+	type Item = C::Item;
+	type Async = CheckpointTask<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		CheckpointTask {
+			task: self.task.into_async(),
+			path: self.path,
+			index: self.index,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+
+impl<C: Stream> Stream for CheckpointTask<C> {
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		let item = ready!(self_.task.poll_next(cx));
+		if item.is_none() {
+			if let Some(path) = self_.path.take() {
+				// Written with a single append, so that the lines of concurrently
+				// completing partitions aren't interleaved. Should it fail, the
+				// partition is just repeated on restart.
+				let line = format!("{}\n", self_.index);
+				let _ = fs::OpenOptions::new()
+					.create(true)
+					.append(true)
+					.open(path)
+					.and_then(|mut file| file.write_all(line.as_bytes()));
+			}
+		}
+		Poll::Ready(item)
+	}
+}
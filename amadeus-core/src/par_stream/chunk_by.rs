@@ -0,0 +1,173 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct ChunkBy<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, K> ParallelStream for ChunkBy<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Item,), Output = K> + Clone + Send + 'static,
+		K: PartialEq,
+	{
+		type Item = (K, Vec<P::Item>);
+		type Task = ChunkByTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, self.pipe.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let f = self_.f;
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| ChunkByTask::new(task, f.clone())))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, K, Input> ParallelPipe<Input> for ChunkBy<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = K> + Clone + Send + 'static,
+		K: PartialEq,
+	{
+		type Output = (K, Vec<P::Output>);
+		type Task = ChunkByTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			ChunkByTask::new(self.pipe.task(), self.f.clone())
+		}
+	}
+}
+
+#[pin_project]
+#[derive(Serialize, Deserialize, new)]
+pub struct ChunkByTask<T, F> {
+	#[pin]
+	task: T,
+	f: F,
+}
+
+impl<C: StreamTask, F, K> StreamTask for ChunkByTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = K> + Clone,
+	K: PartialEq,
+{
+	type Item = (K, Vec<C::Item>);
+	type Async = ChunkByAsync<C::Async, F, K, C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		ChunkByAsync::new(self.task.into_async(), self.f)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F, K, Input> PipeTask<Input> for ChunkByTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = K> + Clone,
+	K: PartialEq,
+{
+	type Output = (K, Vec<C::Output>);
+	type Async = ChunkByAsync<C::Async, F, K, C::Output>;
+
+	fn into_async(self) -> Self::Async {
+		ChunkByAsync::new(self.task.into_async(), self.f)
+	}
+}
+
+/// The current run is held by the task, so runs don't span partitions.
+#[pin_project]
+#[derive(new)]
+pub struct ChunkByAsync<T, F, K, Item> {
+	#[pin]
+	task: T,
+	f: F,
+	#[new(default)]
+	run: Option<(K, Vec<Item>)>,
+	#[new(default)]
+	done: bool,
+}
+
+/// Add `item` to the current run, returning the previous run if `item` starts a
+/// new one.
+fn push<F, K, Item>(
+	f: &mut F, run: &mut Option<(K, Vec<Item>)>, item: Item,
+) -> Option<(K, Vec<Item>)>
+where
+	F: for<'a> FnMut<(&'a Item,), Output = K>,
+	K: PartialEq,
+{
+	let key = f.call_mut((&item,));
+	match run {
+		Some((run_key, items)) if *run_key == key => {
+			items.push(item);
+			None
+		}
+		_ => run.replace((key, vec![item])),
+	}
+}
+
+impl<C: Stream, F, K> Stream for ChunkByAsync<C, F, K, C::Item>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = K> + Clone,
+	K: PartialEq,
+{
+	type Item = (K, Vec<C::Item>);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		if *self_.done {
+			return Poll::Ready(None);
+		}
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx)) {
+			if let Some(run) = push(self_.f, self_.run, item) {
+				return Poll::Ready(Some(run));
+			}
+		}
+		*self_.done = true;
+		Poll::Ready(self_.run.take())
+	}
+}
+
+impl<C: Pipe<Input>, F, K, Input> Pipe<Input> for ChunkByAsync<C, F, K, C::Output>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = K> + Clone,
+	K: PartialEq,
+{
+	type Output = (K, Vec<C::Output>);
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		if *self_.done {
+			return Poll::Ready(None);
+		}
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+			if let Some(run) = push(self_.f, self_.run, item) {
+				return Poll::Ready(Some(run));
+			}
+		}
+		*self_.done = true;
+		Poll::Ready(self_.run.take())
+	}
+}
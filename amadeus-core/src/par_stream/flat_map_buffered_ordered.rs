@@ -0,0 +1,238 @@
+use derive_new::new;
+use futures::Stream;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	collections::VecDeque, pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct FlatMapBufferedOrdered<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+	concurrency: usize,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, R: Stream> ParallelStream for FlatMapBufferedOrdered<P, F>
+	where
+		F: FnMut<(P::Item,), Output = R> + Clone + Send + 'static,
+	{
+		type Item = R::Item;
+		type Task = FlatMapBufferedOrderedTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, None)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (f, concurrency) = (self_.f, *self_.concurrency);
+			self_.pipe.next_task(cx).map(|task| {
+				task.map(|task| FlatMapBufferedOrderedTask::new(task, f.clone(), concurrency))
+			})
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, R: Stream, Input> ParallelPipe<Input> for FlatMapBufferedOrdered<P, F>
+	where
+		F: FnMut<(P::Output,), Output = R> + Clone + Send + 'static,
+	{
+		type Output = R::Item;
+		type Task = FlatMapBufferedOrderedTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			FlatMapBufferedOrderedTask::new(self.pipe.task(), self.f.clone(), self.concurrency)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct FlatMapBufferedOrderedTask<C, F> {
+	task: C,
+	f: F,
+	concurrency: usize,
+}
+impl<C: StreamTask, F: FnMut<(C::Item,), Output = R> + Clone, R: Stream> StreamTask
+	for FlatMapBufferedOrderedTask<C, F>
+{
+	type Item = R::Item;
+	type Async = FlatMapBufferedOrderedAsync<C::Async, F, R>;
+
+	fn into_async(self) -> Self::Async {
+		FlatMapBufferedOrderedAsync::new(self.task.into_async(), self.f, self.concurrency)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F: FnMut<(C::Output,), Output = R> + Clone, R: Stream, Input>
+	PipeTask<Input> for FlatMapBufferedOrderedTask<C, F>
+{
+	type Output = R::Item;
+	type Async = FlatMapBufferedOrderedAsync<C::Async, F, R>;
+
+	fn into_async(self) -> Self::Async {
+		FlatMapBufferedOrderedAsync::new(self.task.into_async(), self.f, self.concurrency)
+	}
+}
+
+/// Polls up to `concurrency` inner streams at once, yielding the items of the
+/// oldest while buffering those of the rest until they become the oldest.
+#[pin_project]
+#[derive(new)]
+pub struct FlatMapBufferedOrderedAsync<T, F, R: Stream> {
+	#[pin]
+	task: T,
+	f: F,
+	concurrency: usize,
+	#[new(default)]
+	streams: Inner<R>,
+	#[new(default)]
+	done: bool,
+}
+
+impl<C: Stream, F, R: Stream> Stream for FlatMapBufferedOrderedAsync<C, F, R>
+where
+	F: FnMut<(C::Item,), Output = R>,
+{
+	type Item = R::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		loop {
+			while !*self_.done && self_.streams.0.len() < *self_.concurrency {
+				match self_.task.as_mut().poll_next(cx) {
+					Poll::Ready(Some(item)) => self_.streams.push(self_.f.call_mut((item,))),
+					Poll::Ready(None) => *self_.done = true,
+					Poll::Pending => break,
+				}
+			}
+			match self_.streams.poll_next(cx) {
+				Next::Item(item) => break Poll::Ready(Some(item)),
+				Next::Finished => (),
+				Next::Empty if *self_.done => break Poll::Ready(None),
+				Next::Empty | Next::Pending => break Poll::Pending,
+			}
+		}
+	}
+}
+
+impl<C: Pipe<Input>, F, R: Stream, Input> Pipe<Input> for FlatMapBufferedOrderedAsync<C, F, R>
+where
+	F: FnMut<(C::Output,), Output = R>,
+{
+	type Output = R::Item;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		loop {
+			while !*self_.done && self_.streams.0.len() < *self_.concurrency {
+				match self_.task.as_mut().poll_next(cx, stream.as_mut()) {
+					Poll::Ready(Some(item)) => self_.streams.push(self_.f.call_mut((item,))),
+					Poll::Ready(None) => *self_.done = true,
+					Poll::Pending => break,
+				}
+			}
+			match self_.streams.poll_next(cx) {
+				Next::Item(item) => break Poll::Ready(Some(item)),
+				Next::Finished => (),
+				Next::Empty if *self_.done => break Poll::Ready(None),
+				Next::Empty | Next::Pending => break Poll::Pending,
+			}
+		}
+	}
+}
+
+/// The most items an inner stream buffers while those before it are yielded.
+const MAX_BUFFERED: usize = 1024;
+
+/// The in-flight inner streams, in input order.
+struct Inner<R: Stream>(VecDeque<InnerStream<R>>);
+impl<R: Stream> Default for Inner<R> {
+	fn default() -> Self {
+		Self(VecDeque::new())
+	}
+}
+
+/// An inner stream, with the items it has yielded ahead of those before it.
+struct InnerStream<R: Stream> {
+	stream: Pin<Box<R>>,
+	buffer: VecDeque<R::Item>,
+	done: bool,
+}
+
+enum Next<T> {
+	Item(T),
+	/// The oldest stream finished, so there's room for another.
+	Finished,
+	Empty,
+	Pending,
+}
+
+impl<R: Stream> Inner<R> {
+	fn push(&mut self, stream: R) {
+		self.0.push_back(InnerStream {
+			stream: Box::pin(stream),
+			buffer: VecDeque::new(),
+			done: false,
+		});
+	}
+	fn poll_next(&mut self, cx: &mut Context) -> Next<R::Item> {
+		// Poll each of the rest once rather than to exhaustion, so an always-ready
+		// stream can't starve the oldest; wake to poll them again if they progressed.
+		// Those with full buffers wait until they're the oldest.
+		let mut progressed = false;
+		for inner in self
+			.0
+			.iter_mut()
+			.skip(1)
+			.filter(|inner| !inner.done && inner.buffer.len() < MAX_BUFFERED)
+		{
+			match inner.stream.as_mut().poll_next(cx) {
+				Poll::Ready(Some(item)) => {
+					inner.buffer.push_back(item);
+					progressed = true;
+				}
+				Poll::Ready(None) => inner.done = true,
+				Poll::Pending => (),
+			}
+		}
+		match self.0.front_mut().map(|front| front.poll_next(cx)) {
+			Some(Poll::Ready(Some(item))) => Next::Item(item),
+			Some(Poll::Ready(None)) => {
+				let _ = self.0.pop_front();
+				Next::Finished
+			}
+			Some(Poll::Pending) => {
+				if progressed {
+					cx.waker().wake_by_ref();
+				}
+				Next::Pending
+			}
+			None => Next::Empty,
+		}
+	}
+}
+impl<R: Stream> InnerStream<R> {
+	fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<R::Item>> {
+		if let Some(item) = self.buffer.pop_front() {
+			return Poll::Ready(Some(item));
+		}
+		if self.done {
+			return Poll::Ready(None);
+		}
+		self.stream.as_mut().poll_next(cx)
+	}
+}
@@ -0,0 +1,150 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct FlatMapOrderedIter<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, R: IntoIterator> ParallelStream for FlatMapOrderedIter<P, F>
+	where
+		F: FnMut<(P::Item,), Output = R> + Clone + Send + 'static,
+	{
+		type Item = (usize, R::Item);
+		type Task = FlatMapOrderedIterTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, None)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let f = self_.f;
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| FlatMapOrderedIterTask::new(task, f.clone())))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, R: IntoIterator, Input> ParallelPipe<Input> for FlatMapOrderedIter<P, F>
+	where
+		F: FnMut<(P::Output,), Output = R> + Clone + Send + 'static,
+	{
+		type Output = (usize, R::Item);
+		type Task = FlatMapOrderedIterTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			FlatMapOrderedIterTask::new(self.pipe.task(), self.f.clone())
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct FlatMapOrderedIterTask<C, F> {
+	task: C,
+	f: F,
+}
+impl<C: StreamTask, F: FnMut<(C::Item,), Output = R> + Clone, R: IntoIterator> StreamTask
+	for FlatMapOrderedIterTask<C, F>
+{
+	type Item = (usize, R::Item);
+	type Async = FlatMapOrderedIterAsync<C::Async, F, R>;
+
+	fn into_async(self) -> Self::Async {
+		FlatMapOrderedIterAsync::new(self.task.into_async(), self.f)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F: FnMut<(C::Output,), Output = R> + Clone, R: IntoIterator, Input>
+	PipeTask<Input> for FlatMapOrderedIterTask<C, F>
+{
+	type Output = (usize, R::Item);
+	type Async = FlatMapOrderedIterAsync<C::Async, F, R>;
+
+	fn into_async(self) -> Self::Async {
+		FlatMapOrderedIterAsync::new(self.task.into_async(), self.f)
+	}
+}
+
+/// Drains the iterator of each item in turn, tagging its outputs with `index`,
+/// the position of the item within the partition.
+#[pin_project]
+#[derive(new)]
+pub struct FlatMapOrderedIterAsync<T, F, R: IntoIterator> {
+	#[pin]
+	task: T,
+	f: F,
+	#[new(default)]
+	next: Option<R::IntoIter>,
+	#[new(default)]
+	index: usize,
+}
+
+impl<C: Stream, F, R: IntoIterator> Stream for FlatMapOrderedIterAsync<C, F, R>
+where
+	F: FnMut<(C::Item,), Output = R>,
+{
+	type Item = (usize, R::Item);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		Poll::Ready(loop {
+			if let Some(iter) = self_.next.as_mut() {
+				if let Some(item) = iter.next() {
+					break Some((*self_.index - 1, item));
+				}
+				*self_.next = None;
+			} else if let Some(item) = ready!(self_.task.as_mut().poll_next(cx)) {
+				*self_.next = Some(self_.f.call_mut((item,)).into_iter());
+				*self_.index += 1;
+			} else {
+				break None;
+			}
+		})
+	}
+}
+
+impl<C: Pipe<Input>, F, R: IntoIterator, Input> Pipe<Input> for FlatMapOrderedIterAsync<C, F, R>
+where
+	F: FnMut<(C::Output,), Output = R>,
+{
+	type Output = (usize, R::Item);
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		Poll::Ready(loop {
+			if let Some(iter) = self_.next.as_mut() {
+				if let Some(item) = iter.next() {
+					break Some((*self_.index - 1, item));
+				}
+				*self_.next = None;
+			} else if let Some(item) = ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+				*self_.next = Some(self_.f.call_mut((item,)).into_iter());
+				*self_.index += 1;
+			} else {
+				break None;
+			}
+		})
+	}
+}
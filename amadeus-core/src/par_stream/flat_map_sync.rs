@@ -7,6 +7,7 @@ use std::{
 };
 
 use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::plan::Plan;
 
 #[pin_project]
 #[derive(new)]
@@ -28,6 +29,9 @@ impl_par_dist! {
 		fn size_hint(&self) -> (usize, Option<usize>) {
 			(0, None)
 		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			let self_ = self.project();
 			let f = self_.f;
@@ -69,6 +73,9 @@ impl<C: StreamTask, F: FnMut<(C::Item,), Output = R> + Clone, R: Iterator> Strea
 	fn into_async(self) -> Self::Async {
 		crate::pipe::FlatMapSync::new(self.task.into_async(), self.f)
 	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
 }
 impl<C: PipeTask<Input>, F: FnMut<(C::Output,), Output = R> + Clone, R: Iterator, Input>
 	PipeTask<Input> for FlatMapSyncTask<C, F>
@@ -0,0 +1,117 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Fuse<P> {
+	#[pin]
+	pipe: P,
+	#[new(default)]
+	done: bool,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for Fuse<P> {
+		type Item = P::Item;
+		type Task = FuseTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			if self.done {
+				(0, Some(0))
+			} else {
+				self.pipe.size_hint()
+			}
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			if *self_.done {
+				return Poll::Ready(None);
+			}
+			let task = ready!(self_.pipe.next_task(cx));
+			*self_.done = task.is_none();
+			Poll::Ready(task.map(FuseTask::new))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for Fuse<P> {
+		type Output = P::Output;
+		type Task = FuseTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			FuseTask::new(self.pipe.task())
+		}
+	}
+}
+
+/// Once the wrapped task has finished, it isn't polled again and `None` is
+/// returned instead.
+#[pin_project]
+#[derive(Serialize, Deserialize, new)]
+pub struct FuseTask<T> {
+	#[pin]
+	task: T,
+	#[new(default)]
+	done: bool,
+}
+
+impl<C: StreamTask> StreamTask for FuseTask<C> {
+	type Item = C::Item;
+	type Async = FuseTask<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		FuseTask::new(self.task.into_async())
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for FuseTask<C> {
+	type Output = C::Output;
+	type Async = FuseTask<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		FuseTask::new(self.task.into_async())
+	}
+}
+
+impl<C: Stream> Stream for FuseTask<C> {
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		if *self_.done {
+			return Poll::Ready(None);
+		}
+		let item = ready!(self_.task.poll_next(cx));
+		*self_.done = item.is_none();
+		Poll::Ready(item)
+	}
+}
+
+impl<C: Pipe<Input>, Input> Pipe<Input> for FuseTask<C> {
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let self_ = self.project();
+		if *self_.done {
+			return Poll::Ready(None);
+		}
+		let item = ready!(self_.task.poll_next(cx, stream));
+		*self_.done = item.is_none();
+		Poll::Ready(item)
+	}
+}
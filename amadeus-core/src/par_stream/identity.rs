@@ -6,7 +6,7 @@ use std::{
 };
 
 use super::{
-	All, Any, Collect, Combine, Count, Filter, FlatMap, Fold, ForEach, Fork, GroupBy, Histogram, Inspect, Map, Max, MaxBy, MaxByKey, Mean, Min, MinBy, MinByKey, MostDistinct, MostFrequent, ParallelPipe, Pipe, PipeTask, SampleUnstable, StdDev, Sum, Update
+	All, Any, Collect, Combine, Count, Filter, FlatMap, Fold, ForEach, Fork, GroupBy, Histogram, Inspect, Map, Max, MaxBy, MaxByKey, Mean, Min, MinBy, MinByKey, MostDistinct, MostFrequent, ParallelPipe, Pipe, PipeTask, RandomHasher, SampleUnstable, StdDev, Sum, Update
 };
 
 // TODO: add type parameter to Identity when type the type system includes HRTB in the ParallelPipe impl https://github.com/dtolnay/ghost/
@@ -112,7 +112,12 @@ mod workaround {
 
 		#[inline]
 		pub fn group_by<S>(self, sink: S) -> GroupBy<Self, S> {
-			GroupBy::new(self, sink)
+			GroupBy::new(self, sink, RandomHasher)
+		}
+
+		#[inline]
+		pub fn group_by_with_hasher<S, H>(self, sink: S, hasher: H) -> GroupBy<Self, S, H> {
+			GroupBy::new(self, sink, hasher)
 		}
 
 		#[inline]
@@ -197,7 +202,15 @@ mod workaround {
 		pub fn most_frequent(
 			self, n: usize, probability: f64, tolerance: f64,
 		) -> MostFrequent<Self> {
-			MostFrequent::new(self, n, probability, tolerance)
+			MostFrequent::new(self, n, probability, tolerance, usize::MAX)
+		}
+
+		#[inline]
+		pub fn most_frequent_bounded(
+			self, n: usize, probability: f64, tolerance: f64, max_counters: usize,
+		) -> MostFrequent<Self> {
+			assert!(max_counters >= 2, "max_counters must be at least 2");
+			MostFrequent::new(self, n, probability, tolerance, max_counters)
 		}
 
 		#[inline]
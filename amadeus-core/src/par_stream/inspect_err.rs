@@ -0,0 +1,140 @@
+use derive_new::new;
+use futures::Stream;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct InspectErr<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream<Item = Result<T, E>>, F, T, E> ParallelStream for InspectErr<P, F>
+	where
+		F: for<'a> FnMut<(&'a E,), Output = ()> + Clone + Send + 'static,
+	{
+		type Item = P::Item;
+		type Task = InspectErrTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let f = self_.f;
+			self_.pipe.next_task(cx).map(|task| {
+				task.map(|task| {
+					let f = f.clone();
+					InspectErrTask { task, f }
+				})
+			})
+		}
+	}
+
+	impl<P: ParallelPipe<Input, Output = Result<T, E>>, F, T, E, Input> ParallelPipe<Input> for InspectErr<P, F>
+	where
+		F: for<'a> FnMut<(&'a E,), Output = ()> + Clone + Send + 'static,
+	{
+		type Output = P::Output;
+		type Task = InspectErrTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			let task = self.pipe.task();
+			let f = self.f.clone();
+			InspectErrTask { task, f }
+		}
+	}
+}
+
+#[pin_project]
+#[derive(Serialize, Deserialize)]
+pub struct InspectErrTask<T, F> {
+	#[pin]
+	task: T,
+	f: F,
+}
+
+impl<C: StreamTask<Item = Result<T, E>>, F, T, E> StreamTask for InspectErrTask<C, F>
+where
+	F: for<'a> FnMut<(&'a E,), Output = ()> + Clone,
+{
+	type Item = C::Item;
+	type Async = InspectErrTask<C::Async, F>;
+
+	fn into_async(self) -> Self::Async {
+		InspectErrTask {
+			task: self.task.into_async(),
+			f: self.f,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input, Output = Result<T, E>>, F, T, E, Input> PipeTask<Input>
+	for InspectErrTask<C, F>
+where
+	F: for<'a> FnMut<(&'a E,), Output = ()> + Clone,
+{
+	type Output = C::Output;
+	type Async = InspectErrTask<C::Async, F>;
+
+	fn into_async(self) -> Self::Async {
+		InspectErrTask {
+			task: self.task.into_async(),
+			f: self.f,
+		}
+	}
+}
+
+impl<C: Stream<Item = Result<T, E>>, F, T, E> Stream for InspectErrTask<C, F>
+where
+	F: for<'a> FnMut<(&'a E,), Output = ()> + Clone,
+{
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		let (task, f) = (self_.task, &mut self_.f);
+		task.poll_next(cx).map(|item| {
+			if let Some(Err(err)) = &item {
+				f.call_mut((err,));
+			}
+			item
+		})
+	}
+}
+
+impl<C: Pipe<Input, Output = Result<T, E>>, F, T, E, Input> Pipe<Input> for InspectErrTask<C, F>
+where
+	F: for<'a> FnMut<(&'a E,), Output = ()> + Clone,
+{
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		let (task, f) = (self_.task, &mut self_.f);
+		task.poll_next(cx, stream).map(|item| {
+			if let Some(Err(err)) = &item {
+				f.call_mut((err,));
+			}
+			item
+		})
+	}
+}
@@ -0,0 +1,79 @@
+use derive_new::new;
+use pin_project::pin_project;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ChainTask, ParallelStream};
+use crate::plan::Plan;
+
+/// Alternates between taking a task from `a` and from `b`. If the preferred stream
+/// has no task ready, a task is taken from the other instead, and once either is
+/// exhausted the remainder of the other follows.
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Interleave<A, B> {
+	#[pin]
+	a: A,
+	#[pin]
+	b: B,
+	#[new(default)]
+	next_b: bool,
+	#[new(default)]
+	a_done: bool,
+	#[new(default)]
+	b_done: bool,
+}
+
+impl_par_dist! {
+	impl<A: ParallelStream, B: ParallelStream<Item = A::Item>> ParallelStream for Interleave<A, B> {
+		type Item = A::Item;
+		type Task = ChainTask<A::Task, B::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			let (a_lower, a_upper) = self.a.size_hint();
+			let (b_lower, b_upper) = self.b.size_hint();
+			(
+				a_lower.saturating_add(b_lower),
+				a_upper.and_then(|a| a.checked_add(b_upper?)),
+			)
+		}
+		fn explain(&self) -> Plan {
+			self.a.explain().merge::<Self>(self.b.explain())
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let mut self_ = self.project();
+			let mut pending = false;
+			for _ in 0..2 {
+				if *self_.next_b {
+					if !*self_.b_done {
+						match self_.b.as_mut().next_task(cx) {
+							Poll::Ready(Some(b)) => {
+								*self_.next_b = false;
+								return Poll::Ready(Some(ChainTask::B(b)));
+							}
+							Poll::Ready(None) => *self_.b_done = true,
+							Poll::Pending => pending = true,
+						}
+					}
+				} else if !*self_.a_done {
+					match self_.a.as_mut().next_task(cx) {
+						Poll::Ready(Some(a)) => {
+							*self_.next_b = true;
+							return Poll::Ready(Some(ChainTask::A(a)));
+						}
+						Poll::Ready(None) => *self_.a_done = true,
+						Poll::Pending => pending = true,
+					}
+				}
+				*self_.next_b = !*self_.next_b;
+			}
+			if pending {
+				Poll::Pending
+			} else {
+				Poll::Ready(None)
+			}
+		}
+	}
+}
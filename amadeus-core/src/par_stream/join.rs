@@ -8,6 +8,7 @@ use std::{
 };
 
 use super::{FilterMapSync, MapSync, ParallelPipe, ParallelStream};
+use crate::plan::Plan;
 
 #[pin_project]
 #[must_use]
@@ -38,6 +39,11 @@ impl_par_dist! {
 		fn size_hint(&self) -> (usize, Option<usize>) {
 			self.right.size_hint()
 		}
+		fn explain(&self) -> Plan {
+			let mut plan = self.right.explain();
+			let _ = plan.stages.pop();
+			plan.then::<Self>()
+		}
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			self.project().right.next_task(cx)
 		}
@@ -95,6 +101,11 @@ impl_par_dist! {
 		fn size_hint(&self) -> (usize, Option<usize>) {
 			self.right.size_hint()
 		}
+		fn explain(&self) -> Plan {
+			let mut plan = self.right.explain();
+			let _ = plan.stages.pop();
+			plan.then::<Self>()
+		}
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			self.project().right.next_task(cx)
 		}
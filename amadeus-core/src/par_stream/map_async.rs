@@ -0,0 +1,154 @@
+use derive_new::new;
+use futures::{stream::FuturesOrdered, Future, Stream, StreamExt};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct MapAsync<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+	concurrency: usize,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, Fut> ParallelStream for MapAsync<P, F>
+	where
+		F: FnMut<(P::Item,), Output = Fut> + Clone + Send + 'static,
+		Fut: Future,
+	{
+		type Item = Fut::Output;
+		type Task = MapAsyncTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (f, concurrency) = (self_.f, *self_.concurrency);
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| MapAsyncTask::new(task, f.clone(), concurrency)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, Fut, Input> ParallelPipe<Input> for MapAsync<P, F>
+	where
+		F: FnMut<(P::Output,), Output = Fut> + Clone + Send + 'static,
+		Fut: Future,
+	{
+		type Output = Fut::Output;
+		type Task = MapAsyncTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			MapAsyncTask::new(self.pipe.task(), self.f.clone(), self.concurrency)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct MapAsyncTask<C, F> {
+	task: C,
+	f: F,
+	concurrency: usize,
+}
+impl<C: StreamTask, F: FnMut<(C::Item,), Output = Fut> + Clone, Fut: Future> StreamTask
+	for MapAsyncTask<C, F>
+{
+	type Item = Fut::Output;
+	type Async = MapAsyncAsync<C::Async, F, Fut>;
+
+	fn into_async(self) -> Self::Async {
+		MapAsyncAsync::new(self.task.into_async(), self.f, self.concurrency)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F: FnMut<(C::Output,), Output = Fut> + Clone, Fut: Future, Input>
+	PipeTask<Input> for MapAsyncTask<C, F>
+{
+	type Output = Fut::Output;
+	type Async = MapAsyncAsync<C::Async, F, Fut>;
+
+	fn into_async(self) -> Self::Async {
+		MapAsyncAsync::new(self.task.into_async(), self.f, self.concurrency)
+	}
+}
+
+/// Runs the futures of up to `concurrency` items at once, yielding their
+/// outputs in input order.
+#[pin_project]
+#[derive(new)]
+pub struct MapAsyncAsync<T, F, Fut: Future> {
+	#[pin]
+	task: T,
+	f: F,
+	concurrency: usize,
+	#[new(default)]
+	pending: FuturesOrdered<Fut>,
+	#[new(default)]
+	done: bool,
+}
+
+impl<C: Stream, F, Fut> Stream for MapAsyncAsync<C, F, Fut>
+where
+	F: FnMut<(C::Item,), Output = Fut>,
+	Fut: Future,
+{
+	type Item = Fut::Output;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		while !*self_.done && self_.pending.len() < *self_.concurrency {
+			match self_.task.as_mut().poll_next(cx) {
+				Poll::Ready(Some(item)) => self_.pending.push_back(self_.f.call_mut((item,))),
+				Poll::Ready(None) => *self_.done = true,
+				Poll::Pending => break,
+			}
+		}
+		match self_.pending.poll_next_unpin(cx) {
+			// None if there are no futures pending, while upstream is
+			Poll::Ready(None) if !*self_.done => Poll::Pending,
+			res => res,
+		}
+	}
+}
+
+impl<C: Pipe<Input>, F, Fut, Input> Pipe<Input> for MapAsyncAsync<C, F, Fut>
+where
+	F: FnMut<(C::Output,), Output = Fut>,
+	Fut: Future,
+{
+	type Output = Fut::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		while !*self_.done && self_.pending.len() < *self_.concurrency {
+			match self_.task.as_mut().poll_next(cx, stream.as_mut()) {
+				Poll::Ready(Some(item)) => self_.pending.push_back(self_.f.call_mut((item,))),
+				Poll::Ready(None) => *self_.done = true,
+				Poll::Pending => break,
+			}
+		}
+		match self_.pending.poll_next_unpin(cx) {
+			Poll::Ready(None) if !*self_.done => Poll::Pending,
+			res => res,
+		}
+	}
+}
@@ -0,0 +1,131 @@
+use derive_new::new;
+use futures::Stream;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct MapRef<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, R> ParallelStream for MapRef<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Item,), Output = R> + Clone + Send + 'static,
+	{
+		type Item = (R, P::Item);
+		type Task = MapRefTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let f = self_.f;
+			self_.pipe.next_task(cx).map(|task| {
+				task.map(|task| {
+					let f = f.clone();
+					MapRefTask { task, f }
+				})
+			})
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, R, Input> ParallelPipe<Input> for MapRef<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = R> + Clone + Send + 'static,
+	{
+		type Output = (R, P::Output);
+		type Task = MapRefTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			let task = self.pipe.task();
+			let f = self.f.clone();
+			MapRefTask { task, f }
+		}
+	}
+}
+
+#[pin_project]
+#[derive(Serialize, Deserialize)]
+pub struct MapRefTask<T, F> {
+	#[pin]
+	task: T,
+	f: F,
+}
+
+impl<C: StreamTask, F, R> StreamTask for MapRefTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = R> + Clone,
+{
+	type Item = (R, C::Item);
+	type Async = MapRefTask<C::Async, F>;
+
+	fn into_async(self) -> Self::Async {
+		MapRefTask {
+			task: self.task.into_async(),
+			f: self.f,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F, R, Input> PipeTask<Input> for MapRefTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = R> + Clone,
+{
+	type Output = (R, C::Output);
+	type Async = MapRefTask<C::Async, F>;
+
+	fn into_async(self) -> Self::Async {
+		MapRefTask {
+			task: self.task.into_async(),
+			f: self.f,
+		}
+	}
+}
+
+impl<C: Stream, F, R> Stream for MapRefTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = R> + Clone,
+{
+	type Item = (R, C::Item);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		let (task, f) = (self_.task, &mut self_.f);
+		task.poll_next(cx)
+			.map(|item| item.map(|item| (f.call_mut((&item,)), item)))
+	}
+}
+
+impl<C: Pipe<Input>, F, R, Input> Pipe<Input> for MapRefTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = R> + Clone,
+{
+	type Output = (R, C::Output);
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		let (task, f) = (self_.task, &mut self_.f);
+		task.poll_next(cx, stream)
+			.map(|item| item.map(|item| (f.call_mut((&item,)), item)))
+	}
+}
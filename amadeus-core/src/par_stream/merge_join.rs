@@ -0,0 +1,140 @@
+#![allow(clippy::type_complexity)]
+
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::FnMutNamed;
+use std::{
+	pin::Pin, task::{Context, Poll}, vec
+};
+
+use super::{FlatMapSync, KeySegment, ParallelStream, SplitAtKeyBoundaries, StreamTask};
+use crate::plan::{Plan, Stage};
+
+/// Which unmatched rows a [`merge_join`](super::ParallelStream::merge_join)
+/// emits, in addition to the matched ones.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum JoinType {
+	/// Only matched rows.
+	Inner,
+	/// Also rows of the left side without a match.
+	Left,
+	/// Also rows of the right side without a match.
+	Right,
+	/// Also rows of either side without a match.
+	Full,
+}
+
+#[pin_project]
+#[must_use]
+pub struct MergeJoin<A, B, TA: StreamTask, TB: StreamTask, K, V1, V2> {
+	#[pin]
+	join: FlatMapSync<SplitAtKeyBoundaries<A, B, TA, TB>, MergeJoinClosure<K, V1, V2>>,
+}
+
+impl<A, B, TA: StreamTask, TB: StreamTask, K, V1, V2> MergeJoin<A, B, TA, TB, K, V1, V2> {
+	pub fn new(a: A, b: B, segment_len: usize, join_type: JoinType) -> Self {
+		Self {
+			join: FlatMapSync::new(
+				SplitAtKeyBoundaries::new(a, b, segment_len),
+				MergeJoinClosure::new(join_type),
+			),
+		}
+	}
+}
+
+impl<A, B, K, V1, V2> ParallelStream for MergeJoin<A, B, A::Task, B::Task, K, V1, V2>
+where
+	A: ParallelStream<Item = (K, V1)>,
+	B: ParallelStream<Item = (K, V2)>,
+	K: Ord + Clone + Send + 'static,
+	V1: Clone + Send + 'static,
+	V2: Clone + Send + 'static,
+{
+	type Item = (K, Option<V1>, Option<V2>);
+	type Task = <FlatMapSync<
+		SplitAtKeyBoundaries<A, B, A::Task, B::Task>,
+		MergeJoinClosure<K, V1, V2>,
+	> as ParallelStream>::Task;
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.join.size_hint()
+	}
+	fn explain(&self) -> Plan {
+		// The combinators this is built from are an implementation detail
+		let mut plan = self.join.explain();
+		let _ = plan.stages.pop();
+		plan.stages.last_mut().unwrap().name = Stage::of::<Self>().name;
+		plan
+	}
+	fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+		self.project().join.next_task(cx)
+	}
+}
+
+FnMutNamed! {
+	pub type MergeJoinClosure<K, V1, V2> = |self, join_type: JoinType|segment=> KeySegment<K, V1, V2>| -> vec::IntoIter<(K, Option<V1>, Option<V2>)> where ; where K: Ord, K: Clone, V1: Clone, V2: Clone {
+		merge_join(segment, self.join_type).into_iter()
+	}
+}
+
+/// Merge join the two sides of a segment, each of which is sorted by key.
+fn merge_join<K, V1, V2>(
+	segment: KeySegment<K, V1, V2>, join_type: JoinType,
+) -> Vec<(K, Option<V1>, Option<V2>)>
+where
+	K: Ord + Clone,
+	V1: Clone,
+	V2: Clone,
+{
+	let (left_outer, right_outer) = match join_type {
+		JoinType::Inner => (false, false),
+		JoinType::Left => (true, false),
+		JoinType::Right => (false, true),
+		JoinType::Full => (true, true),
+	};
+	let mut res = Vec::new();
+	let mut left = segment.left.into_iter().peekable();
+	let mut right = segment.right.into_iter().peekable();
+	loop {
+		let key = match (left.peek(), right.peek()) {
+			(Some((a, _)), Some((b, _))) => a.min(b).clone(),
+			(Some((key, _)), None) | (None, Some((key, _))) => key.clone(),
+			(None, None) => break,
+		};
+		let mut left_matches = Vec::new();
+		while matches!(left.peek(), Some((k, _)) if *k == key) {
+			left_matches.push(left.next().unwrap().1);
+		}
+		let mut right_matches = Vec::new();
+		while matches!(right.peek(), Some((k, _)) if *k == key) {
+			right_matches.push(right.next().unwrap().1);
+		}
+		match (left_matches.is_empty(), right_matches.is_empty()) {
+			(false, false) => {
+				for v1 in left_matches {
+					res.extend(
+						right_matches
+							.iter()
+							.map(|v2| (key.clone(), Some(v1.clone()), Some(v2.clone()))),
+					);
+				}
+			}
+			(false, true) if left_outer => {
+				res.extend(
+					left_matches
+						.into_iter()
+						.map(|v1| (key.clone(), Some(v1), None)),
+				);
+			}
+			(true, false) if right_outer => {
+				res.extend(
+					right_matches
+						.into_iter()
+						.map(|v2| (key.clone(), None, Some(v2))),
+				);
+			}
+			_ => (),
+		}
+	}
+	res
+}
@@ -0,0 +1,211 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::{pin_project, pinned_drop};
+use std::{
+	convert::TryFrom, pin::Pin, sync::{
+		atomic::{AtomicU64, Ordering}, Arc
+	}, task::{Context, Poll}, time::Duration
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::{plan::Plan, util::Instant};
+
+/// The number of items a task counts before adding them to the shared counters.
+const BLOCK: u64 = 1024;
+
+/// A handle to the counters of a stage observed with
+/// [`observe_throughput`](super::ParallelStream::observe_throughput), from which
+/// metrics can be read once the job completes.
+#[derive(Clone, Debug)]
+pub struct Throughput(Arc<Counters>);
+
+#[derive(Debug)]
+struct Counters {
+	created: Instant,
+	items: AtomicU64,
+	bytes: AtomicU64,
+	/// Nanoseconds after `created` of the first poll, or `u64::MAX` if not yet polled.
+	start: AtomicU64,
+	/// Nanoseconds after `created` of the last item or end of a partition.
+	end: AtomicU64,
+}
+
+impl Throughput {
+	pub fn new() -> Self {
+		Self(Arc::new(Counters {
+			created: Instant::now(),
+			items: AtomicU64::new(0),
+			bytes: AtomicU64::new(0),
+			start: AtomicU64::new(u64::MAX),
+			end: AtomicU64::new(0),
+		}))
+	}
+	/// The items and bytes observed so far, and the time between the stage first
+	/// being polled and the last of them.
+	pub fn snapshot(&self) -> ThroughputSnapshot {
+		let (start, end) = (
+			self.0.start.load(Ordering::Relaxed),
+			self.0.end.load(Ordering::Relaxed),
+		);
+		ThroughputSnapshot {
+			items: self.0.items.load(Ordering::Relaxed),
+			bytes: self.0.bytes.load(Ordering::Relaxed),
+			elapsed: Duration::from_nanos(end.saturating_sub(start)),
+		}
+	}
+	fn now(&self) -> u64 {
+		u64::try_from((Instant::now() - self.0.created).as_nanos()).unwrap_or(u64::MAX - 1)
+	}
+}
+impl Default for Throughput {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ThroughputSnapshot {
+	pub items: u64,
+	pub bytes: u64,
+	pub elapsed: Duration,
+}
+impl ThroughputSnapshot {
+	pub fn items_per_sec(&self) -> f64 {
+		rate(self.items, self.elapsed)
+	}
+	pub fn bytes_per_sec(&self) -> f64 {
+		rate(self.bytes, self.elapsed)
+	}
+}
+#[allow(clippy::cast_precision_loss)]
+fn rate(count: u64, elapsed: Duration) -> f64 {
+	if elapsed == Duration::from_secs(0) {
+		0.0
+	} else {
+		count as f64 / elapsed.as_secs_f64()
+	}
+}
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct ObserveThroughput<P, F> {
+	#[pin]
+	pipe: P,
+	throughput: Throughput,
+	bytes: F,
+}
+
+impl<P: ParallelStream, F> ParallelStream for ObserveThroughput<P, F>
+where
+	F: FnMut(&P::Item) -> usize + Clone + Send + 'static,
+{
+	type Item = P::Item;
+	type Task = ObserveThroughputTask<P::Task, F>;
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.pipe.size_hint()
+	}
+	fn explain(&self) -> Plan {
+		self.pipe.explain().then::<Self>()
+	}
+	fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+		let self_ = self.project();
+		let task = ready!(self_.pipe.next_task(cx));
+		let (throughput, bytes) = (self_.throughput, self_.bytes);
+		Poll::Ready(
+			task.map(|task| ObserveThroughputTask::new(task, throughput.clone(), bytes.clone())),
+		)
+	}
+}
+
+#[derive(new)]
+pub struct ObserveThroughputTask<T, F> {
+	task: T,
+	throughput: Throughput,
+	bytes: F,
+}
+
+impl<C: StreamTask, F> StreamTask for ObserveThroughputTask<C, F>
+where
+	F: FnMut(&C::Item) -> usize,
+{
+	type Item = C::Item;
+	type Async = ObserveThroughputAsync<C::Async, F>;
+
+	fn into_async(self) -> Self::Async {
+		ObserveThroughputAsync {
+			task: self.task.into_async(),
+			throughput: self.throughput,
+			bytes: self.bytes,
+			started: false,
+			pending_items: 0,
+			pending_bytes: 0,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+
+/// Counts items locally, adding them to the shared counters every [`BLOCK`]
+/// items and at the end of the partition, so the counters aren't contended.
+#[pin_project(PinnedDrop)]
+pub struct ObserveThroughputAsync<T, F> {
+	#[pin]
+	task: T,
+	throughput: Throughput,
+	bytes: F,
+	started: bool,
+	pending_items: u64,
+	pending_bytes: u64,
+}
+
+impl<T, F> ObserveThroughputAsync<T, F> {
+	fn flush(throughput: &Throughput, pending_items: &mut u64, pending_bytes: &mut u64) {
+		let counters = &throughput.0;
+		let _ = counters.items.fetch_add(*pending_items, Ordering::Relaxed);
+		let _ = counters.bytes.fetch_add(*pending_bytes, Ordering::Relaxed);
+		let _ = counters.end.fetch_max(throughput.now(), Ordering::Relaxed);
+		*pending_items = 0;
+		*pending_bytes = 0;
+	}
+}
+
+impl<C: Stream, F> Stream for ObserveThroughputAsync<C, F>
+where
+	F: FnMut(&C::Item) -> usize,
+{
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		if !*self_.started {
+			*self_.started = true;
+			let now = self_.throughput.now();
+			let _ = self_.throughput.0.start.fetch_min(now, Ordering::Relaxed);
+		}
+		let item = ready!(self_.task.poll_next(cx));
+		match &item {
+			Some(item) => {
+				*self_.pending_items += 1;
+				*self_.pending_bytes += (self_.bytes)(item) as u64;
+				if *self_.pending_items == BLOCK {
+					Self::flush(self_.throughput, self_.pending_items, self_.pending_bytes);
+				}
+			}
+			None => Self::flush(self_.throughput, self_.pending_items, self_.pending_bytes),
+		}
+		Poll::Ready(item)
+	}
+}
+
+#[pinned_drop]
+impl<T, F> PinnedDrop for ObserveThroughputAsync<T, F> {
+	fn drop(self: Pin<&mut Self>) {
+		let self_ = self.project();
+		if *self_.pending_items != 0 {
+			Self::flush(self_.throughput, self_.pending_items, self_.pending_bytes);
+		}
+	}
+}
@@ -0,0 +1,104 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	mem, pin::Pin, task::{Context, Poll}, vec
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Ordered<P, T> {
+	#[pin]
+	pipe: P,
+	#[new(default)]
+	tasks: Vec<T>,
+	#[new(default)]
+	done: bool,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for Ordered<P, P::Task> {
+		type Item = P::Item;
+		type Task = OrderedTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			if self.done {
+				return (0, Some(0));
+			}
+			let (lower, upper) = self.pipe.size_hint();
+			(lower.min(1), Some(upper.map_or(1, |upper| upper.min(1))))
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let mut self_ = self.project();
+			if *self_.done {
+				return Poll::Ready(None);
+			}
+			while let Some(task) = ready!(self_.pipe.as_mut().next_task(cx)) {
+				self_.tasks.push(task);
+			}
+			*self_.done = true;
+			let tasks = mem::take(self_.tasks);
+			Poll::Ready(if !tasks.is_empty() {
+				Some(OrderedTask::new(tasks))
+			} else {
+				None
+			})
+		}
+	}
+}
+
+/// All of the upstream tasks, in the order they were yielded, run one after
+/// another as a single partition.
+#[derive(Serialize, Deserialize, new)]
+pub struct OrderedTask<T> {
+	tasks: Vec<T>,
+}
+
+impl<T: StreamTask> StreamTask for OrderedTask<T> {
+	type Item = T::Item;
+	type Async = OrderedTaskAsync<T, T::Async>;
+
+	fn into_async(self) -> Self::Async {
+		OrderedTaskAsync {
+			tasks: self.tasks.into_iter(),
+			current: None,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.tasks.iter().map(StreamTask::cost).sum()
+	}
+}
+
+#[pin_project]
+pub struct OrderedTaskAsync<T, A> {
+	tasks: vec::IntoIter<T>,
+	current: Option<Pin<Box<A>>>,
+}
+
+impl<T: StreamTask<Async = A>, A: Stream<Item = T::Item>> Stream for OrderedTaskAsync<T, A> {
+	type Item = A::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		loop {
+			if let Some(current) = self_.current {
+				if let Some(item) = ready!(current.as_mut().poll_next(cx)) {
+					break Poll::Ready(Some(item));
+				}
+				*self_.current = None;
+			}
+			match self_.tasks.next() {
+				Some(task) => *self_.current = Some(Box::pin(task.into_async())),
+				None => break Poll::Ready(None),
+			}
+		}
+	}
+}
@@ -0,0 +1,206 @@
+use futures::{future::LocalBoxFuture, ready, Stream};
+use once_cell::sync::Lazy;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap, pin::Pin, sync::{Arc, Mutex}, task::{Context, Poll}, time::Duration
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{
+	pipe::Pipe, plan::Plan, util::{delay, Instant}
+};
+
+/// The limiters of the rate limited streams and pipes running in this process,
+/// by ID, so that tasks can find the one they share. They're kept until unused
+/// and idle, as only then is dropping one equivalent to starting afresh.
+static LIMITERS: Lazy<Mutex<BTreeMap<u64, Arc<Limiter>>>> =
+	Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// The longest interval between items, so the slots handed out stay far from
+/// overflowing an `Instant`.
+const MAX_INTERVAL: Duration = Duration::from_secs(u32::MAX as u64);
+
+#[pin_project]
+#[must_use]
+pub struct RateLimit<P> {
+	#[pin]
+	pipe: P,
+	id: u64,
+	interval: Duration,
+}
+impl<P> RateLimit<P> {
+	/// # Panics
+	///
+	/// If `per_second` isn't positive, or is less than one per `MAX_INTERVAL`.
+	pub fn new(pipe: P, per_second: f64) -> Self {
+		let secs = 1.0 / per_second;
+		assert!(
+			secs >= 0.0 && secs <= MAX_INTERVAL.as_secs_f64(),
+			"per_second must be positive and at least one per {} seconds, not {}",
+			MAX_INTERVAL.as_secs(),
+			per_second
+		);
+		let interval = Duration::from_secs_f64(secs);
+		Self {
+			pipe,
+			id: rand::random(),
+			interval,
+		}
+	}
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for RateLimit<P> {
+		type Item = P::Item;
+		type Task = RateLimitTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (id, interval) = (*self_.id, *self_.interval);
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| RateLimitTask::new(task, id, interval)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for RateLimit<P> {
+		type Output = P::Output;
+		type Task = RateLimitTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			RateLimitTask::new(self.pipe.task(), self.id, self.interval)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RateLimitTask<T> {
+	task: T,
+	id: u64,
+	interval: Duration,
+}
+impl<T> RateLimitTask<T> {
+	fn new(task: T, id: u64, interval: Duration) -> Self {
+		Self { task, id, interval }
+	}
+	fn limiter(&self) -> Arc<Limiter> {
+		let mut limiters = LIMITERS.lock().unwrap();
+		if let Some(limiter) = limiters.get(&self.id) {
+			return limiter.clone();
+		}
+		limiters.retain(|_, limiter| Arc::strong_count(limiter) > 1 || !limiter.is_idle());
+		let limiter = Arc::new(Limiter {
+			interval: self.interval,
+			next: Mutex::new(None),
+		});
+		let _ = limiters.insert(self.id, limiter.clone());
+		limiter
+	}
+}
+
+impl<C: StreamTask> StreamTask for RateLimitTask<C> {
+	type Item = C::Item;
+	type Async = RateLimitAsync<C::Async, C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		RateLimitAsync::new(self.limiter(), self.task.into_async())
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for RateLimitTask<C> {
+	type Output = C::Output;
+	type Async = RateLimitAsync<C::Async, C::Output>;
+
+	fn into_async(self) -> Self::Async {
+		RateLimitAsync::new(self.limiter(), self.task.into_async())
+	}
+}
+
+/// Hands out evenly spaced slots in which an item may be emitted, so that
+/// items are emitted no more often than once per `interval` in total.
+struct Limiter {
+	interval: Duration,
+	next: Mutex<Option<Instant>>,
+}
+impl Limiter {
+	/// Whether the next free slot has already arrived.
+	fn is_idle(&self) -> bool {
+		!matches!(*self.next.lock().unwrap(), Some(next) if next > Instant::now())
+	}
+	/// Wait for the next free slot, or `None` if it has already arrived.
+	fn wait(&self) -> Option<LocalBoxFuture<'static, ()>> {
+		let now = Instant::now();
+		let mut next = self.next.lock().unwrap();
+		let slot = next.map_or(now, |next| next.max(now));
+		*next = Some(slot + self.interval);
+		if slot > now {
+			return Some(delay(slot - now));
+		}
+		None
+	}
+}
+
+/// Takes a slot from the limiter for each item, holding the item back until it
+/// arrives.
+#[pin_project]
+pub struct RateLimitAsync<T, Item> {
+	#[pin]
+	task: T,
+	limiter: Arc<Limiter>,
+	pending: Option<(Item, LocalBoxFuture<'static, ()>)>,
+}
+impl<T, Item> RateLimitAsync<T, Item> {
+	fn new(limiter: Arc<Limiter>, task: T) -> Self {
+		Self {
+			task,
+			limiter,
+			pending: None,
+		}
+	}
+	fn poll_next_with(
+		self: Pin<&mut Self>, cx: &mut Context,
+		poll_task: impl FnOnce(Pin<&mut T>, &mut Context) -> Poll<Option<Item>>,
+	) -> Poll<Option<Item>> {
+		let self_ = self.project();
+		if self_.pending.is_none() {
+			match ready!(poll_task(self_.task, cx)) {
+				Some(item) => match self_.limiter.wait() {
+					Some(delay) => *self_.pending = Some((item, delay)),
+					None => return Poll::Ready(Some(item)),
+				},
+				None => return Poll::Ready(None),
+			}
+		}
+		let (_, delay) = self_.pending.as_mut().unwrap();
+		ready!(delay.as_mut().poll(cx));
+		Poll::Ready(self_.pending.take().map(|(item, _)| item))
+	}
+}
+
+impl<C: Stream> Stream for RateLimitAsync<C, C::Item> {
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		self.poll_next_with(cx, Stream::poll_next)
+	}
+}
+
+impl<C: Pipe<Input>, Input> Pipe<Input> for RateLimitAsync<C, C::Output> {
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		self.poll_next_with(cx, |task, cx| task.poll_next(cx, stream))
+	}
+}
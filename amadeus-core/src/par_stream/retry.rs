@@ -0,0 +1,224 @@
+use derive_new::new;
+use futures::{future::LocalBoxFuture, ready, Future, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}, time::Duration
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan, util::delay};
+
+/// How long [`retry`](super::ParallelStream::retry) waits before retrying an
+/// item that failed.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Backoff {
+	/// Wait the same duration before every retry.
+	Constant(Duration),
+	/// Wait `initial` before the first retry, doubling before each subsequent
+	/// one up to `max`.
+	Exponential { initial: Duration, max: Duration },
+}
+impl Backoff {
+	/// The duration to wait before retry number `retry`, counting from zero.
+	pub fn delay(&self, retry: u32) -> Duration {
+		match *self {
+			Self::Constant(delay) => delay,
+			Self::Exponential { initial, max } => initial
+				.checked_mul(2_u32.saturating_pow(retry))
+				.map_or(max, |delay| delay.min(max)),
+		}
+	}
+}
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Retry<P, F> {
+	#[pin]
+	pipe: P,
+	f: F,
+	attempts: u32,
+	backoff: Backoff,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, Fut, T, E> ParallelStream for Retry<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Item,), Output = Fut> + Clone + Send + 'static,
+		Fut: Future<Output = Result<T, E>>,
+	{
+		type Item = Result<T, E>;
+		type Task = RetryTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (f, attempts, backoff) = (self_.f, *self_.attempts, *self_.backoff);
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| RetryTask::new(task, f.clone(), attempts, backoff)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, Fut, T, E, Input> ParallelPipe<Input> for Retry<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = Fut> + Clone + Send + 'static,
+		Fut: Future<Output = Result<T, E>>,
+	{
+		type Output = Result<T, E>;
+		type Task = RetryTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			RetryTask::new(self.pipe.task(), self.f.clone(), self.attempts, self.backoff)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct RetryTask<T, F> {
+	task: T,
+	f: F,
+	attempts: u32,
+	backoff: Backoff,
+}
+
+impl<C: StreamTask, F, Fut, T, E> StreamTask for RetryTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = Fut>,
+	Fut: Future<Output = Result<T, E>>,
+{
+	type Item = Result<T, E>;
+	type Async = RetryAsync<C::Async, F, C::Item, Fut>;
+
+	fn into_async(self) -> Self::Async {
+		RetryAsync::new(self.task.into_async(), self.f, self.attempts, self.backoff)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F, Fut, T, E, Input> PipeTask<Input> for RetryTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = Fut>,
+	Fut: Future<Output = Result<T, E>>,
+{
+	type Output = Result<T, E>;
+	type Async = RetryAsync<C::Async, F, C::Output, Fut>;
+
+	fn into_async(self) -> Self::Async {
+		RetryAsync::new(self.task.into_async(), self.f, self.attempts, self.backoff)
+	}
+}
+
+/// Runs `f` on each item in turn, calling it again after a backoff each time the
+/// future it returns fails, until it succeeds or runs out of attempts.
+#[pin_project]
+#[derive(new)]
+pub struct RetryAsync<T, F, Item, Fut> {
+	#[pin]
+	task: T,
+	f: F,
+	attempts: u32,
+	backoff: Backoff,
+	#[new(default)]
+	pending: Option<Pending<Item, Fut>>,
+}
+
+pub struct Pending<Item, Fut> {
+	item: Item,
+	retries: u32,
+	attempt: Attempt<Fut>,
+}
+enum Attempt<Fut> {
+	Running(Pin<Box<Fut>>),
+	Waiting(LocalBoxFuture<'static, ()>),
+}
+
+impl<T, F, Item, Fut, A, E> RetryAsync<T, F, Item, Fut>
+where
+	F: for<'a> FnMut<(&'a Item,), Output = Fut>,
+	Fut: Future<Output = Result<A, E>>,
+{
+	fn start(f: &mut F, item: Item) -> Pending<Item, Fut> {
+		let attempt = Attempt::Running(Box::pin(f.call_mut((&item,))));
+		Pending {
+			item,
+			retries: 0,
+			attempt,
+		}
+	}
+	fn poll_pending(
+		pending: &mut Option<Pending<Item, Fut>>, f: &mut F, attempts: u32, backoff: Backoff,
+		cx: &mut Context,
+	) -> Poll<Result<A, E>> {
+		let pending_ = pending.as_mut().unwrap();
+		loop {
+			match &mut pending_.attempt {
+				Attempt::Running(future) => match ready!(future.as_mut().poll(cx)) {
+					Err(_) if pending_.retries + 1 < attempts => {
+						let delay = delay(backoff.delay(pending_.retries));
+						pending_.attempt = Attempt::Waiting(delay);
+						pending_.retries += 1;
+					}
+					res => {
+						*pending = None;
+						break Poll::Ready(res);
+					}
+				},
+				Attempt::Waiting(delay) => {
+					ready!(delay.as_mut().poll(cx));
+					let future = f.call_mut((&pending_.item,));
+					pending_.attempt = Attempt::Running(Box::pin(future));
+				}
+			}
+		}
+	}
+}
+
+impl<C: Stream, F, Fut, T, E> Stream for RetryAsync<C, F, C::Item, Fut>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = Fut>,
+	Fut: Future<Output = Result<T, E>>,
+{
+	type Item = Result<T, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		if self_.pending.is_none() {
+			match ready!(self_.task.as_mut().poll_next(cx)) {
+				Some(item) => *self_.pending = Some(Self::start(self_.f, item)),
+				None => return Poll::Ready(None),
+			}
+		}
+		Self::poll_pending(self_.pending, self_.f, *self_.attempts, *self_.backoff, cx).map(Some)
+	}
+}
+
+impl<C: Pipe<Input>, F, Fut, T, E, Input> Pipe<Input> for RetryAsync<C, F, C::Output, Fut>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = Fut>,
+	Fut: Future<Output = Result<T, E>>,
+{
+	type Output = Result<T, E>;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		if self_.pending.is_none() {
+			match ready!(self_.task.as_mut().poll_next(cx, stream)) {
+				Some(item) => *self_.pending = Some(Self::start(self_.f, item)),
+				None => return Poll::Ready(None),
+			}
+		}
+		Self::poll_pending(self_.pending, self_.f, *self_.attempts, *self_.backoff, cx).map(Some)
+	}
+}
@@ -0,0 +1,134 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+	pin::Pin, sync::atomic::{AtomicU64, Ordering}, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct SampleFraction<P> {
+	#[pin]
+	pipe: P,
+	p: f64,
+	seed: u64,
+	#[new(default)]
+	index: AtomicU64,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for SampleFraction<P> {
+		type Item = P::Item;
+		type Task = SampleFractionTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let task = ready!(self_.pipe.next_task(cx));
+			// Each partition gets its own RNG, so that they don't select the same
+			// offsets as each other
+			let seed = self_
+				.seed
+				.wrapping_add(self_.index.fetch_add(1, Ordering::Relaxed));
+			let p = *self_.p;
+			Poll::Ready(task.map(|task| SampleFractionTask::new(task, p, seed)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for SampleFraction<P> {
+		type Output = P::Output;
+		type Task = SampleFractionTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			// As above, for each partition the pipe is run over
+			let seed = self
+				.seed
+				.wrapping_add(self.index.fetch_add(1, Ordering::Relaxed));
+			SampleFractionTask::new(self.pipe.task(), self.p, seed)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct SampleFractionTask<T> {
+	task: T,
+	p: f64,
+	seed: u64,
+}
+
+impl<C: StreamTask> StreamTask for SampleFractionTask<C> {
+	type Item = C::Item;
+	type Async = SampleFractionAsync<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		SampleFractionAsync {
+			task: self.task.into_async(),
+			p: self.p,
+			rng: StdRng::seed_from_u64(self.seed),
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for SampleFractionTask<C> {
+	type Output = C::Output;
+	type Async = SampleFractionAsync<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		SampleFractionAsync {
+			task: self.task.into_async(),
+			p: self.p,
+			rng: StdRng::seed_from_u64(self.seed),
+		}
+	}
+}
+
+#[pin_project]
+pub struct SampleFractionAsync<T> {
+	#[pin]
+	task: T,
+	p: f64,
+	rng: StdRng,
+}
+
+impl<C: Stream> Stream for SampleFractionAsync<C> {
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx)) {
+			if self_.rng.gen_bool(*self_.p) {
+				return Poll::Ready(Some(item));
+			}
+		}
+		Poll::Ready(None)
+	}
+}
+
+impl<C: Pipe<Input>, Input> Pipe<Input> for SampleFractionAsync<C> {
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+			if self_.rng.gen_bool(*self_.p) {
+				return Poll::Ready(Some(item));
+			}
+		}
+		Poll::Ready(None)
+	}
+}
@@ -0,0 +1,213 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	pin::Pin, task::{Context, Poll}, time::Duration
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+/// Which item of each interval
+/// [`sample_per_interval`](super::ParallelStream::sample_per_interval) keeps.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum SamplePolicy {
+	First,
+	/// The last, which is held back until an item of a later interval or the end
+	/// of the partition is reached.
+	Last,
+}
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct SamplePerInterval<P, F> {
+	#[pin]
+	pipe: P,
+	timestamp: F,
+	interval: Duration,
+	policy: SamplePolicy,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F> ParallelStream for SamplePerInterval<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Item,), Output = Duration> + Clone + Send + 'static,
+	{
+		type Item = P::Item;
+		type Task = SamplePerIntervalTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, self.pipe.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (timestamp, interval, policy) = (self_.timestamp, *self_.interval, *self_.policy);
+			self_.pipe.next_task(cx).map(|task| {
+				task.map(|task| SamplePerIntervalTask::new(task, timestamp.clone(), interval, policy))
+			})
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, Input> ParallelPipe<Input> for SamplePerInterval<P, F>
+	where
+		F: for<'a> FnMut<(&'a P::Output,), Output = Duration> + Clone + Send + 'static,
+	{
+		type Output = P::Output;
+		type Task = SamplePerIntervalTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			SamplePerIntervalTask::new(
+				self.pipe.task(),
+				self.timestamp.clone(),
+				self.interval,
+				self.policy,
+			)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct SamplePerIntervalTask<T, F> {
+	task: T,
+	timestamp: F,
+	interval: Duration,
+	policy: SamplePolicy,
+}
+
+impl<C: StreamTask, F> StreamTask for SamplePerIntervalTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = Duration>,
+{
+	type Item = C::Item;
+	type Async = SamplePerIntervalAsync<C::Async, F, C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		SamplePerIntervalAsync::new(
+			self.task.into_async(),
+			self.timestamp,
+			self.interval,
+			self.policy,
+		)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F, Input> PipeTask<Input> for SamplePerIntervalTask<C, F>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = Duration>,
+{
+	type Output = C::Output;
+	type Async = SamplePerIntervalAsync<C::Async, F, C::Output>;
+
+	fn into_async(self) -> Self::Async {
+		SamplePerIntervalAsync::new(
+			self.task.into_async(),
+			self.timestamp,
+			self.interval,
+			self.policy,
+		)
+	}
+}
+
+/// Yields one item of each run of consecutive items in the same interval.
+#[pin_project]
+#[derive(new)]
+pub struct SamplePerIntervalAsync<T, F, Item> {
+	#[pin]
+	task: T,
+	timestamp: F,
+	interval: Duration,
+	policy: SamplePolicy,
+	#[new(default)]
+	current: Option<u128>,
+	#[new(default)]
+	pending: Option<Item>,
+}
+
+impl<T, F, Item> SamplePerIntervalAsync<T, F, Item>
+where
+	F: for<'a> FnMut<(&'a Item,), Output = Duration>,
+{
+	/// Take `item`, returning the item to yield if it completes one.
+	fn push(
+		timestamp: &mut F, interval: Duration, policy: SamplePolicy, current: &mut Option<u128>,
+		pending: &mut Option<Item>, item: Item,
+	) -> Option<Item> {
+		let bucket = timestamp.call_mut((&item,)).as_nanos() / interval.as_nanos();
+		let new = *current != Some(bucket);
+		*current = Some(bucket);
+		match policy {
+			SamplePolicy::First => Some(item).filter(|_| new),
+			SamplePolicy::Last if new => pending.replace(item),
+			SamplePolicy::Last => {
+				*pending = Some(item);
+				None
+			}
+		}
+	}
+}
+
+impl<C: Stream, F> Stream for SamplePerIntervalAsync<C, F, C::Item>
+where
+	F: for<'a> FnMut<(&'a C::Item,), Output = Duration>,
+{
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		loop {
+			match ready!(self_.task.as_mut().poll_next(cx)) {
+				Some(item) => {
+					if let Some(item) = Self::push(
+						self_.timestamp,
+						*self_.interval,
+						*self_.policy,
+						self_.current,
+						self_.pending,
+						item,
+					) {
+						break Poll::Ready(Some(item));
+					}
+				}
+				None => break Poll::Ready(self_.pending.take()),
+			}
+		}
+	}
+}
+
+impl<C: Pipe<Input>, F, Input> Pipe<Input> for SamplePerIntervalAsync<C, F, C::Output>
+where
+	F: for<'a> FnMut<(&'a C::Output,), Output = Duration>,
+{
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		loop {
+			match ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+				Some(item) => {
+					if let Some(item) = Self::push(
+						self_.timestamp,
+						*self_.interval,
+						*self_.policy,
+						self_.current,
+						self_.pending,
+						item,
+					) {
+						break Poll::Ready(Some(item));
+					}
+				}
+				None => break Poll::Ready(self_.pending.take()),
+			}
+		}
+	}
+}
@@ -0,0 +1,89 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
+
+/// Tag each item with its position in the source: the index of the task it came
+/// from, in the order tasks were yielded, and its index within that task.
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct SourceIndexed<P> {
+	#[pin]
+	pipe: P,
+	#[new(default)]
+	next: usize,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for SourceIndexed<P> {
+		type Item = ((usize, usize), P::Item);
+		type Task = SourceIndexedTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let next = self_.next;
+			let task = ready!(self_.pipe.next_task(cx));
+			Poll::Ready(task.map(|task| {
+				*next += 1;
+				SourceIndexedTask::new(task, *next - 1)
+			}))
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct SourceIndexedTask<T> {
+	task: T,
+	index: usize,
+}
+
+impl<C: StreamTask> StreamTask for SourceIndexedTask<C> {
+	type Item = ((usize, usize), C::Item);
+	type Async = SourceIndexedAsync<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		SourceIndexedAsync {
+			task: self.task.into_async(),
+			index: self.index,
+			next: 0,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+
+#[pin_project]
+pub struct SourceIndexedAsync<T> {
+	#[pin]
+	task: T,
+	index: usize,
+	next: usize,
+}
+
+impl<C: Stream> Stream for SourceIndexedAsync<C> {
+	type Item = ((usize, usize), C::Item);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		let (index, next) = (*self_.index, self_.next);
+		let item = ready!(self_.task.poll_next(cx));
+		Poll::Ready(item.map(|item| {
+			*next += 1;
+			((index, *next - 1), item)
+		}))
+	}
+}
@@ -0,0 +1,298 @@
+use either::Either;
+use futures::{ready, Stream};
+use pin_project::{pin_project, pinned_drop};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::VecDeque, mem, pin::Pin, sync::{Arc, Mutex, PoisonError}, task::{Context, Poll, Waker}
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
+
+/// The items of both streams within a range of keys, each in key order.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct KeySegment<K, V1, V2> {
+	pub left: Vec<(K, V1)>,
+	pub right: Vec<(K, V2)>,
+}
+
+/// Takes the tasks of `a` and `b`, then hands out tasks that take turns to read
+/// them in order, merging them by key into [`KeySegment`]s.
+///
+/// Only the segment being cut, and the partition of each input being read from,
+/// are held in memory at once besides the segments being processed, so the
+/// inputs needn't fit in memory, and segments are processed while the inputs
+/// are still being read. The tasks share the inputs in memory, so this is not
+/// available for distributed streams.
+#[pin_project]
+#[must_use]
+pub struct SplitAtKeyBoundaries<A, B, TA: StreamTask, TB: StreamTask> {
+	#[pin]
+	a: A,
+	#[pin]
+	b: B,
+	segment_len: usize,
+	a_tasks: Vec<TA>,
+	b_tasks: Vec<TB>,
+	a_done: bool,
+	b_done: bool,
+	shared: Option<Arc<Mutex<Shared<TA, TB>>>>,
+	tasks: usize,
+}
+impl<A, B, TA: StreamTask, TB: StreamTask> SplitAtKeyBoundaries<A, B, TA, TB> {
+	pub fn new(a: A, b: B, segment_len: usize) -> Self {
+		Self {
+			a,
+			b,
+			segment_len,
+			a_tasks: Vec::new(),
+			b_tasks: Vec::new(),
+			a_done: false,
+			b_done: false,
+			shared: None,
+			tasks: 0,
+		}
+	}
+}
+
+impl<A, B, K, V1, V2> ParallelStream for SplitAtKeyBoundaries<A, B, A::Task, B::Task>
+where
+	A: ParallelStream<Item = (K, V1)>,
+	B: ParallelStream<Item = (K, V2)>,
+	K: Ord + Send + 'static,
+	V1: Send + 'static,
+	V2: Send + 'static,
+{
+	type Item = KeySegment<K, V1, V2>;
+	type Task = KeySegmentTask<A::Task, B::Task>;
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, None)
+	}
+	fn explain(&self) -> Plan {
+		self.a.explain().merge::<Self>(self.b.explain())
+	}
+	fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+		let mut self_ = self.project();
+		while !*self_.a_done {
+			match ready!(self_.a.as_mut().next_task(cx)) {
+				Some(task) => self_.a_tasks.push(task),
+				None => *self_.a_done = true,
+			}
+		}
+		while !*self_.b_done {
+			match ready!(self_.b.as_mut().next_task(cx)) {
+				Some(task) => self_.b_tasks.push(task),
+				None => *self_.b_done = true,
+			}
+		}
+		if self_.shared.is_none() {
+			// As many tasks as partitions of the larger input, each of which cuts
+			// segments until the inputs are exhausted
+			*self_.tasks = self_.a_tasks.len().max(self_.b_tasks.len());
+			*self_.shared = Some(Arc::new(Mutex::new(Shared {
+				segment_len: *self_.segment_len,
+				a_tasks: mem::take(self_.a_tasks).into(),
+				b_tasks: mem::take(self_.b_tasks).into(),
+				a_items: VecDeque::new(),
+				b_items: VecDeque::new(),
+				left: Vec::new(),
+				right: Vec::new(),
+				reading: false,
+				waiting: Vec::new(),
+			})));
+		}
+		Poll::Ready(if *self_.tasks != 0 {
+			*self_.tasks -= 1;
+			Some(KeySegmentTask {
+				shared: self_.shared.clone().unwrap(),
+			})
+		} else {
+			None
+		})
+	}
+}
+
+/// The inputs, and the segment being cut from them.
+struct Shared<TA: StreamTask, TB: StreamTask> {
+	segment_len: usize,
+	a_tasks: VecDeque<TA>,
+	b_tasks: VecDeque<TB>,
+	a_items: VecDeque<TA::Item>,
+	b_items: VecDeque<TB::Item>,
+	left: Vec<TA::Item>,
+	right: Vec<TB::Item>,
+	/// Whether a task is reading a partition of an input, which the others wait
+	/// for before cutting.
+	reading: bool,
+	waiting: Vec<Waker>,
+}
+
+enum Cut<TA, TB, S> {
+	Read(Either<TA, TB>),
+	Segment(S),
+	Done,
+}
+
+impl<TA, TB, K, V1, V2> Shared<TA, TB>
+where
+	TA: StreamTask<Item = (K, V1)>,
+	TB: StreamTask<Item = (K, V2)>,
+	K: Ord,
+{
+	/// Continue the segment, returning it once it has at least `segment_len`
+	/// items and the next key differs from its last, or a partition to read once
+	/// an input's items run out.
+	fn cut(&mut self) -> Cut<TA, TB, KeySegment<K, V1, V2>> {
+		loop {
+			if self.a_items.is_empty() {
+				if let Some(task) = self.a_tasks.pop_front() {
+					return Cut::Read(Either::Left(task));
+				}
+			}
+			if self.b_items.is_empty() {
+				if let Some(task) = self.b_tasks.pop_front() {
+					return Cut::Read(Either::Right(task));
+				}
+			}
+			let next = match (self.a_items.front(), self.b_items.front()) {
+				(Some(a), Some(b)) if a.0 <= b.0 => Either::Left(&a.0),
+				(_, Some(b)) => Either::Right(&b.0),
+				(Some(a), None) => Either::Left(&a.0),
+				(None, None) => break,
+			};
+			let key = next.into_inner();
+			let last = self.left.last().map(|(key, _)| key);
+			let last = last.max(self.right.last().map(|(key, _)| key));
+			if self.left.len() + self.right.len() >= self.segment_len && last < Some(key) {
+				break;
+			}
+			if next.is_left() {
+				self.left.push(self.a_items.pop_front().unwrap());
+			} else {
+				self.right.push(self.b_items.pop_front().unwrap());
+			}
+		}
+		if !self.left.is_empty() || !self.right.is_empty() {
+			Cut::Segment(KeySegment {
+				left: mem::take(&mut self.left),
+				right: mem::take(&mut self.right),
+			})
+		} else {
+			Cut::Done
+		}
+	}
+}
+impl<TA: StreamTask, TB: StreamTask> Shared<TA, TB> {
+	fn wake(&mut self) {
+		for waker in self.waiting.drain(..) {
+			waker.wake();
+		}
+	}
+}
+
+pub struct KeySegmentTask<TA: StreamTask, TB: StreamTask> {
+	shared: Arc<Mutex<Shared<TA, TB>>>,
+}
+impl<TA, TB, K, V1, V2> StreamTask for KeySegmentTask<TA, TB>
+where
+	TA: StreamTask<Item = (K, V1)>,
+	TB: StreamTask<Item = (K, V2)>,
+	K: Ord,
+{
+	type Item = KeySegment<K, V1, V2>;
+	type Async = KeySegmentAsync<TA, TB>;
+
+	fn into_async(self) -> Self::Async {
+		KeySegmentAsync {
+			shared: self.shared,
+			reading: None,
+		}
+	}
+}
+
+/// Cuts segments until the inputs are exhausted, reading partitions of them into
+/// the shared state as needed.
+#[pin_project(PinnedDrop)]
+pub struct KeySegmentAsync<TA: StreamTask, TB: StreamTask> {
+	shared: Arc<Mutex<Shared<TA, TB>>>,
+	reading: Option<Either<Reading<TA>, Reading<TB>>>,
+}
+
+struct Reading<T: StreamTask> {
+	stream: Pin<Box<T::Async>>,
+	items: Vec<T::Item>,
+}
+impl<T: StreamTask> Reading<T> {
+	fn new(task: T) -> Self {
+		Self {
+			stream: Box::pin(task.into_async()),
+			items: Vec::new(),
+		}
+	}
+	fn poll(&mut self, cx: &mut Context) -> Poll<()> {
+		while let Some(item) = ready!(self.stream.as_mut().poll_next(cx)) {
+			self.items.push(item);
+		}
+		Poll::Ready(())
+	}
+}
+
+impl<TA, TB, K, V1, V2> Stream for KeySegmentAsync<TA, TB>
+where
+	TA: StreamTask<Item = (K, V1)>,
+	TB: StreamTask<Item = (K, V2)>,
+	K: Ord,
+{
+	type Item = KeySegment<K, V1, V2>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		loop {
+			match self_.reading {
+				Some(Either::Left(reading)) => ready!(reading.poll(cx)),
+				Some(Either::Right(reading)) => ready!(reading.poll(cx)),
+				None => (),
+			}
+			// A panic while cutting leaves the inputs intact, so carry on with them
+			let mut shared = self_.shared.lock().unwrap_or_else(PoisonError::into_inner);
+			match self_.reading.take() {
+				Some(Either::Left(reading)) => shared.a_items.extend(reading.items),
+				Some(Either::Right(reading)) => shared.b_items.extend(reading.items),
+				None if shared.reading => {
+					shared.waiting.push(cx.waker().clone());
+					break Poll::Pending;
+				}
+				None => (),
+			}
+			shared.reading = false;
+			match shared.cut() {
+				Cut::Read(task) => {
+					shared.reading = true;
+					*self_.reading = Some(task.map_left(Reading::new).map_right(Reading::new));
+				}
+				Cut::Segment(segment) => {
+					shared.wake();
+					break Poll::Ready(Some(segment));
+				}
+				Cut::Done => {
+					shared.wake();
+					break Poll::Ready(None);
+				}
+			}
+		}
+	}
+}
+
+#[pinned_drop]
+impl<TA: StreamTask, TB: StreamTask> PinnedDrop for KeySegmentAsync<TA, TB> {
+	fn drop(self: Pin<&mut Self>) {
+		let self_ = self.project();
+		// Let the other tasks carry on without the partition this was reading
+		if self_.reading.is_some() {
+			let mut shared = self_.shared.lock().unwrap_or_else(PoisonError::into_inner);
+			shared.reading = false;
+			shared.wake();
+		}
+	}
+}
@@ -0,0 +1,116 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct StepBy<P> {
+	#[pin]
+	pipe: P,
+	step: usize,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for StepBy<P> {
+		type Item = P::Item;
+		type Task = StepByTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, self.pipe.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let step = *self_.step;
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| StepByTask::new(task, step)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for StepBy<P> {
+		type Output = P::Output;
+		type Task = StepByTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			StepByTask::new(self.pipe.task(), self.step)
+		}
+	}
+}
+
+/// The counter is held by the task, so it restarts at the first item of each
+/// partition.
+#[pin_project]
+#[derive(Serialize, Deserialize, new)]
+pub struct StepByTask<T> {
+	#[pin]
+	task: T,
+	step: usize,
+	#[new(default)]
+	index: usize,
+}
+
+impl<C: StreamTask> StreamTask for StepByTask<C> {
+	type Item = C::Item;
+	type Async = StepByTask<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		StepByTask::new(self.task.into_async(), self.step)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for StepByTask<C> {
+	type Output = C::Output;
+	type Async = StepByTask<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		StepByTask::new(self.task.into_async(), self.step)
+	}
+}
+
+impl<C: Stream> Stream for StepByTask<C> {
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx)) {
+			let keep = *self_.index == 0;
+			*self_.index = (*self_.index + 1) % *self_.step;
+			if keep {
+				return Poll::Ready(Some(item));
+			}
+		}
+		Poll::Ready(None)
+	}
+}
+
+impl<C: Pipe<Input>, Input> Pipe<Input> for StepByTask<C> {
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		while let Some(item) = ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+			let keep = *self_.index == 0;
+			*self_.index = (*self_.index + 1) % *self_.step;
+			if keep {
+				return Poll::Ready(Some(item));
+			}
+		}
+		Poll::Ready(None)
+	}
+}
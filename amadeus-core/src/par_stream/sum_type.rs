@@ -5,7 +5,7 @@ use std::{
 use sum::Sum2;
 
 use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
-use crate::pipe::Pipe;
+use crate::{pipe::Pipe, plan::Plan};
 
 impl_par_dist! {
 	impl<A: ParallelStream, B: ParallelStream<Item = A::Item>> ParallelStream for Sum2<A, B> {
@@ -18,6 +18,12 @@ impl_par_dist! {
 				Self::B(i) => i.size_hint(),
 			}
 		}
+		fn explain(&self) -> Plan {
+			match self {
+				Self::A(i) => i.explain(),
+				Self::B(i) => i.explain(),
+			}
+		}
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			match self.as_pin_mut() {
 				Sum2::A(i) => i.next_task(cx).map(|task| task.map(Sum2::A)),
@@ -51,6 +57,12 @@ impl<A: StreamTask, B: StreamTask<Item = A::Item>> StreamTask for Sum2<A, B> {
 			Sum2::B(b) => Sum2::B(b.into_async()),
 		}
 	}
+	fn cost(&self) -> Option<u64> {
+		match self {
+			Sum2::A(a) => a.cost(),
+			Sum2::B(b) => b.cost(),
+		}
+	}
 }
 impl<A: PipeTask<Input>, B: PipeTask<Input, Output = A::Output>, Input> PipeTask<Input>
 	for Sum2<A, B>
@@ -0,0 +1,166 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	io::Write, pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{par_sink::Encoder, pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Tee<P, F, E> {
+	#[pin]
+	pipe: P,
+	writer: F,
+	encoder: E,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream, F, W, E> ParallelStream for Tee<P, F, E>
+	where
+		F: FnMut<(), Output = W> + Clone + Send + 'static,
+		W: Write,
+		E: for<'a> Encoder<&'a P::Item> + Clone + Send + 'static,
+	{
+		type Item = P::Item;
+		type Task = TeeTask<P::Task, F, E>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.pipe.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (writer, encoder) = (self_.writer, self_.encoder);
+			self_.pipe.next_task(cx).map(|task| {
+				task.map(|task| TeeTask::new(task, writer.clone(), encoder.clone()))
+			})
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, F, W, E, Input> ParallelPipe<Input> for Tee<P, F, E>
+	where
+		F: FnMut<(), Output = W> + Clone + Send + 'static,
+		W: Write,
+		E: for<'a> Encoder<&'a P::Output> + Clone + Send + 'static,
+	{
+		type Output = P::Output;
+		type Task = TeeTask<P::Task, F, E>;
+
+		fn task(&self) -> Self::Task {
+			TeeTask::new(self.pipe.task(), self.writer.clone(), self.encoder.clone())
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct TeeTask<T, F, E> {
+	task: T,
+	writer: F,
+	encoder: E,
+}
+
+impl<C: StreamTask, F, W, E> StreamTask for TeeTask<C, F, E>
+where
+	F: FnMut<(), Output = W>,
+	W: Write,
+	E: for<'a> Encoder<&'a C::Item>,
+{
+	type Item = C::Item;
+	type Async = TeeAsync<C::Async, W, E>;
+
+	fn into_async(mut self) -> Self::Async {
+		TeeAsync::new(
+			self.task.into_async(),
+			self.writer.call_mut(()),
+			self.encoder,
+		)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, F, W, E, Input> PipeTask<Input> for TeeTask<C, F, E>
+where
+	F: FnMut<(), Output = W>,
+	W: Write,
+	E: for<'a> Encoder<&'a C::Output>,
+{
+	type Output = C::Output;
+	type Async = TeeAsync<C::Async, W, E>;
+
+	fn into_async(mut self) -> Self::Async {
+		TeeAsync::new(
+			self.task.into_async(),
+			self.writer.call_mut(()),
+			self.encoder,
+		)
+	}
+}
+
+/// Encodes each item to `writer` as it passes through, flushing it once the
+/// partition is done.
+#[pin_project]
+#[derive(new)]
+pub struct TeeAsync<T, W, E> {
+	#[pin]
+	task: T,
+	writer: W,
+	encoder: E,
+	#[new(default)]
+	buf: Vec<u8>,
+}
+
+fn tee<Item, W: Write, E>(
+	item: Option<Item>, writer: &mut W, encoder: &mut E, buf: &mut Vec<u8>,
+) -> Option<Item>
+where
+	E: for<'a> Encoder<&'a Item>,
+{
+	let res = match &item {
+		Some(item) => {
+			buf.clear();
+			encoder
+				.encode(item, buf)
+				.and_then(|()| writer.write_all(buf))
+		}
+		None => writer.flush(),
+	};
+	res.unwrap_or_else(|err| panic!("Amadeus: failed to write teed item: {}", err));
+	item
+}
+
+impl<C: Stream, W: Write, E> Stream for TeeAsync<C, W, E>
+where
+	E: for<'a> Encoder<&'a C::Item>,
+{
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		let item = ready!(self_.task.poll_next(cx));
+		Poll::Ready(tee(item, self_.writer, self_.encoder, self_.buf))
+	}
+}
+
+impl<C: Pipe<Input>, W: Write, E, Input> Pipe<Input> for TeeAsync<C, W, E>
+where
+	E: for<'a> Encoder<&'a C::Output>,
+{
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let self_ = self.project();
+		let item = ready!(self_.task.poll_next(cx, stream));
+		Poll::Ready(tee(item, self_.writer, self_.encoder, self_.buf))
+	}
+}
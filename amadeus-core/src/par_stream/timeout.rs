@@ -0,0 +1,188 @@
+use derive_new::new;
+use futures::{future::LocalBoxFuture, ready, Future, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	error, fmt, pin::Pin, task::{Context, Poll}, time::Duration
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan, util::delay};
+
+/// What [`timeout`](super::ParallelStream::timeout) does with an item that
+/// doesn't complete within the deadline.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum TimeoutPolicy {
+	/// Yield `Err(TimedOut)` in its place.
+	Error,
+	/// Skip it.
+	Drop,
+}
+
+/// The error yielded for an item that timed out under [`TimeoutPolicy::Error`].
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct TimedOut;
+impl error::Error for TimedOut {}
+impl fmt::Display for TimedOut {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("item timed out")
+	}
+}
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct Timeout<P> {
+	#[pin]
+	pipe: P,
+	duration: Duration,
+	policy: TimeoutPolicy,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for Timeout<P>
+	where
+		P::Item: Future,
+	{
+		type Item = Result<<P::Item as Future>::Output, TimedOut>;
+		type Task = TimeoutTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			let (lower, upper) = self.pipe.size_hint();
+			match self.policy {
+				TimeoutPolicy::Error => (lower, upper),
+				TimeoutPolicy::Drop => (0, upper),
+			}
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (duration, policy) = (*self_.duration, *self_.policy);
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| TimeoutTask::new(task, duration, policy)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for Timeout<P>
+	where
+		P::Output: Future,
+	{
+		type Output = Result<<P::Output as Future>::Output, TimedOut>;
+		type Task = TimeoutTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			TimeoutTask::new(self.pipe.task(), self.duration, self.policy)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct TimeoutTask<T> {
+	task: T,
+	duration: Duration,
+	policy: TimeoutPolicy,
+}
+
+impl<C: StreamTask> StreamTask for TimeoutTask<C>
+where
+	C::Item: Future,
+{
+	type Item = Result<<C::Item as Future>::Output, TimedOut>;
+	type Async = TimeoutAsync<C::Async, C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		TimeoutAsync::new(self.task.into_async(), self.duration, self.policy)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for TimeoutTask<C>
+where
+	C::Output: Future,
+{
+	type Output = Result<<C::Output as Future>::Output, TimedOut>;
+	type Async = TimeoutAsync<C::Async, C::Output>;
+
+	fn into_async(self) -> Self::Async {
+		TimeoutAsync::new(self.task.into_async(), self.duration, self.policy)
+	}
+}
+
+/// Awaits each item in turn, racing it against a timer started when it is
+/// received.
+#[pin_project]
+#[derive(new)]
+pub struct TimeoutAsync<T, Fut> {
+	#[pin]
+	task: T,
+	duration: Duration,
+	policy: TimeoutPolicy,
+	#[new(default)]
+	pending: Option<(Pin<Box<Fut>>, LocalBoxFuture<'static, ()>)>,
+}
+
+impl<T, Fut: Future> TimeoutAsync<T, Fut> {
+	/// Poll the pending item, returning `None` if it was dropped.
+	fn poll_pending(
+		pending: &mut Option<(Pin<Box<Fut>>, LocalBoxFuture<'static, ()>)>, policy: TimeoutPolicy,
+		cx: &mut Context,
+	) -> Poll<Option<Result<Fut::Output, TimedOut>>> {
+		let (future, delay) = pending.as_mut().unwrap();
+		let res = if let Poll::Ready(output) = future.as_mut().poll(cx) {
+			Some(Ok(output))
+		} else {
+			ready!(delay.as_mut().poll(cx));
+			match policy {
+				TimeoutPolicy::Error => Some(Err(TimedOut)),
+				TimeoutPolicy::Drop => None,
+			}
+		};
+		*pending = None;
+		Poll::Ready(res)
+	}
+}
+
+impl<C: Stream<Item = Fut>, Fut: Future> Stream for TimeoutAsync<C, Fut> {
+	type Item = Result<Fut::Output, TimedOut>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		loop {
+			if self_.pending.is_some() {
+				if let Some(item) = ready!(Self::poll_pending(self_.pending, *self_.policy, cx)) {
+					break Poll::Ready(Some(item));
+				}
+			}
+			match ready!(self_.task.as_mut().poll_next(cx)) {
+				Some(future) => *self_.pending = Some((Box::pin(future), delay(*self_.duration))),
+				None => break Poll::Ready(None),
+			}
+		}
+	}
+}
+
+impl<C: Pipe<Input, Output = Fut>, Fut: Future, Input> Pipe<Input> for TimeoutAsync<C, Fut> {
+	type Output = Result<Fut::Output, TimedOut>;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		loop {
+			if self_.pending.is_some() {
+				if let Some(item) = ready!(Self::poll_pending(self_.pending, *self_.policy, cx)) {
+					break Poll::Ready(Some(item));
+				}
+			}
+			match ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+				Some(future) => *self_.pending = Some((Box::pin(future), delay(*self_.duration))),
+				None => break Poll::Ready(None),
+			}
+		}
+	}
+}
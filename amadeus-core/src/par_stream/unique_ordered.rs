@@ -0,0 +1,130 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashSet, hash::Hash, pin::Pin, task::{Context, Poll}
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct UniqueOrdered<P> {
+	#[pin]
+	pipe: P,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream> ParallelStream for UniqueOrdered<P>
+	where
+		P::Item: Eq + Hash + Clone,
+	{
+		type Item = P::Item;
+		type Task = UniqueOrderedTask<P::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, self.pipe.size_hint().1)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			self.project()
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(UniqueOrderedTask::new))
+		}
+	}
+
+	impl<P: ParallelPipe<Input>, Input> ParallelPipe<Input> for UniqueOrdered<P>
+	where
+		P::Output: Eq + Hash + Clone,
+	{
+		type Output = P::Output;
+		type Task = UniqueOrderedTask<P::Task>;
+
+		fn task(&self) -> Self::Task {
+			UniqueOrderedTask::new(self.pipe.task())
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct UniqueOrderedTask<C> {
+	task: C,
+}
+impl<C: StreamTask> StreamTask for UniqueOrderedTask<C>
+where
+	C::Item: Eq + Hash + Clone,
+{
+	type Item = C::Item;
+	type Async = UniqueOrderedAsync<C::Async, C::Item>;
+
+	fn into_async(self) -> Self::Async {
+		UniqueOrderedAsync::new(self.task.into_async())
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input>, Input> PipeTask<Input> for UniqueOrderedTask<C>
+where
+	C::Output: Eq + Hash + Clone,
+{
+	type Output = C::Output;
+	type Async = UniqueOrderedAsync<C::Async, C::Output>;
+
+	fn into_async(self) -> Self::Async {
+		UniqueOrderedAsync::new(self.task.into_async())
+	}
+}
+
+/// Passes through the first occurrence of each item, remembering those seen so
+/// far in the partition.
+#[pin_project]
+#[derive(new)]
+pub struct UniqueOrderedAsync<T, Item> {
+	#[pin]
+	task: T,
+	#[new(default)]
+	seen: HashSet<Item>,
+}
+
+impl<C: Stream> Stream for UniqueOrderedAsync<C, C::Item>
+where
+	C::Item: Eq + Hash + Clone,
+{
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		Poll::Ready(loop {
+			match ready!(self_.task.as_mut().poll_next(cx)) {
+				Some(item) if !self_.seen.insert(item.clone()) => (),
+				item => break item,
+			}
+		})
+	}
+}
+
+impl<C: Pipe<Input>, Input> Pipe<Input> for UniqueOrderedAsync<C, C::Output>
+where
+	C::Output: Eq + Hash + Clone,
+{
+	type Output = C::Output;
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		Poll::Ready(loop {
+			match ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+				Some(item) if !self_.seen.insert(item.clone()) => (),
+				item => break item,
+			}
+		})
+	}
+}
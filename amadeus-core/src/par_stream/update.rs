@@ -8,7 +8,7 @@ use std::{
 };
 
 use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
-use crate::pipe::Pipe;
+use crate::{pipe::Pipe, plan::Plan};
 
 #[pin_project]
 #[derive(new)]
@@ -30,6 +30,9 @@ impl_par_dist! {
 		fn size_hint(&self) -> (usize, Option<usize>) {
 			self.pipe.size_hint()
 		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
 		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 			let self_ = self.project();
 			let f = self_.f;
@@ -78,6 +81,9 @@ where
 			f: self.f,
 		}
 	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
 }
 impl<C: PipeTask<Input>, F, Input> PipeTask<Input> for UpdateTask<C, F>
 where
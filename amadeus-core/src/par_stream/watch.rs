@@ -0,0 +1,253 @@
+use futures::{ready, Stream};
+use pin_project::{pin_project, pinned_drop};
+use std::{
+	mem, ops::AddAssign, pin::Pin, sync::{Arc, Mutex, PoisonError}, task::{Context, Poll}, time::Duration
+};
+use tokio::sync::watch;
+
+use super::{ParallelStream, StreamTask};
+use crate::{
+	plan::Plan, util::{u64_to_f64, Instant}
+};
+
+/// The number of items a task aggregates before adding them to the shared total.
+const BLOCK: u64 = 1024;
+
+/// A running aggregate of the items passing through
+/// [`watch`](super::ParallelStream::watch), built up within each partition and
+/// merged into a total shared by all of them.
+pub trait Running<Item>: Clone + Send + Sync + 'static {
+	type Output: Clone + Send + Sync + 'static;
+
+	fn push(&mut self, item: &Item);
+	/// Add `other`, an aggregate of other items, to this one.
+	fn merge(&mut self, other: Self);
+	fn output(&self) -> Self::Output;
+}
+
+/// The running number of items.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RunningCount(u64);
+impl RunningCount {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+impl<Item> Running<Item> for RunningCount {
+	type Output = u64;
+
+	fn push(&mut self, _item: &Item) {
+		self.0 += 1;
+	}
+	fn merge(&mut self, other: Self) {
+		self.0 += other.0;
+	}
+	fn output(&self) -> u64 {
+		self.0
+	}
+}
+
+/// The running sum of the items.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RunningSum<T>(T);
+impl<T: Default> RunningSum<T> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+impl<T> Running<T> for RunningSum<T>
+where
+	T: AddAssign + Clone + Send + Sync + 'static,
+{
+	type Output = T;
+
+	fn push(&mut self, item: &T) {
+		self.0 += item.clone();
+	}
+	fn merge(&mut self, other: Self) {
+		self.0 += other.0;
+	}
+	fn output(&self) -> T {
+		self.0.clone()
+	}
+}
+
+/// The running mean of the items, or 0 before there are any.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RunningMean {
+	sum: f64,
+	count: u64,
+}
+impl RunningMean {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+impl Running<f64> for RunningMean {
+	type Output = f64;
+
+	fn push(&mut self, item: &f64) {
+		self.sum += item;
+		self.count += 1;
+	}
+	fn merge(&mut self, other: Self) {
+		self.sum += other.sum;
+		self.count += other.count;
+	}
+	fn output(&self) -> f64 {
+		if self.count == 0 {
+			0.0
+		} else {
+			self.sum / u64_to_f64(self.count)
+		}
+	}
+}
+
+/// The total of a watched stage, and how to publish it.
+struct Shared<R> {
+	zero: R,
+	period: Duration,
+	total: Mutex<(R, Instant)>,
+	merge: fn(&mut R, R),
+	publish: Box<dyn Fn(&R) + Send + Sync>,
+}
+
+#[pin_project]
+#[must_use]
+pub struct Watch<P, R> {
+	#[pin]
+	pipe: P,
+	shared: Arc<Shared<R>>,
+}
+impl<P, R> Watch<P, R> {
+	pub(crate) fn new<Item>(
+		pipe: P, running: R, period: Duration, sender: watch::Sender<R::Output>,
+	) -> Self
+	where
+		R: Running<Item>,
+	{
+		let shared = Arc::new(Shared {
+			zero: running.clone(),
+			period,
+			total: Mutex::new((running, Instant::now())),
+			merge: R::merge,
+			publish: Box::new(move |total: &R| {
+				let _ = sender.broadcast(total.output());
+			}),
+		});
+		Self { pipe, shared }
+	}
+}
+
+impl<P: ParallelStream, R> ParallelStream for Watch<P, R>
+where
+	R: Running<P::Item>,
+{
+	type Item = P::Item;
+	type Task = WatchTask<P::Task, R>;
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.pipe.size_hint()
+	}
+	fn explain(&self) -> Plan {
+		self.pipe.explain().then::<Self>()
+	}
+	fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+		let self_ = self.project();
+		let task = ready!(self_.pipe.next_task(cx));
+		let shared = self_.shared;
+		Poll::Ready(task.map(|task| WatchTask {
+			task,
+			shared: shared.clone(),
+		}))
+	}
+}
+
+pub struct WatchTask<T, R> {
+	task: T,
+	shared: Arc<Shared<R>>,
+}
+
+impl<C: StreamTask, R> StreamTask for WatchTask<C, R>
+where
+	R: Running<C::Item>,
+{
+	type Item = C::Item;
+	type Async = WatchAsync<C::Async, R>;
+
+	fn into_async(self) -> Self::Async {
+		WatchAsync {
+			task: self.task.into_async(),
+			local: self.shared.zero.clone(),
+			deadline: Instant::now() + self.shared.period,
+			shared: self.shared,
+			pending: 0,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+
+/// Aggregates items locally, merging them into the shared total every [`BLOCK`]
+/// items, once `period` has passed since this partition last did, and at the
+/// end of the partition. The total is published if `period` has passed since it
+/// last was, or if the partition has ended.
+#[pin_project(PinnedDrop)]
+pub struct WatchAsync<T, R: Clone> {
+	#[pin]
+	task: T,
+	shared: Arc<Shared<R>>,
+	local: R,
+	pending: u64,
+	deadline: Instant,
+}
+
+fn flush<R: Clone>(shared: &Shared<R>, local: &mut R, pending: &mut u64, end: bool) {
+	// A panic while publishing leaves the total intact, so carry on with it
+	let mut total = shared.total.lock().unwrap_or_else(PoisonError::into_inner);
+	let (total, published) = &mut *total;
+	(shared.merge)(total, mem::replace(local, shared.zero.clone()));
+	*pending = 0;
+	if end || Instant::now() - *published >= shared.period {
+		(shared.publish)(total);
+		*published = Instant::now();
+	}
+}
+
+impl<C: Stream, R> Stream for WatchAsync<C, R>
+where
+	R: Running<C::Item>,
+{
+	type Item = C::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		let item = ready!(self_.task.poll_next(cx));
+		match &item {
+			Some(item) => {
+				self_.local.push(item);
+				*self_.pending += 1;
+				// Checked on every item, so that slow partitions are published
+				// each `period` too
+				let now = Instant::now();
+				if *self_.pending == BLOCK || now >= *self_.deadline {
+					flush(self_.shared, self_.local, self_.pending, false);
+					*self_.deadline = now + self_.shared.period;
+				}
+			}
+			None => flush(self_.shared, self_.local, self_.pending, true),
+		}
+		Poll::Ready(item)
+	}
+}
+
+#[pinned_drop]
+impl<T, R: Clone> PinnedDrop for WatchAsync<T, R> {
+	fn drop(self: Pin<&mut Self>) {
+		let self_ = self.project();
+		if *self_.pending != 0 {
+			flush(self_.shared, self_.local, self_.pending, true);
+		}
+	}
+}
@@ -0,0 +1,239 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use serde_closure::traits::FnMut;
+use std::{
+	convert::TryFrom, pin::Pin, task::{Context, Poll}, time::Duration
+};
+
+use super::{ParallelPipe, ParallelStream, PipeTask, StreamTask};
+use crate::{pipe::Pipe, plan::Plan};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct WindowBy<P, F> {
+	#[pin]
+	pipe: P,
+	timestamp: F,
+	size: Duration,
+	slide: Duration,
+}
+
+impl_par_dist! {
+	impl<P: ParallelStream<Item = (K, V)>, F, K, V> ParallelStream for WindowBy<P, F>
+	where
+		F: for<'a> FnMut<(&'a V,), Output = Duration> + Clone + Send + 'static,
+		K: Clone,
+		V: Clone,
+	{
+		type Item = ((Duration, K), V);
+		type Task = WindowByTask<P::Task, F>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(0, None)
+		}
+		fn explain(&self) -> Plan {
+			self.pipe.explain().then::<Self>()
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			let (timestamp, size, slide) = (self_.timestamp, *self_.size, *self_.slide);
+			self_
+				.pipe
+				.next_task(cx)
+				.map(|task| task.map(|task| WindowByTask::new(task, timestamp.clone(), size, slide)))
+		}
+	}
+
+	impl<P: ParallelPipe<Input, Output = (K, V)>, F, K, V, Input> ParallelPipe<Input> for WindowBy<P, F>
+	where
+		F: for<'a> FnMut<(&'a V,), Output = Duration> + Clone + Send + 'static,
+		K: Clone,
+		V: Clone,
+	{
+		type Output = ((Duration, K), V);
+		type Task = WindowByTask<P::Task, F>;
+
+		fn task(&self) -> Self::Task {
+			WindowByTask::new(self.pipe.task(), self.timestamp.clone(), self.size, self.slide)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, new)]
+pub struct WindowByTask<T, F> {
+	task: T,
+	timestamp: F,
+	size: Duration,
+	slide: Duration,
+}
+
+impl<C: StreamTask<Item = (K, V)>, F, K, V> StreamTask for WindowByTask<C, F>
+where
+	F: for<'a> FnMut<(&'a V,), Output = Duration>,
+	K: Clone,
+	V: Clone,
+{
+	type Item = ((Duration, K), V);
+	type Async = WindowByAsync<C::Async, F, K, V>;
+
+	fn into_async(self) -> Self::Async {
+		WindowByAsync::new(
+			self.task.into_async(),
+			self.timestamp,
+			self.size,
+			self.slide,
+		)
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+impl<C: PipeTask<Input, Output = (K, V)>, F, K, V, Input> PipeTask<Input> for WindowByTask<C, F>
+where
+	F: for<'a> FnMut<(&'a V,), Output = Duration>,
+	K: Clone,
+	V: Clone,
+{
+	type Output = ((Duration, K), V);
+	type Async = WindowByAsync<C::Async, F, K, V>;
+
+	fn into_async(self) -> Self::Async {
+		WindowByAsync::new(
+			self.task.into_async(),
+			self.timestamp,
+			self.size,
+			self.slide,
+		)
+	}
+}
+
+/// Yields each item once for each window it falls in, in order of window start.
+#[pin_project]
+#[derive(new)]
+pub struct WindowByAsync<T, F, K, V> {
+	#[pin]
+	task: T,
+	timestamp: F,
+	size: Duration,
+	slide: Duration,
+	#[new(default)]
+	pending: Option<Windows<K, V>>,
+}
+
+impl<C: Stream<Item = (K, V)>, F, K, V> Stream for WindowByAsync<C, F, K, V>
+where
+	F: for<'a> FnMut<(&'a V,), Output = Duration>,
+	K: Clone,
+	V: Clone,
+{
+	type Item = ((Duration, K), V);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let mut self_ = self.project();
+		loop {
+			if let Some(item) = self_.pending.as_mut().and_then(Iterator::next) {
+				break Poll::Ready(Some(item));
+			}
+			match ready!(self_.task.as_mut().poll_next(cx)) {
+				Some((key, value)) => {
+					let timestamp = self_.timestamp.call_mut((&value,));
+					*self_.pending = Some(Windows::new(
+						key,
+						value,
+						timestamp,
+						*self_.size,
+						*self_.slide,
+					));
+				}
+				None => break Poll::Ready(None),
+			}
+		}
+	}
+}
+
+impl<C: Pipe<Input, Output = (K, V)>, F, K, V, Input> Pipe<Input> for WindowByAsync<C, F, K, V>
+where
+	F: for<'a> FnMut<(&'a V,), Output = Duration>,
+	K: Clone,
+	V: Clone,
+{
+	type Output = ((Duration, K), V);
+
+	fn poll_next(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Input>>,
+	) -> Poll<Option<Self::Output>> {
+		let mut self_ = self.project();
+		loop {
+			if let Some(item) = self_.pending.as_mut().and_then(Iterator::next) {
+				break Poll::Ready(Some(item));
+			}
+			match ready!(self_.task.as_mut().poll_next(cx, stream.as_mut())) {
+				Some((key, value)) => {
+					let timestamp = self_.timestamp.call_mut((&value,));
+					*self_.pending = Some(Windows::new(
+						key,
+						value,
+						timestamp,
+						*self_.size,
+						*self_.slide,
+					));
+				}
+				None => break Poll::Ready(None),
+			}
+		}
+	}
+}
+
+/// The windows `[start, start + size)` containing an item, where each `start` is
+/// a multiple of `slide`. The item is cloned for all but the last.
+struct Windows<K, V> {
+	item: Option<(K, V)>,
+	next: u128,
+	last: u128,
+	slide: u128,
+}
+impl<K, V> Windows<K, V> {
+	fn new(key: K, value: V, timestamp: Duration, size: Duration, slide: Duration) -> Self {
+		let (timestamp, size, slide) = (timestamp.as_nanos(), size.as_nanos(), slide.as_nanos());
+		let first = match timestamp.checked_sub(size) {
+			Some(before) => before / slide + 1,
+			None => 0,
+		};
+		let last = timestamp / slide;
+		// If `slide` exceeds `size`, an item between windows is in none of them
+		let item = if first <= last {
+			Some((key, value))
+		} else {
+			None
+		};
+		Self {
+			item,
+			next: first,
+			last,
+			slide,
+		}
+	}
+}
+impl<K: Clone, V: Clone> Iterator for Windows<K, V> {
+	type Item = ((Duration, K), V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let start = self.next * self.slide;
+		let start = Duration::new(
+			u64::try_from(start / NANOS_PER_SEC).unwrap(),
+			u32::try_from(start % NANOS_PER_SEC).unwrap(),
+		);
+		let (key, value) = if self.next < self.last {
+			self.item.clone()?
+		} else {
+			self.item.take()?
+		};
+		self.next += 1;
+		Some(((start, key), value))
+	}
+}
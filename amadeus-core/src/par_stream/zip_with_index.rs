@@ -0,0 +1,95 @@
+use derive_new::new;
+use futures::{ready, Stream};
+use pin_project::pin_project;
+use std::{
+	pin::Pin, sync::{
+		atomic::{AtomicU64, Ordering}, Arc
+	}, task::{Context, Poll}
+};
+
+use super::{ParallelStream, StreamTask};
+use crate::plan::Plan;
+
+/// The number of IDs a partition reserves at a time.
+const BLOCK: u64 = 1024;
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct ZipWithIndex<P> {
+	#[pin]
+	pipe: P,
+	#[new(default)]
+	base: Arc<AtomicU64>,
+}
+
+impl<P: ParallelStream> ParallelStream for ZipWithIndex<P> {
+	type Item = (u64, P::Item);
+	type Task = ZipWithIndexTask<P::Task>;
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.pipe.size_hint()
+	}
+	fn explain(&self) -> Plan {
+		self.pipe.explain().then::<Self>()
+	}
+	fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+		let self_ = self.project();
+		let task = ready!(self_.pipe.next_task(cx));
+		let base = self_.base;
+		Poll::Ready(task.map(|task| ZipWithIndexTask::new(task, base.clone())))
+	}
+}
+
+#[derive(new)]
+pub struct ZipWithIndexTask<T> {
+	task: T,
+	base: Arc<AtomicU64>,
+}
+
+impl<C: StreamTask> StreamTask for ZipWithIndexTask<C> {
+	type Item = (u64, C::Item);
+	type Async = ZipWithIndexAsync<C::Async>;
+
+	fn into_async(self) -> Self::Async {
+		ZipWithIndexAsync {
+			task: self.task.into_async(),
+			base: self.base,
+			next: 0,
+			end: 0,
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
+}
+
+/// Hands out the IDs `next..end`, reserving a new block from `base` once they
+/// run out.
+#[pin_project]
+pub struct ZipWithIndexAsync<T> {
+	#[pin]
+	task: T,
+	base: Arc<AtomicU64>,
+	next: u64,
+	end: u64,
+}
+
+impl<C: Stream> Stream for ZipWithIndexAsync<C> {
+	type Item = (u64, C::Item);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let self_ = self.project();
+		let (base, next, end) = (self_.base, self_.next, self_.end);
+		let item = ready!(self_.task.poll_next(cx));
+		Poll::Ready(item.map(|item| {
+			if *next == *end {
+				*next = base.fetch_add(BLOCK, Ordering::Relaxed);
+				*end = *next + BLOCK;
+			}
+			let id = *next;
+			*next += 1;
+			(id, item)
+		}))
+	}
+}
@@ -0,0 +1,157 @@
+//! A description of how a pipeline will be executed, as returned by
+//! [`explain`](crate::par_stream::ParallelStream::explain).
+//!
+//! Building a plan doesn't read any data: it reports what each source has
+//! already decided, such as its partitions and the columns and predicates it
+//! pushes down, and the chain of combinators applied on top.
+
+use serde::{Deserialize, Serialize};
+use std::{any::type_name, fmt};
+
+/// The planned execution of a stream: its source, with the number of
+/// partitions it's split into, followed by the combinators applied to it and
+/// the sink, if any, that it's reduced by.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Plan {
+	/// The number of partitions, i.e. tasks, if the source knows it up front.
+	pub partitions: Option<usize>,
+	/// The source followed by each combinator, in the order they're applied.
+	pub stages: Vec<Stage>,
+	/// The sink the stream is reduced by.
+	pub sink: Option<String>,
+}
+
+/// A source or combinator of a [`Plan`], with anything it has decided ahead
+/// of execution, such as `("projection", "a, b")` for a source that only reads
+/// columns `a` and `b`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Stage {
+	pub name: String,
+	pub details: Vec<(String, String)>,
+}
+
+impl Plan {
+	/// A plan of just the source `stage`.
+	pub fn new(stage: Stage, partitions: Option<usize>) -> Self {
+		Self {
+			partitions,
+			stages: vec![stage],
+			sink: None,
+		}
+	}
+
+	/// The plan of the source `S`, named after its type.
+	pub fn source<S: ?Sized>(partitions: Option<usize>) -> Self {
+		Self::new(Stage::of::<S>(), partitions)
+	}
+
+	/// This plan followed by the combinator `S`, named after its type.
+	#[must_use]
+	pub fn then<S: ?Sized>(self) -> Self {
+		self.then_stage(Stage::of::<S>())
+	}
+
+	/// This plan followed by `stage`.
+	#[must_use]
+	pub fn then_stage(mut self, stage: Stage) -> Self {
+		self.stages.push(stage);
+		self
+	}
+
+	/// This plan combined with `other` by `S`, such as
+	/// [`chain`](crate::par_stream::ParallelStream::chain). The partitions are
+	/// those of both, and `other` is described as a detail of `S`.
+	#[must_use]
+	pub fn merge<S: ?Sized>(mut self, other: Self) -> Self {
+		self.partitions = self.partitions.and_then(|a| Some(a + other.partitions?));
+		self.then_stage(Stage::of::<S>().detail("with", other.to_string().replace('\n', "; ")))
+	}
+
+	/// This plan reduced by the sink `S`.
+	#[must_use]
+	pub fn sink<S: ?Sized>(mut self) -> Self {
+		self.sink = Some(short_name::<S>());
+		self
+	}
+
+	/// The detail `key` of any stage, such as the `"projection"` of the source.
+	pub fn detail(&self, key: &str) -> Option<&str> {
+		self.stages.iter().find_map(|stage| stage.detail_value(key))
+	}
+}
+
+impl Stage {
+	pub fn new(name: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			details: Vec::new(),
+		}
+	}
+
+	/// A stage named after the type `S`, without its path or generic
+	/// parameters.
+	pub fn of<S: ?Sized>() -> Self {
+		let name = short_name::<S>();
+		Self::new(name.find('<').map_or(&*name, |i| &name[..i]))
+	}
+
+	/// This stage with the detail `key`.
+	#[must_use]
+	pub fn detail(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.details.push((key.into(), value.into()));
+		self
+	}
+
+	fn detail_value(&self, key: &str) -> Option<&str> {
+		self.details
+			.iter()
+			.find(|(key_, _)| key_ == key)
+			.map(|(_, value)| &**value)
+	}
+}
+
+impl fmt::Display for Plan {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (i, stage) in self.stages.iter().enumerate() {
+			if i == 0 {
+				write!(f, "{}", stage.name)?;
+				if let Some(partitions) = self.partitions {
+					write!(f, " ({} partitions)", partitions)?;
+				}
+			} else {
+				write!(f, "\n-> {}", stage.name)?;
+			}
+			for (key, value) in &stage.details {
+				write!(f, "\n   {}: {}", key, value)?;
+			}
+		}
+		if let Some(sink) = &self.sink {
+			write!(f, "\n=> {}", sink)?;
+		}
+		Ok(())
+	}
+}
+
+/// The name of `T` with the paths stripped from each type in it, so
+/// `amadeus_core::par_sink::Sum<amadeus_core::par_pipe::Identity, u64>` is
+/// `Sum<Identity, u64>`.
+pub(crate) fn short_name<T: ?Sized>() -> String {
+	let name = type_name::<T>();
+	let mut ret = String::with_capacity(name.len());
+	let mut start = 0;
+	let mut chars = name.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			':' if chars.peek() == Some(&':') => {
+				let _ = chars.next();
+				ret.truncate(start);
+			}
+			'<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | '&' | '*' | ';' => {
+				ret.push(c);
+				start = ret.len();
+			}
+			c => ret.push(c),
+		}
+	}
+	ret
+}
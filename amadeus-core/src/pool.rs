@@ -10,12 +10,49 @@ impl<T: ?Sized> ProcessSend for T where T: Send + Serialize + for<'de> Deseriali
 
 type Result<T> = std::result::Result<T, Box<dyn Error + Send>>;
 
+/// How the partitions of a stream are allocated among the workers of a pool.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Scheduling {
+	/// In the order the stream yields them, each worker taking a contiguous run.
+	InOrder,
+	/// Largest first by [`StreamTask::cost`](crate::par_stream::StreamTask::cost),
+	/// each to the worker with the least cost allocated so far, so that large
+	/// partitions start early rather than straggling at the end. Partitions
+	/// without a cost hint count as a cost of 1.
+	///
+	/// Each worker is no longer given a contiguous run of partitions, so their
+	/// items aren't reduced in source order. This is only suitable for sinks that
+	/// are commutative, such as [`count`](crate::par_stream::ParallelStream::count),
+	/// [`sum`](crate::par_stream::ParallelStream::sum) or
+	/// [`max`](crate::par_stream::ParallelStream::max), rather than ones like
+	/// [`collect`](crate::par_stream::ParallelStream::collect) into a `Vec` or
+	/// [`fold`](crate::par_stream::ParallelStream::fold) whose output depends on
+	/// order. Pools that are deterministic ignore it and schedule in order.
+	LargestFirst,
+}
+impl Default for Scheduling {
+	fn default() -> Self {
+		Self::InOrder
+	}
+}
+
 #[cfg_attr(not(nightly), serde_closure::desugar)]
 pub trait ProcessPool: Clone + Send + Sync + RefUnwindSafe + UnwindSafe + Unpin {
 	type ThreadPool: ThreadPool + 'static;
 
 	fn processes(&self) -> usize;
 
+	/// Whether the partial results of partitions are combined in the order of
+	/// the partitions, rather than as they complete, for reproducible output.
+	fn is_deterministic(&self) -> bool {
+		false
+	}
+
+	/// How the partitions of a stream are allocated among the pool's workers.
+	fn scheduling(&self) -> Scheduling {
+		Scheduling::InOrder
+	}
+
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: traits::FnOnce(&Self::ThreadPool) -> Fut + ProcessSend + 'static,
@@ -42,6 +79,17 @@ pub trait ProcessPool: Clone + Send + Sync + RefUnwindSafe + UnwindSafe + Unpin
 pub trait ThreadPool: Clone + Send + Sync + RefUnwindSafe + UnwindSafe + Unpin {
 	fn threads(&self) -> usize;
 
+	/// Whether the partial results of partitions are combined in the order of
+	/// the partitions, rather than as they complete, for reproducible output.
+	fn is_deterministic(&self) -> bool {
+		false
+	}
+
+	/// How the partitions of a stream are allocated among the pool's workers.
+	fn scheduling(&self) -> Scheduling {
+		Scheduling::InOrder
+	}
+
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: FnOnce() -> Fut + Send + 'static,
@@ -75,6 +123,12 @@ where
 	fn processes(&self) -> usize {
 		(*self).processes()
 	}
+	fn is_deterministic(&self) -> bool {
+		(*self).is_deterministic()
+	}
+	fn scheduling(&self) -> Scheduling {
+		(*self).scheduling()
+	}
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: traits::FnOnce(&Self::ThreadPool) -> Fut + ProcessSend + 'static,
@@ -101,6 +155,12 @@ where
 	fn threads(&self) -> usize {
 		(*self).threads()
 	}
+	fn is_deterministic(&self) -> bool {
+		(*self).is_deterministic()
+	}
+	fn scheduling(&self) -> Scheduling {
+		(*self).scheduling()
+	}
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: FnOnce() -> Fut + Send + 'static,
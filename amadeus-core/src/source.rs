@@ -1,7 +1,14 @@
-use std::{error::Error, fmt::Debug};
+use derive_new::new;
+use futures::Stream;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use std::{
+	error::Error, fmt::Debug, pin::Pin, task::{Context, Poll}
+};
+use sum::Sum2;
 
 use crate::{
-	par_sink::{DistributedSink, ParallelSink}, par_stream::{DistributedStream, ParallelStream}
+	par_sink::{DistributedSink, ParallelSink}, par_stream::{DistributedStream, ParallelStream, StreamTask}, plan::Plan
 };
 
 pub trait Source: Clone + Debug {
@@ -25,3 +32,125 @@ pub trait Destination: Clone + Debug {
 	fn par_sink(self) -> Self::ParSink;
 	fn dist_sink(self) -> Self::DistSink;
 }
+
+/// Two sources of the same item type combined into one, whose partitions are
+/// those of `a` followed by those of `b`.
+///
+/// The sources can be of different kinds, e.g. Parquet files on S3 and CSV
+/// files on local disk. More than two can be combined by nesting.
+#[derive(Clone, Debug, new)]
+pub struct Union<A, B> {
+	a: A,
+	b: B,
+}
+impl<A, B> Union<A, B> {
+	pub fn into_inner(self) -> (A, B) {
+		(self.a, self.b)
+	}
+}
+
+impl<A, B> Source for Union<A, B>
+where
+	A: Source,
+	B: Source<Item = A::Item>,
+{
+	type Item = A::Item;
+	type Error = Sum2<A::Error, B::Error>;
+
+	type ParStream = UnionStream<A::ParStream, B::ParStream>;
+	type DistStream = UnionStream<A::DistStream, B::DistStream>;
+
+	fn par_stream(self) -> Self::ParStream {
+		UnionStream::new(self.a.par_stream(), self.b.par_stream())
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		UnionStream::new(self.a.dist_stream(), self.b.dist_stream())
+	}
+}
+
+#[pin_project]
+#[derive(new)]
+#[must_use]
+pub struct UnionStream<A, B> {
+	#[pin]
+	a: A,
+	#[pin]
+	b: B,
+}
+
+impl_par_dist! {
+	impl<A, B, T, EA, EB> ParallelStream for UnionStream<A, B>
+	where
+		A: ParallelStream<Item = Result<T, EA>>,
+		B: ParallelStream<Item = Result<T, EB>>,
+	{
+		type Item = Result<T, Sum2<EA, EB>>;
+		type Task = UnionTask<A::Task, B::Task>;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			let (a_lower, a_upper) = self.a.size_hint();
+			let (b_lower, b_upper) = self.b.size_hint();
+			(
+				a_lower.saturating_add(b_lower),
+				a_upper.and_then(|a| a.checked_add(b_upper?)),
+			)
+		}
+		fn explain(&self) -> Plan {
+			self.a.explain().merge::<Self>(self.b.explain())
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			let self_ = self.project();
+			match self_.a.next_task(cx) {
+				Poll::Ready(Some(a)) => Poll::Ready(Some(UnionTask::A(a))),
+				Poll::Ready(None) => self_.b.next_task(cx).map(|task| task.map(UnionTask::B)),
+				Poll::Pending => Poll::Pending,
+			}
+		}
+	}
+}
+
+#[pin_project(project = UnionTaskProj)]
+#[derive(Serialize, Deserialize)]
+pub enum UnionTask<A, B> {
+	A(#[pin] A),
+	B(#[pin] B),
+}
+impl<A, B, T, EA, EB> StreamTask for UnionTask<A, B>
+where
+	A: StreamTask<Item = Result<T, EA>>,
+	B: StreamTask<Item = Result<T, EB>>,
+{
+	type Item = Result<T, Sum2<EA, EB>>;
+	type Async = UnionTask<A::Async, B::Async>;
+
+	fn into_async(self) -> Self::Async {
+		match self {
+			UnionTask::A(a) => UnionTask::A(a.into_async()),
+			UnionTask::B(b) => UnionTask::B(b.into_async()),
+		}
+	}
+	fn cost(&self) -> Option<u64> {
+		match self {
+			UnionTask::A(a) => a.cost(),
+			UnionTask::B(b) => b.cost(),
+		}
+	}
+}
+impl<A, B, T, EA, EB> Stream for UnionTask<A, B>
+where
+	A: Stream<Item = Result<T, EA>>,
+	B: Stream<Item = Result<T, EB>>,
+{
+	type Item = Result<T, Sum2<EA, EB>>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		match self.project() {
+			UnionTaskProj::A(a) => a
+				.poll_next(cx)
+				.map(|item| item.map(|item| item.map_err(Sum2::A))),
+			UnionTaskProj::B(b) => b
+				.poll_next(cx)
+				.map(|item| item.map(|item| item.map_err(Sum2::B))),
+		}
+	}
+}
@@ -1,12 +1,14 @@
 use derive_new::new;
-use futures::{ready, Stream};
+use futures::{future::LocalBoxFuture, ready, FutureExt, Stream};
 use pin_project::pin_project;
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
 use std::{
-	any::{Any, TypeId}, error, fmt, hash::{Hash, Hasher}, io, marker::PhantomData, pin::Pin, sync::Arc, task::{Context, Poll}
+	any::{Any, TypeId}, error, fmt, hash::{Hash, Hasher}, io, marker::PhantomData, pin::Pin, sync::Arc, task::{Context, Poll}, time::Duration
 };
 
-use crate::par_stream::{DistributedStream, ParallelStream};
+use crate::{
+	par_stream::{DistributedStream, ParallelStream}, plan::{Plan, Stage}
+};
 
 pub struct ResultExpand<T, E>(pub Result<T, E>);
 impl<T, E> IntoIterator for ResultExpand<T, E>
@@ -105,11 +107,44 @@ where
 	fn size_hint(&self) -> (usize, Option<usize>) {
 		self.0.size_hint()
 	}
+	fn explain(&self) -> Plan {
+		self.0.explain()
+	}
 	fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
 		self.project().0.next_task(cx)
 	}
 }
 
+/// A stream that describes itself by `stage`, for a source to report what it
+/// has decided ahead of execution, such as the columns it projects, in its
+/// [`Plan`] in place of the combinators it's built from.
+#[pin_project]
+#[derive(new)]
+pub struct Planned<S> {
+	#[pin]
+	stream: S,
+	stage: Stage,
+}
+impl_par_dist! {
+	impl<S> ParallelStream for Planned<S>
+	where
+		S: ParallelStream,
+	{
+		type Item = S::Item;
+		type Task = S::Task;
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			self.stream.size_hint()
+		}
+		fn explain(&self) -> Plan {
+			Plan::new(self.stage.clone(), self.stream.explain().partitions)
+		}
+		fn next_task(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Task>> {
+			self.project().stream.next_task(cx)
+		}
+	}
+}
+
 // This is a dumb hack to avoid triggering https://github.com/rust-lang/rust/issues/48214 in amadeus-derive: see https://github.com/taiki-e/pin-project/issues/102#issuecomment-540472282
 #[doc(hidden)]
 #[repr(transparent)]
@@ -243,3 +278,68 @@ pub fn f64_to_u64(x: f64) -> u64 {
 	assert_eq!(x, x as u64 as f64);
 	x as u64
 }
+
+/// A future that completes after `duration`. On wasm32, where tokio's timer
+/// isn't available, it's scheduled with `setTimeout`.
+pub(crate) fn delay(duration: Duration) -> LocalBoxFuture<'static, ()> {
+	#[cfg(not(target_arch = "wasm32"))]
+	return tokio::time::delay_for(duration).boxed_local();
+	#[cfg(target_arch = "wasm32")]
+	return async move {
+		// setTimeout fires immediately for timeouts that don't fit in an i32
+		let max = Duration::from_millis(i32::MAX as u64);
+		let mut remaining = duration;
+		while remaining > Duration::from_millis(0) {
+			let step = remaining.min(max);
+			let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+				let _ = wasm::set_timeout(&resolve, step.as_secs_f64() * 1000.0);
+			});
+			let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+			remaining -= step;
+		}
+	}
+	.boxed_local();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use wasm::Instant;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+	use std::{
+		ops::{Add, Sub}, time::Duration
+	};
+	use wasm_bindgen::prelude::wasm_bindgen;
+
+	#[wasm_bindgen]
+	extern "C" {
+		#[wasm_bindgen(js_name = setTimeout)]
+		pub(super) fn set_timeout(handler: &js_sys::Function, timeout: f64) -> f64;
+	}
+
+	/// What's used of [`std::time::Instant`], which isn't supported on
+	/// wasm32, measured with `Date.now()`.
+	#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+	pub(crate) struct Instant(Duration);
+	impl Instant {
+		pub(crate) fn now() -> Self {
+			Self(Duration::from_secs_f64(js_sys::Date::now() / 1000.0))
+		}
+	}
+	impl Add<Duration> for Instant {
+		type Output = Self;
+
+		fn add(self, other: Duration) -> Self {
+			Self(self.0 + other)
+		}
+	}
+	impl Sub for Instant {
+		type Output = Duration;
+
+		fn sub(self, other: Self) -> Duration {
+			self.0.checked_sub(other.0).unwrap_or_default()
+		}
+	}
+}
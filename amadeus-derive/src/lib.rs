@@ -474,7 +474,7 @@ fn impl_struct(
 			pub use #amadeus_path::amadeus_core::util::Wrapper;
 			pub use #amadeus_path::amadeus_types::{AmadeusOrd, Data as CoreData, DowncastFrom, Downcast, DowncastError, Value, Group, SchemaIncomplete, ListVec, __internal::{Serialize as Serialize_, Deserialize as Deserialize_, Serializer as Serializer_, Deserializer as Deserializer_, SerializeTuple, Error as SerdeError, Visitor, SeqAccess}};
 			pub use #amadeus_path::data::Data;
-			pub use ::std::{borrow::ToOwned, boxed::Box, clone::Clone, collections::HashMap, convert::{From, Into}, cmp::{Ordering, PartialEq}, default::Default, error::Error, fmt::{self, Debug, Write}, format, hash::{Hash, Hasher}, iter::{ExactSizeIterator, IntoIterator, Iterator}, marker::{PhantomData, Send, Sized, Sync}, result::Result::{self, Ok, Err}, string::String, panic, vec, vec::{IntoIter, Vec}, option::Option::{self, Some, None}};
+			pub use ::std::{borrow::{Cow, ToOwned}, boxed::Box, clone::Clone, collections::HashMap, convert::{From, Into}, cmp::{Ordering, PartialEq}, default::Default, error::Error, fmt::{self, Debug, Write}, format, hash::{Hash, Hasher}, iter::{ExactSizeIterator, IntoIterator, Iterator}, marker::{PhantomData, Send, Sized, Sync}, result::Result::{self, Ok, Err}, string::String, panic, vec, vec::{IntoIter, Vec}, option::Option::{self, Some, None}};
 		}
 
 		#parquet_derives
@@ -530,6 +530,15 @@ fn impl_struct(
 				self.__len
 			}
 			#[inline(always)]
+			fn get_a(&self, index: usize) -> __::Option<__::Cow<'_, #name #ty_generics>> {
+				if index >= self.__len {
+					return __::None;
+				}
+				__::Some(__::Cow::Owned(#name {
+					#(#field_names1: <<#field_types1 as __::CoreData>::Vec as __::ListVec<#field_types1>>::get_a(&self.#field_names2, index).unwrap().into_owned(),)*
+				}))
+			}
+			#[inline(always)]
 			fn from_vec(vec: __::Vec<#name #ty_generics>) -> Self {
 				// TODO: reserve capacity
 				let mut self_ = Self::new();
@@ -622,7 +631,13 @@ fn impl_struct(
 		#[automatically_derived]
 		impl #impl_generics __::DowncastFrom<__::Value> for #name #ty_generics #where_clause_with_data {
 			fn downcast_from(t: __::Value) -> __::Result<Self, __::DowncastError> {
-				let group = t.into_group()?;
+				__::Downcast::downcast(__::Downcast::<__::Group>::downcast(t)?)
+			}
+		}
+
+		#[automatically_derived]
+		impl #impl_generics __::DowncastFrom<__::Group> for #name #ty_generics #where_clause_with_data {
+			fn downcast_from(group: __::Group) -> __::Result<Self, __::DowncastError> {
 				let field_names = group.field_names().map(__::Clone::clone);
 				let mut fields = __::IntoIterator::into_iter(group.into_fields());
 				let err = __::DowncastError{from:"group",to:stringify!(#name)};
@@ -142,6 +142,11 @@ pub enum LogicalType {
 	/// the number of milliseconds associated with the provided duration.
 	/// This duration of time is independent of any particular timezone or date.
 	Interval,
+
+	/// A UUID, stored as a FIXED_LEN_BYTE_ARRAY of length 16 in big-endian byte order.
+	/// It has no converted type, so is only read from the logical type of a schema
+	/// element.
+	Uuid,
 }
 
 // ----------------------------------------------------------------------
@@ -308,7 +313,7 @@ impl ColumnOrder {
 			| LogicalType::TimestampMillis
 			| LogicalType::TimestampMicros => SortOrder::Signed,
 
-			LogicalType::Interval => SortOrder::Unsigned,
+			LogicalType::Interval | LogicalType::Uuid => SortOrder::Unsigned,
 
 			LogicalType::List | LogicalType::Map | LogicalType::MapKeyValue => SortOrder::Undefined,
 
@@ -386,6 +391,7 @@ impl fmt::Display for LogicalType {
 			LogicalType::Json => "JSON",
 			LogicalType::Bson => "BSON",
 			LogicalType::Interval => "INTERVAL",
+			LogicalType::Uuid => "UUID",
 		})
 	}
 }
@@ -555,6 +561,7 @@ impl From<LogicalType> for Option<parquet::ConvertedType> {
 			LogicalType::Json => Some(parquet::ConvertedType::Json),
 			LogicalType::Bson => Some(parquet::ConvertedType::Bson),
 			LogicalType::Interval => Some(parquet::ConvertedType::Interval),
+			LogicalType::Uuid => None,
 		}
 	}
 }
@@ -733,6 +740,7 @@ impl str::FromStr for LogicalType {
 			"JSON" => Ok(LogicalType::Json),
 			"BSON" => Ok(LogicalType::Bson),
 			"INTERVAL" => Ok(LogicalType::Interval),
+			"UUID" => Ok(LogicalType::Uuid),
 			other => Err(general_err!("Invalid logical type {}", other)),
 		}
 	}
@@ -40,6 +40,23 @@ pub enum ColumnReader {
 	FixedLenByteArrayColumnReader(ColumnReaderImpl<FixedLenByteArrayType>),
 }
 
+impl ColumnReader {
+	/// Set the number of leading records to be skipped by the reader of its
+	/// triplets.
+	pub fn skip_records(&mut self, records: u64) {
+		match self {
+			ColumnReader::BoolColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::Int32ColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::Int64ColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::Int96ColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::FloatColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::DoubleColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::ByteArrayColumnReader(r) => r.records_to_skip = records,
+			ColumnReader::FixedLenByteArrayColumnReader(r) => r.records_to_skip = records,
+		}
+	}
+}
+
 /// Gets a specific column reader corresponding to column descriptor `col_descr`. The
 /// column reader will read from pages in `col_page_reader`.
 pub fn get_column_reader(
@@ -108,6 +125,10 @@ pub struct ColumnReaderImpl<T: DataType> {
 
 	// Cache of decoders for existing encodings
 	decoders: HashMap<Encoding, Box<dyn Decoder<T>>>,
+
+	// The number of leading records to be skipped by the reader of its triplets,
+	// as where a record starts is only known from the repetition levels.
+	records_to_skip: u64,
 }
 
 impl<T: DataType> ColumnReaderImpl<T> {
@@ -122,9 +143,15 @@ impl<T: DataType> ColumnReaderImpl<T> {
 			num_buffered_values: 0,
 			num_decoded_values: 0,
 			decoders: HashMap::new(),
+			records_to_skip: 0,
 		}
 	}
 
+	/// The number of leading records to be skipped by the reader of its triplets.
+	pub fn records_to_skip(&self) -> u64 {
+		self.records_to_skip
+	}
+
 	/// Reads a batch of values of at most `batch_size`.
 	///
 	/// This will try to read from the row group, and fills up at most `batch_size` values
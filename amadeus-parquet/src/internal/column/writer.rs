@@ -20,7 +20,7 @@
 use std::{cmp, collections::VecDeque, mem, rc::Rc};
 
 use crate::internal::{
-	basic::{Compression, Encoding, PageType, Type}, column::page::{CompressedPage, Page, PageWriteSpec, PageWriter}, compression::{create_codec, Codec}, data_type::*, encodings::{
+	basic::{Compression, Encoding, PageType, Type}, column::page::{CompressedPage, Page, PageWriteSpec, PageWriter}, compression::{create_codec, create_codec_with_level, Codec}, data_type::*, encodings::{
 		encoding::{get_encoder, DictEncoder, Encoder}, levels::{max_buffer_size, LevelEncoder}
 	}, errors::{ParquetError, Result}, file::{
 		metadata::ColumnChunkMetaData, properties::{WriterProperties, WriterPropertiesPtr, WriterVersion}
@@ -123,7 +123,11 @@ impl<T: DataType> ColumnWriterImpl<T> {
 		descr: ColumnDescPtr, props: WriterPropertiesPtr, page_writer: Box<dyn PageWriter>,
 	) -> Self {
 		let codec = props.compression(descr.path());
-		let compressor = create_codec(codec).unwrap();
+		// An invalid level is rejected by the row group writer before it gets here;
+		// other callers fall back to the codec's default level.
+		let compressor = create_codec_with_level(codec, props.compression_level(descr.path()))
+			.or_else(|_| create_codec(codec))
+			.unwrap();
 
 		// Optionally set dictionary encoder.
 		let dict_encoder =
@@ -66,17 +66,58 @@ pub trait Codec {
 /// bytes for the compression type.
 /// This returns `None` if the codec type is `UNCOMPRESSED`.
 pub fn create_codec(codec: CodecType) -> Result<Option<Box<dyn Codec>>> {
+	create_codec_with_level(codec, None)
+}
+
+/// Like [`create_codec`], but compressing at `level` rather than the codec's
+/// default. Returns an error if `level` is outside the range the codec supports.
+/// Codecs without levels, such as Snappy, ignore it.
+pub fn create_codec_with_level(
+	codec: CodecType, level: Option<u32>,
+) -> Result<Option<Box<dyn Codec>>> {
+	check_compression_level(codec, level)?;
 	match codec {
-		CodecType::Brotli => Ok(Some(Box::new(BrotliCodec::new()))),
-		CodecType::Gzip => Ok(Some(Box::new(GZipCodec::new()))),
+		CodecType::Brotli => Ok(Some(Box::new(BrotliCodec::new(
+			level.unwrap_or(BROTLI_DEFAULT_COMPRESSION_QUALITY),
+		)))),
+		CodecType::Gzip => Ok(Some(Box::new(GZipCodec::new(
+			level.map_or_else(Compression::default, Compression::new),
+		)))),
 		CodecType::Snappy => Ok(Some(Box::new(SnappyCodec::new()))),
 		CodecType::Lz4 => Ok(Some(Box::new(LZ4Codec::new()))),
-		CodecType::Zstd => Ok(Some(Box::new(ZSTDCodec::new()))),
+		CodecType::Zstd => Ok(Some(Box::new(ZSTDCodec::new(
+			level.map_or(ZSTD_COMPRESSION_LEVEL, |level| level as i32),
+		)))),
 		CodecType::Uncompressed => Ok(None),
 		_ => Err(nyi_err!("The codec type {} is not supported yet", codec)),
 	}
 }
 
+/// Returns an error if `level` isn't valid for `codec`. Any level is valid for
+/// codecs without levels, as they ignore it.
+pub fn check_compression_level(codec: CodecType, level: Option<u32>) -> Result<()> {
+	let level = match level {
+		Some(level) => level,
+		None => return Ok(()),
+	};
+	let range = match codec {
+		CodecType::Brotli => 0..=11,
+		CodecType::Gzip => 0..=9,
+		CodecType::Zstd => 1..=21,
+		_ => return Ok(()),
+	};
+	if !range.contains(&level) {
+		return Err(general_err!(
+			"Compression level {} is invalid for {}, expected {}-{}",
+			level,
+			codec,
+			range.start(),
+			range.end()
+		));
+	}
+	Ok(())
+}
+
 /// Codec for Snappy compression format.
 pub struct SnappyCodec {
 	decoder: Decoder,
@@ -114,12 +155,14 @@ impl Codec for SnappyCodec {
 }
 
 /// Codec for GZIP compression algorithm.
-pub struct GZipCodec {}
+pub struct GZipCodec {
+	level: Compression,
+}
 
 impl GZipCodec {
 	/// Creates new GZIP compression codec.
-	fn new() -> Self {
-		Self {}
+	fn new(level: Compression) -> Self {
+		Self { level }
 	}
 }
 
@@ -130,23 +173,25 @@ impl Codec for GZipCodec {
 	}
 
 	fn compress(&mut self, input_buf: &[u8], output_buf: &mut Vec<u8>) -> Result<()> {
-		let mut encoder = write::GzEncoder::new(output_buf, Compression::default());
+		let mut encoder = write::GzEncoder::new(output_buf, self.level);
 		encoder.write_all(input_buf)?;
 		encoder.try_finish().map_err(|e| e.into())
 	}
 }
 
 const BROTLI_DEFAULT_BUFFER_SIZE: usize = 4096;
-const BROTLI_DEFAULT_COMPRESSION_QUALITY: u32 = 1; // supported levels 0-9
+const BROTLI_DEFAULT_COMPRESSION_QUALITY: u32 = 1; // supported levels 0-11
 const BROTLI_DEFAULT_LG_WINDOW_SIZE: u32 = 22; // recommended between 20-22
 
 /// Codec for Brotli compression algorithm.
-pub struct BrotliCodec {}
+pub struct BrotliCodec {
+	quality: u32,
+}
 
 impl BrotliCodec {
 	/// Creates new Brotli compression codec.
-	fn new() -> Self {
-		Self {}
+	fn new(quality: u32) -> Self {
+		Self { quality }
 	}
 }
 
@@ -161,7 +206,7 @@ impl Codec for BrotliCodec {
 		let mut encoder = brotli::CompressorWriter::new(
 			output_buf,
 			BROTLI_DEFAULT_BUFFER_SIZE,
-			BROTLI_DEFAULT_COMPRESSION_QUALITY,
+			self.quality,
 			BROTLI_DEFAULT_LG_WINDOW_SIZE,
 		);
 		encoder.write_all(&input_buf[..])?;
@@ -204,16 +249,19 @@ impl Codec for LZ4Codec {
 }
 
 /// Codec for Zstandard compression algorithm.
-pub struct ZSTDCodec {}
+pub struct ZSTDCodec {
+	level: i32,
+}
 
 impl ZSTDCodec {
 	/// Creates new Zstandard compression codec.
-	fn new() -> Self {
-		Self {}
+	fn new(level: i32) -> Self {
+		Self { level }
 	}
 }
 
-/// Compression level (1-21) for ZSTD. Choose 1 here for better compression speed.
+/// Default compression level (1-21) for ZSTD. Choose 1 here for better compression
+/// speed.
 const ZSTD_COMPRESSION_LEVEL: i32 = 1;
 
 impl Codec for ZSTDCodec {
@@ -226,7 +274,7 @@ impl Codec for ZSTDCodec {
 	}
 
 	fn compress(&mut self, input_buf: &[u8], output_buf: &mut Vec<u8>) -> Result<()> {
-		let mut encoder = zstd::Encoder::new(output_buf, ZSTD_COMPRESSION_LEVEL)?;
+		let mut encoder = zstd::Encoder::new(output_buf, self.level)?;
 		encoder.write_all(&input_buf[..])?;
 		match encoder.finish() {
 			Ok(_) => Ok(()),
@@ -319,6 +367,44 @@ mod tests {
 		test_codec(CodecType::Zstd);
 	}
 
+	#[test]
+	#[cfg_attr(miri, ignore)]
+	fn test_codec_level() {
+		// Compressible but not trivially so
+		let data = (0..100_000_u32)
+			.flat_map(|i| format!("{},{}\n", i, (i * 7919) % 1009).into_bytes())
+			.collect::<Vec<u8>>();
+		for &(codec_type, fast, small) in &[
+			(CodecType::Gzip, 1, 9),
+			(CodecType::Brotli, 0, 11),
+			(CodecType::Zstd, 1, 19),
+		] {
+			let mut sizes = Vec::new();
+			for &level in &[fast, small] {
+				let mut codec = create_codec_with_level(codec_type, Some(level))
+					.unwrap()
+					.unwrap();
+				let mut compressed = Vec::new();
+				codec.compress(&data, &mut compressed).unwrap();
+				let mut decompressed = Vec::new();
+				let len = create_codec(codec_type)
+					.unwrap()
+					.unwrap()
+					.decompress(&compressed, &mut decompressed)
+					.unwrap();
+				assert_eq!(len, data.len());
+				assert_eq!(decompressed, data);
+				sizes.push(compressed.len());
+			}
+			assert!(sizes[1] < sizes[0], "{}: {:?}", codec_type, sizes);
+		}
+
+		assert!(create_codec_with_level(CodecType::Gzip, Some(10)).is_err());
+		assert!(create_codec_with_level(CodecType::Zstd, Some(0)).is_err());
+		assert!(create_codec_with_level(CodecType::Snappy, Some(1)).is_ok());
+		assert!(create_codec_with_level(CodecType::Snappy, None).is_ok());
+	}
+
 	// Benches
 
 	// 10k rows written in page v2 with type:
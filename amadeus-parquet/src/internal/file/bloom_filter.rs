@@ -0,0 +1,169 @@
+//! Split block Bloom filters, as described in
+//! https://github.com/apache/parquet-format/blob/master/BloomFilter.md
+
+use std::{
+	convert::TryFrom, hash::Hasher, io::{Cursor, Write}
+};
+use thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol, TOutputProtocol};
+use twox_hash::XxHash64;
+
+use crate::internal::{
+	errors::{ParquetError, Result}, format::{
+		BloomFilterAlgorithm, BloomFilterCompression, BloomFilterHash, BloomFilterHeader, SplitBlockAlgorithm, Uncompressed, XxHash
+	}
+};
+
+const SALT: [u32; 8] = [
+	0x47b6_137b,
+	0x4497_4d91,
+	0x8824_ad5b,
+	0xa2b7_289d,
+	0x7054_95c7,
+	0x2df1_424b,
+	0x9efc_4947,
+	0x5c6b_fb31,
+];
+
+/// The largest a serialized `BloomFilterHeader` can reasonably be, used to bound
+/// the initial read of a Bloom filter.
+pub(crate) const MAX_HEADER_SIZE: usize = 64;
+
+type Block = [u32; 8];
+
+/// A split block Bloom filter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sbbf(Vec<Block>);
+
+impl Sbbf {
+	/// Create an empty filter of `num_bytes`, which is rounded up to a whole
+	/// number of 32-byte blocks.
+	pub fn new(num_bytes: usize) -> Self {
+		Self(vec![[0; 8]; ((num_bytes + 31) / 32).max(1)])
+	}
+
+	/// Parse the header at the start of `buf`, returning it along with its length
+	/// in bytes. The bitset follows immediately after.
+	pub fn read_header(buf: &[u8]) -> Result<(BloomFilterHeader, usize)> {
+		let mut cursor = Cursor::new(buf);
+		let header = {
+			let mut prot = TCompactInputProtocol::new(&mut cursor);
+			BloomFilterHeader::read_from_in_protocol(&mut prot).map_err(|e| {
+				ParquetError::General(format!("Could not parse Bloom filter header: {}", e))
+			})?
+		};
+		match header {
+			BloomFilterHeader {
+				algorithm: BloomFilterAlgorithm::BLOCK(_),
+				hash: BloomFilterHash::XXHASH(_),
+				compression: BloomFilterCompression::UNCOMPRESSED(_),
+				num_bytes,
+			} if num_bytes > 0 && num_bytes % 32 == 0 => (),
+			_ => return Err(general_err!("Unsupported Bloom filter: {:?}", header)),
+		}
+		Ok((header, usize::try_from(cursor.position()).unwrap()))
+	}
+
+	/// Create a filter from its bitset.
+	pub fn from_bitset(bitset: &[u8]) -> Result<Self> {
+		if bitset.is_empty() || bitset.len() % 32 != 0 {
+			return Err(general_err!(
+				"Invalid Bloom filter bitset length {}",
+				bitset.len()
+			));
+		}
+		Ok(Self(
+			bitset
+				.chunks_exact(32)
+				.map(|block| {
+					let mut words = [0; 8];
+					for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+						*word = u32::from_le_bytes(<[u8; 4]>::try_from(bytes).unwrap());
+					}
+					words
+				})
+				.collect(),
+		))
+	}
+
+	/// Serialize the filter, header followed by bitset, to `out`.
+	pub fn write<W: Write>(&self, out: &mut W) -> Result<()> {
+		let header = BloomFilterHeader::new(
+			i32::try_from(self.0.len() * 32).unwrap(),
+			BloomFilterAlgorithm::BLOCK(SplitBlockAlgorithm::new()),
+			BloomFilterHash::XXHASH(XxHash::new()),
+			BloomFilterCompression::UNCOMPRESSED(Uncompressed::new()),
+		);
+		{
+			let mut prot = TCompactOutputProtocol::new(&mut *out);
+			header.write_to_out_protocol(&mut prot)?;
+			prot.flush()?;
+		}
+		for word in self.0.iter().flatten() {
+			out.write_all(&word.to_le_bytes())?;
+		}
+		Ok(())
+	}
+
+	/// Hash the plain encoding of a value, as required for [`Sbbf::insert`] and
+	/// [`Sbbf::check`].
+	pub fn hash(plain: &[u8]) -> u64 {
+		let mut hasher = XxHash64::with_seed(0);
+		hasher.write(plain);
+		hasher.finish()
+	}
+
+	pub fn insert(&mut self, hash: u64) {
+		let (block, mask) = self.locate(hash);
+		for (word, mask) in self.0[block].iter_mut().zip(&mask) {
+			*word |= mask;
+		}
+	}
+
+	/// Returns `false` if the value with this hash is definitely absent.
+	pub fn check(&self, hash: u64) -> bool {
+		let (block, mask) = self.locate(hash);
+		self.0[block]
+			.iter()
+			.zip(&mask)
+			.all(|(word, mask)| word & mask != 0)
+	}
+
+	fn locate(&self, hash: u64) -> (usize, Block) {
+		let block = usize::try_from(((hash >> 32) * self.0.len() as u64) >> 32).unwrap();
+		let key = hash as u32;
+		let mut mask = [0; 8];
+		for (mask, salt) in mask.iter_mut().zip(&SALT) {
+			*mask = 1 << (key.wrapping_mul(*salt) >> 27);
+		}
+		(block, mask)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_sbbf_roundtrip() {
+		let mut filter = Sbbf::new(1024);
+		for i in 0..100_i64 {
+			filter.insert(Sbbf::hash(&i.to_le_bytes()));
+		}
+		let mut buf = Vec::new();
+		filter.write(&mut buf).unwrap();
+
+		let (header, header_len) = Sbbf::read_header(&buf).unwrap();
+		assert_eq!(header.num_bytes, 1024);
+		assert_eq!(buf.len(), header_len + 1024);
+		let read = Sbbf::from_bitset(&buf[header_len..]).unwrap();
+		assert_eq!(read, filter);
+
+		for i in 0..100_i64 {
+			assert!(read.check(Sbbf::hash(&i.to_le_bytes())));
+		}
+		let false_positives = (100..10_100_i64)
+			.filter(|i| read.check(Sbbf::hash(&i.to_le_bytes())))
+			.count();
+		assert!(false_positives < 100, "{}", false_positives);
+	}
+}
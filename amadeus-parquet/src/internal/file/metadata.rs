@@ -328,6 +328,7 @@ pub struct ColumnChunkMetaData {
 	index_page_offset: Option<i64>,
 	dictionary_page_offset: Option<i64>,
 	statistics: Option<Statistics>,
+	bloom_filter_offset: Option<i64>,
 }
 
 /// Represents common operations for a column chunk.
@@ -426,6 +427,11 @@ impl ColumnChunkMetaData {
 		self.statistics.as_ref()
 	}
 
+	/// Returns the offset for the Bloom filter, if any.
+	pub fn bloom_filter_offset(&self) -> Option<i64> {
+		self.bloom_filter_offset
+	}
+
 	/// Method to convert from Thrift.
 	pub fn from_thrift(column_descr: ColumnDescPtr, cc: ColumnChunk) -> Result<Self> {
 		if cc.meta_data.is_none() {
@@ -449,6 +455,7 @@ impl ColumnChunkMetaData {
 		let index_page_offset = col_metadata.index_page_offset;
 		let dictionary_page_offset = col_metadata.dictionary_page_offset;
 		let statistics = statistics::from_thrift(column_type, col_metadata.statistics);
+		let bloom_filter_offset = col_metadata.bloom_filter_offset;
 		let result = ColumnChunkMetaData {
 			column_type,
 			column_path,
@@ -464,6 +471,7 @@ impl ColumnChunkMetaData {
 			index_page_offset,
 			dictionary_page_offset,
 			statistics,
+			bloom_filter_offset,
 		};
 		Ok(result)
 	}
@@ -484,7 +492,7 @@ impl ColumnChunkMetaData {
 			dictionary_page_offset: self.dictionary_page_offset,
 			statistics: statistics::to_thrift(self.statistics.as_ref()),
 			encoding_stats: None,
-			bloom_filter_offset: None,
+			bloom_filter_offset: self.bloom_filter_offset,
 		};
 
 		ColumnChunk {
@@ -515,6 +523,7 @@ pub struct ColumnChunkMetaDataBuilder {
 	index_page_offset: Option<i64>,
 	dictionary_page_offset: Option<i64>,
 	statistics: Option<Statistics>,
+	bloom_filter_offset: Option<i64>,
 }
 
 impl ColumnChunkMetaDataBuilder {
@@ -533,6 +542,7 @@ impl ColumnChunkMetaDataBuilder {
 			index_page_offset: None,
 			dictionary_page_offset: None,
 			statistics: None,
+			bloom_filter_offset: None,
 		}
 	}
 
@@ -602,6 +612,12 @@ impl ColumnChunkMetaDataBuilder {
 		self
 	}
 
+	/// Sets optional Bloom filter offset in bytes.
+	pub fn set_bloom_filter_offset(mut self, value: Option<i64>) -> Self {
+		self.bloom_filter_offset = value;
+		self
+	}
+
 	/// Builds column chunk metadata.
 	pub fn build(self) -> Result<ColumnChunkMetaData> {
 		Ok(ColumnChunkMetaData {
@@ -619,6 +635,7 @@ impl ColumnChunkMetaDataBuilder {
 			index_page_offset: self.index_page_offset,
 			dictionary_page_offset: self.dictionary_page_offset,
 			statistics: self.statistics,
+			bloom_filter_offset: self.bloom_filter_offset,
 		})
 	}
 }
@@ -681,6 +698,7 @@ mod tests {
 			.set_total_uncompressed_size(3000)
 			.set_data_page_offset(4000)
 			.set_dictionary_page_offset(Some(5000))
+			.set_bloom_filter_offset(Some(6000))
 			.build()
 			.unwrap();
 
@@ -186,6 +186,15 @@ impl WriterProperties {
 			.unwrap_or(DEFAULT_COMPRESSION)
 	}
 
+	/// Returns compression level for a column, or `None` to use the codec's
+	/// default.
+	pub fn compression_level(&self, col: &ColumnPath) -> Option<u32> {
+		self.column_properties
+			.get(col)
+			.and_then(|c| c.compression_level())
+			.or_else(|| self.default_column_properties.compression_level())
+	}
+
 	/// Returns `true` if dictionary encoding is enabled for a column.
 	pub fn dictionary_enabled(&self, col: &ColumnPath) -> bool {
 		self.column_properties
@@ -317,6 +326,15 @@ impl WriterPropertiesBuilder {
 		self
 	}
 
+	/// Sets compression level for any column, trading off speed against size. It
+	/// must be valid for the column's codec, e.g. 0-9 for Gzip or 1-21 for Zstd,
+	/// otherwise starting to write the column returns an error. Codecs without
+	/// levels, such as Snappy, ignore it.
+	pub fn set_compression_level(mut self, value: u32) -> Self {
+		self.default_column_properties.set_compression_level(value);
+		self
+	}
+
 	/// Sets flag to enable/disable dictionary encoding for any column.
 	///
 	/// Use this method to set dictionary encoding, instead of explicitly specifying
@@ -373,6 +391,13 @@ impl WriterPropertiesBuilder {
 		self
 	}
 
+	/// Sets compression level for a column.
+	/// Takes precedence over globally defined settings.
+	pub fn set_column_compression_level(mut self, col: ColumnPath, value: u32) -> Self {
+		self.get_mut_props(col).set_compression_level(value);
+		self
+	}
+
 	/// Sets flag to enable/disable dictionary encoding for a column.
 	/// Takes precedence over globally defined settings.
 	pub fn set_column_dictionary_enabled(mut self, col: ColumnPath, value: bool) -> Self {
@@ -403,6 +428,7 @@ impl WriterPropertiesBuilder {
 struct ColumnProperties {
 	encoding: Option<Encoding>,
 	codec: Option<Compression>,
+	compression_level: Option<u32>,
 	dictionary_enabled: Option<bool>,
 	statistics_enabled: Option<bool>,
 	max_statistics_size: Option<usize>,
@@ -414,6 +440,7 @@ impl ColumnProperties {
 		Self {
 			encoding: None,
 			codec: None,
+			compression_level: None,
 			dictionary_enabled: None,
 			statistics_enabled: None,
 			max_statistics_size: None,
@@ -441,6 +468,11 @@ impl ColumnProperties {
 		self.codec = Some(value);
 	}
 
+	/// Sets compression level for this column.
+	fn set_compression_level(&mut self, value: u32) {
+		self.compression_level = Some(value);
+	}
+
 	/// Sets whether or not dictionary encoding is enabled for this column.
 	fn set_dictionary_enabled(&mut self, enabled: bool) {
 		self.dictionary_enabled = Some(enabled);
@@ -466,6 +498,11 @@ impl ColumnProperties {
 		self.codec
 	}
 
+	/// Returns optional compression level for this column.
+	fn compression_level(&self) -> Option<u32> {
+		self.compression_level
+	}
+
 	/// Returns `Some(true)` if dictionary encoding is enabled for this column, if
 	/// disabled then returns `Some(false)`. If result is `None`, then no setting has
 	/// been provided.
@@ -591,12 +628,14 @@ mod tests {
 			// global column settings
 			.set_encoding(Encoding::DeltaBinaryPacked)
 			.set_compression(Compression::Gzip)
+			.set_compression_level(9)
 			.set_dictionary_enabled(false)
 			.set_statistics_enabled(false)
 			.set_max_statistics_size(50)
 			// specific column settings
 			.set_column_encoding(ColumnPath::from("col"), Encoding::Rle)
-			.set_column_compression(ColumnPath::from("col"), Compression::Snappy)
+			.set_column_compression(ColumnPath::from("col"), Compression::Zstd)
+			.set_column_compression_level(ColumnPath::from("col"), 3)
 			.set_column_dictionary_enabled(ColumnPath::from("col"), true)
 			.set_column_statistics_enabled(ColumnPath::from("col"), true)
 			.set_column_max_statistics_size(ColumnPath::from("col"), 123)
@@ -614,6 +653,7 @@ mod tests {
 			Some(Encoding::DeltaBinaryPacked)
 		);
 		assert_eq!(props.compression(&ColumnPath::from("a")), Compression::Gzip);
+		assert_eq!(props.compression_level(&ColumnPath::from("a")), Some(9));
 		assert_eq!(props.dictionary_enabled(&ColumnPath::from("a")), false);
 		assert_eq!(props.statistics_enabled(&ColumnPath::from("a")), false);
 		assert_eq!(props.max_statistics_size(&ColumnPath::from("a")), 50);
@@ -624,8 +664,9 @@ mod tests {
 		);
 		assert_eq!(
 			props.compression(&ColumnPath::from("col")),
-			Compression::Snappy
+			Compression::Zstd
 		);
+		assert_eq!(props.compression_level(&ColumnPath::from("col")), Some(3));
 		assert_eq!(props.dictionary_enabled(&ColumnPath::from("col")), true);
 		assert_eq!(props.statistics_enabled(&ColumnPath::from("col")), true);
 		assert_eq!(props.max_statistics_size(&ColumnPath::from("col")), 123);
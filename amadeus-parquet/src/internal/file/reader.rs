@@ -116,6 +116,9 @@ pub trait RowGroupReader {
 	/// Get the total number of column chunks in this row group.
 	fn num_columns(&self) -> usize;
 
+	/// Get the number of leading rows skipped by the value readers.
+	fn num_skipped_rows(&self) -> u64;
+
 	/// Get page reader for the `i`th column chunk.
 	fn get_column_page_reader(&self, i: usize) -> Result<Box<dyn PageReader>>;
 
@@ -363,26 +366,28 @@ impl<'a> TryFrom<&'a str> for SerializedFileReader<File> {
 pub struct SerializedRowGroupReader<R: ParquetReader> {
 	buf: Rc<RefCell<BufReader<R>>>,
 	metadata: RowGroupMetaDataPtr,
+	skip_rows: u64,
 }
 
 impl<R: 'static + ParquetReader> SerializedRowGroupReader<R> {
 	/// Creates new row group reader from a file and row group metadata.
 	fn new(buf: Rc<RefCell<BufReader<R>>>, metadata: RowGroupMetaDataPtr) -> Self {
-		Self { buf, metadata }
-	}
-}
-
-impl<R: 'static + ParquetReader> RowGroupReader for SerializedRowGroupReader<R> {
-	fn metadata(&self) -> RowGroupMetaDataPtr {
-		self.metadata.clone()
+		Self {
+			buf,
+			metadata,
+			skip_rows: 0,
+		}
 	}
 
-	fn num_columns(&self) -> usize {
-		self.metadata.num_columns()
+	/// Skip the first `rows` rows when reading values or rows, such that the data
+	/// pages holding only them aren't decompressed or decoded. The rest of the
+	/// skipped rows are decoded and discarded. Page readers aren't affected.
+	pub fn skip_rows(mut self, rows: u64) -> Self {
+		self.skip_rows = rows;
+		self
 	}
 
-	// TODO: fix PARQUET-816
-	fn get_column_page_reader(&self, i: usize) -> Result<Box<dyn PageReader>> {
+	fn page_reader(&self, i: usize) -> Result<SerializedPageReader<FileSource<R>>> {
 		let col = self.metadata.column(i);
 		let mut col_start = col.data_page_offset();
 		if col.has_dictionary_page() {
@@ -390,20 +395,47 @@ impl<R: 'static + ParquetReader> RowGroupReader for SerializedRowGroupReader<R>
 		}
 		let col_length = col.compressed_size();
 		let file_chunk = FileSource::new(self.buf.clone(), col_start as u64, col_length as u64);
-		let page_reader = SerializedPageReader::new(
+		SerializedPageReader::new(
 			file_chunk,
 			col.num_values(),
 			col.compression(),
 			col.column_descr().physical_type(),
-		)?;
-		Ok(Box::new(page_reader))
+		)
+	}
+}
+
+impl<R: 'static + ParquetReader> RowGroupReader for SerializedRowGroupReader<R> {
+	fn metadata(&self) -> RowGroupMetaDataPtr {
+		self.metadata.clone()
+	}
+
+	fn num_columns(&self) -> usize {
+		self.metadata.num_columns()
+	}
+
+	fn num_skipped_rows(&self) -> u64 {
+		self.skip_rows
+	}
+
+	// TODO: fix PARQUET-816
+	fn get_column_page_reader(&self, i: usize) -> Result<Box<dyn PageReader>> {
+		Ok(Box::new(self.page_reader(i)?))
 	}
 
 	fn get_column_reader(&self, i: usize) -> Result<ColumnReader> {
 		let schema_descr = self.metadata.schema_descr();
 		let col_descr = schema_descr.column(i);
-		let col_page_reader = self.get_column_page_reader(i)?;
-		let col_reader = match col_descr.physical_type() {
+		let mut page_reader = self.page_reader(i)?;
+		// The rows of the data pages that weren't skipped are skipped by the reader
+		// of its triplets
+		let records_to_skip = if self.skip_rows != 0 {
+			self.skip_rows
+				- page_reader.skip_pages(self.skip_rows, col_descr.max_rep_level() > 0)?
+		} else {
+			0
+		};
+		let col_page_reader: Box<dyn PageReader> = Box::new(page_reader);
+		let mut col_reader = match col_descr.physical_type() {
 			Type::Boolean => {
 				ColumnReader::BoolColumnReader(ColumnReaderImpl::new(col_descr, col_page_reader))
 			}
@@ -430,6 +462,7 @@ impl<R: 'static + ParquetReader> RowGroupReader for SerializedRowGroupReader<R>
 				ColumnReaderImpl::new(col_descr, col_page_reader),
 			),
 		};
+		col_reader.skip_records(records_to_skip);
 		Ok(col_reader)
 	}
 
@@ -461,6 +494,11 @@ pub struct SerializedPageReader<T: Read> {
 
 	// Column chunk type.
 	physical_type: Type,
+
+	// The dictionary page, and the header of the first data page, read while
+	// skipping pages.
+	pending_page: Option<Page>,
+	pending_header: Option<PageHeader>,
 }
 
 impl<T: Read> SerializedPageReader<T> {
@@ -475,10 +513,63 @@ impl<T: Read> SerializedPageReader<T> {
 			seen_num_values: 0,
 			decompressor,
 			physical_type,
+			pending_page: None,
+			pending_header: None,
 		};
 		Ok(result)
 	}
 
+	/// Skips the leading data pages that only hold rows before the `rows`th,
+	/// without decompressing or decoding them, returning the number of rows they
+	/// held. This must be called before any page is read. Only v2 data pages
+	/// record their number of rows, so the v1 data pages of a column with
+	/// repetition levels are never skipped.
+	pub fn skip_pages(&mut self, rows: u64, repeated: bool) -> Result<u64> {
+		assert!(self.pending_page.is_none() && self.pending_header.is_none());
+		let mut skipped = 0;
+		while self.seen_num_values < self.total_num_values {
+			let page_header = self.read_page_header()?;
+			let (num_values, num_rows) = match (
+				page_header.type_,
+				&page_header.data_page_header,
+				&page_header.data_page_header_v2,
+			) {
+				(PageType::DataPage, Some(header), _) => (
+					header.num_values,
+					Some(header.num_values).filter(|_| !repeated),
+				),
+				(PageType::DataPageV2, _, Some(header)) => {
+					(header.num_values, Some(header.num_rows))
+				}
+				(PageType::DictionaryPage, _, _) => {
+					self.pending_header = Some(page_header);
+					self.pending_page = self.get_next_page()?;
+					continue;
+				}
+				(PageType::DataPage, _, _) | (PageType::DataPageV2, _, _) => (0, None),
+				// Pages of other types hold no rows and are skipped anyway
+				_ => (0, Some(0)),
+			};
+			match num_rows.and_then(|num_rows| u64::try_from(num_rows).ok()) {
+				Some(num_rows) if skipped + num_rows <= rows => {
+					let len = u64::try_from(page_header.compressed_page_size).map_err(|_| {
+						general_err!("Invalid page size {}", page_header.compressed_page_size)
+					})?;
+					if io::copy(&mut self.buf.by_ref().take(len), &mut io::sink())? < len {
+						return Err(eof_err!("Unexpected end of column chunk"));
+					}
+					self.seen_num_values += i64::from(num_values);
+					skipped += num_rows;
+				}
+				_ => {
+					self.pending_header = Some(page_header);
+					break;
+				}
+			}
+		}
+		Ok(skipped)
+	}
+
 	/// Reads Page header from Thrift.
 	fn read_page_header(&mut self) -> Result<PageHeader> {
 		let mut prot = TCompactInputProtocol::new(&mut self.buf);
@@ -489,8 +580,14 @@ impl<T: Read> SerializedPageReader<T> {
 
 impl<T: Read> PageReader for SerializedPageReader<T> {
 	fn get_next_page(&mut self) -> Result<Option<Page>> {
+		if let Some(page) = self.pending_page.take() {
+			return Ok(Some(page));
+		}
 		while self.seen_num_values < self.total_num_values {
-			let page_header = self.read_page_header()?;
+			let page_header = match self.pending_header.take() {
+				Some(page_header) => page_header,
+				None => self.read_page_header()?,
+			};
 
 			// When processing data page v2, depending on enabled compression for the
 			// page, we should account for uncompressed data ('offset') of
@@ -671,7 +768,11 @@ mod tests {
 
 	use super::*;
 	use crate::internal::{
-		basic::SortOrder, column::reader::{get_typed_column_reader, ColumnReader}, data_type::*, file::reader::{FileReader, RowGroupReader, SerializedFileReader}, format::TypeDefinedOrder, schema::types::{ColumnPath, Type as SchemaType}, util::test_common::{get_temp_file, get_test_file, get_test_path}
+		basic::{self, Repetition, SortOrder}, column::{
+			reader::{get_typed_column_reader, ColumnReader}, writer::ColumnWriter
+		}, data_type::*, file::{
+			properties::WriterProperties, reader::{FileReader, RowGroupReader, SerializedFileReader}, writer::{FileWriter, SerializedFileWriter}
+		}, format::TypeDefinedOrder, schema::types::{ColumnPath, Type as SchemaType}, util::test_common::{get_temp_file, get_test_file, get_test_path}
 	};
 
 	#[test]
@@ -1065,6 +1166,71 @@ mod tests {
 		assert_eq!(page_count, 2);
 	}
 
+	#[test]
+	fn test_row_group_reader_skip_rows() {
+		let file = get_temp_file("test_row_group_reader_skip_rows", &[]);
+		let schema = Rc::new(
+			SchemaType::group_type_builder("schema")
+				.with_fields(&mut vec![Rc::new(
+					SchemaType::primitive_type_builder("col1", Type::Int64)
+						.with_repetition(Repetition::Required)
+						.build()
+						.unwrap(),
+				)])
+				.build()
+				.unwrap(),
+		);
+		// Pages of 10 values each
+		let props = Rc::new(
+			WriterProperties::builder()
+				.set_write_batch_size(10)
+				.set_data_pagesize_limit(1)
+				.build(),
+		);
+		let mut file_writer =
+			SerializedFileWriter::new(file.try_clone().unwrap(), schema, props).unwrap();
+		let mut row_group_writer = file_writer.next_row_group().unwrap();
+		let mut writer = row_group_writer.next_column().unwrap().unwrap();
+		match writer {
+			ColumnWriter::Int64ColumnWriter(ref mut typed) => {
+				let _ = typed
+					.write_batch(&(0..100).collect::<Vec<i64>>(), None, None)
+					.unwrap();
+			}
+			_ => unreachable!(),
+		}
+		row_group_writer.close_column(writer).unwrap();
+		file_writer.close_row_group(row_group_writer).unwrap();
+		file_writer.close().unwrap();
+
+		let reader = SerializedFileReader::new(file).unwrap();
+
+		// Only the whole pages before the 25th row are skipped, leaving the
+		// dictionary page and then the page holding it
+		let mut page_reader = reader.get_row_group(0).unwrap().page_reader(0).unwrap();
+		assert_eq!(page_reader.skip_pages(25, false).unwrap(), 20);
+		let page = page_reader.get_next_page().unwrap().unwrap();
+		assert_eq!(page.page_type(), basic::PageType::DictionaryPage);
+		let page = page_reader.get_next_page().unwrap().unwrap();
+		assert_eq!(page.page_type(), basic::PageType::DataPage);
+		assert_eq!(page.num_values(), 10);
+		let mut pages = 2;
+		while page_reader.get_next_page().unwrap().is_some() {
+			pages += 1;
+		}
+		assert_eq!(pages, 9);
+
+		let rows = reader
+			.get_row_group(0)
+			.unwrap()
+			.skip_rows(25)
+			.get_row_iter::<Group>(None)
+			.unwrap()
+			.map(|row| row.unwrap()[0].as_i64().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(rows, (25..100).collect::<Vec<_>>());
+	}
+
 	// #[test]
 	// fn test_page_iterator() {
 	//     let file = get_test_file("alltypes_plain.parquet");
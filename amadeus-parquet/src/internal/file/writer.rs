@@ -28,7 +28,7 @@ use thrift::protocol::{TCompactOutputProtocol, TOutputProtocol};
 use crate::internal::{
 	basic::PageType, column::{
 		page::{CompressedPage, Page, PageWriteSpec, PageWriter}, writer::{get_column_writer, ColumnWriter}
-	}, errors::{ParquetError, Result}, file::{
+	}, compression::check_compression_level, errors::{ParquetError, Result}, file::{
 		metadata::*, properties::WriterPropertiesPtr, statistics::to_thrift as statistics_to_thrift, FOOTER_SIZE, PARQUET_MAGIC
 	}, format as parquet, schema::types::{self, SchemaDescPtr, SchemaDescriptor, TypePtr}, util::io::{FileSink, Position}
 };
@@ -326,13 +326,14 @@ impl RowGroupWriter for SerializedRowGroupWriter {
 		if self.column_index >= self.descr.num_columns() {
 			return Ok(None);
 		}
+		let descr = self.descr.column(self.column_index);
+		check_compression_level(
+			self.props.compression(descr.path()),
+			self.props.compression_level(descr.path()),
+		)?;
 		let sink = FileSink::new(&self.file);
 		let page_writer = Box::new(SerializedPageWriter::new(sink));
-		let column_writer = get_column_writer(
-			self.descr.column(self.column_index),
-			self.props.clone(),
-			page_writer,
-		);
+		let column_writer = get_column_writer(descr, self.props.clone(), page_writer);
 		self.column_index += 1;
 		self.previous_writer_closed = false;
 
@@ -503,14 +504,14 @@ impl<T: Write + Position> PageWriter for SerializedPageWriter<T> {
 mod tests {
 	use super::*;
 
-	use std::io::Cursor;
+	use std::{collections::HashMap, io::Cursor};
 
 	use crate::internal::{
-		basic::{Compression, Encoding, Repetition, Type}, column::page::PageReader, compression::{create_codec, Codec}, file::{
-			properties::WriterProperties, reader::{FileReader, RowGroupReader, SerializedFileReader, SerializedPageReader}, statistics::{from_thrift, to_thrift, Statistics}
-		}, util::{memory::ByteBufferPtr, test_common::get_temp_file}
+		basic::{Compression, Encoding, LogicalType, Repetition, Type}, column::page::PageReader, compression::{create_codec, Codec}, data_type::ByteArray, file::{
+			properties::{WriterProperties, WriterPropertiesPtr}, reader::{FileReader, RowGroupReader, SerializedFileReader, SerializedPageReader}, statistics::{from_thrift, to_thrift, Statistics}
+		}, schema::types::ColumnPath, util::{memory::ByteBufferPtr, test_common::get_temp_file}
 	};
-	use amadeus_types::Group;
+	use amadeus_types::{Group, Uuid, Value};
 
 	#[test]
 	fn test_file_writer_error_after_close() {
@@ -691,6 +692,124 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_file_writer_compression_level() {
+		let file = get_temp_file("test_file_writer_compression_level", &[]);
+		let props = WriterProperties::builder()
+			.set_compression(Compression::Gzip)
+			.set_compression_level(9)
+			.build();
+		test_file_roundtrip_with_props(file, Rc::new(props), vec![vec![1, 2, 3], vec![4; 1000]]);
+
+		let file = get_temp_file("test_file_writer_column_compression_level", &[]);
+		let props = WriterProperties::builder()
+			.set_compression(Compression::Snappy)
+			.set_compression_level(9)
+			.set_column_compression(ColumnPath::from("col1"), Compression::Zstd)
+			.set_column_compression_level(ColumnPath::from("col1"), 19)
+			.build();
+		test_file_roundtrip_with_props(file, Rc::new(props), vec![vec![1, 2, 3], vec![4; 1000]]);
+	}
+
+	#[test]
+	fn test_row_group_writer_invalid_compression_level() {
+		let file = get_temp_file("test_row_group_writer_invalid_compression_level", &[]);
+		let schema = Rc::new(
+			types::Type::group_type_builder("schema")
+				.with_fields(&mut vec![Rc::new(
+					types::Type::primitive_type_builder("col1", Type::Int32)
+						.with_repetition(Repetition::Required)
+						.build()
+						.unwrap(),
+				)])
+				.build()
+				.unwrap(),
+		);
+		let props = WriterProperties::builder()
+			.set_compression(Compression::Gzip)
+			.set_compression_level(10)
+			.build();
+		let mut writer = SerializedFileWriter::new(file, schema, Rc::new(props)).unwrap();
+		let mut row_group_writer = writer.next_row_group().unwrap();
+		let res = row_group_writer.next_column();
+		assert!(res.is_err());
+		if let Err(err) = res {
+			assert_eq!(
+				err.to_string(),
+				"Parquet error: Compression level 10 is invalid for GZIP, expected 0-9"
+			);
+		}
+	}
+
+	#[test]
+	fn test_file_writer_uuid() {
+		let file = get_temp_file("test_file_writer_uuid", &[]);
+		let schema = Rc::new(
+			types::Type::group_type_builder("schema")
+				.with_fields(&mut vec![Rc::new(
+					types::Type::primitive_type_builder("id", Type::FixedLenByteArray)
+						.with_repetition(Repetition::Required)
+						.with_logical_type(LogicalType::Uuid)
+						.with_length(16)
+						.build()
+						.unwrap(),
+				)])
+				.build()
+				.unwrap(),
+		);
+		let uuids = [
+			"67e55044-10b1-426f-9247-bb680e5fe0c8",
+			"00000000-0000-0000-0000-000000000000",
+			"ffffffff-ffff-ffff-ffff-ffffffffffff",
+		]
+		.iter()
+		.map(|uuid| uuid.parse::<Uuid>().unwrap())
+		.collect::<Vec<_>>();
+		let data = (0..30).map(|i| uuids[i % 3]).collect::<Vec<_>>();
+
+		let props = Rc::new(WriterProperties::builder().build());
+		let mut file_writer =
+			SerializedFileWriter::new(file.try_clone().unwrap(), schema, props).unwrap();
+		let mut row_group_writer = file_writer.next_row_group().unwrap();
+		let mut col_writer = row_group_writer.next_column().unwrap().unwrap();
+		if let ColumnWriter::FixedLenByteArrayColumnWriter(ref mut typed) = col_writer {
+			let values = data
+				.iter()
+				.map(|uuid| ByteArray::from(uuid.as_bytes().to_vec()))
+				.collect::<Vec<_>>();
+			assert_eq!(typed.write_batch(&values, None, None).unwrap(), 30);
+		} else {
+			unreachable!();
+		}
+		row_group_writer.close_column(col_writer).unwrap();
+		file_writer.close_row_group(row_group_writer).unwrap();
+		file_writer.close().unwrap();
+
+		// Read back dynamically and as the type, and group by it
+		let reader = SerializedFileReader::new(file).unwrap();
+		let row_group_reader = reader.get_row_group(0).unwrap();
+		let res = row_group_reader
+			.get_row_iter::<Group>(None)
+			.unwrap()
+			.map(Result::unwrap)
+			.map(|row| row.into_fields().pop().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			res,
+			data.iter().copied().map(Value::Uuid).collect::<Vec<_>>()
+		);
+		let mut counts = HashMap::new();
+		for (uuid,) in row_group_reader
+			.get_row_iter::<(Uuid,)>(None)
+			.unwrap()
+			.map(Result::unwrap)
+		{
+			*counts.entry(uuid).or_insert(0) += 1;
+		}
+		assert_eq!(counts.len(), 3);
+		assert!(uuids.iter().all(|uuid| counts[uuid] == 10));
+	}
+
 	#[test]
 	fn test_page_writer_data_pages() {
 		let pages = vec![
@@ -889,6 +1008,11 @@ mod tests {
 	/// File write-read roundtrip.
 	/// `data` consists of arrays of values for each row group.
 	fn test_file_roundtrip(file: File, data: Vec<Vec<i32>>) {
+		test_file_roundtrip_with_props(file, Rc::new(WriterProperties::builder().build()), data)
+	}
+
+	/// File write-read roundtrip with writer properties `props`.
+	fn test_file_roundtrip_with_props(file: File, props: WriterPropertiesPtr, data: Vec<Vec<i32>>) {
 		let schema = Rc::new(
 			types::Type::group_type_builder("schema")
 				.with_fields(&mut vec![Rc::new(
@@ -900,7 +1024,6 @@ mod tests {
 				.build()
 				.unwrap(),
 		);
-		let props = Rc::new(WriterProperties::builder().build());
 		let mut file_writer =
 			SerializedFileWriter::new(file.try_clone().unwrap(), schema, props).unwrap();
 
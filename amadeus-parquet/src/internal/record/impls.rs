@@ -1,11 +1,11 @@
 use hashlink::LinkedHashMap;
 use std::{
-	any::type_name, collections::HashMap, convert::{TryFrom, TryInto}, fmt, hash::{BuildHasher, Hash}, marker::PhantomData, string::FromUtf8Error, sync::Arc
+	any::type_name, cell::Cell, collections::HashMap, convert::{TryFrom, TryInto}, fmt, hash::{BuildHasher, Hash}, marker::PhantomData, string::FromUtf8Error, sync::Arc
 };
 use sum::{Sum2, Sum3};
 
 use amadeus_types::{
-	Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Enum, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, Value, Webpage
+	Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Duration, Enum, GeoPoint, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, Uuid, Value, Webpage
 };
 
 use crate::internal::{
@@ -15,7 +15,7 @@ use crate::internal::{
 		display::{DisplayFmt, DisplaySchemaGroup}, predicates::{GroupPredicate, MapPredicate, ValuePredicate}, reader::{
 			BoolReader, BoxFixedLenByteArrayReader, BoxReader, ByteArrayReader, F32Reader, F64Reader, FixedLenByteArrayReader, GroupReader, I32Reader, I64Reader, I96Reader, KeyValueReader, MapReader, OptionReader, RepeatedReader, RootReader, TryIntoReader, TupleReader, ValueReader, VecU8Reader
 		}, schemas::{
-			BoolSchema, BoxSchema, BsonSchema, ByteArraySchema, DateSchema, DateTimeSchema, DecimalSchema, EnumSchema, F32Schema, F64Schema, FixedByteArraySchema, GroupSchema, I16Schema, I32Schema, I64Schema, I8Schema, JsonSchema, ListSchema, ListSchemaType, MapSchema, OptionSchema, RootSchema, StringSchema, TimeSchema, TupleSchema, U16Schema, U32Schema, U64Schema, U8Schema, ValueSchema, VecU8Schema
+			BoolSchema, BoxSchema, BsonSchema, ByteArraySchema, DateSchema, DateTimeSchema, DecimalSchema, EnumSchema, F32Schema, F64Schema, FixedByteArraySchema, GroupSchema, I16Schema, I32Schema, I64Schema, I8Schema, IntervalSchema, JsonSchema, ListSchema, ListSchemaType, MapSchema, OptionSchema, RootSchema, StringSchema, TimeSchema, TupleSchema, U16Schema, U32Schema, U64Schema, U8Schema, UuidSchema, ValueSchema, VecU8Schema
 		}, triplet::TypedTripletIter, types::{downcast, Downcast, Root}, ParquetData, Predicate, Reader, Schema
 	}, schema::types::{ColumnPath, Type}
 };
@@ -139,6 +139,71 @@ impl ParquetData for Enum {
 	}
 }
 
+impl ParquetData for Uuid {
+	type Schema = UuidSchema;
+	type Reader = impl Reader<Item = Self>;
+	type Predicate = Predicate;
+
+	fn parse(
+		schema: &Type, _predicate: Option<&Self::Predicate>, repetition: Option<Repetition>,
+	) -> Result<(String, Self::Schema)> {
+		Value::parse(schema, None, repetition).and_then(downcast)
+	}
+
+	fn reader(
+		_schema: &Self::Schema, path: &mut Vec<String>, def_level: i16, rep_level: i16,
+		paths: &mut HashMap<ColumnPath, ColumnReader>, batch_size: usize,
+	) -> Self::Reader {
+		MapReader(
+			<[u8; 16]>::reader(
+				&FixedByteArraySchema(PhantomData),
+				path,
+				def_level,
+				rep_level,
+				paths,
+				batch_size,
+			),
+			|x| Ok(Uuid::from(x)),
+		)
+	}
+}
+
+/// Corresponds to the [Interval logical type](https://github.com/apache/parquet-format/blob/master/LogicalTypes.md#interval),
+/// little-endian unsigned months, days and milliseconds.
+impl ParquetData for Duration {
+	type Schema = IntervalSchema;
+	type Reader = impl Reader<Item = Self>;
+	type Predicate = Predicate;
+
+	fn parse(
+		schema: &Type, _predicate: Option<&Self::Predicate>, repetition: Option<Repetition>,
+	) -> Result<(String, Self::Schema)> {
+		Value::parse(schema, None, repetition).and_then(downcast)
+	}
+
+	fn reader(
+		_schema: &Self::Schema, path: &mut Vec<String>, def_level: i16, rep_level: i16,
+		paths: &mut HashMap<ColumnPath, ColumnReader>, batch_size: usize,
+	) -> Self::Reader {
+		MapReader(
+			<[u8; 12]>::reader(
+				&FixedByteArraySchema(PhantomData),
+				path,
+				def_level,
+				rep_level,
+				paths,
+				batch_size,
+			),
+			|x: [u8; 12]| {
+				let part = |i: usize| {
+					i64::from(u32::from_le_bytes(x[i * 4..(i + 1) * 4].try_into().unwrap()))
+				};
+				Ok(Duration::new(part(0), part(1), part(2) * 1_000_000).unwrap())
+			},
+		)
+	}
+}
+
 // Implement ParquetData for common array lengths.
 macro_rules! array {
 	($($i:tt)*) => {$(
@@ -1293,7 +1358,37 @@ fn date_time_from_parquet(date_time: Sum3<Int96, i64, i64>) -> Result<DateTime>
 	}
 }
 
+thread_local! {
+	/// The timezone INT96 timestamps are wall-clock times in, for the readers
+	/// built on this thread. It's set around building them, as
+	/// [`ParquetData::reader`] takes no options.
+	static INT96_TIMEZONE: Cell<Timezone> = Cell::new(Timezone::UTC);
+}
+
+/// The timezone INT96 timestamps are read in, as set by [`with_int96_timezone`].
+pub(crate) fn int96_timezone() -> Timezone {
+	INT96_TIMEZONE.with(Cell::get)
+}
+
+/// Run `f`, with the readers it builds reading INT96 timestamps as wall-clock
+/// times in `timezone`.
+pub(crate) fn with_int96_timezone<T>(timezone: Timezone, f: impl FnOnce() -> T) -> T {
+	struct Reset(Timezone);
+	impl Drop for Reset {
+		fn drop(&mut self) {
+			INT96_TIMEZONE.with(|cell| cell.set(self.0));
+		}
+	}
+	let _reset = Reset(INT96_TIMEZONE.with(|cell| cell.replace(timezone)));
+	f()
+}
+
 /// Corresponds to the UTC [DateTime logical type](https://github.com/apache/parquet-format/blob/master/LogicalTypes.md#timestamp).
+///
+/// Legacy INT96 timestamps, a Julian day and nanoseconds of the day, are
+/// decoded as UTC by default. Impala and older versions of Hive write them in
+/// local time; [`Parquet::int96_timezone`](crate::Parquet::int96_timezone)
+/// reads them as such.
 impl ParquetData for DateTime {
 	type Schema = DateTimeSchema;
 	type Reader = impl Reader<Item = Self>;
@@ -1320,7 +1415,22 @@ impl ParquetData for DateTime {
 						),
 					}
 				},
-				|date_time: Int96| date_time_from_parquet(Sum3::A(date_time)),
+				{
+					let timezone = int96_timezone();
+					move |date_time: Int96| {
+						let date_time = date_time_from_parquet(Sum3::A(date_time))?;
+						if timezone == Timezone::UTC {
+							return Ok(date_time);
+						}
+						let date_time = date_time.without_timezone();
+						date_time.localize(timezone).ok_or_else(|| {
+							ParquetError::General(format!(
+								"Invalid DateTime {} in {}",
+								date_time, timezone
+							))
+						})
+					}
+				},
 			)),
 			DateTimeSchema::Millis => Sum3::B(MapReader(
 				i64::reader(&I64Schema, path, def_level, rep_level, paths, batch_size),
@@ -1583,6 +1693,19 @@ mod tests {
 			value.as_chrono().unwrap().timestamp_millis(),
 			-210866803200000
 		);
+
+		// 2020-01-01T12:34:56.789Z, as written by Spark
+		let value =
+			date_time_from_parquet(Sum3::A(Int96::new(2063896384, 10546, 2458850))).unwrap();
+		assert_eq!(value.as_chrono().unwrap().timestamp_millis(), 1577882096789);
+		assert_eq!(value.nanosecond(), 789_000_000);
+
+		// The same wall-clock time written by Impala in New York
+		let value = value
+			.without_timezone()
+			.localize(Timezone::from_name("America/New_York").unwrap())
+			.unwrap();
+		assert_eq!(value.as_chrono().unwrap().timestamp_millis(), 1577900096789);
 	}
 
 	#[test]
@@ -1881,6 +2004,16 @@ impl ParquetData for Value {
 							scale,
 						})
 					}
+					(PhysicalType::FixedLenByteArray, LogicalType::Uuid)
+						if schema.get_type_length() == 16 =>
+					{
+						ValueSchema::Uuid(UuidSchema)
+					}
+					(PhysicalType::FixedLenByteArray, LogicalType::Interval)
+						if schema.get_type_length() == 12 =>
+					{
+						ValueSchema::Duration(IntervalSchema)
+					}
 					(PhysicalType::ByteArray, LogicalType::Interval)
 					| (PhysicalType::FixedLenByteArray, LogicalType::Interval) => {
 						unimplemented!("Interval logical type not yet implemented")
@@ -2038,6 +2171,14 @@ impl ParquetData for Value {
 			ValueSchema::Enum(ref schema) => ValueReader::Enum(<Enum as ParquetData>::reader(
 				schema, path, def_level, rep_level, paths, batch_size,
 			)),
+			ValueSchema::Uuid(ref schema) => ValueReader::Uuid(<Uuid as ParquetData>::reader(
+				schema, path, def_level, rep_level, paths, batch_size,
+			)),
+			ValueSchema::Duration(ref schema) => {
+				ValueReader::Duration(<Duration as ParquetData>::reader(
+					schema, path, def_level, rep_level, paths, batch_size,
+				))
+			}
 			ValueSchema::List(ref schema) => {
 				ValueReader::List(Box::new(<List<Value> as ParquetData>::reader(
 					type_coerce(&**schema),
@@ -2071,6 +2212,7 @@ via_string!(
 	"" Webpage<'static>
 	"" Url
 	"" IpAddr
+	"" GeoPoint
 );
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -2095,6 +2237,11 @@ impl From<amadeus_types::ParseWebpageError> for ParquetError {
 		ParquetError::General(err.to_string())
 	}
 }
+impl From<amadeus_types::ParseGeoPointError> for ParquetError {
+	fn from(err: amadeus_types::ParseGeoPointError) -> Self {
+		ParquetError::General(err.to_string())
+	}
+}
 
 ////////////////////////////////////////////////////////////////////////////////
 
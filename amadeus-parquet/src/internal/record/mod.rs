@@ -58,7 +58,8 @@ use crate::internal::{
 };
 
 /// This is used by `#[derive(Data)]`
-pub use display::DisplaySchemaGroup;
+pub use display::{DisplayFmt, DisplaySchemaGroup};
+pub(crate) use impls::{int96_timezone, with_int96_timezone};
 pub use reader::RowIter;
 pub use schemas::RootSchema;
 
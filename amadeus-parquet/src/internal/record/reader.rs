@@ -30,14 +30,16 @@ use std::{
 };
 use sum::derive_sum;
 
-use super::{triplet::TypedTripletIter, types::Root, ParquetData, Reader};
+use super::{
+	int96_timezone, triplet::TypedTripletIter, types::Root, with_int96_timezone, ParquetData, Reader
+};
 use crate::internal::{
 	column::reader::ColumnReader, data_type::{
 		BoolType, ByteArrayType, DoubleType, FixedLenByteArrayType, FloatType, Int32Type, Int64Type, Int96, Int96Type
 	}, errors::{ParquetError, Result}, file::reader::{FileReader, RowGroupReader}, schema::types::ColumnPath
 };
 use amadeus_types::{
-	Bson, Data, Date, DateTime, Decimal, Enum, Group, Json, List, Time, Value, ValueRequired
+	Bson, Data, Date, DateTime, Decimal, Duration, Enum, Group, Json, List, Time, Timezone, Uuid, Value, ValueRequired
 };
 
 /// Default batch size for a reader
@@ -408,6 +410,8 @@ pub enum ValueReader {
 	String(<String as ParquetData>::Reader),
 	Json(<Json as ParquetData>::Reader),
 	Enum(<Enum as ParquetData>::Reader),
+	Uuid(<Uuid as ParquetData>::Reader),
+	Duration(<Duration as ParquetData>::Reader),
 	List(Box<<List<Value> as ParquetData>::Reader>),
 	Map(Box<<HashMap<Value, Value> as ParquetData>::Reader>),
 	Group(<Group as ParquetData>::Reader),
@@ -446,6 +450,10 @@ impl Reader for ValueReader {
 			}
 			ValueReader::Json(ref mut reader) => reader.read(def_level, rep_level).map(Value::Json),
 			ValueReader::Enum(ref mut reader) => reader.read(def_level, rep_level).map(Value::Enum),
+			ValueReader::Uuid(ref mut reader) => reader.read(def_level, rep_level).map(Value::Uuid),
+			ValueReader::Duration(ref mut reader) => {
+				reader.read(def_level, rep_level).map(Value::Duration)
+			}
 			ValueReader::List(ref mut reader) => reader.read(def_level, rep_level).map(Value::List),
 			ValueReader::Map(ref mut reader) => reader.read(def_level, rep_level).map(Value::Map),
 			ValueReader::Group(ref mut reader) => {
@@ -479,6 +487,8 @@ impl Reader for ValueReader {
 			ValueReader::String(ref mut reader) => reader.advance_columns(),
 			ValueReader::Json(ref mut reader) => reader.advance_columns(),
 			ValueReader::Enum(ref mut reader) => reader.advance_columns(),
+			ValueReader::Uuid(ref mut reader) => reader.advance_columns(),
+			ValueReader::Duration(ref mut reader) => reader.advance_columns(),
 			ValueReader::List(ref mut reader) => reader.advance_columns(),
 			ValueReader::Map(ref mut reader) => reader.advance_columns(),
 			ValueReader::Group(ref mut reader) => reader.advance_columns(),
@@ -508,6 +518,8 @@ impl Reader for ValueReader {
 			ValueReader::String(ref reader) => reader.has_next(),
 			ValueReader::Json(ref reader) => reader.has_next(),
 			ValueReader::Enum(ref reader) => reader.has_next(),
+			ValueReader::Uuid(ref reader) => reader.has_next(),
+			ValueReader::Duration(ref reader) => reader.has_next(),
 			ValueReader::List(ref reader) => reader.has_next(),
 			ValueReader::Map(ref reader) => reader.has_next(),
 			ValueReader::Group(ref reader) => reader.has_next(),
@@ -537,6 +549,8 @@ impl Reader for ValueReader {
 			ValueReader::String(ref reader) => reader.current_def_level(),
 			ValueReader::Json(ref reader) => reader.current_def_level(),
 			ValueReader::Enum(ref reader) => reader.current_def_level(),
+			ValueReader::Uuid(ref reader) => reader.current_def_level(),
+			ValueReader::Duration(ref reader) => reader.current_def_level(),
 			ValueReader::List(ref reader) => reader.current_def_level(),
 			ValueReader::Map(ref reader) => reader.current_def_level(),
 			ValueReader::Group(ref reader) => reader.current_def_level(),
@@ -566,6 +580,8 @@ impl Reader for ValueReader {
 			ValueReader::String(ref reader) => reader.current_rep_level(),
 			ValueReader::Json(ref reader) => reader.current_rep_level(),
 			ValueReader::Enum(ref reader) => reader.current_rep_level(),
+			ValueReader::Uuid(ref reader) => reader.current_rep_level(),
+			ValueReader::Duration(ref reader) => reader.current_rep_level(),
 			ValueReader::List(ref reader) => reader.current_rep_level(),
 			ValueReader::Map(ref reader) => reader.current_rep_level(),
 			ValueReader::Group(ref reader) => reader.current_rep_level(),
@@ -733,6 +749,8 @@ where
 	current_row_group: usize,
 	num_row_groups: usize,
 	row_iter: Option<ReaderIter<T>>,
+	/// The timezone INT96 timestamps are read in, as row groups are read lazily
+	int96_timezone: Timezone,
 }
 
 impl<R, T> RowIter<R, T>
@@ -756,6 +774,7 @@ where
 			current_row_group: 0,
 			num_row_groups,
 			row_iter,
+			int96_timezone: int96_timezone(),
 		}
 	}
 
@@ -786,6 +805,7 @@ where
 			current_row_group: 0,
 			num_row_groups: 0,
 			row_iter: Some(row_iter),
+			int96_timezone: int96_timezone(),
 		})
 	}
 
@@ -810,7 +830,11 @@ where
 		// Build reader for the message type, requires definition level 0
 		let mut path = Vec::new();
 		let reader = <Root<T>>::reader(&schema, &mut path, 0, 0, &mut paths, DEFAULT_BATCH_SIZE);
-		ReaderIter::new(reader, row_group_reader.metadata().num_rows() as u64)
+		let num_rows = row_group_reader.metadata().num_rows() as u64;
+		ReaderIter::new(
+			reader,
+			num_rows.saturating_sub(row_group_reader.num_skipped_rows()),
+		)
 	}
 
 	// /// Creates a iterator of [`Row`](crate::internal::record::api::Row)s from a
@@ -888,7 +912,10 @@ where
 				.get_row_group(self.current_row_group)
 				.expect("Row group is required to advance");
 
-			let mut row_iter = match Self::get_reader_iter(&self.schema, &row_group_reader) {
+			let row_iter = with_int96_timezone(self.int96_timezone, || {
+				Self::get_reader_iter(&self.schema, &row_group_reader)
+			});
+			let mut row_iter = match row_iter {
 				Err(err) => return Some(Err(err)),
 				Ok(row_iter) => row_iter,
 			};
@@ -267,6 +267,34 @@ impl Schema for BsonSchema {
 	}
 }
 
+#[derive(Default, Debug)]
+pub struct UuidSchema;
+impl Schema for UuidSchema {
+	fn fmt(
+		_self_: Option<&Self>, r: Option<Repetition>, name: Option<&str>, f: &mut fmt::Formatter,
+	) -> fmt::Result {
+		f.write_fmt(format_args!(
+			"{} fixed_len_byte_array(16) {} (UUID);",
+			r.unwrap(),
+			name.unwrap_or("<name>")
+		))
+	}
+}
+
+#[derive(Default, Debug)]
+pub struct IntervalSchema;
+impl Schema for IntervalSchema {
+	fn fmt(
+		_self_: Option<&Self>, r: Option<Repetition>, name: Option<&str>, f: &mut fmt::Formatter,
+	) -> fmt::Result {
+		f.write_fmt(format_args!(
+			"{} fixed_len_byte_array(12) {} (INTERVAL);",
+			r.unwrap(),
+			name.unwrap_or("<name>")
+		))
+	}
+}
+
 #[derive(Default, Debug)]
 pub struct StringSchema(pub(super) ByteArraySchema);
 impl Schema for StringSchema {
@@ -734,6 +762,8 @@ pub enum ValueSchema {
 	String(StringSchema),
 	Json(JsonSchema),
 	Enum(EnumSchema),
+	Uuid(UuidSchema),
+	Duration(IntervalSchema),
 	ByteArray(ByteArraySchema),
 	List(Box<ListSchema<ValueSchema>>),
 	Map(Box<MapSchema<ValueSchema, ValueSchema>>),
@@ -765,6 +795,8 @@ impl Schema for ValueSchema {
 				ValueSchema::String(schema) => Schema::fmt(Some(schema), r, name, f),
 				ValueSchema::Json(schema) => Schema::fmt(Some(schema), r, name, f),
 				ValueSchema::Enum(schema) => Schema::fmt(Some(schema), r, name, f),
+				ValueSchema::Uuid(schema) => Schema::fmt(Some(schema), r, name, f),
+				ValueSchema::Duration(schema) => Schema::fmt(Some(schema), r, name, f),
 				ValueSchema::ByteArray(schema) => Schema::fmt(Some(schema), r, name, f),
 				ValueSchema::List(schema) => Schema::fmt(Some(&**schema), r, name, f),
 				ValueSchema::Map(schema) => Schema::fmt(Some(&**schema), r, name, f),
@@ -777,6 +809,45 @@ impl Schema for ValueSchema {
 	}
 }
 impl ValueSchema {
+	/// The [`Schema`](amadeus_types::Schema) of the [`Value`](amadeus_types::Value)s
+	/// read with this schema.
+	pub fn to_schema(&self) -> amadeus_types::Schema {
+		use amadeus_types::Schema;
+		match self {
+			ValueSchema::Bool(_) => Schema::Bool,
+			ValueSchema::U8(_) => Schema::U8,
+			ValueSchema::I8(_) => Schema::I8,
+			ValueSchema::U16(_) => Schema::U16,
+			ValueSchema::I16(_) => Schema::I16,
+			ValueSchema::U32(_) => Schema::U32,
+			ValueSchema::I32(_) => Schema::I32,
+			ValueSchema::U64(_) => Schema::U64,
+			ValueSchema::I64(_) => Schema::I64,
+			ValueSchema::F32(_) => Schema::F32,
+			ValueSchema::F64(_) => Schema::F64,
+			ValueSchema::Date(_) => Schema::Date,
+			ValueSchema::Time(_) => Schema::Time,
+			ValueSchema::DateTime(_) => Schema::DateTime,
+			ValueSchema::Decimal(_) => Schema::Decimal,
+			ValueSchema::Bson(_) => Schema::Bson,
+			ValueSchema::String(_) => Schema::String,
+			ValueSchema::Json(_) => Schema::Json,
+			ValueSchema::Enum(_) => Schema::Enum,
+			ValueSchema::Uuid(_) => Schema::String,
+			ValueSchema::Duration(_) => Schema::Duration,
+			ValueSchema::ByteArray(_) => Schema::List(Box::new(Schema::U8)),
+			ValueSchema::List(schema) => Schema::List(Box::new(schema.0.to_schema())),
+			ValueSchema::Map(schema) => {
+				Schema::Map(Box::new((schema.0.to_schema(), schema.1.to_schema())))
+			}
+			ValueSchema::Group(schema) => Schema::Group(
+				schema.0.iter().map(ValueSchema::to_schema).collect(),
+				Some(std::sync::Arc::new(schema.1.clone())),
+			),
+			ValueSchema::Option(schema) => Schema::Option(Box::new(schema.0.to_schema())),
+		}
+	}
+
 	pub fn is_bool(&self) -> bool {
 		if let ValueSchema::Bool(_) = self {
 			true
@@ -1377,6 +1448,58 @@ impl ValueSchema {
 		}
 	}
 
+	pub fn is_uuid(&self) -> bool {
+		matches!(self, ValueSchema::Uuid(_))
+	}
+
+	pub fn as_uuid(&self) -> Result<&UuidSchema> {
+		if let ValueSchema::Uuid(ret) = self {
+			Ok(ret)
+		} else {
+			Err(ParquetError::General(format!(
+				"Cannot access {:?} as uuid",
+				self
+			)))
+		}
+	}
+
+	pub fn into_uuid(self) -> Result<UuidSchema> {
+		if let ValueSchema::Uuid(ret) = self {
+			Ok(ret)
+		} else {
+			Err(ParquetError::General(format!(
+				"Cannot access {:?} as uuid",
+				self
+			)))
+		}
+	}
+
+	pub fn is_duration(&self) -> bool {
+		matches!(self, ValueSchema::Duration(_))
+	}
+
+	pub fn as_duration(&self) -> Result<&IntervalSchema> {
+		if let ValueSchema::Duration(ret) = self {
+			Ok(ret)
+		} else {
+			Err(ParquetError::General(format!(
+				"Cannot access {:?} as duration",
+				self
+			)))
+		}
+	}
+
+	pub fn into_duration(self) -> Result<IntervalSchema> {
+		if let ValueSchema::Duration(ret) = self {
+			Ok(ret)
+		} else {
+			Err(ParquetError::General(format!(
+				"Cannot access {:?} as duration",
+				self
+			)))
+		}
+	}
+
 	pub fn is_list(&self) -> bool {
 		if let ValueSchema::List(_) = self {
 			true
@@ -1603,6 +1726,16 @@ impl Downcast<EnumSchema> for ValueSchema {
 		self.into_enum()
 	}
 }
+impl Downcast<UuidSchema> for ValueSchema {
+	fn downcast(self) -> Result<UuidSchema> {
+		self.into_uuid()
+	}
+}
+impl Downcast<IntervalSchema> for ValueSchema {
+	fn downcast(self) -> Result<IntervalSchema> {
+		self.into_duration()
+	}
+}
 impl Downcast<VecU8Schema> for ValueSchema {
 	fn downcast(self) -> Result<VecU8Schema> {
 		Ok(if !self.is_list() {
@@ -124,6 +124,8 @@ pub struct TypedTripletIter<T: DataType> {
 	triplets_left: usize,
 	// helper flag to quickly check if we have more values/levels to read
 	has_next: bool,
+	// how many leading records are yet to be skipped
+	records_to_skip: u64,
 }
 
 impl<T: DataType> TypedTripletIter<T> {
@@ -143,8 +145,10 @@ impl<T: DataType> TypedTripletIter<T> {
 			Some(vec![0; batch_size])
 		};
 
+		let reader = get_typed_column_reader::<T>(reader);
+		let records_to_skip = reader.records_to_skip();
 		Self {
-			reader: get_typed_column_reader(reader),
+			reader,
 			batch_size,
 			def_level,
 			rep_level,
@@ -154,6 +158,7 @@ impl<T: DataType> TypedTripletIter<T> {
 			curr_triplet_index: 0,
 			triplets_left: 0,
 			has_next: false,
+			records_to_skip,
 		}
 	}
 
@@ -178,6 +183,20 @@ impl<T: DataType> TypedTripletIter<T> {
 	/// Returns true, if there are more records to read, false there are no records left.
 	#[inline]
 	pub fn advance_columns(&mut self) -> Result<()> {
+		self.advance()?;
+		// Skip the leading records when first advanced. Each record starts at a
+		// triplet with repetition level 0.
+		while self.records_to_skip != 0 && self.has_next {
+			self.advance()?;
+			if !self.has_next || self.current_rep_level() == 0 {
+				self.records_to_skip -= 1;
+			}
+		}
+		Ok(())
+	}
+
+	#[inline]
+	fn advance(&mut self) -> Result<()> {
 		self.curr_triplet_index += 1;
 
 		if self.curr_triplet_index >= self.triplets_left {
@@ -20,7 +20,7 @@
 use std::{collections::HashMap, convert::From, fmt, rc::Rc};
 
 use crate::internal::{
-	basic::{LogicalType, Repetition, Type as PhysicalType}, errors::{ParquetError, Result}, format::SchemaElement
+	basic::{LogicalType, Repetition, Type as PhysicalType}, errors::{ParquetError, Result}, format::{self, SchemaElement}
 };
 
 // ----------------------------------------------------------------------
@@ -385,6 +385,13 @@ impl<'a> PrimitiveTypeBuilder<'a> {
 					));
 				}
 			}
+			LogicalType::Uuid => {
+				if self.physical_type != PhysicalType::FixedLenByteArray || self.length != 16 {
+					return Err(general_err!(
+						"UUID can only annotate FIXED_LEN_BYTE_ARRAY(16)"
+					));
+				}
+			}
 			LogicalType::Enum => {
 				if self.physical_type != PhysicalType::ByteArray {
 					return Err(general_err!("ENUM can only annotate BYTE_ARRAY fields"));
@@ -898,7 +905,10 @@ fn from_thrift_helper(elements: &[SchemaElement], index: usize) -> Result<(usize
 			elements.len()
 		));
 	}
-	let logical_type = LogicalType::from(elements[index].converted_type);
+	let logical_type = match elements[index].logical_type {
+		Some(format::LogicalType::UUID(_)) => LogicalType::Uuid,
+		_ => LogicalType::from(elements[index].converted_type),
+	};
 	let field_id = elements[index].field_id;
 	match elements[index].num_children {
 		// From parquet-format:
@@ -1006,7 +1016,11 @@ fn to_thrift_helper(schema: &Type, elements: &mut Vec<SchemaElement>) {
 				} else {
 					None
 				},
-				logical_type: None,
+				logical_type: if basic_info.logical_type() == LogicalType::Uuid {
+					Some(format::LogicalType::UUID(format::UUIDType::new()))
+				} else {
+					None
+				},
 			};
 
 			elements.push(element);
@@ -46,19 +46,23 @@ mod wrap {
 	use super::internal;
 	use async_trait::async_trait;
 	use educe::Educe;
-	use futures::{pin_mut, stream, AsyncReadExt, FutureExt, StreamExt};
+	use futures::{future, pin_mut, stream, AsyncReadExt, FutureExt, StreamExt, TryStreamExt};
 	use internal::{
-		errors::ParquetError as InternalParquetError, file::reader::{FileReader, ParquetReader, SerializedFileReader}
+		basic::Type as PhysicalType, errors::ParquetError as InternalParquetError, file::{
+			bloom_filter::{Sbbf, MAX_HEADER_SIZE}, metadata::{ParquetMetaDataPtr, RowGroupMetaData}, reader::{FileReader, ParquetReader, RowGroupReader, SerializedFileReader}, FOOTER_SIZE
+		}, record::{types::Root, with_int96_timezone, DisplayFmt, Schema as ParquetSchema}, schema::types::Type
 	};
 	use serde::{Deserialize, Serialize};
 	use serde_closure::*;
 	use std::{
-		error, fmt::{self, Debug, Display}, io::Cursor, marker::PhantomData, ops::FnMut
+		convert::{TryFrom, TryInto}, error, fmt::{self, Debug, Display}, io::Cursor, marker::PhantomData, ops::{FnMut, Range}
 	};
+	use sum::Sum2;
 
 	use amadeus_core::{
-		file::{Directory, File, Page, Partition, PathBuf}, into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::{DistParStream, ResultExpandIter}, Source
+		file::{Directory, File, Page, Partition, PathBuf}, into_par_stream::IntoDistributedStream, par_stream::DistributedStream, plan::Stage, util::{DistParStream, Planned, ResultExpandIter}, Source
 	};
+	use amadeus_types::{Predicate, Schema, Timezone, Value};
 
 	pub use internal::record::ParquetData;
 
@@ -79,6 +83,11 @@ mod wrap {
 		Row: ParquetData,
 	{
 		partitions: Vec<File::Partition>,
+		splits: Option<Vec<Split<File::Partition>>>,
+		filters: Vec<(String, Value)>,
+		predicate: Option<Predicate>,
+		rows: Option<Range<u64>>,
+		int96_timezone: Timezone,
 		marker: PhantomData<fn() -> Row>,
 	}
 	impl<F, Row> Parquet<F, Row>
@@ -89,14 +98,440 @@ mod wrap {
 		pub async fn new(file: F) -> Result<Self, <Self as Source>::Error> {
 			Ok(Self {
 				partitions: file.partitions().await.map_err(ParquetError::File)?,
+				splits: None,
+				filters: Vec::new(),
+				predicate: None,
+				rows: None,
+				int96_timezone: Timezone::UTC,
 				marker: PhantomData,
 			})
 		}
+
+		/// Read legacy INT96 timestamps as wall-clock times in `timezone`, rather
+		/// than as UTC, as Impala and older versions of Hive write them in local
+		/// time. Other timestamps are always UTC, so aren't affected.
+		pub fn int96_timezone(mut self, timezone: Timezone) -> Self {
+			self.int96_timezone = timezone;
+			self
+		}
+
+		/// Skip the row groups whose Bloom filter for `column`, a dotted path, shows
+		/// they don't contain `value`. Only the footer, the Bloom filters and the
+		/// column chunks of the remaining row groups are read.
+		///
+		/// This prunes whole row groups; rows of the remaining groups that don't
+		/// equal `value` are still yielded. Row groups without a Bloom filter for
+		/// `column` are never skipped.
+		pub fn filter_eq(mut self, column: &str, value: impl Into<Value>) -> Self {
+			self.filters.push((column.to_owned(), value.into()));
+			self
+		}
+
+		/// Yield only the rows matching `predicate`, skipping the row groups that
+		/// can't contain any by taking each of its
+		/// [`equalities`](Predicate::equalities) as a
+		/// [`filter_eq`](Self::filter_eq), and evaluating it against each row of
+		/// the rest. Predicates given by repeated calls must all match.
+		///
+		/// # Panics
+		///
+		/// Reading panics if `predicate` fails to evaluate against a row, as for
+		/// [`filter_by_schema_predicate`](amadeus_types::ParallelStreamRows::filter_by_schema_predicate).
+		pub fn filter_by_schema_predicate(mut self, predicate: Predicate) -> Self {
+			self.filters.extend(predicate.equalities());
+			self.predicate = Some(match self.predicate.take() {
+				Some(existing) => existing.and(predicate),
+				None => predicate,
+			});
+			self
+		}
+
+		/// Read each file as runs of adjacent row groups whose column chunks total
+		/// at least `target_bytes`, rather than as a whole, so that files of many
+		/// tiny row groups don't make for many tiny tasks, while large files are
+		/// read in parallel. This reads the footer of each file.
+		pub async fn coalesce_row_groups(
+			mut self, target_bytes: u64,
+		) -> Result<Self, <Self as Source>::Error> {
+			let mut splits = Vec::new();
+			for partition in self.partitions.iter().cloned() {
+				let pages = partition
+					.clone()
+					.pages()
+					.await
+					.map_err(ParquetError::Partition)?;
+				for (page_index, page) in pages.iter().enumerate() {
+					let metadata =
+						read_metadata::<_, F::Error, <F::Partition as Partition>::Error>(page)
+							.await?;
+					let (mut start, mut bytes) = (0, 0);
+					for (i, row_group) in metadata.row_groups().iter().enumerate() {
+						bytes += row_group
+							.columns()
+							.iter()
+							.map(|column| u64::try_from(column.compressed_size()).unwrap())
+							.sum::<u64>();
+						if bytes >= target_bytes || i + 1 == metadata.num_row_groups() {
+							splits.push(Split {
+								partition: partition.clone(),
+								row_groups: Some((page_index, start..i + 1)),
+								rows: None,
+							});
+							start = i + 1;
+							bytes = 0;
+						}
+					}
+				}
+			}
+			self.splits = Some(splits);
+			self.rows = None;
+			Ok(self)
+		}
+
+		/// Read only the `len` rows starting at the `start`th, counting across the
+		/// files in order, such as to page through a large file. The footers are
+		/// used to find the row groups holding the range, so the column chunks of
+		/// the others aren't read at all, and the page headers to find the data
+		/// pages holding it, so those of only earlier rows aren't decompressed or
+		/// decoded. This replaces any
+		/// [`coalesce_row_groups`](Self::coalesce_row_groups), and reads the
+		/// footer of each file up to the one holding the end of the range.
+		///
+		/// Rows are counted before any [`filter_eq`](Self::filter_eq) is applied.
+		/// They're yielded in order within each file, but the rows of different
+		/// files may be interleaved.
+		pub async fn row_range(
+			mut self, start: u64, len: u64,
+		) -> Result<Self, <Self as Source>::Error> {
+			let end = start.saturating_add(len);
+			let mut splits = Vec::new();
+			// The index of the first row of the current row group from the start
+			let mut offset = 0;
+			'partition: for partition in self.partitions.iter().cloned() {
+				let pages = partition
+					.clone()
+					.pages()
+					.await
+					.map_err(ParquetError::Partition)?;
+				for (page_index, page) in pages.iter().enumerate() {
+					if offset >= end {
+						break 'partition;
+					}
+					let metadata =
+						read_metadata::<_, F::Error, <F::Partition as Partition>::Error>(page)
+							.await?;
+					// The first row group overlapping the range and its offset, and
+					// the same just past the last
+					let (mut first, mut last) = (None, (0, 0));
+					for (i, row_group) in metadata.row_groups().iter().enumerate() {
+						let num_rows = num_rows(row_group)?;
+						if offset < end && start < offset + num_rows {
+							let _ = first.get_or_insert((i, offset));
+							last = (i + 1, offset + num_rows);
+						}
+						offset += num_rows;
+					}
+					if let Some((first, first_offset)) = first {
+						let (last, last_offset) = last;
+						splits.push(Split {
+							partition: partition.clone(),
+							row_groups: Some((page_index, first..last)),
+							rows: Some(
+								start.saturating_sub(first_offset)
+									..end.min(last_offset) - first_offset,
+							),
+						});
+					}
+				}
+			}
+			self.splits = Some(splits);
+			self.rows = Some(start..end);
+			Ok(self)
+		}
+
+		/// Read the row count, row group count and schema from the footer of each
+		/// file, without reading any data pages.
+		pub async fn metadata(&self) -> Result<ParquetMetadata, <Self as Source>::Error> {
+			let mut metadata = ParquetMetadata {
+				num_rows: 0,
+				num_row_groups: 0,
+				schema: None,
+			};
+			for partition in self.partitions.iter().cloned() {
+				for page in partition.pages().await.map_err(ParquetError::Partition)? {
+					let page_metadata =
+						read_metadata::<_, F::Error, <F::Partition as Partition>::Error>(&page)
+							.await?;
+					let file_metadata = page_metadata.file_metadata();
+					metadata.num_rows += u64::try_from(file_metadata.num_rows()).unwrap();
+					metadata.num_row_groups += page_metadata.num_row_groups();
+					if metadata.schema.is_none() {
+						metadata.schema = Some(file_metadata.schema().clone());
+					}
+				}
+			}
+			Ok(metadata)
+		}
+
+		/// The schema of the rows as [`Value`]s, read from the footer of the first
+		/// file, or `None` if there are no files.
+		pub async fn schema(&self) -> Result<Option<Schema>, <Self as Source>::Error> {
+			for partition in self.partitions.iter().cloned() {
+				if let Some(page) = partition
+					.pages()
+					.await
+					.map_err(ParquetError::Partition)?
+					.into_iter()
+					.next()
+				{
+					let metadata =
+						read_metadata::<_, F::Error, <F::Partition as Partition>::Error>(&page)
+							.await?;
+					let (_, schema) = <Root<Value> as ParquetData>::parse(
+						metadata.file_metadata().schema(),
+						None,
+						None,
+					)?;
+					return Ok(Some(schema.1.to_schema()));
+				}
+			}
+			Ok(None)
+		}
 	}
+
+	/// A partition to be read as a whole, or the range `row_groups.1` of the row
+	/// groups of its `row_groups.0`th page, of which only `rows` are yielded if
+	/// given, counting from the first row of the range.
+	#[derive(Clone, Serialize, Deserialize, Debug)]
+	struct Split<P> {
+		partition: P,
+		row_groups: Option<(usize, Range<usize>)>,
+		rows: Option<Range<u64>>,
+	}
+
+	/// The summary of a [`Parquet`] source returned by [`Parquet::metadata`].
+	#[derive(Clone, PartialEq, Debug)]
+	pub struct ParquetMetadata {
+		/// The total number of rows across all files.
+		pub num_rows: u64,
+		/// The total number of row groups across all files.
+		pub num_row_groups: usize,
+		/// The schema of the first file, or `None` if there are no files.
+		pub schema: Option<Type>,
+	}
+
+	/// Read the metadata of a Parquet file by fetching only its footer: first the
+	/// fixed-size trailer holding the metadata length, and then the metadata itself.
+	async fn read_metadata<P, A, B>(
+		page: &P,
+	) -> Result<ParquetMetaDataPtr, ParquetError<A, B, P::Error>>
+	where
+		P: Page,
+	{
+		let len = page.len().await.map_err(ParquetError::Page)?;
+		let tail = read_tail(page, len).await?;
+		let tail = PassError::new(Ok(Cursor::new(tail.into_vec())));
+		Ok(SerializedFileReader::new(tail)?.metadata())
+	}
+
+	async fn read_tail<P, A, B>(
+		page: &P, len: u64,
+	) -> Result<Box<[u8]>, ParquetError<A, B, P::Error>>
+	where
+		P: Page,
+	{
+		let footer = if len >= FOOTER_SIZE as u64 {
+			page.read(len - FOOTER_SIZE as u64, FOOTER_SIZE)
+				.await
+				.map_err(ParquetError::Page)?
+		} else {
+			Box::default()
+		};
+		// If the footer is invalid, pass it alone to `SerializedFileReader` to
+		// surface the appropriate error.
+		let tail_len = footer
+			.get(..4)
+			.and_then(|metadata_len| {
+				usize::try_from(i32::from_le_bytes(metadata_len.try_into().unwrap())).ok()
+			})
+			.map(|metadata_len| metadata_len + FOOTER_SIZE)
+			.filter(|&tail_len| tail_len as u64 <= len)
+			.unwrap_or(FOOTER_SIZE);
+		Ok(if tail_len > FOOTER_SIZE {
+			page.read(len - tail_len as u64, tail_len)
+				.await
+				.map_err(ParquetError::Page)?
+		} else {
+			footer
+		})
+	}
+
+	/// Read the rows of the row groups of a Parquet file, or of the range
+	/// `row_groups` of them, that aren't pruned by `filters`, fetching only the
+	/// footer, the Bloom filters, and the column chunks of the remaining row
+	/// groups. If `rows` is given only those rows of the range are yielded, and
+	/// the row groups that don't overlap them aren't read.
+	async fn read_filtered<P, Row, A, B>(
+		page: P, filters: &[(String, Value)], row_groups: Option<Range<usize>>,
+		rows: Option<Range<u64>>, int96_timezone: Timezone,
+	) -> Result<impl Iterator<Item = Result<Row, InternalParquetError>>, ParquetError<A, B, P::Error>>
+	where
+		P: Page,
+		Row: ParquetData + 'static,
+	{
+		let len = page.len().await.map_err(ParquetError::Page)?;
+		let tail = read_tail(&page, len).await?;
+		let metadata =
+			SerializedFileReader::new(PassError::new(Ok(Cursor::new(tail.to_vec()))))?.metadata();
+		let row_groups = row_groups.unwrap_or(0..metadata.num_row_groups());
+		let counts = row_groups
+			.clone()
+			.map(|i| num_rows(&metadata.row_group(i)))
+			.collect::<Result<Vec<_>, _>>()?;
+		let num_rows = |i: usize| counts[i - row_groups.start];
+		// The index of the first row of each row group from that of the first
+		let offsets = row_groups
+			.clone()
+			.scan(0, |offset, i| {
+				let start = *offset;
+				*offset += num_rows(i);
+				Some(start)
+			})
+			.collect::<Vec<_>>();
+		let first = row_groups.start;
+		let offset = |i: usize| offsets[i - first];
+		let rows = rows.unwrap_or(0..u64::MAX);
+		let overlapping = row_groups
+			.clone()
+			.filter(|&i| offset(i) < rows.end && rows.start < offset(i) + num_rows(i))
+			.collect::<Vec<_>>();
+		let candidates = match (overlapping.first(), overlapping.last()) {
+			(Some(&start), Some(&end)) => start..end + 1,
+			_ => 0..0,
+		};
+		let row_groups = prune_row_groups(&page, len, &metadata, candidates, filters).await?;
+
+		let mut ranges = vec![(len - tail.len() as u64, tail)];
+		for &i in &row_groups {
+			for column in metadata.row_group(i).columns() {
+				let start = column
+					.dictionary_page_offset()
+					.unwrap_or_else(|| column.data_page_offset());
+				let start = u64::try_from(start).unwrap();
+				let chunk_len = usize::try_from(column.compressed_size()).unwrap();
+				let chunk = page
+					.read(start, chunk_len)
+					.await
+					.map_err(ParquetError::Page)?;
+				ranges.push((start, chunk));
+			}
+		}
+		let reader = SerializedFileReader::new(SparseReader::new(len, ranges))?;
+		let rows = row_groups
+			.into_iter()
+			.map(|i| {
+				let skip = rows.start.saturating_sub(offset(i));
+				let take = rows.end.min(offset(i) + num_rows(i)) - offset(i) - skip;
+				// The data pages holding only rows before the range aren't decoded
+				let row_group = reader.get_row_group(i)?.skip_rows(skip);
+				Ok(
+					with_int96_timezone(int96_timezone, || row_group.get_row_iter::<Row>(None))?
+						.take(usize::try_from(take).unwrap_or(usize::MAX)),
+				)
+			})
+			.collect::<Result<Vec<_>, InternalParquetError>>()?;
+		Ok(rows.into_iter().flatten())
+	}
+
+	/// The number of rows of `row_group`, which is negative only if the footer is
+	/// corrupt.
+	fn num_rows(row_group: &RowGroupMetaData) -> Result<u64, InternalParquetError> {
+		u64::try_from(row_group.num_rows()).map_err(|_| {
+			InternalParquetError::General(format!(
+				"Invalid number of rows {} in row group",
+				row_group.num_rows()
+			))
+		})
+	}
+
+	/// Returns the indices of the `candidates` row groups that may contain rows
+	/// matching every filter, reading only the relevant Bloom filters.
+	async fn prune_row_groups<P, A, B>(
+		page: &P, len: u64, metadata: &ParquetMetaDataPtr, candidates: Range<usize>,
+		filters: &[(String, Value)],
+	) -> Result<Vec<usize>, ParquetError<A, B, P::Error>>
+	where
+		P: Page,
+	{
+		let mut row_groups = Vec::new();
+		'row_group: for i in candidates {
+			let row_group = metadata.row_group(i);
+			for (column, value) in filters {
+				let chunk = row_group
+					.columns()
+					.iter()
+					.find(|chunk| chunk.column_path().string() == *column);
+				let (offset, plain) = match chunk.and_then(|chunk| {
+					Some((
+						chunk.bloom_filter_offset()?,
+						plain_encoding(chunk.column_type(), value)?,
+					))
+				}) {
+					Some((offset, plain)) => (u64::try_from(offset).unwrap(), plain),
+					None => continue,
+				};
+				let header_len = MAX_HEADER_SIZE.min(usize::try_from(len - offset).unwrap());
+				let header = page
+					.read(offset, header_len)
+					.await
+					.map_err(ParquetError::Page)?;
+				let (header, header_len) = Sbbf::read_header(&header)?;
+				let bitset = page
+					.read(
+						offset + header_len as u64,
+						usize::try_from(header.num_bytes).unwrap(),
+					)
+					.await
+					.map_err(ParquetError::Page)?;
+				if !Sbbf::from_bitset(&bitset)?.check(Sbbf::hash(&plain)) {
+					continue 'row_group;
+				}
+			}
+			row_groups.push(i);
+		}
+		Ok(row_groups)
+	}
+
+	/// The plain encoding of `value` as stored in a column of `physical_type`, which
+	/// is what Bloom filters hash. `None` if `value` can't be stored in the column.
+	fn plain_encoding(physical_type: PhysicalType, value: &Value) -> Option<Vec<u8>> {
+		let int = match *value {
+			Value::U8(value) => Some(i64::from(value)),
+			Value::I8(value) => Some(i64::from(value)),
+			Value::U16(value) => Some(i64::from(value)),
+			Value::I16(value) => Some(i64::from(value)),
+			Value::U32(value) => Some(i64::from(value)),
+			Value::I32(value) => Some(i64::from(value)),
+			Value::U64(value) => i64::try_from(value).ok(),
+			Value::I64(value) => Some(value),
+			_ => None,
+		};
+		Some(match (physical_type, value) {
+			(PhysicalType::Int32, _) => i32::try_from(int?).ok()?.to_le_bytes().to_vec(),
+			(PhysicalType::Int64, _) => int?.to_le_bytes().to_vec(),
+			(PhysicalType::Float, Value::F32(value)) => value.to_le_bytes().to_vec(),
+			(PhysicalType::Double, Value::F64(value)) => value.to_le_bytes().to_vec(),
+			(PhysicalType::ByteArray, Value::String(value))
+			| (PhysicalType::FixedLenByteArray, Value::String(value)) => value.as_bytes().to_vec(),
+			(PhysicalType::FixedLenByteArray, Value::Uuid(value)) => value.as_bytes().to_vec(),
+			_ => return None,
+		})
+	}
+
 	impl<F, Row> Source for Parquet<F, Row>
 	where
 		F: File,
-		Row: ParquetData + 'static,
+		Row: ParquetData + Into<Value> + 'static,
 	{
 		type Item = Row;
 		#[allow(clippy::type_complexity)]
@@ -113,41 +548,139 @@ mod wrap {
 		fn par_stream(self) -> Self::ParStream {
 			DistParStream::new(self.dist_stream())
 		}
-		#[allow(clippy::let_and_return)]
 		fn dist_stream(self) -> Self::DistStream {
-			self.partitions
+			let mut stage = Stage::new("Parquet").detail("projection", projection::<Row>());
+			if !self.filters.is_empty() {
+				let filters = self
+					.filters
+					.iter()
+					.map(|(column, value)| format!("{} = {:?}", column, value))
+					.collect::<Vec<_>>();
+				stage = stage.detail("filters", filters.join(", "));
+			}
+			if let Some(predicate) = &self.predicate {
+				stage = stage.detail("predicate", format!("{:?}", predicate));
+			}
+			if let Some(rows) = &self.rows {
+				stage = stage.detail("rows", format!("{}..{}", rows.start, rows.end));
+			} else if self.splits.is_some() {
+				stage = stage.detail("row_groups", "coalesced");
+			}
+			if self.int96_timezone != Timezone::UTC {
+				stage = stage.detail("int96_timezone", self.int96_timezone.to_string());
+			}
+			let (filters, predicate, partitions, int96_timezone) = (
+				self.filters,
+				self.predicate,
+				self.partitions,
+				self.int96_timezone,
+			);
+			let splits = self.splits.unwrap_or_else(|| {
+				partitions
+					.into_iter()
+					.map(|partition| Split {
+						partition,
+						row_groups: None,
+						rows: None,
+					})
+					.collect()
+			});
+			let stream = splits
 				.into_dist_stream()
-				.flat_map(FnMut!(|partition: F::Partition| async move {
-					Ok(stream::iter(
-						partition
+				.flat_map(FnMut!(move |split: Split<F::Partition>| {
+					let filters = filters.clone();
+					let predicate = predicate.clone();
+					let int96_timezone = int96_timezone;
+					async move {
+						let pages = split
+							.partition
 							.pages()
 							.await
-							.map_err(ParquetError::Partition)?
-							.into_iter(),
-					)
-					.flat_map(|page| {
-						async move {
-							let mut buf = Vec::with_capacity(10 * 1024 * 1024);
-							let reader = Page::reader(page);
-							pin_mut!(reader);
-							let buf = PassError::new(
-								reader.read_to_end(&mut buf).await.map(|_| Cursor::new(buf)),
-							);
-							Ok(stream::iter(
-								SerializedFileReader::new(buf)?.get_row_iter::<Row>(None)?,
-							))
-						}
-						.map(ResultExpandIter::new)
-						.flatten_stream()
+							.map_err(ParquetError::Partition)?;
+						let pages = match split.row_groups {
+							Some((page, row_groups)) => vec![(
+								pages.into_iter().nth(page).unwrap(),
+								Some(row_groups),
+								split.rows,
+							)],
+							None => pages.into_iter().map(|page| (page, None, None)).collect(),
+						};
+						Ok(stream::iter(pages)
+							.flat_map(move |(page, row_groups, rows)| {
+								let filters = filters.clone();
+								async move {
+									if !filters.is_empty() || row_groups.is_some() {
+										let rows =
+											read_filtered::<
+												_,
+												Row,
+												F::Error,
+												<F::Partition as Partition>::Error,
+											>(
+												page, &filters, row_groups, rows, int96_timezone
+											)
+											.await?;
+										return Ok(stream::iter(Sum2::B(rows)));
+									}
+									let mut buf = Vec::with_capacity(10 * 1024 * 1024);
+									let reader = Page::reader(page);
+									pin_mut!(reader);
+									let buf = PassError::new(
+										reader
+											.read_to_end(&mut buf)
+											.await
+											.map(|_| Cursor::new(buf)),
+									);
+									let reader = SerializedFileReader::new(buf)?;
+									Ok(stream::iter(Sum2::A(with_int96_timezone(
+										int96_timezone,
+										|| reader.get_row_iter::<Row>(None),
+									)?)))
+								}
+								.map(ResultExpandIter::new)
+								.flatten_stream()
+							})
+							.map(|row: Result<Result<Row, _>, Self::Error>| Ok(row??)))
+					}
+					.map(ResultExpandIter::new)
+					.flatten_stream()
+					.map(|row: Result<Result<Row, Self::Error>, Self::Error>| Ok(row??))
+					.try_filter(move |row: &Row| {
+						future::ready(
+							predicate
+								.as_ref()
+								.map_or(true, |predicate| matches(predicate, row)),
+						)
 					})
-					.map(|row: Result<Result<Row, _>, Self::Error>| Ok(row??)))
-				}
-				.map(ResultExpandIter::new)
-				.flatten_stream()
-				.map(|row: Result<Result<Row, Self::Error>, Self::Error>| Ok(row??))))
+				}));
+			Planned::new(stream, stage)
 		}
 	}
 
+	/// Whether `row`, which must be a group of named fields, matches `predicate`.
+	fn matches<Row: Clone + Into<Value>>(predicate: &Predicate, row: &Row) -> bool {
+		let row = row
+			.clone()
+			.into()
+			.into_group()
+			.unwrap_or_else(|_| panic!("predicates can only filter rows of named fields"));
+		predicate
+			.eval(&row)
+			.unwrap_or_else(|err| panic!("failed to evaluate predicate: {}", err))
+			== Some(true)
+	}
+
+	/// The columns read for `Row`, as a Parquet schema on one line.
+	fn projection<Row: ParquetData>() -> String {
+		DisplayFmt::new(|fmt| {
+			<<Root<Row> as ParquetData>::Schema as ParquetSchema>::fmt(None, None, None, fmt)
+		})
+		.to_string()
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ")
+	}
+
 	// impl<P> ParquetReader for amadeus_core::file::Reader<P>
 	// where
 	// 	P: Page,
@@ -329,6 +862,68 @@ mod wrap {
 		}
 	}
 
+	/// A reader over the ranges of a file that have been fetched. Reads anywhere
+	/// else error.
+	struct SparseReader {
+		len: u64,
+		ranges: Vec<(u64, Box<[u8]>)>,
+		offset: u64,
+	}
+	impl SparseReader {
+		fn new(len: u64, ranges: Vec<(u64, Box<[u8]>)>) -> Self {
+			Self {
+				len,
+				ranges,
+				offset: 0,
+			}
+		}
+	}
+	impl ParquetReader for SparseReader {
+		fn len(&self) -> u64 {
+			self.len
+		}
+	}
+	impl io::Read for SparseReader {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			if self.offset >= self.len {
+				return Ok(0);
+			}
+			let offset = self.offset;
+			let range = self
+				.ranges
+				.iter()
+				.find(|(start, range)| *start <= offset && offset < start + range.len() as u64);
+			let (start, range) = range.ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::Other,
+					format!("read at {} outside of fetched ranges", offset),
+				)
+			})?;
+			let range = &range[usize::try_from(offset - start).unwrap()..];
+			let len = range.len().min(buf.len());
+			buf[..len].copy_from_slice(&range[..len]);
+			self.offset += len as u64;
+			Ok(len)
+		}
+	}
+	impl io::Seek for SparseReader {
+		fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+			let offset = match pos {
+				io::SeekFrom::Start(offset) => Some(offset),
+				io::SeekFrom::End(offset) => i64::try_from(self.len)
+					.ok()
+					.and_then(|len| u64::try_from(len + offset).ok()),
+				io::SeekFrom::Current(offset) => i64::try_from(self.offset)
+					.ok()
+					.and_then(|current| u64::try_from(current + offset).ok()),
+			};
+			self.offset = offset.ok_or_else(|| {
+				io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative offset")
+			})?;
+			Ok(self.offset)
+		}
+	}
+
 	struct PassError<R>(Result<R, Option<io::Error>>);
 	impl<R> PassError<R> {
 		fn new(r: Result<R, io::Error>) -> Self {
@@ -357,6 +952,504 @@ mod wrap {
 			}
 		}
 	}
+	#[cfg(test)]
+	mod tests {
+		use futures::{future, Future, FutureExt};
+		use std::{cell::RefCell, env, fs, path::PathBuf, process, rc::Rc};
+		use thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol, TOutputProtocol};
+
+		use amadeus_core::{par_stream::StreamTask, util::IoError};
+		use amadeus_types::{Expr, Group};
+
+		use super::*;
+		use internal::{
+			basic::{Repetition, Type as PhysicalType}, column::writer::ColumnWriter, data_type::Int96, file::{
+				properties::WriterProperties, writer::{FileWriter, SerializedFileWriter}, PARQUET_MAGIC
+			}, format::FileMetaData as TFileMetaData, schema::types
+		};
+
+		/// Run `future` to completion on a Tokio runtime, which local files are
+		/// read on.
+		fn block_on<F: Future>(future: F) -> F::Output {
+			tokio::runtime::Builder::new()
+				.basic_scheduler()
+				.enable_all()
+				.build()
+				.unwrap()
+				.block_on(future)
+		}
+
+		/// A page over an in-memory file that records the ranges read from it.
+		struct InstrumentedPage {
+			data: Rc<[u8]>,
+			reads: Rc<RefCell<Vec<(u64, usize)>>>,
+		}
+		impl Page for InstrumentedPage {
+			type Error = IoError;
+
+			fn len(&self) -> future::LocalBoxFuture<'static, Result<u64, Self::Error>> {
+				future::ready(Ok(self.data.len() as u64)).boxed_local()
+			}
+			fn read(
+				&self, offset: u64, len: usize,
+			) -> future::LocalBoxFuture<'static, Result<Box<[u8]>, Self::Error>> {
+				self.reads.borrow_mut().push((offset, len));
+				let offset = usize::try_from(offset).unwrap();
+				future::ready(Ok(self.data[offset..offset + len].into())).boxed_local()
+			}
+			fn write(
+				&self, _offset: u64, _buf: Box<[u8]>,
+			) -> future::LocalBoxFuture<'static, Result<(), Self::Error>> {
+				unimplemented!()
+			}
+		}
+
+		#[test]
+		fn metadata_from_footer() {
+			let mut path = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+			path.push("../amadeus-testing/parquet/stock_simulated.parquet");
+			let data: Rc<[u8]> = fs::read(&path).unwrap().into();
+			let reads = Rc::new(RefCell::new(Vec::new()));
+			let page = InstrumentedPage {
+				data: data.clone(),
+				reads: reads.clone(),
+			};
+
+			let metadata = block_on(read_metadata::<_, IoError, IoError>(&page)).unwrap();
+
+			let rows = SerializedFileReader::new(fs::File::open(&path).unwrap())
+				.unwrap()
+				.get_row_iter::<Group>(None)
+				.unwrap()
+				.count();
+			assert_eq!(
+				metadata.file_metadata().num_rows(),
+				i64::try_from(rows).unwrap()
+			);
+			assert_eq!(rows, 42_000);
+
+			// Only the footer was read: every read lies after the last column chunk.
+			let data_end = metadata
+				.row_groups()
+				.iter()
+				.flat_map(|row_group| row_group.columns())
+				.map(|column| {
+					let start = column
+						.dictionary_page_offset()
+						.unwrap_or_else(|| column.data_page_offset());
+					u64::try_from(start + column.compressed_size()).unwrap()
+				})
+				.max()
+				.unwrap();
+			let reads = reads.borrow();
+			assert_eq!(reads.len(), 2);
+			assert!(reads.iter().all(|&(offset, _)| offset >= data_end));
+		}
+
+		/// Write a file with an `id` column holding each of `row_groups`, in pages
+		/// of 10 rows, and then append a Bloom filter for each column chunk.
+		fn bloom_filtered_file(row_groups: &[Vec<i64>]) -> Vec<u8> {
+			let path = env::temp_dir().join(format!("amadeus-bloom-{}.parquet", process::id()));
+			let schema = Rc::new(
+				types::Type::group_type_builder("schema")
+					.with_fields(&mut vec![Rc::new(
+						types::Type::primitive_type_builder("id", PhysicalType::Int64)
+							.with_repetition(Repetition::Required)
+							.build()
+							.unwrap(),
+					)])
+					.build()
+					.unwrap(),
+			);
+			let props = Rc::new(
+				WriterProperties::builder()
+					.set_write_batch_size(10)
+					.set_data_pagesize_limit(1)
+					.build(),
+			);
+			let mut file_writer =
+				SerializedFileWriter::new(fs::File::create(&path).unwrap(), schema, props).unwrap();
+			for values in row_groups {
+				let mut row_group_writer = file_writer.next_row_group().unwrap();
+				let mut writer = row_group_writer.next_column().unwrap().unwrap();
+				match writer {
+					ColumnWriter::Int64ColumnWriter(ref mut typed) => {
+						let _ = typed.write_batch(values, None, None).unwrap();
+					}
+					_ => unreachable!(),
+				}
+				row_group_writer.close_column(writer).unwrap();
+				file_writer.close_row_group(row_group_writer).unwrap();
+			}
+			file_writer.close().unwrap();
+			let mut data = fs::read(&path).unwrap();
+			fs::remove_file(&path).unwrap();
+
+			// Replace the footer with one pointing at the appended Bloom filters
+			let metadata_len = data.len() - FOOTER_SIZE;
+			let metadata_len = usize::try_from(i32::from_le_bytes(
+				data[metadata_len..metadata_len + 4].try_into().unwrap(),
+			))
+			.unwrap();
+			let metadata_start = data.len() - FOOTER_SIZE - metadata_len;
+			let mut metadata = TFileMetaData::read_from_in_protocol(
+				&mut TCompactInputProtocol::new(&data[metadata_start..data.len() - FOOTER_SIZE]),
+			)
+			.unwrap();
+			data.truncate(metadata_start);
+			for (row_group, values) in metadata.row_groups.iter_mut().zip(row_groups) {
+				let mut filter = Sbbf::new(1024);
+				for value in values {
+					filter.insert(Sbbf::hash(&value.to_le_bytes()));
+				}
+				let column = row_group.columns[0].meta_data.as_mut().unwrap();
+				column.bloom_filter_offset = Some(i64::try_from(data.len()).unwrap());
+				filter.write(&mut data).unwrap();
+			}
+			let metadata_start = data.len();
+			{
+				let mut prot = TCompactOutputProtocol::new(&mut data);
+				metadata.write_to_out_protocol(&mut prot).unwrap();
+				prot.flush().unwrap();
+			}
+			let metadata_len = i32::try_from(data.len() - metadata_start).unwrap();
+			data.extend_from_slice(&metadata_len.to_le_bytes());
+			data.extend_from_slice(&PARQUET_MAGIC);
+			data
+		}
+
+		#[test]
+		fn filter_eq_bloom_filter() {
+			let row_groups = (0..4)
+				.map(|i| (i * 100..(i + 1) * 100).collect())
+				.collect::<Vec<Vec<i64>>>();
+			let data: Rc<[u8]> = bloom_filtered_file(&row_groups).into();
+			let metadata =
+				SerializedFileReader::new(PassError::new(Ok(Cursor::new(data.to_vec()))))
+					.unwrap()
+					.metadata();
+			let chunk = |i: usize| {
+				let row_group = metadata.row_group(i);
+				let column = row_group.column(0);
+				let start = column
+					.dictionary_page_offset()
+					.unwrap_or_else(|| column.data_page_offset());
+				(
+					u64::try_from(start).unwrap(),
+					usize::try_from(column.compressed_size()).unwrap(),
+				)
+			};
+
+			let reads = Rc::new(RefCell::new(Vec::new()));
+			let page = InstrumentedPage {
+				data: data.clone(),
+				reads: reads.clone(),
+			};
+			let filters = [("id".to_owned(), Value::I64(250))];
+			let rows = block_on(read_filtered::<_, Group, IoError, IoError>(
+				page,
+				&filters,
+				None,
+				None,
+				Timezone::UTC,
+			))
+			.unwrap()
+			.map(|row| row.unwrap()[0].as_i64().unwrap())
+			.collect::<Vec<_>>();
+			assert_eq!(rows, row_groups[2]);
+
+			// The pages of the pruned row groups weren't read
+			let reads = reads.borrow();
+			assert!(reads.contains(&chunk(2)));
+			for i in [0, 1, 3].iter().copied() {
+				let (start, len) = chunk(i);
+				assert!(reads.iter().all(|&(offset, read_len)| {
+					offset + read_len as u64 <= start || offset >= start + len as u64
+				}));
+			}
+
+			// No rows are read for a value absent from every row group
+			let page = InstrumentedPage {
+				data: data.clone(),
+				reads: Rc::new(RefCell::new(Vec::new())),
+			};
+			let filters = [("id".to_owned(), Value::I64(1000))];
+			let rows = block_on(read_filtered::<_, Group, IoError, IoError>(
+				page,
+				&filters,
+				None,
+				None,
+				Timezone::UTC,
+			))
+			.unwrap()
+			.count();
+			assert_eq!(rows, 0);
+		}
+
+		#[test]
+		fn filter_by_schema_predicate() {
+			let row_groups = (0..4)
+				.map(|i| (i * 100..(i + 1) * 100).collect())
+				.collect::<Vec<Vec<i64>>>();
+			let data = bloom_filtered_file(&row_groups);
+			let path = env::temp_dir().join(format!("amadeus-predicate-{}.parquet", process::id()));
+			fs::write(&path, &data).unwrap();
+			let data: Rc<[u8]> = data.into();
+			// The number of rows of the row groups left once pruned by `predicate`
+			let unpruned = |predicate: &Predicate| {
+				let page = InstrumentedPage {
+					data: data.clone(),
+					reads: Rc::new(RefCell::new(Vec::new())),
+				};
+				block_on(read_filtered::<_, Group, IoError, IoError>(
+					page,
+					&predicate.equalities(),
+					None,
+					None,
+					Timezone::UTC,
+				))
+				.unwrap()
+				.count()
+			};
+			// The ids of the rows the source yields
+			let read = |predicates: Vec<Predicate>| {
+				let mut parquet = block_on(Parquet::<_, Group>::new(path.clone())).unwrap();
+				for predicate in predicates {
+					parquet = parquet.filter_by_schema_predicate(predicate);
+				}
+				let stream = parquet.dist_stream();
+				pin_mut!(stream);
+				let mut rows = Vec::new();
+				while let Some(task) = block_on(future::poll_fn(|cx| stream.as_mut().next_task(cx)))
+				{
+					rows.extend(block_on(
+						task.into_async()
+							.map(|row| row.unwrap()[0].as_i64().unwrap())
+							.collect::<Vec<_>>(),
+					));
+				}
+				rows
+			};
+
+			// The equality is pushed down, pruning all but the third row group, and
+			// the source filters the rows of that by the whole predicate
+			let pushdown = Expr::col("id").equals(250_i64).and(Expr::col("id").gt(200));
+			assert_eq!(unpruned(&pushdown), 100);
+			assert_eq!(read(vec![pushdown]), vec![250]);
+
+			// Nothing is pruned without an equality, but rows are still filtered
+			let in_stream = Expr::col("id").gt(250).and(Expr::col("id").lt(260));
+			assert_eq!(unpruned(&in_stream), 400);
+			assert_eq!(read(vec![in_stream]), (251..260).collect::<Vec<_>>());
+			let disjunction = Expr::col("id").equals(5).or(Expr::col("id").equals(305));
+			assert_eq!(unpruned(&disjunction), 400);
+			assert_eq!(read(vec![disjunction]), vec![5, 305]);
+
+			// Rows must match every predicate given
+			let predicates = vec![Expr::col("id").gt(100), Expr::col("id").lt(103)];
+			assert_eq!(read(predicates), vec![101, 102]);
+			fs::remove_file(&path).unwrap();
+		}
+
+		#[test]
+		fn coalesce_row_groups() {
+			let row_groups = (0..40)
+				.map(|i| (i * 25..(i + 1) * 25).collect())
+				.collect::<Vec<Vec<i64>>>();
+			let path = env::temp_dir().join(format!("amadeus-coalesce-{}.parquet", process::id()));
+			fs::write(&path, bloom_filtered_file(&row_groups)).unwrap();
+			let metadata = SerializedFileReader::new(fs::File::open(&path).unwrap())
+				.unwrap()
+				.metadata();
+			let row_group_bytes = metadata
+				.row_group(0)
+				.columns()
+				.iter()
+				.map(|column| u64::try_from(column.compressed_size()).unwrap())
+				.sum::<u64>();
+
+			let parquet = block_on(Parquet::<_, Group>::new(path.clone())).unwrap();
+			assert_eq!(block_on(parquet.metadata()).unwrap().num_row_groups, 40);
+			let parquet = block_on(parquet.coalesce_row_groups(10 * row_group_bytes)).unwrap();
+			let splits = parquet.splits.unwrap();
+			assert!(splits.len() > 1 && splits.len() <= 4, "{:?}", splits);
+
+			// The splits cover every row group exactly once, in order
+			let mut next = 0;
+			let mut rows = Vec::new();
+			for split in splits {
+				let (page, range) = split.row_groups.unwrap();
+				assert_eq!((page, range.start), (0, next));
+				next = range.end;
+				let page = block_on(split.partition.pages()).unwrap().remove(page);
+				rows.extend(
+					block_on(read_filtered::<_, Group, IoError, IoError>(
+						page,
+						&[],
+						Some(range),
+						None,
+						Timezone::UTC,
+					))
+					.unwrap()
+					.map(|row| row.unwrap()[0].as_i64().unwrap()),
+				);
+			}
+			assert_eq!(next, 40);
+			assert_eq!(rows, (0..1000).collect::<Vec<_>>());
+			fs::remove_file(&path).unwrap();
+		}
+
+		#[test]
+		fn row_range() {
+			let row_groups = (0..10)
+				.map(|i| (i * 100..(i + 1) * 100).collect())
+				.collect::<Vec<Vec<i64>>>();
+			let data = bloom_filtered_file(&row_groups);
+			let path = env::temp_dir().join(format!("amadeus-row-range-{}.parquet", process::id()));
+			fs::write(&path, &data).unwrap();
+			let data: Rc<[u8]> = data.into();
+			let metadata =
+				SerializedFileReader::new(PassError::new(Ok(Cursor::new(data.to_vec()))))
+					.unwrap()
+					.metadata();
+			let chunk = |i: usize| {
+				let row_group = metadata.row_group(i);
+				let column = row_group.column(0);
+				let start = column
+					.dictionary_page_offset()
+					.unwrap_or_else(|| column.data_page_offset());
+				(
+					u64::try_from(start).unwrap(),
+					usize::try_from(column.compressed_size()).unwrap(),
+				)
+			};
+
+			// A range starting and ending mid row group spans just those it overlaps
+			let parquet = block_on(Parquet::<_, Group>::new(path.clone())).unwrap();
+			let parquet = block_on(parquet.row_range(250, 170)).unwrap();
+			let mut splits = parquet.splits.unwrap();
+			assert_eq!(splits.len(), 1);
+			let split = splits.remove(0);
+			assert_eq!(split.row_groups, Some((0, 2..5)));
+			assert_eq!(split.rows, Some(50..220));
+
+			let reads = Rc::new(RefCell::new(Vec::new()));
+			let page = InstrumentedPage {
+				data: data.clone(),
+				reads: reads.clone(),
+			};
+			let (_, row_groups_range) = split.row_groups.unwrap();
+			let rows = block_on(read_filtered::<_, Group, IoError, IoError>(
+				page,
+				&[],
+				Some(row_groups_range),
+				split.rows,
+				Timezone::UTC,
+			))
+			.unwrap()
+			.map(|row| row.unwrap()[0].as_i64().unwrap())
+			.collect::<Vec<_>>();
+			assert_eq!(rows, (250..420).collect::<Vec<_>>());
+
+			// The pages of the row groups outside the range weren't read
+			let reads = reads.borrow();
+			for i in 2..5 {
+				assert!(reads.contains(&chunk(i)));
+			}
+			for i in (0..2).chain(5..10) {
+				let (start, len) = chunk(i);
+				assert!(reads.iter().all(|&(offset, read_len)| {
+					offset + read_len as u64 <= start || offset >= start + len as u64
+				}));
+			}
+
+			// A range past the end is empty
+			let parquet = block_on(Parquet::<_, Group>::new(path.clone())).unwrap();
+			let parquet = block_on(parquet.row_range(1000, 10)).unwrap();
+			assert!(parquet.splits.unwrap().is_empty());
+			fs::remove_file(&path).unwrap();
+		}
+
+		#[test]
+		fn int96_timezone() {
+			let path = env::temp_dir().join(format!("amadeus-int96-{}.parquet", process::id()));
+			let schema = Rc::new(
+				types::Type::group_type_builder("schema")
+					.with_fields(&mut vec![Rc::new(
+						types::Type::primitive_type_builder("timestamp", PhysicalType::Int96)
+							.with_repetition(Repetition::Required)
+							.build()
+							.unwrap(),
+					)])
+					.build()
+					.unwrap(),
+			);
+			let props = Rc::new(WriterProperties::builder().build());
+			let mut file_writer =
+				SerializedFileWriter::new(fs::File::create(&path).unwrap(), schema, props).unwrap();
+			// 2020-01-01T12:34:56.789 in each of two row groups
+			for _ in 0..2 {
+				let mut row_group_writer = file_writer.next_row_group().unwrap();
+				let mut writer = row_group_writer.next_column().unwrap().unwrap();
+				match writer {
+					ColumnWriter::Int96ColumnWriter(ref mut typed) => {
+						let value = Int96::new(2063896384, 10546, 2458850);
+						let _ = typed.write_batch(&[value], None, None).unwrap();
+					}
+					_ => unreachable!(),
+				}
+				row_group_writer.close_column(writer).unwrap();
+				file_writer.close_row_group(row_group_writer).unwrap();
+			}
+			file_writer.close().unwrap();
+			let data = fs::read(&path).unwrap();
+			fs::remove_file(&path).unwrap();
+
+			let reader =
+				|| SerializedFileReader::new(PassError::new(Ok(Cursor::new(data.clone()))));
+			let millis = |row: Result<Group, _>| {
+				let row = row.unwrap();
+				row[0]
+					.as_date_time()
+					.unwrap()
+					.as_chrono()
+					.unwrap()
+					.timestamp_millis()
+			};
+			let rows = reader()
+				.unwrap()
+				.get_row_iter::<Group>(None)
+				.unwrap()
+				.map(millis)
+				.collect::<Vec<_>>();
+			assert_eq!(rows, [1577882096789; 2]);
+
+			// Row groups are read lazily, after the timezone has been restored
+			let new_york = Timezone::from_name("America/New_York").unwrap();
+			let reader = reader().unwrap();
+			let rows = with_int96_timezone(new_york, || reader.get_row_iter::<Group>(None))
+				.unwrap()
+				.map(millis)
+				.collect::<Vec<_>>();
+			assert_eq!(rows, [1577900096789; 2]);
+
+			let page = InstrumentedPage {
+				data: data.into(),
+				reads: Rc::new(RefCell::new(Vec::new())),
+			};
+			let rows = block_on(read_filtered::<_, Group, IoError, IoError>(
+				page,
+				&[],
+				Some(0..2),
+				None,
+				new_york,
+			))
+			.unwrap()
+			.map(millis)
+			.collect::<Vec<_>>();
+			assert_eq!(rows, [1577900096789; 2]);
+		}
+	}
 }
 #[cfg(nightly)]
 pub use wrap::*;
@@ -1,12 +1,12 @@
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use postgres::types::{FromSql, Type, WasNull};
 use std::{
-	collections::HashMap, error::Error, fmt::{self, Display}, hash::{BuildHasher, Hash}
+	collections::HashMap, convert::TryInto, error::Error, fmt::{self, Display}, hash::{BuildHasher, Hash}
 };
 
 use super::{Names, PostgresData};
 use amadeus_types::{
-	Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Enum, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, Value, Webpage
+	Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Duration, Enum, GeoPoint, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, Uuid, Value, Webpage
 };
 
 impl<T> PostgresData for Box<T>
@@ -126,6 +126,46 @@ impl PostgresData for IpAddr {
 	}
 }
 
+impl PostgresData for GeoPoint {
+	fn query(f: &mut fmt::Formatter, name: Option<&Names<'_>>) -> fmt::Result {
+		name.unwrap().fmt(f)
+	}
+	fn decode(type_: &Type, buf: Option<&[u8]>) -> Result<Self, Box<dyn Error + Sync + Send>> {
+		if type_ != &Type::POINT {
+			return Err(Into::into("invalid type"));
+		}
+		let buf = buf.ok_or_else(|| Box::new(WasNull))?;
+		if buf.len() != 16 {
+			return Err(Into::into("invalid point"));
+		}
+		// x is the longitude and y the latitude, each a big-endian f64
+		let mut x = [0; 8];
+		let mut y = [0; 8];
+		x.copy_from_slice(&buf[..8]);
+		y.copy_from_slice(&buf[8..]);
+		GeoPoint::new(f64::from_be_bytes(y), f64::from_be_bytes(x))
+			.ok_or_else(|| Into::into("invalid point"))
+	}
+}
+
+impl PostgresData for Uuid {
+	fn query(f: &mut fmt::Formatter, name: Option<&Names<'_>>) -> fmt::Result {
+		name.unwrap().fmt(f)
+	}
+	fn decode(type_: &Type, buf: Option<&[u8]>) -> Result<Self, Box<dyn Error + Sync + Send>> {
+		if type_ != &Type::UUID {
+			return Err(Into::into("invalid type"));
+		}
+		let buf = buf.ok_or_else(|| Box::new(WasNull))?;
+		if buf.len() != 16 {
+			return Err(Into::into("invalid uuid"));
+		}
+		let mut bytes = [0; 16];
+		bytes.copy_from_slice(buf);
+		Ok(Self::from_bytes(bytes))
+	}
+}
+
 impl PostgresData for Decimal {
 	fn query(f: &mut fmt::Formatter, name: Option<&Names<'_>>) -> fmt::Result {
 		name.unwrap().fmt(f)
@@ -244,6 +284,29 @@ impl PostgresData for DateTimeWithoutTimezone {
 	}
 }
 
+/// Decodes `interval`, whose binary format is microseconds, days and months.
+impl PostgresData for Duration {
+	fn query(f: &mut fmt::Formatter, name: Option<&Names<'_>>) -> fmt::Result {
+		name.unwrap().fmt(f)
+	}
+	fn decode(type_: &Type, buf: Option<&[u8]>) -> Result<Self, Box<dyn Error + Sync + Send>> {
+		if *type_ != Type::INTERVAL {
+			return Err(Into::into("invalid type"));
+		}
+		let buf = buf.ok_or_else(|| Box::new(WasNull))?;
+		if buf.len() != 16 {
+			return Err(Into::into("invalid buffer size"));
+		}
+		let micros = i64::from_be_bytes(buf[..8].try_into().unwrap());
+		let days = i32::from_be_bytes(buf[8..12].try_into().unwrap());
+		let months = i32::from_be_bytes(buf[12..].try_into().unwrap());
+		micros
+			.checked_mul(1000)
+			.and_then(|nanos| Self::new(months.into(), days.into(), nanos))
+			.ok_or_else(|| Into::into("interval overflows Duration"))
+	}
+}
+
 impl PostgresData for Timezone {
 	fn query(f: &mut fmt::Formatter, name: Option<&Names<'_>>) -> fmt::Result {
 		name.unwrap().fmt(f)
@@ -0,0 +1,273 @@
+//! Harmonious distributed data processing & analysis in Rust.
+//!
+//! <p style="font-family: 'Fira Sans',sans-serif;padding:0.3em 0"><strong>
+//! <a href="https://crates.io/crates/amadeus">📦&nbsp;&nbsp;Crates.io</a>&nbsp;&nbsp;│&nbsp;&nbsp;<a href="https://github.com/constellation-rs/amadeus">📑&nbsp;&nbsp;GitHub</a>&nbsp;&nbsp;│&nbsp;&nbsp;<a href="https://constellation.zulipchat.com/#narrow/stream/213231-amadeus">💬&nbsp;&nbsp;Chat</a>
+//! </strong></p>
+//!
+//! This is a support crate of [Amadeus](https://github.com/constellation-rs/amadeus) and is not intended to be used directly. These types are re-exposed in [`amadeus::source`](https://docs.rs/amadeus/0.3/amadeus/source/index.html).
+
+#![doc(html_root_url = "https://docs.rs/amadeus-redis/0.4.2")]
+#![warn(
+	// missing_copy_implementations,
+	// missing_debug_implementations,
+	// missing_docs,
+	trivial_numeric_casts,
+	unused_import_braces,
+	unused_qualifications,
+	unused_results,
+	unreachable_pub,
+	clippy::pedantic,
+)]
+#![allow(
+	clippy::module_name_repetitions,
+	clippy::similar_names,
+	clippy::if_not_else,
+	clippy::must_use_candidate,
+	clippy::missing_errors_doc,
+	clippy::missing_panics_doc
+)]
+#![deny(unsafe_code)]
+
+mod sink;
+
+#[doc(hidden)]
+pub use redis as _internal;
+
+pub use sink::{RedisSet, RedisSetReducer, RedisSetReducerAsync};
+
+use educe::Educe;
+use futures::{stream, FutureExt, Stream, StreamExt};
+use redis::{
+	aio::Connection, ConnectionInfo, IntoConnectionInfo, RedisError as InternalRedisError
+};
+use serde::{Deserialize, Serialize};
+use serde_closure::FnMutNamed;
+use std::{
+	collections::{HashMap, HashSet}, error, fmt::{self, Debug, Display}, marker::PhantomData, pin::Pin, vec
+};
+
+use amadeus_core::{
+	into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::DistParStream, Source
+};
+use amadeus_types::{Downcast, DowncastFrom, List, Value};
+
+const DEFAULT_COUNT: usize = 1000;
+
+/// Types that the value of a Redis key can be read as.
+pub trait RedisData: DowncastFrom<Value> + Send + 'static {}
+impl<T> RedisData for T where T: DowncastFrom<Value> + Send + 'static {}
+
+/// Where and how to connect to a Redis server.
+#[derive(Educe, Serialize, Deserialize)]
+#[educe(Clone, Debug)]
+pub struct RedisConfig {
+	url: String,
+	#[educe(Debug(ignore))]
+	password: Option<String>,
+}
+impl RedisConfig {
+	/// Connect to `url`, e.g. `redis://127.0.0.1:6379/0`, authenticating with
+	/// `password` if given. A password in `url` is overridden by `password`.
+	pub fn new(url: &str, password: Option<&str>) -> Self {
+		Self {
+			url: url.to_owned(),
+			password: password.map(ToOwned::to_owned),
+		}
+	}
+	async fn connect(&self) -> Result<Connection, RedisError> {
+		let mut info: ConnectionInfo = self.url.as_str().into_connection_info()?;
+		if let Some(password) = &self.password {
+			info.passwd = Some(password.clone());
+		}
+		Ok(redis::Client::open(info)?.get_async_connection().await?)
+	}
+}
+
+/// The keys matching a pattern in a Redis database, along with their values.
+///
+/// The keys are enumerated with `SCAN` when this is constructed, with each page
+/// of keys returned becoming a partition. Each partition then fetches its values
+/// with two pipelined round trips: one for the `TYPE` of each of its keys, and
+/// one to `GET` or `HGETALL` them.
+///
+/// String values are read as [`Value::String`], or as `List<u8>` if they
+/// aren't valid UTF-8. Hashes are read as a [`Value::Map`] of their fields,
+/// such as a `HashMap<String, String>`. Keys deleted since being scanned are
+/// skipped.
+#[derive(Educe)]
+#[educe(Clone, Debug)]
+pub struct Redis<Row> {
+	config: RedisConfig,
+	partitions: Vec<Vec<String>>,
+	marker: PhantomData<fn() -> Row>,
+}
+impl<Row> Redis<Row> {
+	pub async fn new(config: RedisConfig, pattern: &str) -> Result<Self, RedisError> {
+		Self::new_with(config, pattern, DEFAULT_COUNT).await
+	}
+	/// Scan the keys matching `pattern`, passing `count` as the `COUNT` hint of
+	/// each `SCAN`, which roughly bounds the size of each partition.
+	pub async fn new_with(
+		config: RedisConfig, pattern: &str, count: usize,
+	) -> Result<Self, RedisError> {
+		let mut connection = config.connect().await?;
+		let mut partitions = Vec::new();
+		// SCAN can return a key more than once
+		let mut seen = HashSet::new();
+		let mut cursor = 0_u64;
+		loop {
+			let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+				.arg(cursor)
+				.arg("MATCH")
+				.arg(pattern)
+				.arg("COUNT")
+				.arg(count)
+				.query_async(&mut connection)
+				.await?;
+			let keys = keys
+				.into_iter()
+				.filter(|key| seen.insert(key.clone()))
+				.collect::<Vec<_>>();
+			if !keys.is_empty() {
+				partitions.push(keys);
+			}
+			if next == 0 {
+				break;
+			}
+			cursor = next;
+		}
+		Ok(Self {
+			config,
+			partitions,
+			marker: PhantomData,
+		})
+	}
+}
+
+type Output<Row> = Pin<Box<dyn Stream<Item = Result<(String, Row), RedisError>> + Send>>;
+
+FnMutNamed! {
+	pub type Closure<Row> = |self, config: RedisConfig|keys=> Vec<String>| -> Output<Row>
+	where
+		Row: RedisData
+	{
+		let config = self.config.clone();
+		async move {
+			let rows = read(&config, keys)
+				.await
+				.unwrap_or_else(|err| vec![Err(err)]);
+			stream::iter(rows)
+		}
+		.flatten_stream()
+		.boxed()
+	}
+}
+
+#[allow(clippy::type_complexity)]
+async fn read<Row>(
+	config: &RedisConfig, keys: Vec<String>,
+) -> Result<Vec<Result<(String, Row), RedisError>>, RedisError>
+where
+	Row: RedisData,
+{
+	let mut connection = config.connect().await?;
+	let mut pipe = redis::pipe();
+	for key in &keys {
+		let _ = pipe.cmd("TYPE").arg(key);
+	}
+	let types: Vec<String> = pipe.query_async(&mut connection).await?;
+	let mut pipe = redis::pipe();
+	let mut fetched = Vec::new();
+	for (key, type_) in keys.into_iter().zip(types) {
+		match &*type_ {
+			"string" => {
+				let _ = pipe.cmd("GET").arg(&key);
+			}
+			"hash" => {
+				let _ = pipe.cmd("HGETALL").arg(&key);
+			}
+			"none" => continue,
+			_ => return Err(RedisError::Unsupported(format!("{} at key {}", type_, key))),
+		}
+		fetched.push((key, type_));
+	}
+	let values: Vec<redis::Value> = pipe.query_async(&mut connection).await?;
+	let mut rows = Vec::with_capacity(values.len());
+	for ((key, type_), value) in fetched.into_iter().zip(values) {
+		let value = if type_ == "string" {
+			match redis::from_redis_value::<Option<Vec<u8>>>(&value)? {
+				Some(value) => decode(value),
+				None => continue,
+			}
+		} else {
+			let fields: Vec<(Vec<u8>, Vec<u8>)> = redis::from_redis_value(&value)?;
+			if fields.is_empty() {
+				continue;
+			}
+			Value::Map(
+				fields
+					.into_iter()
+					.map(|(field, value)| (decode(field), decode(value)))
+					.collect::<HashMap<_, _>>(),
+			)
+		};
+		rows.push(
+			Downcast::<Row>::downcast(value)
+				.map(|row| (key, row))
+				.map_err(|err| RedisError::Downcast(err.to_string())),
+		);
+	}
+	Ok(rows)
+}
+
+fn decode(value: Vec<u8>) -> Value {
+	match String::from_utf8(value) {
+		Ok(value) => Value::String(value),
+		Err(err) => List::<u8>::from(err.into_bytes()).into(),
+	}
+}
+
+impl<Row> Source for Redis<Row>
+where
+	Row: RedisData,
+{
+	type Item = (String, Row);
+	type Error = RedisError;
+
+	type ParStream = DistParStream<Self::DistStream>;
+	#[allow(clippy::type_complexity)]
+	type DistStream = amadeus_core::par_stream::FlatMap<
+		amadeus_core::into_par_stream::IterDistStream<vec::IntoIter<Vec<String>>>,
+		Closure<Row>,
+	>;
+
+	fn par_stream(self) -> Self::ParStream {
+		DistParStream::new(self.dist_stream())
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		self.partitions
+			.into_dist_stream()
+			.flat_map(Closure::new(self.config))
+	}
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub enum RedisError {
+	Redis(String),
+	Downcast(String),
+	Unsupported(String),
+}
+impl error::Error for RedisError {}
+impl Display for RedisError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Redis(err) | Self::Downcast(err) => Display::fmt(err, f),
+			Self::Unsupported(value) => write!(f, "unsupported value: {}", value),
+		}
+	}
+}
+impl From<InternalRedisError> for RedisError {
+	fn from(err: InternalRedisError) -> Self {
+		Self::Redis(err.to_string())
+	}
+}
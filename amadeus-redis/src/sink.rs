@@ -0,0 +1,255 @@
+use futures::{future::LocalBoxFuture, ready, FutureExt, Stream};
+use redis::{aio::Connection, Pipeline};
+use serde::{Deserialize, Serialize};
+use std::{
+	mem, pin::Pin, task::{Context, Poll}
+};
+
+use amadeus_core::{
+	par_sink::{
+		DistributedSink, FromDistributedStream, FromParallelStream, ParallelSink, Reducer, ReducerSend
+	}, par_stream::Identity, pipe::Sink
+};
+use amadeus_types::Value;
+
+use super::{RedisConfig, RedisError};
+
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// A sink that writes `(key, value)` pairs to a Redis database.
+///
+/// Maps and [`Group`](amadeus_types::Group)s with named fields are written to a
+/// hash with `HSET`, omitting fields that are `None`; other values are written
+/// with `SET`, and `None` deletes the key. Each reducer sends its writes in
+/// pipelined batches of up to `batch_size`, so that each batch needs only a
+/// single round trip.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RedisSet {
+	config: RedisConfig,
+	batch_size: usize,
+}
+impl RedisSet {
+	pub fn new(config: RedisConfig) -> Self {
+		Self::new_with(config, DEFAULT_BATCH_SIZE)
+	}
+	pub fn new_with(config: RedisConfig, batch_size: usize) -> Self {
+		assert_ne!(batch_size, 0, "batch_size must be nonzero");
+		Self { config, batch_size }
+	}
+}
+
+impl<K, V> ParallelSink<(K, V)> for RedisSet
+where
+	K: Into<String>,
+	V: Into<Value>,
+{
+	type Done = Result<(), RedisError>;
+	type Pipe = Identity;
+	type ReduceA = RedisSetReducer;
+	type ReduceC = <Result<(), RedisError> as FromParallelStream<Result<(), RedisError>>>::ReduceC;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		let (_, reduce_c) =
+			<Result<(), RedisError> as FromParallelStream<Result<(), RedisError>>>::reducers();
+		(Identity, RedisSetReducer(self), reduce_c)
+	}
+}
+impl<K, V> DistributedSink<(K, V)> for RedisSet
+where
+	K: Into<String>,
+	V: Into<Value>,
+{
+	type Done = Result<(), RedisError>;
+	type Pipe = Identity;
+	type ReduceA = RedisSetReducer;
+	type ReduceB =
+		<Result<(), RedisError> as FromDistributedStream<Result<(), RedisError>>>::ReduceB;
+	type ReduceC =
+		<Result<(), RedisError> as FromDistributedStream<Result<(), RedisError>>>::ReduceC;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		let (_, reduce_b, reduce_c) =
+			<Result<(), RedisError> as FromDistributedStream<Result<(), RedisError>>>::reducers();
+		(Identity, RedisSetReducer(self), reduce_b, reduce_c)
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RedisSetReducer(RedisSet);
+impl<K, V> Reducer<(K, V)> for RedisSetReducer
+where
+	K: Into<String>,
+	V: Into<Value>,
+{
+	type Done = Result<(), RedisError>;
+	type Async = RedisSetReducerAsync;
+
+	fn into_async(self) -> Self::Async {
+		RedisSetReducerAsync {
+			set: self.0,
+			connection: None,
+			pipe: redis::pipe(),
+			len: 0,
+			pending: None,
+			done: false,
+			result: Some(Ok(())),
+		}
+	}
+}
+impl<K, V> ReducerSend<(K, V)> for RedisSetReducer
+where
+	K: Into<String>,
+	V: Into<Value>,
+{
+	type Done = Result<(), RedisError>;
+}
+
+pub struct RedisSetReducerAsync {
+	set: RedisSet,
+	connection: Option<Connection>,
+	pipe: Pipeline,
+	len: usize,
+	pending: Option<LocalBoxFuture<'static, Result<Connection, RedisError>>>,
+	done: bool,
+	result: Option<Result<(), RedisError>>,
+}
+impl RedisSetReducerAsync {
+	fn push(&mut self, key: String, value: Value) -> Result<(), RedisError> {
+		match value {
+			Value::Map(map) => {
+				let fields = map
+					.into_iter()
+					.map(|(field, value)| Ok(encode(field)?.zip(encode(value)?)))
+					.filter_map(Result::transpose)
+					.collect::<Result<Vec<_>, RedisError>>()?;
+				self.hset(key, fields);
+			}
+			Value::Group(group) if group.field_names().is_some() => {
+				let names = group.field_names().unwrap().clone();
+				let fields = names
+					.keys()
+					.cloned()
+					.zip(group.into_fields())
+					.map(|(field, value)| {
+						Ok(encode(value)?.map(|value| (field.into_bytes(), value)))
+					})
+					.filter_map(Result::transpose)
+					.collect::<Result<Vec<_>, RedisError>>()?;
+				self.hset(key, fields);
+			}
+			value => {
+				let _ = match encode(value)? {
+					Some(value) => self.pipe.cmd("SET").arg(key).arg(value).ignore(),
+					None => self.pipe.cmd("DEL").arg(key).ignore(),
+				};
+			}
+		}
+		self.len += 1;
+		if self.len == self.set.batch_size {
+			self.flush();
+		}
+		Ok(())
+	}
+	/// Null fields are omitted, and a hash without any fields isn't written.
+	fn hset(&mut self, key: String, fields: Vec<(Vec<u8>, Vec<u8>)>) {
+		if fields.is_empty() {
+			return;
+		}
+		let cmd = self.pipe.cmd("HSET").arg(key);
+		for (field, value) in fields {
+			let _ = cmd.arg(field).arg(value);
+		}
+		let _ = cmd.ignore();
+	}
+	/// Send the batch, with the connection handed back once it's written.
+	fn flush(&mut self) {
+		let pipe = mem::replace(&mut self.pipe, redis::pipe());
+		if mem::take(&mut self.len) == 0 {
+			return;
+		}
+		let connection = self.connection.take();
+		let config = self.set.config.clone();
+		self.pending = Some(
+			async move {
+				let mut connection = match connection {
+					Some(connection) => connection,
+					None => config.connect().await?,
+				};
+				pipe.query_async::<_, ()>(&mut connection).await?;
+				Ok(connection)
+			}
+			.boxed_local(),
+		);
+	}
+}
+impl<K, V> Sink<(K, V)> for RedisSetReducerAsync
+where
+	K: Into<String>,
+	V: Into<Value>,
+{
+	type Done = Result<(), RedisError>;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = (K, V)>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.get_mut();
+		loop {
+			if let Some(pending) = &mut self_.pending {
+				let res = ready!(pending.as_mut().poll(cx));
+				self_.pending = None;
+				match res {
+					Ok(connection) => self_.connection = Some(connection),
+					Err(err) => {
+						if let Some(Ok(())) = self_.result {
+							self_.result = Some(Err(err));
+						}
+					}
+				}
+			}
+			if self_.done {
+				break Poll::Ready(self_.result.take().unwrap());
+			}
+			match ready!(stream.as_mut().poll_next(cx)) {
+				// Once a batch has failed, drain the stream without writing the rest
+				Some((key, value)) if matches!(self_.result, Some(Ok(()))) => {
+					if let Err(err) = self_.push(key.into(), value.into()) {
+						self_.result = Some(Err(err));
+					}
+				}
+				Some(_) => (),
+				None => {
+					if matches!(self_.result, Some(Ok(()))) {
+						self_.flush();
+					}
+					self_.done = true;
+				}
+			}
+		}
+	}
+}
+
+/// Encode a value as the bytes of a Redis string, or `None` if it's null.
+fn encode(value: Value) -> Result<Option<Vec<u8>>, RedisError> {
+	Ok(Some(match value {
+		Value::Bool(value) => value.to_string().into_bytes(),
+		Value::U8(value) => value.to_string().into_bytes(),
+		Value::I8(value) => value.to_string().into_bytes(),
+		Value::U16(value) => value.to_string().into_bytes(),
+		Value::I16(value) => value.to_string().into_bytes(),
+		Value::U32(value) => value.to_string().into_bytes(),
+		Value::I32(value) => value.to_string().into_bytes(),
+		Value::U64(value) => value.to_string().into_bytes(),
+		Value::I64(value) => value.to_string().into_bytes(),
+		Value::F32(value) => value.to_string().into_bytes(),
+		Value::F64(value) => value.to_string().into_bytes(),
+		Value::String(value) => value.into_bytes(),
+		Value::List(list) => list
+			.into_iter()
+			.map(|value| value.into_u8().ok())
+			.collect::<Option<_>>()
+			.ok_or_else(|| RedisError::Unsupported(String::from("a list of non-u8 values")))?,
+		Value::Option(None) => return Ok(None),
+		Value::Option(Some(value)) => return encode(value.into()),
+		value => return Err(RedisError::Unsupported(format!("{:?}", value))),
+	}))
+}
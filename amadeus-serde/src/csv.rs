@@ -2,16 +2,18 @@ use csv::Error as InternalCsvError;
 use educe::Educe;
 use futures::{pin_mut, stream, AsyncReadExt, FutureExt, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use serde_closure::FnMutNamed;
+use serde_closure::{traits::FnMut, FnMutNamed};
 use std::{
-	error, fmt::{self, Display}, io::Cursor, marker::PhantomData
+	any::Any, collections::HashMap, error, fmt::{self, Display}, io::{self, Cursor}, marker::PhantomData, mem
 };
 
 use amadeus_core::{
-	file::{File, Page, Partition}, into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::{DistParStream, ResultExpandIter}, Source
+	file::{File, Page, Partition}, into_par_stream::IntoDistributedStream, par_sink::Encoder, par_stream::DistributedStream, pool::ProcessSend, util::{DistParStream, ResultExpandIter}, Source
 };
 
-use super::{SerdeData, SerdeDeserializeGroup};
+use amadeus_types::{Group, Schema, Value};
+
+use super::{SerdeData, SerdeDeserializeGroup, SerdeSerialize, READ_CHUNK_SIZE};
 
 // #[doc(inline)]
 // pub type Trim = csv::Trim;
@@ -59,6 +61,8 @@ where
 	// double_quote: bool,
 	// quoting: bool,
 	// comment: Option<u8>,
+	null_tokens: NullTokens,
+	read_chunk_size: usize,
 	partitions: Vec<File::Partition>,
 	marker: PhantomData<fn() -> Row>,
 }
@@ -68,13 +72,178 @@ where
 	Row: SerdeData,
 {
 	pub async fn new(file: F) -> Result<Self, <Self as Source>::Error> {
+		Self::new_with(file, NullTokens::new()).await
+	}
+	pub async fn new_with(
+		file: F, null_tokens: NullTokens,
+	) -> Result<Self, <Self as Source>::Error> {
 		Ok(Self {
+			null_tokens,
+			read_chunk_size: READ_CHUNK_SIZE,
 			partitions: file.partitions().await.map_err(CsvError::File)?,
 			marker: PhantomData,
 		})
 	}
+	/// Read pages `read_chunk_size` bytes at a time, rather than the default of
+	/// 10 MiB. Larger reads mean fewer requests to high-latency sources like S3.
+	///
+	/// # Panics
+	///
+	/// Panics if `read_chunk_size` is zero.
+	#[must_use]
+	pub fn read_chunk_size(mut self, read_chunk_size: usize) -> Self {
+		assert_ne!(read_chunk_size, 0, "read_chunk_size must be non-zero");
+		self.read_chunk_size = read_chunk_size;
+		self
+	}
 	// pub fn open<Row>(files: Vec<PathBuf>) -> Csv<Row> {}
 	// pub fn create<Row>(files: Vec<PathBuf>) -> Csv<Row> {}
+	/// The schema of the rows, inferred by reading up to `sample` of them from
+	/// the start of the source as [`Value`]s, without consuming it. Columns are
+	/// unnamed, as CSV is read without a header row.
+	///
+	/// Each column is typed as the narrowest type that all of its sampled
+	/// fields parse as: a bool, integer or float, an ISO 8601 date or date-time,
+	/// with or without a timezone, or otherwise a string. Columns with null
+	/// tokens are optional.
+	pub async fn schema(&self, sample: usize) -> Result<Option<Schema>, <Self as Source>::Error> {
+		let mut rows = Vec::new();
+		for partition in self.partitions.iter().cloned() {
+			if rows.len() == sample {
+				break;
+			}
+			let partition =
+				Closure::<_, Value, F::Error>::new(self.null_tokens.clone(), self.read_chunk_size)
+					.call_mut((partition,));
+			pin_mut!(partition);
+			while rows.len() < sample {
+				match partition.next().await {
+					Some(row) => rows.push(typed(row?)),
+					None => break,
+				}
+			}
+		}
+		Ok(Schema::infer(rows))
+	}
+	/// Map each row with `f` as it's read, with its fields borrowed from the
+	/// buffer it was read into rather than copied into owned `String`s. `f` can
+	/// extract what it needs, or deserialize the row into a type with `&str` or
+	/// `Cow<str>` fields with [`CsvRecord::deserialize`], to avoid allocating
+	/// for fields it doesn't keep.
+	pub fn map_borrowed<G>(self, f: G) -> CsvMapBorrowed<F, G>
+	where
+		G: CsvRecordFn + Clone + ProcessSend + 'static,
+		G::Output: 'static,
+	{
+		CsvMapBorrowed {
+			null_tokens: self.null_tokens,
+			read_chunk_size: self.read_chunk_size,
+			partitions: self.partitions,
+			f,
+		}
+	}
+}
+
+/// `value` with the strings that the CSV reader doesn't type itself parsed as
+/// the temporal types they represent, so that [`Csv::schema`] infers those too.
+fn typed(value: Value) -> Value {
+	match value {
+		Value::Group(group) => {
+			let names = group.field_names().cloned();
+			Value::Group(Group::new(
+				group.into_fields().into_iter().map(typed).collect(),
+				names,
+			))
+		}
+		Value::Option(Some(value)) => Value::from(Some(typed(value.into()))),
+		Value::String(string) => string
+			.parse()
+			.map(Value::DateTime)
+			.or_else(|_| string.parse().map(Value::DateTimeWithoutTimezone))
+			.or_else(|_| string.parse().map(Value::DateWithoutTimezone))
+			.unwrap_or(Value::String(string)),
+		value => value,
+	}
+}
+
+/// The fields to read as null, by default none.
+///
+/// A null field is read as `None` for `Option` columns, and as
+/// `Value::Option(None)` for rows of [`Value`]. Tokens can be set for all
+/// columns, and overridden for individual columns by their index.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct NullTokens {
+	all: Vec<String>,
+	columns: HashMap<usize, Vec<String>>,
+}
+impl NullTokens {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Treat `tokens` as null in all columns without their own tokens.
+	#[must_use]
+	pub fn all<I>(mut self, tokens: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<String>,
+	{
+		self.all = tokens.into_iter().map(Into::into).collect();
+		self
+	}
+	/// Treat `tokens` as null in column `column`, instead of those set with
+	/// [`all`](Self::all).
+	#[must_use]
+	pub fn column<I>(mut self, column: usize, tokens: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<String>,
+	{
+		let _ = self
+			.columns
+			.insert(column, tokens.into_iter().map(Into::into).collect());
+		self
+	}
+	fn is_empty(&self) -> bool {
+		self.all.is_empty() && self.columns.values().all(Vec::is_empty)
+	}
+	fn is_null(&self, column: usize, field: &str) -> bool {
+		self.columns
+			.get(&column)
+			.unwrap_or(&self.all)
+			.iter()
+			.any(|token| token == field)
+	}
+}
+
+/// Deserialize a row, blanking its null fields first so that the `Option`
+/// columns they're in read as `None`.
+fn deserialize<Row: SerdeData>(
+	record: csv::StringRecord, null_tokens: &NullTokens,
+) -> Result<Row, InternalCsvError> {
+	if null_tokens.is_empty() {
+		return Ok(record.deserialize::<SerdeDeserializeGroup<Row>>(None)?.0);
+	}
+	let nulls = record
+		.iter()
+		.enumerate()
+		.map(|(i, field)| null_tokens.is_null(i, field))
+		.collect::<Vec<_>>();
+	let record = record
+		.iter()
+		.zip(&nulls)
+		.map(|(field, &null)| if null { "" } else { field })
+		.collect::<csv::StringRecord>();
+	let mut row = record.deserialize::<SerdeDeserializeGroup<Row>>(None)?.0;
+	// Dynamic rows read blank fields as empty strings, so set them to null
+	if let Some(Value::Group(group)) = (&mut row as &mut dyn Any).downcast_mut::<Value>() {
+		let names = group.field_names().cloned();
+		let mut fields = mem::replace(group, Group::new(Vec::new(), None)).into_fields();
+		for (field, _) in fields.iter_mut().zip(&nulls).filter(|(_, &null)| null) {
+			*field = Value::Option(None);
+		}
+		*group = Group::new(fields, names);
+	}
+	Ok(row)
 }
 
 type Error<P, E> = CsvError<E, <P as Partition>::Error, <<P as Partition>::Page as Page>::Error>;
@@ -84,54 +253,118 @@ type Output<P, Row: SerdeData, E> = std::pin::Pin<Box<dyn Stream<Item = Result<R
 type Output<P: Partition, Row: SerdeData, E> = impl Stream<Item = Result<Row, Error<P, E>>>;
 
 FnMutNamed! {
-	pub type Closure<P, Row, E> = |self|partition=> P| -> Output<P, Row, E>
+	pub type Closure<P, Row, E> = |self, null_tokens: NullTokens, read_chunk_size: usize|partition=> P| -> Output<P, Row, E>
 	where
 		P: Partition,
 		Row: SerdeData,
 		E: 'static
 	{
+		let null_tokens = self.null_tokens.clone();
 		#[allow(clippy::let_and_return)]
-		let ret = async move {
-				Ok(stream::iter(
-					partition
-						.pages()
-						.await
-						.map_err(CsvError::Partition)?
-						.into_iter(),
-				)
-				.flat_map(|page| {
-					async move {
-						let mut buf = Vec::with_capacity(10 * 1024 * 1024);
-						let reader = Page::reader(page);
-						pin_mut!(reader);
-						let _ = reader
-							.read_to_end(&mut buf)
-							.await
-							.map_err(InternalCsvError::from)?;
-						Ok(stream::iter(
-							csv::ReaderBuilder::new()
-								.has_headers(false)
-								.from_reader(Cursor::new(buf))
-								.into_deserialize()
-								.map(|x: Result<SerdeDeserializeGroup<Row>, InternalCsvError>| {
-									Ok(x?.0)
-								}),
-						))
-					}
-					.map(ResultExpandIter::new)
-					.flatten_stream()
-				})
-				.map(|row: Result<Result<Row, InternalCsvError>, Error<P, E>>| Ok(row??)))
-			}
-			.map(ResultExpandIter::new)
-			.flatten_stream()
-			.map(|row: Result<Result<Row, Error<P, E>>, Error<P, E>>| Ok(row??));
+		let ret = read_pages(partition, self.read_chunk_size, move |buf| {
+			let null_tokens = null_tokens.clone();
+			csv::ReaderBuilder::new()
+				.has_headers(false)
+				.from_reader(Cursor::new(buf))
+				.into_records()
+				.map(move |record| deserialize(record?, &null_tokens))
+		});
 		#[cfg(not(nightly))]
 		let ret = ret.boxed_local();
 		ret
 	}
 }
 
+/// Read each page of `partition` `chunk_size` bytes at a time, streaming the
+/// rows `parse` parses from each run of whole records read.
+fn read_pages<P, T, E, I>(
+	partition: P, chunk_size: usize, mut parse: impl std::ops::FnMut(Vec<u8>) -> I + 'static,
+) -> impl Stream<Item = Result<T, Error<P, E>>>
+where
+	P: Partition,
+	I: Iterator<Item = Result<T, InternalCsvError>>,
+{
+	async move {
+		let pages = partition.pages().await.map_err(CsvError::Partition)?;
+		Ok(stream::iter(pages)
+			.flat_map(move |page| {
+				let reader = Box::pin(Page::reader(page).chunk_size(chunk_size));
+				let state = (reader, RecordSplitter::new(), vec![0; chunk_size]);
+				stream::unfold(Some(state), |state| async move {
+					let (mut reader, mut splitter, mut chunk) = state?;
+					loop {
+						let len = match reader.read(&mut chunk).await {
+							Ok(len) => len,
+							Err(err) => {
+								return Some((Err(InternalCsvError::from(err).into()), None))
+							}
+						};
+						if len == 0 {
+							return splitter.finish().map(|records| (Ok(records), None));
+						}
+						if let Some(records) = splitter.push(&chunk[..len]) {
+							return Some((Ok(records), Some((reader, splitter, chunk))));
+						}
+					}
+				})
+			})
+			.flat_map(move |buf: Result<_, Error<P, E>>| {
+				stream::iter(ResultExpandIter::new(buf.map(&mut parse)))
+			})
+			.map(|row: Result<Result<T, InternalCsvError>, Error<P, E>>| Ok(row??)))
+	}
+	.map(ResultExpandIter::new)
+	.flatten_stream()
+	.map(|row: Result<Result<T, Error<P, E>>, Error<P, E>>| row?)
+}
+
+/// Splits the chunks of a page into runs of whole records, holding back a record
+/// that straddles the end of a chunk, such as one with a quoted field containing
+/// newlines, until the rest of it has been read. The state of the scan is kept
+/// across chunks, so each byte is scanned only once.
+struct RecordSplitter {
+	reader: csv_core::Reader,
+	pending: Vec<u8>,
+	scanned: usize,
+}
+impl RecordSplitter {
+	fn new() -> Self {
+		Self {
+			reader: csv_core::Reader::new(),
+			pending: Vec::new(),
+			scanned: 0,
+		}
+	}
+	/// Append `chunk`, returning the whole records not yet returned, if any.
+	fn push(&mut self, chunk: &[u8]) -> Option<Vec<u8>> {
+		self.pending.extend_from_slice(chunk);
+		// The fields themselves are parsed later, so they're discarded here
+		let (mut output, mut ends) = ([0; 1024], [0; 64]);
+		let mut end = None;
+		while self.scanned < self.pending.len() {
+			let (res, read, _, _) =
+				self.reader
+					.read_record(&self.pending[self.scanned..], &mut output, &mut ends);
+			self.scanned += read;
+			match res {
+				csv_core::ReadRecordResult::Record => end = Some(self.scanned),
+				csv_core::ReadRecordResult::OutputFull
+				| csv_core::ReadRecordResult::OutputEndsFull => (),
+				csv_core::ReadRecordResult::InputEmpty | csv_core::ReadRecordResult::End => break,
+			}
+		}
+		end.map(|end| {
+			let rest = self.pending.split_off(end);
+			self.scanned -= end;
+			mem::replace(&mut self.pending, rest)
+		})
+	}
+	/// The remaining bytes, at the end of the page.
+	fn finish(self) -> Option<Vec<u8>> {
+		Some(self.pending).filter(|pending| !pending.is_empty())
+	}
+}
+
 impl<F, Row> Source for Csv<F, Row>
 where
 	F: File,
@@ -160,7 +393,200 @@ where
 	}
 	#[allow(clippy::let_and_return)]
 	fn dist_stream(self) -> Self::DistStream {
-		self.partitions.into_dist_stream().flat_map(Closure::new())
+		self.partitions
+			.into_dist_stream()
+			.flat_map(Closure::new(self.null_tokens, self.read_chunk_size))
+	}
+}
+
+/// A row of CSV as passed to the closure of [`Csv::map_borrowed`], whose fields
+/// borrow from the buffer it was read into.
+#[derive(Copy, Clone, Debug)]
+pub struct CsvRecord<'a> {
+	record: &'a csv::StringRecord,
+	nulls: &'a [bool],
+}
+impl<'a> CsvRecord<'a> {
+	pub fn len(&self) -> usize {
+		self.record.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.record.is_empty()
+	}
+	/// The field at `index`, or `None` if there isn't one or it's null.
+	pub fn get(&self, index: usize) -> Option<&'a str> {
+		if self.nulls.get(index).copied().unwrap_or(false) {
+			return None;
+		}
+		self.record.get(index)
+	}
+	/// Deserialize the row, with `&str` and `Cow<str>` fields borrowing from it.
+	/// Null fields are blank, so read as `None` for `Option` columns.
+	pub fn deserialize<T: Deserialize<'a>>(&self) -> Result<T, InternalCsvError> {
+		self.record.deserialize(None)
+	}
+}
+
+/// A closure from a [`CsvRecord`] to an owned item, as taken by
+/// [`Csv::map_borrowed`]. It's implemented for closures created by the
+/// `FnMut` macro.
+pub trait CsvRecordFn {
+	type Output;
+
+	fn call(&mut self, record: CsvRecord<'_>) -> Self::Output;
+}
+impl<G, T> CsvRecordFn for G
+where
+	G: for<'a> FnMut<(CsvRecord<'a>,), Output = T>,
+{
+	type Output = T;
+
+	fn call(&mut self, record: CsvRecord<'_>) -> T {
+		self.call_mut((record,))
+	}
+}
+
+/// Reads the records of a buffer into a single reused record, blanking null
+/// fields into another, so that no allocation is needed per row.
+struct Records<G> {
+	reader: csv::Reader<Cursor<Vec<u8>>>,
+	null_tokens: NullTokens,
+	record: csv::StringRecord,
+	blanked: csv::StringRecord,
+	nulls: Vec<bool>,
+	f: G,
+}
+impl<G: CsvRecordFn> Iterator for Records<G> {
+	type Item = Result<G::Output, InternalCsvError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.reader.read_record(&mut self.record) {
+			Ok(true) => (),
+			Ok(false) => return None,
+			Err(err) => return Some(Err(err)),
+		}
+		let record = if self.null_tokens.is_empty() {
+			&self.record
+		} else {
+			self.nulls.clear();
+			self.blanked.clear();
+			for (i, field) in self.record.iter().enumerate() {
+				let null = self.null_tokens.is_null(i, field);
+				self.nulls.push(null);
+				self.blanked.push_field(if null { "" } else { field });
+			}
+			&self.blanked
+		};
+		let nulls = &self.nulls;
+		Some(Ok(self.f.call(CsvRecord { record, nulls })))
+	}
+}
+
+#[cfg(not(nightly))]
+type BorrowedOutput<P, G, E> =
+	std::pin::Pin<Box<dyn Stream<Item = Result<<G as CsvRecordFn>::Output, Error<P, E>>>>>;
+#[cfg(nightly)]
+type BorrowedOutput<P: Partition, G: CsvRecordFn, E> =
+	impl Stream<Item = Result<G::Output, Error<P, E>>>;
+
+FnMutNamed! {
+	pub type BorrowedClosure<P, G, E> = |self, null_tokens: NullTokens, read_chunk_size: usize, f: G|partition=> P| -> BorrowedOutput<P, G, E>
+	where
+		P: Partition,
+		G: CsvRecordFn,
+		G: Clone,
+		G: 'static,
+		E: 'static
+	{
+		let (null_tokens, f) = (self.null_tokens.clone(), self.f.clone());
+		#[allow(clippy::let_and_return)]
+		let ret = read_pages(partition, self.read_chunk_size, move |buf| Records {
+			reader: csv::ReaderBuilder::new()
+				.has_headers(false)
+				.from_reader(Cursor::new(buf)),
+			null_tokens: null_tokens.clone(),
+			record: csv::StringRecord::new(),
+			blanked: csv::StringRecord::new(),
+			nulls: Vec::new(),
+			f: f.clone(),
+		});
+		#[cfg(not(nightly))]
+		let ret = ret.boxed_local();
+		ret
+	}
+}
+
+/// The rows of a [`Csv`] source mapped by a closure over their borrowed fields,
+/// as returned by [`Csv::map_borrowed`].
+#[derive(Educe)]
+#[educe(
+	Clone(bound = "G: Clone"),
+	Debug(bound = "File::Partition: fmt::Debug")
+)]
+pub struct CsvMapBorrowed<File, G>
+where
+	File: amadeus_core::file::File,
+{
+	null_tokens: NullTokens,
+	read_chunk_size: usize,
+	partitions: Vec<File::Partition>,
+	#[educe(Debug(ignore))]
+	f: G,
+}
+
+impl<F, G> Source for CsvMapBorrowed<F, G>
+where
+	F: File,
+	G: CsvRecordFn + Clone + ProcessSend + 'static,
+	G::Output: 'static,
+{
+	type Item = G::Output;
+	#[allow(clippy::type_complexity)]
+	type Error = CsvError<
+		F::Error,
+		<F::Partition as Partition>::Error,
+		<<F::Partition as Partition>::Page as Page>::Error,
+	>;
+
+	type ParStream = DistParStream<Self::DistStream>;
+	#[cfg(not(nightly))]
+	#[allow(clippy::type_complexity)]
+	type DistStream = amadeus_core::par_stream::FlatMap<
+		amadeus_core::into_par_stream::IterDistStream<std::vec::IntoIter<F::Partition>>,
+		BorrowedClosure<F::Partition, G, F::Error>,
+	>;
+	#[cfg(nightly)]
+	type DistStream = impl DistributedStream<Item = Result<Self::Item, Self::Error>>;
+
+	fn par_stream(self) -> Self::ParStream {
+		DistParStream::new(self.dist_stream())
+	}
+	#[allow(clippy::let_and_return)]
+	fn dist_stream(self) -> Self::DistStream {
+		self.partitions
+			.into_dist_stream()
+			.flat_map(BorrowedClosure::new(
+				self.null_tokens,
+				self.read_chunk_size,
+				self.f,
+			))
+	}
+}
+
+/// Encode each item as a line of CSV, without a header row, for writing with
+/// [`WritePage`](amadeus_core::par_sink::WritePage).
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Debug)]
+pub struct CsvLines;
+impl<Row> Encoder<Row> for CsvLines
+where
+	Row: SerdeData,
+{
+	fn encode(&mut self, item: Row, buf: &mut Vec<u8>) -> io::Result<()> {
+		let mut writer = csv::WriterBuilder::new()
+			.has_headers(false)
+			.from_writer(buf);
+		writer.serialize(SerdeSerialize(&item))?;
+		writer.flush()
 	}
 }
 
@@ -11,7 +11,7 @@ use std::{
 
 use amadeus_core::util::{type_coerce, type_coerce_ref, type_eq};
 use amadeus_types::{
-	Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Enum, Group, IpAddr, Json, List, SchemaIncomplete, Time, TimeWithoutTimezone, Timezone, Url, Value, ValueRequired, Webpage
+	Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Duration, Enum, GeoPoint, Group, IpAddr, Json, List, SchemaIncomplete, Time, TimeWithoutTimezone, Timezone, Url, Uuid, Value, ValueRequired, Webpage
 };
 
 use super::{SerdeData, SerdeDeserialize, SerdeSerialize};
@@ -61,7 +61,7 @@ macro_rules! via_string {
 	)*};
 }
 
-via_string!(Decimal Date DateWithoutTimezone Time TimeWithoutTimezone DateTime DateTimeWithoutTimezone Timezone Webpage<'static> Url IpAddr);
+via_string!(Decimal Date DateWithoutTimezone Time TimeWithoutTimezone DateTime DateTimeWithoutTimezone Timezone Duration Webpage<'static> Url IpAddr GeoPoint Uuid);
 
 impl<T> SerdeData for Option<T>
 where
@@ -246,6 +246,7 @@ impl SerdeData for Value {
 			Self::DateTime(value) => SerdeData::serialize(value, serializer),
 			Self::DateTimeWithoutTimezone(value) => SerdeData::serialize(value, serializer),
 			Self::Timezone(value) => SerdeData::serialize(value, serializer),
+			Self::Duration(value) => SerdeData::serialize(value, serializer),
 			Self::Decimal(value) => SerdeData::serialize(value, serializer),
 			Self::Bson(value) => SerdeData::serialize(value, serializer),
 			Self::String(value) => SerdeData::serialize(value, serializer),
@@ -254,6 +255,10 @@ impl SerdeData for Value {
 			Self::Url(value) => SerdeData::serialize(value, serializer),
 			Self::Webpage(value) => SerdeData::serialize(value, serializer),
 			Self::IpAddr(value) => SerdeData::serialize(value, serializer),
+			Self::GeoPoint(value) => SerdeData::serialize(value, serializer),
+			Self::Uuid(value) => SerdeData::serialize(value, serializer),
+			// CSV and JSON have no unions, so only the payload is written
+			Self::Union(value) => SerdeData::serialize(value.value(), serializer),
 			Self::List(value) => SerdeData::serialize(value, serializer),
 			Self::Map(value) => SerdeData::serialize(value, serializer),
 			Self::Group(value) => SerdeData::serialize(value, serializer),
@@ -287,6 +292,9 @@ impl SerdeData for Value {
 					ValueRequired::Timezone(value) => {
 						serializer.serialize_some(&SerdeSerialize(value))
 					}
+					ValueRequired::Duration(value) => {
+						serializer.serialize_some(&SerdeSerialize(value))
+					}
 					ValueRequired::Decimal(value) => {
 						serializer.serialize_some(&SerdeSerialize(value))
 					}
@@ -303,6 +311,13 @@ impl SerdeData for Value {
 					ValueRequired::IpAddr(value) => {
 						serializer.serialize_some(&SerdeSerialize(value))
 					}
+					ValueRequired::GeoPoint(value) => {
+						serializer.serialize_some(&SerdeSerialize(value))
+					}
+					ValueRequired::Uuid(value) => serializer.serialize_some(&SerdeSerialize(value)),
+					ValueRequired::Union(value) => {
+						serializer.serialize_some(&SerdeSerialize(value.value()))
+					}
 					ValueRequired::List(value) => serializer.serialize_some(&SerdeSerialize(value)),
 					ValueRequired::Map(value) => serializer.serialize_some(&SerdeSerialize(value)),
 					ValueRequired::Group(value) => {
@@ -1,17 +1,20 @@
 use educe::Educe;
 use futures::{pin_mut, stream, AsyncReadExt, FutureExt, Stream, StreamExt};
-use serde::{Deserialize, Serialize};
-use serde_closure::FnMutNamed;
-use serde_json::Error as InternalJsonError;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use serde_closure::{traits::FnMut, FnMutNamed};
+use serde_json::{Error as InternalJsonError, Map, Value as JsonValue};
 use std::{
-	error, fmt::{self, Debug, Display}, io::{self, Cursor}, marker::PhantomData
+	error, fmt::{self, Debug, Display}, io::{self, Cursor}, marker::PhantomData, mem
 };
+use sum::Sum2;
 
 use amadeus_core::{
-	file::{File, Page, Partition}, into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::{DistParStream, ResultExpandIter}, Source
+	file::{File, Page, Partition}, into_par_stream::IntoDistributedStream, par_sink::Encoder, par_stream::DistributedStream, pool::ProcessSend, util::{DistParStream, ResultExpandIter}, Source
 };
 
-use super::{SerdeData, SerdeDeserialize};
+use amadeus_types::{Group, Schema, Value};
+
+use super::{SerdeData, SerdeDeserialize, SerdeSerialize, READ_CHUNK_SIZE};
 
 #[derive(Educe)]
 #[educe(Clone, Debug)]
@@ -21,6 +24,8 @@ where
 	Row: SerdeData,
 {
 	partitions: Vec<File::Partition>,
+	flatten: Option<JsonFlatten>,
+	read_chunk_size: usize,
 	marker: PhantomData<fn() -> Row>,
 }
 impl<F, Row> Json<F, Row>
@@ -31,9 +36,150 @@ where
 	pub async fn new(file: F) -> Result<Self, <Self as Source>::Error> {
 		Ok(Self {
 			partitions: file.partitions().await.map_err(JsonError::File)?,
+			flatten: None,
+			read_chunk_size: READ_CHUNK_SIZE,
 			marker: PhantomData,
 		})
 	}
+	/// Flatten nested objects of each document into columns named by their dotted
+	/// path, e.g. `{"address":{"city":"x"}}` into `{"address.city":"x"}`, before
+	/// deserializing it into `Row`.
+	#[must_use]
+	pub fn flatten(mut self, flatten: JsonFlatten) -> Self {
+		self.flatten = Some(flatten);
+		self
+	}
+	/// Read pages `read_chunk_size` bytes at a time, rather than the default of
+	/// 10 MiB. Larger reads mean fewer requests to high-latency sources like S3.
+	///
+	/// # Panics
+	///
+	/// Panics if `read_chunk_size` is zero.
+	#[must_use]
+	pub fn read_chunk_size(mut self, read_chunk_size: usize) -> Self {
+		assert_ne!(read_chunk_size, 0, "read_chunk_size must be non-zero");
+		self.read_chunk_size = read_chunk_size;
+		self
+	}
+	/// The schema of the documents, after any flattening, inferred by reading
+	/// up to `sample` of them from the start of the source as [`Group`]s,
+	/// without consuming it.
+	pub async fn schema(&self, sample: usize) -> Result<Option<Schema>, <Self as Source>::Error> {
+		let mut rows = Vec::new();
+		for partition in self.partitions.iter().cloned() {
+			if rows.len() == sample {
+				break;
+			}
+			let partition = Closure::<_, Group, F::Error>::new(self.flatten, self.read_chunk_size)
+				.call_mut((partition,));
+			pin_mut!(partition);
+			while rows.len() < sample {
+				match partition.next().await {
+					Some(row) => rows.push(Value::Group(row?)),
+					None => break,
+				}
+			}
+		}
+		Ok(Schema::infer(rows))
+	}
+	/// Map each document with `f` as it's read, borrowing it from the buffer it
+	/// was read into rather than copying it into owned values. `f` can
+	/// deserialize it into a type with `&str` or `Cow<str>` fields with
+	/// [`JsonRecord::deserialize`], which borrow strings without escapes.
+	///
+	/// # Panics
+	///
+	/// Panics if [`flatten`](Self::flatten) is set, as flattening needs an owned
+	/// document.
+	pub fn map_borrowed<G>(self, f: G) -> JsonMapBorrowed<F, G>
+	where
+		G: JsonRecordFn + Clone + ProcessSend + 'static,
+		G::Output: 'static,
+	{
+		assert!(
+			self.flatten.is_none(),
+			"documents can't be flattened when mapped borrowed"
+		);
+		JsonMapBorrowed {
+			partitions: self.partitions,
+			read_chunk_size: self.read_chunk_size,
+			f,
+		}
+	}
+}
+
+/// How [`Json::flatten`] treats arrays.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum JsonArrays {
+	/// Leave arrays as values.
+	Keep,
+	/// Flatten arrays like objects, naming elements by their index, e.g. `tags.0`.
+	Index,
+	/// Produce a row for each element, with the element in place of the array. An
+	/// empty array is left as is.
+	Explode,
+}
+
+/// Configuration for [`Json::flatten`].
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct JsonFlatten {
+	/// The number of levels of nesting to flatten, or `None` for all of them.
+	/// Anything nested deeper is left as a value.
+	pub depth: Option<usize>,
+	pub arrays: JsonArrays,
+}
+impl Default for JsonFlatten {
+	fn default() -> Self {
+		Self {
+			depth: None,
+			arrays: JsonArrays::Index,
+		}
+	}
+}
+impl JsonFlatten {
+	fn apply(self, document: JsonValue) -> Vec<JsonValue> {
+		let object = match document {
+			JsonValue::Object(object) => object,
+			document => return vec![document],
+		};
+		let depth = self.depth.unwrap_or(usize::MAX);
+		let mut rows = vec![Map::new()];
+		for (key, value) in object {
+			self.push(&mut rows, key, value, depth);
+		}
+		rows.into_iter().map(JsonValue::Object).collect()
+	}
+	fn push(
+		self, rows: &mut Vec<Map<String, JsonValue>>, path: String, value: JsonValue, depth: usize,
+	) {
+		match value {
+			JsonValue::Object(object) if depth > 0 => {
+				for (key, value) in object {
+					self.push(rows, format!("{}.{}", path, key), value, depth - 1);
+				}
+			}
+			JsonValue::Array(array) if depth > 0 && self.arrays == JsonArrays::Index => {
+				for (i, value) in array.into_iter().enumerate() {
+					self.push(rows, format!("{}.{}", path, i), value, depth - 1);
+				}
+			}
+			JsonValue::Array(array)
+				if depth > 0 && self.arrays == JsonArrays::Explode && !array.is_empty() =>
+			{
+				let base = mem::take(rows);
+				for value in array {
+					let mut exploded = base.clone();
+					self.push(&mut exploded, path.clone(), value, depth - 1);
+					rows.append(&mut exploded);
+				}
+			}
+			value => {
+				for row in rows.iter_mut() {
+					let _ = row.insert(path.clone(), value.clone());
+				}
+			}
+		}
+	}
 }
 
 type Error<P, E> = JsonError<E, <P as Partition>::Error, <<P as Partition>::Page as Page>::Error>;
@@ -43,12 +189,13 @@ type Output<P, Row: SerdeData, E> = std::pin::Pin<Box<dyn Stream<Item = Result<R
 type Output<P: Partition, Row: SerdeData, E> = impl Stream<Item = Result<Row, Error<P, E>>>;
 
 FnMutNamed! {
-	pub type Closure<P, Row, E> = |self|partition=> P| -> Output<P, Row, E>
+	pub type Closure<P, Row, E> = |self, flatten: Option<JsonFlatten>, read_chunk_size: usize|partition=> P| -> Output<P, Row, E>
 	where
 		P: Partition,
 		Row: SerdeData,
 		E: 'static
 	{
+		let (flatten, chunk_size) = (self.flatten, self.read_chunk_size);
 		#[allow(clippy::let_and_return)]
 		let ret = async move {
 				Ok(stream::iter(
@@ -58,19 +205,32 @@ FnMutNamed! {
 						.map_err(JsonError::Partition)?
 						.into_iter(),
 				)
-				.flat_map(|page| {
+				.flat_map(move |page| {
 					async move {
-						let mut buf = Vec::with_capacity(10 * 1024 * 1024);
-						let reader = Page::reader(page);
+						let mut buf = Vec::with_capacity(chunk_size);
+						let reader = Page::reader(page).chunk_size(chunk_size);
 						pin_mut!(reader);
 						let buf = PassError::new(
 							reader.read_to_end(&mut buf).await.map(|_| Cursor::new(buf)),
 						);
-						Ok(stream::iter(
-							serde_json::Deserializer::from_reader(buf).into_iter().map(
+						let documents = serde_json::Deserializer::from_reader(buf);
+						Ok(stream::iter(match flatten {
+							None => Sum2::A(documents.into_iter().map(
 								|x: Result<SerdeDeserialize<Row>, InternalJsonError>| Ok(x?.0),
+							)),
+							Some(flatten) => Sum2::B(
+								documents
+									.into_iter()
+									.flat_map(move |x: Result<JsonValue, InternalJsonError>| {
+										ResultExpandIter::new(
+											x.map(|document| flatten.apply(document).into_iter()),
+										)
+									})
+									.map(|x: Result<JsonValue, InternalJsonError>| {
+										Ok(serde_json::from_value::<SerdeDeserialize<Row>>(x?)?.0)
+									}),
 							),
-						))
+						}))
 					}
 					.map(ResultExpandIter::new)
 					.flatten_stream()
@@ -114,7 +274,193 @@ where
 	}
 	#[allow(clippy::let_and_return)]
 	fn dist_stream(self) -> Self::DistStream {
-		self.partitions.into_dist_stream().flat_map(Closure::new())
+		self.partitions
+			.into_dist_stream()
+			.flat_map(Closure::new(self.flatten, self.read_chunk_size))
+	}
+}
+
+/// A document as passed to the closure of [`Json::map_borrowed`], borrowing
+/// from the buffer it was read into.
+#[derive(Copy, Clone, Debug)]
+pub struct JsonRecord<'a> {
+	document: &'a [u8],
+}
+impl<'a> JsonRecord<'a> {
+	/// The document's JSON text.
+	pub fn as_bytes(&self) -> &'a [u8] {
+		self.document
+	}
+	/// Deserialize the document, with `&str` and `Cow<str>` fields borrowing
+	/// from it where they contain no escapes.
+	pub fn deserialize<T: Deserialize<'a>>(&self) -> Result<T, InternalJsonError> {
+		serde_json::from_slice(self.document)
+	}
+}
+
+/// A closure from a [`JsonRecord`] to an owned item, as taken by
+/// [`Json::map_borrowed`]. It's implemented for closures created by the
+/// `FnMut` macro.
+pub trait JsonRecordFn {
+	type Output;
+
+	fn call(&mut self, record: JsonRecord<'_>) -> Self::Output;
+}
+impl<G, T> JsonRecordFn for G
+where
+	G: for<'a> FnMut<(JsonRecord<'a>,), Output = T>,
+{
+	type Output = T;
+
+	fn call(&mut self, record: JsonRecord<'_>) -> T {
+		self.call_mut((record,))
+	}
+}
+
+/// Splits a buffer into its documents, skipping over each to find where it
+/// ends without allocating.
+struct Documents<G> {
+	buf: Vec<u8>,
+	offset: usize,
+	f: G,
+}
+impl<G: JsonRecordFn> Iterator for Documents<G> {
+	type Item = Result<G::Output, InternalJsonError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let rest = &self.buf[self.offset..];
+		let mut documents = serde_json::Deserializer::from_slice(rest).into_iter::<IgnoredAny>();
+		let res = documents.next()?;
+		let end = documents.byte_offset();
+		// Stop at the first error, as the rest of the buffer can't be delimited
+		self.offset = if res.is_ok() {
+			self.offset + end
+		} else {
+			self.buf.len()
+		};
+		let f = &mut self.f;
+		Some(res.map(|IgnoredAny| {
+			f.call(JsonRecord {
+				document: &rest[..end],
+			})
+		}))
+	}
+}
+
+#[cfg(not(nightly))]
+type BorrowedOutput<P, G, E> =
+	std::pin::Pin<Box<dyn Stream<Item = Result<<G as JsonRecordFn>::Output, Error<P, E>>>>>;
+#[cfg(nightly)]
+type BorrowedOutput<P: Partition, G: JsonRecordFn, E> =
+	impl Stream<Item = Result<G::Output, Error<P, E>>>;
+
+FnMutNamed! {
+	pub type BorrowedClosure<P, G, E> = |self, read_chunk_size: usize, f: G|partition=> P| -> BorrowedOutput<P, G, E>
+	where
+		P: Partition,
+		G: JsonRecordFn,
+		G: Clone,
+		G: 'static,
+		E: 'static
+	{
+		let (chunk_size, f) = (self.read_chunk_size, self.f.clone());
+		#[allow(clippy::let_and_return, clippy::type_complexity)]
+		let ret = async move {
+				Ok(stream::iter(
+					partition
+						.pages()
+						.await
+						.map_err(JsonError::Partition)?
+						.into_iter(),
+				)
+				.then(move |page| async move {
+					let mut buf = Vec::with_capacity(chunk_size);
+					let reader = Page::reader(page).chunk_size(chunk_size);
+					pin_mut!(reader);
+					let _ = reader
+						.read_to_end(&mut buf)
+						.await
+						.map_err(InternalJsonError::io)?;
+					Ok(buf)
+				})
+				.flat_map(move |buf: Result<_, Error<P, E>>| {
+					let f = f.clone();
+					stream::iter(ResultExpandIter::new(
+						buf.map(|buf| Documents { buf, offset: 0, f }),
+					))
+				})
+				.map(|row: Result<Result<G::Output, InternalJsonError>, Error<P, E>>| Ok(row??)))
+			}
+			.map(ResultExpandIter::new)
+			.flatten_stream()
+			.map(|row: Result<Result<G::Output, Error<P, E>>, Error<P, E>>| row?);
+		#[cfg(not(nightly))]
+		let ret = ret.boxed_local();
+		ret
+	}
+}
+
+/// The documents of a [`Json`] source mapped by a closure over their borrowed
+/// contents, as returned by [`Json::map_borrowed`].
+#[derive(Educe)]
+#[educe(Clone(bound = "G: Clone"), Debug(bound = "File::Partition: Debug"))]
+pub struct JsonMapBorrowed<File, G>
+where
+	File: amadeus_core::file::File,
+{
+	partitions: Vec<File::Partition>,
+	read_chunk_size: usize,
+	#[educe(Debug(ignore))]
+	f: G,
+}
+
+impl<F, G> Source for JsonMapBorrowed<F, G>
+where
+	F: File,
+	G: JsonRecordFn + Clone + ProcessSend + 'static,
+	G::Output: 'static,
+{
+	type Item = G::Output;
+	#[allow(clippy::type_complexity)]
+	type Error = JsonError<
+		F::Error,
+		<F::Partition as Partition>::Error,
+		<<F::Partition as Partition>::Page as Page>::Error,
+	>;
+
+	type ParStream = DistParStream<Self::DistStream>;
+	#[cfg(not(nightly))]
+	#[allow(clippy::type_complexity)]
+	type DistStream = amadeus_core::par_stream::FlatMap<
+		amadeus_core::into_par_stream::IterDistStream<std::vec::IntoIter<F::Partition>>,
+		BorrowedClosure<F::Partition, G, F::Error>,
+	>;
+	#[cfg(nightly)]
+	type DistStream = impl DistributedStream<Item = Result<Self::Item, Self::Error>>;
+
+	fn par_stream(self) -> Self::ParStream {
+		DistParStream::new(self.dist_stream())
+	}
+	#[allow(clippy::let_and_return)]
+	fn dist_stream(self) -> Self::DistStream {
+		self.partitions
+			.into_dist_stream()
+			.flat_map(BorrowedClosure::new(self.read_chunk_size, self.f))
+	}
+}
+
+/// Encode each item as a line of JSON, for writing with
+/// [`WritePage`](amadeus_core::par_sink::WritePage).
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Debug)]
+pub struct JsonLines;
+impl<Row> Encoder<Row> for JsonLines
+where
+	Row: SerdeData,
+{
+	fn encode(&mut self, item: Row, buf: &mut Vec<u8>) -> io::Result<()> {
+		serde_json::to_writer(&mut *buf, &SerdeSerialize(&item))?;
+		buf.push(b'\n');
+		Ok(())
 	}
 }
 
@@ -7,7 +7,7 @@
 //! This is a support crate of [Amadeus](https://github.com/constellation-rs/amadeus) and is not intended to be used directly. These types are re-exposed in [`amadeus::source`](https://docs.rs/amadeus/0.3/amadeus/source/index.html).
 
 #![doc(html_root_url = "https://docs.rs/amadeus-serde/0.4.2")]
-#![cfg_attr(nightly, feature(type_alias_impl_trait))]
+#![cfg_attr(nightly, feature(type_alias_impl_trait, unboxed_closures))]
 #![warn(
 	// missing_copy_implementations,
 	// missing_debug_implementations,
@@ -33,6 +33,7 @@
 mod csv;
 mod impls;
 mod json;
+mod write;
 
 #[doc(hidden)]
 pub use serde as _internal;
@@ -42,9 +43,14 @@ use serde::{Deserializer, Serializer};
 use std::fmt::Debug;
 
 pub use self::{
-	csv::*, json::{Json, JsonError}
+	csv::*, json::{
+		Json, JsonArrays, JsonError, JsonFlatten, JsonLines, JsonMapBorrowed, JsonRecord, JsonRecordFn
+	}, write::{DistributedStreamWrite, ParallelStreamWrite}
 };
 
+/// The number of bytes `Csv` and `Json` read from a page at a time by default.
+const READ_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
 pub trait SerdeData
 where
 	Self: Clone + PartialEq + Debug + 'static,
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use std::{fs, path::Path};
+
+use amadeus_core::{
+	file::LocalFile, par_stream::{DistributedStream, ParallelStream}, pool::{ProcessPool, ThreadPool}, util::IoError
+};
+
+use super::{CsvLines, JsonLines, SerdeData};
+
+/// Write all items to a new local file at `path`, replacing any existing one,
+/// returning the number of bytes written. These wrap
+/// [`write_page`](ParallelStream::write_page) with the default [`CsvLines`] and
+/// [`JsonLines`] encoders, so items are written in no particular order, and CSV
+/// is written without a header row as [`Csv`](super::Csv) reads it.
+#[async_trait(?Send)]
+pub trait ParallelStreamWrite: ParallelStream {
+	async fn write_csv<P, F>(self, pool: &P, path: F) -> Result<u64, IoError>
+	where
+		P: ThreadPool,
+		F: AsRef<Path>,
+		Self::Item: SerdeData,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let page = create(path)?;
+		self.write_page(pool, page, CsvLines).await
+	}
+	async fn write_json<P, F>(self, pool: &P, path: F) -> Result<u64, IoError>
+	where
+		P: ThreadPool,
+		F: AsRef<Path>,
+		Self::Item: SerdeData,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let page = create(path)?;
+		self.write_page(pool, page, JsonLines).await
+	}
+}
+impl<S: ParallelStream> ParallelStreamWrite for S {}
+
+/// Like [`ParallelStreamWrite`], for [`DistributedStream`]s.
+#[async_trait(?Send)]
+pub trait DistributedStreamWrite: DistributedStream {
+	async fn write_csv<P, F>(self, pool: &P, path: F) -> Result<u64, IoError>
+	where
+		P: ProcessPool,
+		F: AsRef<Path>,
+		Self::Item: SerdeData,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let page = create(path)?;
+		self.write_page(pool, page, CsvLines).await
+	}
+	async fn write_json<P, F>(self, pool: &P, path: F) -> Result<u64, IoError>
+	where
+		P: ProcessPool,
+		F: AsRef<Path>,
+		Self::Item: SerdeData,
+		Self::Task: 'static,
+		Self: Sized,
+	{
+		let page = create(path)?;
+		self.write_page(pool, page, JsonLines).await
+	}
+}
+impl<S: DistributedStream> DistributedStreamWrite for S {}
+
+fn create<F: AsRef<Path>>(path: F) -> Result<LocalFile, IoError> {
+	Ok(fs::File::create(path)?.into())
+}
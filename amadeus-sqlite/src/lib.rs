@@ -0,0 +1,249 @@
+//! Harmonious distributed data processing & analysis in Rust.
+//!
+//! <p style="font-family: 'Fira Sans',sans-serif;padding:0.3em 0"><strong>
+//! <a href="https://crates.io/crates/amadeus">📦&nbsp;&nbsp;Crates.io</a>&nbsp;&nbsp;│&nbsp;&nbsp;<a href="https://github.com/constellation-rs/amadeus">📑&nbsp;&nbsp;GitHub</a>&nbsp;&nbsp;│&nbsp;&nbsp;<a href="https://constellation.zulipchat.com/#narrow/stream/213231-amadeus">💬&nbsp;&nbsp;Chat</a>
+//! </strong></p>
+//!
+//! This is a support crate of [Amadeus](https://github.com/constellation-rs/amadeus) and is not intended to be used directly. These types are re-exposed in [`amadeus::source`](https://docs.rs/amadeus/0.3/amadeus/source/index.html).
+
+#![doc(html_root_url = "https://docs.rs/amadeus-sqlite/0.4.2")]
+#![warn(
+	// missing_copy_implementations,
+	// missing_debug_implementations,
+	// missing_docs,
+	trivial_numeric_casts,
+	unused_import_braces,
+	unused_qualifications,
+	unused_results,
+	unreachable_pub,
+	clippy::pedantic,
+)]
+#![allow(
+	clippy::module_name_repetitions,
+	clippy::similar_names,
+	clippy::if_not_else,
+	clippy::must_use_candidate,
+	clippy::missing_errors_doc,
+	clippy::missing_panics_doc,
+	clippy::doc_markdown
+)]
+#![deny(unsafe_code)]
+
+mod sink;
+
+#[doc(hidden)]
+pub use rusqlite as _internal;
+
+pub use sink::{SqliteInsert, SqliteInsertReducer, SqliteInsertReducerAsync};
+
+use educe::Educe;
+use futures::stream;
+use fxhash::FxBuildHasher;
+use hashlink::LinkedHashMap;
+use rusqlite::{types::ValueRef, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use serde_closure::FnMutNamed;
+use std::{
+	error, fmt::{self, Debug, Display}, marker::PhantomData, path::PathBuf, str, sync::Arc, vec
+};
+
+use amadeus_core::{
+	into_par_stream::IntoDistributedStream, par_stream::DistributedStream, util::DistParStream, Source
+};
+use amadeus_types::{Downcast, DowncastFrom, Group, List, Value};
+
+const DEFAULT_ROWS_PER_PARTITION: u64 = 100_000;
+
+/// Types that a row of a SQLite table can be read as.
+pub trait SqliteData: DowncastFrom<Value> + Send + 'static {}
+impl<T> SqliteData for T where T: DowncastFrom<Value> + Send + 'static {}
+
+/// A table in a SQLite database file, read as ranges of its `rowid`s holding
+/// an equal number of rows each.
+///
+/// `INTEGER` columns are read as `i64`, `REAL` as `f64`, `TEXT` as `String`
+/// and `BLOB` as `List<u8>`. Columns that aren't declared `NOT NULL` or
+/// `PRIMARY KEY` are read as `Option`s, with `NULL`s as `None`. Each row is a
+/// [`Group`] named by column, which `Row` is downcast from.
+#[derive(Educe)]
+#[educe(Clone, Debug)]
+pub struct Sqlite<Row> {
+	path: PathBuf,
+	table: String,
+	partitions: Vec<(i64, i64)>,
+	marker: PhantomData<fn() -> Row>,
+}
+impl<Row> Sqlite<Row> {
+	pub fn new(path: PathBuf, table: &str) -> Result<Self, SqliteError> {
+		Self::new_with(path, table, DEFAULT_ROWS_PER_PARTITION)
+	}
+	/// Read `table` from the database at `path` in partitions of
+	/// `rows_per_partition` rows, the last possibly fewer, each read by a
+	/// single query bounded by the first and last `rowid` of the partition.
+	pub fn new_with(
+		path: PathBuf, table: &str, rows_per_partition: u64,
+	) -> Result<Self, SqliteError> {
+		assert_ne!(rows_per_partition, 0, "rows_per_partition must be nonzero");
+		let connection = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+		let mut statement = connection.prepare(&format!(
+			"SELECT rowid FROM {} ORDER BY rowid",
+			EscapeIdentifier(table)
+		))?;
+		let mut rowids = statement.query(rusqlite::NO_PARAMS)?;
+		let mut partitions: Vec<(i64, i64)> = Vec::new();
+		let mut rows = 0;
+		while let Some(row) = rowids.next()? {
+			let rowid = row.get(0)?;
+			match partitions.last_mut() {
+				Some((_, end)) if rows % rows_per_partition != 0 => *end = rowid,
+				_ => partitions.push((rowid, rowid)),
+			}
+			rows += 1;
+		}
+		drop(rowids);
+		drop(statement);
+		Ok(Self {
+			path,
+			table: table.to_owned(),
+			partitions,
+			marker: PhantomData,
+		})
+	}
+}
+
+type Output<Row> = stream::Iter<vec::IntoIter<Result<Row, SqliteError>>>;
+
+FnMutNamed! {
+	pub type Closure<Row> = |self, path: PathBuf, table: String|range=> (i64, i64)| -> Output<Row>
+	where
+		Row: SqliteData
+	{
+		let rows = read(&self.path, &self.table, range)
+			.unwrap_or_else(|err| vec![Err(err)]);
+		stream::iter(rows)
+	}
+}
+
+fn read<Row>(
+	path: &PathBuf, table: &str, (start, end): (i64, i64),
+) -> Result<Vec<Result<Row, SqliteError>>, SqliteError>
+where
+	Row: SqliteData,
+{
+	let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+	let mut not_null = connection.prepare(&format!(
+		"SELECT name FROM pragma_table_info({}) WHERE \"notnull\" OR pk",
+		EscapeString(table)
+	))?;
+	let not_null = not_null
+		.query_map(rusqlite::NO_PARAMS, |row| row.get::<_, String>(0))?
+		.collect::<Result<Vec<_>, _>>()?;
+	let mut statement = connection.prepare(&format!(
+		"SELECT * FROM {} WHERE rowid BETWEEN ?1 AND ?2 ORDER BY rowid",
+		EscapeIdentifier(table)
+	))?;
+	let mut field_names =
+		LinkedHashMap::with_capacity_and_hasher(statement.column_count(), FxBuildHasher::default());
+	for (i, name) in statement.column_names().into_iter().enumerate() {
+		let _ = field_names.insert(name.to_owned(), i);
+	}
+	let nullable = field_names
+		.keys()
+		.map(|name| !not_null.contains(name))
+		.collect::<Vec<_>>();
+	let field_names = Arc::new(field_names);
+	let mut rows = statement.query([start, end])?;
+	let mut ret = Vec::new();
+	while let Some(row) = rows.next()? {
+		let fields = (0..field_names.len())
+			.map(|i| decode(row.get_raw(i), nullable[i]))
+			.collect::<Result<Vec<_>, _>>()?;
+		let group = Group::new(fields, Some(field_names.clone()));
+		ret.push(
+			Downcast::<Row>::downcast(Value::Group(group))
+				.map_err(|err| SqliteError::Downcast(err.to_string())),
+		);
+	}
+	Ok(ret)
+}
+
+fn decode(value: ValueRef<'_>, nullable: bool) -> Result<Value, SqliteError> {
+	let value = match value {
+		ValueRef::Null => return Ok(Value::Option(None)),
+		ValueRef::Integer(value) => Value::I64(value),
+		ValueRef::Real(value) => Value::F64(value),
+		ValueRef::Text(value) => Value::String(
+			String::from_utf8(value.to_owned())
+				.map_err(|err| SqliteError::Sqlite(err.to_string()))?,
+		),
+		ValueRef::Blob(value) => List::<u8>::from(value.to_owned()).into(),
+	};
+	Ok(if nullable {
+		Value::from(Some(value))
+	} else {
+		value
+	})
+}
+
+impl<Row> Source for Sqlite<Row>
+where
+	Row: SqliteData,
+{
+	type Item = Row;
+	type Error = SqliteError;
+
+	type ParStream = DistParStream<Self::DistStream>;
+	#[allow(clippy::type_complexity)]
+	type DistStream = amadeus_core::par_stream::FlatMap<
+		amadeus_core::into_par_stream::IterDistStream<vec::IntoIter<(i64, i64)>>,
+		Closure<Row>,
+	>;
+
+	fn par_stream(self) -> Self::ParStream {
+		DistParStream::new(self.dist_stream())
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		self.partitions
+			.into_dist_stream()
+			.flat_map(Closure::new(self.path, self.table))
+	}
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub enum SqliteError {
+	Sqlite(String),
+	Downcast(String),
+	Unsupported(String),
+}
+impl error::Error for SqliteError {}
+impl Display for SqliteError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Sqlite(err) | Self::Downcast(err) => Display::fmt(err, f),
+			Self::Unsupported(value) => write!(f, "can't write {} to SQLite", value),
+		}
+	}
+}
+impl From<rusqlite::Error> for SqliteError {
+	fn from(err: rusqlite::Error) -> Self {
+		Self::Sqlite(err.to_string())
+	}
+}
+
+struct EscapeString<T>(T);
+impl<T: AsRef<str>> Display for EscapeString<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("'")?;
+		f.write_str(&self.0.as_ref().replace('\'', "''"))?;
+		f.write_str("'")
+	}
+}
+
+struct EscapeIdentifier<T>(T);
+impl<T: AsRef<str>> Display for EscapeIdentifier<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("\"")?;
+		f.write_str(&self.0.as_ref().replace('"', "\"\""))?;
+		f.write_str("\"")
+	}
+}
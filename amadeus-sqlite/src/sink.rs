@@ -0,0 +1,244 @@
+use futures::{ready, Stream};
+use rusqlite::{types::Value as SqliteValue, Connection, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use std::{
+	convert::TryFrom, fmt::Write, mem, path::PathBuf, pin::Pin, task::{Context, Poll}, time::Duration
+};
+
+use amadeus_core::{
+	par_sink::{
+		DistributedSink, FromDistributedStream, FromParallelStream, ParallelSink, Reducer, ReducerSend
+	}, par_stream::Identity, pipe::Sink
+};
+use amadeus_types::Value;
+
+use super::{EscapeIdentifier, SqliteError};
+
+const DEFAULT_BATCH_SIZE: usize = 10_000;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A sink that inserts rows into `table` in the SQLite database at `path`.
+///
+/// Each item is converted to a [`Value`]. The fields of a
+/// [`Group`](amadeus_types::Group) are inserted into the columns of the same
+/// name, or in order if the group's fields aren't named; any other value is
+/// inserted as a single column. Each reducer buffers up to `batch_size` rows and
+/// inserts each batch in a single transaction.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SqliteInsert {
+	path: PathBuf,
+	table: String,
+	batch_size: usize,
+}
+impl SqliteInsert {
+	pub fn new(path: PathBuf, table: &str) -> Self {
+		Self::new_with(path, table, DEFAULT_BATCH_SIZE)
+	}
+	pub fn new_with(path: PathBuf, table: &str, batch_size: usize) -> Self {
+		assert_ne!(batch_size, 0, "batch_size must be nonzero");
+		Self {
+			path,
+			table: table.to_owned(),
+			batch_size,
+		}
+	}
+}
+
+impl<Item> ParallelSink<Item> for SqliteInsert
+where
+	Item: Into<Value>,
+{
+	type Done = Result<(), SqliteError>;
+	type Pipe = Identity;
+	type ReduceA = SqliteInsertReducer;
+	type ReduceC =
+		<Result<(), SqliteError> as FromParallelStream<Result<(), SqliteError>>>::ReduceC;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceC) {
+		let (_, reduce_c) =
+			<Result<(), SqliteError> as FromParallelStream<Result<(), SqliteError>>>::reducers();
+		(Identity, SqliteInsertReducer(self), reduce_c)
+	}
+}
+impl<Item> DistributedSink<Item> for SqliteInsert
+where
+	Item: Into<Value>,
+{
+	type Done = Result<(), SqliteError>;
+	type Pipe = Identity;
+	type ReduceA = SqliteInsertReducer;
+	type ReduceB =
+		<Result<(), SqliteError> as FromDistributedStream<Result<(), SqliteError>>>::ReduceB;
+	type ReduceC =
+		<Result<(), SqliteError> as FromDistributedStream<Result<(), SqliteError>>>::ReduceC;
+
+	fn reducers(self) -> (Self::Pipe, Self::ReduceA, Self::ReduceB, Self::ReduceC) {
+		let (_, reduce_b, reduce_c) =
+			<Result<(), SqliteError> as FromDistributedStream<Result<(), SqliteError>>>::reducers();
+		(Identity, SqliteInsertReducer(self), reduce_b, reduce_c)
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SqliteInsertReducer(SqliteInsert);
+impl<Item> Reducer<Item> for SqliteInsertReducer
+where
+	Item: Into<Value>,
+{
+	type Done = Result<(), SqliteError>;
+	type Async = SqliteInsertReducerAsync;
+
+	fn into_async(self) -> Self::Async {
+		SqliteInsertReducerAsync {
+			insert: self.0,
+			connection: None,
+			rows: Vec::new(),
+			result: Some(Ok(())),
+		}
+	}
+}
+impl<Item> ReducerSend<Item> for SqliteInsertReducer
+where
+	Item: Into<Value>,
+{
+	type Done = Result<(), SqliteError>;
+}
+
+pub struct SqliteInsertReducerAsync {
+	insert: SqliteInsert,
+	connection: Option<Connection>,
+	rows: Vec<Value>,
+	result: Option<Result<(), SqliteError>>,
+}
+impl SqliteInsertReducerAsync {
+	fn flush(&mut self) {
+		let rows = mem::take(&mut self.rows);
+		if rows.is_empty() || !matches!(self.result, Some(Ok(()))) {
+			return;
+		}
+		if let Err(err) = self.write(rows) {
+			self.result = Some(Err(err));
+		}
+	}
+	fn write(&mut self, rows: Vec<Value>) -> Result<(), SqliteError> {
+		if self.connection.is_none() {
+			let connection = Connection::open(&self.insert.path)?;
+			// Other reducers may be writing to the same database
+			connection.busy_timeout(BUSY_TIMEOUT)?;
+			self.connection = Some(connection);
+		}
+		let connection = self.connection.as_mut().unwrap();
+		// Take the write lock up front, rather than when the first row is
+		// inserted, so that concurrent transactions wait rather than deadlock
+		let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+		for row in rows {
+			let (names, values) = columns(row)?;
+			let mut statement = transaction.prepare_cached(&insert(
+				&self.insert.table,
+				names.as_deref(),
+				values.len(),
+			))?;
+			let _ = statement.execute(values)?;
+		}
+		transaction.commit()?;
+		Ok(())
+	}
+}
+impl<Item> Sink<Item> for SqliteInsertReducerAsync
+where
+	Item: Into<Value>,
+{
+	type Done = Result<(), SqliteError>;
+
+	fn poll_forward(
+		self: Pin<&mut Self>, cx: &mut Context, mut stream: Pin<&mut impl Stream<Item = Item>>,
+	) -> Poll<Self::Done> {
+		let self_ = self.get_mut();
+		loop {
+			match ready!(stream.as_mut().poll_next(cx)) {
+				// Once a batch has failed, drain the stream without writing the rest
+				Some(item) if matches!(self_.result, Some(Ok(()))) => {
+					self_.rows.push(item.into());
+					if self_.rows.len() == self_.insert.batch_size {
+						self_.flush();
+					}
+				}
+				Some(_) => (),
+				None => {
+					self_.flush();
+					break Poll::Ready(self_.result.take().unwrap());
+				}
+			}
+		}
+	}
+}
+
+fn columns(row: Value) -> Result<(Option<Vec<String>>, Vec<SqliteValue>), SqliteError> {
+	match row {
+		Value::Group(group) => {
+			let names = group
+				.field_names()
+				.map(|names| names.keys().cloned().collect());
+			let values = group
+				.into_fields()
+				.into_iter()
+				.map(encode)
+				.collect::<Result<_, _>>()?;
+			Ok((names, values))
+		}
+		value => Ok((None, vec![encode(value)?])),
+	}
+}
+
+fn encode(value: Value) -> Result<SqliteValue, SqliteError> {
+	Ok(match value {
+		Value::Bool(value) => SqliteValue::Integer(value.into()),
+		Value::U8(value) => SqliteValue::Integer(value.into()),
+		Value::I8(value) => SqliteValue::Integer(value.into()),
+		Value::U16(value) => SqliteValue::Integer(value.into()),
+		Value::I16(value) => SqliteValue::Integer(value.into()),
+		Value::U32(value) => SqliteValue::Integer(value.into()),
+		Value::I32(value) => SqliteValue::Integer(value.into()),
+		Value::U64(value) => SqliteValue::Integer(
+			i64::try_from(value).map_err(|_| SqliteError::Unsupported(value.to_string()))?,
+		),
+		Value::I64(value) => SqliteValue::Integer(value),
+		Value::F32(value) => SqliteValue::Real(value.into()),
+		Value::F64(value) => SqliteValue::Real(value),
+		Value::String(value) => SqliteValue::Text(value),
+		Value::List(list) => {
+			let bytes = list
+				.into_iter()
+				.map(|value| value.into_u8().ok())
+				.collect::<Option<_>>()
+				.ok_or_else(|| SqliteError::Unsupported(String::from("a list of non-u8 values")))?;
+			SqliteValue::Blob(bytes)
+		}
+		Value::Option(None) => SqliteValue::Null,
+		Value::Option(Some(value)) => encode(value.into())?,
+		value => return Err(SqliteError::Unsupported(format!("{:?}", value))),
+	})
+}
+
+fn insert(table: &str, names: Option<&[String]>, len: usize) -> String {
+	let mut query = format!("INSERT INTO {}", EscapeIdentifier(table));
+	if let Some(names) = names {
+		query.push_str(" (");
+		for (i, name) in names.iter().enumerate() {
+			if i != 0 {
+				query.push_str(", ");
+			}
+			write!(query, "{}", EscapeIdentifier(name)).unwrap();
+		}
+		query.push(')');
+	}
+	query.push_str(" VALUES (");
+	for i in 1..=len {
+		if i != 1 {
+			query.push_str(", ");
+		}
+		write!(query, "?{}", i).unwrap();
+	}
+	query.push(')');
+	query
+}
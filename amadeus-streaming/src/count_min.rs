@@ -55,8 +55,26 @@ where
 {
 	/// Create an empty `CountMinSketch` data structure with the specified error tolerance.
 	pub fn new(probability: f64, tolerance: f64, config: C::Config) -> Self {
-		let width = Self::optimal_width(tolerance);
-		let k_num = Self::optimal_k_num(probability);
+		Self::with_max_counters(probability, tolerance, usize::MAX, config)
+	}
+
+	/// Like [`new`](Self::new), but with at most `max_counters` counters. If the
+	/// given `probability` and `tolerance` would need more, the number of hash
+	/// functions and then the width are reduced to fit, so estimates may exceed
+	/// the true value by more than `tolerance` times the total.
+	///
+	/// # Panics
+	///
+	/// Panics if `max_counters` is less than 2.
+	pub fn with_max_counters(
+		probability: f64, tolerance: f64, max_counters: usize, config: C::Config,
+	) -> Self {
+		assert!(max_counters >= 2, "max_counters must be at least 2");
+		let k_num = Self::optimal_k_num(probability).min(max_counters / 2);
+		let mut width = Self::optimal_width(tolerance);
+		while width.saturating_mul(k_num) > max_counters {
+			width /= 2;
+		}
 		let counters: Vec<Vec<C>> = (0..k_num)
 			.map(|_| (0..width).map(|_| C::new(&config)).collect())
 			.collect();
@@ -154,6 +172,11 @@ where
 	// 	Ok(width * mem::size_of::<C>() * k_num)
 	// }
 
+	/// The number of counters, which determines the memory used.
+	pub fn counters(&self) -> usize {
+		(self.mask + 1) * self.k_num
+	}
+
 	/// Clears the `CountMinSketch` data structure, as if it was new.
 	pub fn clear(&mut self) {
 		let config = &self.config;
@@ -241,6 +264,22 @@ mod tests {
 	type CountMinSketch16<K> = super::CountMinSketch<K, u16>;
 	type CountMinSketch64<K> = super::CountMinSketch<K, u64>;
 
+	#[test]
+	fn test_max_counters() {
+		let cms = CountMinSketch64::<&str>::new(0.99, 1e-6, ());
+		assert!(cms.counters() > 1 << 20);
+		let mut cms = CountMinSketch64::<&str>::with_max_counters(0.99, 1e-6, 1000, ());
+		assert!(cms.counters() <= 1000);
+		for _ in 0..300 {
+			let _ = cms.push("key", &1);
+		}
+		assert!(cms.get("key") >= 300);
+		assert_eq!(
+			CountMinSketch64::<&str>::with_max_counters(0.99, 1e-6, 2, ()).counters(),
+			2
+		);
+	}
+
 	#[ignore] // release mode stops panic
 	#[test]
 	#[should_panic]
@@ -13,6 +13,7 @@
 //  * Count–min sketch
 //  * Top k (Count–min sketch plus a doubly linked hashmap to track heavy hitters / top k keys when ordered by aggregated value)
 //  * HyperLogLog
+//  * MinHash
 //  * Reservoir sampling
 //
 // A goal of this library is to enable composition of these algorithms; for example Top k + HyperLogLog to enable an approximate version of something akin to `SELECT key FROM table GROUP BY key ORDER BY COUNT(DISTINCT value) DESC LIMIT k`.
@@ -59,6 +60,7 @@
 mod count_min;
 mod distinct;
 mod linked_list;
+mod min_hash;
 mod ordered_linked_list;
 mod sample;
 mod sort;
@@ -67,6 +69,7 @@ mod traits;
 
 pub use count_min::*;
 pub use distinct::*;
+pub use min_hash::*;
 pub use sample::*;
 pub use sort::*;
 pub use top::*;
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::{
+	fmt, hash::{Hash, Hasher}, marker::PhantomData, ops
+};
+use twox_hash::XxHash;
+
+use super::usize_to_f64;
+use crate::traits::{New, UnionAssign};
+
+/// A [MinHash](https://en.wikipedia.org/wiki/MinHash) signature of a set, for
+/// estimating the [Jaccard similarity](https://en.wikipedia.org/wiki/Jaccard_index)
+/// of sets.
+///
+/// The signature of the union of two sets is the [`union`](MinHash::union) of
+/// their signatures, so signatures of parts of a set can be computed separately
+/// and merged.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MinHash<V: ?Sized> {
+	mins: Box<[u64]>,
+	marker: PhantomData<fn(V)>,
+}
+
+impl<V: ?Sized> MinHash<V>
+where
+	V: Hash,
+{
+	/// Create the signature of an empty set with `hashes` hash functions. The
+	/// standard error of [`similarity`](MinHash::similarity) is roughly
+	/// `1/sqrt(hashes)`.
+	///
+	/// # Panics
+	///
+	/// Panics if `hashes` is zero.
+	pub fn new(hashes: usize) -> Self {
+		assert_ne!(hashes, 0);
+		Self {
+			mins: vec![u64::MAX; hashes].into_boxed_slice(),
+			marker: PhantomData,
+		}
+	}
+
+	/// Create the signature of an empty set, copying the number of hash functions from `min_hash`.
+	pub fn new_from(min_hash: &Self) -> Self {
+		Self::new(min_hash.mins.len())
+	}
+
+	/// "Visit" an element.
+	#[inline]
+	pub fn push(&mut self, value: &V) {
+		let mut hasher = XxHash::default();
+		value.hash(&mut hasher);
+		let x = hasher.finish();
+		for (i, min) in self.mins.iter_mut().enumerate() {
+			*min = (*min).min(mix(x ^ (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)));
+		}
+	}
+
+	/// Merge another signature into `self`.
+	///
+	/// This is the same as the signature of the union of the two sets.
+	///
+	/// # Panics
+	///
+	/// Panics if the signatures have different numbers of hash functions.
+	pub fn union(&mut self, src: &Self) {
+		assert_eq!(src.mins.len(), self.mins.len());
+		for (min, src) in self.mins.iter_mut().zip(src.mins.iter()) {
+			*min = (*min).min(*src);
+		}
+	}
+
+	/// Estimate the Jaccard similarity of the sets `self` and `other` are
+	/// signatures of, i.e. the size of their intersection over that of their union.
+	///
+	/// # Panics
+	///
+	/// Panics if the signatures have different numbers of hash functions.
+	pub fn similarity(&self, other: &Self) -> f64 {
+		assert_eq!(other.mins.len(), self.mins.len());
+		let equal = self
+			.mins
+			.iter()
+			.zip(other.mins.iter())
+			.filter(|(a, b)| a == b)
+			.count();
+		usize_to_f64(equal) / usize_to_f64(self.mins.len())
+	}
+
+	/// Returns true if empty.
+	pub fn is_empty(&self) -> bool {
+		self.mins.iter().all(|&min| min == u64::MAX)
+	}
+
+	/// The signature, i.e. the minimum of each hash function over the set.
+	pub fn signature(&self) -> &[u64] {
+		&self.mins
+	}
+
+	/// Hash each band of `rows` consecutive hash functions, for
+	/// [locality-sensitive hashing](https://en.wikipedia.org/wiki/Locality-sensitive_hashing).
+	/// Sets with Jaccard similarity `s` share at least one band hash with
+	/// probability `1 - (1 - s^rows)^bands`. Trailing hash functions that don't
+	/// fill a band are ignored.
+	///
+	/// # Panics
+	///
+	/// Panics if `rows` is zero.
+	pub fn bands(&self, rows: usize) -> impl Iterator<Item = u64> + '_ {
+		assert_ne!(rows, 0);
+		self.mins.chunks_exact(rows).map(|band| {
+			let mut hasher = XxHash::default();
+			band.hash(&mut hasher);
+			hasher.finish()
+		})
+	}
+}
+
+/// The finalizer of [SplitMix64](http://prng.di.unimi.it/splitmix64.c), deriving
+/// an independent hash for each hash function from a single hash of the element.
+fn mix(mut x: u64) -> u64 {
+	x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	x ^ (x >> 31)
+}
+
+impl<V: ?Sized> Clone for MinHash<V> {
+	fn clone(&self) -> Self {
+		Self {
+			mins: self.mins.clone(),
+			marker: PhantomData,
+		}
+	}
+}
+impl<V: ?Sized> fmt::Debug for MinHash<V>
+where
+	V: Hash,
+{
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("MinHash")
+			.field("hashes", &self.mins.len())
+			.finish()
+	}
+}
+impl<V: ?Sized> New for MinHash<V>
+where
+	V: Hash,
+{
+	type Config = usize;
+	fn new(config: &Self::Config) -> Self {
+		Self::new(*config)
+	}
+}
+impl<'a, V: ?Sized> UnionAssign<&'a MinHash<V>> for MinHash<V>
+where
+	V: Hash,
+{
+	fn union_assign(&mut self, rhs: &'a Self) {
+		self.union(rhs);
+	}
+}
+impl<'a, V: ?Sized> ops::AddAssign<&'a V> for MinHash<V>
+where
+	V: Hash,
+{
+	fn add_assign(&mut self, rhs: &'a V) {
+		self.push(rhs);
+	}
+}
+impl<'a, V: ?Sized> ops::AddAssign<&'a Self> for MinHash<V>
+where
+	V: Hash,
+{
+	fn add_assign(&mut self, rhs: &'a Self) {
+		self.union(rhs);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::MinHash;
+
+	#[test]
+	fn similarity() {
+		let mut a = MinHash::<u32>::new(256);
+		let mut b = MinHash::<u32>::new(256);
+		// |a ∩ b| = 600, |a ∪ b| = 1000
+		for i in 0..800 {
+			a.push(&i);
+		}
+		for i in 200..1000 {
+			b.push(&i);
+		}
+		assert!((a.similarity(&b) - 0.6).abs() < 0.1);
+
+		// Merging the signatures of halves gives the signature of the whole
+		let (mut c, mut d) = (MinHash::new_from(&a), MinHash::new_from(&a));
+		for i in 0..400 {
+			c.push(&i);
+		}
+		for i in 400..800 {
+			d.push(&i);
+		}
+		c.union(&d);
+		assert_eq!(c.signature(), a.signature());
+		assert_eq!(a.similarity(&c), 1.0);
+	}
+}
@@ -46,10 +46,23 @@ pub struct Top<A, C: New> {
 impl<A: Hash + Eq + Clone, C: Ord + New + for<'a> UnionAssign<&'a C> + Intersect> Top<A, C> {
 	/// Create an empty `Top` data structure with the specified `n` capacity.
 	pub fn new(n: usize, probability: f64, tolerance: f64, config: <C as New>::Config) -> Self {
+		Self::with_max_counters(n, probability, tolerance, usize::MAX, config)
+	}
+	/// Like [`new`](Self::new), but with at most `max_counters` counters in the
+	/// count-min sketch, bounding its memory regardless of `probability` and
+	/// `tolerance`. See [`CountMinSketch::with_max_counters`].
+	pub fn with_max_counters(
+		n: usize, probability: f64, tolerance: f64, max_counters: usize, config: <C as New>::Config,
+	) -> Self {
 		Self {
 			map: HashMap::with_capacity_and_hasher(n, RandomXxHashBuilder::default()),
 			list: OrderedLinkedList::new(n),
-			count_min: CountMinSketch::new(probability, tolerance, config.clone()),
+			count_min: CountMinSketch::with_max_counters(
+				probability,
+				tolerance,
+				max_counters,
+				config.clone(),
+			),
 			config,
 		}
 	}
@@ -70,6 +83,11 @@ impl<A: Hash + Eq + Clone, C: Ord + New + for<'a> UnionAssign<&'a C> + Intersect
 	pub fn capacity(&self) -> usize {
 		self.list.capacity()
 	}
+	/// The number of counters in the count-min sketch tracking the elements
+	/// outside the top `n`.
+	pub fn counters(&self) -> usize {
+		self.count_min.counters()
+	}
 	/// "Visit" an element.
 	pub fn push<V: ?Sized>(&mut self, item: A, value: &V)
 	where
@@ -1,10 +1,10 @@
 //! Implement [`Record`] for `Vec<u8>` (byte_array/fixed_len_byte_array), [`Bson`] (bson),
-//! `String` (utf8), [`Json`] (json), [`Enum`] (enum), and `[u8; N]`
+//! `String` (utf8), [`Json`] (json), [`Enum`] (enum), [`Uuid`] (uuid), and `[u8; N]`
 //! (fixed_len_byte_array).
 
 use serde::{Deserialize, Serialize};
 use std::{
-	cmp::Ordering, fmt::{self, Display}
+	cmp::Ordering, error::Error, fmt::{self, Display}, str::FromStr
 };
 
 use super::AmadeusOrd;
@@ -92,3 +92,103 @@ impl From<String> for Enum {
 		Self(string)
 	}
 }
+
+/// A Rust type corresponding to the [UUID logical type](https://github.com/apache/parquet-format/blob/master/LogicalTypes.md#uuid),
+/// a `fixed_len_byte_array(16)` holding the bytes of the UUID in big-endian order.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub struct Uuid([u8; 16]);
+impl Uuid {
+	pub fn from_bytes(bytes: [u8; 16]) -> Self {
+		Self(bytes)
+	}
+	pub fn as_bytes(&self) -> &[u8; 16] {
+		&self.0
+	}
+}
+impl AmadeusOrd for Uuid {
+	fn amadeus_cmp(&self, other: &Self) -> Ordering {
+		Ord::cmp(self, other)
+	}
+}
+impl Display for Uuid {
+	/// Format as lowercase hex in the hyphenated 8-4-4-4-12 form, like
+	/// `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (i, byte) in self.0.iter().enumerate() {
+			if let 4 | 6 | 8 | 10 = i {
+				f.write_str("-")?;
+			}
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+impl FromStr for Uuid {
+	type Err = ParseUuidError;
+
+	/// Parse a UUID as 32 hex digits, either hyphenated in the 8-4-4-4-12 form or
+	/// not at all.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let hyphenated = s.len() == 36 && [8, 13, 18, 23].iter().all(|&i| s.as_bytes()[i] == b'-');
+		let digits = if hyphenated {
+			s.replace('-', "")
+		} else {
+			s.to_owned()
+		};
+		if digits.len() != 32 || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+			return Err(ParseUuidError);
+		}
+		let mut bytes = [0; 16];
+		for (i, byte) in bytes.iter_mut().enumerate() {
+			*byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).unwrap();
+		}
+		Ok(Self(bytes))
+	}
+}
+impl From<[u8; 16]> for Uuid {
+	fn from(bytes: [u8; 16]) -> Self {
+		Self(bytes)
+	}
+}
+impl From<Uuid> for [u8; 16] {
+	fn from(uuid: Uuid) -> Self {
+		uuid.0
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseUuidError;
+impl Display for ParseUuidError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "error parsing uuid")
+	}
+}
+impl Error for ParseUuidError {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_uuid() {
+		let uuid = Uuid::from_bytes([
+			0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f,
+			0xe0, 0xc8,
+		]);
+		assert_eq!(uuid.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+		assert_eq!(uuid.to_string().parse(), Ok(uuid));
+		assert_eq!("67E5504410B1426F9247BB680E5FE0C8".parse(), Ok(uuid));
+		assert_eq!(
+			"67e55044-10b1-426f-9247-bb680e5fe0c".parse::<Uuid>(),
+			Err(ParseUuidError)
+		);
+		assert_eq!(
+			"67e5504410b1-426f-9247-bb680e5fe0c8".parse::<Uuid>(),
+			Err(ParseUuidError)
+		);
+		assert_eq!(
+			"67e55044-10b1-426f-9247-bb680e5fe0cg".parse::<Uuid>(),
+			Err(ParseUuidError)
+		);
+	}
+}
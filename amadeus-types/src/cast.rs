@@ -0,0 +1,486 @@
+//! Cast [`Value`]s between primitive types, either checked or lossy.
+
+use serde::{Deserialize, Serialize};
+use std::{
+	convert::TryFrom, error::Error, fmt::{self, Display}
+};
+
+use super::{
+	coerce::as_int, DateTimeWithoutTimezone, DateWithoutTimezone, Duration, Schema, Timezone, Value
+};
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+/// Why a [`Value`] couldn't be cast.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum CastErrorKind {
+	/// The value is out of the range of the type.
+	Overflow,
+	/// The value can't be represented exactly by the type, such as a fraction
+	/// cast to an integer.
+	PrecisionLoss,
+	/// The string couldn't be parsed as the type.
+	Parse,
+	/// There's no cast between the types.
+	Incompatible,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct CastError {
+	pub value: String,
+	pub schema: String,
+	pub kind: CastErrorKind,
+}
+impl Error for CastError {}
+impl Display for CastError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let reason = match self.kind {
+			CastErrorKind::Overflow => "out of range",
+			CastErrorKind::PrecisionLoss => "would lose precision",
+			CastErrorKind::Parse => "could not parse",
+			CastErrorKind::Incompatible => "incompatible types",
+		};
+		write!(
+			f,
+			"could not cast {} to {}: {}",
+			self.value, self.schema, reason
+		)
+	}
+}
+
+impl Value {
+	/// Cast to the primitive type `schema`, failing rather than changing the
+	/// value.
+	///
+	/// Integers and floats cast to each other and to `String`, and strings are
+	/// parsed into them. Dates cast to and from integers as days since the Unix
+	/// epoch, and date-times as milliseconds, taken to be UTC. A value out of
+	/// range of the type is an [`Overflow`](CastErrorKind::Overflow), and one it
+	/// can't represent exactly, such as `1.5` as an integer or a date-time with
+	/// a time of day as a date, is a [`PrecisionLoss`](CastErrorKind::PrecisionLoss).
+	/// Nulls cast to nulls of optional schemas.
+	pub fn cast_checked(self, schema: &Schema) -> Result<Self, CastError> {
+		self.cast(schema, false)
+	}
+
+	/// Cast to the primitive type `schema` as [`cast_checked`](Self::cast_checked)
+	/// does, but saturating values out of range of an integer type to its
+	/// bounds, truncating floats towards zero, rounding to the nearest float,
+	/// and truncating date-times to dates. Only strings that don't parse,
+	/// dates out of range and incompatible types still fail.
+	pub fn cast_lossy(self, schema: &Schema) -> Result<Self, CastError> {
+		self.cast(schema, true)
+	}
+
+	fn cast(self, schema: &Schema, lossy: bool) -> Result<Self, CastError> {
+		match (schema, self) {
+			(Schema::Option(_), Value::Option(None)) => Ok(Value::Option(None)),
+			(Schema::Option(schema), Value::Option(Some(value))) => Value::from(value)
+				.cast(schema, lossy)
+				.map(|value| Value::from(Some(value))),
+			(Schema::Option(schema), value) => value
+				.cast(schema, lossy)
+				.map(|value| Value::from(Some(value))),
+			(schema, Value::Option(Some(value))) => Value::from(value).cast(schema, lossy),
+			(schema, value) => cast(schema, &value, lossy).map_err(|kind| CastError {
+				value: format!("{:?}", value),
+				schema: format!("{:?}", schema),
+				kind,
+			}),
+		}
+	}
+}
+
+#[allow(clippy::too_many_lines)]
+fn cast(schema: &Schema, value: &Value, lossy: bool) -> Result<Value, CastErrorKind> {
+	macro_rules! int {
+		($($variant:ident $type:ty),*) => {
+			match schema {
+				$(Schema::$variant => {
+					let int = to_int(value, <$type>::MIN.into(), <$type>::MAX.into(), lossy)?;
+					return Ok(Value::$variant(<$type>::try_from(int).unwrap()));
+				})*
+				_ => (),
+			}
+		};
+	}
+	int!(U8 u8, I8 i8, U16 u16, I16 i16, U32 u32, I32 i32, U64 u64, I64 i64);
+	Ok(match schema {
+		Schema::F64 => Value::F64(match *value {
+			Value::Bool(value) => value.into(),
+			Value::F32(value) => value.into(),
+			Value::F64(value) => value,
+			Value::String(ref string) => string.trim().parse().map_err(|_| CastErrorKind::Parse)?,
+			ref value => {
+				let int = as_int(value).ok_or(CastErrorKind::Incompatible)?;
+				#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+				let float = int as f64;
+				#[allow(clippy::cast_possible_truncation)]
+				if !lossy && float as i128 != int {
+					return Err(CastErrorKind::PrecisionLoss);
+				}
+				float
+			}
+		}),
+		Schema::F32 => Value::F32(match *value {
+			Value::Bool(value) => value.into(),
+			Value::F32(value) => value,
+			#[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+			Value::F64(value) => {
+				let float = value as f32;
+				if !lossy && float.is_infinite() && value.is_finite() {
+					return Err(CastErrorKind::Overflow);
+				}
+				if !lossy && f64::from(float) != value && !value.is_nan() {
+					return Err(CastErrorKind::PrecisionLoss);
+				}
+				float
+			}
+			Value::String(ref string) => string.trim().parse().map_err(|_| CastErrorKind::Parse)?,
+			ref value => {
+				let int = as_int(value).ok_or(CastErrorKind::Incompatible)?;
+				#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+				let float = int as f32;
+				#[allow(clippy::cast_possible_truncation)]
+				if !lossy && float as i128 != int {
+					return Err(CastErrorKind::PrecisionLoss);
+				}
+				float
+			}
+		}),
+		Schema::Bool => match *value {
+			Value::Bool(value) => Value::Bool(value),
+			Value::String(ref string) => {
+				Value::Bool(string.trim().parse().map_err(|_| CastErrorKind::Parse)?)
+			}
+			_ => return Err(CastErrorKind::Incompatible),
+		},
+		Schema::String => Value::String(match *value {
+			Value::Bool(value) => value.to_string(),
+			Value::F32(value) => value.to_string(),
+			Value::F64(value) => value.to_string(),
+			Value::Date(ref value) => value.to_string(),
+			Value::DateWithoutTimezone(ref value) => value.to_string(),
+			Value::DateTime(ref value) => value.to_string(),
+			Value::DateTimeWithoutTimezone(ref value) => value.to_string(),
+			Value::String(ref value) => value.clone(),
+			ref value => as_int(value)
+				.ok_or(CastErrorKind::Incompatible)?
+				.to_string(),
+		}),
+		Schema::Date | Schema::DateWithoutTimezone => {
+			let (date, timezone) = match *value {
+				Value::Date(date) => (date.without_timezone(), date.timezone()),
+				Value::DateWithoutTimezone(date) => (date, Timezone::UTC),
+				Value::DateTime(date_time) => (
+					to_date(date_time.without_timezone(), lossy)?,
+					date_time.timezone(),
+				),
+				Value::DateTimeWithoutTimezone(date_time) => {
+					(to_date(date_time, lossy)?, Timezone::UTC)
+				}
+				Value::String(ref string) => {
+					return schema.parse(string.trim()).ok_or(CastErrorKind::Parse)
+				}
+				ref value => {
+					let days = as_int(value).ok_or(CastErrorKind::Incompatible)?;
+					let date = i64::try_from(days)
+						.ok()
+						.and_then(DateWithoutTimezone::from_days)
+						.ok_or(CastErrorKind::Overflow)?;
+					(date, Timezone::UTC)
+				}
+			};
+			if let Schema::Date = schema {
+				Value::Date(date.with_timezone(timezone))
+			} else {
+				Value::DateWithoutTimezone(date)
+			}
+		}
+		Schema::DateTime | Schema::DateTimeWithoutTimezone => {
+			let (date_time, timezone) = match *value {
+				Value::Date(date) => (midnight(date.without_timezone()), date.timezone()),
+				Value::DateWithoutTimezone(date) => (midnight(date), Timezone::UTC),
+				Value::DateTime(date_time) => (date_time.without_timezone(), date_time.timezone()),
+				Value::DateTimeWithoutTimezone(date_time) => (date_time, Timezone::UTC),
+				Value::String(ref string) => {
+					return schema.parse(string.trim()).ok_or(CastErrorKind::Parse)
+				}
+				ref value => {
+					let millis = as_int(value).ok_or(CastErrorKind::Incompatible)?;
+					(from_millis(millis)?, Timezone::UTC)
+				}
+			};
+			if let Schema::DateTime = schema {
+				Value::DateTime(date_time.with_timezone(timezone))
+			} else {
+				Value::DateTimeWithoutTimezone(date_time)
+			}
+		}
+		_ => return Err(CastErrorKind::Incompatible),
+	})
+}
+
+/// Cast to an integer in the range `min..=max`.
+fn to_int(value: &Value, min: i128, max: i128, lossy: bool) -> Result<i128, CastErrorKind> {
+	let int = match *value {
+		Value::Bool(value) => value.into(),
+		Value::F32(value) => float_to_int(value.into(), lossy)?,
+		Value::F64(value) => float_to_int(value, lossy)?,
+		Value::String(ref string) => {
+			let string = string.trim();
+			match string.parse::<i128>() {
+				Ok(int) => int,
+				Err(_) => float_to_int(string.parse().map_err(|_| CastErrorKind::Parse)?, lossy)?,
+			}
+		}
+		Value::Date(date) => date.as_days().into(),
+		Value::DateWithoutTimezone(date) => date.as_days().into(),
+		Value::DateTime(date_time) => as_millis(date_time.without_timezone(), lossy)?,
+		Value::DateTimeWithoutTimezone(date_time) => as_millis(date_time, lossy)?,
+		ref value => as_int(value).ok_or(CastErrorKind::Incompatible)?,
+	};
+	if (min..=max).contains(&int) {
+		Ok(int)
+	} else if lossy {
+		Ok(int.max(min).min(max))
+	} else {
+		Err(CastErrorKind::Overflow)
+	}
+}
+
+/// Truncate towards zero if `lossy`, saturating at the bounds of `i128`, which
+/// are out of range of every integer type a value can be cast to.
+fn float_to_int(value: f64, lossy: bool) -> Result<i128, CastErrorKind> {
+	if !lossy && (value.is_nan() || value.is_finite() && value.fract() != 0.0) {
+		return Err(CastErrorKind::PrecisionLoss);
+	}
+	#[allow(clippy::cast_possible_truncation)]
+	Ok(value as i128)
+}
+
+fn epoch() -> DateTimeWithoutTimezone {
+	DateTimeWithoutTimezone::new(1970, 1, 1, 0, 0, 0, 0).unwrap()
+}
+
+fn midnight(date: DateWithoutTimezone) -> DateTimeWithoutTimezone {
+	DateTimeWithoutTimezone::from_date_time(date, epoch().time()).unwrap()
+}
+
+fn to_date(
+	date_time: DateTimeWithoutTimezone, lossy: bool,
+) -> Result<DateWithoutTimezone, CastErrorKind> {
+	if !lossy && date_time.time() != epoch().time() {
+		return Err(CastErrorKind::PrecisionLoss);
+	}
+	Ok(date_time.date())
+}
+
+/// Milliseconds since the Unix epoch, truncated towards zero if `lossy`.
+fn as_millis(date_time: DateTimeWithoutTimezone, lossy: bool) -> Result<i128, CastErrorKind> {
+	let duration = date_time.duration_since(&epoch());
+	if !lossy && duration.nanos() % NANOS_PER_MILLI != 0 {
+		return Err(CastErrorKind::PrecisionLoss);
+	}
+	Ok(i128::from(duration.days()) * i128::from(MILLIS_PER_DAY)
+		+ i128::from(duration.nanos() / NANOS_PER_MILLI))
+}
+
+fn from_millis(millis: i128) -> Result<DateTimeWithoutTimezone, CastErrorKind> {
+	let millis = i64::try_from(millis).map_err(|_| CastErrorKind::Overflow)?;
+	Duration::new(
+		0,
+		millis / MILLIS_PER_DAY,
+		millis % MILLIS_PER_DAY * NANOS_PER_MILLI,
+	)
+	.and_then(|duration| epoch().checked_add(&duration))
+	.ok_or(CastErrorKind::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn int_narrowing() {
+		assert_eq!(
+			Value::I64(100).cast_checked(&Schema::U8),
+			Ok(Value::U8(100))
+		);
+		let err = Value::I64(300).cast_checked(&Schema::U8).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+		let err = Value::I32(-1).cast_checked(&Schema::U64).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+
+		assert_eq!(Value::I64(300).cast_lossy(&Schema::U8), Ok(Value::U8(255)));
+		assert_eq!(
+			Value::I64(-300).cast_lossy(&Schema::I8),
+			Ok(Value::I8(-128))
+		);
+		assert_eq!(Value::I32(-1).cast_lossy(&Schema::U64), Ok(Value::U64(0)));
+		assert_eq!(
+			Value::U64(u64::MAX).cast_lossy(&Schema::I64),
+			Ok(Value::I64(i64::MAX))
+		);
+	}
+
+	#[test]
+	fn float_to_int() {
+		assert_eq!(
+			Value::F64(42.0).cast_checked(&Schema::I32),
+			Ok(Value::I32(42))
+		);
+		let err = Value::F64(1.5).cast_checked(&Schema::I32).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		let err = Value::F64(f64::NAN).cast_checked(&Schema::I32).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		let err = Value::F64(1e10).cast_checked(&Schema::I32).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+		let err = Value::F32(f32::INFINITY)
+			.cast_checked(&Schema::I64)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+
+		assert_eq!(Value::F64(1.9).cast_lossy(&Schema::I32), Ok(Value::I32(1)));
+		assert_eq!(
+			Value::F64(-1.9).cast_lossy(&Schema::I32),
+			Ok(Value::I32(-1))
+		);
+		assert_eq!(
+			Value::F64(1e10).cast_lossy(&Schema::I32),
+			Ok(Value::I32(i32::MAX))
+		);
+		assert_eq!(
+			Value::F64(f64::NAN).cast_lossy(&Schema::U8),
+			Ok(Value::U8(0))
+		);
+	}
+
+	#[test]
+	fn int_to_float() {
+		assert_eq!(
+			Value::I64(1 << 53).cast_checked(&Schema::F64),
+			Ok(Value::F64(9_007_199_254_740_992.0))
+		);
+		let err = Value::I64((1 << 53) + 1)
+			.cast_checked(&Schema::F64)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		assert_eq!(
+			Value::I64((1 << 53) + 1).cast_lossy(&Schema::F64),
+			Ok(Value::F64(9_007_199_254_740_992.0))
+		);
+		let err = Value::F64(0.1).cast_checked(&Schema::F32).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		let err = Value::F64(1e300).cast_checked(&Schema::F32).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+		assert_eq!(
+			Value::F64(0.5).cast_checked(&Schema::F32),
+			Ok(Value::F32(0.5))
+		);
+		assert_eq!(
+			Value::F64(0.1).cast_lossy(&Schema::F32),
+			Ok(Value::F32(0.1))
+		);
+	}
+
+	#[test]
+	fn strings() {
+		assert_eq!(
+			Value::from(String::from(" 42 ")).cast_checked(&Schema::U16),
+			Ok(Value::U16(42))
+		);
+		assert_eq!(
+			Value::from(String::from("2.5")).cast_checked(&Schema::F64),
+			Ok(Value::F64(2.5))
+		);
+		let err = Value::from(String::from("2.5"))
+			.cast_checked(&Schema::I64)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		assert_eq!(
+			Value::from(String::from("2.5")).cast_lossy(&Schema::I64),
+			Ok(Value::I64(2))
+		);
+		for schema in &[Schema::I64, Schema::F64, Schema::Bool, Schema::Date] {
+			let err = Value::from(String::from("abc"))
+				.cast_lossy(schema)
+				.unwrap_err();
+			assert_eq!(err.kind, CastErrorKind::Parse, "{:?}", schema);
+		}
+		let err = Value::from(String::from("300"))
+			.cast_checked(&Schema::U8)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+
+		assert_eq!(
+			Value::I32(-7).cast_checked(&Schema::String),
+			Ok(Value::from(String::from("-7")))
+		);
+		assert_eq!(
+			Value::F64(2.5).cast_checked(&Schema::String),
+			Ok(Value::from(String::from("2.5")))
+		);
+	}
+
+	#[test]
+	fn temporal() {
+		let date = DateWithoutTimezone::new(2020, 3, 1).unwrap();
+		let days = date.as_days();
+		assert_eq!(
+			Value::DateWithoutTimezone(date).cast_checked(&Schema::I64),
+			Ok(Value::I64(days))
+		);
+		assert_eq!(
+			Value::I64(days).cast_checked(&Schema::Date),
+			Ok(Value::Date(date.with_timezone(Timezone::UTC)))
+		);
+		let err = Value::I64(i64::MAX)
+			.cast_lossy(&Schema::DateWithoutTimezone)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Overflow);
+
+		let date_time =
+			DateTimeWithoutTimezone::new(1969, 12, 31, 23, 59, 58, 500_000_000).unwrap();
+		assert_eq!(
+			Value::DateTimeWithoutTimezone(date_time).cast_checked(&Schema::I64),
+			Ok(Value::I64(-1500))
+		);
+		assert_eq!(
+			Value::I64(-1500).cast_checked(&Schema::DateTimeWithoutTimezone),
+			Ok(Value::DateTimeWithoutTimezone(date_time))
+		);
+		let precise = DateTimeWithoutTimezone::new(2020, 3, 1, 12, 0, 0, 1).unwrap();
+		let err = Value::DateTimeWithoutTimezone(precise)
+			.cast_checked(&Schema::I64)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		let err = Value::DateTimeWithoutTimezone(precise)
+			.cast_checked(&Schema::DateWithoutTimezone)
+			.unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::PrecisionLoss);
+		assert_eq!(
+			Value::DateTimeWithoutTimezone(precise).cast_lossy(&Schema::DateWithoutTimezone),
+			Ok(Value::DateWithoutTimezone(date))
+		);
+	}
+
+	#[test]
+	fn options() {
+		assert_eq!(
+			Value::Option(None).cast_checked(&Schema::Option(Box::new(Schema::U8))),
+			Ok(Value::Option(None))
+		);
+		assert_eq!(
+			Value::I64(7).cast_checked(&Schema::Option(Box::new(Schema::U8))),
+			Ok(Value::from(Some(Value::U8(7))))
+		);
+		assert_eq!(
+			Value::from(Some(Value::I64(7))).cast_checked(&Schema::U8),
+			Ok(Value::U8(7))
+		);
+		let err = Value::Option(None).cast_checked(&Schema::U8).unwrap_err();
+		assert_eq!(err.kind, CastErrorKind::Incompatible);
+	}
+}
@@ -0,0 +1,261 @@
+//! Coerce dynamically-typed [`Value`]s into a [`Schema`].
+
+use fxhash::FxBuildHasher;
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use serde_closure::FnMutNamed;
+use std::{
+	collections::HashMap, convert::TryFrom, error::Error, fmt::{self, Display}, mem, str::FromStr, sync::Arc
+};
+
+use super::{
+	Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Enum, Group, Json, List, Schema, TimeWithoutTimezone, Timezone, Union, Value
+};
+
+/// What [`Schema::coerce`] does with a value that can't be coerced.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum CoercePolicy {
+	/// Fail with a [`CoerceError`].
+	Error,
+	/// Replace it with a null, i.e. `Value::Option(None)`.
+	Null,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct CoerceError {
+	pub value: String,
+	pub schema: String,
+}
+impl Error for CoerceError {}
+impl Display for CoerceError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "could not coerce {} to {}", self.value, self.schema)
+	}
+}
+
+impl Schema {
+	/// Coerce `value` into this schema.
+	///
+	/// Values already of the right type are left as they are. Integers are
+	/// converted to other integer types if they fit, and to floats; strings are
+	/// parsed into numbers, bools, dates and times; lists, maps and groups are
+	/// coerced element-wise, with named fields matched up by name; and unions
+	/// have their payload coerced to the schema of their variant. A missing
+	/// field or empty string becomes null where the schema is optional.
+	/// Anything else is incompatible, and handled per `policy`.
+	pub fn coerce(&self, value: Value, policy: CoercePolicy) -> Result<Value, CoerceError> {
+		let value = match (self, value) {
+			(Schema::Option(_), Value::Option(None)) => return Ok(Value::Option(None)),
+			(Schema::Option(schema), Value::String(value))
+				if value.is_empty() && **schema != Schema::String =>
+			{
+				return Ok(Value::Option(None))
+			}
+			(Schema::Option(schema), Value::Option(Some(value))) => {
+				return schema.coerce_some(value.into(), policy)
+			}
+			(Schema::Option(schema), value) => return schema.coerce_some(value, policy),
+			(schema, Value::Option(Some(value))) => return schema.coerce(value.into(), policy),
+			(Schema::List(schema), Value::List(list)) => {
+				return list
+					.into_iter()
+					.map(|value| schema.coerce(value, policy))
+					.collect::<Result<List<Value>, _>>()
+					.map(Value::List)
+			}
+			(Schema::Map(schema), Value::Map(map)) => {
+				let (key_schema, value_schema) = &**schema;
+				return map
+					.into_iter()
+					.map(|(key, value)| {
+						Ok((
+							key_schema.coerce(key, policy)?,
+							value_schema.coerce(value, policy)?,
+						))
+					})
+					.collect::<Result<HashMap<_, _>, _>>()
+					.map(Value::Map);
+			}
+			(Schema::Group(schemas, names), Value::Group(group)) => {
+				return self.coerce_group(schemas, names.as_ref(), group, policy)
+			}
+			(Schema::Union(schemas), Value::Union(union))
+				if union.discriminant() < schemas.len() =>
+			{
+				let discriminant = union.discriminant();
+				return schemas[discriminant]
+					.coerce(union.into_value(), policy)
+					.map(|value| Value::Union(Union::new(discriminant, value)));
+			}
+			(schema, value) => match schema.convert(value) {
+				Ok(value) => return Ok(value),
+				Err(value) => value,
+			},
+		};
+		self.incompatible(&value, policy)
+	}
+
+	fn coerce_some(&self, value: Value, policy: CoercePolicy) -> Result<Value, CoerceError> {
+		Ok(match self.coerce(value, policy)? {
+			Value::Option(None) => Value::Option(None),
+			value => Value::from(Some(value)),
+		})
+	}
+
+	fn coerce_group(
+		&self, schemas: &[Schema],
+		names: Option<&Arc<LinkedHashMap<String, usize, FxBuildHasher>>>, group: Group,
+		policy: CoercePolicy,
+	) -> Result<Value, CoerceError> {
+		let mut fields = match (names, group.field_names().cloned()) {
+			(Some(names), Some(group_names)) => {
+				let mut values = group
+					.into_fields()
+					.into_iter()
+					.map(Some)
+					.collect::<Vec<_>>();
+				let mut fields = vec![Value::Option(None); schemas.len()];
+				for (name, &i) in names.iter() {
+					if let Some(&j) = group_names.get(name) {
+						fields[i] = values[j].take().unwrap();
+					}
+				}
+				fields
+			}
+			_ if group.fields().len() == schemas.len() => group.into_fields(),
+			_ => return self.incompatible(&Value::Group(group), policy),
+		};
+		for (field, schema) in fields.iter_mut().zip(schemas) {
+			let value = mem::replace(field, Value::Option(None));
+			*field = schema.coerce(value, policy)?;
+		}
+		Ok(Value::Group(Group::new(fields, names.cloned())))
+	}
+
+	fn incompatible(&self, value: &Value, policy: CoercePolicy) -> Result<Value, CoerceError> {
+		match policy {
+			CoercePolicy::Error => Err(CoerceError {
+				value: format!("{:?}", value),
+				schema: format!("{:?}", self),
+			}),
+			CoercePolicy::Null => Ok(Value::Option(None)),
+		}
+	}
+
+	/// Convert a non-null value to a primitive type, returning it unchanged if
+	/// it's incompatible.
+	#[allow(clippy::result_large_err)]
+	fn convert(&self, value: Value) -> Result<Value, Value> {
+		macro_rules! int {
+			($($variant:ident $type:ty),*) => {
+				match self {
+					$(Schema::$variant => if let Some(int) = as_int(&value) {
+						return <$type>::try_from(int).map(Value::$variant).map_err(|_| value);
+					},)*
+					_ => (),
+				}
+			};
+		}
+		int!(U8 u8, I8 i8, U16 u16, I16 i16, U32 u32, I32 i32, U64 u64, I64 i64);
+		#[allow(clippy::cast_precision_loss)]
+		let value = match (self, value) {
+			(Schema::F32, Value::F32(value)) => Value::F32(value),
+			(Schema::F64, Value::F32(value)) => Value::F64(value.into()),
+			(Schema::F64, Value::F64(value)) => Value::F64(value),
+			(Schema::F32, value) if as_int(&value).is_some() => {
+				Value::F32(as_int(&value).unwrap() as f32)
+			}
+			(Schema::F64, value) if as_int(&value).is_some() => {
+				Value::F64(as_int(&value).unwrap() as f64)
+			}
+			(schema, Value::String(string)) => {
+				return schema.parse(&string).ok_or(Value::String(string))
+			}
+			(Schema::Bool, value @ Value::Bool(_))
+			| (Schema::Date, value @ Value::Date(_))
+			| (Schema::DateWithoutTimezone, value @ Value::DateWithoutTimezone(_))
+			| (Schema::Time, value @ Value::Time(_))
+			| (Schema::TimeWithoutTimezone, value @ Value::TimeWithoutTimezone(_))
+			| (Schema::DateTime, value @ Value::DateTime(_))
+			| (Schema::DateTimeWithoutTimezone, value @ Value::DateTimeWithoutTimezone(_))
+			| (Schema::Timezone, value @ Value::Timezone(_))
+			| (Schema::Duration, value @ Value::Duration(_))
+			| (Schema::Decimal, value @ Value::Decimal(_))
+			| (Schema::Bson, value @ Value::Bson(_))
+			| (Schema::Json, value @ Value::Json(_))
+			| (Schema::Enum, value @ Value::Enum(_)) => value,
+			(_, value) => return Err(value),
+		};
+		Ok(value)
+	}
+
+	/// Parse a string as a primitive type. Strings without a timezone are
+	/// taken to be UTC where the schema requires one.
+	pub(crate) fn parse(&self, string: &str) -> Option<Value> {
+		Some(match self {
+			Schema::Bool => Value::Bool(string.parse().ok()?),
+			Schema::U8 => Value::U8(string.parse().ok()?),
+			Schema::I8 => Value::I8(string.parse().ok()?),
+			Schema::U16 => Value::U16(string.parse().ok()?),
+			Schema::I16 => Value::I16(string.parse().ok()?),
+			Schema::U32 => Value::U32(string.parse().ok()?),
+			Schema::I32 => Value::I32(string.parse().ok()?),
+			Schema::U64 => Value::U64(string.parse().ok()?),
+			Schema::I64 => Value::I64(string.parse().ok()?),
+			Schema::F32 => Value::F32(string.parse().ok()?),
+			Schema::F64 => Value::F64(string.parse().ok()?),
+			Schema::Date => Value::Date(Date::from_str(string).ok().or_else(|| {
+				DateWithoutTimezone::from_str(string)
+					.ok()
+					.map(|date| date.with_timezone(Timezone::UTC))
+			})?),
+			Schema::DateWithoutTimezone => Value::DateWithoutTimezone(string.parse().ok()?),
+			Schema::Time => Value::Time(
+				TimeWithoutTimezone::from_str(string)
+					.ok()?
+					.with_timezone(Timezone::UTC),
+			),
+			Schema::TimeWithoutTimezone => Value::TimeWithoutTimezone(string.parse().ok()?),
+			Schema::DateTime => Value::DateTime(DateTime::from_str(string).ok().or_else(|| {
+				DateTimeWithoutTimezone::from_str(string)
+					.ok()
+					.map(|date_time| date_time.with_timezone(Timezone::UTC))
+			})?),
+			Schema::DateTimeWithoutTimezone => Value::DateTimeWithoutTimezone(string.parse().ok()?),
+			Schema::Duration => Value::Duration(string.parse().ok()?),
+			Schema::String => Value::String(string.to_owned()),
+			Schema::Json => Value::Json(Json::from(string.to_owned())),
+			Schema::Enum => Value::Enum(Enum::from(string.to_owned())),
+			_ => return None,
+		})
+	}
+}
+
+pub(crate) fn as_int(value: &Value) -> Option<i128> {
+	Some(match *value {
+		Value::U8(value) => value.into(),
+		Value::I8(value) => value.into(),
+		Value::U16(value) => value.into(),
+		Value::I16(value) => value.into(),
+		Value::U32(value) => value.into(),
+		Value::I32(value) => value.into(),
+		Value::U64(value) => value.into(),
+		Value::I64(value) => value.into(),
+		_ => return None,
+	})
+}
+
+FnMutNamed! {
+	pub type CoerceSchema<> = |self, schema: Schema, policy: CoercePolicy|value=> Value| -> Result<Value, CoerceError>
+	where
+	{
+		self.schema.coerce(value, self.policy)
+	}
+}
+
+/// A closure that coerces each row of a distributed stream of [`Value`]s into
+/// `schema`, for use with `map`. Parallel streams can instead call
+/// [`Schema::coerce`] from a closure.
+pub fn coerce_schema(schema: Schema, policy: CoercePolicy) -> CoerceSchema {
+	CoerceSchema::new(schema, policy)
+}
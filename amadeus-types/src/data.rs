@@ -72,7 +72,7 @@ macro_rules! impl_data {
 		}
 	)*);
 }
-impl_data!(bool u8 i8 u16 i16 u32 i32 u64 i64 f32 f64 String Bson Json Enum Decimal Group Date DateWithoutTimezone Time TimeWithoutTimezone DateTime DateTimeWithoutTimezone Timezone Webpage<'static> Url IpAddr);
+impl_data!(bool u8 i8 u16 i16 u32 i32 u64 i64 f32 f64 String Bson Json Enum Decimal Group Date DateWithoutTimezone Time TimeWithoutTimezone DateTime DateTimeWithoutTimezone Timezone Duration Webpage<'static> Url IpAddr GeoPoint Uuid);
 
 // Implement Record for common array lengths.
 macro_rules! array {
@@ -0,0 +1,617 @@
+//! Evaluate simple expressions and predicates over the fields of
+//! dynamically-typed rows.
+
+use serde::{Deserialize, Serialize};
+use std::{
+	cmp::Ordering, convert::TryFrom, error::Error, fmt::{self, Display}, ops
+};
+
+use super::{infer::widen, logic, Group, Schema, Value};
+
+/// An arithmetic expression over the fields of a [`Group`], built from
+/// [`Expr::col`] and literals with the usual operators, for example
+/// `Expr::col("price") * Expr::col("quantity")` or `Expr::col("n") + 1`.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Expr {
+	/// The value of the named field.
+	Column(String),
+	Literal(Value),
+	Add(Box<Expr>, Box<Expr>),
+	Sub(Box<Expr>, Box<Expr>),
+	Mul(Box<Expr>, Box<Expr>),
+	Div(Box<Expr>, Box<Expr>),
+	Neg(Box<Expr>),
+}
+
+impl Expr {
+	pub fn col(name: impl Into<String>) -> Self {
+		Self::Column(name.into())
+	}
+	pub fn lit(value: impl Into<Value>) -> Self {
+		Self::Literal(value.into())
+	}
+
+	/// Evaluate against `row`.
+	///
+	/// Operands of numeric types are promoted to a common type as
+	/// [`Schema::infer`] does, so integers widen to the wider type and mix with
+	/// floats as `F64`; integer arithmetic that overflows that type is an
+	/// error, as is integer division by zero. A null operand makes the result
+	/// null, and an optional operand makes it optional.
+	pub fn eval(&self, row: &Group) -> Result<Value, EvalError> {
+		Ok(match self {
+			Self::Column(name) => row
+				.get(name)
+				.cloned()
+				.ok_or_else(|| EvalError::MissingColumn(name.clone()))?,
+			Self::Literal(value) => value.clone(),
+			Self::Add(a, b) => binary(Op::Add, a.eval(row)?, b.eval(row)?)?,
+			Self::Sub(a, b) => binary(Op::Sub, a.eval(row)?, b.eval(row)?)?,
+			Self::Mul(a, b) => binary(Op::Mul, a.eval(row)?, b.eval(row)?)?,
+			Self::Div(a, b) => binary(Op::Div, a.eval(row)?, b.eval(row)?)?,
+			Self::Neg(a) => binary(Op::Sub, Value::I8(0), a.eval(row)?)?,
+		})
+	}
+
+	pub fn equals(self, other: impl Into<Self>) -> Predicate {
+		Predicate::Eq(self, other.into())
+	}
+	pub fn lt(self, other: impl Into<Self>) -> Predicate {
+		Predicate::Lt(self, other.into())
+	}
+	pub fn le(self, other: impl Into<Self>) -> Predicate {
+		Predicate::Le(self, other.into())
+	}
+	pub fn gt(self, other: impl Into<Self>) -> Predicate {
+		Predicate::Gt(self, other.into())
+	}
+	pub fn ge(self, other: impl Into<Self>) -> Predicate {
+		Predicate::Ge(self, other.into())
+	}
+}
+
+/// A condition on the fields of a [`Group`], built by comparing [`Expr`]s and
+/// combining the results with [`and`](Predicate::and), [`or`](Predicate::or)
+/// and `!`, for example `Expr::col("id").equals(7).and(Expr::col("n").gt(0))`.
+///
+/// [`filter_by_schema_predicate`](super::ParallelStreamRows::filter_by_schema_predicate)
+/// applies it to each row of a stream. Sources that can skip data, such as
+/// Parquet's Bloom filters, take it themselves, skipping what can't match the
+/// [`equalities`](Predicate::equalities) they can push down and applying it to
+/// each row of the rest.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Predicate {
+	Eq(Expr, Expr),
+	Lt(Expr, Expr),
+	Le(Expr, Expr),
+	Gt(Expr, Expr),
+	Ge(Expr, Expr),
+	And(Box<Predicate>, Box<Predicate>),
+	Or(Box<Predicate>, Box<Predicate>),
+	Not(Box<Predicate>),
+}
+
+impl Predicate {
+	#[must_use]
+	pub fn and(self, other: Self) -> Self {
+		Self::And(Box::new(self), Box::new(other))
+	}
+	#[must_use]
+	pub fn or(self, other: Self) -> Self {
+		Self::Or(Box::new(self), Box::new(other))
+	}
+
+	/// Evaluate against `row`, following SQL's [three-valued logic](logic): a
+	/// comparison with null is null, as is `NOT` of null, while `AND` and `OR`
+	/// are null unless the other operand decides them. Rows are kept only where
+	/// this is `Some(true)`.
+	///
+	/// Numeric operands are promoted to a common type as for [`Expr::eval`],
+	/// while others compare only with values of the same type.
+	pub fn eval(&self, row: &Group) -> Result<Option<bool>, EvalError> {
+		let cmp = |op: &str, a: &Expr, b: &Expr, f: fn(Ordering) -> bool| {
+			compare(op, a.eval(row)?, b.eval(row)?).map(|ord| ord.map(f))
+		};
+		match self {
+			Self::Eq(a, b) => cmp("=", a, b, |ord| ord == Ordering::Equal),
+			Self::Lt(a, b) => cmp("<", a, b, |ord| ord == Ordering::Less),
+			Self::Le(a, b) => cmp("<=", a, b, |ord| ord != Ordering::Greater),
+			Self::Gt(a, b) => cmp(">", a, b, |ord| ord == Ordering::Greater),
+			Self::Ge(a, b) => cmp(">=", a, b, |ord| ord != Ordering::Less),
+			Self::And(a, b) => match a.eval(row)? {
+				Some(false) => Ok(Some(false)),
+				a => Ok(logic::and(a, b.eval(row)?)),
+			},
+			Self::Or(a, b) => match a.eval(row)? {
+				Some(true) => Ok(Some(true)),
+				a => Ok(logic::or(a, b.eval(row)?)),
+			},
+			Self::Not(a) => a.eval(row).map(logic::not),
+		}
+	}
+
+	/// The `(column, value)` pairs of the columns this requires to equal a
+	/// literal, i.e. those of its top-level conjunction of the form
+	/// `Expr::col(column).equals(value)`. Every row matching this matches all of
+	/// them, so a source can skip data that contains none of these values.
+	pub fn equalities(&self) -> Vec<(String, Value)> {
+		let mut equalities = Vec::new();
+		self.equalities_(&mut equalities);
+		equalities
+	}
+	fn equalities_(&self, equalities: &mut Vec<(String, Value)>) {
+		match self {
+			Self::Eq(Expr::Column(column), Expr::Literal(value))
+			| Self::Eq(Expr::Literal(value), Expr::Column(column))
+				if !matches!(value, Value::Option(None)) =>
+			{
+				equalities.push((column.clone(), unwrap_option(value.clone())));
+			}
+			Self::And(a, b) => {
+				a.equalities_(equalities);
+				b.equalities_(equalities);
+			}
+			_ => (),
+		}
+	}
+}
+impl ops::Not for Predicate {
+	type Output = Self;
+
+	fn not(self) -> Self {
+		Self::Not(Box::new(self))
+	}
+}
+
+macro_rules! ops {
+	($($trait:ident $fn:ident $variant:ident),*) => {$(
+		impl<T: Into<Expr>> ops::$trait<T> for Expr {
+			type Output = Self;
+
+			fn $fn(self, other: T) -> Self {
+				Self::$variant(Box::new(self), Box::new(other.into()))
+			}
+		}
+	)*};
+}
+ops!(Add add Add, Sub sub Sub, Mul mul Mul, Div div Div);
+impl ops::Neg for Expr {
+	type Output = Self;
+
+	fn neg(self) -> Self {
+		Self::Neg(Box::new(self))
+	}
+}
+
+macro_rules! literal {
+	($($t:ty)*) => {$(
+		impl From<$t> for Expr {
+			fn from(value: $t) -> Self {
+				Self::lit(value)
+			}
+		}
+	)*};
+}
+literal!(u8 i8 u16 i16 u32 i32 u64 i64 f32 f64 Value);
+
+#[derive(Copy, Clone)]
+enum Op {
+	Add,
+	Sub,
+	Mul,
+	Div,
+}
+impl Display for Op {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Self::Add => "+",
+			Self::Sub => "-",
+			Self::Mul => "*",
+			Self::Div => "/",
+		})
+	}
+}
+
+fn binary(op: Op, a: Value, b: Value) -> Result<Value, EvalError> {
+	let optional = a.is_option() || b.is_option();
+	let (a, b) = match (a, b) {
+		(Value::Option(None), _) | (_, Value::Option(None)) => return Ok(Value::Option(None)),
+		(a, b) => (unwrap_option(a), unwrap_option(b)),
+	};
+	let type_error = |a: &Value, b: &Value| EvalError::Type {
+		op: op.to_string(),
+		left: a.type_name().to_owned(),
+		right: b.type_name().to_owned(),
+	};
+	if let Some(value) = temporal(op, &a, &b) {
+		let value = value?;
+		return Ok(if optional {
+			Value::from(Some(value))
+		} else {
+			value
+		});
+	}
+	let schema = match (primitive(&a), primitive(&b)) {
+		(Some(a), Some(b)) => widen(a, b),
+		_ => return Err(type_error(&a, &b)),
+	};
+	let value = match schema {
+		Schema::F32 | Schema::F64 => {
+			let (x, y) = (as_f64(&a).unwrap(), as_f64(&b).unwrap());
+			let res = match op {
+				Op::Add => x + y,
+				Op::Sub => x - y,
+				Op::Mul => x * y,
+				Op::Div => x / y,
+			};
+			#[allow(clippy::cast_possible_truncation)]
+			match schema {
+				Schema::F32 => Value::F32(res as f32),
+				_ => Value::F64(res),
+			}
+		}
+		_ => {
+			let (x, y) = (as_i128(&a).unwrap(), as_i128(&b).unwrap());
+			let res = match op {
+				Op::Add => x.checked_add(y),
+				Op::Sub => x.checked_sub(y),
+				Op::Mul => x.checked_mul(y),
+				Op::Div if y == 0 => return Err(EvalError::DivisionByZero),
+				Op::Div => x.checked_div(y),
+			};
+			int(&schema, res.ok_or(EvalError::Overflow)?).ok_or(EvalError::Overflow)?
+		}
+	};
+	Ok(if optional {
+		Value::from(Some(value))
+	} else {
+		value
+	})
+}
+
+/// Add or subtract durations from date-times and each other, and subtract
+/// date-times to give the duration between them, or `None` if `a` and `b` aren't
+/// such operands.
+fn temporal(op: Op, a: &Value, b: &Value) -> Option<Result<Value, EvalError>> {
+	let value = match (op, a, b) {
+		(Op::Add, Value::DateTime(a), Value::Duration(b))
+		| (Op::Add, Value::Duration(b), Value::DateTime(a)) => a.checked_add(b).map(Value::from),
+		(Op::Sub, Value::DateTime(a), Value::Duration(b)) => a.checked_sub(b).map(Value::from),
+		(Op::Sub, Value::DateTime(a), Value::DateTime(b)) => Some(a.duration_since(b).into()),
+		(Op::Add, Value::DateTimeWithoutTimezone(a), Value::Duration(b))
+		| (Op::Add, Value::Duration(b), Value::DateTimeWithoutTimezone(a)) => {
+			a.checked_add(b).map(Value::from)
+		}
+		(Op::Sub, Value::DateTimeWithoutTimezone(a), Value::Duration(b)) => {
+			a.checked_sub(b).map(Value::from)
+		}
+		(Op::Sub, Value::DateTimeWithoutTimezone(a), Value::DateTimeWithoutTimezone(b)) => {
+			Some(a.duration_since(b).into())
+		}
+		(Op::Add, Value::Duration(a), Value::Duration(b)) => a.checked_add(b).map(Value::from),
+		(Op::Sub, Value::Duration(a), Value::Duration(b)) => a.checked_sub(b).map(Value::from),
+		_ => return None,
+	};
+	Some(value.ok_or(EvalError::Overflow))
+}
+
+/// Compare `a` and `b`, or `None` if either is null or they're unordered.
+fn compare(op: &str, a: Value, b: Value) -> Result<Option<Ordering>, EvalError> {
+	let (a, b) = match (a, b) {
+		(Value::Option(None), _) | (_, Value::Option(None)) => return Ok(None),
+		(a, b) => (unwrap_option(a), unwrap_option(b)),
+	};
+	match (primitive(&a), primitive(&b)) {
+		(Some(x), Some(y)) => Ok(match widen(x, y) {
+			Schema::F32 | Schema::F64 => as_f64(&a).unwrap().partial_cmp(&as_f64(&b).unwrap()),
+			_ => Some(as_i128(&a).unwrap().cmp(&as_i128(&b).unwrap())),
+		}),
+		_ if a.type_name() == b.type_name() => Ok(a.partial_cmp(&b)),
+		_ => Err(EvalError::Type {
+			op: op.to_owned(),
+			left: a.type_name().to_owned(),
+			right: b.type_name().to_owned(),
+		}),
+	}
+}
+
+fn unwrap_option(value: Value) -> Value {
+	match value {
+		Value::Option(Some(value)) => value.into(),
+		value => value,
+	}
+}
+
+/// The schema of a numeric value.
+fn primitive(value: &Value) -> Option<Schema> {
+	Some(match value {
+		Value::U8(_) => Schema::U8,
+		Value::I8(_) => Schema::I8,
+		Value::U16(_) => Schema::U16,
+		Value::I16(_) => Schema::I16,
+		Value::U32(_) => Schema::U32,
+		Value::I32(_) => Schema::I32,
+		Value::U64(_) => Schema::U64,
+		Value::I64(_) => Schema::I64,
+		Value::F32(_) => Schema::F32,
+		Value::F64(_) => Schema::F64,
+		_ => return None,
+	})
+}
+
+fn as_i128(value: &Value) -> Option<i128> {
+	Some(match *value {
+		Value::U8(value) => value.into(),
+		Value::I8(value) => value.into(),
+		Value::U16(value) => value.into(),
+		Value::I16(value) => value.into(),
+		Value::U32(value) => value.into(),
+		Value::I32(value) => value.into(),
+		Value::U64(value) => value.into(),
+		Value::I64(value) => value.into(),
+		_ => return None,
+	})
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn as_f64(value: &Value) -> Option<f64> {
+	Some(match *value {
+		Value::F32(value) => value.into(),
+		Value::F64(value) => value,
+		ref value => as_i128(value)? as f64,
+	})
+}
+
+fn int(schema: &Schema, value: i128) -> Option<Value> {
+	Some(match schema {
+		Schema::U8 => Value::U8(u8::try_from(value).ok()?),
+		Schema::I8 => Value::I8(i8::try_from(value).ok()?),
+		Schema::U16 => Value::U16(u16::try_from(value).ok()?),
+		Schema::I16 => Value::I16(i16::try_from(value).ok()?),
+		Schema::U32 => Value::U32(u32::try_from(value).ok()?),
+		Schema::I32 => Value::I32(i32::try_from(value).ok()?),
+		Schema::U64 => Value::U64(u64::try_from(value).ok()?),
+		Schema::I64 => Value::I64(i64::try_from(value).ok()?),
+		_ => unreachable!(),
+	})
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum EvalError {
+	/// The row has no field of this name.
+	MissingColumn(String),
+	/// An operator was applied to values of types it doesn't support.
+	Type {
+		op: String,
+		left: String,
+		right: String,
+	},
+	/// Integer arithmetic overflowed the type of the result.
+	Overflow,
+	DivisionByZero,
+}
+impl Error for EvalError {}
+impl Display for EvalError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::MissingColumn(name) => write!(f, "no column named {}", name),
+			Self::Type { op, left, right } => {
+				write!(f, "can't evaluate {} {} {}", left, op, right)
+			}
+			Self::Overflow => f.write_str("arithmetic overflow"),
+			Self::DivisionByZero => f.write_str("division by zero"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{DateTimeWithoutTimezone, Duration, Timezone};
+
+	fn row(fields: Vec<(&str, Value)>) -> Group {
+		let names = fields
+			.iter()
+			.enumerate()
+			.map(|(i, (name, _))| (String::from(*name), i))
+			.collect();
+		let values = fields.into_iter().map(|(_, value)| value).collect();
+		Group::new(values, Some(std::sync::Arc::new(names)))
+	}
+
+	#[test]
+	fn arithmetic() {
+		let row = row(vec![
+			("a", Value::U8(200)),
+			("b", Value::I32(-7)),
+			("c", Value::F32(1.5)),
+			("d", Value::U64(u64::MAX)),
+		]);
+		let eval = |expr: Expr| expr.eval(&row);
+		assert_eq!(eval(Expr::col("a") + 100_u8), Err(EvalError::Overflow));
+		assert_eq!(eval(Expr::col("a") + 100_u16), Ok(Value::U16(300)));
+		// Mixed signedness widens to a signed type holding both
+		assert_eq!(eval(Expr::col("a") + Expr::col("b")), Ok(Value::I32(193)));
+		assert_eq!(eval(Expr::col("b") / 2), Ok(Value::I32(-3)));
+		assert_eq!(eval(-Expr::col("b")), Ok(Value::I32(7)));
+		assert_eq!(eval(Expr::col("c") * 2_f32), Ok(Value::F32(3.0)));
+		assert_eq!(eval(Expr::col("c") * Expr::col("b")), Ok(Value::F64(-10.5)));
+		assert_eq!(eval(Expr::col("d") - 1_u64), Ok(Value::U64(u64::MAX - 1)));
+		assert_eq!(eval(Expr::col("d") + 1_u64), Err(EvalError::Overflow));
+		assert_eq!(eval(Expr::col("b") / 0), Err(EvalError::DivisionByZero));
+	}
+
+	#[test]
+	fn temporal() {
+		let date_time = |day, hour| {
+			DateTimeWithoutTimezone::new(2020, 1, day, hour, 0, 0, 0)
+				.unwrap()
+				.with_timezone(Timezone::UTC)
+		};
+		let row = row(vec![
+			("start", Value::DateTime(date_time(31, 12))),
+			("end", Value::DateTime(date_time(2, 0))),
+			("interval", Value::Duration(Duration::from_months(1))),
+			("n", Value::I64(1)),
+		]);
+		let eval = |expr: Expr| expr.eval(&row);
+		let start = date_time(31, 12);
+		assert_eq!(
+			eval(Expr::col("start") + Expr::col("interval")),
+			Ok(Value::DateTime(start + Duration::from_months(1)))
+		);
+		assert_eq!(
+			eval(Expr::col("interval") + Expr::col("start")),
+			eval(Expr::col("start") + Expr::col("interval"))
+		);
+		assert_eq!(
+			eval(Expr::col("start") - Expr::col("end")),
+			Ok(Value::Duration(
+				Duration::new(0, 29, 12 * 3_600_000_000_000).unwrap()
+			))
+		);
+		assert_eq!(
+			eval(Expr::col("interval") - Expr::col("interval")),
+			Ok(Value::Duration(Duration::from_days(0)))
+		);
+		assert!(matches!(
+			eval(Expr::col("start") + Expr::col("n")),
+			Err(EvalError::Type { .. })
+		));
+		assert_eq!(
+			Expr::col("interval")
+				.gt(Expr::lit(Duration::from_days(30)))
+				.eval(&row),
+			Ok(None)
+		);
+		assert_eq!(
+			Expr::col("interval")
+				.gt(Expr::lit(Duration::from_days(27)))
+				.eval(&row),
+			Ok(Some(true))
+		);
+	}
+
+	#[test]
+	fn nulls() {
+		let row = row(vec![
+			("a", Value::Option(None)),
+			("b", Value::from(Some(Value::I64(4)))),
+			("c", Value::I64(2)),
+		]);
+		let eval = |expr: Expr| expr.eval(&row);
+		assert_eq!(
+			eval(Expr::col("a") + Expr::col("c")),
+			Ok(Value::Option(None))
+		);
+		assert_eq!(
+			eval(Expr::col("c") * Expr::col("a")),
+			Ok(Value::Option(None))
+		);
+		assert_eq!(
+			eval(Expr::col("b") * Expr::col("c")),
+			Ok(Value::from(Some(Value::I64(8))))
+		);
+	}
+
+	#[test]
+	fn errors() {
+		let row = row(vec![
+			("a", Value::String(String::from("x"))),
+			("b", Value::I64(1)),
+		]);
+		let eval = |expr: Expr| expr.eval(&row);
+		assert_eq!(
+			eval(Expr::col("a") + Expr::col("b")),
+			Err(EvalError::Type {
+				op: String::from("+"),
+				left: String::from("string"),
+				right: String::from("i64"),
+			})
+		);
+		assert_eq!(
+			eval(Expr::col("missing") * 2),
+			Err(EvalError::MissingColumn(String::from("missing")))
+		);
+		assert_eq!(
+			eval(Expr::col("a") + Expr::col("b"))
+				.unwrap_err()
+				.to_string(),
+			"can't evaluate string + i64"
+		);
+	}
+
+	#[test]
+	fn predicates() {
+		let row = row(vec![
+			("a", Value::U8(200)),
+			("b", Value::F64(1.5)),
+			("c", Value::String(String::from("x"))),
+			("d", Value::Option(None)),
+		]);
+		let eval = |predicate: Predicate| predicate.eval(&row);
+		assert_eq!(eval(Expr::col("a").equals(200_i64)), Ok(Some(true)));
+		assert_eq!(eval(Expr::col("a").gt(Expr::col("b"))), Ok(Some(true)));
+		assert_eq!(eval(Expr::col("b").le(1)), Ok(Some(false)));
+		assert_eq!(
+			eval(Expr::col("c").lt(Value::String(String::from("y")))),
+			Ok(Some(true))
+		);
+		// Comparisons with null are null, which only AND with false and OR with
+		// true decide
+		assert_eq!(eval(Expr::col("d").equals(1)), Ok(None));
+		assert_eq!(eval(!Expr::col("d").equals(1)), Ok(None));
+		assert_eq!(
+			eval(Expr::col("d").equals(1).and(Expr::col("b").lt(0))),
+			Ok(Some(false))
+		);
+		assert_eq!(
+			eval(Expr::col("d").equals(1).and(Expr::col("b").gt(0))),
+			Ok(None)
+		);
+		assert_eq!(
+			eval(Expr::col("d").equals(1).or(Expr::col("b").gt(0))),
+			Ok(Some(true))
+		);
+		assert_eq!(
+			eval(Expr::col("d").equals(1).or(Expr::col("b").lt(0))),
+			Ok(None)
+		);
+		assert_eq!(
+			eval(Expr::col("a").ge(200).and(Expr::col("b").lt(0))),
+			Ok(Some(false))
+		);
+		assert_eq!(
+			eval(Expr::col("a").ge(200).or(Expr::col("b").lt(0))),
+			Ok(Some(true))
+		);
+		assert_eq!(
+			eval(Expr::col("c").equals(1)),
+			Err(EvalError::Type {
+				op: String::from("="),
+				left: String::from("string"),
+				right: String::from("i32"),
+			})
+		);
+	}
+
+	#[test]
+	fn equalities() {
+		let predicate = Expr::col("a")
+			.equals(1)
+			.and(Expr::lit(Value::String(String::from("x"))).equals(Expr::col("b")))
+			.and(Expr::col("c").gt(2))
+			.and(Expr::col("d").equals(Expr::col("e")));
+		assert_eq!(
+			predicate.equalities(),
+			vec![
+				(String::from("a"), Value::I32(1)),
+				(String::from("b"), Value::String(String::from("x"))),
+			]
+		);
+		// A row can match without the equality holding, so it doesn't prune
+		let predicate = Expr::col("a").equals(1).or(Expr::col("b").equals(2));
+		assert_eq!(predicate.equalities(), vec![]);
+		assert_eq!((!Expr::col("a").equals(1)).equalities(), vec![]);
+	}
+}
@@ -0,0 +1,137 @@
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::{
+	cmp::Ordering, error::Error, fmt::{self, Display}, str::FromStr
+};
+
+use super::AmadeusOrd;
+
+/// The mean radius of the Earth in metres.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// A point on the surface of the Earth, as a latitude and longitude in degrees.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
+pub struct GeoPoint {
+	lat: f64,
+	lon: f64,
+}
+impl GeoPoint {
+	/// Create a point, or `None` if `lat` isn't within ±90° or `lon` within ±180°.
+	pub fn new(lat: f64, lon: f64) -> Option<Self> {
+		if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon) {
+			Some(Self { lat, lon })
+		} else {
+			None
+		}
+	}
+	pub fn lat(&self) -> f64 {
+		self.lat
+	}
+	pub fn lon(&self) -> f64 {
+		self.lon
+	}
+	/// The great-circle distance in metres to `other`, by the haversine formula
+	/// on a spherical Earth, which is accurate to within about 0.5%.
+	pub fn haversine_distance(&self, other: &Self) -> f64 {
+		let (lat_a, lat_b) = (self.lat.to_radians(), other.lat.to_radians());
+		let d_lat = lat_b - lat_a;
+		let d_lon = (other.lon - self.lon).to_radians();
+		let a =
+			(d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+		2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+	}
+	/// The distance to `other`, totally ordered so it can be used as the key for
+	/// sinks like `min_by_key` to find the nearest point to a reference.
+	pub fn distance(&self, other: &Self) -> Distance {
+		Distance(self.haversine_distance(other))
+	}
+}
+impl AmadeusOrd for GeoPoint {
+	fn amadeus_cmp(&self, other: &Self) -> Ordering {
+		self.lat
+			.amadeus_cmp(&other.lat)
+			.then_with(|| self.lon.amadeus_cmp(&other.lon))
+	}
+}
+impl Display for GeoPoint {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{},{}", self.lat, self.lon)
+	}
+}
+impl FromStr for GeoPoint {
+	type Err = ParseGeoPointError;
+
+	/// Parse a point formatted as `lat,lon`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, ',');
+		let (lat, lon) = (
+			parts.next().unwrap(),
+			parts.next().ok_or(ParseGeoPointError)?,
+		);
+		let lat = lat.trim().parse().map_err(|_| ParseGeoPointError)?;
+		let lon = lon.trim().parse().map_err(|_| ParseGeoPointError)?;
+		Self::new(lat, lon).ok_or(ParseGeoPointError)
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseGeoPointError;
+impl Display for ParseGeoPointError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "error parsing geo point")
+	}
+}
+impl Error for ParseGeoPointError {}
+
+/// A distance in metres between [`GeoPoint`]s, ordered as an [`f64`] with NaN
+/// greatest.
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct Distance(pub f64);
+impl PartialEq for Distance {
+	fn eq(&self, other: &Self) -> bool {
+		OrderedFloat(self.0) == OrderedFloat(other.0)
+	}
+}
+impl Eq for Distance {}
+impl PartialOrd for Distance {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Ord for Distance {
+	fn cmp(&self, other: &Self) -> Ordering {
+		OrderedFloat(self.0).cmp(&OrderedFloat(other.0))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn haversine_distance() {
+		let point = |lat, lon| GeoPoint::new(lat, lon).unwrap();
+		let (nashville, los_angeles) = (point(36.12, -86.67), point(33.94, -118.40));
+		let distance = nashville.haversine_distance(&los_angeles);
+		assert!((distance - 2_886_448.43).abs() < 1.0, "{}", distance);
+		assert!((distance - los_angeles.haversine_distance(&nashville)).abs() < 1e-6);
+
+		// A degree of longitude along the equator
+		let degree = point(0.0, 0.0).haversine_distance(&point(0.0, 1.0));
+		assert!((degree - 111_195.08).abs() < 0.01, "{}", degree);
+		// Antipodes are half the circumference apart
+		let antipodes = point(0.0, 0.0).haversine_distance(&point(0.0, 180.0));
+		assert!((antipodes - std::f64::consts::PI * EARTH_RADIUS).abs() < 1e-6);
+		assert!(nashville.haversine_distance(&nashville).abs() < 1e-6);
+	}
+
+	#[test]
+	fn parse() {
+		let point = GeoPoint::new(51.5007, -0.1246).unwrap();
+		assert_eq!(point.to_string().parse(), Ok(point));
+		assert_eq!(" 51.5007 , -0.1246".parse(), Ok(point));
+		assert_eq!("91,0".parse::<GeoPoint>(), Err(ParseGeoPointError));
+		assert_eq!("51.5007".parse::<GeoPoint>(), Err(ParseGeoPointError));
+		assert!(GeoPoint::new(0.0, f64::NAN).is_none());
+	}
+}
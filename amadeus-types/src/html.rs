@@ -0,0 +1,290 @@
+//! Extract the readable text from HTML, as used by
+//! [`Webpage::extract_text`](super::Webpage::extract_text).
+
+/// Elements whose contents are never shown.
+const HIDDEN: &[&str] = &["script", "style", "noscript", "template", "title"];
+/// Elements whose contents are markup, but which are boilerplate rather than
+/// main content.
+const BOILERPLATE: &[&str] = &["nav", "aside"];
+/// Elements that start a new line.
+const BLOCK: &[&str] = &[
+	"address",
+	"article",
+	"blockquote",
+	"br",
+	"dd",
+	"div",
+	"dl",
+	"dt",
+	"figcaption",
+	"figure",
+	"footer",
+	"form",
+	"h1",
+	"h2",
+	"h3",
+	"h4",
+	"h5",
+	"h6",
+	"header",
+	"hr",
+	"li",
+	"main",
+	"ol",
+	"p",
+	"pre",
+	"section",
+	"table",
+	"tr",
+	"ul",
+];
+
+pub(crate) fn extract_text(html: &str) -> String {
+	let mut text = String::with_capacity(html.len() / 2);
+	let mut boilerplate = 0_usize;
+	let mut rest = html;
+	while let Some(i) = rest.find('<') {
+		if boilerplate == 0 {
+			push_text(&mut text, &rest[..i]);
+		}
+		rest = &rest[i..];
+		if let Some(comment) = rest.strip_prefix("<!--") {
+			rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+			continue;
+		}
+		let (name, closing, self_closing, len) = match tag(rest) {
+			Some(tag) => tag,
+			None => {
+				// Not a tag, so a stray `<` of text
+				if boilerplate == 0 {
+					text.push('<');
+				}
+				rest = &rest[1..];
+				continue;
+			}
+		};
+		rest = &rest[len..];
+		if !closing && HIDDEN.contains(&&*name) {
+			rest = skip_to_close(rest, &name);
+		} else if BOILERPLATE.contains(&&*name) {
+			if closing {
+				boilerplate = boilerplate.saturating_sub(1);
+			} else if !self_closing {
+				boilerplate += 1;
+			}
+		} else if BLOCK.contains(&&*name) {
+			text.push('\n');
+		} else if name == "td" || name == "th" {
+			text.push(' ');
+		}
+	}
+	if boilerplate == 0 {
+		push_text(&mut text, rest);
+	}
+	normalize_whitespace(&text)
+}
+
+/// Parse the tag at the start of `html`, returning its lowercased name, whether
+/// it's a closing or self-closing tag, and its length.
+fn tag(html: &str) -> Option<(String, bool, bool, usize)> {
+	let bytes = html.as_bytes();
+	let mut i = 1;
+	let closing = bytes.get(i) == Some(&b'/');
+	if closing {
+		i += 1;
+	}
+	// Doctypes and processing instructions are skipped like tags
+	if !bytes.get(i)?.is_ascii_alphabetic() && !matches!(bytes[i], b'!' | b'?') {
+		return None;
+	}
+	let start = i;
+	while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !matches!(bytes[i], b'/' | b'>') {
+		i += 1;
+	}
+	let name = html[start..i].to_ascii_lowercase();
+	// Find the end of the tag, skipping over quoted attribute values
+	let mut quote = None;
+	while i < bytes.len() {
+		match (quote, bytes[i]) {
+			(None, b'"') | (None, b'\'') => quote = Some(bytes[i]),
+			(Some(q), c) if q == c => quote = None,
+			(None, b'>') => {
+				let self_closing = bytes[i - 1] == b'/';
+				return Some((name, closing, self_closing, i + 1));
+			}
+			_ => (),
+		}
+		i += 1;
+	}
+	// An unterminated tag runs to the end
+	Some((name, closing, false, bytes.len()))
+}
+
+/// Skip past the closing tag of the raw-text element `name`.
+fn skip_to_close<'a>(html: &'a str, name: &str) -> &'a str {
+	let mut from = 0;
+	while let Some(i) = html[from..].find("</") {
+		let start = from + i + 2;
+		let end = start + name.len();
+		if html
+			.get(start..end)
+			.map_or(false, |tag| tag.eq_ignore_ascii_case(name))
+		{
+			return html[end..].find('>').map_or("", |i| &html[end + i + 1..]);
+		}
+		from = start;
+	}
+	""
+}
+
+fn push_text(text: &mut String, html: &str) {
+	let mut rest = html;
+	while let Some(i) = rest.find('&') {
+		text.push_str(&rest[..i]);
+		rest = &rest[i..];
+		if let Some((c, len)) = entity(rest) {
+			text.push(c);
+			rest = &rest[len..];
+		} else {
+			text.push('&');
+			rest = &rest[1..];
+		}
+	}
+	text.push_str(rest);
+}
+
+/// Decode the character reference at the start of `html`, returning it and its
+/// length.
+fn entity(html: &str) -> Option<(char, usize)> {
+	// Character references are ASCII, so look no further than the longest
+	let (end, _) = html.char_indices().take(12).find(|&(_, c)| c == ';')?;
+	let name = &html[1..end];
+	let c = if let Some(code) = name.strip_prefix('#') {
+		let code = match code.strip_prefix('x').or_else(|| code.strip_prefix('X')) {
+			Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+			None => code.parse().ok()?,
+		};
+		std::char::from_u32(code).unwrap_or('\u{FFFD}')
+	} else {
+		match name {
+			"amp" => '&',
+			"lt" => '<',
+			"gt" => '>',
+			"quot" => '"',
+			"apos" => '\'',
+			"nbsp" => ' ',
+			"copy" => '©',
+			"reg" => '®',
+			"trade" => '™',
+			"ndash" => '–',
+			"mdash" => '—',
+			"hellip" => '…',
+			"lsquo" => '‘',
+			"rsquo" => '’',
+			"ldquo" => '“',
+			"rdquo" => '”',
+			"laquo" => '«',
+			"raquo" => '»',
+			"middot" => '·',
+			"bull" => '•',
+			_ => return None,
+		}
+	};
+	Some((c, end + 1))
+}
+
+/// Collapse runs of whitespace into a single space, trim each line, and drop
+/// empty lines.
+fn normalize_whitespace(text: &str) -> String {
+	let mut ret = String::with_capacity(text.len());
+	for line in text.lines() {
+		let mut words = line.split_whitespace().peekable();
+		if words.peek().is_none() {
+			continue;
+		}
+		if !ret.is_empty() {
+			ret.push('\n');
+		}
+		for (i, word) in words.enumerate() {
+			if i != 0 {
+				ret.push(' ');
+			}
+			ret.push_str(word);
+		}
+	}
+	ret
+}
+
+#[cfg(test)]
+mod tests {
+	use std::borrow::Cow;
+
+	use super::{super::Webpage, extract_text};
+
+	const FIXTURE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+	<title>Page title</title>
+	<meta charset="utf-8">
+	<style>body { color: red; }</style>
+	<script type="text/javascript">
+		var x = "<p>not text</p>";
+		if (a < b && c > d) {}
+	</script>
+</head>
+<body>
+	<nav><ul><li><a href="/">Home</a></li><li><a href="/about">About</a></li></ul></nav>
+	<h1>Main   heading</h1>
+	<!-- a comment <p>hidden</p> -->
+	<p class="intro" data-x='a > b'>Fish &amp; chips &mdash; &#8220;tasty&#x201D; &unknown; 3 < 4</p>
+	<div>First<br>Second</div>
+	<table><tr><td>a</td><td>b</td></tr></table>
+	<aside>Related links</aside>
+	<SCRIPT>alert("upper case")</SCRIPT>
+	<p>Unclosed paragraph
+</body>
+</html>"#;
+
+	#[test]
+	fn extract() {
+		let text = extract_text(FIXTURE);
+		assert_eq!(
+			text,
+			"Main heading\nFish & chips — “tasty” &unknown; 3 < 4\nFirst\nSecond\na b\nUnclosed paragraph"
+		);
+		for hidden in &[
+			"color",
+			"not text",
+			"upper case",
+			"Page title",
+			"Home",
+			"Related",
+			"hidden",
+		] {
+			assert!(!text.contains(hidden), "{:?} wasn't removed", hidden);
+		}
+	}
+
+	#[test]
+	fn webpage() {
+		let contents = format!(
+			"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n{}",
+			FIXTURE
+		);
+		let webpage = Webpage {
+			ip: "127.0.0.1".parse().unwrap(),
+			url: "http://example.com/".parse().unwrap(),
+			contents: Cow::Borrowed(contents.as_bytes()),
+		};
+		assert_eq!(webpage.extract_text(), extract_text(FIXTURE));
+	}
+
+	#[test]
+	fn malformed() {
+		assert_eq!(extract_text("a <b"), "a");
+		assert_eq!(extract_text("<script>never closed"), "");
+		assert_eq!(extract_text("a &amp b &#xZZ; c"), "a &amp b &#xZZ; c");
+		assert_eq!(extract_text("x</p>y<!-- unterminated"), "x\ny");
+		assert_eq!(extract_text("&中文字符; &amp中文"), "&中文字符; &amp中文");
+	}
+}
@@ -21,6 +21,10 @@ impl AmadeusOrd for Url {
 	}
 }
 
+#[cfg(feature = "language")]
+#[doc(inline)]
+pub use whatlang::Lang;
+
 #[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
 pub struct Webpage<'a> {
 	pub ip: IpAddr,
@@ -35,6 +39,57 @@ impl<'a> Webpage<'a> {
 			contents: Cow::Owned(self.contents.clone().into_owned()),
 		}
 	}
+	/// The page's URL in a canonical form, so that URLs of the same page compare
+	/// equal: the scheme and host lowercased, internationalized hosts
+	/// punycode-encoded, default ports removed, the query parameters sorted, and
+	/// the fragment and any empty query removed.
+	pub fn normalized_url(&self) -> Url {
+		// Parsing has already lowercased and punycode-encoded the host and removed
+		// any default port
+		let mut url = self.url.clone();
+		url.set_fragment(None);
+		let mut pairs = url.query_pairs().into_owned().collect::<Vec<_>>();
+		if pairs.is_empty() {
+			url.set_query(None);
+		} else {
+			pairs.sort();
+			let _ = url.query_pairs_mut().clear().extend_pairs(pairs);
+		}
+		url
+	}
+	/// The registrable domain of the page's host, that is one label more than its
+	/// public suffix (eTLD+1), such as `example.co.uk` for `www.example.co.uk`.
+	/// Internationalized domains are given in punycode. `None` if the host is an
+	/// IP address, or is itself a public suffix.
+	pub fn domain(&self) -> Option<&str> {
+		match self.url.host()? {
+			url::Host::Domain(host) => psl::domain_str(host.trim_end_matches('.')),
+			url::Host::Ipv4(_) | url::Host::Ipv6(_) => None,
+		}
+	}
+	/// The readable text of the page, with tags, comments, scripts and styles
+	/// removed, entities decoded, and whitespace collapsed, with a line per
+	/// block such as a paragraph or heading. As a simple heuristic for finding
+	/// the main content, navigation and sidebars are removed too. Any leading
+	/// HTTP headers are skipped, and malformed markup is handled leniently.
+	pub fn extract_text(&self) -> String {
+		let mut contents = &*self.contents;
+		if contents.starts_with(b"HTTP/") {
+			if let Some(i) = contents.windows(4).position(|x| x == b"\r\n\r\n") {
+				contents = &contents[i + 4..];
+			}
+		}
+		super::html::extract_text(&String::from_utf8_lossy(contents))
+	}
+	/// The language of the page's [text](Self::extract_text), detected by
+	/// comparing its trigrams against those of each language. `None` if there's
+	/// too little text to tell reliably.
+	#[cfg(feature = "language")]
+	pub fn detect_language(&self) -> Option<Lang> {
+		whatlang::detect(&self.extract_text())
+			.filter(whatlang::Info::is_reliable)
+			.map(|info| info.lang())
+	}
 }
 impl<'a> AmadeusOrd for Webpage<'a> {
 	fn amadeus_cmp(&self, other: &Self) -> Ordering {
@@ -62,3 +117,108 @@ impl Display for ParseWebpageError {
 	}
 }
 impl Error for ParseWebpageError {}
+
+#[cfg(test)]
+mod tests {
+	use std::borrow::Cow;
+
+	#[cfg(feature = "language")]
+	use super::Lang;
+	use super::Webpage;
+
+	fn webpage(html: &str) -> Webpage<'_> {
+		Webpage {
+			ip: "127.0.0.1".parse().unwrap(),
+			url: "http://example.com/".parse().unwrap(),
+			contents: Cow::Borrowed(html.as_bytes()),
+		}
+	}
+
+	fn at(url: &str) -> Webpage<'static> {
+		Webpage {
+			url: url.parse().unwrap(),
+			..webpage("")
+		}
+	}
+
+	#[test]
+	fn normalized_url() {
+		let canonical = "http://example.com/a/b?x=1&y=2";
+		for url in &[
+			"http://example.com/a/b?x=1&y=2",
+			"HTTP://EXAMPLE.com:80/a/b?y=2&x=1",
+			"http://Example.COM/a/./c/../b?x=1&y=2#section",
+		] {
+			assert_eq!(at(url).normalized_url().as_str(), canonical, "{}", url);
+		}
+		assert_eq!(
+			at("https://example.com:443/?#top")
+				.normalized_url()
+				.as_str(),
+			"https://example.com/"
+		);
+		// A non-default port is kept
+		assert_eq!(
+			at("https://example.com:8443/").normalized_url().as_str(),
+			"https://example.com:8443/"
+		);
+		// Repeated parameters keep both values
+		assert_eq!(
+			at("http://example.com/?b=2&a=3&b=1")
+				.normalized_url()
+				.as_str(),
+			"http://example.com/?a=3&b=1&b=2"
+		);
+		assert_eq!(
+			at("http://BÜCHER.example/").normalized_url().as_str(),
+			"http://xn--bcher-kva.example/"
+		);
+	}
+
+	#[test]
+	fn domain() {
+		assert_eq!(at("http://example.com/").domain(), Some("example.com"));
+		assert_eq!(
+			at("http://a.b.www.example.com/").domain(),
+			Some("example.com")
+		);
+		assert_eq!(
+			at("http://www.example.co.uk/").domain(),
+			Some("example.co.uk")
+		);
+		assert_eq!(at("http://www.example.com./").domain(), Some("example.com"));
+		assert_eq!(
+			at("http://foo.bar.github.io/").domain(),
+			Some("bar.github.io")
+		);
+		assert_eq!(
+			at("http://www.bücher.de/").domain(),
+			Some("xn--bcher-kva.de")
+		);
+		assert_eq!(
+			at("http://www.xn--85x722f.xn--55qx5d.cn/").domain(),
+			Some("xn--85x722f.xn--55qx5d.cn")
+		);
+		assert_eq!(at("http://co.uk/").domain(), None);
+		assert_eq!(at("http://127.0.0.1/").domain(), None);
+		assert_eq!(at("http://[::1]/").domain(), None);
+	}
+
+	#[test]
+	#[cfg(feature = "language")]
+	fn detect_language() {
+		let english = "<html><body><nav>Startseite</nav><h1>The history of the city</h1>\
+			<p>The old town was founded on the banks of the river more than eight hundred \
+			years ago, and many of its narrow streets and timber houses have survived to \
+			this day.</p></body></html>";
+		let german = "<html><body><nav>Home</nav><h1>Die Geschichte der Stadt</h1>\
+			<p>Die Altstadt wurde vor mehr als achthundert Jahren am Ufer des Flusses \
+			gegründet, und viele ihrer engen Gassen und Fachwerkhäuser sind bis heute \
+			erhalten geblieben.</p></body></html>";
+		assert_eq!(webpage(english).detect_language(), Some(Lang::Eng));
+		assert_eq!(webpage(german).detect_language(), Some(Lang::Deu));
+
+		assert_eq!(webpage("").detect_language(), None);
+		assert_eq!(webpage("<p>ok</p>").detect_language(), None);
+	}
+}
@@ -0,0 +1,272 @@
+//! Infer a [`Schema`] from a sample of dynamically-typed [`Value`]s.
+
+use fxhash::FxBuildHasher;
+use hashlink::LinkedHashMap;
+use std::{cmp, mem, sync::Arc};
+
+use super::{Schema, Value};
+
+impl Schema {
+	/// Infer the schema of `values`, or `None` if there are none.
+	///
+	/// Values are unified, so a field that is sometimes null is optional,
+	/// integers of different widths widen to the wider, integers and floats mix
+	/// as `F64`, and otherwise incompatible primitives fall back to `String`.
+	/// Groups are unified field by field, matching them by name if named,
+	/// with any missing from some values being optional, and unions variant by
+	/// variant. Other incompatible structures fall back to `Json`. A field that
+	/// is only ever null, a list that is only ever empty, or a union variant
+	/// that is never seen, is inferred as a `String`.
+	pub fn infer<I>(values: I) -> Option<Self>
+	where
+		I: IntoIterator<Item = Value>,
+	{
+		values
+			.into_iter()
+			.map(Inferred::of)
+			.fold(None, |acc, inferred| {
+				Some(match acc {
+					Some(acc) => Inferred::unify(acc, inferred),
+					None => inferred,
+				})
+			})
+			.map(Inferred::into_schema)
+	}
+}
+
+/// A [`Schema`] with holes where only nulls or empty lists have been seen.
+enum Inferred {
+	Unknown,
+	Primitive(Schema),
+	List(Box<Inferred>),
+	Map(Box<(Inferred, Inferred)>),
+	Group(
+		Vec<Inferred>,
+		Option<Arc<LinkedHashMap<String, usize, FxBuildHasher>>>,
+	),
+	Union(Vec<Inferred>),
+	Option(Box<Inferred>),
+}
+
+impl Inferred {
+	fn of(value: Value) -> Self {
+		let primitive = match value {
+			Value::Bool(_) => Schema::Bool,
+			Value::U8(_) => Schema::U8,
+			Value::I8(_) => Schema::I8,
+			Value::U16(_) => Schema::U16,
+			Value::I16(_) => Schema::I16,
+			Value::U32(_) => Schema::U32,
+			Value::I32(_) => Schema::I32,
+			Value::U64(_) => Schema::U64,
+			Value::I64(_) => Schema::I64,
+			Value::F32(_) => Schema::F32,
+			Value::F64(_) => Schema::F64,
+			Value::Date(_) => Schema::Date,
+			Value::DateWithoutTimezone(_) => Schema::DateWithoutTimezone,
+			Value::Time(_) => Schema::Time,
+			Value::TimeWithoutTimezone(_) => Schema::TimeWithoutTimezone,
+			Value::DateTime(_) => Schema::DateTime,
+			Value::DateTimeWithoutTimezone(_) => Schema::DateTimeWithoutTimezone,
+			Value::Timezone(_) => Schema::Timezone,
+			Value::Duration(_) => Schema::Duration,
+			Value::Decimal(_) => Schema::Decimal,
+			Value::Bson(_) => Schema::Bson,
+			Value::Json(_) => Schema::Json,
+			Value::Enum(_) => Schema::Enum,
+			Value::String(_)
+			| Value::Url(_)
+			| Value::Webpage(_)
+			| Value::IpAddr(_)
+			| Value::GeoPoint(_)
+			| Value::Uuid(_) => Schema::String,
+			Value::List(list) => {
+				return Self::List(Box::new(
+					list.into_iter()
+						.map(Self::of)
+						.fold(Self::Unknown, Self::unify),
+				))
+			}
+			Value::Map(map) => {
+				return Self::Map(Box::new(map.into_iter().fold(
+					(Self::Unknown, Self::Unknown),
+					|(keys, values), (key, value)| {
+						(keys.unify(Self::of(key)), values.unify(Self::of(value)))
+					},
+				)))
+			}
+			Value::Group(group) => {
+				let names = group.field_names().cloned();
+				return Self::Group(
+					group.into_fields().into_iter().map(Self::of).collect(),
+					names,
+				);
+			}
+			Value::Union(union) => {
+				let mut variants = (0..union.discriminant())
+					.map(|_| Self::Unknown)
+					.collect::<Vec<_>>();
+				variants.push(Self::of(union.into_value()));
+				return Self::Union(variants);
+			}
+			Value::Option(None) => return Self::Option(Box::new(Self::Unknown)),
+			Value::Option(Some(value)) => return Self::Option(Box::new(Self::of(value.into()))),
+		};
+		Self::Primitive(primitive)
+	}
+
+	fn unify(self, other: Self) -> Self {
+		match (self, other) {
+			(Self::Unknown, other) | (other, Self::Unknown) => other,
+			(Self::Option(a), Self::Option(b)) => Self::Option(Box::new(a.unify(*b))),
+			(Self::Option(a), b) | (b, Self::Option(a)) => Self::Option(Box::new(a.unify(b))),
+			(Self::Primitive(a), Self::Primitive(b)) => Self::Primitive(widen(a, b)),
+			(Self::List(a), Self::List(b)) => Self::List(Box::new(a.unify(*b))),
+			(Self::Map(a), Self::Map(b)) => {
+				let ((a_keys, a_values), (b_keys, b_values)) = (*a, *b);
+				Self::Map(Box::new((a_keys.unify(b_keys), a_values.unify(b_values))))
+			}
+			(Self::Group(a, a_names), Self::Group(b, b_names))
+				if a.len() == b.len() && a_names == b_names =>
+			{
+				Self::Group(
+					a.into_iter().zip(b).map(|(a, b)| a.unify(b)).collect(),
+					a_names,
+				)
+			}
+			(Self::Group(a, Some(a_names)), Self::Group(b, Some(b_names))) => {
+				Self::unify_named(a, &a_names, b, &b_names)
+			}
+			(Self::Union(a), Self::Union(b)) => {
+				let (mut longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+				for (i, variant) in shorter.into_iter().enumerate() {
+					let longer_variant = mem::replace(&mut longer[i], Self::Unknown);
+					longer[i] = longer_variant.unify(variant);
+				}
+				Self::Union(longer)
+			}
+			_ => Self::Primitive(Schema::Json),
+		}
+	}
+
+	/// Unify groups with different field names by name, with the fields missing
+	/// from either being optional.
+	fn unify_named(
+		a: Vec<Self>, a_names: &LinkedHashMap<String, usize, FxBuildHasher>, b: Vec<Self>,
+		b_names: &LinkedHashMap<String, usize, FxBuildHasher>,
+	) -> Self {
+		let missing = || Self::Option(Box::new(Self::Unknown));
+		let mut a = a.into_iter().map(Some).collect::<Vec<_>>();
+		let mut b = b.into_iter().map(Some).collect::<Vec<_>>();
+		let mut names = LinkedHashMap::with_hasher(FxBuildHasher::default());
+		let mut fields = Vec::new();
+		for (name, &i) in a_names {
+			let field = a[i].take().unwrap();
+			let other = b_names
+				.get(name)
+				.map_or_else(missing, |&j| b[j].take().unwrap());
+			let _ = names.insert(name.clone(), fields.len());
+			fields.push(field.unify(other));
+		}
+		for (name, &j) in b_names {
+			if let Some(field) = b[j].take() {
+				let _ = names.insert(name.clone(), fields.len());
+				fields.push(field.unify(missing()));
+			}
+		}
+		Self::Group(fields, Some(Arc::new(names)))
+	}
+
+	fn into_schema(self) -> Schema {
+		match self {
+			Self::Unknown => Schema::String,
+			Self::Primitive(schema) => schema,
+			Self::List(schema) => Schema::List(Box::new(schema.into_schema())),
+			Self::Map(schema) => {
+				let (keys, values) = *schema;
+				Schema::Map(Box::new((keys.into_schema(), values.into_schema())))
+			}
+			Self::Group(schemas, names) => {
+				Schema::Group(schemas.into_iter().map(Self::into_schema).collect(), names)
+			}
+			Self::Union(variants) => {
+				Schema::Union(variants.into_iter().map(Self::into_schema).collect())
+			}
+			Self::Option(schema) => Schema::Option(Box::new(schema.into_schema())),
+		}
+	}
+}
+
+/// The narrowest primitive both `a` and `b` can be read as.
+pub(crate) fn widen(a: Schema, b: Schema) -> Schema {
+	fn int(schema: &Schema) -> Option<(bool, u8)> {
+		Some(match schema {
+			Schema::U8 => (false, 8),
+			Schema::U16 => (false, 16),
+			Schema::U32 => (false, 32),
+			Schema::U64 => (false, 64),
+			Schema::I8 => (true, 8),
+			Schema::I16 => (true, 16),
+			Schema::I32 => (true, 32),
+			Schema::I64 => (true, 64),
+			_ => return None,
+		})
+	}
+	let float = |schema: &Schema| matches!(schema, Schema::F32 | Schema::F64);
+	if a == b {
+		return a;
+	}
+	match (int(&a), int(&b)) {
+		(Some((a_signed, a_bits)), Some((b_signed, b_bits))) if a_signed == b_signed => {
+			if a_bits >= b_bits {
+				a
+			} else {
+				b
+			}
+		}
+		// Mixed signedness, so widen to a signed type that holds both, or to
+		// `F64` for `U64`, as no signed integer holds every `U64`
+		(Some((a_signed, a_bits)), Some((_, b_bits))) => {
+			let (signed, unsigned) = if a_signed {
+				(a_bits, b_bits)
+			} else {
+				(b_bits, a_bits)
+			};
+			match cmp::max(signed, unsigned * 2) {
+				16 => Schema::I16,
+				32 => Schema::I32,
+				64 => Schema::I64,
+				_ => Schema::F64,
+			}
+		}
+		(a_int, b_int) if (a_int.is_some() || float(&a)) && (b_int.is_some() || float(&b)) => {
+			Schema::F64
+		}
+		_ => Schema::String,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		super::{CoercePolicy, Value}, *
+	};
+
+	#[test]
+	#[allow(clippy::cast_precision_loss)]
+	fn widen_mixed_signedness() {
+		assert_eq!(widen(Schema::U8, Schema::I8), Schema::I16);
+		assert_eq!(widen(Schema::I64, Schema::U32), Schema::I64);
+		assert_eq!(widen(Schema::U64, Schema::I8), Schema::F64);
+		for (a, b) in &[(Schema::U64, Schema::I64), (Schema::U32, Schema::I16)] {
+			assert_eq!(widen(a.clone(), b.clone()), a.clone().merge(b.clone()).unwrap());
+		}
+
+		let schema = Schema::infer(vec![Value::I8(-1), Value::U64(u64::MAX)]).unwrap();
+		assert_eq!(schema, Schema::F64);
+		assert_eq!(
+			schema.coerce(Value::U64(u64::MAX), CoercePolicy::Error),
+			Ok(Value::F64(u64::MAX as f64))
+		);
+	}
+}
@@ -78,13 +78,23 @@ macro_rules! tuple {
 }
 
 mod array;
+mod cast;
+mod coerce;
 mod data;
 mod decimal;
+mod expr;
+mod geo;
 mod group;
+mod html;
 mod http;
+mod infer;
 mod list;
+pub mod logic;
+mod merge;
 mod ord;
+mod relational;
 mod time;
+mod union;
 mod util;
 mod value;
 mod value_required;
@@ -94,11 +104,16 @@ use std::{
 };
 
 pub use self::{
-	array::{Bson, Enum, Json}, data::Data, decimal::Decimal, group::Group, http::{IpAddr, ParseAddrError, ParseUrlError, ParseWebpageError, Url, Webpage}, list::{List, ListVec}, ord::AmadeusOrd, time::{
-		Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, ParseDateError, Time, TimeWithoutTimezone, Timezone
-	}, value::{Schema, SchemaIncomplete, Value}, value_required::ValueRequired
+	array::{Bson, Enum, Json, ParseUuidError, Uuid}, cast::{CastError, CastErrorKind}, coerce::{coerce_schema, CoerceError, CoercePolicy, CoerceSchema}, data::Data, decimal::Decimal, expr::{EvalError, Expr, Predicate}, geo::{Distance, GeoPoint, ParseGeoPointError}, group::Group, http::{IpAddr, ParseAddrError, ParseUrlError, ParseWebpageError, Url, Webpage}, list::{List, ListVec}, merge::MergeError, ord::AmadeusOrd, relational::{
+		DistributedStreamRows, DropColumn, Explode, ExplodePolicy, FilterBy, ParallelStreamRows, Select, WithColumn
+	}, time::{
+		Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Duration, ParseDateError, Time, TimeWithoutTimezone, Timezone
+	}, union::Union, value::{Schema, SchemaIncomplete, Value}, value_required::ValueRequired
 };
 
+#[cfg(feature = "language")]
+pub use self::http::Lang;
+
 pub mod __internal {
 	pub use serde::{
 		de::{Deserializer, Error, SeqAccess, Visitor}, ser::{SerializeTuple, Serializer}, Deserialize, Serialize
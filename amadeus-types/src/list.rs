@@ -2,7 +2,7 @@
 
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
 use std::{
-	cmp::Ordering, fmt::{self, Debug}, hash::{Hash, Hasher}, iter::FromIterator, mem::ManuallyDrop, ops::{Deref, DerefMut}, panic::{RefUnwindSafe, UnwindSafe}
+	borrow::Cow, cmp::Ordering, fmt::{self, Debug}, hash::{Hash, Hasher}, iter::FromIterator, mem::ManuallyDrop, ops::{Deref, DerefMut}, panic::{RefUnwindSafe, UnwindSafe}
 };
 
 use super::{util::IteratorExt, AmadeusOrd, Data};
@@ -41,14 +41,22 @@ impl<T: Data> List<T> {
 	pub fn is_empty(&self) -> bool {
 		self.len() == 0
 	}
+	/// The item at `index`, or `None` if it's out of bounds. Items of lists
+	/// stored by column, as of derived structs, are reassembled into an owned
+	/// item.
+	#[inline(always)]
+	pub fn get(&self, index: usize) -> Option<Cow<'_, T>> {
+		self.vec.get_a(index)
+	}
+	/// An iterator over the items, as given by [`get`](Self::get).
+	#[inline(always)]
+	pub fn iter(&self) -> impl Iterator<Item = Cow<'_, T>> + '_ {
+		(0..self.len()).filter_map(move |index| self.get(index))
+	}
 	#[inline(always)]
 	pub fn into_boxed_slice(self) -> Box<[T]> {
 		self.vec.into_vec().into_boxed_slice()
 	}
-	// #[inline(always)]
-	// pub fn iter(&self) -> <&Self as IntoIterator>::IntoIter {
-	// 	self.into_iter()
-	// }
 	#[inline(always)]
 	pub fn map<F, U: Data>(self, f: F) -> List<U>
 	where
@@ -269,6 +277,7 @@ where
 	fn push(&mut self, t: T);
 	fn pop(&mut self) -> Option<T>;
 	fn len(&self) -> usize;
+	fn get_a(&self, index: usize) -> Option<Cow<'_, T>>;
 	fn from_vec(vec: Vec<T>) -> Self;
 	fn into_vec(self) -> Vec<T>;
 	fn into_iter_a(self) -> Self::IntoIter;
@@ -313,6 +322,10 @@ impl<T: Data> ListVec<T> for Vec<T> {
 		self.len()
 	}
 	#[inline(always)]
+	fn get_a(&self, index: usize) -> Option<Cow<'_, T>> {
+		self.get(index).map(Cow::Borrowed)
+	}
+	#[inline(always)]
 	fn from_vec(vec: Vec<T>) -> Self {
 		vec
 	}
@@ -435,4 +448,61 @@ mod test {
 		// list2.push(list.clone());
 		// println!("{:#?}", list);
 	}
+
+	#[test]
+	fn get() {
+		let list: List<Value> =
+			vec![Value::U8(0), Value::String("a".into()), Value::Bool(true)].into();
+		let value = Value::List(list);
+		assert_eq!(value.get(1).as_deref(), Some(&Value::String("a".into())));
+		assert_eq!(value.get(3), None);
+		assert_eq!(value.as_list().unwrap().len(), 3);
+		let items = value
+			.as_list()
+			.unwrap()
+			.iter()
+			.map(Cow::into_owned)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			items,
+			[Value::U8(0), Value::String("a".into()), Value::Bool(true)]
+		);
+		assert_eq!(Value::U8(0).get(0), None);
+
+		// Typed lists reassemble their items
+		let mut list: List<Value> = List::new_with(crate::value::ValueType::U8);
+		list.push(Value::U8(7));
+		list.push(Value::U8(8));
+		assert_eq!(list.get(1).as_deref(), Some(&Value::U8(8)));
+		assert_eq!(list.get(2), None);
+		assert_eq!(
+			list.iter().map(Cow::into_owned).collect::<Vec<_>>(),
+			[Value::U8(7), Value::U8(8)]
+		);
+
+		let map = [("a", 1_u32), ("b", 2)]
+			.iter()
+			.map(|&(key, value)| (Value::String(key.into()), Value::U32(value)))
+			.collect();
+		let value = Value::Map(map);
+		assert_eq!(
+			value.get_key(&Value::String("b".into())),
+			Some(&Value::U32(2))
+		);
+		assert_eq!(value.get_key(&Value::String("c".into())), None);
+		assert_eq!(value.as_map().unwrap().len(), 2);
+		let mut entries = value.as_map().unwrap().iter().collect::<Vec<_>>();
+		entries.sort_by(|a, b| a.0.amadeus_cmp(b.0));
+		assert_eq!(
+			entries,
+			[
+				(&Value::String("a".into()), &Value::U32(1)),
+				(&Value::String("b".into()), &Value::U32(2))
+			]
+		);
+		assert_eq!(
+			Value::String("a".into()).get_key(&Value::String("a".into())),
+			None
+		);
+	}
 }
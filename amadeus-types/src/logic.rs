@@ -0,0 +1,56 @@
+//! SQL's three-valued logic over `Option<bool>`, where `None` is null: an
+//! unknown truth value, as results from comparing with a null.
+//!
+//! Null propagates except where the known operand decides the result, so
+//! `and(None, Some(false))` is `Some(false)` and `or(None, Some(true))` is
+//! `Some(true)`.
+
+/// SQL's `AND`.
+pub fn and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+	match (a, b) {
+		(Some(false), _) | (_, Some(false)) => Some(false),
+		(Some(true), Some(true)) => Some(true),
+		_ => None,
+	}
+}
+
+/// SQL's `OR`.
+pub fn or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+	match (a, b) {
+		(Some(true), _) | (_, Some(true)) => Some(true),
+		(Some(false), Some(false)) => Some(false),
+		_ => None,
+	}
+}
+
+/// SQL's `NOT`.
+pub fn not(a: Option<bool>) -> Option<bool> {
+	a.map(|a| !a)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const VALUES: [Option<bool>; 3] = [Some(true), Some(false), None];
+
+	#[test]
+	fn truth_tables() {
+		let (t, f, n) = (Some(true), Some(false), None);
+		// Rows and columns in the order of VALUES
+		let and_table = [[t, f, n], [f, f, f], [n, f, n]];
+		let or_table = [[t, t, t], [t, f, n], [t, n, n]];
+		for (i, &a) in VALUES.iter().enumerate() {
+			for (j, &b) in VALUES.iter().enumerate() {
+				assert_eq!(and(a, b), and_table[i][j], "{:?} AND {:?}", a, b);
+				assert_eq!(or(a, b), or_table[i][j], "{:?} OR {:?}", a, b);
+			}
+		}
+		assert_eq!(and(n, f), f);
+		assert_eq!(or(n, t), t);
+		assert_eq!(
+			VALUES.iter().map(|&a| not(a)).collect::<Vec<_>>(),
+			[f, t, n]
+		);
+	}
+}
@@ -0,0 +1,234 @@
+//! Merge the [`Schema`]s of sources into one that can hold the rows of each.
+
+use fxhash::FxBuildHasher;
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+	error::Error, fmt::{self, Display}, sync::Arc
+};
+
+use super::{infer::widen, Schema};
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct MergeError {
+	pub a: String,
+	pub b: String,
+}
+impl Error for MergeError {}
+impl Display for MergeError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "could not merge {} with {}", self.a, self.b)
+	}
+}
+
+impl Schema {
+	/// Merge with `other` into a schema that the values of both can be
+	/// [coerced](Self::coerce) to, such as to union sources whose schemas
+	/// differ slightly.
+	///
+	/// Integers of different widths widen to the wider, and integers and
+	/// floats to `F64`, as do `U64` and signed integers, as no signed integer
+	/// holds every `U64`. Either being optional makes the merged schema
+	/// optional. Named groups are merged field by field by name, with fields
+	/// missing from either made optional so that they can be filled with nulls,
+	/// and unnamed groups position by position. Lists and maps are merged
+	/// element-wise, and unions variant by variant, as a union value's
+	/// discriminant indexes its variant, with the variants only in the longer
+	/// union kept as they are.
+	/// Anything else, such as a string with a number, is incompatible.
+	pub fn merge(self, other: Self) -> Result<Self, MergeError> {
+		let signed = |schema: &Self| matches!(schema, Self::I8 | Self::I16 | Self::I32 | Self::I64);
+		let numeric = |schema: &Self| {
+			signed(schema)
+				|| matches!(
+					schema,
+					Self::U8 | Self::U16 | Self::U32 | Self::U64 | Self::F32 | Self::F64
+				)
+		};
+		Ok(match (self, other) {
+			(a, b) if a == b => a,
+			(Self::U64, b) | (b, Self::U64) if signed(&b) => Self::F64,
+			(a, b) if numeric(&a) && numeric(&b) => widen(a, b),
+			(Self::Option(a), Self::Option(b)) => Self::Option(Box::new(a.merge(*b)?)),
+			(Self::Option(a), b) | (b, Self::Option(a)) => Self::Option(Box::new(a.merge(b)?)),
+			(Self::List(a), Self::List(b)) => Self::List(Box::new(a.merge(*b)?)),
+			(Self::Map(a), Self::Map(b)) => {
+				let ((a_keys, a_values), (b_keys, b_values)) = (*a, *b);
+				Self::Map(Box::new((a_keys.merge(b_keys)?, a_values.merge(b_values)?)))
+			}
+			(Self::Group(a, Some(a_names)), Self::Group(b, Some(b_names))) => {
+				Self::merge_named(a, &a_names, b, &b_names)?
+			}
+			(Self::Group(a, None), Self::Group(b, None)) if a.len() == b.len() => Self::Group(
+				a.into_iter()
+					.zip(b)
+					.map(|(a, b)| a.merge(b))
+					.collect::<Result<_, _>>()?,
+				None,
+			),
+			(Self::Union(a), Self::Union(b)) => {
+				let (longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+				let mut shorter = shorter.into_iter();
+				Self::Union(
+					longer
+						.into_iter()
+						.map(|variant| match shorter.next() {
+							Some(other) => variant.merge(other),
+							None => Ok(variant),
+						})
+						.collect::<Result<_, _>>()?,
+				)
+			}
+			(a, b) => {
+				return Err(MergeError {
+					a: format!("{:?}", a),
+					b: format!("{:?}", b),
+				})
+			}
+		})
+	}
+
+	/// Merge named groups by name, in the order of `a`'s fields followed by
+	/// those only in `b`.
+	fn merge_named(
+		a: Vec<Self>, a_names: &LinkedHashMap<String, usize, FxBuildHasher>, b: Vec<Self>,
+		b_names: &LinkedHashMap<String, usize, FxBuildHasher>,
+	) -> Result<Self, MergeError> {
+		let optional = |schema: Self| match schema {
+			Self::Option(_) => schema,
+			schema => Self::Option(Box::new(schema)),
+		};
+		let mut a = a.into_iter().map(Some).collect::<Vec<_>>();
+		let mut b = b.into_iter().map(Some).collect::<Vec<_>>();
+		let mut names = LinkedHashMap::with_hasher(FxBuildHasher::default());
+		let mut fields = Vec::new();
+		for (name, &i) in a_names {
+			let field = a[i].take().unwrap();
+			let field = match b_names.get(name) {
+				Some(&j) => field.merge(b[j].take().unwrap())?,
+				None => optional(field),
+			};
+			let _ = names.insert(name.clone(), fields.len());
+			fields.push(field);
+		}
+		for (name, &j) in b_names {
+			if let Some(field) = b[j].take() {
+				let _ = names.insert(name.clone(), fields.len());
+				fields.push(optional(field));
+			}
+		}
+		Ok(Self::Group(fields, Some(Arc::new(names))))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		super::{CoercePolicy, Union, Value}, *
+	};
+
+	fn group(fields: Vec<(&str, Schema)>) -> Schema {
+		let names = fields
+			.iter()
+			.enumerate()
+			.map(|(i, (name, _))| ((*name).to_owned(), i))
+			.collect();
+		Schema::Group(
+			fields.into_iter().map(|(_, schema)| schema).collect(),
+			Some(Arc::new(names)),
+		)
+	}
+
+	#[test]
+	fn merge_missing_column() {
+		let a = group(vec![("id", Schema::U64), ("name", Schema::String)]);
+		let b = group(vec![
+			("id", Schema::U64),
+			("email", Schema::Option(Box::new(Schema::String))),
+		]);
+		assert_eq!(
+			a.merge(b),
+			Ok(group(vec![
+				("id", Schema::U64),
+				("name", Schema::Option(Box::new(Schema::String))),
+				("email", Schema::Option(Box::new(Schema::String)))
+			]))
+		);
+	}
+
+	#[test]
+	fn merge_widen() {
+		let a = group(vec![("id", Schema::U32), ("score", Schema::I64)]);
+		let b = group(vec![
+			("id", Schema::U64),
+			("score", Schema::Option(Box::new(Schema::F32))),
+		]);
+		assert_eq!(
+			a.merge(b),
+			Ok(group(vec![
+				("id", Schema::U64),
+				("score", Schema::Option(Box::new(Schema::F64)))
+			]))
+		);
+		assert_eq!(
+			Schema::List(Box::new(Schema::I8)).merge(Schema::List(Box::new(Schema::U8))),
+			Ok(Schema::List(Box::new(Schema::I16)))
+		);
+		assert_eq!(Schema::U64.merge(Schema::I64), Ok(Schema::F64));
+		assert_eq!(Schema::I8.merge(Schema::U64), Ok(Schema::F64));
+		assert_eq!(Schema::U32.merge(Schema::I64), Ok(Schema::I64));
+	}
+
+	#[test]
+	fn merge_union() {
+		let a = Schema::Union(vec![Schema::U32, Schema::String]);
+		let b = Schema::Union(vec![Schema::U64, Schema::String, Schema::Bool]);
+		let merged = a.merge(b).unwrap();
+		assert_eq!(
+			merged,
+			Schema::Union(vec![Schema::U64, Schema::String, Schema::Bool])
+		);
+
+		// Values of either input keep their discriminant in the merged schema
+		let values = vec![
+			Value::Union(Union::new(0, Value::U32(1))),
+			Value::Union(Union::new(1, Value::String(String::from("a")))),
+			Value::Union(Union::new(0, Value::U64(u64::MAX))),
+			Value::Union(Union::new(2, Value::Bool(true))),
+		];
+		let coerced = values
+			.into_iter()
+			.map(|value| merged.coerce(value, CoercePolicy::Error))
+			.collect::<Result<Vec<_>, _>>();
+		assert_eq!(
+			coerced,
+			Ok(vec![
+				Value::Union(Union::new(0, Value::U64(1))),
+				Value::Union(Union::new(1, Value::String(String::from("a")))),
+				Value::Union(Union::new(0, Value::U64(u64::MAX))),
+				Value::Union(Union::new(2, Value::Bool(true))),
+			])
+		);
+
+		// Variants at the same position must be compatible
+		assert!(Schema::Union(vec![Schema::String, Schema::U64])
+			.merge(Schema::Union(vec![Schema::U64, Schema::Bool]))
+			.is_err());
+	}
+
+	#[test]
+	fn merge_incompatible() {
+		let a = group(vec![("id", Schema::U64), ("at", Schema::DateTime)]);
+		let b = group(vec![("id", Schema::U64), ("at", Schema::String)]);
+		assert_eq!(
+			a.merge(b),
+			Err(MergeError {
+				a: String::from("DateTime"),
+				b: String::from("String")
+			})
+		);
+		assert!(Schema::Group(vec![Schema::U64], None)
+			.merge(Schema::Group(vec![Schema::U64, Schema::U64], None))
+			.is_err());
+	}
+}
@@ -0,0 +1,296 @@
+//! Project, drop, add and explode columns of streams of named [`Group`]s,
+//! rewriting the field names of each row, and filter them by [`Predicate`]s.
+
+use fxhash::FxBuildHasher;
+use hashlink::LinkedHashMap;
+use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
+use serde_closure::FnMutNamed;
+use std::{fmt, mem, sync::Arc, vec};
+
+use super::{Expr, Group, Predicate, Value};
+use amadeus_core::par_stream::{DistributedStream, Filter, FlatMapSync, MapSync, ParallelStream};
+
+type Names = Arc<LinkedHashMap<String, usize, FxBuildHasher>>;
+
+/// What [`explode`](ParallelStreamRows::explode) does with a row whose list is
+/// empty or null.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ExplodePolicy {
+	/// Emit no rows for it.
+	Drop,
+	/// Emit a single row with the column null, i.e. `Value::Option(None)`.
+	Null,
+}
+
+/// Relational combinators for [`ParallelStream`]s of [`Group`]s with named
+/// fields, such as those read from CSV or JSON as [`Value`]s.
+///
+/// Each panics on a row without field names.
+pub trait ParallelStreamRows: ParallelStream<Item = Group> + Sized {
+	/// Keep only `columns`, in that order.
+	///
+	/// # Panics
+	///
+	/// Panics if a row is missing any of `columns`, or if they contain duplicates.
+	fn select<I>(self, columns: I) -> MapSync<Self, Select>
+	where
+		I: IntoIterator,
+		I::Item: Into<String>,
+	{
+		MapSync::new(
+			self,
+			Select::new(
+				columns.into_iter().map(Into::into).collect(),
+				Cache::default(),
+			),
+		)
+	}
+	/// Remove `column`, leaving rows without it unchanged.
+	fn drop_column(self, column: impl Into<String>) -> MapSync<Self, DropColumn> {
+		MapSync::new(self, DropColumn::new(column.into(), Cache::default()))
+	}
+	/// Set `column` to the result of evaluating `expr` against each row,
+	/// replacing it if it exists and appending it otherwise.
+	///
+	/// # Panics
+	///
+	/// Panics if `expr` fails to evaluate.
+	fn with_column(self, column: impl Into<String>, expr: Expr) -> MapSync<Self, WithColumn> {
+		MapSync::new(self, WithColumn::new(column.into(), expr, Cache::default()))
+	}
+	/// Replace each row with a row per element of the list in `column`, with the
+	/// other columns duplicated, like SQL's `UNNEST`. A null list is treated as
+	/// empty.
+	///
+	/// # Panics
+	///
+	/// Panics if a row is missing `column`, or if it isn't a list.
+	fn explode(
+		self, column: impl Into<String>, policy: ExplodePolicy,
+	) -> FlatMapSync<Self, Explode> {
+		FlatMapSync::new(self, Explode::new(column.into(), policy, Cache::default()))
+	}
+	/// Keep only the rows for which `predicate` is true, so not those for which
+	/// it's null.
+	///
+	/// Sources that can skip data by a predicate, such as Parquet with
+	/// `Parquet::filter_by_schema_predicate`, take it themselves and filter the
+	/// rows they yield by it too, so don't need this as well.
+	///
+	/// # Panics
+	///
+	/// Panics if `predicate` fails to evaluate.
+	fn filter_by_schema_predicate(self, predicate: Predicate) -> Filter<Self, FilterBy> {
+		Filter::new(self, FilterBy::new(predicate))
+	}
+}
+impl<S: ParallelStream<Item = Group>> ParallelStreamRows for S {}
+
+/// Like [`ParallelStreamRows`], for [`DistributedStream`]s.
+pub trait DistributedStreamRows: DistributedStream<Item = Group> + Sized {
+	fn select<I>(self, columns: I) -> MapSync<Self, Select>
+	where
+		I: IntoIterator,
+		I::Item: Into<String>,
+	{
+		MapSync::new(
+			self,
+			Select::new(
+				columns.into_iter().map(Into::into).collect(),
+				Cache::default(),
+			),
+		)
+	}
+	fn drop_column(self, column: impl Into<String>) -> MapSync<Self, DropColumn> {
+		MapSync::new(self, DropColumn::new(column.into(), Cache::default()))
+	}
+	fn with_column(self, column: impl Into<String>, expr: Expr) -> MapSync<Self, WithColumn> {
+		MapSync::new(self, WithColumn::new(column.into(), expr, Cache::default()))
+	}
+	fn explode(
+		self, column: impl Into<String>, policy: ExplodePolicy,
+	) -> FlatMapSync<Self, Explode> {
+		FlatMapSync::new(self, Explode::new(column.into(), policy, Cache::default()))
+	}
+	fn filter_by_schema_predicate(self, predicate: Predicate) -> Filter<Self, FilterBy> {
+		Filter::new(self, FilterBy::new(predicate))
+	}
+}
+impl<S: DistributedStream<Item = Group>> DistributedStreamRows for S {}
+
+FnMutNamed! {
+	pub type Select<> = |self, columns: Vec<String>, cache: Cache<(Names, Vec<usize>)>|row=> Group| -> Group
+	where
+	{
+		let columns = &self.columns;
+		let (names, indices) = self.cache.get(&row, |names| {
+			let mut output = LinkedHashMap::with_capacity_and_hasher(columns.len(), Default::default());
+			let indices = columns
+				.iter()
+				.map(|column| {
+					let index = *names
+						.get(column)
+						.unwrap_or_else(|| panic!("no column named {}", column));
+					assert!(
+						output.insert(column.clone(), output.len()).is_none(),
+						"column {} selected more than once",
+						column
+					);
+					index
+				})
+				.collect();
+			(Arc::new(output), indices)
+		});
+		let mut fields = row.into_fields();
+		let fields = indices
+			.iter()
+			.map(|&index| mem::replace(&mut fields[index], Value::Option(None)))
+			.collect();
+		Group::new(fields, Some(names.clone()))
+	}
+}
+
+FnMutNamed! {
+	pub type DropColumn<> = |self, column: String, cache: Cache<(Names, Option<usize>)>|row=> Group| -> Group
+	where
+	{
+		let column = &self.column;
+		let (names, index) = self.cache.get(&row, |names| match names.get(column) {
+			Some(&index) => {
+				let output = names
+					.keys()
+					.filter(|&name| name != column)
+					.enumerate()
+					.map(|(i, name)| (name.clone(), i))
+					.collect();
+				(Arc::new(output), Some(index))
+			}
+			None => (names.clone(), None),
+		});
+		let names = names.clone();
+		let mut fields = row.into_fields();
+		if let Some(index) = *index {
+			let _ = fields.remove(index);
+		}
+		Group::new(fields, Some(names))
+	}
+}
+
+FnMutNamed! {
+	pub type WithColumn<> = |self, column: String, expr: Expr, cache: Cache<(Names, Option<usize>)>|row=> Group| -> Group
+	where
+	{
+		let value = self
+			.expr
+			.eval(&row)
+			.unwrap_or_else(|err| panic!("failed to evaluate column {}: {}", self.column, err));
+		let column = &self.column;
+		let (names, index) = self.cache.get(&row, |names| {
+			if let Some(&index) = names.get(column) {
+				return (names.clone(), Some(index));
+			}
+			let mut output = (**names).clone();
+			let _ = output.insert(column.clone(), output.len());
+			(Arc::new(output), None)
+		});
+		let names = names.clone();
+		let mut fields = row.into_fields();
+		match *index {
+			Some(index) => fields[index] = value,
+			None => fields.push(value),
+		}
+		Group::new(fields, Some(names))
+	}
+}
+
+FnMutNamed! {
+	pub type Explode<> = |self, column: String, policy: ExplodePolicy, cache: Cache<usize>|row=> Group| -> vec::IntoIter<Group>
+	where
+	{
+		let column = &self.column;
+		let index = *self.cache.get(&row, |names| {
+			*names
+				.get(column)
+				.unwrap_or_else(|| panic!("no column named {}", column))
+		});
+		let names = row.field_names().cloned();
+		let mut fields = row.into_fields();
+		let list = match mem::replace(&mut fields[index], Value::Option(None)) {
+			Value::Option(None) => Ok(Vec::new()),
+			Value::Option(Some(value)) => Value::from(value).into_list().map(Vec::from),
+			value => value.into_list().map(Vec::from),
+		}
+		.unwrap_or_else(|err| panic!("column {} isn't a list: {}", column, err));
+		if list.is_empty() {
+			return match self.policy {
+				ExplodePolicy::Drop => Vec::new(),
+				ExplodePolicy::Null => vec![Group::new(fields, names)],
+			}
+			.into_iter();
+		}
+		let mut rows = Vec::with_capacity(list.len());
+		for value in list {
+			let mut fields = fields.clone();
+			fields[index] = value;
+			rows.push(Group::new(fields, names.clone()));
+		}
+		rows.into_iter()
+	}
+}
+
+FnMutNamed! {
+	pub type FilterBy<> = |self, predicate: Predicate|row=> &Group| -> bool
+	where
+	{
+		self.predicate
+			.eval(row)
+			.unwrap_or_else(|err| panic!("failed to evaluate predicate: {}", err))
+			== Some(true)
+	}
+}
+
+/// The field names of the last row seen along with what was derived from them,
+/// so that consecutive rows sharing field names, as they typically do, are
+/// rewritten without rederiving them. It's not sent with the closure.
+pub(crate) struct Cache<T>(Option<(Names, T)>);
+impl<T> Cache<T> {
+	fn get(&mut self, row: &Group, f: impl FnOnce(&Names) -> T) -> &T {
+		let names = row.field_names().expect("row has no field names");
+		match &self.0 {
+			Some((cached, _)) if Arc::ptr_eq(cached, names) || cached == names => (),
+			_ => self.0 = Some((names.clone(), f(names))),
+		}
+		&self.0.as_ref().unwrap().1
+	}
+}
+impl<T> Default for Cache<T> {
+	fn default() -> Self {
+		Self(None)
+	}
+}
+impl<T> Clone for Cache<T> {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+impl<T> fmt::Debug for Cache<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Cache").finish()
+	}
+}
+impl<T> Serialize for Cache<T> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		().serialize(serializer)
+	}
+}
+impl<'de, T> Deserialize<'de> for Cache<T> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		<()>::deserialize(deserializer).map(|()| Self::default())
+	}
+}
@@ -7,7 +7,7 @@ use chrono::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-	cmp::Ordering, convert::TryInto, error::Error, fmt::{self, Display}, str::FromStr
+	cmp::Ordering, convert::{TryFrom, TryInto}, error::Error, fmt::{self, Display}, ops::{Add, Neg, Sub}, str::FromStr
 };
 
 use super::AmadeusOrd;
@@ -576,6 +576,28 @@ impl DateTime {
 			timezone: self.timezone,
 		}
 	}
+	/// The date and time in UTC.
+	pub fn without_timezone(&self) -> DateTimeWithoutTimezone {
+		self.date_time
+	}
+	pub fn timezone(&self) -> Timezone {
+		self.timezone
+	}
+	/// Add `duration` as [`DateTimeWithoutTimezone::checked_add`] does, to the
+	/// date and time in UTC.
+	pub fn checked_add(&self, duration: &Duration) -> Option<Self> {
+		Some(Self {
+			date_time: self.date_time.checked_add(duration)?,
+			timezone: self.timezone,
+		})
+	}
+	pub fn checked_sub(&self, duration: &Duration) -> Option<Self> {
+		self.checked_add(&duration.checked_neg()?)
+	}
+	/// The duration from `earlier` to this, in days and nanoseconds.
+	pub fn duration_since(&self, earlier: &Self) -> Duration {
+		self.date_time.duration_since(&earlier.date_time)
+	}
 }
 impl AmadeusOrd for DateTime {
 	fn amadeus_cmp(&self, other: &Self) -> Ordering {
@@ -604,18 +626,248 @@ impl FromStr for DateTime {
 	}
 }
 
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_DAY: i64 = 86_400 * NANOS_PER_SECOND;
+
+/// A duration of calendar months, days and nanoseconds, like Parquet's
+/// [Interval logical type](https://github.com/apache/parquet-format/blob/master/LogicalTypes.md#interval)
+/// and Postgres's `interval`.
+///
+/// Months vary in length, so are kept apart from days, whose nanoseconds are
+/// normalized into them so that, for example, a day equals 24 hours. Months
+/// make durations only partially ordered: [`checked_cmp`](Self::checked_cmp)
+/// gives the order that holds whatever the length of the months, while the
+/// total [`Ord`] takes a month as 30 days.
 // https://github.com/chronotope/chrono/issues/52
-#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub struct Duration {
 	months: i64,
 	days: i64,
 	nanos: i64,
 }
+impl Duration {
+	/// Create a duration, or `None` if the nanoseconds overflow the days.
+	pub fn new(months: i64, days: i64, nanos: i64) -> Option<Self> {
+		let days = days.checked_add(nanos / NANOS_PER_DAY)?;
+		let mut nanos = nanos % NANOS_PER_DAY;
+		// Give the days and nanoseconds the same sign
+		let days = if days > 0 && nanos < 0 {
+			nanos += NANOS_PER_DAY;
+			days - 1
+		} else if days < 0 && nanos > 0 {
+			nanos -= NANOS_PER_DAY;
+			days + 1
+		} else {
+			days
+		};
+		Some(Self {
+			months,
+			days,
+			nanos,
+		})
+	}
+	pub fn from_months(months: i64) -> Self {
+		Self {
+			months,
+			days: 0,
+			nanos: 0,
+		}
+	}
+	pub fn from_days(days: i64) -> Self {
+		Self {
+			months: 0,
+			days,
+			nanos: 0,
+		}
+	}
+	pub fn from_nanos(nanos: i64) -> Self {
+		Self {
+			months: 0,
+			days: nanos / NANOS_PER_DAY,
+			nanos: nanos % NANOS_PER_DAY,
+		}
+	}
+	pub fn months(&self) -> i64 {
+		self.months
+	}
+	pub fn days(&self) -> i64 {
+		self.days
+	}
+	/// The nanoseconds beyond [`days`](Self::days), which are fewer than a day.
+	pub fn nanos(&self) -> i64 {
+		self.nanos
+	}
+	pub fn checked_add(&self, other: &Self) -> Option<Self> {
+		Self::new(
+			self.months.checked_add(other.months)?,
+			self.days.checked_add(other.days)?,
+			self.nanos.checked_add(other.nanos)?,
+		)
+	}
+	pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+		self.checked_add(&other.checked_neg()?)
+	}
+	pub fn checked_neg(&self) -> Option<Self> {
+		Self::new(
+			self.months.checked_neg()?,
+			self.days.checked_neg()?,
+			self.nanos.checked_neg()?,
+		)
+	}
+	/// Compare with `other`, or `None` if the order depends on the length of the
+	/// months, as with 1 month and 30 days.
+	pub fn checked_cmp(&self, other: &Self) -> Option<Ordering> {
+		let shortest = self.approx_nanos(28).cmp(&other.approx_nanos(28));
+		let longest = self.approx_nanos(31).cmp(&other.approx_nanos(31));
+		if shortest == longest {
+			Some(shortest)
+		} else {
+			None
+		}
+	}
+	/// The length in nanoseconds, taking months as `month_days` days.
+	fn approx_nanos(&self, month_days: i64) -> i128 {
+		(i128::from(self.months) * i128::from(month_days) + i128::from(self.days))
+			* i128::from(NANOS_PER_DAY)
+			+ i128::from(self.nanos)
+	}
+}
+impl PartialOrd for Duration {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+/// Orders by length taking a month as 30 days, and then by months, so that 1
+/// month is greater than 30 days.
+impl Ord for Duration {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.approx_nanos(30)
+			.cmp(&other.approx_nanos(30))
+			.then_with(|| self.months.cmp(&other.months))
+	}
+}
 impl AmadeusOrd for Duration {
 	fn amadeus_cmp(&self, other: &Self) -> Ordering {
 		Ord::cmp(self, other)
 	}
 }
+impl Add for Duration {
+	type Output = Self;
+
+	fn add(self, other: Self) -> Self {
+		self.checked_add(&other).expect("overflow adding durations")
+	}
+}
+impl Sub for Duration {
+	type Output = Self;
+
+	fn sub(self, other: Self) -> Self {
+		self.checked_sub(&other)
+			.expect("overflow subtracting durations")
+	}
+}
+impl Neg for Duration {
+	type Output = Self;
+
+	fn neg(self) -> Self {
+		self.checked_neg().expect("overflow negating duration")
+	}
+}
+/// Corresponds to ISO 8601 string `PnMnDTnS`, such as `P1M2DT3.5S`
+impl Display for Duration {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("P")?;
+		if self.months != 0 {
+			write!(f, "{}M", self.months)?;
+		}
+		if self.days != 0 {
+			write!(f, "{}D", self.days)?;
+		}
+		if self.nanos != 0 || (self.months == 0 && self.days == 0) {
+			let sign = if self.nanos < 0 { "-" } else { "" };
+			let (seconds, nanos) = (
+				(self.nanos / NANOS_PER_SECOND).abs(),
+				(self.nanos % NANOS_PER_SECOND).abs(),
+			);
+			write!(f, "T{}{}", sign, seconds)?;
+			if nanos != 0 {
+				let nanos = format!("{:09}", nanos);
+				write!(f, ".{}", nanos.trim_end_matches('0'))?;
+			}
+			f.write_str("S")?;
+		}
+		Ok(())
+	}
+}
+impl FromStr for Duration {
+	type Err = ParseDateError;
+
+	/// Parse an ISO 8601 duration such as `P1Y2M3W4DT5H6M7.5S`, whose components
+	/// may be negative, taking a year as 12 months and a week as 7 days.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut rest = s.strip_prefix('P').ok_or(ParseDateError)?;
+		let (mut months, mut days, mut nanos) = (0_i64, 0_i64, 0_i64);
+		let mut time = false;
+		while !rest.is_empty() {
+			if !time && rest.starts_with('T') {
+				time = true;
+				rest = &rest[1..];
+				continue;
+			}
+			let end = rest
+				.find(|c: char| c.is_ascii_alphabetic())
+				.ok_or(ParseDateError)?;
+			let (number, designator) = (&rest[..end], &rest[end..=end]);
+			rest = &rest[end + 1..];
+			let (total, unit) = match (time, designator) {
+				(false, "Y") => (&mut months, 12),
+				(false, "M") => (&mut months, 1),
+				(false, "W") => (&mut days, 7),
+				(false, "D") => (&mut days, 1),
+				(true, "H") => (&mut nanos, 3600 * NANOS_PER_SECOND),
+				(true, "M") => (&mut nanos, 60 * NANOS_PER_SECOND),
+				(true, "S") => {
+					nanos = nanos
+						.checked_add(parse_seconds(number)?)
+						.ok_or(ParseDateError)?;
+					continue;
+				}
+				_ => return Err(ParseDateError),
+			};
+			let number = number.parse::<i64>().map_err(|_| ParseDateError)?;
+			*total = number
+				.checked_mul(unit)
+				.and_then(|number| total.checked_add(number))
+				.ok_or(ParseDateError)?;
+		}
+		if s.len() == 1 || s.ends_with('T') {
+			return Err(ParseDateError);
+		}
+		Self::new(months, days, nanos).ok_or(ParseDateError)
+	}
+}
+
+/// Parse seconds with up to 9 decimal places into nanoseconds.
+fn parse_seconds(s: &str) -> Result<i64, ParseDateError> {
+	let (negative, s) = s.strip_prefix('-').map_or((false, s), |s| (true, s));
+	let mut parts = s.splitn(2, '.');
+	let seconds = parts.next().unwrap();
+	let fraction = parts.next().unwrap_or("");
+	if seconds.is_empty()
+		|| fraction.len() > 9
+		|| !(seconds.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit())
+	{
+		return Err(ParseDateError);
+	}
+	let fraction = format!("{:0<9}", fraction).parse::<i64>().unwrap();
+	let nanos = seconds
+		.parse::<i64>()
+		.ok()
+		.and_then(|seconds| seconds.checked_mul(NANOS_PER_SECOND))
+		.and_then(|nanos| nanos.checked_add(fraction))
+		.ok_or(ParseDateError)?;
+	Ok(if negative { -nanos } else { nanos })
+}
 
 // Parquet's [Date logical type](https://github.com/apache/parquet-format/blob/master/LogicalTypes.md#date) is i32 days from Unix epoch
 // Postgres https://www.postgresql.org/docs/11/datatype-datetime.html is 4713 BC to 5874897 AD
@@ -671,6 +923,16 @@ impl DateWithoutTimezone {
 	pub fn as_days(&self) -> i64 {
 		self.0
 	}
+	/// Add `months` calendar months, clamping the day to the end of the month
+	/// if it's shorter, or `None` on overflow.
+	pub fn checked_add_months(&self, months: i64) -> Option<Self> {
+		let months = (self.year() * 12 + i64::from(self.month()) - 1).checked_add(months)?;
+		let (year, month) = (months.div_euclid(12), months.rem_euclid(12) + 1);
+		let month = u8::try_from(month).ok()?;
+		(1..=self.day())
+			.rev()
+			.find_map(|day| Self::new(year, month, day))
+	}
 	#[doc(hidden)]
 	pub fn from_chrono(date: &NaiveDate) -> Self {
 		Self::from_days(i64::from(date.num_days_from_ce()) - GREGORIAN_DAY_OF_EPOCH).unwrap()
@@ -877,6 +1139,23 @@ impl DateTimeWithoutTimezone {
 			timezone,
 		}
 	}
+	/// Interpret this as a wall-clock time in `timezone`, rather than UTC as
+	/// [`with_timezone`](Self::with_timezone) does, for example to correct
+	/// Parquet INT96 timestamps written in local time. A wall-clock time that's
+	/// skipped or repeated by a change of offset resolves to either side of it.
+	pub fn localize(self, timezone: Timezone) -> Option<DateTime> {
+		let local = self.as_chrono()?;
+		let offset_at = |utc: NaiveDateTime| {
+			let offset =
+				timezone.as_offset_at(&Self::from_chrono(&utc).with_timezone(Timezone::UTC));
+			chrono::Duration::seconds(offset.into())
+		};
+		// The offset at the local time taken as UTC is at most one change of
+		// offset away from the right one, so a second step settles it
+		let utc = local.checked_sub_signed(offset_at(local))?;
+		let utc = local.checked_sub_signed(offset_at(utc))?;
+		Some(Self::from_chrono(&utc).with_timezone(timezone))
+	}
 	#[doc(hidden)]
 	pub fn from_chrono(date_time: &NaiveDateTime) -> Self {
 		Self::new(
@@ -900,6 +1179,29 @@ impl DateTimeWithoutTimezone {
 			time: self.time.truncate_minutes(minutes),
 		}
 	}
+	/// Add `duration`: first its months, as by
+	/// [`checked_add_months`](DateWithoutTimezone::checked_add_months), and then
+	/// its days and nanoseconds. `None` on overflow.
+	pub fn checked_add(&self, duration: &Duration) -> Option<Self> {
+		let date = self.date.checked_add_months(duration.months)?;
+		let date = DateWithoutTimezone::from_days(date.as_days().checked_add(duration.days)?)?;
+		let date_time = Self::from_date_time(date, self.time)?
+			.as_chrono()?
+			.checked_add_signed(chrono::Duration::nanoseconds(duration.nanos))?;
+		Some(Self::from_chrono(&date_time))
+	}
+	pub fn checked_sub(&self, duration: &Duration) -> Option<Self> {
+		self.checked_add(&duration.checked_neg()?)
+	}
+	/// The duration from `earlier` to this, in days and nanoseconds.
+	pub fn duration_since(&self, earlier: &Self) -> Duration {
+		let nanos = |time: TimeWithoutTimezone| {
+			i64::from(time.0.num_seconds_from_midnight()) * NANOS_PER_SECOND
+				+ i64::from(time.nanosecond())
+		};
+		Duration::from_days(self.date.as_days() - earlier.date.as_days())
+			+ Duration::from_nanos(nanos(self.time) - nanos(earlier.time))
+	}
 	// /// Create a DateTimeWithoutTimezone from the number of milliseconds since the Unix epoch
 	// pub fn from_millis(millis: i64) -> Self {
 	// 	let mut days = millis / (SECONDS_PER_DAY * MILLIS_PER_SECOND);
@@ -998,6 +1300,33 @@ impl FromStr for DateTimeWithoutTimezone {
 	}
 }
 
+macro_rules! duration_ops {
+	($($t:ty)*) => {$(
+		impl Add<Duration> for $t {
+			type Output = Self;
+
+			fn add(self, duration: Duration) -> Self {
+				self.checked_add(&duration).expect("overflow adding duration")
+			}
+		}
+		impl Sub<Duration> for $t {
+			type Output = Self;
+
+			fn sub(self, duration: Duration) -> Self {
+				self.checked_sub(&duration).expect("overflow subtracting duration")
+			}
+		}
+		impl Sub for $t {
+			type Output = Duration;
+
+			fn sub(self, earlier: Self) -> Duration {
+				self.duration_since(&earlier)
+			}
+		}
+	)*};
+}
+duration_ops!(DateTime DateTimeWithoutTimezone);
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1018,6 +1347,135 @@ mod tests {
 	// 	assert_eq!(value.as_millis().unwrap(), -210866803200000);
 	// }
 
+	#[test]
+	fn duration_arithmetic() {
+		let date_time = |year, month, day, hour| {
+			DateTimeWithoutTimezone::new(year, month, day, hour, 0, 0, 0)
+				.unwrap()
+				.with_timezone(Timezone::UTC)
+		};
+		let nanos_per_hour = 3600 * NANOS_PER_SECOND;
+		let month_and_a_bit = Duration::new(1, 1, 2 * nanos_per_hour).unwrap();
+		assert_eq!(
+			date_time(2020, 1, 15, 0) + month_and_a_bit,
+			date_time(2020, 2, 16, 2)
+		);
+		// The day is clamped to the end of a shorter month
+		assert_eq!(
+			date_time(2020, 1, 31, 12) + Duration::from_months(1),
+			date_time(2020, 2, 29, 12)
+		);
+		assert_eq!(
+			date_time(2020, 3, 31, 12) - Duration::from_months(1),
+			date_time(2020, 2, 29, 12)
+		);
+		assert_eq!(
+			date_time(2020, 12, 31, 23) + Duration::from_nanos(nanos_per_hour),
+			date_time(2021, 1, 1, 0)
+		);
+		assert_eq!(
+			date_time(2020, 3, 2, 1) - date_time(2020, 2, 28, 3),
+			Duration::new(0, 2, 22 * nanos_per_hour).unwrap()
+		);
+		assert_eq!(
+			date_time(2020, 2, 28, 3) - date_time(2020, 3, 2, 1),
+			Duration::new(0, -2, -22 * nanos_per_hour).unwrap()
+		);
+		let max = DateTimeWithoutTimezone::from_chrono(&NaiveDateTime::MAX);
+		assert_eq!(max.checked_add(&Duration::from_days(1)), None);
+	}
+
+	#[test]
+	fn duration_cmp() {
+		let nanos_per_hour = 3600 * NANOS_PER_SECOND;
+		// Nanoseconds normalize into days
+		assert_eq!(
+			Duration::from_days(1),
+			Duration::from_nanos(24 * nanos_per_hour)
+		);
+		assert_eq!(
+			Duration::new(0, 1, -nanos_per_hour),
+			Duration::new(0, 0, 23 * nanos_per_hour)
+		);
+		// A month is between 28 and 31 days
+		let month = Duration::from_months(1);
+		assert_eq!(month.checked_cmp(&Duration::from_days(30)), None);
+		assert_eq!(month.checked_cmp(&Duration::from_days(28)), None);
+		assert_eq!(
+			month.checked_cmp(&Duration::from_days(27)),
+			Some(Ordering::Greater)
+		);
+		assert_eq!(
+			month.checked_cmp(&Duration::from_days(32)),
+			Some(Ordering::Less)
+		);
+		assert_eq!(month.checked_cmp(&month), Some(Ordering::Equal));
+		// The total order takes a month as 30 days, months breaking ties
+		assert!(month > Duration::from_days(29));
+		assert!(month > Duration::from_days(30));
+		assert!(month < Duration::new(0, 30, 1).unwrap());
+		assert!(-month < Duration::from_nanos(-1));
+	}
+
+	#[test]
+	fn duration_parse() {
+		let duration = Duration::new(14, 3, 4_500_000_000).unwrap();
+		assert_eq!(duration.to_string(), "P14M3DT4.5S");
+		assert_eq!(duration.to_string().parse(), Ok(duration));
+		assert_eq!("P1Y2M3DT4.5S".parse(), Ok(duration));
+		assert_eq!("P2W".parse(), Ok(Duration::from_days(14)));
+		assert_eq!(
+			"PT1H-30M".parse(),
+			Ok(Duration::from_nanos(1800 * NANOS_PER_SECOND))
+		);
+		assert_eq!(Duration::from_nanos(-1).to_string(), "PT-0.000000001S");
+		assert_eq!("PT-0.000000001S".parse(), Ok(Duration::from_nanos(-1)));
+		assert_eq!(Duration::from_days(0).to_string(), "PT0S");
+		for invalid in &[
+			"",
+			"P",
+			"PT",
+			"1D",
+			"P1H",
+			"PT1D",
+			"P1.5D",
+			"PT1.0000000001S",
+		] {
+			assert_eq!(
+				invalid.parse::<Duration>(),
+				Err(ParseDateError),
+				"{}",
+				invalid
+			);
+		}
+	}
+
+	#[test]
+	fn localize() {
+		let local = DateTimeWithoutTimezone::new(2020, 7, 1, 12, 0, 0, 0).unwrap();
+		let utc = |hour| DateTimeWithoutTimezone::new(2020, 7, 1, hour, 0, 0, 0).unwrap();
+		assert_eq!(
+			local.localize(Timezone::UTC).unwrap(),
+			local.with_timezone(Timezone::UTC)
+		);
+		let timezone = Timezone::from_offset(-5 * 60 * 60).unwrap();
+		assert_eq!(
+			local.localize(timezone).unwrap(),
+			utc(17).with_timezone(timezone)
+		);
+		// British Summer Time is an hour ahead of UTC
+		let timezone = Timezone::from_name("Europe/London").unwrap();
+		assert_eq!(
+			local.localize(timezone).unwrap(),
+			utc(11).with_timezone(timezone)
+		);
+		let winter = DateTimeWithoutTimezone::new(2020, 1, 1, 12, 0, 0, 0).unwrap();
+		assert_eq!(
+			winter.localize(timezone).unwrap().without_timezone(),
+			winter
+		);
+	}
+
 	#[test]
 	fn timezone() {
 		assert_eq!(
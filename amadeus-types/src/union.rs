@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::{AmadeusOrd, Value};
+
+/// A value of a union (or tagged union) type, as found in Avro unions and
+/// Thrift-style Parquet unions: the index of the variant it holds, and that
+/// variant's payload.
+///
+/// Unions are ordered by discriminant, and then by payload.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct Union {
+	discriminant: usize,
+	value: Box<Value>,
+}
+impl Union {
+	pub fn new(discriminant: usize, value: Value) -> Self {
+		Self {
+			discriminant,
+			value: Box::new(value),
+		}
+	}
+	pub fn discriminant(&self) -> usize {
+		self.discriminant
+	}
+	pub fn value(&self) -> &Value {
+		&self.value
+	}
+	pub fn into_value(self) -> Value {
+		*self.value
+	}
+}
+impl AmadeusOrd for Union {
+	fn amadeus_cmp(&self, other: &Self) -> Ordering {
+		self.discriminant
+			.cmp(&other.discriminant)
+			.then_with(|| self.value.amadeus_cmp(&other.value))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{CoercePolicy, Schema};
+	use std::collections::HashSet;
+
+	#[test]
+	fn ordering() {
+		let a = Union::new(0, Value::U32(5));
+		let b = Union::new(0, Value::U32(7));
+		let c = Union::new(1, Value::String(String::from("a")));
+		// Differing payload types are ordered by discriminant alone
+		assert_eq!(a.amadeus_cmp(&b), Ordering::Less);
+		assert_eq!(c.amadeus_cmp(&a), Ordering::Greater);
+		assert_eq!(a.partial_cmp(&c), Some(Ordering::Less));
+		assert_eq!(
+			Value::Union(b.clone()).amadeus_cmp(&Value::Union(a.clone())),
+			Ordering::Greater
+		);
+
+		let values = vec![
+			a.clone(),
+			c.clone(),
+			a.clone(),
+			Union::new(1, Value::U32(5)),
+			b,
+		];
+		let distinct = values.into_iter().map(Value::Union).collect::<HashSet<_>>();
+		assert_eq!(distinct.len(), 4);
+		assert!(distinct.contains(&Value::Union(c)));
+		assert_eq!(
+			Value::Union(a).into_union().unwrap().into_value(),
+			Value::U32(5)
+		);
+	}
+
+	#[test]
+	fn schema() {
+		let values = vec![
+			Value::Union(Union::new(0, Value::U8(1))),
+			Value::Union(Union::new(2, Value::Bool(true))),
+			Value::Union(Union::new(0, Value::U16(300))),
+		];
+		let schema = Schema::infer(values).unwrap();
+		let expected = Schema::Union(vec![Schema::U16, Schema::String, Schema::Bool]);
+		assert_eq!(schema, expected);
+
+		let value = Value::Union(Union::new(0, Value::U8(1)));
+		let coerced = schema.coerce(value, CoercePolicy::Error).unwrap();
+		assert_eq!(coerced, Value::Union(Union::new(0, Value::U16(1))));
+		let value = Value::Union(Union::new(3, Value::U8(1)));
+		assert!(schema.coerce(value, CoercePolicy::Error).is_err());
+	}
+}
@@ -7,13 +7,13 @@ use hashlink::LinkedHashMap;
 use recycle::VecExt;
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
 use std::{
-	cmp::Ordering, collections::HashMap, convert::TryInto, fmt, fmt::Debug, hash::{BuildHasher, Hash, Hasher}, iter::FromIterator, sync::Arc
+	borrow::Cow, cmp::Ordering, collections::HashMap, convert::TryInto, fmt, fmt::Debug, hash::{BuildHasher, Hash, Hasher}, iter::FromIterator, sync::Arc
 };
 
 use crate::list::ListVec;
 
 use super::{
-	AmadeusOrd, Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Downcast, DowncastError, DowncastFrom, Enum, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, ValueRequired, Webpage
+	AmadeusOrd, Bson, Data, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Downcast, DowncastError, DowncastFrom, Duration, Enum, GeoPoint, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Union, Url, Uuid, ValueRequired, Webpage
 };
 
 #[derive(Clone, PartialEq, Debug)]
@@ -36,6 +36,7 @@ pub enum SchemaIncomplete {
 	DateTime,
 	DateTimeWithoutTimezone,
 	Timezone,
+	Duration,
 	Decimal,
 	Bson,
 	String,
@@ -49,10 +50,11 @@ pub enum SchemaIncomplete {
 			Option<Arc<LinkedHashMap<String, usize, FxBuildHasher>>>,
 		)>,
 	),
+	Union(Vec<SchemaIncomplete>),
 	Option(Box<SchemaIncomplete>),
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub enum Schema {
 	Bool,
 	U8,
@@ -72,6 +74,7 @@ pub enum Schema {
 	DateTime,
 	DateTimeWithoutTimezone,
 	Timezone,
+	Duration,
 	Decimal,
 	Bson,
 	String,
@@ -81,11 +84,54 @@ pub enum Schema {
 	Map(Box<(Schema, Schema)>),
 	Group(
 		Vec<Schema>,
-		Option<Arc<LinkedHashMap<String, usize, FxBuildHasher>>>,
+		#[serde(with = "field_names")] Option<Arc<LinkedHashMap<String, usize, FxBuildHasher>>>,
 	),
+	/// The schemas of each variant, by discriminant.
+	Union(Vec<Schema>),
 	Option(Box<Schema>),
 }
 
+mod field_names {
+	use fxhash::FxBuildHasher;
+	use hashlink::LinkedHashMap;
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+	use std::sync::Arc;
+
+	#[allow(clippy::ref_option)]
+	pub fn serialize<S>(
+		t: &Option<Arc<LinkedHashMap<String, usize, FxBuildHasher>>>, serializer: S,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		t.as_ref()
+			.map(|names| {
+				let mut names = names.iter().collect::<Vec<_>>();
+				names.sort_by_key(|&(_, &i)| i);
+				names.into_iter().map(|(name, _)| name).collect::<Vec<_>>()
+			})
+			.serialize(serializer)
+	}
+	pub fn deserialize<'de, D>(
+		deserializer: D,
+	) -> Result<Option<Arc<LinkedHashMap<String, usize, FxBuildHasher>>>, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		Ok(
+			Option::<Vec<String>>::deserialize(deserializer)?.map(|names| {
+				Arc::new(
+					names
+						.into_iter()
+						.enumerate()
+						.map(|(i, name)| (name, i))
+						.collect(),
+				)
+			}),
+		)
+	}
+}
+
 /// Represents any valid Amadeus value.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Value {
@@ -128,6 +174,8 @@ pub enum Value {
 	DateTimeWithoutTimezone(DateTimeWithoutTimezone),
 	/// Timezone.
 	Timezone(Timezone),
+	/// Duration of months, days and nanoseconds.
+	Duration(Duration),
 	/// Decimal value.
 	Decimal(Decimal),
 	/// BSON binary value.
@@ -144,6 +192,12 @@ pub enum Value {
 	Webpage(Webpage<'static>),
 	/// Ip Address
 	IpAddr(IpAddr),
+	/// Latitude and longitude
+	GeoPoint(GeoPoint),
+	/// UUID
+	Uuid(Uuid),
+	/// Variant of a union, by index.
+	Union(Union),
 
 	// Complex types
 	/// List of elements.
@@ -184,6 +238,7 @@ mod optional_value {
 				ValueRequired::DateTime(value) => serializer.serialize_some(&value),
 				ValueRequired::DateTimeWithoutTimezone(value) => serializer.serialize_some(&value),
 				ValueRequired::Timezone(value) => serializer.serialize_some(&value),
+				ValueRequired::Duration(value) => serializer.serialize_some(&value),
 				ValueRequired::Decimal(value) => serializer.serialize_some(&value),
 				ValueRequired::Bson(value) => serializer.serialize_some(&value),
 				ValueRequired::String(value) => serializer.serialize_some(&value),
@@ -192,6 +247,9 @@ mod optional_value {
 				ValueRequired::Url(value) => serializer.serialize_some(&value),
 				ValueRequired::Webpage(value) => serializer.serialize_some(&value),
 				ValueRequired::IpAddr(value) => serializer.serialize_some(&value),
+				ValueRequired::GeoPoint(value) => serializer.serialize_some(&value),
+				ValueRequired::Uuid(value) => serializer.serialize_some(&value),
+				ValueRequired::Union(value) => serializer.serialize_some(&value),
 				ValueRequired::List(value) => serializer.serialize_some(&value),
 				ValueRequired::Map(value) => serializer.serialize_some(&value),
 				ValueRequired::Group(value) => serializer.serialize_some(&value),
@@ -281,6 +339,10 @@ impl Hash for Value {
 				13_u8.hash(state);
 				value.hash(state);
 			}
+			Self::Duration(value) => {
+				29_u8.hash(state);
+				value.hash(state);
+			}
 			Self::Decimal(_value) => {
 				14_u8.hash(state);
 			}
@@ -312,6 +374,17 @@ impl Hash for Value {
 				21_u8.hash(state);
 				value.hash(state);
 			}
+			Self::GeoPoint(_value) => {
+				26_u8.hash(state);
+			}
+			Self::Uuid(value) => {
+				28_u8.hash(state);
+				value.hash(state);
+			}
+			Self::Union(value) => {
+				27_u8.hash(state);
+				value.hash(state);
+			}
 			Self::List(value) => {
 				22_u8.hash(state);
 				value.hash(state);
@@ -353,6 +426,7 @@ impl PartialOrd for Value {
 				a.partial_cmp(b)
 			}
 			(Self::Timezone(a), Self::Timezone(b)) => a.partial_cmp(b),
+			(Self::Duration(a), Self::Duration(b)) => a.checked_cmp(b),
 			(Self::Decimal(a), Self::Decimal(b)) => a.partial_cmp(b),
 			(Self::Bson(a), Self::Bson(b)) => a.partial_cmp(b),
 			(Self::String(a), Self::String(b)) => a.partial_cmp(b),
@@ -361,6 +435,9 @@ impl PartialOrd for Value {
 			(Self::Url(a), Self::Url(b)) => a.partial_cmp(b),
 			(Self::Webpage(a), Self::Webpage(b)) => a.partial_cmp(b),
 			(Self::IpAddr(a), Self::IpAddr(b)) => a.partial_cmp(b),
+			(Self::GeoPoint(a), Self::GeoPoint(b)) => a.partial_cmp(b),
+			(Self::Uuid(a), Self::Uuid(b)) => a.partial_cmp(b),
+			(Self::Union(a), Self::Union(b)) => a.partial_cmp(b),
 			(Self::List(a), Self::List(b)) => a.partial_cmp(b),
 			(Self::Map(_a), Self::Map(_b)) => None, // TODO?
 			(Self::Group(a), Self::Group(b)) => a.partial_cmp(b),
@@ -392,6 +469,7 @@ impl AmadeusOrd for Value {
 				a.amadeus_cmp(b)
 			}
 			(Self::Timezone(a), Self::Timezone(b)) => a.amadeus_cmp(b),
+			(Self::Duration(a), Self::Duration(b)) => a.amadeus_cmp(b),
 			(Self::Decimal(a), Self::Decimal(b)) => a.amadeus_cmp(b),
 			(Self::Bson(a), Self::Bson(b)) => a.amadeus_cmp(b),
 			(Self::String(a), Self::String(b)) => a.amadeus_cmp(b),
@@ -400,6 +478,9 @@ impl AmadeusOrd for Value {
 			(Self::Url(a), Self::Url(b)) => a.amadeus_cmp(b),
 			(Self::Webpage(a), Self::Webpage(b)) => a.amadeus_cmp(b),
 			(Self::IpAddr(a), Self::IpAddr(b)) => a.amadeus_cmp(b),
+			(Self::GeoPoint(a), Self::GeoPoint(b)) => a.amadeus_cmp(b),
+			(Self::Uuid(a), Self::Uuid(b)) => a.amadeus_cmp(b),
+			(Self::Union(a), Self::Union(b)) => a.amadeus_cmp(b),
 			(Self::List(a), Self::List(b)) => a.amadeus_cmp(b),
 			(Self::Map(a), Self::Map(b)) => a.amadeus_cmp(b),
 			(Self::Group(a), Self::Group(b)) => a.amadeus_cmp(b),
@@ -410,7 +491,7 @@ impl AmadeusOrd for Value {
 }
 
 impl Value {
-	fn type_name(&self) -> &'static str {
+	pub(crate) fn type_name(&self) -> &'static str {
 		match self {
 			Self::Bool(_value) => "bool",
 			Self::U8(_value) => "u8",
@@ -430,6 +511,7 @@ impl Value {
 			Self::DateTime(_value) => "date_time",
 			Self::DateTimeWithoutTimezone(_value) => "date_time_without_timezone",
 			Self::Timezone(_value) => "timezone",
+			Self::Duration(_value) => "duration",
 			Self::Decimal(_value) => "decimal",
 			Self::Bson(_value) => "bson",
 			Self::String(_value) => "string",
@@ -438,6 +520,9 @@ impl Value {
 			Self::Url(_value) => "url",
 			Self::Webpage(_value) => "webpage",
 			Self::IpAddr(_value) => "ip_addr",
+			Self::GeoPoint(_value) => "geo_point",
+			Self::Uuid(_value) => "uuid",
+			Self::Union(_value) => "union",
 			Self::List(_value) => "list",
 			Self::Map(_value) => "map",
 			Self::Group(_value) => "group",
@@ -967,6 +1052,35 @@ impl Value {
 		}
 	}
 
+	/// Returns true if the `Value` is a Duration. Returns false otherwise.
+	pub fn is_duration(&self) -> bool {
+		matches!(self, Self::Duration(_))
+	}
+
+	/// If the `Value` is a Duration, return a reference to it. Returns Err otherwise.
+	pub fn as_duration(&self) -> Result<&Duration, DowncastError> {
+		if let Self::Duration(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "duration",
+			})
+		}
+	}
+
+	/// If the `Value` is a Duration, return it. Returns Err otherwise.
+	pub fn into_duration(self) -> Result<Duration, DowncastError> {
+		if let Self::Duration(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "duration",
+			})
+		}
+	}
+
 	/// Returns true if the `Value` is an Decimal. Returns false otherwise.
 	pub fn is_decimal(&self) -> bool {
 		matches!(self, Self::Decimal(_))
@@ -1199,6 +1313,93 @@ impl Value {
 		}
 	}
 
+	/// Returns true if the `Value` is an GeoPoint. Returns false otherwise.
+	pub fn is_geo_point(&self) -> bool {
+		matches!(self, Self::GeoPoint(_))
+	}
+
+	/// If the `Value` is an GeoPoint, return a reference to it. Returns Err otherwise.
+	pub fn as_geo_point(&self) -> Result<&GeoPoint, DowncastError> {
+		if let Self::GeoPoint(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "geo_point",
+			})
+		}
+	}
+
+	/// If the `Value` is an GeoPoint, return it. Returns Err otherwise.
+	pub fn into_geo_point(self) -> Result<GeoPoint, DowncastError> {
+		if let Self::GeoPoint(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "geo_point",
+			})
+		}
+	}
+
+	/// Returns true if the `Value` is an Uuid. Returns false otherwise.
+	pub fn is_uuid(&self) -> bool {
+		matches!(self, Self::Uuid(_))
+	}
+
+	/// If the `Value` is an Uuid, return a reference to it. Returns Err otherwise.
+	pub fn as_uuid(&self) -> Result<&Uuid, DowncastError> {
+		if let Self::Uuid(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "uuid",
+			})
+		}
+	}
+
+	/// If the `Value` is an Uuid, return it. Returns Err otherwise.
+	pub fn into_uuid(self) -> Result<Uuid, DowncastError> {
+		if let Self::Uuid(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "uuid",
+			})
+		}
+	}
+
+	/// Returns true if the `Value` is an Union. Returns false otherwise.
+	pub fn is_union(&self) -> bool {
+		matches!(self, Self::Union(_))
+	}
+
+	/// If the `Value` is an Union, return a reference to it. Returns Err otherwise.
+	pub fn as_union(&self) -> Result<&Union, DowncastError> {
+		if let Self::Union(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "union",
+			})
+		}
+	}
+
+	/// If the `Value` is an Union, return it. Returns Err otherwise.
+	pub fn into_union(self) -> Result<Union, DowncastError> {
+		if let Self::Union(ret) = self {
+			Ok(ret)
+		} else {
+			Err(DowncastError {
+				from: self.type_name(),
+				to: "union",
+			})
+		}
+	}
+
 	/// Returns true if the `Value` is an List. Returns false otherwise.
 	pub fn is_list(&self) -> bool {
 		matches!(self, Self::List(_))
@@ -1228,6 +1429,12 @@ impl Value {
 		}
 	}
 
+	/// If the `Value` is a List, return its item at `index`. Returns None if it
+	/// isn't a List or `index` is out of bounds.
+	pub fn get(&self, index: usize) -> Option<Cow<'_, Self>> {
+		self.as_list().ok()?.get(index)
+	}
+
 	/// Returns true if the `Value` is an Map. Returns false otherwise.
 	pub fn is_map(&self) -> bool {
 		matches!(self, Self::Map(_))
@@ -1257,6 +1464,12 @@ impl Value {
 		}
 	}
 
+	/// If the `Value` is a Map, return the value of `key`. Returns None if it
+	/// isn't a Map or doesn't contain `key`.
+	pub fn get_key(&self, key: &Self) -> Option<&Self> {
+		self.as_map().ok()?.get(key)
+	}
+
 	/// Returns true if the `Value` is an Group. Returns false otherwise.
 	pub fn is_group(&self) -> bool {
 		matches!(self, Self::Group(_))
@@ -1406,6 +1619,11 @@ impl From<Timezone> for Value {
 		Self::Timezone(value)
 	}
 }
+impl From<Duration> for Value {
+	fn from(value: Duration) -> Self {
+		Self::Duration(value)
+	}
+}
 impl From<Decimal> for Value {
 	fn from(value: Decimal) -> Self {
 		Self::Decimal(value)
@@ -1446,6 +1664,21 @@ impl From<IpAddr> for Value {
 		Self::IpAddr(value)
 	}
 }
+impl From<GeoPoint> for Value {
+	fn from(value: GeoPoint) -> Self {
+		Self::GeoPoint(value)
+	}
+}
+impl From<Uuid> for Value {
+	fn from(value: Uuid) -> Self {
+		Self::Uuid(value)
+	}
+}
+impl From<Union> for Value {
+	fn from(value: Union) -> Self {
+		Self::Union(value)
+	}
+}
 impl<T: Data> From<List<T>> for Value
 where
 	T: Into<Self>,
@@ -1619,6 +1852,11 @@ impl DowncastFrom<Value> for Timezone {
 		self_.into_timezone()
 	}
 }
+impl DowncastFrom<Value> for Duration {
+	fn downcast_from(self_: Value) -> Result<Self, DowncastError> {
+		self_.into_duration()
+	}
+}
 impl DowncastFrom<Value> for Decimal {
 	fn downcast_from(self_: Value) -> Result<Self, DowncastError> {
 		self_.into_decimal()
@@ -1659,6 +1897,21 @@ impl DowncastFrom<Value> for IpAddr {
 		self_.into_ip_addr()
 	}
 }
+impl DowncastFrom<Value> for GeoPoint {
+	fn downcast_from(self_: Value) -> Result<Self, DowncastError> {
+		self_.into_geo_point()
+	}
+}
+impl DowncastFrom<Value> for Uuid {
+	fn downcast_from(self_: Value) -> Result<Self, DowncastError> {
+		self_.into_uuid()
+	}
+}
+impl DowncastFrom<Value> for Union {
+	fn downcast_from(self_: Value) -> Result<Self, DowncastError> {
+		self_.into_union()
+	}
+}
 impl<T: Data> DowncastFrom<Value> for List<T>
 where
 	T: DowncastFrom<Value>,
@@ -1836,6 +2089,11 @@ impl PartialEq<Timezone> for Value {
 			.unwrap_or(false)
 	}
 }
+impl PartialEq<Duration> for Value {
+	fn eq(&self, other: &Duration) -> bool {
+		matches!(self, Self::Duration(duration) if duration == other)
+	}
+}
 impl PartialEq<Decimal> for Value {
 	fn eq(&self, other: &Decimal) -> bool {
 		self.as_decimal()
@@ -1884,6 +2142,21 @@ impl PartialEq<IpAddr> for Value {
 			.unwrap_or(false)
 	}
 }
+impl PartialEq<GeoPoint> for Value {
+	fn eq(&self, other: &GeoPoint) -> bool {
+		matches!(self, Self::GeoPoint(geo_point) if geo_point == other)
+	}
+}
+impl PartialEq<Uuid> for Value {
+	fn eq(&self, other: &Uuid) -> bool {
+		matches!(self, Self::Uuid(uuid) if uuid == other)
+	}
+}
+impl PartialEq<Union> for Value {
+	fn eq(&self, other: &Union) -> bool {
+		matches!(self, Self::Union(union) if union == other)
+	}
+}
 impl<T: Data> PartialEq<List<T>> for Value
 where
 	Value: PartialEq<T>,
@@ -1955,6 +2228,7 @@ where
 						&Value::DateTimeWithoutTimezone(*value) == b
 					}
 					ValueRequired::Timezone(value) => &Value::Timezone(*value) == b,
+					ValueRequired::Duration(value) => &Value::Duration(*value) == b,
 					ValueRequired::Decimal(value) => &Value::Decimal(value.clone()) == b,
 					ValueRequired::Bson(value) => &Value::Bson(value.clone()) == b,
 					ValueRequired::String(value) => &Value::String(value.clone()) == b,
@@ -1963,6 +2237,9 @@ where
 					ValueRequired::Url(value) => &Value::Url(value.clone()) == b,
 					ValueRequired::Webpage(value) => &Value::Webpage(value.clone()) == b,
 					ValueRequired::IpAddr(value) => &Value::IpAddr(*value) == b,
+					ValueRequired::GeoPoint(value) => &Value::GeoPoint(*value) == b,
+					ValueRequired::Uuid(value) => &Value::Uuid(*value) == b,
+					ValueRequired::Union(value) => &Value::Union(value.clone()) == b,
 					ValueRequired::List(value) => &Value::List(value.clone()) == b,
 					ValueRequired::Map(value) => &Value::Map(value.clone()) == b,
 					ValueRequired::Group(value) => &Value::Group(value.clone()) == b,
@@ -2170,6 +2447,15 @@ impl ListVec<Value> for ValueVec {
 		}
 	}
 	#[inline(always)]
+	fn get_a(&self, index: usize) -> Option<Cow<'_, Value>> {
+		match self {
+			ValueVec::U8(list) => list.get(index).map(|&t| Cow::Owned(Value::U8(t))),
+			ValueVec::U16(list) => list.get(index).map(|&t| Cow::Owned(Value::U16(t))),
+			ValueVec::List(list) => list.get(index).map(|t| Cow::Owned(Value::List(t.clone()))),
+			ValueVec::Value(list) => list.get(index).map(Cow::Borrowed),
+		}
+	}
+	#[inline(always)]
 	fn from_vec(vec: Vec<Value>) -> Self {
 		Self::Value(vec)
 	}
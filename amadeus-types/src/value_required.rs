@@ -6,7 +6,7 @@ use std::{
 };
 
 use super::{
-	AmadeusOrd, Bson, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Enum, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, Value, Webpage
+	AmadeusOrd, Bson, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Duration, Enum, GeoPoint, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Union, Url, Uuid, Value, Webpage
 };
 
 /// Represents any valid required Parquet value. Exists to avoid [`Value`] being recursive
@@ -52,6 +52,8 @@ pub enum ValueRequired {
 	DateTimeWithoutTimezone(DateTimeWithoutTimezone),
 	/// Timezone
 	Timezone(Timezone),
+	/// Duration of months, days and nanoseconds.
+	Duration(Duration),
 	/// Decimal value.
 	Decimal(Decimal),
 	/// BSON binary value.
@@ -68,6 +70,12 @@ pub enum ValueRequired {
 	Webpage(Webpage<'static>),
 	/// Ip Address
 	IpAddr(IpAddr),
+	/// Latitude and longitude
+	GeoPoint(GeoPoint),
+	/// UUID
+	Uuid(Uuid),
+	/// Variant of a union, by index.
+	Union(Union),
 
 	// Complex types
 	/// List of elements.
@@ -103,6 +111,7 @@ impl ValueRequired {
 					Value::DateTimeWithoutTimezone(ptr::read(value))
 				}
 				Self::Timezone(value) => Value::Timezone(ptr::read(value)),
+				Self::Duration(value) => Value::Duration(ptr::read(value)),
 				Self::Decimal(value) => Value::Decimal(ptr::read(value)),
 				Self::Bson(value) => Value::Bson(ptr::read(value)),
 				Self::String(value) => Value::String(ptr::read(value)),
@@ -111,6 +120,9 @@ impl ValueRequired {
 				Self::Url(value) => Value::Url(ptr::read(value)),
 				Self::Webpage(value) => Value::Webpage(ptr::read(value)),
 				Self::IpAddr(value) => Value::IpAddr(ptr::read(value)),
+				Self::GeoPoint(value) => Value::GeoPoint(ptr::read(value)),
+				Self::Uuid(value) => Value::Uuid(ptr::read(value)),
+				Self::Union(value) => Value::Union(ptr::read(value)),
 				Self::List(value) => Value::List(ptr::read(value)),
 				Self::Map(value) => Value::Map(ptr::read(value)),
 				Self::Group(value) => Value::Group(ptr::read(value)),
@@ -194,6 +206,10 @@ impl Hash for ValueRequired {
 				13_u8.hash(state);
 				value.hash(state);
 			}
+			Self::Duration(value) => {
+				29_u8.hash(state);
+				value.hash(state);
+			}
 			Self::Decimal(_value) => {
 				14_u8.hash(state);
 			}
@@ -225,6 +241,17 @@ impl Hash for ValueRequired {
 				21_u8.hash(state);
 				value.hash(state);
 			}
+			Self::GeoPoint(_value) => {
+				26_u8.hash(state);
+			}
+			Self::Uuid(value) => {
+				28_u8.hash(state);
+				value.hash(state);
+			}
+			Self::Union(value) => {
+				27_u8.hash(state);
+				value.hash(state);
+			}
 			Self::List(value) => {
 				22_u8.hash(state);
 				value.hash(state);
@@ -262,6 +289,7 @@ impl PartialOrd for ValueRequired {
 				a.partial_cmp(b)
 			}
 			(Self::Timezone(a), Self::Timezone(b)) => a.partial_cmp(b),
+			(Self::Duration(a), Self::Duration(b)) => a.checked_cmp(b),
 			(Self::Decimal(a), Self::Decimal(b)) => a.partial_cmp(b),
 			(Self::Bson(a), Self::Bson(b)) => a.partial_cmp(b),
 			(Self::String(a), Self::String(b)) => a.partial_cmp(b),
@@ -270,6 +298,9 @@ impl PartialOrd for ValueRequired {
 			(Self::Url(a), Self::Url(b)) => a.partial_cmp(b),
 			(Self::Webpage(a), Self::Webpage(b)) => a.partial_cmp(b),
 			(Self::IpAddr(a), Self::IpAddr(b)) => a.partial_cmp(b),
+			(Self::GeoPoint(a), Self::GeoPoint(b)) => a.partial_cmp(b),
+			(Self::Uuid(a), Self::Uuid(b)) => a.partial_cmp(b),
+			(Self::Union(a), Self::Union(b)) => a.partial_cmp(b),
 			(Self::List(a), Self::List(b)) => a.partial_cmp(b),
 			(Self::Map(_a), Self::Map(_b)) => None, // TODO?
 			(Self::Group(a), Self::Group(b)) => a.partial_cmp(b),
@@ -300,6 +331,7 @@ impl AmadeusOrd for ValueRequired {
 				a.amadeus_cmp(b)
 			}
 			(Self::Timezone(a), Self::Timezone(b)) => a.amadeus_cmp(b),
+			(Self::Duration(a), Self::Duration(b)) => a.amadeus_cmp(b),
 			(Self::Decimal(a), Self::Decimal(b)) => a.amadeus_cmp(b),
 			(Self::Bson(a), Self::Bson(b)) => a.amadeus_cmp(b),
 			(Self::String(a), Self::String(b)) => a.amadeus_cmp(b),
@@ -308,6 +340,9 @@ impl AmadeusOrd for ValueRequired {
 			(Self::Url(a), Self::Url(b)) => a.amadeus_cmp(b),
 			(Self::Webpage(a), Self::Webpage(b)) => a.amadeus_cmp(b),
 			(Self::IpAddr(a), Self::IpAddr(b)) => a.amadeus_cmp(b),
+			(Self::GeoPoint(a), Self::GeoPoint(b)) => a.amadeus_cmp(b),
+			(Self::Uuid(a), Self::Uuid(b)) => a.amadeus_cmp(b),
+			(Self::Union(a), Self::Union(b)) => a.amadeus_cmp(b),
 			(Self::List(a), Self::List(b)) => a.amadeus_cmp(b),
 			(Self::Map(a), Self::Map(b)) => a.amadeus_cmp(b),
 			(Self::Group(a), Self::Group(b)) => a.amadeus_cmp(b),
@@ -337,6 +372,7 @@ impl From<ValueRequired> for Value {
 			ValueRequired::DateTime(value) => Self::DateTime(value),
 			ValueRequired::DateTimeWithoutTimezone(value) => Self::DateTimeWithoutTimezone(value),
 			ValueRequired::Timezone(value) => Self::Timezone(value),
+			ValueRequired::Duration(value) => Self::Duration(value),
 			ValueRequired::Decimal(value) => Self::Decimal(value),
 			ValueRequired::Bson(value) => Self::Bson(value),
 			ValueRequired::String(value) => Self::String(value),
@@ -345,6 +381,9 @@ impl From<ValueRequired> for Value {
 			ValueRequired::Url(value) => Self::Url(value),
 			ValueRequired::Webpage(value) => Self::Webpage(value),
 			ValueRequired::IpAddr(value) => Self::IpAddr(value),
+			ValueRequired::GeoPoint(value) => Self::GeoPoint(value),
+			ValueRequired::Uuid(value) => Self::Uuid(value),
+			ValueRequired::Union(value) => Self::Union(value),
 			ValueRequired::List(value) => Self::List(value),
 			ValueRequired::Map(value) => Self::Map(value),
 			ValueRequired::Group(value) => Self::Group(value),
@@ -373,6 +412,7 @@ impl From<Value> for Option<ValueRequired> {
 			Value::DateTime(value) => ValueRequired::DateTime(value),
 			Value::DateTimeWithoutTimezone(value) => ValueRequired::DateTimeWithoutTimezone(value),
 			Value::Timezone(value) => ValueRequired::Timezone(value),
+			Value::Duration(value) => ValueRequired::Duration(value),
 			Value::Decimal(value) => ValueRequired::Decimal(value),
 			Value::Bson(value) => ValueRequired::Bson(value),
 			Value::String(value) => ValueRequired::String(value),
@@ -381,6 +421,9 @@ impl From<Value> for Option<ValueRequired> {
 			Value::Url(value) => ValueRequired::Url(value),
 			Value::Webpage(value) => ValueRequired::Webpage(value),
 			Value::IpAddr(value) => ValueRequired::IpAddr(value),
+			Value::GeoPoint(value) => ValueRequired::GeoPoint(value),
+			Value::Uuid(value) => ValueRequired::Uuid(value),
+			Value::Union(value) => ValueRequired::Union(value),
 			Value::List(value) => ValueRequired::List(value),
 			Value::Map(value) => ValueRequired::Map(value),
 			Value::Group(value) => ValueRequired::Group(value),
@@ -0,0 +1,106 @@
+#![cfg(nightly)]
+#![feature(test)]
+
+extern crate test;
+
+use once_cell::sync::Lazy;
+use std::{
+	io::{BufRead, BufReader, Write}, net::TcpListener, thread
+};
+use test::Bencher;
+use tokio::runtime::Runtime;
+
+use amadeus::{
+	amadeus_core::file::{Page, Partition}, source::aws::{AwsCredentials, AwsRegion, S3Partition}
+};
+
+static RT: Lazy<Runtime> = Lazy::new(|| {
+	tokio::runtime::Builder::new()
+		.threaded_scheduler()
+		.enable_all()
+		.build()
+		.unwrap()
+});
+
+/// Serve ranged GETs of zeros over HTTP/1.1, with keep-alive or closing the
+/// connection after each response, returning the endpoint.
+fn serve(keep_alive: bool) -> String {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let endpoint = format!("http://{}", listener.local_addr().unwrap());
+	let _ = thread::spawn(move || {
+		for stream in listener.incoming() {
+			let mut stream = stream.unwrap();
+			let _ = thread::spawn(move || {
+				let mut reader = BufReader::new(stream.try_clone().unwrap());
+				loop {
+					let mut len = 0;
+					loop {
+						let mut line = String::new();
+						if reader.read_line(&mut line).unwrap() == 0 {
+							return;
+						}
+						let line = line.to_ascii_lowercase();
+						if let Some(range) = line.strip_prefix("range: bytes=") {
+							let mut bounds = range.trim().splitn(2, '-');
+							let (start, end) = (bounds.next().unwrap(), bounds.next().unwrap());
+							len =
+								end.parse::<usize>().unwrap() - start.parse::<usize>().unwrap() + 1;
+						}
+						if line == "\r\n" {
+							break;
+						}
+					}
+					let connection = if keep_alive { "keep-alive" } else { "close" };
+					let mut response = format!(
+						"HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: {}\r\n\r\n",
+						len, connection
+					)
+					.into_bytes();
+					response.resize(response.len() + len, 0);
+					stream.write_all(&response).unwrap();
+					if !keep_alive {
+						return;
+					}
+				}
+			});
+		}
+	});
+	endpoint
+}
+
+#[bench]
+fn s3_small_reads_keep_alive(b: &mut Bencher) {
+	run(b, true)
+}
+
+#[bench]
+fn s3_small_reads_connection_close(b: &mut Bencher) {
+	run(b, false)
+}
+
+/// Ten small reads of each of 20 partitions, one after another.
+fn run(b: &mut Bencher, keep_alive: bool) {
+	let region = AwsRegion::Custom {
+		name: "local".to_owned(),
+		endpoint: serve(keep_alive),
+	};
+	let credentials = AwsCredentials::Anonymous;
+	b.bytes = 20 * 10 * 10;
+	b.iter(|| {
+		RT.handle().block_on(async {
+			for i in 0..20 {
+				let partition = S3Partition::new(
+					region.clone(),
+					"bucket".to_owned(),
+					format!("key{}", i),
+					1000,
+					credentials.clone(),
+				);
+				let page = partition.pages().await.unwrap().pop().unwrap();
+				for offset in (0..1000).step_by(100) {
+					assert_eq!(page.read(offset, 10).await.unwrap().len(), 10);
+				}
+			}
+		})
+	})
+}
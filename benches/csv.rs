@@ -10,7 +10,8 @@ use std::{fs, future::Future, path::PathBuf};
 use test::Bencher;
 use tokio::runtime::Runtime;
 
-use amadeus::prelude::*;
+use amadeus::{prelude::*, source::CsvRecord};
+use serde_closure::FnMut;
 
 static RT: Lazy<Runtime> = Lazy::new(|| {
 	tokio::runtime::Builder::new()
@@ -70,6 +71,37 @@ fn csv_typed_serde(b: &mut Bencher) {
 	});
 }
 
+#[derive(Deserialize)]
+struct GameBorrowed<'a> {
+	a: &'a str,
+	b: &'a str,
+	c: &'a str,
+	d: &'a str,
+	e: u32,
+	f: &'a str,
+}
+
+#[bench]
+fn csv_typed_borrowed(b: &mut Bencher) {
+	let file = "amadeus-testing/csv/game.csv"; // 2,600,000 bytes
+	run(b, file, || async {
+		let rows = Csv::<_, Value>::new(vec![PathBuf::from(file)])
+			.await
+			.unwrap()
+			.map_borrowed(FnMut!(|record: CsvRecord| {
+				let row: GameBorrowed = record.deserialize().unwrap();
+				row.a.len() + row.b.len() + row.c.len() + row.d.len() + row.f.len() + row.e as usize
+			}));
+		assert_eq!(
+			rows.par_stream()
+				.map(|row: Result<_, _>| row.unwrap())
+				.count(&*POOL)
+				.await,
+			100_000
+		);
+	})
+}
+
 #[bench]
 fn csv_untyped(b: &mut Bencher) {
 	let file = "amadeus-testing/csv/game.csv"; // 2,600,000 bytes
@@ -10,7 +10,7 @@ use std::{future::Future, mem};
 use test::Bencher;
 use tokio::runtime::Runtime;
 
-use amadeus::prelude::*;
+use amadeus::{par_stream::FromParallelStream, prelude::*};
 
 static RT: Lazy<Runtime> = Lazy::new(|| {
 	tokio::runtime::Builder::new()
@@ -60,6 +60,34 @@ fn rayon(b: &mut Bencher) {
 	});
 }
 
+#[bench]
+fn combine_flat(b: &mut Bencher) {
+	let rows: Vec<u32> = (0..1u32 << 24).collect();
+	let bytes = rows.len() as u64 * mem::size_of::<u32>() as u64;
+	run(b, bytes, || async {
+		let (reduce_a, reduce_c) = <Vec<u32> as FromParallelStream<u32>>::reducers();
+		let res = rows
+			.par_stream()
+			.reduce(&*POOL, reduce_a, reduce_c)
+			.await;
+		assert_eq!(res.len(), rows.len());
+	})
+}
+
+#[bench]
+fn combine_tree(b: &mut Bencher) {
+	let rows: Vec<u32> = (0..1u32 << 24).collect();
+	let bytes = rows.len() as u64 * mem::size_of::<u32>() as u64;
+	run(b, bytes, || async {
+		let (reduce_a, reduce_b) = <Vec<u32> as FromParallelStream<u32>>::reducers();
+		let res = rows
+			.par_stream()
+			.reduce_tree(&*POOL, reduce_a, reduce_b, 2)
+			.await;
+		assert_eq!(res.len(), rows.len());
+	})
+}
+
 fn run<F>(b: &mut Bencher, bytes: u64, mut task: impl FnMut() -> F)
 where
 	F: Future<Output = ()>,
@@ -19,8 +19,10 @@ use std::any::Any as SerdeData;
 
 pub use amadeus_derive::Data;
 pub use amadeus_types::{
-	AmadeusOrd, Bson, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Downcast, DowncastFrom, Enum, Group, IpAddr, Json, List, Time, TimeWithoutTimezone, Timezone, Url, Value, Webpage
+	coerce_schema, AmadeusOrd, Bson, CoerceError, CoercePolicy, CoerceSchema, Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Distance, DistributedStreamRows, Downcast, DowncastFrom, DropColumn, Duration, Enum, EvalError, Explode, ExplodePolicy, Expr, FilterBy, GeoPoint, Group, IpAddr, Json, List, logic, ParallelStreamRows, Predicate, Schema, Select, Time, TimeWithoutTimezone, Timezone, Union, Url, Uuid, Value, Webpage, WithColumn
 };
+#[cfg(feature = "language")]
+pub use amadeus_types::Lang;
 
 pub trait Data:
 	Clone
@@ -107,7 +109,7 @@ macro_rules! impl_data {
 		}
 	)*);
 }
-impl_data!(bool u8 i8 u16 i16 u32 i32 u64 i64 f32 f64 Decimal Group Date DateWithoutTimezone Time TimeWithoutTimezone DateTime DateTimeWithoutTimezone Timezone IpAddr);
+impl_data!(bool u8 i8 u16 i16 u32 i32 u64 i64 f32 f64 Decimal Group Date DateWithoutTimezone Time TimeWithoutTimezone DateTime DateTimeWithoutTimezone Timezone Duration IpAddr GeoPoint Uuid);
 
 macro_rules! impl_data {
 	($($t:ty)*) => ($(
@@ -157,6 +159,7 @@ impl Data for Value {
 			Self::DateTime(value) => value.heap(),
 			Self::DateTimeWithoutTimezone(value) => value.heap(),
 			Self::Timezone(value) => value.heap(),
+			Self::Duration(value) => value.heap(),
 			Self::Decimal(value) => value.heap(),
 			Self::Bson(value) => value.heap(),
 			Self::String(value) => value.heap(),
@@ -165,6 +168,9 @@ impl Data for Value {
 			Self::Url(value) => value.heap(),
 			Self::Webpage(value) => value.heap(),
 			Self::IpAddr(value) => value.heap(),
+			Self::GeoPoint(value) => value.heap(),
+			Self::Uuid(value) => value.heap(),
+			Self::Union(value) => value.value().size(),
 			Self::List(value) => value.heap(),
 			Self::Map(value) => value.heap(),
 			Self::Group(value) => value.heap(),
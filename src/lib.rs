@@ -45,7 +45,7 @@ pub mod data;
 pub mod pool;
 pub mod source;
 
-pub use amadeus_core::{into_par_stream, par_pipe, par_sink, par_stream};
+pub use amadeus_core::{into_par_stream, par_pipe, par_sink, par_stream, plan};
 
 #[doc(inline)]
 pub use crate::{
@@ -62,10 +62,13 @@ pub mod dist {
 		pub use crate::source::aws::{
 			AwsCredentials, AwsError, AwsRegion, CloudfrontRow, S3Directory, S3File
 		};
+		#[cfg(feature = "amadeus-serde")]
+		#[doc(no_inline)]
+		pub use crate::source::write::DistributedStreamWrite;
 		#[doc(no_inline)]
 		pub use crate::{
 			data::{
-				Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Downcast, DowncastFrom, Enum, Group, Time, TimeWithoutTimezone, Timezone
+				Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, DistributedStreamRows, Downcast, DowncastFrom, Enum, Expr, Group, Predicate, Time, TimeWithoutTimezone, Timezone
 			}, par_pipe::DistributedPipe, par_stream::Identity, pool::ThreadPool, source::*, Data, DistributedStream, FromDistributedStream, IntoDistributedStream, IteratorExt, List, Value
 		};
 		#[doc(no_inline)]
@@ -81,10 +84,13 @@ pub mod prelude {
 	pub use crate::source::aws::{
 		AwsCredentials, AwsError, AwsRegion, CloudfrontRow, S3Directory, S3File
 	};
+	#[cfg(feature = "amadeus-serde")]
+	#[doc(no_inline)]
+	pub use crate::source::write::ParallelStreamWrite;
 	#[doc(no_inline)]
 	pub use crate::{
 		data::{
-			Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Downcast, DowncastFrom, Enum, Group, Time, TimeWithoutTimezone, Timezone
+			Date, DateTime, DateTimeWithoutTimezone, DateWithoutTimezone, Decimal, Downcast, DowncastFrom, Enum, Expr, Group, ParallelStreamRows, Predicate, Time, TimeWithoutTimezone, Timezone
 		}, par_pipe::ParallelPipe, par_stream::Identity, pool::ThreadPool, source::*, Data, FromParallelStream, IntoParallelStream, IteratorExt, List, ParallelStream, Value
 	};
 }
@@ -103,8 +109,14 @@ pub use amadeus_parquet;
 #[cfg(feature = "postgres")]
 #[doc(hidden)]
 pub use amadeus_postgres;
+#[cfg(feature = "redis")]
+#[doc(hidden)]
+pub use amadeus_redis;
 #[cfg(feature = "amadeus-serde")]
 #[doc(hidden)]
 pub use amadeus_serde;
+#[cfg(feature = "sqlite")]
+#[doc(hidden)]
+pub use amadeus_sqlite;
 #[doc(hidden)]
 pub use amadeus_types;
@@ -7,8 +7,9 @@ use futures::future::{BoxFuture, TryFutureExt};
 use serde_closure::traits;
 use std::{error::Error, future::Future};
 
+pub use amadeus_core::pool::Scheduling;
 #[cfg(feature = "constellation")]
-pub use process::ProcessPool;
+pub use process::{Bincode, BincodeVarint, Codec, ProcessPool};
 pub use thread::ThreadPool;
 
 use amadeus_core::pool::{
@@ -19,12 +20,15 @@ type Result<T> = std::result::Result<T, Box<dyn Error + Send>>;
 
 #[cfg(feature = "constellation")]
 #[cfg_attr(not(nightly), serde_closure::desugar)]
-impl ProcessPoolTrait for ProcessPool {
+impl<C: Codec> ProcessPoolTrait for ProcessPool<C> {
 	type ThreadPool = ThreadPool;
 
 	fn processes(&self) -> usize {
 		ProcessPool::processes(self)
 	}
+	fn scheduling(&self) -> Scheduling {
+		ProcessPool::scheduling(self)
+	}
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: traits::FnOnce(&Self::ThreadPool) -> Fut + ProcessSend + 'static,
@@ -51,6 +55,12 @@ impl ProcessPoolTrait for ThreadPool {
 	fn processes(&self) -> usize {
 		1
 	}
+	fn is_deterministic(&self) -> bool {
+		ThreadPool::is_deterministic(self)
+	}
+	fn scheduling(&self) -> Scheduling {
+		ThreadPool::scheduling(self)
+	}
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: traits::FnOnce(&Self::ThreadPool) -> Fut + ProcessSend + 'static,
@@ -78,6 +88,12 @@ impl ThreadPoolTrait for ThreadPool {
 	fn threads(&self) -> usize {
 		ThreadPool::threads(self)
 	}
+	fn is_deterministic(&self) -> bool {
+		ThreadPool::is_deterministic(self)
+	}
+	fn scheduling(&self) -> Scheduling {
+		ThreadPool::scheduling(self)
+	}
 	fn spawn<F, Fut, T>(&self, work: F) -> BoxFuture<'static, Result<T>>
 	where
 		F: FnOnce() -> Fut + Send + 'static,
@@ -1,20 +1,24 @@
+use bincode::Options;
 use constellation::{spawn, Receiver, Resources, Sender, SpawnError};
 use futures::{future::LocalBoxFuture, FutureExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_bytes::ByteBuf;
 use serde_closure::{traits, FnOnce};
 use serde_traitobject as st;
 use std::{
-	any, collections::VecDeque, fmt, future::Future, mem, panic::{self, RefUnwindSafe, UnwindSafe}, sync::{Arc, Mutex}
+	collections::VecDeque, error::Error, fmt, future::Future, mem, panic::{self, RefUnwindSafe, UnwindSafe}, sync::{Arc, Mutex}
 };
 
 use amadeus_core::pool::ProcessSend;
 
 use super::{
-	util::{assert_sync_and_send, OnDrop, Panicked, RoundRobin, Synchronize}, ThreadPool
+	util::{assert_sync_and_send, OnDrop, Panicked, RoundRobin, Synchronize}, Scheduling, ThreadPool
 };
 
+/// Work, which resolves to its encoded result.
 #[cfg_attr(not(nightly), serde_closure::desugar)]
 type Request = st::Box<dyn st::sc::FnOnce(&ThreadPool) -> LocalBoxFuture<'static, Response> + Send>;
-type Response = Box<dyn st::Any + Send>;
+type Response = Result<ByteBuf, Panicked>;
 
 mod future_ext {
 	use futures::{future::Future, pin_mut};
@@ -59,17 +63,66 @@ mod future_ext {
 }
 use future_ext::FutureExt1;
 
+/// How a [`ProcessPool`] serializes the work it sends to its processes, along
+/// with everything the work captures, and the results it gets back.
+///
+/// Each is encoded once, and the bytes are sent to and from the processes as
+/// they are.
+pub trait Codec: Copy + Send + Sync + Serialize + DeserializeOwned + fmt::Debug + 'static {
+	fn encode<T: Serialize>(self, t: &T) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+	fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, Box<dyn Error + Send + Sync>>;
+}
+
+/// [`bincode`] with fixed-width integers, the fastest to encode and decode.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Bincode;
+impl Codec for Bincode {
+	fn encode<T: Serialize>(self, t: &T) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+		Ok(bincode::serialize(t)?)
+	}
+	fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, Box<dyn Error + Send + Sync>> {
+		Ok(bincode::deserialize(bytes)?)
+	}
+}
+
+/// [`bincode`] with variable-length integers, which is more compact for
+/// payloads of many small integers, at some cost in speed.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct BincodeVarint;
+impl Codec for BincodeVarint {
+	fn encode<T: Serialize>(self, t: &T) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+		Ok(bincode::DefaultOptions::new().serialize(t)?)
+	}
+	fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, Box<dyn Error + Send + Sync>> {
+		Ok(bincode::DefaultOptions::new().deserialize(bytes)?)
+	}
+}
+
+fn encode<C: Codec, T: Serialize>(codec: C, t: &T) -> Result<ByteBuf, Panicked> {
+	codec
+		.encode(t)
+		.map(ByteBuf::from)
+		.map_err(|err| Panicked::new(format!("couldn't encode with {:?}: {}", codec, err)))
+}
+fn decode<C: Codec, T: DeserializeOwned>(codec: C, bytes: &[u8]) -> Result<T, Panicked> {
+	codec
+		.decode(bytes)
+		.map_err(|err| Panicked::new(format!("couldn't decode with {:?}: {}", codec, err)))
+}
+
 #[derive(Debug)]
 struct Process {
-	sender: Sender<Option<Request>>,
-	receiver: Receiver<Result<Response, Panicked>>,
+	sender: Sender<Option<ByteBuf>>,
+	receiver: Receiver<Response>,
 	inner: Mutex<ProcessInner>,
 	synchronize: Synchronize,
 }
 struct ProcessInner {
-	queue: VecDeque<Queued<Result<Response, Panicked>>>,
+	queue: VecDeque<Queued<Response>>,
 	received: usize,
 	tail: usize,
+	/// The process died, so nothing more will be received from it.
+	dead: bool,
 }
 impl fmt::Debug for ProcessInner {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -77,6 +130,7 @@ impl fmt::Debug for ProcessInner {
 			.field("queue", &())
 			.field("received", &self.received)
 			.field("tail", &self.tail)
+			.field("dead", &self.dead)
 			.finish()
 	}
 }
@@ -105,16 +159,22 @@ impl<T> Queued<T> {
 	}
 }
 
+/// The number of processes a piece of work is sent to before giving up, if
+/// each dies before responding.
+const MAX_ATTEMPTS: usize = 3;
+
 #[derive(Debug)]
-struct ProcessPoolInner {
+struct ProcessPoolInner<C> {
 	processes: Vec<Process>,
 	i: RoundRobin,
+	codec: C,
 }
-impl ProcessPoolInner {
+#[cfg_attr(not(nightly), serde_closure::desugar)]
+impl<C: Codec> ProcessPoolInner<C> {
 	#[allow(clippy::double_parens)] // TODO: work out what's triggering this
 	fn new(
 		processes: Option<usize>, threads: Option<usize>, tasks: Option<usize>,
-		resources: Resources,
+		resources: Resources, codec: C,
 	) -> Result<Self, SpawnError> {
 		let processes = processes.unwrap_or(3); // TODO!
 		let mut processes_vec = Vec::with_capacity(processes);
@@ -128,20 +188,28 @@ impl ProcessPoolInner {
 						.build()
 						.unwrap()
 						.block_on(async {
-							let receiver = Receiver::<Option<Request>>::new(parent);
-							let sender = Sender::<Result<Response, Panicked>>::new(parent);
+							let receiver = Receiver::<Option<ByteBuf>>::new(parent);
+							let sender = Sender::<Response>::new(parent);
 
 							let thread_pool = ThreadPool::new(threads, tasks).unwrap();
 
 							while let Some(work) = receiver.recv().await.unwrap() {
-								let ret = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-									work.into_box().call_once_box((&thread_pool,))
-								}));
-								let ret = match ret {
-									Ok(t) => panic::AssertUnwindSafe(t).catch_unwind().await,
-									Err(e) => Err(e),
-								}
-								.map_err(Panicked::from);
+								let ret = match decode::<_, Request>(codec, &work) {
+									Ok(work) => {
+										let ret =
+											panic::catch_unwind(panic::AssertUnwindSafe(|| {
+												work.into_box().call_once_box((&thread_pool,))
+											}));
+										match ret {
+											Ok(t) => {
+												panic::AssertUnwindSafe(t).catch_unwind().await
+											}
+											Err(e) => Err(e),
+										}
+										.unwrap_or_else(|e| Err(Panicked::from(e)))
+									}
+									Err(err) => Err(err),
+								};
 								sender.send(ret).await;
 							}
 						})
@@ -160,7 +228,7 @@ impl ProcessPoolInner {
 			let sender = Sender::new(child);
 			let receiver = Receiver::new(child);
 
-			let (queue, received, tail) = (VecDeque::new(), 0, 0);
+			let (queue, received, tail, dead) = (VecDeque::new(), 0, 0, false);
 
 			processes_vec.push(Process {
 				sender,
@@ -169,6 +237,7 @@ impl ProcessPoolInner {
 					queue,
 					received,
 					tail,
+					dead,
 				}),
 				synchronize: Synchronize::new(),
 			})
@@ -177,28 +246,46 @@ impl ProcessPoolInner {
 		Ok(Self {
 			processes: processes_vec,
 			i,
+			codec,
 		})
 	}
 	fn processes(&self) -> usize {
 		self.processes.len()
 	}
-	async fn spawn<F, Fut, T>(&self, work: F) -> Result<T, Panicked>
-	where
-		F: for<'a> traits::FnOnce<(&'a ThreadPool,), Output = Fut> + ProcessSend + 'static,
-		Fut: Future<Output = T> + 'static,
-		T: ProcessSend + 'static,
-	{
-		let process_index = self.i.get();
-		let process = &self.processes[process_index];
-		let x = process
-			.sender
-			.send(Some(st::Box::new(FnOnce!(move |thread_pool: &_| {
-				let work: F = work;
-				work.call_once((thread_pool,))
-					.map(|res| Box::new(res) as Response)
-					.boxed_local()
-			})) as Request));
-		x.await;
+	/// The next process in the rotation that hasn't died.
+	fn alive(&self) -> Option<&Process> {
+		(0..self.processes.len())
+			.map(|_| &self.processes[self.i.get()])
+			.find(|process| !process.inner.lock().unwrap().dead)
+	}
+	/// Send the encoded `request` to a process and await its response. If the
+	/// process dies before responding the request is sent to another, so work
+	/// may run more than once. Work that has killed [`MAX_ATTEMPTS`] processes,
+	/// or that's left with no process alive to run on, fails with [`Panicked`].
+	async fn request(&self, request: ByteBuf) -> Response {
+		for _ in 0..MAX_ATTEMPTS {
+			let process = self
+				.alive()
+				.ok_or_else(|| Panicked::new("all processes in the pool have died"))?;
+			if let Some(response) = Self::request_to(process, request.clone()).await {
+				return response;
+			}
+		}
+		Err(Panicked::new(format!(
+			"the process running this work died {} times",
+			MAX_ATTEMPTS
+		)))
+	}
+	/// Returns `None` if the process died before responding.
+	async fn request_to(process: &Process, request: ByteBuf) -> Option<Response> {
+		// Sending to a process that's known to have died panics
+		let sent = panic::AssertUnwindSafe(process.sender.send(Some(request)))
+			.catch_unwind()
+			.await;
+		if sent.is_err() {
+			process.inner.lock().unwrap().dead = true;
+			return None;
+		}
 		let index;
 		{
 			// https://github.com/rust-lang/rust/issues/57478
@@ -217,19 +304,34 @@ impl ProcessPoolInner {
 			}
 			drop(process_inner_lock);
 		});
-		while process.inner.lock().unwrap().received <= index {
+		loop {
+			{
+				let process_inner_lock = process.inner.lock().unwrap();
+				if process_inner_lock.received > index {
+					break;
+				}
+				if process_inner_lock.dead {
+					return None;
+				}
+			}
 			process
 				.synchronize
 				.synchronize(async {
-					if process.inner.lock().unwrap().received > index {
-						return;
+					{
+						let process_inner_lock = process.inner.lock().unwrap();
+						if process_inner_lock.received > index || process_inner_lock.dead {
+							return;
+						}
 					}
 					let z = process.receiver.recv().await;
-					let t = z.unwrap();
 					let mut process_inner_lock = process.inner.lock().unwrap();
-					let offset = process_inner_lock.received - process_inner_lock.tail;
-					process_inner_lock.queue[offset].received(t);
-					process_inner_lock.received += 1;
+					if let Ok(t) = z {
+						let offset = process_inner_lock.received - process_inner_lock.tail;
+						process_inner_lock.queue[offset].received(t);
+						process_inner_lock.received += 1;
+					} else {
+						process_inner_lock.dead = true;
+					}
 					drop(process_inner_lock);
 				})
 				.await;
@@ -243,7 +345,23 @@ impl ProcessPoolInner {
 			process_inner_lock.tail += 1;
 		}
 		drop(process_inner_lock);
-		boxed.map(|boxed| *Box::<dyn any::Any>::downcast::<T>(boxed.into_any_send()).unwrap())
+		Some(boxed)
+	}
+	async fn spawn<F, Fut, T>(&self, work: F) -> Result<T, Panicked>
+	where
+		F: for<'a> traits::FnOnce<(&'a ThreadPool,), Output = Fut> + ProcessSend + 'static,
+		Fut: Future<Output = T> + 'static,
+		T: ProcessSend + 'static,
+	{
+		let codec = self.codec;
+		let request = st::Box::new(FnOnce!(move |thread_pool: &_| {
+			let work: F = work;
+			work.call_once((thread_pool,))
+				.map(move |response| encode(codec, &response))
+				.boxed_local()
+		})) as Request;
+		let response = self.request(encode(codec, &request)?).await?;
+		decode(codec, &response)
 	}
 	#[allow(unsafe_code)]
 	async unsafe fn spawn_unchecked<'a, F, Fut, T>(&self, work: F) -> Result<T, Panicked>
@@ -252,78 +370,27 @@ impl ProcessPoolInner {
 		Fut: Future<Output = T> + 'a,
 		T: ProcessSend + 'a,
 	{
-		let process_index = self.i.get();
-		let process = &self.processes[process_index];
+		let codec = self.codec;
 		let request = st::Box::new(FnOnce!(move |thread_pool: &_| {
 			let work: F = work;
 			work.call_once((thread_pool,))
-				.map(|response| {
-					let response = bincode::serialize(&response).unwrap();
-					Box::new(response) as Response
-				})
+				.map(move |response| encode(codec, &response))
 				.boxed_local()
 		}));
 		let request = mem::transmute::<
 			st::Box<dyn st::sc::FnOnce(&ThreadPool) -> LocalBoxFuture<'a, Response> + Send>,
 			st::Box<dyn st::sc::FnOnce(&ThreadPool) -> LocalBoxFuture<'static, Response> + Send>,
 		>(request);
-		let x = process.sender.send(Some(request));
-		x.await;
-		let index;
-		{
-			// https://github.com/rust-lang/rust/issues/57478
-			let mut process_inner_lock = process.inner.lock().unwrap();
-			process_inner_lock.queue.push_back(Queued::Awaiting);
-			index = process_inner_lock.tail + process_inner_lock.queue.len() - 1;
-			drop(process_inner_lock);
-		}
-		let on_drop = OnDrop::new(|| {
-			let mut process_inner_lock = process.inner.lock().unwrap();
-			let offset = index - process_inner_lock.tail;
-			process_inner_lock.queue[offset].drop_();
-			while let Some(Queued::Taken) = process_inner_lock.queue.front() {
-				let _ = process_inner_lock.queue.pop_front().unwrap();
-				process_inner_lock.tail += 1;
-			}
-			drop(process_inner_lock);
-		});
-		while process.inner.lock().unwrap().received <= index {
-			process
-				.synchronize
-				.synchronize(async {
-					if process.inner.lock().unwrap().received > index {
-						return;
-					}
-					let z = process.receiver.recv().await;
-					let t = z.unwrap();
-					let mut process_inner_lock = process.inner.lock().unwrap();
-					let offset = process_inner_lock.received - process_inner_lock.tail;
-					process_inner_lock.queue[offset].received(t);
-					process_inner_lock.received += 1;
-					drop(process_inner_lock);
-				})
-				.await;
-		}
-		on_drop.cancel();
-		let mut process_inner_lock = process.inner.lock().unwrap();
-		let offset = index - process_inner_lock.tail;
-		let boxed = process_inner_lock.queue[offset].take();
-		while let Some(Queued::Taken) = process_inner_lock.queue.front() {
-			let _ = process_inner_lock.queue.pop_front().unwrap();
-			process_inner_lock.tail += 1;
-		}
-		drop(process_inner_lock);
-		boxed.map(|boxed| {
-			bincode::deserialize(
-				&Box::<dyn any::Any>::downcast::<Vec<u8>>(boxed.into_any_send()).unwrap(),
-			)
-			.unwrap()
-		})
+		let response = self.request(encode(codec, &request)?).await?;
+		decode(codec, &response)
 	}
 }
-impl Drop for ProcessPoolInner {
+impl<C> Drop for ProcessPoolInner<C> {
 	fn drop(&mut self) {
-		for Process { sender, .. } in &self.processes {
+		for Process { sender, inner, .. } in &self.processes {
+			if inner.lock().unwrap().dead {
+				continue;
+			}
 			// TODO: select, incl recv
 			sender.send(None).block();
 		}
@@ -331,20 +398,45 @@ impl Drop for ProcessPoolInner {
 }
 
 #[derive(Debug)]
-pub struct ProcessPool(Arc<ProcessPoolInner>);
-#[cfg_attr(not(nightly), serde_closure::desugar)]
+pub struct ProcessPool<C = Bincode>(Arc<ProcessPoolInner<C>>, Scheduling);
 impl ProcessPool {
 	pub fn new(
 		processes: Option<usize>, threads: Option<usize>, tasks: Option<usize>,
 		resources: Resources,
 	) -> Result<Self, SpawnError> {
-		Ok(Self(Arc::new(ProcessPoolInner::new(
-			processes, threads, tasks, resources,
-		)?)))
+		Self::with_codec(processes, threads, tasks, resources, Bincode)
+	}
+}
+#[cfg_attr(not(nightly), serde_closure::desugar)]
+impl<C: Codec> ProcessPool<C> {
+	/// Like [`new`](ProcessPool::new), serializing work and results with
+	/// `codec` rather than [`Bincode`].
+	pub fn with_codec(
+		processes: Option<usize>, threads: Option<usize>, tasks: Option<usize>,
+		resources: Resources, codec: C,
+	) -> Result<Self, SpawnError> {
+		Ok(Self(
+			Arc::new(ProcessPoolInner::new(
+				processes, threads, tasks, resources, codec,
+			)?),
+			Scheduling::InOrder,
+		))
 	}
 	pub fn processes(&self) -> usize {
 		self.0.processes()
 	}
+	pub fn codec(&self) -> C {
+		self.0.codec
+	}
+	/// A handle to this pool that allocates the partitions of each stream among
+	/// its processes, and the tasks of each process, according to `scheduling`.
+	#[must_use]
+	pub fn schedule(self, scheduling: Scheduling) -> Self {
+		Self(self.0, scheduling)
+	}
+	pub fn scheduling(&self) -> Scheduling {
+		self.1
+	}
 	pub fn spawn<F, Fut, T>(&self, work: F) -> impl Future<Output = Result<T, Panicked>> + Send
 	where
 		F: traits::FnOnce(&ThreadPool) -> Fut + ProcessSend + 'static,
@@ -368,18 +460,18 @@ impl ProcessPool {
 	}
 }
 
-impl Clone for ProcessPool {
+impl<C> Clone for ProcessPool<C> {
 	/// Cloning a pool will create a new handle to the pool.
 	/// The behavior is similar to [Arc](https://doc.rust-lang.org/stable/std/sync/struct.Arc.html).
 	///
 	/// We could for example submit jobs from multiple threads concurrently.
 	fn clone(&self) -> Self {
-		Self(self.0.clone())
+		Self(self.0.clone(), self.1)
 	}
 }
 
-impl UnwindSafe for ProcessPool {}
-impl RefUnwindSafe for ProcessPool {}
+impl<C> UnwindSafe for ProcessPool<C> {}
+impl<C> RefUnwindSafe for ProcessPool<C> {}
 
 fn _assert() {
 	let _ = assert_sync_and_send::<ProcessPool>;
@@ -5,13 +5,15 @@ use std::{
 };
 
 #[cfg(not(target_arch = "wasm32"))]
-use tokio::task::JoinError;
+use tokio::{runtime::Handle, task::JoinError};
 #[cfg(target_arch = "wasm32")]
 use {
 	futures::{future, FutureExt}, std::panic::AssertUnwindSafe
 };
 
-use super::util::{assert_sync_and_send, Panicked};
+use super::{
+	util::{assert_sync_and_send, Panicked}, Scheduling
+};
 
 const DEFAULT_TASKS_PER_CORE: usize = 100;
 
@@ -24,9 +26,38 @@ struct ThreadPoolInner {
 }
 
 #[derive(Debug)]
-pub struct ThreadPool(Arc<ThreadPoolInner>);
+pub struct ThreadPool {
+	inner: Arc<ThreadPoolInner>,
+	deterministic: bool,
+	scheduling: Scheduling,
+}
 impl ThreadPool {
+	/// Create a pool running on the current Tokio runtime.
+	///
+	/// # Panics
+	///
+	/// Panics if called outside of a Tokio runtime; see
+	/// [`with_handle`](Self::with_handle) to provide one explicitly.
 	pub fn new(threads: Option<usize>, tasks: Option<usize>) -> io::Result<Self> {
+		#[cfg(not(target_arch = "wasm32"))]
+		return Self::with_handle(threads, tasks, &Handle::current());
+		#[cfg(target_arch = "wasm32")]
+		Ok(Self::new_(threads, tasks))
+	}
+	/// Create a pool running on the Tokio runtime `handle`, such as that of an
+	/// application embedding Amadeus, rather than the current one. The pool's
+	/// threads are spawned onto it, and tasks run on them, so pipelines can be
+	/// awaited from any runtime or none.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn with_handle(
+		threads: Option<usize>, tasks: Option<usize>, handle: &Handle,
+	) -> io::Result<Self> {
+		Ok(Self::new_(threads, tasks, handle))
+	}
+	fn new_(
+		threads: Option<usize>, tasks: Option<usize>,
+		#[cfg(not(target_arch = "wasm32"))] handle: &Handle,
+	) -> Self {
 		let threads = if let Some(threads) = threads {
 			threads
 		} else if !cfg!(target_arch = "wasm32") {
@@ -37,16 +68,47 @@ impl ThreadPool {
 
 		let tasks = tasks.unwrap_or(DEFAULT_TASKS_PER_CORE);
 		#[cfg(not(target_arch = "wasm32"))]
-		let pool = Pool::new(threads);
-		Ok(ThreadPool(Arc::new(ThreadPoolInner {
-			threads,
-			tasks,
-			#[cfg(not(target_arch = "wasm32"))]
-			pool,
-		})))
+		let pool = Pool::new(threads, handle);
+		ThreadPool {
+			inner: Arc::new(ThreadPoolInner {
+				threads,
+				tasks,
+				#[cfg(not(target_arch = "wasm32"))]
+				pool,
+			}),
+			deterministic: false,
+			scheduling: Scheduling::InOrder,
+		}
+	}
+	/// A handle to this pool that runs pipelines reproducibly, for tests
+	/// comparing output against a golden file. The partial results of
+	/// partitions are combined in the order of the partitions rather than as
+	/// they complete, so as partitions are allocated to tasks deterministically
+	/// and items within a partition are processed in order, order-preserving
+	/// sinks such as `collect` into a `Vec` give the same output each run,
+	/// whatever async stages complete out of order.
+	#[must_use]
+	pub fn deterministic(self) -> Self {
+		Self {
+			deterministic: true,
+			..self
+		}
+	}
+	pub fn is_deterministic(&self) -> bool {
+		self.deterministic
+	}
+	/// A handle to this pool that allocates the partitions of each stream among
+	/// its tasks according to `scheduling`, such as
+	/// [`LargestFirst`](Scheduling::LargestFirst) for partitions of skewed cost.
+	#[must_use]
+	pub fn schedule(self, scheduling: Scheduling) -> Self {
+		Self { scheduling, ..self }
+	}
+	pub fn scheduling(&self) -> Scheduling {
+		self.scheduling
 	}
 	pub fn threads(&self) -> usize {
-		self.0.threads * self.0.tasks
+		self.inner.threads * self.inner.tasks
 	}
 	pub fn spawn<F, Fut, T>(&self, task: F) -> impl Future<Output = Result<T, Panicked>> + Send
 	where
@@ -56,7 +118,7 @@ impl ThreadPool {
 	{
 		#[cfg(not(target_arch = "wasm32"))]
 		return self
-			.0
+			.inner
 			.pool
 			.spawn_pinned(task)
 			.map_err(JoinError::into_panic)
@@ -83,7 +145,7 @@ impl ThreadPool {
 	{
 		#[cfg(not(target_arch = "wasm32"))]
 		return Guard::new(
-			self.0
+			self.inner
 				.pool
 				.spawn_pinned_unchecked(task)
 				.map_err(JoinError::into_panic)
@@ -121,7 +183,11 @@ impl Clone for ThreadPool {
 	///
 	/// We could for example submit jobs from multiple threads concurrently.
 	fn clone(&self) -> Self {
-		Self(self.0.clone())
+		Self {
+			inner: self.inner.clone(),
+			deterministic: self.deterministic,
+			scheduling: self.scheduling,
+		}
 	}
 }
 
@@ -183,8 +249,7 @@ mod pool {
 		sender: Sender<(Request, Sender<RemoteHandle<Response>>)>,
 	}
 	impl Pool {
-		pub(super) fn new(threads: usize) -> Self {
-			let handle = Handle::current();
+		pub(super) fn new(threads: usize, handle: &Handle) -> Self {
 			let handle1 = handle.clone();
 			let (sender, receiver) = bounded::<(Request, Sender<RemoteHandle<Response>>)>(1);
 			for _ in 0..threads {
@@ -283,7 +348,7 @@ mod pool {
 			const TASKS: usize = 1000;
 			const ITERS: usize = 200;
 			const THREADS: usize = 4;
-			let pool = Pool::new(THREADS);
+			let pool = Pool::new(THREADS, &Handle::current());
 			let count = Arc::new(AtomicUsize::new((1..TASKS).sum()));
 			for _ in 0..ITERS {
 				join_all((0..TASKS).map(|i| {
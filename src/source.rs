@@ -5,6 +5,7 @@ use pin_project::pin_project;
 use std::{
 	error::Error, fmt::Debug, marker::PhantomData, pin::Pin, task::{Context, Poll}
 };
+use sum::Sum2;
 
 use crate::{
 	par_sink::{DistributedSink, ParallelSink}, par_stream::{DistributedStream, ParallelStream, StreamTask}
@@ -13,24 +14,41 @@ use crate::{
 #[cfg(feature = "aws")]
 #[doc(inline)]
 pub use amadeus_aws::Cloudfront;
+#[doc(inline)]
+pub use amadeus_core::{Union, UnionStream};
 #[cfg(feature = "aws")]
 pub mod aws {
 	pub use crate::data::CloudfrontRow;
 	#[doc(inline)]
-	pub use amadeus_aws::{AwsCredentials, AwsError, AwsRegion, S3Directory, S3File};
+	pub use amadeus_aws::{
+		AwsCredentials, AwsError, AwsRegion, S3Copy, S3Directory, S3Encryption, S3File, S3Object, S3Partition
+	};
 }
 #[cfg(feature = "commoncrawl")]
 #[doc(inline)]
 pub use amadeus_commoncrawl::CommonCrawl;
 #[cfg(feature = "parquet")]
 #[doc(inline)]
-pub use amadeus_parquet::{Parquet, ParquetDirectory};
+pub use amadeus_parquet::{Parquet, ParquetDirectory, ParquetMetadata};
 #[cfg(feature = "postgres")]
 #[doc(inline)]
 pub use amadeus_postgres::{Postgres, PostgresSelect, PostgresTable};
+#[cfg(feature = "redis")]
+#[doc(inline)]
+pub use amadeus_redis::{Redis, RedisConfig, RedisSet};
 #[cfg(feature = "amadeus-serde")]
 #[doc(inline)]
-pub use amadeus_serde::{Csv, Json};
+pub use amadeus_serde::{
+	Csv, CsvLines, CsvMapBorrowed, CsvRecord, CsvRecordFn, Json, JsonArrays, JsonFlatten, JsonLines, JsonMapBorrowed, JsonRecord, JsonRecordFn, NullTokens
+};
+#[cfg(feature = "amadeus-serde")]
+pub mod write {
+	#[doc(inline)]
+	pub use amadeus_serde::{DistributedStreamWrite, ParallelStreamWrite};
+}
+#[cfg(feature = "sqlite")]
+#[doc(inline)]
+pub use amadeus_sqlite::{Sqlite, SqliteInsert};
 
 pub trait Source: Clone + Debug {
 	type Item: crate::data::Data;
@@ -92,6 +110,46 @@ where
 		<Self as amadeus_core::Source>::dist_stream(self)
 	}
 }
+#[cfg(feature = "amadeus-serde")]
+impl<File, G> Source for CsvMapBorrowed<File, G>
+where
+	File: amadeus_core::file::File,
+	G: CsvRecordFn + Clone + amadeus_core::pool::ProcessSend + 'static,
+	G::Output: super::data::Data,
+{
+	type Item = <Self as amadeus_core::Source>::Item;
+	type Error = <Self as amadeus_core::Source>::Error;
+
+	type ParStream = <Self as amadeus_core::Source>::ParStream;
+	type DistStream = <Self as amadeus_core::Source>::DistStream;
+
+	fn par_stream(self) -> Self::ParStream {
+		<Self as amadeus_core::Source>::par_stream(self)
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		<Self as amadeus_core::Source>::dist_stream(self)
+	}
+}
+#[cfg(feature = "amadeus-serde")]
+impl<File, G> Source for JsonMapBorrowed<File, G>
+where
+	File: amadeus_core::file::File,
+	G: JsonRecordFn + Clone + amadeus_core::pool::ProcessSend + 'static,
+	G::Output: super::data::Data,
+{
+	type Item = <Self as amadeus_core::Source>::Item;
+	type Error = <Self as amadeus_core::Source>::Error;
+
+	type ParStream = <Self as amadeus_core::Source>::ParStream;
+	type DistStream = <Self as amadeus_core::Source>::DistStream;
+
+	fn par_stream(self) -> Self::ParStream {
+		<Self as amadeus_core::Source>::par_stream(self)
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		<Self as amadeus_core::Source>::dist_stream(self)
+	}
+}
 #[cfg(feature = "parquet")]
 impl<File, Row> Source for Parquet<File, Row>
 where
@@ -129,6 +187,62 @@ where
 		<Self as amadeus_core::Source>::dist_stream(self)
 	}
 }
+#[cfg(feature = "redis")]
+impl<Row> Source for Redis<Row>
+where
+	Row: super::data::Data,
+{
+	type Item = <Self as amadeus_core::Source>::Item;
+	type Error = <Self as amadeus_core::Source>::Error;
+
+	type ParStream = <Self as amadeus_core::Source>::ParStream;
+	type DistStream = <Self as amadeus_core::Source>::DistStream;
+
+	fn par_stream(self) -> Self::ParStream {
+		<Self as amadeus_core::Source>::par_stream(self)
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		<Self as amadeus_core::Source>::dist_stream(self)
+	}
+}
+#[cfg(feature = "sqlite")]
+impl<Row> Source for Sqlite<Row>
+where
+	Row: super::data::Data,
+{
+	type Item = <Self as amadeus_core::Source>::Item;
+	type Error = <Self as amadeus_core::Source>::Error;
+
+	type ParStream = <Self as amadeus_core::Source>::ParStream;
+	type DistStream = <Self as amadeus_core::Source>::DistStream;
+
+	fn par_stream(self) -> Self::ParStream {
+		<Self as amadeus_core::Source>::par_stream(self)
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		<Self as amadeus_core::Source>::dist_stream(self)
+	}
+}
+impl<A, B> Source for Union<A, B>
+where
+	A: Source,
+	B: Source<Item = A::Item>,
+{
+	type Item = A::Item;
+	type Error = Sum2<A::Error, B::Error>;
+
+	type ParStream = UnionStream<A::ParStream, B::ParStream>;
+	type DistStream = UnionStream<A::DistStream, B::DistStream>;
+
+	fn par_stream(self) -> Self::ParStream {
+		let (a, b) = self.into_inner();
+		UnionStream::new(a.par_stream(), b.par_stream())
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		let (a, b) = self.into_inner();
+		UnionStream::new(a.dist_stream(), b.dist_stream())
+	}
+}
 #[cfg(feature = "aws")]
 impl Source for Cloudfront {
 	type Item = crate::data::CloudfrontRow;
@@ -229,6 +343,9 @@ where
 			marker: self.marker,
 		}
 	}
+	fn cost(&self) -> Option<u64> {
+		self.task.cost()
+	}
 }
 impl<I, T, E, U> Stream for IntoTask<I, U>
 where
@@ -1,8 +1,12 @@
 #![allow(clippy::suspicious_map)]
 
-use std::{path::PathBuf, time::SystemTime};
+use serde::Deserialize;
+use std::{borrow::Cow, fs, path::PathBuf, time::SystemTime};
 
-use amadeus::prelude::*;
+use amadeus::{
+	amadeus_core::file::{Codec, Decompress}, data::Schema, prelude::*, source::CsvRecord
+};
+use serde_closure::FnMut;
 
 #[tokio::test(threaded_scheduler)]
 #[cfg_attr(miri, ignore)]
@@ -60,3 +64,369 @@ async fn csv() {
 
 	println!("in {:?}", start.elapsed().unwrap());
 }
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn csv_null_tokens() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let dir = std::env::temp_dir().join("amadeus-csv-null-tokens");
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	let path = dir.join("nulls.csv");
+	fs::write(
+		&path,
+		"1,alice,NA,3.5\n2,NA,,NULL\n3,\\N,GB,\n4,NULL,NA,\\N\n",
+	)
+	.unwrap();
+	// "NA" is a country code in the third column, so only empty fields are null there
+	let null_tokens = NullTokens::new()
+		.all(vec!["", "NA", "NULL", "\\N"])
+		.column(2, vec![""]);
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Row {
+		id: u32,
+		name: Option<String>,
+		country: Option<String>,
+		score: Option<f64>,
+	}
+	let mut rows: Vec<Row> = Csv::<_, Row>::new_with(path.clone(), null_tokens.clone())
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	rows.sort_by_key(|row| row.id);
+	let row = |id, name: Option<&str>, country: Option<&str>, score| Row {
+		id,
+		name: name.map(String::from),
+		country: country.map(String::from),
+		score,
+	};
+	assert_eq!(
+		rows,
+		vec![
+			row(1, Some("alice"), Some("NA"), Some(3.5)),
+			row(2, None, None, None),
+			row(3, None, Some("GB"), None),
+			row(4, None, Some("NA"), None),
+		]
+	);
+
+	let mut rows: Vec<Vec<Value>> = Csv::<_, Value>::new_with(path, null_tokens)
+		.await
+		.unwrap()
+		.par_stream()
+		.map(|row: Result<Value, _>| row.unwrap().into_group().unwrap().into_fields())
+		.collect(pool)
+		.await;
+	rows.sort_by_key(|row| row[0].clone().into_u64().unwrap());
+	let null = Value::Option(None);
+	assert_eq!(
+		rows,
+		vec![
+			vec![
+				Value::U64(1),
+				Value::String(String::from("alice")),
+				Value::String(String::from("NA")),
+				Value::F64(3.5)
+			],
+			vec![Value::U64(2), null.clone(), null.clone(), null.clone()],
+			vec![
+				Value::U64(3),
+				null.clone(),
+				Value::String(String::from("GB")),
+				null.clone()
+			],
+			vec![
+				Value::U64(4),
+				null.clone(),
+				Value::String(String::from("NA")),
+				null
+			],
+		]
+	);
+
+	fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn csv_quoted_across_chunks() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-csv-quoted.csv");
+	let data = "1,plain\n2,\"spans\nseveral, \"\"quoted\"\"\r\nlines\"\n3,\"a,b\"\n4,last";
+	fs::write(&path, data).unwrap();
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Row {
+		id: u32,
+		text: String,
+	}
+	let expected = vec![
+		Row {
+			id: 1,
+			text: String::from("plain"),
+		},
+		Row {
+			id: 2,
+			text: String::from("spans\nseveral, \"quoted\"\r\nlines"),
+		},
+		Row {
+			id: 3,
+			text: String::from("a,b"),
+		},
+		Row {
+			id: 4,
+			text: String::from("last"),
+		},
+	];
+	// Every chunk size puts a read boundary at each position of the quoted
+	// fields for one of them
+	for read_chunk_size in 1..=data.len() {
+		let mut rows: Vec<Row> = Csv::<_, Row>::new(path.clone())
+			.await
+			.unwrap()
+			.read_chunk_size(read_chunk_size)
+			.par_stream()
+			.map(Result::unwrap)
+			.collect(pool)
+			.await;
+		rows.sort_by_key(|row| row.id);
+		assert_eq!(rows, expected, "read_chunk_size {}", read_chunk_size);
+	}
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn csv_zstd() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Row {
+		id: u32,
+		text: String,
+	}
+
+	let path = std::env::temp_dir().join("amadeus-csv-zstd.csv.zst");
+	let data = (0..1000)
+		.map(|id| format!("{},\"row\n{}\"\n", id, id))
+		.collect::<String>();
+	fs::write(&path, zstd::stream::encode_all(data.as_bytes(), 0).unwrap()).unwrap();
+
+	// A small read_chunk_size splits records across the decompressed chunks
+	let mut rows: Vec<Row> = Csv::<_, Row>::new(Decompress::new(path.clone(), Codec::Detect))
+		.await
+		.unwrap()
+		.read_chunk_size(100)
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	rows.sort_by_key(|row| row.id);
+	let expected = (0..1000)
+		.map(|id| Row {
+			id,
+			text: format!("row\n{}", id),
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(rows, expected);
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn csv_borrowed() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-csv-borrowed.csv");
+	fs::write(
+		&path,
+		"1,alice,GB\n2,\"bob \"\"the builder\"\"\",NA\n3,carol,\n",
+	)
+	.unwrap();
+	let null_tokens = NullTokens::new().all(vec!["", "NA"]);
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Row {
+		id: u32,
+		name: String,
+		country: Option<String>,
+	}
+	#[derive(Deserialize)]
+	struct Borrowed<'a> {
+		id: u32,
+		#[serde(borrow)]
+		name: Cow<'a, str>,
+		country: Option<&'a str>,
+	}
+	let mut owned: Vec<Row> = Csv::<_, Row>::new_with(path.clone(), null_tokens.clone())
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	owned.sort_by_key(|row| row.id);
+
+	let mut borrowed: Vec<(Row, bool)> = Csv::<_, Value>::new_with(path.clone(), null_tokens)
+		.await
+		.unwrap()
+		.map_borrowed(FnMut!(|record: CsvRecord| {
+			let row: Borrowed = record.deserialize().unwrap();
+			assert_eq!(record.get(2), row.country);
+			let is_borrowed = matches!(row.name, Cow::Borrowed(_));
+			let row = Row {
+				id: row.id,
+				name: row.name.into_owned(),
+				country: row.country.map(String::from),
+			};
+			(row, is_borrowed)
+		}))
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	borrowed.sort_by_key(|(row, _)| row.id);
+
+	assert!(borrowed.iter().all(|&(_, is_borrowed)| is_borrowed));
+	assert_eq!(
+		borrowed.into_iter().map(|(row, _)| row).collect::<Vec<_>>(),
+		owned
+	);
+	assert_eq!(owned[1].name, "bob \"the builder\"");
+	assert_eq!(owned[1].country, None);
+
+	fs::remove_file(path).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn csv_schema() {
+	let dir = std::env::temp_dir().join("amadeus-csv-schema");
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	let path = dir.join("schema.csv");
+	fs::write(
+		&path,
+		"1,alice,3.5,true,2020-01-02,2020-01-02T03:04:05Z\n\
+		 2,,4,false,2020-02-03,2020-02-03T04:05:06+01:00\n\
+		 -3,carol,NA,true,,2020-03-04T05:06:07-01:00\n",
+	)
+	.unwrap();
+
+	let csv = Csv::<_, Value>::new_with(path, NullTokens::new().all(vec!["", "NA"]))
+		.await
+		.unwrap();
+	let schema = csv.schema(100).await.unwrap().unwrap();
+	assert_eq!(
+		schema,
+		Schema::Group(
+			vec![
+				Schema::I64,
+				Schema::Option(Box::new(Schema::String)),
+				Schema::Option(Box::new(Schema::F64)),
+				Schema::Bool,
+				Schema::Option(Box::new(Schema::DateWithoutTimezone)),
+				Schema::DateTime,
+			],
+			None
+		)
+	);
+
+	// Only the first row is sampled, and the source is still readable
+	assert_eq!(
+		csv.schema(1).await.unwrap().unwrap(),
+		Schema::Group(
+			vec![
+				Schema::U64,
+				Schema::String,
+				Schema::F64,
+				Schema::Bool,
+				Schema::DateWithoutTimezone,
+				Schema::DateTime
+			],
+			None
+		)
+	);
+	let pool = &ThreadPool::new(None, None).unwrap();
+	assert_eq!(csv.par_stream().count(pool).await, 3);
+
+	fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn write_csv() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Row {
+		id: u64,
+		name: String,
+		score: Option<f64>,
+	}
+
+	let dir = std::env::temp_dir().join("amadeus-write-csv");
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	let (input, output) = (dir.join("input.csv"), dir.join("output.csv"));
+
+	let rows = (0..100_u64)
+		.map(|id| Row {
+			id,
+			name: format!("row, \"{}\"", id),
+			score: if id % 3 == 0 {
+				None
+			} else {
+				Some(id as f64 / 4.0)
+			},
+		})
+		.collect::<Vec<_>>();
+	let _ = rows
+		.clone()
+		.into_par_stream()
+		.write_csv(pool, &input)
+		.await
+		.unwrap();
+
+	// Read, transform, write, read
+	let written = Csv::<_, Row>::new(input)
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.map(|row: Row| Row {
+			id: row.id * 2,
+			..row
+		})
+		.write_csv(pool, &output)
+		.await
+		.unwrap();
+	assert_eq!(written, fs::metadata(&output).unwrap().len());
+
+	let mut res: Vec<Row> = Csv::new(output)
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	res.sort_by_key(|row| row.id);
+	let expected = rows
+		.into_iter()
+		.map(|row| Row {
+			id: row.id * 2,
+			..row
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(res, expected);
+
+	fs::remove_dir_all(&dir).unwrap();
+}
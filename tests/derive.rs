@@ -1,5 +1,9 @@
-use amadeus::prelude::*;
+use fxhash::FxBuildHasher;
+use hashlink::LinkedHashMap;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use amadeus::prelude::*;
 
 #[derive(Data, Clone, PartialEq, Debug)]
 struct GenericRow<G> {
@@ -34,6 +38,89 @@ fn list() {
 	assert_eq!(rows, rows2);
 }
 
+/// A group with the named fields `fields`, as read from a source with a schema.
+fn group(fields: Vec<(&str, Value)>) -> Value {
+	let mut names = LinkedHashMap::with_hasher(FxBuildHasher::default());
+	let fields = fields
+		.into_iter()
+		.enumerate()
+		.map(|(i, (name, value))| {
+			let _ = names.insert(name.to_owned(), i);
+			value
+		})
+		.collect();
+	Value::Group(Group::new(fields, Some(Arc::new(names))))
+}
+
+#[test]
+fn downcast_group() {
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Address {
+		city: String,
+		zip: Option<u64>,
+	}
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Person {
+		name: String,
+		age: u64,
+		nickname: Option<String>,
+		address: Address,
+		tags: List<String>,
+	}
+
+	// Fields are matched by name, whatever their order
+	let row = group(vec![
+		("age", Value::U64(30)),
+		("name", Value::String("a".to_owned())),
+		("nickname", Value::from(Some("x".to_owned()))),
+		(
+			"address",
+			group(vec![
+				("zip", Value::from(None::<u64>)),
+				("city", Value::String("p".to_owned())),
+			]),
+		),
+		("tags", Value::List(vec![Value::String("t".to_owned())].into())),
+	]);
+	let person = Person {
+		name: "a".to_owned(),
+		age: 30,
+		nickname: Some("x".to_owned()),
+		address: Address {
+			city: "p".to_owned(),
+			zip: None,
+		},
+		tags: vec!["t".to_owned()].into(),
+	};
+	assert_eq!(Downcast::<Person>::downcast(row.clone()), Ok(person.clone()));
+	assert_eq!(
+		Downcast::<Person>::downcast(row.into_group().unwrap()),
+		Ok(person.clone())
+	);
+
+	// Unnamed groups are matched by position
+	assert_eq!(
+		Downcast::<Person>::downcast(Value::from(person.clone())),
+		Ok(person)
+	);
+
+	// Fields are type checked
+	let row = group(vec![
+		("name", Value::String("b".to_owned())),
+		("age", Value::String("forty".to_owned())),
+		("nickname", Value::from(None::<String>)),
+		(
+			"address",
+			group(vec![
+				("city", Value::String("q".to_owned())),
+				("zip", Value::from(None::<u64>)),
+			]),
+		),
+		("tags", Value::List(List::new())),
+	]);
+	assert!(Downcast::<Person>::downcast(row).is_err());
+}
+
 mod no_prelude {
 	#![no_implicit_prelude]
 
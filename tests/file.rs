@@ -0,0 +1,231 @@
+use flate2::{write::GzEncoder, Compression};
+use futures::{future, future::LocalBoxFuture, AsyncReadExt, FutureExt};
+use std::{
+	cell::RefCell, collections::HashSet, io::{self, Write}, rc::Rc, time::Duration
+};
+
+use amadeus::amadeus_core::{
+	file::{Codec, DecompressingPage, Page, RetryingPage}, util::IoError
+};
+
+/// A page that returns at most 4 bytes per read, and fails the first read at each
+/// of `fail_at`.
+struct FlakyPage {
+	data: Vec<u8>,
+	fail_at: RefCell<HashSet<u64>>,
+	reads: Rc<RefCell<Vec<u64>>>,
+}
+impl Page for FlakyPage {
+	type Error = IoError;
+
+	fn len(&self) -> LocalBoxFuture<'static, Result<u64, Self::Error>> {
+		future::ready(Ok(self.data.len() as u64)).boxed_local()
+	}
+	fn read(
+		&self, offset: u64, len: usize,
+	) -> LocalBoxFuture<'static, Result<Box<[u8]>, Self::Error>> {
+		self.reads.borrow_mut().push(offset);
+		if self.fail_at.borrow_mut().remove(&offset) {
+			let err = io::Error::new(io::ErrorKind::ConnectionReset, "connection reset");
+			return future::ready(Err(err.into())).boxed_local();
+		}
+		let start = (offset as usize).min(self.data.len());
+		let end = (start + len.min(4)).min(self.data.len());
+		future::ready(Ok(self.data[start..end].into())).boxed_local()
+	}
+	fn write(
+		&self, _offset: u64, _buf: Box<[u8]>,
+	) -> LocalBoxFuture<'static, Result<(), Self::Error>> {
+		unimplemented!()
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn retrying_page() {
+	let data = (0..20).collect::<Vec<u8>>();
+	let reads = Rc::new(RefCell::new(Vec::new()));
+	let page = RetryingPage::new(
+		FlakyPage {
+			data: data.clone(),
+			fail_at: RefCell::new(vec![2, 10].into_iter().collect()),
+			reads: reads.clone(),
+		},
+		1,
+		Duration::from_millis(1),
+	);
+
+	assert_eq!(&*page.read(2, 16).await.unwrap(), &data[2..18]);
+	// Each failed read is retried from the offset it failed at
+	assert_eq!(*reads.borrow(), [2, 2, 6, 10, 10, 14]);
+
+	// Reading past the end returns what's available
+	assert_eq!(&*page.read(16, 10).await.unwrap(), &data[16..]);
+
+	// Consecutive failures beyond `retries` are returned
+	let page = RetryingPage::new(
+		FlakyPage {
+			data,
+			fail_at: RefCell::new(vec![0].into_iter().collect()),
+			reads: Rc::new(RefCell::new(Vec::new())),
+		},
+		0,
+		Duration::from_millis(1),
+	);
+	assert!(page.read(0, 4).await.is_err());
+}
+
+#[test]
+fn retrying_page_backoff() {
+	let page = |backoff| RetryingPage::new((), 20, backoff);
+
+	let backoff = page(Duration::from_millis(1));
+	assert_eq!(backoff.backoff(0), Duration::from_millis(1));
+	assert_eq!(backoff.backoff(3), Duration::from_millis(8));
+	assert_eq!(backoff.backoff(20), Duration::from_millis(65_536));
+
+	// Large backoffs are capped rather than overflowing
+	let day = Duration::from_secs(24 * 60 * 60);
+	let backoff = page(day);
+	assert_eq!(backoff.backoff(0), day);
+	assert_eq!(backoff.backoff(20), day);
+	let backoff = page(Duration::from_secs(u64::MAX / 2));
+	assert_eq!(backoff.backoff(20), Duration::from_secs(u64::MAX / 2));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn decompressing_page() {
+	let gzip = |data: &[u8]| {
+		let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(data).unwrap();
+		encoder.finish().unwrap()
+	};
+	let decompressing = |data: Vec<u8>, codec| {
+		DecompressingPage::new(
+			FlakyPage {
+				data,
+				fail_at: RefCell::new(HashSet::new()),
+				reads: Rc::new(RefCell::new(Vec::new())),
+			},
+			codec,
+		)
+	};
+	let plaintext = (0..1000)
+		.map(|i| format!("line {}\n", i))
+		.collect::<String>();
+	let (first, second) = plaintext.as_bytes().split_at(3000);
+	// Concatenated gzip members, as in WARC files
+	let compressed = [gzip(first), gzip(second)].concat();
+
+	for &codec in &[Codec::Gzip, Codec::Detect] {
+		let page = decompressing(compressed.clone(), codec);
+		assert_eq!(page.len().await.unwrap(), plaintext.len() as u64);
+		assert_eq!(&*page.read(2590, 8).await.unwrap(), b"line 300");
+		let mut read = String::new();
+		let _ = page.reader().read_to_string(&mut read).await.unwrap();
+		assert_eq!(read, plaintext);
+	}
+
+	// Concatenated zstd frames
+	let compressed = [
+		zstd::stream::encode_all(first, 0).unwrap(),
+		zstd::stream::encode_all(second, 0).unwrap(),
+	]
+	.concat();
+	for &codec in &[Codec::Zstd, Codec::Detect] {
+		let page = decompressing(compressed.clone(), codec);
+		assert_eq!(&*page.read(2590, 8).await.unwrap(), b"line 300");
+		assert_eq!(page.len().await.unwrap(), plaintext.len() as u64);
+		let mut read = String::new();
+		let _ = page.reader().read_to_string(&mut read).await.unwrap();
+		assert_eq!(read, plaintext);
+	}
+
+	// Reads go forwards through a stream many chunks long, and a read from
+	// before the last restarts it
+	let long = (0..200_000)
+		.map(|i| format!("line {}\n", i))
+		.collect::<String>();
+	let page = decompressing(gzip(long.as_bytes()), Codec::Detect);
+	let mut read = Vec::new();
+	let _ = page
+		.clone()
+		.reader()
+		.chunk_size(1000)
+		.read_to_end(&mut read)
+		.await
+		.unwrap();
+	assert_eq!(read, long.as_bytes());
+	assert_eq!(&*page.read(2590, 8).await.unwrap(), b"line 300");
+
+	// Uncompressed pages are passed through
+	let page = decompressing(plaintext.clone().into_bytes(), Codec::Detect);
+	assert_eq!(&*page.read(0, 100_000).await.unwrap(), plaintext.as_bytes());
+
+	// Corrupt data is an error
+	let mut corrupt = compressed;
+	corrupt.truncate(corrupt.len() / 2);
+	assert!(decompressing(corrupt, Codec::Gzip).len().await.is_err());
+	let page = decompressing(plaintext.into_bytes(), Codec::Gzip);
+	assert!(page.len().await.is_err());
+}
+
+/// A page that counts the reads made of it.
+struct CountingPage {
+	data: Vec<u8>,
+	reads: Rc<RefCell<usize>>,
+}
+impl Page for CountingPage {
+	type Error = IoError;
+
+	fn len(&self) -> LocalBoxFuture<'static, Result<u64, Self::Error>> {
+		future::ready(Ok(self.data.len() as u64)).boxed_local()
+	}
+	fn read(
+		&self, offset: u64, len: usize,
+	) -> LocalBoxFuture<'static, Result<Box<[u8]>, Self::Error>> {
+		*self.reads.borrow_mut() += 1;
+		let start = (offset as usize).min(self.data.len());
+		let end = (start + len).min(self.data.len());
+		future::ready(Ok(self.data[start..end].into())).boxed_local()
+	}
+	fn write(
+		&self, _offset: u64, _buf: Box<[u8]>,
+	) -> LocalBoxFuture<'static, Result<(), Self::Error>> {
+		unimplemented!()
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn reader_chunk_size() {
+	let data = (0..100_000).map(|i| i as u8).collect::<Vec<_>>();
+	let read = |chunk_size| {
+		let data = data.clone();
+		async move {
+			let reads = Rc::new(RefCell::new(0));
+			let page = CountingPage {
+				data,
+				reads: reads.clone(),
+			};
+			let mut buf = Vec::with_capacity(chunk_size);
+			let _ = page
+				.reader()
+				.chunk_size(chunk_size)
+				.read_to_end(&mut buf)
+				.await
+				.unwrap();
+			let reads = *reads.borrow();
+			(buf, reads)
+		}
+	};
+
+	let (small, small_reads) = read(1000).await;
+	let (large, large_reads) = read(50_000).await;
+	assert_eq!(small, data);
+	assert_eq!(large, data);
+	// One read per chunk, plus one to find the end
+	assert_eq!(small_reads, 101);
+	assert_eq!(large_reads, 3);
+}
@@ -1,6 +1,7 @@
 use either::Either;
+use futures::{channel::mpsc, stream, SinkExt};
 
-use amadeus::prelude::*;
+use amadeus::{into_par_stream::from_stream, prelude::*};
 
 #[tokio::test(threaded_scheduler)]
 #[cfg_attr(miri, ignore)]
@@ -34,3 +35,33 @@ async fn into_par_stream() {
 	let sum: usize = slice.iter().cloned().par().sum(&pool).await;
 	assert_eq!(sum, slice.iter().sum::<usize>());
 }
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn stream_source() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let res: Vec<u32> = from_stream(stream::iter(0..100_u32))
+		.filter(|i: &u32| i % 3 == 1)
+		.map(|i: u32| i * 2)
+		.collect(pool)
+		.await;
+	assert_eq!(
+		res,
+		(0..100)
+			.filter(|i| i % 3 == 1)
+			.map(|i| i * 2)
+			.collect::<Vec<_>>()
+	);
+
+	// A producer feeding a channel concurrently with the pipeline consuming it
+	let (mut sender, receiver) = mpsc::channel(4);
+	let producer = tokio::spawn(async move {
+		for i in 0..1000_u64 {
+			sender.send(i).await.unwrap();
+		}
+	});
+	let sum: u64 = from_stream(receiver).sum(pool).await;
+	assert_eq!(sum, (0..1000).sum::<u64>());
+	producer.await.unwrap();
+}
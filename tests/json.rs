@@ -1,8 +1,16 @@
 #![allow(clippy::suspicious_map)]
 
-use std::{path::PathBuf, time::SystemTime};
+use flate2::{write::GzEncoder, Compression};
+use futures::{future, future::LocalBoxFuture, FutureExt};
+use serde::Deserialize;
+use serde_closure::FnMut;
+use std::{borrow::Cow, cell::RefCell, io::Write, path::PathBuf, rc::Rc, time::SystemTime};
 
-use amadeus::prelude::*;
+use amadeus::{
+	amadeus_core::{
+		file::{Codec, Decompress, Page}, util::IoError
+	}, data::{ExplodePolicy, Schema, Uuid}, prelude::*, source::JsonRecord
+};
 
 #[tokio::test(threaded_scheduler)]
 #[cfg_attr(miri, ignore)]
@@ -86,3 +94,575 @@ async fn json() {
 
 	println!("in {:?}", start.elapsed().unwrap());
 }
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn json_flatten() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-json-flatten.json");
+	std::fs::write(
+		&path,
+		r#"{"name":"a","address":{"city":"x","geo":{"lat":1.5}},"tags":["p","q"]}
+{"name":"b","address":{"city":"y","geo":{"lat":2.5}},"tags":[]}"#,
+	)
+	.unwrap();
+
+	let mut rows = Json::<_, Group>::new(path.clone())
+		.await
+		.unwrap()
+		.flatten(JsonFlatten::default())
+		.par_stream()
+		.map(|row: Result<_, _>| row.unwrap())
+		.collect::<_, Vec<_>>(pool)
+		.await;
+	rows.sort_by(|a, b| a.get("name").partial_cmp(&b.get("name")).unwrap());
+	let names = rows[0]
+		.field_names()
+		.unwrap()
+		.keys()
+		.cloned()
+		.collect::<Vec<_>>();
+	assert_eq!(
+		names,
+		[
+			"address.city",
+			"address.geo.lat",
+			"name",
+			"tags.0",
+			"tags.1"
+		]
+	);
+	assert_eq!(
+		rows[0].get("address.city"),
+		Some(&Value::from("x".to_owned()))
+	);
+	assert_eq!(rows[0].get("address.geo.lat"), Some(&Value::from(1.5)));
+	assert_eq!(rows[0].get("tags.1"), Some(&Value::from("q".to_owned())));
+	assert_eq!(rows[1].get("tags.0"), None);
+
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Flattened {
+		name: String,
+		#[amadeus(name = "address.city")]
+		city: String,
+		#[amadeus(name = "address.geo")]
+		geo: Value,
+		tags: Value,
+	}
+	let mut rows = Json::<_, Flattened>::new(path)
+		.await
+		.unwrap()
+		.flatten(JsonFlatten {
+			depth: Some(1),
+			arrays: JsonArrays::Explode,
+		})
+		.par_stream()
+		.map(|row: Result<_, _>| row.unwrap())
+		.collect::<_, Vec<_>>(pool)
+		.await;
+	rows.sort_by(|a, b| {
+		(&a.name, a.tags.clone())
+			.partial_cmp(&(&b.name, b.tags.clone()))
+			.unwrap()
+	});
+	assert_eq!(
+		rows.iter()
+			.map(|row| (row.name.as_str(), row.city.as_str()))
+			.collect::<Vec<_>>(),
+		[("a", "x"), ("a", "x"), ("b", "y")]
+	);
+	assert_eq!(rows[0].tags, Value::from("p".to_owned()));
+	assert_eq!(rows[1].tags, Value::from("q".to_owned()));
+	assert_eq!(rows[2].tags, Value::from(List::<Value>::new()));
+}
+
+/// An in-memory page that grows to fit whatever is written to it.
+#[derive(Clone, Default)]
+struct MemoryPage(Rc<RefCell<Vec<u8>>>);
+impl Page for MemoryPage {
+	type Error = IoError;
+
+	fn len(&self) -> LocalBoxFuture<'static, Result<u64, Self::Error>> {
+		future::ready(Ok(self.0.borrow().len() as u64)).boxed_local()
+	}
+	fn read(
+		&self, offset: u64, len: usize,
+	) -> LocalBoxFuture<'static, Result<Box<[u8]>, Self::Error>> {
+		let data = self.0.borrow();
+		let start = (offset as usize).min(data.len());
+		let end = (start + len).min(data.len());
+		future::ready(Ok(data[start..end].into())).boxed_local()
+	}
+	fn write(
+		&self, offset: u64, buf: Box<[u8]>,
+	) -> LocalBoxFuture<'static, Result<(), Self::Error>> {
+		let mut data = self.0.borrow_mut();
+		let offset = offset as usize;
+		if data.len() < offset + buf.len() {
+			data.resize(offset + buf.len(), 0);
+		}
+		data[offset..offset + buf.len()].copy_from_slice(&buf);
+		future::ready(Ok(())).boxed_local()
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn write_page() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Row {
+		id: u64,
+		name: String,
+	}
+
+	let page = MemoryPage::default();
+	let written = (0..100_u64)
+		.par()
+		.map(|id| Row {
+			id,
+			name: format!("row {}", id),
+		})
+		.write_page(pool, page.clone(), JsonLines)
+		.await
+		.unwrap();
+
+	let data = String::from_utf8(page.0.borrow().clone()).unwrap();
+	assert_eq!(written, data.len() as u64);
+	let mut rows = data
+		.lines()
+		.map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+		.collect::<Vec<_>>();
+	rows.sort_by_key(|row| row["id"].as_u64().unwrap());
+	assert_eq!(rows.len(), 100);
+	for (id, row) in rows.iter().enumerate() {
+		assert_eq!(
+			*row,
+			serde_json::json!({ "id": id, "name": format!("row {}", id) })
+		);
+	}
+
+	// Enough rows to span several chunks
+	let page = MemoryPage::default();
+	let written = (0..200_000_u64)
+		.par()
+		.map(|id| Row {
+			id,
+			name: format!("row {}", id),
+		})
+		.write_page(pool, page.clone(), JsonLines)
+		.await
+		.unwrap();
+
+	let data = String::from_utf8(page.0.borrow().clone()).unwrap();
+	assert_eq!(written, data.len() as u64);
+	let mut ids = data
+		.lines()
+		.map(|line| {
+			serde_json::from_str::<serde_json::Value>(line).unwrap()["id"]
+				.as_u64()
+				.unwrap()
+		})
+		.collect::<Vec<_>>();
+	ids.sort_unstable();
+	assert_eq!(ids, (0..200_000_u64).collect::<Vec<_>>());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn json_schema() {
+	let path = std::env::temp_dir().join("amadeus-json-schema.json");
+	std::fs::write(
+		&path,
+		r#"{"id":1,"name":"a","tags":["x"]}
+{"id":2,"tags":[],"score":0.5}"#,
+	)
+	.unwrap();
+
+	let schema = Json::<_, Value>::new(path.clone())
+		.await
+		.unwrap()
+		.schema(100)
+		.await
+		.unwrap();
+	let (fields, names) = match schema {
+		Some(Schema::Group(fields, Some(names))) => (fields, names),
+		schema => panic!("{:?}", schema),
+	};
+	assert_eq!(
+		names.keys().cloned().collect::<Vec<_>>(),
+		["id", "name", "tags", "score"]
+	);
+	assert_eq!(
+		fields,
+		[
+			Schema::U64,
+			Schema::Option(Box::new(Schema::String)),
+			Schema::List(Box::new(Schema::String)),
+			Schema::Option(Box::new(Schema::F64)),
+		]
+	);
+
+	std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn write_json() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Row {
+		id: u64,
+		name: String,
+		score: Option<f64>,
+	}
+
+	let dir = std::env::temp_dir().join("amadeus-write-json");
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	let (input, output) = (dir.join("input.json"), dir.join("output.json"));
+
+	let rows = (0..100_u64)
+		.map(|id| Row {
+			id,
+			name: format!("row \"{}\"\n", id),
+			score: if id % 3 == 0 {
+				None
+			} else {
+				Some(id as f64 / 4.0)
+			},
+		})
+		.collect::<Vec<_>>();
+	let _ = rows
+		.clone()
+		.into_par_stream()
+		.write_json(pool, &input)
+		.await
+		.unwrap();
+
+	// Read, transform, write, read
+	let written = Json::<_, Row>::new(input)
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.filter(|row: &Row| row.id < 50)
+		.write_json(pool, &output)
+		.await
+		.unwrap();
+	assert_eq!(written, std::fs::metadata(&output).unwrap().len());
+
+	let mut res: Vec<Row> = Json::new(output)
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	res.sort_by_key(|row| row.id);
+	let expected = rows
+		.into_iter()
+		.filter(|row| row.id < 50)
+		.collect::<Vec<_>>();
+	assert_eq!(res, expected);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn write_json_uuid() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Row {
+		id: Uuid,
+		parent: Option<Uuid>,
+	}
+
+	let dir = std::env::temp_dir().join("amadeus-write-json-uuid");
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	let path = dir.join("uuids.json");
+
+	let rows = (0..100_u8)
+		.map(|i| Row {
+			id: Uuid::from_bytes([i; 16]),
+			parent: if i % 2 == 0 {
+				None
+			} else {
+				Some(Uuid::from_bytes([i - 1; 16]))
+			},
+		})
+		.collect::<Vec<_>>();
+	let _ = rows
+		.clone()
+		.into_par_stream()
+		.write_json(pool, &path)
+		.await
+		.unwrap();
+
+	let mut res: Vec<Row> = Json::new(path.clone())
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	res.sort_by_key(|row| row.id);
+	assert_eq!(res, rows);
+
+	// JSON has no UUID type, so they're written as their hyphenated hex
+	assert!(std::fs::read_to_string(&path)
+		.unwrap()
+		.contains(r#""id":"01010101-0101-0101-0101-010101010101""#));
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn json_gzip() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Row {
+		id: u64,
+		name: String,
+	}
+
+	let dir = std::env::temp_dir().join("amadeus-json-gzip");
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	let file = dir.join("rows.json.gz");
+
+	let mut encoder = GzEncoder::new(
+		std::fs::File::create(&file).unwrap(),
+		Compression::default(),
+	);
+	for id in 0..100 {
+		writeln!(encoder, r#"{{"id":{},"name":"row {}"}}"#, id, id).unwrap();
+	}
+	let _ = encoder.finish().unwrap();
+
+	let mut res: Vec<Row> = Json::new(Decompress::new(file, Codec::Detect))
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	res.sort_by_key(|row| row.id);
+	let expected = (0..100)
+		.map(|id| Row {
+			id,
+			name: format!("row {}", id),
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(res, expected);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn relational() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-json-relational.json");
+	std::fs::write(
+		&path,
+		r#"{"name":"apple","price":0.5,"quantity":10,"discount":null}
+{"name":"pear","price":0.75,"quantity":4,"discount":0.25}"#,
+	)
+	.unwrap();
+	let rows = Json::<_, Group>::new(path.clone())
+		.await
+		.unwrap()
+		.par_stream()
+		.map(|row: Result<_, _>| row.unwrap());
+	let names = |row: &Group| {
+		row.field_names()
+			.unwrap()
+			.keys()
+			.cloned()
+			.collect::<Vec<_>>()
+	};
+
+	let mut res = rows
+		.with_column("total", Expr::col("price") * Expr::col("quantity"))
+		.with_column("net", Expr::col("total") - Expr::col("discount"))
+		.drop_column("discount")
+		.select(vec!["name", "net", "total"])
+		.collect::<_, Vec<_>>(pool)
+		.await;
+	res.sort_by(|a, b| a.get("name").partial_cmp(&b.get("name")).unwrap());
+	for row in &res {
+		assert_eq!(names(row), ["name", "net", "total"]);
+	}
+	let fields = res.into_iter().map(Group::into_fields).collect::<Vec<_>>();
+	assert_eq!(
+		fields,
+		vec![
+			vec![
+				Value::String(String::from("apple")),
+				Value::Option(None),
+				Value::F64(5.0)
+			],
+			vec![
+				Value::String(String::from("pear")),
+				Value::F64(2.75),
+				Value::F64(3.0)
+			],
+		]
+	);
+
+	// Replacing a column keeps its position, and dropping a missing column is a no-op
+	let mut res = Json::<_, Group>::new(path.clone())
+		.await
+		.unwrap()
+		.par_stream()
+		.map(|row: Result<_, _>| row.unwrap())
+		.with_column("quantity", Expr::col("quantity") * 2_u8)
+		.drop_column("missing")
+		.collect::<_, Vec<_>>(pool)
+		.await;
+	res.sort_by(|a, b| a.get("name").partial_cmp(&b.get("name")).unwrap());
+	for row in &res {
+		assert_eq!(names(row), ["name", "price", "quantity", "discount"]);
+	}
+	let quantities = res
+		.iter()
+		.map(|row| row.get("quantity").cloned().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(quantities, [Value::U64(20), Value::U64(8)]);
+	std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn explode() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-json-explode.json");
+	std::fs::write(
+		&path,
+		r#"{"id":1,"tags":["a","b","c"]}
+{"id":2,"tags":[]}
+{"id":3,"tags":null}
+{"id":4,"tags":["d"]}"#,
+	)
+	.unwrap();
+	let explode = |policy| {
+		let path = path.clone();
+		async move {
+			let mut res = Json::<_, Group>::new(path)
+				.await
+				.unwrap()
+				.par_stream()
+				.map(|row: Result<_, _>| row.unwrap())
+				.explode("tags", policy)
+				.map(|row: Group| (row.get("id").cloned(), row.get("tags").cloned()))
+				.collect::<_, Vec<_>>(pool)
+				.await;
+			res.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			res.into_iter()
+				.map(|(id, tag)| (id.unwrap(), tag.unwrap()))
+				.collect::<Vec<_>>()
+		}
+	};
+	let (id, tag) = (Value::U64, |tag: &str| Value::String(tag.to_owned()));
+
+	assert_eq!(
+		explode(ExplodePolicy::Drop).await,
+		[
+			(id(1), tag("a")),
+			(id(1), tag("b")),
+			(id(1), tag("c")),
+			(id(4), tag("d")),
+		]
+	);
+	let res = explode(ExplodePolicy::Null).await;
+	assert_eq!(res.len(), 6);
+	assert_eq!(
+		res.iter()
+			.filter(|(_, tag)| *tag == Value::Option(None))
+			.map(|(id, _)| id.clone())
+			.collect::<Vec<_>>(),
+		[id(2), id(3)]
+	);
+	std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn json_borrowed() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-json-borrowed.json");
+	std::fs::write(
+		&path,
+		r#"{"id":1,"name":"alice","city":"x"}
+{"id":2,"name":"bob \"the builder\"","city":null}
+{"id":3,"name":"carol","city":"y"}"#,
+	)
+	.unwrap();
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Row {
+		id: u64,
+		name: String,
+		city: Option<String>,
+	}
+	#[derive(Deserialize)]
+	struct Borrowed<'a> {
+		id: u64,
+		#[serde(borrow)]
+		name: Cow<'a, str>,
+		city: Option<&'a str>,
+	}
+	let mut owned: Vec<Row> = Json::<_, Row>::new(path.clone())
+		.await
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	owned.sort_by_key(|row| row.id);
+
+	let mut borrowed: Vec<(Row, bool)> = Json::<_, Value>::new(path.clone())
+		.await
+		.unwrap()
+		.map_borrowed(FnMut!(|record: JsonRecord| {
+			let row: Borrowed = record.deserialize().unwrap();
+			let is_borrowed = matches!(row.name, Cow::Borrowed(_));
+			let row = Row {
+				id: row.id,
+				name: row.name.into_owned(),
+				city: row.city.map(String::from),
+			};
+			(row, is_borrowed)
+		}))
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	borrowed.sort_by_key(|(row, _)| row.id);
+
+	// Strings with escapes can't be borrowed
+	assert_eq!(
+		borrowed
+			.iter()
+			.map(|&(_, is_borrowed)| is_borrowed)
+			.collect::<Vec<_>>(),
+		[true, false, true]
+	);
+	assert_eq!(
+		borrowed.into_iter().map(|(row, _)| row).collect::<Vec<_>>(),
+		owned
+	);
+	std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,852 @@
+use amadeus::{
+	data::{AmadeusOrd, GeoPoint, Union, Uuid}, par_sink::{Encoder, LimitExceeded, SeededHasher}, prelude::*
+};
+use either::Either;
+use std::{
+	cmp::Reverse, collections::{BTreeSet, HashMap}, hash::{BuildHasher, Hash, Hasher}, io, sync::{
+		atomic::{AtomicUsize, Ordering}, Arc, Mutex
+	}
+};
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn ewma() {
+	let pool = &ThreadPool::new(Some(1), Some(1)).unwrap();
+
+	let items = (0..100).map(|i| f64::from(i % 7) * 1.5).collect::<Vec<_>>();
+	let alpha = 0.3;
+
+	let mut expected = items[0];
+	for &item in &items[1..] {
+		expected = alpha * item + (1.0 - alpha) * expected;
+	}
+
+	let res = items.clone().into_par_stream().ewma(pool, alpha).await;
+	assert!((res.unwrap() - expected).abs() < 1e-9);
+
+	// Each item is a partition of its own, so as a single partition
+	let res = vec![items]
+		.into_par_stream()
+		.flat_map(futures::stream::iter)
+		.ewma_partitions(pool, alpha)
+		.await;
+	assert_eq!(res.len(), 1);
+	assert!((res[0] - expected).abs() < 1e-9);
+
+	let res = Vec::<f64>::new().into_par_stream().ewma(pool, alpha).await;
+	assert_eq!(res, None);
+
+	// More partitions than workers, one of them empty
+	let pool = &ThreadPool::new(Some(2), Some(1)).unwrap();
+	let partitions = vec![
+		vec![1.0, 2.0, 3.0],
+		vec![],
+		vec![4.0],
+		vec![5.0, 6.0],
+		vec![7.0],
+	];
+	let mut res = partitions
+		.clone()
+		.into_par_stream()
+		.flat_map(futures::stream::iter)
+		.ewma_partitions(pool, alpha)
+		.await;
+	res.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	let mut expected = partitions
+		.iter()
+		.filter(|items| !items.is_empty())
+		.map(|items| {
+			items[1..]
+				.iter()
+				.fold(items[0], |value, item| alpha * item + (1.0 - alpha) * value)
+		})
+		.collect::<Vec<_>>();
+	expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	assert_eq!(res.len(), expected.len());
+	for (res, expected) in res.iter().zip(&expected) {
+		assert!((res - expected).abs() < 1e-9);
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn fold_partitions() {
+	let pool = &ThreadPool::new(Some(3), Some(1)).unwrap();
+
+	// More partitions than workers, one of them empty
+	let partitions = (0..10_u64)
+		.map(|i| (0..i * 3).map(|j| i * 100 + j).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	let mut res = partitions
+		.clone()
+		.into_par_stream()
+		.flat_map(futures::stream::iter)
+		.fold_partitions(
+			pool,
+			|| (0, Vec::new()),
+			|(sum, mut items): (u64, Vec<u64>), item| {
+				items.push(item);
+				(sum + item, items)
+			},
+		)
+		.await;
+	assert_eq!(res.len(), partitions.len());
+	for (sum, items) in &res {
+		assert_eq!(*sum, items.iter().sum::<u64>());
+	}
+	res.sort();
+	let mut expected = partitions
+		.into_iter()
+		.map(|items| (items.iter().sum(), items))
+		.collect::<Vec<_>>();
+	expected.sort();
+	assert_eq!(res, expected);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn zip_sinks() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let items = (0..1000_u64).map(|i| (i * 7919) % 1009).collect::<Vec<_>>();
+
+	let (count, sum, max): (usize, u64, Option<u64>) = items
+		.clone()
+		.into_par_stream()
+		.zip_sinks(pool, (Identity.count(), Identity.sum(), Identity.max()))
+		.await;
+
+	assert_eq!(count, items.clone().into_par_stream().count(pool).await);
+	assert_eq!(
+		sum,
+		items.clone().into_par_stream().sum::<_, u64>(pool).await
+	);
+	assert_eq!(max, items.clone().into_par_stream().max(pool).await);
+
+	let (count, strings): (usize, Vec<String>) = items
+		.iter()
+		.map(u64::to_string)
+		.par()
+		.zip_sinks(pool, (Identity.count(), Identity.collect()))
+		.await;
+	assert_eq!(count, items.len());
+	assert_eq!(strings.len(), items.len());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn distinct_count_by() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// "a" sees overlapping values across partitions, "b" disjoint ones, and "c"
+	// the same value repeatedly.
+	let items = (0..100_u32)
+		.map(|i| ("a", i % 10))
+		.chain((0..100).map(|i| ("b", i)))
+		.chain((0..100).map(|_| ("c", 0)))
+		.collect::<Vec<_>>();
+
+	let counts = items.into_par_stream().distinct_count_by(pool).await;
+	assert_eq!(counts.len(), 3);
+	assert_eq!(counts["a"], 10);
+	assert_eq!(counts["b"], 100);
+	assert_eq!(counts["c"], 1);
+
+	let counts = Vec::<(u32, u32)>::new()
+		.into_par_stream()
+		.distinct_count_by(pool)
+		.await;
+	assert!(counts.is_empty());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn count_where() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let items = (0..10_000_u64).collect::<Vec<_>>();
+	let count = items
+		.clone()
+		.into_par_stream()
+		.count_where(pool, |i: &u64| i % 7 == 3)
+		.await;
+	let filtered = items
+		.into_par_stream()
+		.filter(|i: &u64| i % 7 == 3)
+		.count(pool)
+		.await;
+	assert_eq!(count, filtered);
+	assert_eq!(count, 1429);
+
+	let count = Vec::<u64>::new()
+		.into_par_stream()
+		.count_where(pool, |_: &u64| true)
+		.await;
+	assert_eq!(count, 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn distinct_by() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// 50 ids, each with 5 records differing in their version
+	let records = (0..5_u32)
+		.flat_map(|version| (0..50_u32).map(move |id| (id, version, format!("record {}", id))))
+		.collect::<Vec<_>>();
+
+	let mut distinct = records
+		.clone()
+		.into_par_stream()
+		.distinct_by(pool, |&(id, _, _): &(u32, u32, String)| id)
+		.await;
+	assert_eq!(distinct.len(), 50);
+	distinct.sort_unstable_by_key(|&(id, _, _)| id);
+	for (id, record) in (0..50).zip(&distinct) {
+		assert_eq!(record.0, id);
+		assert!(records.contains(record));
+	}
+
+	let distinct = Vec::<(u32, u32)>::new()
+		.into_par_stream()
+		.distinct_by(pool, |&(id, _): &(u32, u32)| id)
+		.await;
+	assert!(distinct.is_empty());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn collect_bounded() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let mut res = (0..1000_u32)
+		.into_par_stream()
+		.collect_bounded(pool, 1000)
+		.await
+		.unwrap();
+	res.sort_unstable();
+	assert_eq!(res, (0..1000).collect::<Vec<_>>());
+
+	// Over the limit in total, though perhaps not within any one partition
+	let err = (0..1000_u32)
+		.into_par_stream()
+		.collect_bounded(pool, 999)
+		.await
+		.unwrap_err();
+	assert_eq!(err, LimitExceeded { max_items: 999 });
+
+	let res = vec![(0..1000_u32).collect::<Vec<_>>()]
+		.into_par_stream()
+		.flat_map(futures::stream::iter)
+		.collect_bounded(pool, 10)
+		.await;
+	assert_eq!(res, Err(LimitExceeded { max_items: 10 }));
+
+	let res = Vec::<u32>::new()
+		.into_par_stream()
+		.collect_bounded(pool, 0)
+		.await;
+	assert_eq!(res, Ok(Vec::new()));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn scan_global() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Partitions of varying sizes, some empty
+	let partitions = (0..50_u64)
+		.map(|i| (0..i * 7 % 11).map(|j| i * 100 + j).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	let expected = partitions
+		.iter()
+		.flatten()
+		.scan(0, |total, &i| {
+			*total += i;
+			Some(*total)
+		})
+		.collect::<Vec<_>>();
+
+	let res = partitions
+		.into_par_stream()
+		.flat_map(futures::stream::iter)
+		.scan_global(pool, 0, |a: &u64, b: &u64| a + b)
+		.await;
+	assert_eq!(res, expected);
+
+	let res = Vec::<u64>::new()
+		.into_par_stream()
+		.scan_global(pool, 0, |a: &u64, b: &u64| a + b)
+		.await;
+	assert!(res.is_empty());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn distinct_approx() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// 20 unrelated documents of 50 words, each with 3 near-duplicates differing in
+	// a single word
+	let docs = (0..20_u64)
+		.flat_map(|doc| {
+			let words = (0..50)
+				.map(|i| ((doc * 50 + i) * 7919 % 10007).to_string())
+				.collect::<Vec<_>>();
+			(0..4).map(move |variant| {
+				let mut words = words.clone();
+				if variant != 0 {
+					words[variant * 10] = format!("variant{}", variant);
+				}
+				(doc, words.join(" "))
+			})
+		})
+		.collect::<Vec<_>>();
+
+	let groups = docs
+		.into_par_stream()
+		.distinct_approx(
+			pool,
+			|(_, doc): &(u64, String)| {
+				doc.split(' ')
+					.collect::<Vec<_>>()
+					.windows(3)
+					.map(|shingle| shingle.join(" "))
+					.collect::<Vec<_>>()
+			},
+			20,
+			5,
+		)
+		.await;
+	assert_eq!(groups.len(), 20);
+	let mut docs = groups
+		.into_iter()
+		.map(|group| {
+			assert_eq!(group.len(), 4);
+			assert!(group.iter().all(|&(doc, _)| doc == group[0].0));
+			group[0].0
+		})
+		.collect::<Vec<_>>();
+	docs.sort_unstable();
+	assert_eq!(docs, (0..20).collect::<Vec<_>>());
+
+	let groups = Vec::<String>::new()
+		.into_par_stream()
+		.distinct_approx(pool, |doc: &String| doc.chars().collect::<Vec<_>>(), 20, 5)
+		.await;
+	assert!(groups.is_empty());
+
+	// Documents without any features aren't near-duplicates of each other
+	let groups = vec![String::new(); 5]
+		.into_par_stream()
+		.distinct_approx(pool, |doc: &String| doc.chars().collect::<Vec<_>>(), 20, 5)
+		.await;
+	assert_eq!(groups.len(), 5);
+	assert!(groups.iter().all(|group| group.len() == 1));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn sorted_spill() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let dir = std::env::temp_dir().join("amadeus-sorted-spill");
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir(&dir).unwrap();
+
+	let items = (0..1000_u64).map(|i| (i * 7919) % 1009).collect::<Vec<_>>();
+
+	// A budget of 3 items forces many runs to be spilled
+	let dir_ = dir.clone();
+	let res = (0..10)
+		.par()
+		.flat_map(move |i| futures::stream::iter(items[i * 100..(i + 1) * 100].to_vec()))
+		.sorted_spill(pool, 3, dir_)
+		.await;
+	assert!(std::fs::read_dir(&dir).unwrap().count() > 1);
+
+	let res = res.collect::<Vec<_>>();
+	let mut expected = (0..1000_u64).map(|i| (i * 7919) % 1009).collect::<Vec<_>>();
+	expected.sort_unstable();
+	assert_eq!(res, expected);
+
+	// Spill files are removed once the output is consumed
+	assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn distinct_spill() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let dir = std::env::temp_dir().join("amadeus-distinct-spill");
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir(&dir).unwrap();
+
+	// 1000 items with 200 unique values, each repeated across partitions
+	let items = (0..1000_u64).map(|i| (i * 7919) % 200).collect::<Vec<_>>();
+
+	// A budget of 3 items forces many runs to be spilled
+	let items_ = items.clone();
+	let dir_ = dir.clone();
+	let res = (0..10)
+		.par()
+		.flat_map(move |i| futures::stream::iter(items_[i * 100..(i + 1) * 100].to_vec()))
+		.distinct_spill(pool, 3, dir_)
+		.await;
+	assert!(std::fs::read_dir(&dir).unwrap().count() > 1);
+
+	let res = res.collect::<Vec<_>>();
+	let expected = items.into_iter().collect::<BTreeSet<_>>();
+	assert_eq!(res, expected.into_iter().collect::<Vec<_>>());
+
+	// Spill files are removed once the output is consumed
+	assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn write_partitioned() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Clone)]
+	struct IdLines;
+	impl Encoder<(u32, &'static str)> for IdLines {
+		fn encode(&mut self, item: (u32, &'static str), buf: &mut Vec<u8>) -> io::Result<()> {
+			buf.extend_from_slice(format!("{}\n", item.0).as_bytes());
+			Ok(())
+		}
+	}
+
+	let dir = std::env::temp_dir().join("amadeus-write-partitioned");
+	let _ = std::fs::remove_dir_all(&dir);
+
+	let cities = ["London", "New York", "a/b=c", ""];
+	let rows = (0..1000_u32)
+		.map(|id| (id, cities[id as usize % cities.len()]))
+		.collect::<Vec<_>>();
+	// A cap of 1 partition forces frequent flushes
+	let written = rows
+		.clone()
+		.into_par_stream()
+		.write_partitioned(
+			pool,
+			dir.clone(),
+			"city",
+			|row: &(u32, &str)| row.1,
+			IdLines,
+			1,
+		)
+		.await
+		.unwrap();
+
+	let mut partitions = std::fs::read_dir(&dir)
+		.unwrap()
+		.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+		.collect::<Vec<_>>();
+	partitions.sort();
+	assert_eq!(
+		partitions,
+		[
+			"city=London",
+			"city=New York",
+			"city=__HIVE_DEFAULT_PARTITION__",
+			"city=a%2Fb%3Dc"
+		]
+	);
+	let mut total = 0;
+	for (partition, city) in partitions.iter().zip(&["London", "New York", "", "a/b=c"]) {
+		let mut ids = Vec::new();
+		for file in std::fs::read_dir(dir.join(partition)).unwrap() {
+			let file = file.unwrap();
+			assert!(file.file_name().to_str().unwrap().starts_with("part-"));
+			let contents = std::fs::read_to_string(file.path()).unwrap();
+			total += contents.len() as u64;
+			ids.extend(contents.lines().map(|id| id.parse::<u32>().unwrap()));
+		}
+		ids.sort_unstable();
+		let expected = rows
+			.iter()
+			.filter(|row| row.1 == *city)
+			.map(|row| row.0)
+			.collect::<Vec<_>>();
+		assert_eq!(ids, expected);
+	}
+	assert_eq!(written, total);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn for_each_batched() {
+	let pool = &ThreadPool::new(Some(1), Some(1)).unwrap();
+
+	// Partitions of 100, 50 and 13 items, all processed by the one worker
+	let lens = [100_u32, 50, 13];
+	let batches = Arc::new(Mutex::new(Vec::new()));
+	let batches_ = batches.clone();
+	lens.iter()
+		.enumerate()
+		.map(|(partition, &len)| (0..len).map(|i| (partition, i)).collect::<Vec<_>>())
+		.collect::<Vec<_>>()
+		.into_par_stream()
+		.flat_map(futures::stream::iter)
+		.for_each_batched(pool, 7, move |batch: Vec<(usize, u32)>| {
+			batches_.lock().unwrap().push(batch)
+		})
+		.await;
+
+	// ceil(len / 7) flushes per partition, the last holding its remainder
+	let batches = batches.lock().unwrap();
+	assert_eq!(batches.len(), 15 + 8 + 2);
+	for (partition, &len) in lens.iter().enumerate() {
+		let batches = batches
+			.iter()
+			.filter(|batch| batch[0].0 == partition)
+			.collect::<Vec<_>>();
+		assert_eq!(batches.len(), ((len + 7 - 1) / 7) as usize);
+		assert!(batches
+			.iter()
+			.all(|batch| batch.iter().all(|&(p, _)| p == partition)));
+		let (last, full) = batches.split_last().unwrap();
+		assert!(full.iter().all(|batch| batch.len() == 7));
+		assert_eq!(last.len(), (len - (len - 1) / 7 * 7) as usize);
+		assert_eq!(
+			batches
+				.iter()
+				.flat_map(|batch| batch.iter().map(|&(_, i)| i))
+				.collect::<Vec<_>>(),
+			(0..len).collect::<Vec<_>>()
+		);
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn group_by_snapshots() {
+	let pool = &ThreadPool::new(Some(2), Some(2)).unwrap();
+
+	let items = (0..1000_u64).map(|i| (i % 7, i)).collect::<Vec<_>>();
+
+	let snapshots = Arc::new(Mutex::new(Vec::new()));
+	let snapshots_ = snapshots.clone();
+	let res = items
+		.clone()
+		.into_par_stream()
+		.group_by_snapshots(
+			pool,
+			100,
+			|| 0,
+			|a: u64, b: Either<u64, u64>| a + b.into_inner(),
+			move |snapshot: &_| snapshots_.lock().unwrap().push(snapshot.clone()),
+		)
+		.await;
+	let res = res.into_iter().collect::<HashMap<_, _>>();
+
+	let expected = items
+		.into_par_stream()
+		.group_by(pool, Identity.sum::<u64>())
+		.await
+		.into_iter()
+		.collect::<HashMap<_, _>>();
+	assert_eq!(res, expected);
+
+	// Each snapshot is a total over all workers that only grows, ending with the
+	// batch result
+	let snapshots = snapshots.lock().unwrap();
+	let last = snapshots
+		.last()
+		.unwrap()
+		.clone()
+		.into_iter()
+		.collect::<HashMap<_, _>>();
+	assert_eq!(last, expected);
+	for (prev, snapshot) in snapshots.iter().zip(snapshots.iter().skip(1)) {
+		for (key, sum) in prev {
+			assert!(*sum <= snapshot[key]);
+		}
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn map_with_errors() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let items = (0..1000_u32)
+		.map(|i| {
+			if i % 100 == 7 {
+				format!("bad {}", i)
+			} else {
+				i.to_string()
+			}
+		})
+		.collect::<Vec<_>>();
+	let (sum, mut errors): (u32, Vec<String>) = items
+		.into_par_stream()
+		.pipe(
+			pool,
+			Identity.map_with_errors(
+				|item: String| item.parse::<u32>().map_err(|_| item),
+				Identity.sum(),
+			),
+		)
+		.await;
+	assert_eq!(sum, (0..1000).filter(|i| i % 100 != 7).sum::<u32>());
+	errors.sort();
+	let mut expected = (0..10)
+		.map(|i| format!("bad {}", i * 100 + 7))
+		.collect::<Vec<_>>();
+	expected.sort();
+	assert_eq!(errors, expected);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn top_n_per_key() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Distinct scores, so the top n of each key is unambiguous
+	let items = (0..1000_u32)
+		.map(|i| (i % 5, (i * 37) % 1000))
+		.collect::<Vec<_>>();
+	let res = items
+		.clone()
+		.into_par_stream()
+		.top_n_per_key(pool, 3, |&(key, _): &(u32, u32)| key, |&(_, score)| score)
+		.await;
+	assert_eq!(res.len(), 5);
+	for key in 0..5 {
+		let mut expected = items
+			.iter()
+			.filter(|&&(k, _)| k == key)
+			.copied()
+			.collect::<Vec<_>>();
+		expected.sort_by_key(|&(_, score)| Reverse(score));
+		expected.truncate(3);
+		assert_eq!(res[&key], expected);
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn reduce_by_key() {
+	static LIVE: AtomicUsize = AtomicUsize::new(0);
+	static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+	/// A value that tracks the peak number of instances alive at once.
+	struct Tracked(u64);
+	impl Tracked {
+		fn new(value: u64) -> Self {
+			let live = LIVE.fetch_add(1, Ordering::SeqCst) + 1;
+			let _ = PEAK.fetch_max(live, Ordering::SeqCst);
+			Self(value)
+		}
+	}
+	impl Drop for Tracked {
+		fn drop(&mut self) {
+			let _ = LIVE.fetch_sub(1, Ordering::SeqCst);
+		}
+	}
+
+	let pool = &ThreadPool::new(None, None).unwrap();
+	let (keys, items) = (10_u64, 100_000_u64);
+
+	let res = (0..items)
+		.into_par_stream()
+		.map(move |i: u64| (i % keys, Tracked::new(i)))
+		.reduce_by_key(pool, |a: Tracked, b: Tracked| Tracked::new(a.0 + b.0))
+		.await;
+	assert_eq!(res.len(), keys as usize);
+	for (key, sum) in &res {
+		let expected = (0..items).filter(|i| i % keys == *key).sum::<u64>();
+		assert_eq!(sum.0, expected);
+	}
+	// One accumulator per key per partition, plus the handful being combined
+	let peak = PEAK.load(Ordering::SeqCst);
+	assert!(peak < 1000, "peak of {} values alive", peak);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn most_frequent_bounded() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A long tail of keys seen once each, and a few heavy hitters
+	let heavy = 5_u64;
+	let items = (0..200_000_u64)
+		.map(|i| {
+			if i % 10 == 0 {
+				i / 10 % heavy
+			} else {
+				heavy + i
+			}
+		})
+		.collect::<Vec<_>>();
+
+	// This tolerance would need hundreds of millions of counters uncapped
+	let res = items
+		.into_par_stream()
+		.most_frequent_bounded(pool, 10, 0.99, 1e-8, 4096)
+		.await;
+	assert!(res.counters() <= 4096);
+	let mut top = res
+		.iter()
+		.take(heavy as usize)
+		.map(|(&key, _)| key)
+		.collect::<Vec<_>>();
+	top.sort_unstable();
+	assert_eq!(top, (0..heavy).collect::<Vec<_>>());
+	for (_, &count) in res.iter().take(heavy as usize) {
+		assert!(count >= 4000);
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn group_by_with_hasher() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Clustered keys, which modulo partitioning would put all in one partition
+	let keys = (0..8000_u64).map(|i| i * 1024).collect::<Vec<_>>();
+	let partitions = 8;
+
+	let assign = |hasher: SeededHasher| {
+		keys.iter()
+			.map(|key| {
+				let mut hasher = hasher.build_hasher();
+				key.hash(&mut hasher);
+				(hasher.finish() % partitions as u64) as usize
+			})
+			.collect::<Vec<_>>()
+	};
+	let assignment = assign(SeededHasher::new(42));
+	assert_eq!(assignment, assign(SeededHasher::new(42)));
+	assert_ne!(assignment, assign(SeededHasher::new(43)));
+	let mut sizes = vec![0; partitions];
+	for &partition in &assignment {
+		sizes[partition] += 1;
+	}
+	let mean = keys.len() / partitions;
+	for &size in &sizes {
+		assert!(size > mean * 9 / 10 && size < mean * 11 / 10, "{:?}", sizes);
+	}
+
+	let items = keys.iter().map(|&key| (key % 7, key)).collect::<Vec<_>>();
+	let res = items
+		.clone()
+		.into_par_stream()
+		.group_by_with_hasher(pool, Identity.sum::<u64>(), SeededHasher::new(42))
+		.await;
+	let expected = items
+		.into_par_stream()
+		.group_by(pool, Identity.sum::<u64>())
+		.await;
+	assert_eq!(res.len(), 7);
+	for (key, sum) in &expected {
+		assert_eq!(res[key], *sum);
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn nearest_geo_point() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let point = |lat, lon| GeoPoint::new(lat, lon).unwrap();
+	let cities = vec![
+		("Nashville", point(36.12, -86.67)),
+		("Los Angeles", point(33.94, -118.40)),
+		("London", point(51.5007, -0.1246)),
+		("Sydney", point(-33.8688, 151.2093)),
+		("Tokyo", point(35.6762, 139.6503)),
+	];
+	let nearest = |reference: GeoPoint| {
+		cities
+			.clone()
+			.into_par_stream()
+			.min_by_key(pool, move |(_, city): &(&str, GeoPoint)| {
+				city.distance(&reference)
+			})
+	};
+	// Paris, San Francisco and Auckland
+	assert_eq!(nearest(point(48.8566, 2.3522)).await.unwrap().0, "London");
+	assert_eq!(
+		nearest(point(37.7749, -122.4194)).await.unwrap().0,
+		"Los Angeles"
+	);
+	assert_eq!(
+		nearest(point(-36.8485, 174.7633)).await.unwrap().0,
+		"Sydney"
+	);
+
+	// As a Value, points are ordered by latitude then longitude
+	let mut values: Vec<Value> = cities.iter().map(|&(_, city)| city.into()).collect();
+	values.sort_by(AmadeusOrd::amadeus_cmp);
+	let lats = values
+		.into_iter()
+		.map(|value| value.into_geo_point().unwrap().lat())
+		.collect::<Vec<_>>();
+	assert_eq!(lats, [-33.8688, 33.94, 35.6762, 36.12, 51.5007]);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn group_by_union() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A union of an integer id and a string name, as an Avro union column might be
+	let key = |i: u32| match i % 3 {
+		0 => Value::Union(Union::new(0, Value::U32(i % 2))),
+		_ => Value::Union(Union::new(1, Value::String(format!("name{}", i % 2)))),
+	};
+	let counts = (0..300_u32)
+		.into_par_stream()
+		.map(move |i| (key(i), ()))
+		.group_by(pool, Identity.count())
+		.await;
+	let mut counts = counts.into_iter().collect::<Vec<_>>();
+	counts.sort_by(|(a, _), (b, _)| a.amadeus_cmp(b));
+	let expected = vec![
+		(Union::new(0, Value::U32(0)), 50),
+		(Union::new(0, Value::U32(1)), 50),
+		(Union::new(1, Value::String(String::from("name0"))), 100),
+		(Union::new(1, Value::String(String::from("name1"))), 100),
+	];
+	let expected = expected
+		.into_iter()
+		.map(|(union, count)| (Value::Union(union), count))
+		.collect::<Vec<_>>();
+	assert_eq!(counts, expected);
+
+	// Round trips through serde with its discriminant
+	for (value, _) in expected {
+		let json = serde_json::to_string(&value).unwrap();
+		assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn group_by_uuid() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Rows keyed by one of 5 UUIDs, as read from a fixed_len_byte_array(16) column
+	let uuid = |i: u8| Uuid::from_bytes([i; 16]);
+	let counts = (0..100_u8)
+		.into_par_stream()
+		.map(move |i| (Value::Uuid(uuid(i % 5)), i))
+		.group_by(pool, Identity.count())
+		.await;
+	let mut counts = counts.into_iter().collect::<Vec<_>>();
+	counts.sort_by(|(a, _), (b, _)| a.amadeus_cmp(b));
+	let expected = (0..5)
+		.map(|i| (Value::Uuid(uuid(i)), 20))
+		.collect::<Vec<_>>();
+	assert_eq!(counts, expected);
+	assert_eq!(
+		counts[1].0.as_uuid().unwrap().to_string(),
+		"01010101-0101-0101-0101-010101010101"
+	);
+}
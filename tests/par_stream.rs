@@ -0,0 +1,1714 @@
+use either::Either;
+use futures::{executor::block_on, future, stream, StreamExt};
+use std::{
+	collections::HashMap, fs, io, pin::Pin, sync::{
+		atomic::{AtomicUsize, Ordering}, Arc, Mutex
+	}, task::{Context, Poll}, time::{Duration, Instant}
+};
+
+use amadeus::{
+	amadeus_core::{par_pipe::PipeTask, pipe::Pipe}, data::{CoerceError, CoercePolicy, Schema}, par_sink::{Encoder, ParallelSink}, par_stream::{
+		Backoff, CheckpointStore, JoinType, KeySegment, PanicPolicy, Panicked, RunningCount, RunningMean, RunningSum, SamplePolicy, Throughput, TimedOut, TimeoutPolicy
+	}, pool::Scheduling, prelude::*, DistributedStream, IntoDistributedStream
+};
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn step_by() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A single partition containing all of the items
+	let res: Vec<usize> = vec![(0..100).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.step_by(3)
+		.collect(pool)
+		.await;
+	assert_eq!(res, (0..100).step_by(3).collect::<Vec<_>>());
+
+	// Each item is its own partition, so the counter restarts for each
+	let res: usize = (0..100).par().step_by(3).count(pool).await;
+	assert_eq!(res, 100);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn sample_per_interval() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A reading every 300ms for 10s, in a single partition
+	let events = (0..34_u64)
+		.map(|i| (Duration::from_millis(i * 300), i))
+		.collect::<Vec<_>>();
+	let second = |i: u64| i * 300 / 1000;
+	let sample = |policy| {
+		vec![events.clone()]
+			.into_par_stream()
+			.flat_map(stream::iter)
+			.sample_per_interval(
+				|&(timestamp, _): &(Duration, u64)| timestamp,
+				Duration::from_secs(1),
+				policy,
+			)
+			.map(|(_, i): (Duration, u64)| i)
+			.collect::<_, Vec<u64>>(pool)
+	};
+
+	let first = sample(SamplePolicy::First).await;
+	assert_eq!(first, vec![0, 4, 7, 10, 14, 17, 20, 24, 27, 30]);
+	assert_eq!(
+		first,
+		(0..34)
+			.filter(|&i| i == 0 || second(i) != second(i - 1))
+			.collect::<Vec<_>>()
+	);
+	let last = sample(SamplePolicy::Last).await;
+	assert_eq!(
+		last,
+		(0..34)
+			.filter(|&i| i == 33 || second(i) != second(i + 1))
+			.collect::<Vec<_>>()
+	);
+	assert_eq!(last.len(), 10);
+
+	// Intervals are per partition
+	let count = vec![events.clone(), events]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.sample_per_interval(
+			|&(timestamp, _): &(Duration, u64)| timestamp,
+			Duration::from_secs(1),
+			SamplePolicy::First,
+		)
+		.count(pool)
+		.await;
+	assert_eq!(count, 20);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn interleave() {
+	let pool = &ThreadPool::new(Some(1), Some(1)).unwrap();
+
+	let res: Vec<u32> = (0..50)
+		.par()
+		.interleave((100..130).par())
+		.collect(pool)
+		.await;
+
+	let mut sorted = res.clone();
+	sorted.sort_unstable();
+	assert_eq!(sorted, (0..50).chain(100..130).collect::<Vec<_>>());
+
+	// Both sources contribute evenly until the shorter is exhausted
+	let from_b = res[..40].iter().filter(|&&i| i >= 100).count();
+	assert!((15..=25).contains(&from_b), "{}", from_b);
+	assert!(res[60..].iter().all(|&i| i < 100));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn map_ref() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Not `Clone`, so the items can only be passed through, not copied
+	#[derive(PartialEq, Debug)]
+	struct Text(String);
+
+	let res: Vec<(usize, Text)> = (0..100)
+		.map(|i| Text("a".repeat(i)))
+		.collect::<Vec<_>>()
+		.into_par_stream()
+		.map_ref(|text: &Text| text.0.len())
+		.collect(pool)
+		.await;
+	assert_eq!(res.len(), 100);
+	for (length, text) in &res {
+		assert_eq!(*length, text.0.len());
+	}
+
+	let res: Vec<(usize, Text)> = vec![Text("ab".to_owned()), Text("abc".to_owned())]
+		.into_par_stream()
+		.pipe(pool, Identity.map_ref(|text: &Text| text.0.len()).collect())
+		.await;
+	assert_eq!(
+		res,
+		[(2, Text("ab".to_owned())), (3, Text("abc".to_owned()))]
+	);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn chunk_by() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let items = vec![1, 3, 5, 2, 4, 7, 6, 8, 10, 12, 9];
+	let res: Vec<(u32, Vec<u32>)> = vec![items.clone()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.chunk_by(|i: &u32| i % 2)
+		.collect(pool)
+		.await;
+	assert_eq!(
+		res,
+		[
+			(1, vec![1, 3, 5]),
+			(0, vec![2, 4]),
+			(1, vec![7]),
+			(0, vec![6, 8, 10, 12]),
+			(1, vec![9]),
+		]
+	);
+
+	// Runs don't span partitions
+	let res: usize = items
+		.into_par_stream()
+		.chunk_by(|i: &u32| i % 2)
+		.count(pool)
+		.await;
+	assert_eq!(res, 11);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn batch_by_bytes() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Strings of varying lengths, including one longer than the limit
+	let items = (0..200_usize)
+		.map(|i| "x".repeat(if i == 100 { 500 } else { i * 7 % 30 }))
+		.collect::<Vec<_>>();
+	let batches: Vec<Vec<String>> = vec![items.clone()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.batch_by_bytes(100, String::len)
+		.collect(pool)
+		.await;
+	for batch in &batches {
+		let bytes = batch.iter().map(String::len).sum::<usize>();
+		assert!(!batch.is_empty());
+		assert!(bytes <= 100 || batch.len() == 1, "{}", bytes);
+	}
+	assert!(batches.contains(&vec!["x".repeat(500)]));
+	// Each batch is as full as it can be
+	for (batch, next) in batches.iter().zip(&batches[1..]) {
+		let bytes = batch.iter().map(String::len).sum::<usize>();
+		assert!(bytes + next[0].len() > 100);
+	}
+	assert_eq!(batches.into_iter().flatten().collect::<Vec<_>>(), items);
+
+	// Batches don't span partitions
+	let res: usize = (0..10_u32)
+		.into_par_stream()
+		.batch_by_bytes(1000, |_: &u32| 1)
+		.count(pool)
+		.await;
+	assert_eq!(res, 10);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn ordered() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let expected = (0..1000_u32)
+		.step_by(3)
+		.map(|i| i * 2)
+		.filter(|i| i % 10 < 7)
+		.collect::<Vec<_>>();
+
+	// A single partition
+	let res: Vec<u32> = vec![(0..1000_u32).collect::<Vec<_>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.ordered()
+		.step_by(3)
+		.map(|i: u32| i * 2)
+		.filter(|i: &u32| i % 10 < 7)
+		.collect(pool)
+		.await;
+	assert_eq!(res, expected);
+
+	// Many partitions are run in order as one
+	let res: Vec<u32> = (0..1000_u32)
+		.into_par_stream()
+		.ordered()
+		.step_by(3)
+		.map(|i: u32| i * 2)
+		.filter(|i: &u32| i % 10 < 7)
+		.collect(pool)
+		.await;
+	assert_eq!(res, expected);
+
+	let res: Vec<(u32, Vec<u32>)> = (0..100_u32)
+		.into_par_stream()
+		.ordered()
+		.chunk_by(|i: &u32| i / 10)
+		.collect(pool)
+		.await;
+	assert_eq!(
+		res,
+		(0..10)
+			.map(|k| (k, (k * 10..(k + 1) * 10).collect()))
+			.collect::<Vec<_>>()
+	);
+}
+
+/// A pipe that forwards its input, and panics if polled again once finished.
+#[derive(Clone)]
+struct PanicOnRepoll;
+impl ParallelPipe<u32> for PanicOnRepoll {
+	type Output = u32;
+	type Task = PanicOnRepollTask;
+
+	fn task(&self) -> Self::Task {
+		PanicOnRepollTask(false)
+	}
+}
+struct PanicOnRepollTask(bool);
+impl PipeTask<u32> for PanicOnRepollTask {
+	type Output = u32;
+	type Async = Self;
+
+	fn into_async(self) -> Self::Async {
+		self
+	}
+}
+impl Pipe<u32> for PanicOnRepollTask {
+	type Output = u32;
+
+	fn poll_next(
+		mut self: Pin<&mut Self>, cx: &mut Context,
+		stream: Pin<&mut impl futures::Stream<Item = u32>>,
+	) -> Poll<Option<Self::Output>> {
+		assert!(!self.0, "polled after completion");
+		let item = futures::ready!(stream.poll_next(cx));
+		self.0 = item.is_none();
+		Poll::Ready(item)
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn fuse() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let mut task = PanicOnRepoll.fuse().task().into_async();
+	let mut task = Pin::new(&mut task);
+	let mut input = stream::iter(vec![1, 2]);
+	let mut input = Pin::new(&mut input);
+	let mut poll = || {
+		block_on(future::poll_fn(|cx| {
+			task.as_mut().poll_next(cx, input.as_mut())
+		}))
+	};
+	assert_eq!(poll(), Some(1));
+	assert_eq!(poll(), Some(2));
+	assert_eq!(poll(), None);
+	// The wrapped pipe isn't polled again
+	assert_eq!(poll(), None);
+
+	let res: Vec<u32> = (0..10_u32)
+		.into_par_stream()
+		.pipe(pool, PanicOnRepoll.fuse().collect())
+		.await;
+	assert_eq!(res.len(), 10);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn sample_fraction() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let n = 100_000_u32;
+	let sample = |seed| async move {
+		let mut res: Vec<u32> = (0..100)
+			.into_par_stream()
+			.flat_map(move |i: u32| stream::iter(i * n / 100..(i + 1) * n / 100))
+			.sample_fraction(0.1, seed)
+			.collect(pool)
+			.await;
+		res.sort_unstable();
+		res
+	};
+
+	let a = sample(0).await;
+	// Each item is kept with probability 0.1, so the stddev of the count is ~95
+	assert!((9_500..=10_500).contains(&a.len()), "{}", a.len());
+	assert_eq!(a, sample(0).await);
+	assert_ne!(a, sample(1).await);
+
+	let res: Vec<u32> = (0..1000_u32)
+		.into_par_stream()
+		.sample_fraction(0.0, 0)
+		.collect(pool)
+		.await;
+	assert!(res.is_empty());
+
+	// Through a pipe, identical partitions are still sampled differently, so
+	// some items are kept from only one of them
+	let res: Vec<u32> = vec![(0..1000).collect::<Vec<u32>>(); 2]
+		.into_par_stream()
+		.pipe(
+			pool,
+			Identity
+				.flat_map(stream::iter)
+				.sample_fraction(0.5, 0)
+				.collect(),
+		)
+		.await;
+	let mut counts = HashMap::new();
+	for i in res {
+		*counts.entry(i).or_insert(0) += 1;
+	}
+	assert!(counts.values().any(|&count| count == 1));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn timeout() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Every 7th item never completes
+	let work = |i: u32| async move {
+		if i % 7 == 3 {
+			future::pending::<()>().await;
+		}
+		i
+	};
+
+	let mut res: Vec<Result<u32, TimedOut>> = (0..20_u32)
+		.into_par_stream()
+		.map(work)
+		.timeout(Duration::from_millis(100), TimeoutPolicy::Error)
+		.collect(pool)
+		.await;
+	res.sort_by_key(|res| *res.as_ref().unwrap_or(&u32::MAX));
+	let expected = (0..20)
+		.filter(|i| i % 7 != 3)
+		.map(Ok)
+		.chain(vec![Err(TimedOut); 3])
+		.collect::<Vec<_>>();
+	assert_eq!(res, expected);
+
+	let mut res: Vec<u32> = (0..20_u32)
+		.into_par_stream()
+		.pipe(
+			pool,
+			Identity
+				.map(work)
+				.timeout(Duration::from_millis(100), TimeoutPolicy::Drop)
+				.map(Result::unwrap)
+				.collect(),
+		)
+		.await;
+	res.sort_unstable();
+	assert_eq!(res, (0..20).filter(|i| i % 7 != 3).collect::<Vec<_>>());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn retry() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A flaky API that fails the first two requests for each item
+	let calls = Arc::new(Mutex::new(HashMap::new()));
+	let work = {
+		let calls = calls.clone();
+		move |i: &u32| {
+			let i = *i;
+			let mut calls = calls.lock().unwrap();
+			let call = calls.entry(i).or_insert(0);
+			*call += 1;
+			let res = if *call > 2 { Ok(i) } else { Err(*call) };
+			async move { res }
+		}
+	};
+	let backoff = Backoff::Exponential {
+		initial: Duration::from_millis(1),
+		max: Duration::from_millis(10),
+	};
+
+	let mut res: Vec<Result<u32, u32>> = (0..20_u32)
+		.into_par_stream()
+		.retry(3, backoff, work.clone())
+		.collect(pool)
+		.await;
+	res.sort();
+	assert_eq!(res, (0..20).map(Ok).collect::<Vec<_>>());
+	assert!(calls.lock().unwrap().values().all(|&calls| calls == 3));
+
+	// Giving up after two attempts yields the error of the second
+	calls.lock().unwrap().clear();
+	let res: Vec<Result<u32, u32>> = (0..20_u32)
+		.into_par_stream()
+		.pipe(
+			pool,
+			Identity
+				.retry(2, Backoff::Constant(Duration::from_millis(1)), work)
+				.collect(),
+		)
+		.await;
+	assert_eq!(res, vec![Err(2); 20]);
+	assert!(calls.lock().unwrap().values().all(|&calls| calls == 2));
+
+	assert_eq!(backoff.delay(0), Duration::from_millis(1));
+	assert_eq!(backoff.delay(3), Duration::from_millis(8));
+	assert_eq!(backoff.delay(4), Duration::from_millis(10));
+	assert_eq!(backoff.delay(100), Duration::from_millis(10));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn collect_ordered() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Later partitions finish first, so an unordered collect would reorder them
+	let res: Vec<(u32, u32)> = (0..20_u32)
+		.into_par_stream()
+		.flat_map(|partition: u32| stream::iter((0..100).map(move |i| (partition, i))))
+		.map(|(partition, i): (u32, u32)| {
+			if i == 0 {
+				std::thread::sleep(Duration::from_millis(u64::from(20 - partition)));
+			}
+			(partition, i)
+		})
+		.filter(|&(_, i): &(u32, u32)| i % 7 != 3)
+		.collect_ordered(pool)
+		.await;
+	let expected = (0..20)
+		.flat_map(|partition| (0..100).map(move |i| (partition, i)))
+		.filter(|&(_, i)| i % 7 != 3)
+		.collect::<Vec<_>>();
+	assert_eq!(res, expected);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn tee() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Clone)]
+	struct Lines;
+	impl<'a> Encoder<&'a u32> for Lines {
+		fn encode(&mut self, item: &'a u32, buf: &mut Vec<u8>) -> io::Result<()> {
+			buf.extend_from_slice(format!("{}\n", item).as_bytes());
+			Ok(())
+		}
+	}
+
+	let path = std::env::temp_dir().join("amadeus-tee");
+	let _ = fs::remove_file(&path);
+
+	let path_ = path.clone();
+	let mut res: Vec<u32> = (0..1000_u32)
+		.into_par_stream()
+		.tee(
+			move || {
+				let file = fs::OpenOptions::new()
+					.create(true)
+					.append(true)
+					.open(&path_);
+				io::BufWriter::new(file.unwrap())
+			},
+			Lines,
+		)
+		.map(|i: u32| i * 2)
+		.collect(pool)
+		.await;
+	res.sort_unstable();
+	assert_eq!(res, (0..1000).map(|i| i * 2).collect::<Vec<_>>());
+
+	// Every item was written, before the map
+	let file = fs::read_to_string(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	let mut teed = file
+		.lines()
+		.map(|line| line.parse().unwrap())
+		.collect::<Vec<u32>>();
+	teed.sort_unstable();
+	assert_eq!(teed, (0..1000).collect::<Vec<_>>());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn zip_with_index() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Partitions of differing lengths, some longer than a block of IDs
+	let mut res: Vec<(u64, (u32, u32))> = (0..50_u32)
+		.into_par_stream()
+		.flat_map(|partition: u32| stream::iter((0..partition * 100).map(move |i| (partition, i))))
+		.zip_with_index()
+		.collect(pool)
+		.await;
+	assert_eq!(res.len(), (0..50).map(|i| i * 100).sum::<usize>());
+
+	let mut ids = res.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+	ids.sort_unstable();
+	ids.dedup();
+	assert_eq!(ids.len(), res.len());
+
+	// Within each partition, IDs increase with the order of the items
+	res.sort_by_key(|(_, item)| *item);
+	for pair in res.windows(2) {
+		if (pair[0].1).0 == (pair[1].1).0 {
+			assert!(pair[0].0 < pair[1].0);
+		}
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn coerce_schema() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Rows as read from a CSV file, with every field a string
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct CsvRow {
+		name: String,
+		age: String,
+		height: String,
+		born: String,
+	}
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Person {
+		name: String,
+		age: Option<u32>,
+		height: f64,
+		born: DateWithoutTimezone,
+	}
+	let row = |name: &str, age: &str, height: &str, born: &str| {
+		Value::from(CsvRow {
+			name: name.to_owned(),
+			age: age.to_owned(),
+			height: height.to_owned(),
+			born: born.to_owned(),
+		})
+	};
+	let rows = vec![
+		row("alice", "30", "1.7", "1990-01-31"),
+		row("bob", "", "2", "1985-06-01"),
+		row("carol", "forty", "1.6", "1980-12-25"),
+	];
+	let names = rows[0].as_group().unwrap().field_names().cloned();
+	let schema = Schema::Group(
+		vec![
+			Schema::String,
+			Schema::Option(Box::new(Schema::U32)),
+			Schema::F64,
+			Schema::DateWithoutTimezone,
+		],
+		names,
+	);
+	let person = |name: &str, age, height, (year, month, day)| Person {
+		name: name.to_owned(),
+		age,
+		height,
+		born: DateWithoutTimezone::new(year, month, day).unwrap(),
+	};
+
+	let mut res: Vec<Person> = rows
+		.clone()
+		.into_par_stream()
+		.map({
+			let schema = schema.clone();
+			move |row: Value| schema.coerce(row, CoercePolicy::Null).unwrap()
+		})
+		.map(|row: Value| row.downcast().unwrap())
+		.collect(pool)
+		.await;
+	res.sort_by(|a, b| a.name.cmp(&b.name));
+	assert_eq!(
+		res,
+		vec![
+			person("alice", Some(30), 1.7, (1990, 1, 31)),
+			person("bob", None, 2.0, (1985, 6, 1)),
+			person("carol", None, 1.6, (1980, 12, 25)),
+		]
+	);
+
+	let res: Vec<Result<Value, CoerceError>> = rows
+		.into_dist_stream()
+		.map(amadeus::data::coerce_schema(
+			schema.clone(),
+			CoercePolicy::Error,
+		))
+		.collect(pool)
+		.await;
+	let errors = res.into_iter().filter_map(Result::err).collect::<Vec<_>>();
+	assert_eq!(
+		errors,
+		vec![CoerceError {
+			value: String::from("String(\"forty\")"),
+			schema: String::from("U32"),
+		}]
+	);
+
+	// Integers are coerced into floats
+	assert_eq!(
+		Schema::F64.coerce(Value::U8(2), CoercePolicy::Error),
+		Ok(Value::F64(2.0))
+	);
+	assert!(Schema::U8
+		.coerce(Value::U16(256), CoercePolicy::Error)
+		.is_err());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn buffered() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A single partition, so that items are consumed in order
+	let produced = Arc::new(AtomicUsize::new(0));
+	let max_ahead = Arc::new(AtomicUsize::new(0));
+	vec![(0..1000).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.inspect({
+			let produced = produced.clone();
+			move |_: &usize| {
+				let _ = produced.fetch_add(1, Ordering::SeqCst);
+			}
+		})
+		.buffered(16)
+		.for_each(pool, {
+			let (produced, max_ahead) = (produced.clone(), max_ahead.clone());
+			move |i: usize| {
+				// A slow consumer
+				std::thread::sleep(Duration::from_micros(100));
+				let ahead = produced.load(Ordering::SeqCst) - (i + 1);
+				let _ = max_ahead.fetch_max(ahead, Ordering::SeqCst);
+			}
+		})
+		.await;
+	assert_eq!(produced.load(Ordering::SeqCst), 1000);
+	// Upstream runs ahead by no more than the capacity of the buffer
+	assert!(max_ahead.load(Ordering::SeqCst) <= 16);
+
+	// Upstream is polled while downstream is waiting
+	let produced = Arc::new(AtomicUsize::new(0));
+	let res: Vec<(usize, usize)> = vec![(0..100).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.inspect({
+			let produced = produced.clone();
+			move |_: &usize| {
+				let _ = produced.fetch_add(1, Ordering::SeqCst);
+			}
+		})
+		.buffered(4)
+		.map_async({
+			let produced = produced.clone();
+			move |i: usize| {
+				let produced = produced.clone();
+				async move {
+					let before = produced.load(Ordering::SeqCst);
+					let _ = tokio::task::yield_now().await;
+					(i, produced.load(Ordering::SeqCst) - before)
+				}
+			}
+		})
+		.collect(pool)
+		.await;
+	assert_eq!(
+		res.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+		(0..100).collect::<Vec<_>>()
+	);
+	assert!(res.iter().any(|&(_, progressed)| progressed > 0));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn buffered_read_ahead() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A single partition from a latent source, with equally slow processing
+	let run = |window: Option<usize>| async move {
+		let read = Arc::new(AtomicUsize::new(0));
+		let stream = vec![(0..20).collect::<Vec<usize>>()]
+			.into_par_stream()
+			.flat_map(|items: Vec<usize>| {
+				stream::iter(items).then(|i| async move {
+					let _ = tokio::task::yield_now().await;
+					i
+				})
+			})
+			.inspect({
+				let read = read.clone();
+				move |_: &usize| {
+					let _ = read.fetch_add(1, Ordering::SeqCst);
+				}
+			});
+		// How many items had been read ahead of each once it was processed
+		let process = move |i: usize| {
+			let read = read.clone();
+			async move {
+				for _ in 0..3 {
+					let _ = tokio::task::yield_now().await;
+				}
+				(i, read.load(Ordering::SeqCst) - (i + 1))
+			}
+		};
+		let res: Vec<(usize, usize)> = match window {
+			Some(window) => {
+				stream
+					.buffered(window)
+					.map_async(process)
+					.collect(pool)
+					.await
+			}
+			None => stream.map_async(process).collect(pool).await,
+		};
+		assert_eq!(
+			res.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+			(0..20).collect::<Vec<_>>()
+		);
+		res.iter().map(|&(_, ahead)| ahead).max().unwrap()
+	};
+	assert_eq!(run(None).await, 0);
+	// Reads overlap with processing, without running more than the window ahead
+	let ahead = run(Some(2)).await;
+	assert!(ahead > 0 && ahead <= 2, "{:?}", ahead);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn catch_panics() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let double = |i: u32| {
+		if i == 42 {
+			panic!("bad item {}", i);
+		}
+		i * 2
+	};
+
+	// A single partition, which ends at the panic
+	let partition = || vec![(0..100).collect::<Vec<u32>>()].into_par_stream();
+	let res: Vec<Result<u32, Panicked>> = partition()
+		.flat_map(stream::iter)
+		.map(double)
+		.catch_panics(PanicPolicy::Error)
+		.collect(pool)
+		.await;
+	let expected = (0..42)
+		.map(|i| Ok(i * 2))
+		.chain(vec![Err(Panicked {
+			message: Some(String::from("bad item 42")),
+		})])
+		.collect::<Vec<_>>();
+	assert_eq!(res, expected);
+
+	let res: Vec<u32> = partition()
+		.flat_map(stream::iter)
+		.map(double)
+		.catch_panics(PanicPolicy::Drop)
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	assert_eq!(res, (0..42).map(|i| i * 2).collect::<Vec<_>>());
+
+	// Each item is its own partition, so only the one that panicked is lost
+	let mut res: Vec<u32> = (0..100_u32)
+		.into_par_stream()
+		.map(double)
+		.catch_panics(PanicPolicy::Drop)
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	res.sort_unstable();
+	assert_eq!(
+		res,
+		(0..100)
+			.filter(|&i| i != 42)
+			.map(|i| i * 2)
+			.collect::<Vec<_>>()
+	);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn inspect_err() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let parse = |i: u32| if i % 7 == 3 { Err(i) } else { Ok(i) };
+	let errors = Arc::new(Mutex::new(Vec::new()));
+	let mut res: Vec<Result<u32, u32>> = (0..100_u32)
+		.into_par_stream()
+		.map(parse)
+		.inspect_err({
+			let errors = errors.clone();
+			move |err: &u32| errors.lock().unwrap().push(*err)
+		})
+		.collect(pool)
+		.await;
+	let mut expected = (0..100).map(parse).collect::<Vec<_>>();
+	res.sort_unstable();
+	expected.sort_unstable();
+	assert_eq!(res, expected);
+	let mut errors = errors.lock().unwrap().clone();
+	errors.sort_unstable();
+	assert_eq!(errors, (3..100).step_by(7).collect::<Vec<_>>());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn observe_throughput() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let (input, output) = (Throughput::new(), Throughput::new());
+	let res: usize = (0..10_000_u32)
+		.map(|i| i.to_string())
+		.par()
+		.observe_throughput(&input, String::len)
+		.filter(|s: &String| s.ends_with('7'))
+		.observe_throughput(&output, String::len)
+		.count(pool)
+		.await;
+	assert_eq!(res, 1000);
+
+	let input = input.snapshot();
+	assert_eq!(input.items, 10_000);
+	assert_eq!(
+		input.bytes,
+		(0..10_000_u32)
+			.map(|i| i.to_string().len() as u64)
+			.sum::<u64>()
+	);
+	assert!(input.items_per_sec() > 0.0 && input.bytes_per_sec() > 0.0);
+
+	let output = output.snapshot();
+	assert_eq!(output.items, 1000);
+	assert!(output.items_per_sec() > 0.0);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn reduce_tree() {
+	// More threads than cores, for a partial result per thread to combine
+	let pool = &ThreadPool::new(Some(16), None).unwrap();
+
+	let partitions = (0..1000_u64)
+		.map(|i| (i * 100..(i + 1) * 100).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	let (_, reduce_a, _) = ParallelSink::<u64>::reducers(Identity.sum::<u64>());
+	for &arity in &[2, 3, 8, 16, 1000] {
+		let sum = partitions
+			.clone()
+			.into_par_stream()
+			.flat_map(stream::iter)
+			.reduce_tree(pool, reduce_a.clone(), reduce_a.clone(), arity)
+			.await;
+		assert_eq!(sum, (0..100_000).sum::<u64>());
+	}
+
+	let sum = Vec::<u64>::new()
+		.into_par_stream()
+		.reduce_tree(pool, reduce_a.clone(), reduce_a, 2)
+		.await;
+	assert_eq!(sum, 0);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn watch() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let partitions = (0..16_u64)
+		.map(|i| (i * 10_000..(i + 1) * 10_000).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+
+	let (stream, watched) = partitions
+		.clone()
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.watch(RunningCount::new(), Duration::from_millis(1));
+	let snapshots = tokio::spawn(watched.collect::<Vec<u64>>());
+	let count = stream.count(pool).await;
+	let snapshots = snapshots.await.unwrap();
+	assert_eq!(count, 160_000);
+	assert_eq!(snapshots.last(), Some(&160_000));
+	assert!(snapshots.windows(2).all(|pair| pair[0] <= pair[1]));
+
+	let (stream, watched) = partitions
+		.clone()
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.watch(RunningSum::new(), Duration::from_millis(1));
+	let snapshots = tokio::spawn(watched.collect::<Vec<u64>>());
+	let sum = stream.sum::<_, u64>(pool).await;
+	assert_eq!(snapshots.await.unwrap().last(), Some(&sum));
+
+	let (stream, watched) = partitions
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.map(|i: u64| i as f64)
+		.watch(RunningMean::new(), Duration::from_millis(1));
+	let snapshots = tokio::spawn(watched.collect::<Vec<f64>>());
+	let mean = stream.mean(pool).await;
+	assert!((snapshots.await.unwrap().last().unwrap() - mean).abs() < 1e-6);
+
+	// A slow partition of fewer items than are merged at once is still
+	// published as it goes
+	let (stream, watched) = vec![(0..20_u64).collect::<Vec<_>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.map_async(|i: u64| async move {
+			tokio::time::delay_for(Duration::from_millis(5)).await;
+			i
+		})
+		.watch(RunningCount::new(), Duration::from_millis(1));
+	let snapshots = tokio::spawn(watched.collect::<Vec<u64>>());
+	assert_eq!(stream.count(pool).await, 20);
+	let snapshots = snapshots.await.unwrap();
+	assert!(
+		snapshots.iter().any(|&count| 0 < count && count < 20),
+		"{:?}",
+		snapshots
+	);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn group_by_window() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// (user, seconds)
+	let events = vec![
+		("a", 1),
+		("b", 3),
+		("a", 7),
+		("a", 12),
+		("b", 14),
+		("b", 25),
+		("a", 29),
+	];
+	let timestamp = |&secs: &u64| Duration::from_secs(secs);
+	let secs = Duration::from_secs;
+
+	let mut res: Vec<((Duration, &str), u64)> = events
+		.clone()
+		.into_par_stream()
+		.group_by_window(pool, timestamp, secs(10), secs(10), Identity.sum())
+		.await
+		.into_iter()
+		.collect();
+	res.sort();
+	assert_eq!(
+		res,
+		vec![
+			((secs(0), "a"), 8),
+			((secs(0), "b"), 3),
+			((secs(10), "a"), 12),
+			((secs(10), "b"), 14),
+			((secs(20), "a"), 29),
+			((secs(20), "b"), 25),
+		]
+	);
+
+	// Overlapping windows, with events after the first 5s landing in two
+	let mut res: Vec<((Duration, &str), usize)> = events
+		.into_par_stream()
+		.group_by_window(pool, timestamp, secs(10), secs(5), Identity.count())
+		.await
+		.into_iter()
+		.collect();
+	res.sort();
+	assert_eq!(
+		res,
+		vec![
+			((secs(0), "a"), 2),
+			((secs(0), "b"), 1),
+			((secs(5), "a"), 2),
+			((secs(5), "b"), 1),
+			((secs(10), "a"), 1),
+			((secs(10), "b"), 1),
+			((secs(20), "a"), 1),
+			((secs(20), "b"), 1),
+			((secs(25), "a"), 1),
+			((secs(25), "b"), 1),
+		]
+	);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn collect_into() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let mut res: Vec<usize> = Vec::with_capacity(1000);
+	let (capacity, ptr) = (res.capacity(), res.as_ptr());
+
+	(0..100).par().collect_into(pool, &mut res).await;
+	(100..200)
+		.par()
+		.map(|i: usize| i)
+		.collect_into(pool, &mut res)
+		.await;
+	res.sort_unstable();
+	assert_eq!(res, (0..200).collect::<Vec<_>>());
+	assert_eq!((res.capacity(), res.as_ptr()), (capacity, ptr));
+
+	// Clearing between runs reuses the allocation
+	res.clear();
+	(0..10).par().collect_into(pool, &mut res).await;
+	res.sort_unstable();
+	assert_eq!(res, (0..10).collect::<Vec<_>>());
+	assert_eq!((res.capacity(), res.as_ptr()), (capacity, ptr));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn flat_map_buffered_ordered() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Later items' streams complete first, but their output is held back
+	let active = Arc::new(AtomicUsize::new(0));
+	let max_active = Arc::new(AtomicUsize::new(0));
+	let res: Vec<usize> = vec![(0..20).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.flat_map_buffered_ordered(
+			{
+				let (active, max_active) = (active.clone(), max_active.clone());
+				move |i: usize| {
+					let (active, max_active) = (active.clone(), max_active.clone());
+					stream::once(async move {
+						let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+						let _ = max_active.fetch_max(now, Ordering::SeqCst);
+						tokio::time::delay_for(Duration::from_millis(20 - i as u64)).await;
+						let _ = active.fetch_sub(1, Ordering::SeqCst);
+						stream::iter(vec![i * 2, i * 2 + 1])
+					})
+					.flatten()
+				}
+			},
+			4,
+		)
+		.collect(pool)
+		.await;
+	assert_eq!(res, (0..40).collect::<Vec<_>>());
+	assert_eq!(max_active.load(Ordering::SeqCst), 4);
+
+	// A later stream that's always ready buffers no more than 1024 items while
+	// the oldest is pending
+	let produced = Arc::new(AtomicUsize::new(0));
+	let res: Vec<usize> = vec![vec![0, 1]]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.flat_map_buffered_ordered(
+			{
+				let produced = produced.clone();
+				move |i: usize| {
+					let produced = produced.clone();
+					if i == 0 {
+						stream::once(async move {
+							for _ in 0..5000 {
+								let _ = tokio::task::yield_now().await;
+							}
+							produced.load(Ordering::SeqCst)
+						})
+						.left_stream()
+					} else {
+						stream::iter(0..10_000)
+							.map(move |_| produced.fetch_add(1, Ordering::SeqCst))
+							.right_stream()
+					}
+				}
+			},
+			2,
+		)
+		.collect(pool)
+		.await;
+	assert_eq!(res.len(), 10_001);
+	assert!(res[0] <= 1024, "{:?}", res[0]);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn map_async() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let mut res: Vec<String> = (0..100_u64)
+		.into_par_stream()
+		.map_async(|i: u64| async move {
+			tokio::time::delay_for(Duration::from_micros(i % 5)).await;
+			format!("item {}", i)
+		})
+		.collect(pool)
+		.await;
+	res.sort_unstable();
+	let mut expected = (0..100).map(|i| format!("item {}", i)).collect::<Vec<_>>();
+	expected.sort_unstable();
+	assert_eq!(res, expected);
+
+	// Later items complete first, but are yielded in order, with no more than 4
+	// in flight at once
+	let active = Arc::new(AtomicUsize::new(0));
+	let max_active = Arc::new(AtomicUsize::new(0));
+	let res: Vec<usize> = vec![(0..20).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.map_async_buffered(
+			{
+				let (active, max_active) = (active.clone(), max_active.clone());
+				move |i: usize| {
+					let (active, max_active) = (active.clone(), max_active.clone());
+					async move {
+						let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+						let _ = max_active.fetch_max(now, Ordering::SeqCst);
+						tokio::time::delay_for(Duration::from_millis(20 - i as u64)).await;
+						let _ = active.fetch_sub(1, Ordering::SeqCst);
+						i * 2
+					}
+				}
+			},
+			4,
+		)
+		.collect(pool)
+		.await;
+	assert_eq!(res, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+	assert_eq!(max_active.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn deterministic() {
+	let pool = &ThreadPool::new(None, None).unwrap().deterministic();
+
+	// Partitions whose async stages take varying times, so complete out of order
+	let run = || async move {
+		let lines: Vec<String> = (0..20_u64)
+			.map(|partition| (0..20).map(|i| partition * 20 + i).collect::<Vec<_>>())
+			.collect::<Vec<_>>()
+			.into_par_stream()
+			.flat_map(stream::iter)
+			.map_async_buffered(
+				|i: u64| async move {
+					tokio::time::delay_for(Duration::from_millis(i * 7 % 5)).await;
+					format!("item {}", i)
+				},
+				4,
+			)
+			.collect(pool)
+			.await;
+		lines.join("\n").into_bytes()
+	};
+	let first = run().await;
+	for _ in 0..3 {
+		assert_eq!(run().await, first);
+	}
+	let mut lines = String::from_utf8(first)
+		.unwrap()
+		.lines()
+		.map(ToOwned::to_owned)
+		.collect::<Vec<_>>();
+	lines.sort_unstable();
+	let mut expected = (0..400).map(|i| format!("item {}", i)).collect::<Vec<_>>();
+	expected.sort_unstable();
+	assert_eq!(lines, expected);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn largest_first() {
+	// Two tasks, each processing its partitions one after another, and
+	// partitions of skewed cost with the largest first, so in order both
+	// large partitions are allocated to the first task
+	let run = |scheduling| async move {
+		let pool = &ThreadPool::new(Some(1), Some(2))
+			.unwrap()
+			.schedule(scheduling);
+		// The partitions each task was given
+		let mut tasks = vec![400_u64, 400, 20, 20]
+			.into_iter()
+			.par()
+			.cost_hint(|&cost| cost)
+			.fold(
+				pool,
+				Vec::new,
+				|mut tasks: Vec<Vec<u64>>, item: Either<u64, Vec<Vec<u64>>>| {
+					match item {
+						Either::Left(cost) => match tasks.last_mut() {
+							Some(task) => task.push(cost),
+							None => tasks.push(vec![cost]),
+						},
+						Either::Right(other) => tasks.extend(other),
+					}
+					tasks
+				},
+			)
+			.await;
+		for task in &mut tasks {
+			task.sort_unstable();
+		}
+		tasks.sort_unstable();
+		tasks
+	};
+	assert_eq!(
+		run(Scheduling::InOrder).await,
+		vec![vec![20, 20], vec![400, 400]]
+	);
+	assert_eq!(
+		run(Scheduling::LargestFirst).await,
+		vec![vec![20, 400], vec![20, 400]]
+	);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn largest_first_deterministic() {
+	// Deterministic pools ignore largest-first scheduling, so an order-sensitive
+	// sink sees the partitions in source order
+	let pool = &ThreadPool::new(Some(1), Some(2))
+		.unwrap()
+		.deterministic()
+		.schedule(Scheduling::LargestFirst);
+	let items: Vec<u64> = vec![1_u64, 2, 400, 401]
+		.into_iter()
+		.par()
+		.cost_hint(|&cost| cost)
+		.collect(pool)
+		.await;
+	assert_eq!(items, vec![1, 2, 400, 401]);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn split_at_key_boundaries() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Sorted by key, with keys repeated on both sides and missing from each
+	let left = (0..1000_u32).map(|i| (i / 3, i)).collect::<Vec<_>>();
+	let right = (0..800_u32)
+		.filter(|i| i % 7 != 0)
+		.map(|i| (i / 2, i))
+		.collect::<Vec<_>>();
+
+	let segments: Vec<KeySegment<u32, u32, u32>> = left
+		.clone()
+		.into_par_stream()
+		.split_at_key_boundaries(right.clone(), 100)
+		.collect(pool)
+		.await;
+	assert!(segments.len() > 1);
+	let mut ranges = segments
+		.iter()
+		.map(|segment| {
+			let keys = segment.left.iter().map(|(key, _)| *key);
+			let keys = keys.chain(segment.right.iter().map(|(key, _)| *key));
+			(keys.clone().min().unwrap(), keys.max().unwrap())
+		})
+		.collect::<Vec<_>>();
+	ranges.sort_unstable();
+	// Each key is in only one segment
+	assert!(ranges.windows(2).all(|ranges| ranges[0].1 < ranges[1].0));
+
+	// The inputs are read as segments are processed, rather than all up front
+	let pool_ = &ThreadPool::new(Some(1), Some(1)).unwrap();
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let (events_a, events_b) = (events.clone(), events.clone());
+	let _: Vec<KeySegment<u32, u32, u32>> = left
+		.clone()
+		.into_par_stream()
+		.inspect(move |_: &_| events_a.lock().unwrap().push("read"))
+		.split_at_key_boundaries(right.clone(), 100)
+		.inspect(move |_: &_| events_b.lock().unwrap().push("segment"))
+		.collect(pool_)
+		.await;
+	let events = events.lock().unwrap().clone();
+	let first_segment = events.iter().position(|&event| event == "segment");
+	let last_read = events.iter().rposition(|&event| event == "read");
+	assert!(first_segment.unwrap() < last_read.unwrap());
+
+	fn merge_join(segment: KeySegment<u32, u32, u32>) -> Vec<(u32, u32, u32)> {
+		let mut res = Vec::new();
+		let mut right = segment.right.as_slice();
+		for (key, v1) in segment.left {
+			while matches!(right.first(), Some((k, _)) if *k < key) {
+				right = &right[1..];
+			}
+			let matches = right.iter().take_while(|(k, _)| *k == key);
+			res.extend(matches.map(|&(_, v2)| (key, v1, v2)));
+		}
+		res
+	}
+	let mut merge_joined: Vec<(u32, u32, u32)> = left
+		.clone()
+		.into_par_stream()
+		.split_at_key_boundaries(right.clone(), 100)
+		.flat_map(|segment| stream::iter(merge_join(segment)))
+		.collect(pool)
+		.await;
+	merge_joined.sort_unstable();
+
+	let mut hash_joined: Vec<(u32, u32, u32)> = left
+		.into_par_stream()
+		.inner_join(right)
+		.flat_map(|(key, v1, v2)| {
+			let v1 = v1.collect::<Vec<u32>>();
+			let v2 = v2.collect::<Vec<u32>>();
+			let pairs = v1
+				.into_iter()
+				.flat_map(move |v1| v2.clone().into_iter().map(move |v2| (key, v1, v2)));
+			stream::iter(pairs.collect::<Vec<_>>())
+		})
+		.collect(pool)
+		.await;
+	hash_joined.sort_unstable();
+
+	assert!(!merge_joined.is_empty());
+	assert_eq!(merge_joined, hash_joined);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn merge_join() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Sorted by key, with keys repeated on both sides and missing from each
+	let left = (0..1000_u32)
+		.filter(|i| i % 11 != 0)
+		.map(|i| (i / 3, i))
+		.collect::<Vec<_>>();
+	let right = (0..800_u32)
+		.filter(|i| i % 7 != 0)
+		.map(|i| (i / 2, i))
+		.collect::<Vec<_>>();
+
+	// Hash joins of each side against the other, with unmatched items as None
+	async fn hash_join(
+		pool: &ThreadPool, left: Vec<(u32, u32)>, right: Vec<(u32, u32)>,
+	) -> Vec<(u32, Option<u32>, Option<u32>)> {
+		left.into_par_stream()
+			.left_join(right)
+			.flat_map(|(key, v1, v2)| {
+				let v2 = v2.map(Some).collect::<Vec<_>>();
+				let v2 = if v2.is_empty() { vec![None] } else { v2 };
+				stream::iter(v2.into_iter().map(move |v2| (key, Some(v1), v2)))
+			})
+			.collect(pool)
+			.await
+	}
+	let left_joined = hash_join(pool, left.clone(), right.clone()).await;
+	let right_joined = hash_join(pool, right.clone(), left.clone())
+		.await
+		.into_iter()
+		.map(|(key, v2, v1)| (key, v1, v2))
+		.collect::<Vec<_>>();
+	let inner_joined = left_joined
+		.iter()
+		.filter(|(_, _, v2)| v2.is_some())
+		.cloned()
+		.collect::<Vec<_>>();
+	let full_joined = left_joined
+		.iter()
+		.chain(right_joined.iter().filter(|(_, v1, _)| v1.is_none()))
+		.cloned()
+		.collect::<Vec<_>>();
+
+	let expected = vec![
+		(JoinType::Inner, inner_joined),
+		(JoinType::Left, left_joined),
+		(JoinType::Right, right_joined),
+		(JoinType::Full, full_joined),
+	];
+	for (join_type, mut expected) in expected {
+		let mut res: Vec<(u32, Option<u32>, Option<u32>)> = left
+			.clone()
+			.into_par_stream()
+			.merge_join(right.clone(), 100, join_type)
+			.collect(pool)
+			.await;
+		res.sort_unstable();
+		expected.sort_unstable();
+		assert!(!res.is_empty());
+		assert_eq!(res, expected, "{:?}", join_type);
+	}
+
+	// Rows are joined as the inputs are read, rather than once they all have been
+	let pool = &ThreadPool::new(Some(1), Some(1)).unwrap();
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let (events_a, events_b) = (events.clone(), events.clone());
+	let _: Vec<(u32, Option<u32>, Option<u32>)> = left
+		.into_par_stream()
+		.inspect(move |_: &_| events_a.lock().unwrap().push("read"))
+		.merge_join(right, 100, JoinType::Inner)
+		.inspect(move |_: &_| events_b.lock().unwrap().push("row"))
+		.collect(pool)
+		.await;
+	let events = events.lock().unwrap();
+	let first_row = events.iter().position(|&event| event == "row");
+	let last_read = events.iter().rposition(|&event| event == "read");
+	assert!(first_row.unwrap() < last_read.unwrap());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn checkpoint() {
+	let pool = &ThreadPool::new(Some(1), Some(1)).unwrap();
+
+	let path = std::env::temp_dir().join("amadeus-checkpoint");
+	let _ = std::fs::remove_file(&path);
+	let partitions = |n| {
+		(0..n)
+			.map(|partition| {
+				(0..10)
+					.map(move |i| partition * 10 + i)
+					.collect::<Vec<u32>>()
+			})
+			.collect::<Vec<_>>()
+	};
+
+	// A run that fails partway through partition 5 records the partitions that
+	// completed before it
+	let (pool_, path_) = (pool.clone(), path.clone());
+	let res = std::thread::spawn(move || {
+		let store = CheckpointStore::open(path_).unwrap();
+		block_on(
+			partitions(10)
+				.into_par_stream()
+				.checkpoint(store)
+				.flat_map(stream::iter)
+				.for_each(&pool_, |i: u32| assert_ne!(i, 55, "failed")),
+		);
+	})
+	.join();
+	assert!(res.is_err());
+	let store = CheckpointStore::open(&path).unwrap();
+	assert_eq!(store.completed(), &(0..5).collect());
+
+	// The restart processes only the partitions that didn't complete
+	let mut processed: Vec<u32> = partitions(10)
+		.into_par_stream()
+		.checkpoint(store)
+		.flat_map(stream::iter)
+		.collect(pool)
+		.await;
+	processed.sort_unstable();
+	assert_eq!(processed, (50..100).collect::<Vec<_>>());
+	assert_eq!(
+		CheckpointStore::open(&path).unwrap().completed(),
+		&(0..10).collect()
+	);
+
+	// A run with nothing left to do processes nothing
+	let store = CheckpointStore::open(&path).unwrap();
+	let processed: Vec<u32> = partitions(10)
+		.into_par_stream()
+		.checkpoint(store)
+		.flat_map(stream::iter)
+		.collect(pool)
+		.await;
+	assert!(processed.is_empty());
+	CheckpointStore::open(&path).unwrap().clear().unwrap();
+	assert!(!path.exists());
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn rate_limit() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// Many partitions share the one budget
+	let per_second = 200.0;
+	let interval = Duration::from_secs_f64(1.0 / per_second);
+	let times = Arc::new(Mutex::new(Vec::new()));
+	let start = Instant::now();
+	(0..60_u32)
+		.into_par_stream()
+		.rate_limit(per_second)
+		.for_each(pool, {
+			let times = times.clone();
+			move |_: u32| times.lock().unwrap().push(Instant::now())
+		})
+		.await;
+	let mut times = times.lock().unwrap().clone();
+	times.sort_unstable();
+	assert_eq!(times.len(), 60);
+	assert!(start.elapsed() >= interval * 59);
+	// No window holds many more items than the limit allows. Items are timed
+	// after they're released, so a window of 20 intervals is allowed to appear
+	// several intervals short when a thread is slow to run its first item.
+	for window in times.windows(21) {
+		assert!(
+			window[20] - window[0] >= interval * 15,
+			"{:?}",
+			window[20] - window[0]
+		);
+	}
+
+	let start = Instant::now();
+	let res: Vec<u32> = (0..20_u32)
+		.into_par_stream()
+		.pipe(
+			pool,
+			ParallelPipe::<u32>::rate_limit(Identity, per_second).collect(),
+		)
+		.await;
+	assert_eq!(res.len(), 20);
+	assert!(start.elapsed() >= interval * 19);
+
+	// Rates without a representable interval are rejected up front
+	for &per_second in &[0.0, -1.0, f64::NAN, 1e-30] {
+		let limited =
+			std::panic::catch_unwind(|| (0..1_u32).into_par_stream().rate_limit(per_second));
+		assert!(limited.is_err());
+	}
+}
+
+#[tokio::test]
+#[cfg_attr(miri, ignore)]
+async fn with_handle() {
+	// The test's own single-threaded runtime, as in an application embedding amadeus
+	let pool = &ThreadPool::with_handle(Some(2), None, &tokio::runtime::Handle::current()).unwrap();
+
+	let sum: u64 = (0..1000_u64)
+		.into_par_stream()
+		.map(|i: u64| i * 2)
+		.sum(pool)
+		.await;
+	assert_eq!(sum, (0..1000).map(|i| i * 2).sum::<u64>());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn with_handle_outside_runtime() {
+	let runtime = tokio::runtime::Builder::new()
+		.threaded_scheduler()
+		.enable_all()
+		.build()
+		.unwrap();
+
+	// Neither creating the pool nor awaiting a pipeline needs a current runtime
+	let pool = ThreadPool::with_handle(None, None, runtime.handle()).unwrap();
+	let sum: u64 = futures::executor::block_on(
+		(0..1000_u64)
+			.into_par_stream()
+			.map(|i: u64| i * 2)
+			.sum(&pool),
+	);
+	assert_eq!(sum, (0..1000).map(|i| i * 2).sum::<u64>());
+	drop(pool);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn flat_map_ordered_iter() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let expand = |i: usize| (0..i % 4).map(move |j| (i, j));
+	let expected = (0..100)
+		.enumerate()
+		.flat_map(|(index, i)| expand(i).map(move |out| (index, out)))
+		.collect::<Vec<_>>();
+
+	// A single partition containing all of the items, some of which expand to
+	// no outputs
+	let res: Vec<(usize, (usize, usize))> = vec![(0..100).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.flat_map_ordered_iter(expand)
+		.collect(pool)
+		.await;
+	assert_eq!(res, expected);
+
+	let res: Vec<(usize, (usize, usize))> = vec![(0..100).collect::<Vec<usize>>()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.pipe(pool, Identity.flat_map_ordered_iter(expand).collect())
+		.await;
+	assert_eq!(res, expected);
+
+	// Across partitions the outputs of each item share an index and are adjacent
+	let res: Vec<(usize, usize)> = (0..1000)
+		.into_par_stream()
+		.flat_map_ordered_iter(|i: usize| vec![i; i % 5])
+		.collect(pool)
+		.await;
+	assert_eq!(res.len(), (0..1000).map(|i| i % 5).sum::<usize>());
+	let mut runs = HashMap::new();
+	for (j, &(index, i)) in res.iter().enumerate() {
+		if j == 0 || res[j - 1].1 != i {
+			assert!(runs.insert(i, (index, i % 5)).is_none());
+		}
+		let (run_index, remaining) = runs.get_mut(&i).unwrap();
+		assert_eq!(*run_index, index);
+		*remaining -= 1;
+	}
+	assert!(runs.values().all(|&(_, remaining)| remaining == 0));
+}
+
+#[test]
+fn explain() {
+	let stream = (0..10)
+		.into_par_stream()
+		.map(|i: usize| i * 2)
+		.filter(|i: &usize| *i > 4)
+		.chain(vec![1, 2].into_par_stream());
+	let plan = stream.explain();
+	assert_eq!(plan.partitions, Some(12));
+	let stages = plan
+		.stages
+		.iter()
+		.map(|stage| &*stage.name)
+		.collect::<Vec<_>>();
+	assert_eq!(stages, ["IterParStream", "Map", "Filter", "Chain"]);
+	assert_eq!(
+		plan.stages[3].details,
+		[("with".to_owned(), "IterParStream (2 partitions)".to_owned())]
+	);
+	assert_eq!(plan.sink, None);
+
+	let plan = stream.explain_with(&Identity.map(|i: usize| i as u64).sum::<u64>());
+	assert_eq!(
+		plan.sink.as_deref(),
+		Some("Sum<Map<Identity, {{closure}}>, u64>")
+	);
+	assert_eq!(
+		plan.to_string(),
+		"IterParStream (12 partitions)\n-> Map\n-> Filter\n-> Chain\n   with: IterParStream (2 partitions)\n=> Sum<Map<Identity, {{closure}}>, u64>"
+	);
+
+	// Sources that don't know their length up front don't report partitions
+	let plan = (0..10).filter(|i| i % 2 == 0).par().explain();
+	assert_eq!(plan.partitions, None);
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn unique_ordered() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	// A single partition with non-adjacent duplicates
+	let items = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9, 3, 2];
+	let res: Vec<usize> = vec![items.clone()]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.unique_ordered()
+		.collect(pool)
+		.await;
+	assert_eq!(res, [3, 1, 4, 5, 9, 2, 6, 8, 7]);
+
+	let res: Vec<usize> = vec![items]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.pipe(
+			pool,
+			ParallelPipe::<usize>::unique_ordered(Identity).collect(),
+		)
+		.await;
+	assert_eq!(res, [3, 1, 4, 5, 9, 2, 6, 8, 7]);
+
+	// Repeats are only dropped within each partition
+	let mut res: Vec<usize> = vec![vec![1, 2, 1], vec![2, 2, 3]]
+		.into_par_stream()
+		.flat_map(stream::iter)
+		.unique_ordered()
+		.collect(pool)
+		.await;
+	res.sort_unstable();
+	assert_eq!(res, [1, 2, 2, 3]);
+}
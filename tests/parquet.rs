@@ -1028,3 +1028,103 @@ async fn parquet() {
 
 	println!("in {:?}", start.elapsed().unwrap());
 }
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn parquet_explain() {
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct StockBids {
+		bp1: Option<f64>,
+		bs1: Option<f64>,
+	}
+
+	let path = PathBuf::from("amadeus-testing/parquet/stock_simulated.parquet");
+	let rows = Parquet::<_, StockBids>::new(vec![path.clone(), path])
+		.await
+		.unwrap()
+		.filter_eq("bp1", 1.0);
+	let stream = rows
+		.par_stream()
+		.map(|row: Result<StockBids, _>| row.unwrap().bp1);
+	let plan = stream.explain();
+
+	assert_eq!(plan.partitions, Some(2));
+	assert_eq!(plan.stages[0].name, "Parquet");
+	let projection = plan.detail("projection").unwrap();
+	assert!(projection.contains("bp1") && projection.contains("bs1"));
+	assert!(!projection.contains("ap1"));
+	assert_eq!(
+		plan.detail("filters"),
+		Some(&*format!("bp1 = {:?}", Value::from(1.0)))
+	);
+	assert_eq!(plan.stages.len(), 2);
+	assert_eq!(plan.stages[1].name, "Map");
+
+	let plan = stream.explain_with(&Identity.count());
+	assert_eq!(plan.sink.as_deref(), Some("Count<Identity>"));
+	assert!(plan.to_string().starts_with("Parquet (2 partitions)\n"));
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn parquet_int96() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, Debug)]
+	struct Timestamps {
+		id: Option<i32>,
+		timestamp_col: Option<DateTime>,
+	}
+
+	// timestamp_col is INT96, as written by Impala
+	let path = PathBuf::from("amadeus-testing/parquet/alltypes_plain.parquet");
+	let read = |rows: Parquet<PathBuf, Timestamps>| async move {
+		let mut rows: Vec<_> = rows
+			.par_stream()
+			.map(|row: Result<Timestamps, _>| {
+				let row = row.unwrap();
+				let timestamp = row.timestamp_col.unwrap().as_chrono().unwrap();
+				(row.id.unwrap(), timestamp.timestamp_millis())
+			})
+			.collect(pool)
+			.await;
+		rows.sort_unstable();
+		rows
+	};
+
+	let rows = Parquet::new(path.clone()).await.unwrap();
+	assert_eq!(
+		read(rows).await,
+		[
+			(0, 1_230_768_000_000), // 2009-01-01T00:00:00Z
+			(1, 1_230_768_060_000),
+			(2, 1_233_446_400_000), // 2009-02-01T00:00:00Z
+			(3, 1_233_446_460_000),
+			(4, 1_235_865_600_000), // 2009-03-01T00:00:00Z
+			(5, 1_235_865_660_000),
+			(6, 1_238_544_000_000), // 2009-04-01T00:00:00Z
+			(7, 1_238_544_060_000),
+		]
+	);
+
+	// The same wall-clock times in Los Angeles, eight hours behind UTC until
+	// daylight saving time began on 2009-03-08, and seven after
+	let hour = 60 * 60 * 1000;
+	let rows = Parquet::new(path)
+		.await
+		.unwrap()
+		.int96_timezone(Timezone::from_name("America/Los_Angeles").unwrap());
+	assert_eq!(
+		read(rows).await,
+		[
+			(0, 1_230_768_000_000 + 8 * hour),
+			(1, 1_230_768_060_000 + 8 * hour),
+			(2, 1_233_446_400_000 + 8 * hour),
+			(3, 1_233_446_460_000 + 8 * hour),
+			(4, 1_235_865_600_000 + 8 * hour),
+			(5, 1_235_865_660_000 + 8 * hour),
+			(6, 1_238_544_000_000 + 7 * hour),
+			(7, 1_238_544_060_000 + 7 * hour),
+		]
+	);
+}
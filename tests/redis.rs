@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use amadeus::{amadeus_redis::_internal as redis, prelude::*};
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn redis() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let config = RedisConfig::new("redis://127.0.0.1:6379/0", None);
+	let mut connection = redis::Client::open("redis://127.0.0.1:6379/0")
+		.unwrap()
+		.get_connection()
+		.unwrap();
+	let _: () = redis::cmd("FLUSHDB").query(&mut connection).unwrap();
+
+	let res = (0..1000_u32)
+		.into_par_stream()
+		.map(|i: u32| (format!("amadeus:string:{}", i), (i * 2).to_string()))
+		.pipe(pool, RedisSet::new_with(config.clone(), 64))
+		.await;
+	assert_eq!(res, Ok(()));
+
+	let res = (0..100_u32)
+		.into_par_stream()
+		.map(|i: u32| {
+			let mut fields = HashMap::new();
+			let _ = fields.insert(String::from("name"), format!("person {}", i));
+			if i % 2 == 0 {
+				let _ = fields.insert(String::from("city"), format!("city {}", i));
+			}
+			(format!("amadeus:hash:{}", i), fields)
+		})
+		.pipe(pool, RedisSet::new(config.clone()))
+		.await;
+	assert_eq!(res, Ok(()));
+
+	let mut strings: Vec<(String, String)> =
+		Redis::<String>::new_with(config.clone(), "amadeus:string:*", 100)
+			.await
+			.unwrap()
+			.par_stream()
+			.map(Result::unwrap)
+			.collect(pool)
+			.await;
+	strings.sort();
+	let mut expected = (0..1000_u32)
+		.map(|i| (format!("amadeus:string:{}", i), (i * 2).to_string()))
+		.collect::<Vec<_>>();
+	expected.sort();
+	assert_eq!(strings, expected);
+
+	let mut people: Vec<(String, HashMap<String, String>)> =
+		Redis::<HashMap<String, String>>::new(config, "amadeus:hash:*")
+			.await
+			.unwrap()
+			.par_stream()
+			.map(Result::unwrap)
+			.collect(pool)
+			.await;
+	people.sort_by(|a, b| a.0.cmp(&b.0));
+	let mut expected = (0..100_u32)
+		.map(|i| {
+			let mut person = HashMap::new();
+			let _ = person.insert(String::from("name"), format!("person {}", i));
+			if i % 2 == 0 {
+				let _ = person.insert(String::from("city"), format!("city {}", i));
+			}
+			(format!("amadeus:hash:{}", i), person)
+		})
+		.collect::<Vec<_>>();
+	expected.sort_by(|a, b| a.0.cmp(&b.0));
+	assert_eq!(people, expected);
+}
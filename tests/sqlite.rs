@@ -0,0 +1,77 @@
+use std::{env, fs, process};
+
+use amadeus::{amadeus_sqlite::_internal as rusqlite, prelude::*};
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn sqlite() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	#[derive(Data, Clone, PartialEq, PartialOrd, Debug)]
+	struct Person {
+		id: i64,
+		name: String,
+		score: f64,
+		note: Option<String>,
+	}
+
+	let path = env::temp_dir().join(format!("amadeus-sqlite-{}.db", process::id()));
+	let _ = fs::remove_file(&path);
+	let people = (0..1000_i64)
+		.map(|id| Person {
+			id,
+			name: format!("person {}", id),
+			#[allow(clippy::cast_precision_loss)]
+			score: id as f64 / 4.0,
+			note: if id % 3 == 1 {
+				Some(format!("note {}", id))
+			} else {
+				None
+			},
+		})
+		.collect::<Vec<_>>();
+	{
+		let mut connection = rusqlite::Connection::open(&path).unwrap();
+		connection
+			.execute_batch(
+				"CREATE TABLE people (id INTEGER NOT NULL, name TEXT NOT NULL, score REAL NOT NULL, note TEXT);
+				CREATE TABLE people_copy (id INTEGER PRIMARY KEY, name TEXT NOT NULL, score REAL NOT NULL, note TEXT);",
+			)
+			.unwrap();
+		let transaction = connection.transaction().unwrap();
+		for person in &people {
+			let _ = transaction
+				.execute(
+					"INSERT INTO people (rowid, id, name, score, note) VALUES (?1 * ?1, ?1, ?2, ?3, ?4)",
+					rusqlite::params![person.id, person.name, person.score, person.note],
+				)
+				.unwrap();
+		}
+		transaction.commit().unwrap();
+	}
+
+	// The rowids are sparse, but each partition reads 64 rows
+	let rows = Sqlite::<Person>::new_with(path.clone(), "people", 64).unwrap();
+	let res = rows
+		.par_stream()
+		.map(Result::unwrap)
+		.pipe(
+			pool,
+			SqliteInsert::new_with(path.clone(), "people_copy", 100),
+		)
+		.await;
+	assert_eq!(res, Ok(()));
+
+	let mut copied: Vec<Person> = Sqlite::<Person>::new(path.clone(), "people_copy")
+		.unwrap()
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	copied.sort_by_key(|person| person.id);
+	assert_eq!(copied, people);
+
+	assert!(Sqlite::<Person>::new(path.clone(), "missing").is_err());
+
+	fs::remove_file(path).unwrap();
+}
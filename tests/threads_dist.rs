@@ -8,6 +8,8 @@ use std::{
 use tokio::time::delay_for as sleep;
 
 use amadeus::dist::prelude::*;
+#[cfg(feature = "constellation")]
+use amadeus::pool::{Bincode, BincodeVarint, Codec};
 
 fn main() {
 	if cfg!(miri) {
@@ -35,6 +37,23 @@ fn main() {
 			#[cfg(not(feature = "constellation"))]
 			let process_pool_time = "-";
 
+			#[cfg(feature = "constellation")]
+			{
+				let bincode =
+					ProcessPool::with_codec(None, None, None, Resources::default(), Bincode)
+						.unwrap();
+				let varint =
+					ProcessPool::with_codec(None, None, None, Resources::default(), BincodeVarint)
+						.unwrap();
+				assert_eq!(varint.codec(), BincodeVarint);
+				let sums = [codecs(&bincode).await, codecs(&varint).await];
+				assert_eq!(sums[0], sums[1]);
+				assert_eq!(
+					sums[0],
+					(0..10).map(|i| 500_000 * 999_999 + i).collect::<Vec<_>>()
+				);
+			}
+
 			println!("in {:?} {:?}", thread_pool_time, process_pool_time);
 		})
 }
@@ -56,3 +75,23 @@ async fn run<P: amadeus_core::pool::ProcessPool>(pool: &P, parallel: usize) -> D
 
 	start.elapsed().unwrap()
 }
+
+/// Ship large captured data to the processes of `pool` and back again.
+#[cfg(feature = "constellation")]
+async fn codecs<C: Codec>(pool: &ProcessPool<C>) -> Vec<u64> {
+	let data = (0..1_000_000).collect::<Vec<u64>>();
+	join_all((0..10).map(|i| {
+		let data = data.clone();
+		async move {
+			let (sum, data) = pool
+				.spawn(FnOnce!(move |&_| async move {
+					(data.iter().sum::<u64>() + i, data)
+				}))
+				.await
+				.unwrap();
+			assert_eq!(data.len(), 1_000_000);
+			sum
+		}
+	}))
+	.await
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, vec};
+
+use amadeus::{
+	amadeus_core::{
+		into_par_stream::{IterDistStream, IterParStream}, util::IoError
+	}, prelude::*, IntoDistributedStream
+};
+
+#[derive(Data, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
+struct Row {
+	id: u32,
+	name: String,
+}
+
+#[derive(Clone, Debug)]
+struct Memory(Vec<Row>);
+impl Source for Memory {
+	type Item = Row;
+	type Error = IoError;
+
+	type ParStream = IterParStream<vec::IntoIter<Result<Row, IoError>>>;
+	type DistStream = IterDistStream<vec::IntoIter<Result<Row, IoError>>>;
+
+	fn par_stream(self) -> Self::ParStream {
+		self.0
+			.into_iter()
+			.map(Ok)
+			.collect::<Vec<_>>()
+			.into_par_stream()
+	}
+	fn dist_stream(self) -> Self::DistStream {
+		self.0
+			.into_iter()
+			.map(Ok)
+			.collect::<Vec<_>>()
+			.into_dist_stream()
+	}
+}
+
+#[tokio::test(threaded_scheduler)]
+#[cfg_attr(miri, ignore)]
+async fn union() {
+	let pool = &ThreadPool::new(None, None).unwrap();
+
+	let row = |id: u32| Row {
+		id,
+		name: format!("row {}", id),
+	};
+
+	let dir = std::env::temp_dir().join("amadeus-union");
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	let mut paths = Vec::new();
+	for file in 0..4 {
+		let path = dir.join(format!("{}.json", file));
+		let contents = (file * 100..(file + 1) * 100)
+			.map(|id| format!("{{\"id\":{},\"name\":\"row {}\"}}\n", id, id))
+			.collect::<String>();
+		fs::write(&path, contents).unwrap();
+		paths.push(path);
+	}
+
+	let memory = Memory((400..500).map(row).collect());
+	let json = Json::<_, Row>::new(paths).await.unwrap();
+
+	let mut rows: Vec<Row> = Union::new(memory, json)
+		.par_stream()
+		.map(Result::unwrap)
+		.collect(pool)
+		.await;
+	rows.sort_by_key(|row| row.id);
+	assert_eq!(rows, (0..500).map(row).collect::<Vec<_>>());
+
+	fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,54 @@
+use constellation::*;
+use std::{env, fs, process, time::SystemTime};
+
+use amadeus::dist::prelude::*;
+
+fn main() {
+	if cfg!(miri) {
+		return;
+	}
+	init(Resources::default());
+
+	tokio::runtime::Builder::new()
+		.threaded_scheduler()
+		.enable_all()
+		.build()
+		.unwrap()
+		.block_on(async {
+			let start = SystemTime::now();
+
+			let process_pool = ProcessPool::new(Some(3), None, None, Resources::default()).unwrap();
+
+			// The first process to reach item 500 dies, leaving the marker behind
+			// so the partition succeeds when it's re-executed on another process
+			let marker = env::temp_dir().join(format!("amadeus-worker-failure-{}", process::id()));
+			let _ = fs::remove_file(&marker);
+			let path = marker.clone();
+
+			let sum = (0u64..1_000)
+				.into_dist_stream()
+				.map(FnMut!(move |i| {
+					if i == 500
+						&& fs::OpenOptions::new()
+							.write(true)
+							.create_new(true)
+							.open(&path)
+							.is_ok()
+					{
+						process::abort();
+					}
+					i
+				}))
+				.sum::<_, u64>(&process_pool)
+				.await;
+			assert_eq!(sum, (0..1_000).sum::<u64>());
+
+			let count = (0u64..1_000).into_dist_stream().count(&process_pool).await;
+			assert_eq!(count, 1_000);
+
+			assert!(marker.exists());
+			let _ = fs::remove_file(&marker);
+
+			println!("in {:?}", start.elapsed().unwrap());
+		})
+}